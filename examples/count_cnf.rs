@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+use clap::{ArgEnum, Parser};
+use num::BigUint;
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+use xdd::cnf::{ClauseOrder, Cnf, CnfBuildOptions};
+
+/// Count the solutions of a formula in DIMACS CNF format (#SAT) using a BDD or ZDD.
+#[derive(Parser, Debug)]
+#[clap(author="Andrew Conway", version, about, long_about = None)]
+struct Args {
+    /// The DIMACS CNF file to read.
+    file : PathBuf,
+    /// The order in which to combine clauses.
+    #[clap(long,arg_enum,default_value="smallest-first")]
+    order : Order,
+    /// Garbage collect after this many clauses (not used with smallest-first).
+    #[clap(long)]
+    gc_every : Option<usize>,
+    /// Use a ZDD rather than a BDD.
+    #[clap(long)]
+    zdd : bool,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Order {
+    SmallestFirst,
+    AsGiven,
+    BottomUp,
+}
+
+fn count<F:DecisionDiagramFactory<u32,NoMultiplicity>>(cnf:&Cnf,options:&CnfBuildOptions) {
+    let mut factory = F::new(cnf.num_variables);
+    let f = cnf.build(&mut factory,options);
+    let solutions : BigUint = factory.number_solutions(f);
+    println!("{}",factory.summary());
+    println!("Nodes in result {}",factory.post_order(f).count());
+    println!("Solutions {}",solutions);
+}
+
+fn main() {
+    let args = Args::parse();
+    let cnf = Cnf::load(&args.file).expect("Could not read CNF file");
+    println!("{} variables, {} clauses",cnf.num_variables,cnf.clauses.len());
+    let order = match args.order {
+        Order::SmallestFirst => ClauseOrder::SmallestFirst,
+        Order::AsGiven => ClauseOrder::AsGiven,
+        Order::BottomUp => ClauseOrder::BottomUp,
+    };
+    let options = CnfBuildOptions{ order, gc_every: args.gc_every };
+    if args.zdd { count::<ZDDFactory<u32,NoMultiplicity>>(&cnf,&options) } else { count::<BDDFactory<u32,NoMultiplicity>>(&cnf,&options) }
+}
@@ -0,0 +1,99 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use clap::{ArgEnum, Parser};
+use num::BigUint;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, ZDDFactory};
+use xdd::cnf::{Cnf, CnfBuildOptions};
+use xdd::constraints::ConstraintSystem;
+use xdd::generating_function::SingleVariableGeneratingFunction;
+
+/// Build a BDD or ZDD from constraints in a file, and count its solutions.
+///
+/// The file is either DIMACS CNF or the constraint format described in the xdd::constraints module.
+#[derive(Parser, Debug)]
+#[clap(author="Andrew Conway", version, about, long_about = None)]
+struct Args {
+    /// The file of constraints to read.
+    file : PathBuf,
+    /// The format of the file. Auto means CNF if the file name ends in .cnf, otherwise constraints.
+    #[clap(long,arg_enum,default_value="auto")]
+    format : Format,
+    /// Use a ZDD rather than a BDD.
+    #[clap(long)]
+    zdd : bool,
+    /// Garbage collect, keeping just the result, before printing statistics or writing files.
+    #[clap(long)]
+    gc : bool,
+    /// Also print the number of solutions with each number of variables true.
+    #[clap(long)]
+    generating_function : bool,
+    /// Write the diagram to this file in Graphviz dot format.
+    #[clap(long)]
+    dot : Option<PathBuf>,
+    /// Save the factory and diagram to this file, to be loaded with BDDFactory::load or ZDDFactory::load.
+    #[clap(long)]
+    save : Option<PathBuf>,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Auto,
+    Cnf,
+    Constraints,
+}
+
+enum Problem {
+    Cnf(Cnf),
+    Constraints(ConstraintSystem),
+}
+
+impl Problem {
+    fn num_variables(&self) -> u16 {
+        match self {
+            Problem::Cnf(cnf) => cnf.num_variables,
+            Problem::Constraints(system) => system.num_variables(),
+        }
+    }
+}
+
+fn run<F:DecisionDiagramFactory<u32,NoMultiplicity>>(problem:&Problem,args:&Args,save:impl Fn(&F,&Path,&[NodeIndex<u32,NoMultiplicity>])->std::io::Result<()>) {
+    let mut factory = F::new(problem.num_variables());
+    let mut f = match problem {
+        Problem::Cnf(cnf) => cnf.build(&mut factory,&CnfBuildOptions::default()),
+        Problem::Constraints(system) => system.build(&mut factory),
+    };
+    if args.gc { f = factory.gc([f]).rename(f).unwrap(); }
+    println!("{}",factory.summary());
+    println!("Nodes in result {}",factory.post_order(f).count());
+    let solutions : BigUint = factory.number_solutions(f);
+    println!("Solutions {}",solutions);
+    if args.generating_function {
+        let gf : SingleVariableGeneratingFunction<BigUint> = factory.number_solutions(f);
+        for (num_true,count) in gf.0.iter().enumerate() { println!("{} true\t{}",num_true,count); }
+    }
+    if let Some(path) = &args.dot {
+        let mut writer = BufWriter::new(File::create(path).expect("Could not create dot file"));
+        factory.make_dot_file_default_names(&mut writer,"result",&[(f,None)]).expect("Could not write dot file");
+    }
+    if let Some(path) = &args.save { save(&factory,path,&[f]).expect("Could not save diagram"); }
+}
+
+fn main() {
+    let args = Args::parse();
+    let is_cnf = match args.format {
+        Format::Auto => args.file.extension().is_some_and(|e|e.eq_ignore_ascii_case("cnf")),
+        format => format==Format::Cnf,
+    };
+    let problem = if is_cnf {
+        let cnf = Cnf::load(&args.file).expect("Could not read CNF file");
+        println!("{} variables, {} clauses",cnf.num_variables,cnf.clauses.len());
+        Problem::Cnf(cnf)
+    } else {
+        let system = ConstraintSystem::load(&args.file).expect("Could not read constraint file");
+        println!("{} variables, {} constraints",system.num_variables(),system.constraints.len());
+        Problem::Constraints(system)
+    };
+    if args.zdd { run::<ZDDFactory<u32,NoMultiplicity>>(&problem,&args,|factory,path,roots|factory.save(path,roots)) }
+    else { run::<BDDFactory<u32,NoMultiplicity>>(&problem,&args,|factory,path,roots|factory.save(path,roots)) }
+}
@@ -1,9 +1,12 @@
 use std::ops::RangeInclusive;
+use std::path::PathBuf;
 use clap::Parser;
+use num::{BigUint, ToPrimitive, Zero};
 use xdd::generating_function::GeneratingFunctionSplitByMultiplicity;
 use xdd::permutation::Permutation;
 use xdd::permutation_diagrams::{factorial, LeftRotation, n_choose_r, PermutationDecisionDiagramFactory};
 use std::str::FromStr;
+use xdd::oeis::{BFile, scientific_notation};
 
 /// Pattern avoiding permutations
 ///
@@ -22,31 +25,61 @@ struct Args {
     /// The pattern to count instances of.
     #[clap(parse(try_from_str = xdd::permutation::Permutation::from_str))]
     pattern : Permutation,
+    /// Append each row of the triangle to this OEIS b-file as it is computed. If the file already
+    /// has some rows (from an earlier run over the same range), they are not recomputed.
+    #[clap(long)]
+    bfile : Option<PathBuf>,
+    /// Print numbers in scientific notation with this many significant figures.
+    #[clap(long)]
+    scientific : Option<usize>,
+}
+
+/// The number of terms in the row of the triangle for permutations of length n.
+fn row_length(n:u32,pattern_len:u32) -> usize {
+    if n<pattern_len { 1 } else { n_choose_r::<BigUint>(n,pattern_len).to_usize().expect("Row too long")+1 }
 }
 
 
 fn main() {
     let args = Args::parse();
-    let mut triangle : OEISTriangle<u128> = Default::default();
+    let mut triangle : OEISTriangle<BigUint> = Default::default();
+    let format = |v:&BigUint| match args.scientific { Some(figures) => scientific_notation(v,figures), None => v.to_string() };
 
     let pattern_len = args.pattern.sequence.len() as u32;
+    let mut bfile = args.bfile.as_ref().map(|path|BFile::open_for_append(path,0).expect("Could not open b-file"));
+    let mut already_done = bfile.as_ref().map(|b|b.len()).unwrap_or(0);
     for n in args.range {
+        if already_done>0 {
+            let row_length = row_length(n,pattern_len);
+            if row_length<=already_done {
+                already_done-=row_length;
+                println!("Row for n={} is already in the b-file",n);
+                continue;
+            }
+            // the row was only partly written, so remove it and work it out again.
+            let bfile = bfile.as_mut().unwrap();
+            bfile.truncate(bfile.len()-already_done).expect("Could not remove partly written row from b-file");
+            println!("Removed the partly written row for n={} from the b-file",n);
+            already_done=0;
+        }
         let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(n as u16);
         let containing = factory.permutations_containing_a_given_pattern(&args.pattern.sequence);
         println!("\nTerms created {}",factory.len());
-        let mut num_containing : GeneratingFunctionSplitByMultiplicity::<u128> = factory.number_solutions(containing);
-        let zero = factorial::<u128>(n as u32)-num_containing.0.iter().fold(0,|a,b|a + *b); // the number of elements that avoid the pattern.
+        let mut num_containing : GeneratingFunctionSplitByMultiplicity::<BigUint> = factory.number_solutions(containing);
+        let zero = factorial::<BigUint>(n)-num_containing.0.iter().fold(BigUint::zero(),|a,b|a + b); // the number of elements that avoid the pattern.
         num_containing.0.insert(0,zero);
         print!("{}",n);
-        for &v in &num_containing.0 {
-            print!("\t{}",v);
+        for v in &num_containing.0 {
+            print!("\t{}",format(v));
         }
         println!();
         // make a format more suitable for OEIS.
-        if n>pattern_len { num_containing.0.resize(n_choose_r::<usize>(n,pattern_len)+1,0); }
+        num_containing.0.resize(row_length(n,pattern_len),BigUint::zero());
+        if let Some(bfile) = bfile.as_mut() { bfile.append(&num_containing.0).expect("Could not write to b-file"); }
         triangle.push(num_containing.0);
-        triangle.print_as_single_line();
-        triangle.print_as_triangle();
+        triangle.print_as_single_line(format);
+        triangle.print_as_triangle(format);
+        print!("{}",factory.summary());
     }
 }
 
@@ -55,15 +88,15 @@ struct OEISTriangle<T> {
     triangle : Vec<Vec<T>>
 }
 
-impl <T:ToString+Clone> OEISTriangle<T> {
-    pub fn print_as_single_line(&self) {
-        let line = self.triangle.iter().flatten().map(|v|v.to_string()).collect::<Vec<_>>().join(",");
+impl <T:Clone> OEISTriangle<T> {
+    pub fn print_as_single_line(&self,format:impl Fn(&T)->String) {
+        let line = self.triangle.iter().flatten().map(&format).collect::<Vec<_>>().join(",");
         println!("{}",line);
     }
-    pub fn print_as_triangle(&self) {
+    pub fn print_as_triangle(&self,format:impl Fn(&T)->String) {
         println!("Triangle begins:");
         for row in &self.triangle {
-            println!("{}",row.iter().map(|v|v.to_string()).collect::<Vec<_>>().join(" "));
+            println!("{}",row.iter().map(&format).collect::<Vec<_>>().join(" "));
         }
     }
     pub fn push(&mut self,row : Vec<T>) { self.triangle.push(row); }
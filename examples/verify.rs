@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use clap::{ArgEnum, Parser};
+use xdd::{BDDFactory, NoMultiplicity, ZDDFactory};
+use xdd::serialization::CheckpointProblem;
+
+/// Check checkpoint files written by save_checkpoint with u32 addresses before a long computation relies on them.
+#[derive(Parser, Debug)]
+#[clap(author="Andrew Conway", version, about, long_about = None)]
+struct Args {
+    /// The checkpoint files to check.
+    #[clap(required=true)]
+    files : Vec<PathBuf>,
+    /// The checkpoints are of ZDDs rather than BDDs.
+    #[clap(long)]
+    zdd : bool,
+    /// The multiplicity type the checkpoints were saved with.
+    #[clap(long,arg_enum,default_value="none")]
+    multiplicity : Multiplicity,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Multiplicity {
+    None,
+    U32,
+    U64,
+}
+
+fn verify(path:&Path,args:&Args) -> std::io::Result<Vec<CheckpointProblem<u32>>> {
+    match (args.zdd,args.multiplicity) {
+        (false,Multiplicity::None) => BDDFactory::<u32,NoMultiplicity>::verify_checkpoint(path),
+        (false,Multiplicity::U32) => BDDFactory::<u32,u32>::verify_checkpoint(path),
+        (false,Multiplicity::U64) => BDDFactory::<u32,u64>::verify_checkpoint(path),
+        (true,Multiplicity::None) => ZDDFactory::<u32,NoMultiplicity>::verify_checkpoint(path),
+        (true,Multiplicity::U32) => ZDDFactory::<u32,u32>::verify_checkpoint(path),
+        (true,Multiplicity::U64) => ZDDFactory::<u32,u64>::verify_checkpoint(path),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut healthy = true;
+    for file in &args.files {
+        match verify(file,&args) {
+            Ok(problems) if problems.is_empty() => println!("{} OK",file.display()),
+            Ok(problems) => {
+                healthy=false;
+                for problem in problems { println!("{} {}",file.display(),problem); }
+            }
+            Err(e) => {
+                healthy=false;
+                println!("{} could not be read: {}",file.display(),e);
+            }
+        }
+    }
+    if healthy { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
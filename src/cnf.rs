@@ -0,0 +1,251 @@
+//! Boolean formulas in conjunctive normal form (CNF), as used by SAT solvers.
+//!
+//! A [Cnf] can be read from or written to the DIMACS format used by SAT competitions and
+//! benchmark collections, and turned into a decision diagram with [Cnf::build]. Counting
+//! the solutions of the resulting diagram makes this a #SAT counter.
+//!
+//! A [Cnf] can also be built up from logic gates using [Cnf::and_gate], [Cnf::or_gate] and
+//! [Cnf::xor_gate], each of which introduces a new variable constrained to be the output
+//! of the gate (the Tseitin transformation). Such variables are determined by the others,
+//! so they do not change the number of solutions.
+//!
+//! # DIMACS format
+//! Lines starting with `c` are comments. The header `p cnf variables clauses` gives the number
+//! of variables and clauses. Each clause is a list of non-zero integers terminated by 0, where
+//! `v` means variable v is true and `-v` means it is false. Variables are numbered from 1 in the
+//! file, but from 0 as [VariableIndex]. A line starting with `%` ends the file, as in some benchmark collections.
+//!
+//! # Example
+//! ```
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity};
+//! use xdd::cnf::{Cnf, CnfBuildOptions};
+//! let cnf : Cnf = "c x1 or not x2, and x2 or x3\np cnf 3 2\n1 -2 0\n2 3 0\n".parse().unwrap();
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(cnf.num_variables);
+//! let f = cnf.build(&mut factory,&CnfBuildOptions::default());
+//! assert_eq!(4,factory.number_solutions::<u64>(f));
+//! ```
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::str::FromStr;
+use crate::{DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, VariableIndex};
+
+/// A variable or its negation.
+#[derive(Copy,Clone,Debug,Eq,PartialEq,Hash,Ord,PartialOrd)]
+pub struct Literal {
+    pub variable : VariableIndex,
+    /// True if this literal is true when the variable is false.
+    pub negated : bool,
+}
+
+impl Literal {
+    pub fn positive(variable:VariableIndex) -> Self { Literal{variable,negated:false} }
+    pub fn negative(variable:VariableIndex) -> Self { Literal{variable,negated:true} }
+    /// The DIMACS representation, numbered from 1 and negative if negated.
+    pub fn dimacs(self) -> i64 { let v = self.variable.0 as i64+1; if self.negated { -v } else { v } }
+}
+
+impl std::ops::Not for Literal {
+    type Output = Literal;
+    fn not(self) -> Literal { Literal{variable:self.variable,negated:!self.negated} }
+}
+
+/// A conjunction (and) of clauses, each of which is a disjunction (or) of literals.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct Cnf {
+    pub num_variables : u16,
+    pub clauses : Vec<Vec<Literal>>,
+}
+
+/// The order in which [Cnf::build] combines the clauses. This can make a huge difference to the time taken.
+#[derive(Copy,Clone,Debug,Default,Eq,PartialEq)]
+pub enum ClauseOrder {
+    /// Repeatedly combine the two smallest diagrams. See [DecisionDiagramFactory::poly_and].
+    #[default]
+    SmallestFirst,
+    /// Combine each clause in turn with the conjunction of the ones before it.
+    AsGiven,
+    /// Like AsGiven, but first sort the clauses so those whose first variable is last come first,
+    /// building the diagram from the bottom up.
+    BottomUp,
+}
+
+/// Options for [Cnf::build].
+#[derive(Clone,Debug,Default)]
+pub struct CnfBuildOptions {
+    pub order : ClauseOrder,
+    /// Garbage collect, keeping just the result so far, after every this many clauses.
+    /// Ignored for [ClauseOrder::SmallestFirst], which needs all the clauses from the start.
+    pub gc_every : Option<usize>,
+}
+
+impl Cnf {
+    /// A formula with no clauses (so always true), with the given number of variables.
+    pub fn new(num_variables:u16) -> Self { Cnf{num_variables,clauses:vec![]} }
+
+    pub fn add_clause(&mut self,clause:&[Literal]) {
+        for literal in clause { assert!(literal.variable.0<self.num_variables,"Variable {} used in a formula with {} variables",literal.variable,self.num_variables); }
+        self.clauses.push(clause.to_vec());
+    }
+
+    /// Add a new variable, which will be after all existing ones.
+    pub fn new_variable(&mut self) -> VariableIndex {
+        let res = VariableIndex(self.num_variables);
+        self.num_variables = self.num_variables.checked_add(1).expect("Too many variables");
+        res
+    }
+
+    /// Add a new variable constrained to be the and of the inputs, and return it.
+    pub fn and_gate(&mut self,inputs:&[Literal]) -> Literal {
+        let output = Literal::positive(self.new_variable());
+        for &input in inputs { self.add_clause(&[!output,input]); }
+        let mut all = vec![output];
+        all.extend(inputs.iter().map(|&input|!input));
+        self.add_clause(&all);
+        output
+    }
+
+    /// Add a new variable constrained to be the or of the inputs, and return it.
+    pub fn or_gate(&mut self,inputs:&[Literal]) -> Literal {
+        let inverted : Vec<Literal> = inputs.iter().map(|&input|!input).collect();
+        !self.and_gate(&inverted)
+    }
+
+    /// Add a new variable constrained to be the exclusive or of a and b, and return it.
+    pub fn xor_gate(&mut self,a:Literal,b:Literal) -> Literal {
+        let output = Literal::positive(self.new_variable());
+        self.add_clause(&[!output,a,b]);
+        self.add_clause(&[!output,!a,!b]);
+        self.add_clause(&[output,!a,b]);
+        self.add_clause(&[output,a,!b]);
+        output
+    }
+
+    /// Make a diagram which is true iff at least one of the literals is.
+    pub fn clause<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(factory:&mut F,clause:&[Literal]) -> NodeIndex<A,M> {
+        let mut res = NodeIndex::FALSE;
+        for literal in clause {
+            let v = factory.single_variable(literal.variable);
+            let v = if literal.negated { factory.not(v) } else { v };
+            res = factory.or(res,v);
+        }
+        res
+    }
+
+    /// Make a diagram which is true iff all the clauses are. The factory must have at least as many variables as the formula.
+    pub fn build<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,options:&CnfBuildOptions) -> NodeIndex<A,M> {
+        let always = factory.not(NodeIndex::FALSE); // not NodeIndex::TRUE, as that is not always true for a ZDD.
+        let mut clauses : Vec<&Vec<Literal>> = self.clauses.iter().collect();
+        match options.order {
+            ClauseOrder::SmallestFirst => {
+                let diagrams : Vec<NodeIndex<A,M>> = clauses.iter().map(|clause|Self::clause(factory,clause)).collect();
+                return factory.poly_and(&diagrams).unwrap_or(always);
+            }
+            ClauseOrder::AsGiven => {}
+            ClauseOrder::BottomUp => clauses.sort_by_key(|clause|std::cmp::Reverse(clause.iter().map(|l|l.variable).min())),
+        }
+        let mut res = always;
+        for (i,clause) in clauses.into_iter().enumerate() {
+            let clause = Self::clause(factory,clause);
+            res = factory.and(res,clause);
+            if res.is_false() { break; }
+            if let Some(gc_every) = options.gc_every {
+                if (i+1)%gc_every==0 { res = factory.gc([res]).rename(res).unwrap(); }
+            }
+        }
+        res
+    }
+
+    /// Read a file in the DIMACS format.
+    pub fn read_from<R:Read>(reader:&mut R) -> std::io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        text.parse().map_err(|e|std::io::Error::new(ErrorKind::InvalidData,e))
+    }
+
+    /// Load a file in the DIMACS format.
+    pub fn load(path:impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::read_from(&mut std::fs::File::open(path)?)
+    }
+}
+
+impl FromStr for Cnf {
+    type Err = ParseCnfError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut header : Option<(u16,usize)> = None;
+        let mut clauses = vec![];
+        let mut clause = vec![];
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') { continue; }
+            if line.starts_with('%') { break; }
+            if line.starts_with('p') {
+                let fields : Vec<&str> = line.split_whitespace().collect();
+                if header.is_some() || fields.len()!=4 || fields[1]!="cnf" { return Err(ParseCnfError::BadHeader(line.to_string())); }
+                let num_variables : u64 = fields[2].parse().map_err(|_|ParseCnfError::BadHeader(line.to_string()))?;
+                let num_variables = u16::try_from(num_variables).map_err(|_|ParseCnfError::TooManyVariables(num_variables))?;
+                let num_clauses = fields[3].parse().map_err(|_|ParseCnfError::BadHeader(line.to_string()))?;
+                header = Some((num_variables,num_clauses));
+                continue;
+            }
+            let (num_variables,_) = header.ok_or(ParseCnfError::MissingHeader)?;
+            for word in line.split_whitespace() {
+                let literal : i64 = word.parse().map_err(|_|ParseCnfError::NumberFormat(word.to_string()))?;
+                if literal==0 { clauses.push(std::mem::take(&mut clause)); }
+                else if literal.unsigned_abs()>num_variables as u64 { return Err(ParseCnfError::VariableOutOfRange(literal)); }
+                else { clause.push(Literal{ variable: VariableIndex((literal.unsigned_abs()-1) as u16), negated: literal<0 }); }
+            }
+        }
+        let (num_variables,num_clauses) = header.ok_or(ParseCnfError::MissingHeader)?;
+        if !clause.is_empty() { return Err(ParseCnfError::UnterminatedClause); }
+        if clauses.len()!=num_clauses { return Err(ParseCnfError::WrongNumberOfClauses{expected:num_clauses,found:clauses.len()}); }
+        Ok(Cnf{num_variables,clauses})
+    }
+}
+
+/// Write in the DIMACS format.
+impl Display for Cnf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"p cnf {} {}",self.num_variables,self.clauses.len())?;
+        for clause in &self.clauses {
+            for literal in clause { write!(f,"{} ",literal.dimacs())?; }
+            writeln!(f,"0")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone,Debug,Eq, PartialEq)]
+pub enum ParseCnfError {
+    /// Clauses before the `p cnf` line, or no such line.
+    MissingHeader,
+    /// A `p` line not of the form `p cnf variables clauses`, or more than one of them.
+    BadHeader(String),
+    /// More variables than fit in a [VariableIndex].
+    TooManyVariables(u64),
+    /// Something in a clause that is not an integer.
+    NumberFormat(String),
+    /// A literal whose variable is larger than the number in the header.
+    VariableOutOfRange(i64),
+    /// The last clause is not terminated by 0.
+    UnterminatedClause,
+    WrongNumberOfClauses{expected:usize,found:usize},
+}
+
+impl Error for ParseCnfError { }
+
+impl Display for ParseCnfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseCnfError::MissingHeader => write!(f,"Missing header line p cnf variables clauses"),
+            ParseCnfError::BadHeader(s) => write!(f,"Could not interpret header '{}'; expecting p cnf variables clauses",s),
+            ParseCnfError::TooManyVariables(n) => write!(f,"{} variables is more than the maximum of {}",n,u16::MAX),
+            ParseCnfError::NumberFormat(s) => write!(f,"Could not interpret '{}' as a literal",s),
+            ParseCnfError::VariableOutOfRange(v) => write!(f,"Literal {} refers to a variable beyond the number in the header",v),
+            ParseCnfError::UnterminatedClause => write!(f,"The last clause is not terminated by 0"),
+            ParseCnfError::WrongNumberOfClauses{expected,found} => write!(f,"The header says there are {} clauses but there are {}",expected,found),
+        }
+    }
+}
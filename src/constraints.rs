@@ -0,0 +1,235 @@
+//! A simple text format for a list of constraints on named variables, all of which must hold.
+//!
+//! This is meant for counting experiments without writing Rust; the example program `examples/diagram.rs` reads such a file (or DIMACS CNF, see [crate::cnf]), builds the diagram and prints the number of solutions.
+//!
+//! # Format
+//! Each line is one statement; everything after a `#` is a comment. Variables must be declared before they are used.
+//! * `variables a b c` declares variables, numbered in the order declared. There may be several such lines.
+//! * `clause a !b c` means at least one of the literals is true, `!b` meaning b is false.
+//! * `exactly 2 of a b c`, `at_least 2 of a b c` and `at_most 2 of a b c` constrain how many of the variables are true.
+//! * `between 1 2 of a b c` means at least 1 and at most 2 of them are true.
+//! * `linear 3*a 2*b -c <= 4` compares a weighted sum of the true variables to a bound, with any of `<`, `<=`, `=`, `>=` or `>`.
+//!   A term without a weight, like `b` or `-c`, has weight 1 or -1.
+//!
+//! # Example
+//! ```
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity};
+//! use xdd::constraints::ConstraintSystem;
+//! let text = "variables a b c d\nexactly 2 of a b c d\nclause !a d # a implies d\nlinear 3*a 2*b c <= 4\n";
+//! let system : ConstraintSystem = text.parse().unwrap();
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(system.num_variables());
+//! let f = system.build(&mut factory);
+//! assert_eq!(4,factory.number_solutions::<u64>(f)); // {a,d}, {b,c}, {b,d}, {c,d}.
+//! assert_eq!("d",factory.variable_registry().name(xdd::VariableIndex(3)));
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::str::FromStr;
+use crate::{Comparison, DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, VariableIndex};
+use crate::cnf::{Cnf, Literal};
+
+/// One statement of a [ConstraintSystem], other than a declaration.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum Constraint {
+    /// At least one of the literals is true.
+    Clause(Vec<Literal>),
+    /// The number of the variables that are true is in the range at_least..=at_most. The variables are sorted and distinct.
+    Cardinality{variables:Vec<VariableIndex>,at_least:usize,at_most:usize},
+    /// See [DecisionDiagramFactory::linear_constraint]. The terms are sorted by variable, which are distinct.
+    Linear{terms:Vec<(VariableIndex,i64)>,comparison:Comparison,bound:i64},
+}
+
+/// Named variables and constraints on them, as read from the format described in [crate::constraints].
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct ConstraintSystem {
+    /// The names of variables 0, 1, ...
+    pub names : Vec<String>,
+    pub constraints : Vec<Constraint>,
+}
+
+impl ConstraintSystem {
+    pub fn num_variables(&self) -> u16 { self.names.len() as u16 }
+
+    /// Make a diagram which is true iff the constraint is.
+    pub fn constraint<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(factory:&mut F,constraint:&Constraint) -> NodeIndex<A,M> {
+        match constraint {
+            Constraint::Clause(literals) => Cnf::clause(factory,literals),
+            Constraint::Cardinality{variables,at_least,at_most} => factory.between_n_and_m_of(variables,*at_least,*at_most),
+            Constraint::Linear{terms,comparison,bound} => factory.linear_constraint(terms,*comparison,*bound),
+        }
+    }
+
+    /// Make a diagram which is true iff all the constraints are, combining them with [DecisionDiagramFactory::poly_and].
+    /// The factory must have at least as many variables as the system; they are given the system's names.
+    pub fn build<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F) -> NodeIndex<A,M> {
+        factory.variable_registry_mut().set_names(&self.names);
+        let diagrams : Vec<NodeIndex<A,M>> = self.constraints.iter().map(|c|Self::constraint(factory,c)).collect();
+        factory.poly_and(&diagrams).unwrap_or_else(||factory.not(NodeIndex::FALSE)) // not NodeIndex::TRUE, as that is not always true for a ZDD.
+    }
+
+    /// Read a file in the format described in [crate::constraints].
+    pub fn read_from<R:Read>(reader:&mut R) -> std::io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        text.parse().map_err(|e|std::io::Error::new(ErrorKind::InvalidData,e))
+    }
+
+    /// Load a file in the format described in [crate::constraints].
+    pub fn load(path:impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::read_from(&mut std::fs::File::open(path)?)
+    }
+}
+
+/// Turns the words of one line into variables and numbers.
+struct LineParser<'a> {
+    line : usize,
+    variables : &'a HashMap<String,VariableIndex>,
+    names : &'a [String],
+}
+
+impl LineParser<'_> {
+    fn error(&self,problem:ConstraintProblem) -> ParseConstraintsError { ParseConstraintsError{line:self.line,problem} }
+    fn variable(&self,name:&str) -> Result<VariableIndex,ParseConstraintsError> {
+        self.variables.get(name).copied().ok_or_else(||self.error(ConstraintProblem::UnknownVariable(name.to_string())))
+    }
+    fn number<T:FromStr>(&self,word:&str) -> Result<T,ParseConstraintsError> {
+        word.parse().map_err(|_|self.error(ConstraintProblem::NumberFormat(word.to_string())))
+    }
+    /// The variables in words, sorted, checking each is only given once.
+    fn distinct_variables(&self,words:&[&str]) -> Result<Vec<VariableIndex>,ParseConstraintsError> {
+        let mut variables = words.iter().map(|w|self.variable(w)).collect::<Result<Vec<_>,_>>()?;
+        variables.sort();
+        self.check_distinct(&variables)?;
+        Ok(variables)
+    }
+    fn check_distinct(&self,sorted:&[VariableIndex]) -> Result<(),ParseConstraintsError> {
+        match sorted.windows(2).find(|w|w[0]==w[1]) {
+            Some(w) => Err(self.error(ConstraintProblem::RepeatedVariable(self.names[w[0].0 as usize].clone()))),
+            None => Ok(()),
+        }
+    }
+    /// A term of a linear constraint, like `3*a`, `-a` or `a`.
+    fn term(&self,word:&str) -> Result<(VariableIndex,i64),ParseConstraintsError> {
+        match word.split_once('*') {
+            Some((weight,name)) => Ok((self.variable(name)?,self.number(weight)?)),
+            None => match word.strip_prefix('-') {
+                Some(name) => Ok((self.variable(name)?,-1)),
+                None => Ok((self.variable(word)?,1)),
+            }
+        }
+    }
+    /// The variables after the word `of` in statements like `between 1 2 of a b c`, which has 2 numbers before the `of`.
+    fn cardinality(&self,words:&[&str],numbers:usize) -> Result<(Vec<usize>,Vec<VariableIndex>),ParseConstraintsError> {
+        if words.len()<=numbers+1 || words[numbers+1]!="of" { return Err(self.error(ConstraintProblem::Syntax(words.join(" ")))); }
+        let counts = words[1..=numbers].iter().map(|w|self.number(w)).collect::<Result<Vec<usize>,_>>()?;
+        Ok((counts,self.distinct_variables(&words[numbers+2..])?))
+    }
+}
+
+impl FromStr for ConstraintSystem {
+    type Err = ParseConstraintsError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut names = vec![];
+        let mut variables = HashMap::new();
+        let mut constraints = vec![];
+        for (line_number,line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap();
+            let words : Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() { continue; }
+            let error = |problem|ParseConstraintsError{line:line_number+1,problem};
+            if words[0]=="variables" {
+                for &name in &words[1..] {
+                    let variable = VariableIndex(u16::try_from(names.len()).ok().filter(|&v|v<u16::MAX).ok_or_else(||error(ConstraintProblem::TooManyVariables))?);
+                    if name.starts_with(['!','-']) || name.contains(['*','=','<','>']) || name.parse::<i64>().is_ok() { return Err(error(ConstraintProblem::BadName(name.to_string()))); }
+                    if variables.contains_key(name) { return Err(error(ConstraintProblem::DuplicateName(name.to_string()))); }
+                    variables.insert(name.to_string(),variable);
+                    names.push(name.to_string());
+                }
+                continue;
+            }
+            let parser = LineParser{line:line_number+1,variables:&variables,names:&names};
+            let syntax_error = ||error(ConstraintProblem::Syntax(words.join(" ")));
+            let constraint = match words[0] {
+                "clause" => Constraint::Clause(words[1..].iter().map(|w|match w.strip_prefix('!') {
+                    Some(name) => parser.variable(name).map(Literal::negative),
+                    None => parser.variable(w).map(Literal::positive),
+                }).collect::<Result<_,_>>()?),
+                "exactly" | "at_least" | "at_most" => {
+                    let (counts,variables) = parser.cardinality(&words,1)?;
+                    let (at_least,at_most) = match words[0] { "exactly" => (counts[0],counts[0]), "at_least" => (counts[0],variables.len()), _ => (0,counts[0]) };
+                    Constraint::Cardinality{variables,at_least,at_most}
+                }
+                "between" => {
+                    let (counts,variables) = parser.cardinality(&words,2)?;
+                    Constraint::Cardinality{variables,at_least:counts[0],at_most:counts[1]}
+                }
+                "linear" => {
+                    if words.len()<3 { return Err(syntax_error()); }
+                    let comparison = match words[words.len()-2] {
+                        "<" => Comparison::Less,
+                        "<=" => Comparison::LessOrEqual,
+                        "=" | "==" => Comparison::Equal,
+                        ">=" => Comparison::GreaterOrEqual,
+                        ">" => Comparison::Greater,
+                        _ => return Err(syntax_error()),
+                    };
+                    let bound = parser.number(words[words.len()-1])?;
+                    let mut terms = words[1..words.len()-2].iter().map(|w|parser.term(w)).collect::<Result<Vec<_>,_>>()?;
+                    terms.sort_by_key(|t|t.0);
+                    parser.check_distinct(&terms.iter().map(|t|t.0).collect::<Vec<_>>())?;
+                    Constraint::Linear{terms,comparison,bound}
+                }
+                _ => return Err(syntax_error()),
+            };
+            constraints.push(constraint);
+        }
+        Ok(ConstraintSystem{names,constraints})
+    }
+}
+
+/// What is wrong with a line of a constraint file.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum ConstraintProblem {
+    /// A line that is not one of the statements described in [crate::constraints].
+    Syntax(String),
+    /// A variable used before being declared.
+    UnknownVariable(String),
+    /// A variable declared twice.
+    DuplicateName(String),
+    /// A name that could be confused with a number, negation or weight.
+    BadName(String),
+    /// A variable given twice in one cardinality or linear constraint.
+    RepeatedVariable(String),
+    /// Something that should be a number but is not one (or is out of range).
+    NumberFormat(String),
+    /// More variables than fit in a [VariableIndex].
+    TooManyVariables,
+}
+
+/// An error reading a [ConstraintSystem], with the line it is on (counting from 1).
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct ParseConstraintsError {
+    pub line : usize,
+    pub problem : ConstraintProblem,
+}
+
+impl Error for ParseConstraintsError { }
+
+impl Display for ParseConstraintsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"Line {}: ",self.line)?;
+        match &self.problem {
+            ConstraintProblem::Syntax(s) => write!(f,"Could not interpret '{}'",s),
+            ConstraintProblem::UnknownVariable(s) => write!(f,"Variable {} has not been declared",s),
+            ConstraintProblem::DuplicateName(s) => write!(f,"Variable {} is declared twice",s),
+            ConstraintProblem::BadName(s) => write!(f,"{} is not allowed as a variable name",s),
+            ConstraintProblem::RepeatedVariable(v) => write!(f,"Variable {} appears more than once",v),
+            ConstraintProblem::NumberFormat(s) => write!(f,"Could not interpret '{}' as a number",s),
+            ConstraintProblem::TooManyVariables => write!(f,"More than the maximum of {} variables",u16::MAX-1),
+        }
+    }
+}
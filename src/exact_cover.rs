@@ -0,0 +1,92 @@
+//! Exact cover problems: choose some of a list of options, each a set of items, so that every item is in exactly one chosen option.
+//!
+//! This is the problem solved by Knuth's Algorithm X (dancing links), described in "The Art of Computer Programming"
+//! volume 4B section 7.2.2.1. As there, items are primary, which must be covered exactly once, or secondary,
+//! which must be covered at most once. Tilings (see [crate::tiling]), Sudoku and n queens are all exact cover problems.
+//!
+//! Rather than finding solutions one at a time, [ExactCoverProblem::cover] makes a diagram of all of them with
+//! [DecisionDiagramFactory::exact_cover], with the variable for each option true iff it is chosen. The solutions can then be
+//! counted, enumerated, sampled or combined with other constraints. The diagram is smallest when options with items in common
+//! are close together in the list.
+//!
+//! # Example
+//! The example from Knuth, with primary items a to g.
+//! ```
+//! use xdd::{DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+//! use xdd::exact_cover::ExactCoverProblem;
+//! let mut problem = ExactCoverProblem::new(&["a","b","c","d","e","f","g"],&[]);
+//! for option in [["c","e"].as_slice(),&["a","d","g"],&["b","c","f"],&["a","d","f"],&["b","g"],&["d","e","g"]] {
+//!     problem.add_named_option(option);
+//! }
+//! let (factory,covers) = problem.build::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>();
+//! assert_eq!(1,factory.number_solutions::<u64>(covers));
+//! let solution = factory.solutions(covers).next().unwrap();
+//! assert_eq!(vec![vec!["c","e"],vec!["a","d","f"],vec!["b","g"]],problem.option_names(&solution));
+//! ```
+
+use std::collections::HashMap;
+use crate::{DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, VariableIndex};
+
+/// The index of an item in [ExactCoverProblem::items].
+pub type ItemIndex = usize;
+/// The index of an option in [ExactCoverProblem::options]. Option i is variable i.
+pub type OptionIndex = usize;
+
+/// Named items, and options made of them.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct ExactCoverProblem {
+    /// The names of the items. The first num_primary_items are primary, the rest secondary.
+    pub items : Vec<String>,
+    pub num_primary_items : usize,
+    /// The items in each option.
+    pub options : Vec<Vec<ItemIndex>>,
+    item_index_by_name : HashMap<String,ItemIndex>,
+}
+
+impl ExactCoverProblem {
+    /// A problem with the given items and no options. Panics if a name is used twice.
+    pub fn new<S:AsRef<str>>(primary:&[S],secondary:&[S]) -> Self {
+        let mut problem = ExactCoverProblem{num_primary_items:primary.len(),..Default::default()};
+        for name in primary.iter().chain(secondary) {
+            let name = name.as_ref().to_string();
+            assert!(!problem.item_index_by_name.contains_key(&name),"Item {} given twice",name);
+            problem.item_index_by_name.insert(name.clone(),problem.items.len());
+            problem.items.push(name);
+        }
+        problem
+    }
+    /// A problem with unnamed items (called by their index), for when it is easier to number them.
+    pub fn with_items(num_primary_items:usize,num_secondary_items:usize) -> Self {
+        let names : Vec<String> = (0..num_primary_items+num_secondary_items).map(|i|i.to_string()).collect();
+        Self::new(&names[..num_primary_items],&names[num_primary_items..])
+    }
+    /// The item with the given name, if any.
+    pub fn item(&self,name:&str) -> Option<ItemIndex> { self.item_index_by_name.get(name).copied() }
+    /// Add an option containing the given items. Panics if an item does not exist.
+    pub fn add_option(&mut self,items:Vec<ItemIndex>) -> OptionIndex {
+        for &item in &items { assert!(item<self.items.len(),"No item {}",item); }
+        self.options.push(items);
+        self.options.len()-1
+    }
+    /// Add an option containing the named items. Panics if an item does not exist.
+    pub fn add_named_option<S:AsRef<str>>(&mut self,names:&[S]) -> OptionIndex {
+        let items = names.iter().map(|name|self.item(name.as_ref()).unwrap_or_else(||panic!("No item {}",name.as_ref()))).collect();
+        self.add_option(items)
+    }
+    /// The variable that is true iff the option is chosen.
+    pub fn option_variable(option:OptionIndex) -> VariableIndex { VariableIndex(option as u16) }
+    /// Make a diagram that is true iff the chosen options are an exact cover. The factory must have a variable for each option.
+    pub fn cover<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F) -> NodeIndex<A,M> {
+        factory.exact_cover(self.num_primary_items,&self.options)
+    }
+    /// Make a factory with one variable per option, and a diagram in it that is true iff the chosen options are an exact cover.
+    pub fn build<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self) -> (F,NodeIndex<A,M>) {
+        let mut factory = F::new(u16::try_from(self.options.len()).expect("Too many options"));
+        let covers = self.cover(&mut factory);
+        (factory,covers)
+    }
+    /// The names of the items in each chosen option in a solution.
+    pub fn option_names(&self,solution:&[VariableIndex]) -> Vec<Vec<&str>> {
+        solution.iter().map(|v|self.options[v.0 as usize].iter().map(|&i|self.items[i].as_str()).collect()).collect()
+    }
+}
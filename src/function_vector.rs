@@ -0,0 +1,173 @@
+//! Vectors of functions, sometimes called BDD arrays, treated as bit vectors.
+//!
+//! A [FunctionVector] is a list of diagrams in the same factory, each giving one bit of an unsigned integer
+//! that depends on the variables. Operations on these act bit by bit (like `&` or `^` on integers) or
+//! like arithmetic circuits (like `+`), giving another vector. Comparisons give a single diagram,
+//! true for the assignments where the comparison holds.
+//!
+//! Bits are stored least significant first. Operations on two vectors of different widths treat the
+//! shorter as having extra zero high bits.
+//!
+//! Arithmetic constraints are built by doing the arithmetic and then comparing, so `x+y==z` is
+//! `x.add(factory,&y).compare(factory,&z,Comparison::Equal)`. A sum of weights of selected variables is made by
+//! [FunctionVector::weighted_sum]; if only a comparison of such a sum to a constant is wanted,
+//! [DecisionDiagramFactory::linear_constraint] makes it directly and is faster.
+//!
+//! # Example
+//! ```
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+//! use xdd::function_vector::FunctionVector;
+//! // two 3 bit numbers x and y, with x made of variables 0,1,2 and y of variables 3,4,5.
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+//! let x = FunctionVector::from_variables(&mut factory,&[VariableIndex(0),VariableIndex(1),VariableIndex(2)]);
+//! let y = FunctionVector::from_variables(&mut factory,&[VariableIndex(3),VariableIndex(4),VariableIndex(5)]);
+//! let sum = x.add(&mut factory,&y);
+//! assert_eq!(4,sum.width()); // keeps the carry.
+//! let is_9 = sum.equals_constant(&mut factory,9);
+//! assert_eq!(6,factory.number_solutions::<u64>(is_9)); // 2+7, 3+6, ..., 7+2.
+//! ```
+//!
+//! Constraints relating several numbers are combined with the usual operations on diagrams.
+//! ```
+//! use xdd::{BDDFactory, Comparison, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+//! use xdd::function_vector::FunctionVector;
+//! // x+y==z and x<y, for 2 bit numbers x (variables 0,1), y (2,3) and z (4,5).
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+//! let [x,y,z] = [0,2,4].map(|i|FunctionVector::from_variables(&mut factory,&[VariableIndex(i),VariableIndex(i+1)]));
+//! let sum = x.add(&mut factory,&y);
+//! let sum_is_z = sum.compare(&mut factory,&z,Comparison::Equal);
+//! let x_less = x.compare(&mut factory,&y,Comparison::Less);
+//! let both = factory.and(sum_is_z,x_less);
+//! assert_eq!(4,factory.number_solutions::<u64>(both)); // 0+1, 0+2, 0+3 and 1+2.
+//! ```
+
+use crate::{Comparison, DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, VariableIndex};
+
+/// An unsigned integer, each bit of which is a function of the variables.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct FunctionVector<A:NodeAddress,M:Multiplicity> {
+    /// The bits, least significant first.
+    pub bits : Vec<NodeIndex<A,M>>,
+}
+
+impl <A:NodeAddress,M:Multiplicity> FunctionVector<A,M> {
+    /// A vector with the given bits, least significant first.
+    pub fn new(bits:Vec<NodeIndex<A,M>>) -> Self { FunctionVector{bits} }
+
+    /// The number whose bits are the given variables, least significant first.
+    pub fn from_variables<F:DecisionDiagramFactory<A,M>>(factory:&mut F,variables:&[VariableIndex]) -> Self {
+        FunctionVector{bits:variables.iter().map(|&v|factory.single_variable(v)).collect()}
+    }
+
+    /// The same number for all assignments, with the given number of bits. Panics if value does not fit.
+    pub fn constant<F:DecisionDiagramFactory<A,M>>(factory:&mut F,value:u64,width:usize) -> Self {
+        assert!(width>=64 || value>>width==0,"{} does not fit in {} bits",value,width);
+        let one = factory.not(NodeIndex::FALSE);
+        FunctionVector{bits:(0..width).map(|i|if i<64 && (value>>i)&1==1 { one } else { NodeIndex::FALSE }).collect()}
+    }
+
+    /// The number of bits.
+    pub fn width(&self) -> usize { self.bits.len() }
+
+    /// Bit i, which is false beyond the width.
+    pub fn bit(&self,i:usize) -> NodeIndex<A,M> { self.bits.get(i).copied().unwrap_or(NodeIndex::FALSE) }
+
+    /// Combine two vectors bit by bit, the result being as wide as the wider.
+    fn bitwise<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self,op:impl Fn(&mut F,NodeIndex<A,M>,NodeIndex<A,M>)->NodeIndex<A,M>) -> Self {
+        FunctionVector{bits:(0..self.width().max(other.width())).map(|i|op(factory,self.bit(i),other.bit(i))).collect()}
+    }
+
+    /// Bitwise and.
+    pub fn and<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self) -> Self { self.bitwise(factory,other,|f,a,b|f.and(a,b)) }
+    /// Bitwise or.
+    pub fn or<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self) -> Self { self.bitwise(factory,other,|f,a,b|f.or(a,b)) }
+    /// Bitwise exclusive or.
+    pub fn xor<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self) -> Self { self.bitwise(factory,other,|f,a,b|f.xor(a,b)) }
+    /// Bitwise not, keeping the same width.
+    pub fn not<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F) -> Self {
+        FunctionVector{bits:self.bits.iter().map(|&b|factory.not(b)).collect()}
+    }
+
+    /// The function that is true iff the two numbers are equal.
+    pub fn equals<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self) -> NodeIndex<A,M> {
+        let differences = self.xor(factory,other);
+        let mut any_different = NodeIndex::FALSE;
+        for &d in differences.bits.iter().rev() { any_different = factory.or(any_different,d); }
+        factory.not(any_different)
+    }
+
+    /// The function that is true iff the number is value.
+    pub fn equals_constant<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,value:u64) -> NodeIndex<A,M> {
+        if self.width()<64 && value>>self.width()!=0 { return NodeIndex::FALSE; }
+        let constant = FunctionVector::constant(factory,value,self.width());
+        self.equals(factory,&constant)
+    }
+
+    /// The sum, computed with a ripple carry adder. The result is one bit wider than the wider input, so never overflows.
+    pub fn add<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self) -> Self {
+        let mut carry = NodeIndex::FALSE;
+        let mut bits = Vec::with_capacity(self.width().max(other.width())+1);
+        for i in 0..self.width().max(other.width()) {
+            let (a,b) = (self.bit(i),other.bit(i));
+            let half = factory.xor(a,b);
+            bits.push(factory.xor(half,carry));
+            let both = factory.and(a,b);
+            let propagated = factory.and(half,carry);
+            carry = factory.or(both,propagated);
+        }
+        bits.push(carry);
+        FunctionVector{bits}
+    }
+
+    /// Keep only the lowest width bits (or pad with zero bits), so arithmetic is modulo 2<sup>width</sup>.
+    pub fn truncate(&self,width:usize) -> Self {
+        FunctionVector{bits:(0..width).map(|i|self.bit(i)).collect()}
+    }
+
+    /// The sum of all the vectors, wide enough that it never overflows.
+    pub fn sum<F:DecisionDiagramFactory<A,M>>(factory:&mut F,vectors:&[Self]) -> Self {
+        vectors.iter().fold(FunctionVector::new(vec![]),|total,v|total.add(factory,v))
+    }
+
+    /// The sum of weight over the terms whose variable is true, wide enough for the sum of all the weights.
+    /// Panics if that total does not fit in a u64.
+    pub fn weighted_sum<F:DecisionDiagramFactory<A,M>>(factory:&mut F,terms:&[(VariableIndex,u64)]) -> Self {
+        let total = terms.iter().try_fold(0u64,|total,&(_,weight)|total.checked_add(weight)).expect("Sum of weights does not fit in a u64");
+        let width = (64-total.leading_zeros()) as usize;
+        let mut sum = FunctionVector::new(vec![]);
+        for &(variable,weight) in terms {
+            let x = factory.single_variable(variable);
+            let term = FunctionVector{bits:(0..width).map(|i|if (weight>>i)&1==1 { x } else { NodeIndex::FALSE }).collect()};
+            sum = sum.add(factory,&term).truncate(width);
+        }
+        sum
+    }
+
+    /// The function that is true iff self<other, as unsigned integers.
+    fn less_than<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self) -> NodeIndex<A,M> {
+        // going from the least significant bit, the most significant bit that differs so far decides.
+        let mut less = NodeIndex::FALSE;
+        for i in 0..self.width().max(other.width()) {
+            let differs = factory.xor(self.bit(i),other.bit(i));
+            less = factory.ite(differs,other.bit(i),less);
+        }
+        less
+    }
+
+    /// The function that is true iff self compares to other as given, as unsigned integers.
+    pub fn compare<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,other:&Self,comparison:Comparison) -> NodeIndex<A,M> {
+        match comparison {
+            Comparison::Less => self.less_than(factory,other),
+            Comparison::Greater => other.less_than(factory,self),
+            Comparison::Equal => self.equals(factory,other),
+            Comparison::LessOrEqual => { let greater = other.less_than(factory,self); factory.not(greater) }
+            Comparison::GreaterOrEqual => { let less = self.less_than(factory,other); factory.not(less) }
+        }
+    }
+
+    /// The function that is true iff self compares to value as given.
+    pub fn compare_constant<F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,value:u64,comparison:Comparison) -> NodeIndex<A,M> {
+        let constant = FunctionVector::constant(factory,value,(64-value.leading_zeros()) as usize);
+        self.compare(factory,&constant,comparison)
+    }
+}
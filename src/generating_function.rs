@@ -1,10 +1,14 @@
-use std::fmt::Debug;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::ops::{AddAssign, Mul, MulAssign};
-use num::Integer;
-use crate::{NoMultiplicity, VariableIndex};
+use num::{BigInt, BigUint, CheckedAdd, CheckedMul, Integer, One, Zero};
+use num::rational::Ratio;
+use crate::{NodeAddress, NodeRenaming, NoMultiplicity, VariableIndex};
 
 /// A Generating Function is some aggregate of the variables. This could be:
-///  * An integer, being the number of solutions. (u64, u128)
+///  * An integer, being the number of solutions. (u64, u128, BigUint, or i64, i128, BigInt for signed multiplicities, or a Ratio for rational ones)
+///  * An integer that reports overflow rather than wrapping (CheckedCount)
 ///  * An array, being the number of solutions with a given number of the variables true (SingleVariableGeneratingFunction, SingleVariableGeneratingFunctionFixedLength)
 pub trait GeneratingFunction : Sized + Clone + Debug {
     /// The base value for NodeIndex::FALSE
@@ -59,6 +63,113 @@ impl GeneratingFunction for u128 {
     fn variable_set(self, _variable: VariableIndex) -> Self { self }
 }
 
+/// A simple generating function that separates counts by the number of variables set.
+/// Arbitrary precision, for counts that overflow a u128.
+impl GeneratingFunction for BigUint {
+    fn zero() -> Self { <BigUint as num::Zero>::zero() }
+    fn one() -> Self { <BigUint as num::One>::one() }
+    fn add(self, other: Self) -> Self { self+other }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+}
+
+/// An approximate count, which loses precision past 2^53 but does not overflow until about 2^1024.
+/// Convenient for probabilities and other statistics of huge solution sets.
+impl GeneratingFunction for f64 {
+    fn zero() -> Self { 0.0 }
+    fn one() -> Self { 1.0 }
+    fn add(self, other: Self) -> Self { self+other }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+}
+
+/// The total of signed multiplicities.
+impl GeneratingFunction for i64 {
+    fn zero() -> Self { 0 }
+    fn one() -> Self { 1 }
+    fn add(self, other: Self) -> Self { self+other }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+}
+
+/// The total of signed multiplicities.
+impl GeneratingFunction for i128 {
+    fn zero() -> Self { 0 }
+    fn one() -> Self { 1 }
+    fn add(self, other: Self) -> Self { self+other }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+}
+
+/// The total of signed multiplicities, arbitrary precision.
+impl GeneratingFunction for BigInt {
+    fn zero() -> Self { <BigInt as num::Zero>::zero() }
+    fn one() -> Self { <BigInt as num::One>::one() }
+    fn add(self, other: Self) -> Self { self+other }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+}
+
+/// The total of rational multiplicities, such as a probability.
+impl <T:Clone+Integer+Debug> GeneratingFunction for Ratio<T> {
+    fn zero() -> Self { <Ratio<T> as num::Zero>::zero() }
+    fn one() -> Self { <Ratio<T> as num::One>::one() }
+    fn add(self, other: Self) -> Self { self+other }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+}
+
+/// A count of solutions that records overflow, rather than silently wrapping (or panicking in debug builds) as
+/// u64 and the other primitive integers do. None means the count did not fit in T.
+/// # Example
+/// ```
+/// use xdd::{DecisionDiagramFactory, NodeIndex, NoMultiplicity, ZDDFactory};
+/// use xdd::generating_function::CheckedCount;
+/// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(70);
+/// let everything = factory.not(NodeIndex::FALSE); // 2^70 solutions.
+/// let count : CheckedCount<u64> = factory.number_solutions(everything);
+/// assert!(!count.count_is_exact());
+/// let count : CheckedCount<u128> = factory.number_solutions(everything);
+/// assert!(count.count_is_exact());
+/// assert_eq!(Ok(1<<70),count.into_result());
+/// ```
+#[derive(Clone,Copy,Eq,PartialEq,Debug)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
+pub struct CheckedCount<T>(pub Option<T>);
+
+impl <T> CheckedCount<T> {
+    /// True iff the count did not overflow, so is the actual number of solutions.
+    pub fn count_is_exact(&self) -> bool { self.0.is_some() }
+    /// The count, or an error if it overflowed.
+    pub fn into_result(self) -> Result<T,CountOverflow> { self.0.ok_or(CountOverflow{}) }
+}
+
+impl <T:Clone+Debug+CheckedAdd+Zero+One> GeneratingFunction for CheckedCount<T> {
+    fn zero() -> Self { CheckedCount(Some(T::zero())) }
+    fn one() -> Self { CheckedCount(Some(T::one())) }
+    fn add(self, other: Self) -> Self {
+        match (self.0,other.0) {
+            (Some(a),Some(b)) => CheckedCount(a.checked_add(&b)),
+            _ => CheckedCount(None),
+        }
+    }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+}
+
+/// A multiplicity that can not be converted to T, such as a negative one with an unsigned count, is also treated as overflow.
+impl <T:Clone+Debug+CheckedAdd+CheckedMul+Zero+One,M:Copy+Integer+TryInto<T>> GeneratingFunctionWithMultiplicity<M> for CheckedCount<T> {
+    fn multiply(self, multiple: M) -> Self {
+        CheckedCount(self.0.and_then(|count|multiple.try_into().ok().and_then(|multiple|count.checked_mul(&multiple))))
+    }
+}
+
+/// The error when a [CheckedCount] overflowed.
+#[derive(Clone,Copy,Eq,PartialEq,Debug)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
+pub struct CountOverflow {}
+
+impl Error for CountOverflow { }
+
+impl Display for CountOverflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"The number of solutions is too large for the type used to count them")
+    }
+}
+
 impl <G:GeneratingFunction,I:Into<G>+Ord> GeneratingFunctionWithMultiplicity<I> for G // The requirement on Ord is to prevent a possible clash with NoMultiplicity.
     where G:Mul<G,Output=G>,
 {
@@ -71,6 +182,7 @@ impl <G:GeneratingFunction,I:Into<G>+Ord> GeneratingFunctionWithMultiplicity<I>
 
 
 #[derive(Clone,Eq, PartialEq,Debug)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 /// Measure the number of solutions of the diagram separated
 /// by the number of variables that are true in the solution.
 pub struct SingleVariableGeneratingFunction<E:Integer>(pub Vec<E>);
@@ -96,7 +208,7 @@ impl <E:Clone+Eq+PartialEq+Debug+Clone+Integer+AddAssign> GeneratingFunction for
     /// shift up by one
     fn variable_set(self, _variable: VariableIndex) -> Self {
         let SingleVariableGeneratingFunction(mut res) = self;
-        if res.len()>0 { res.insert(0,E::zero()); }
+        if !res.is_empty() { res.insert(0,E::zero()); }
         SingleVariableGeneratingFunction(res)
     }
 }
@@ -115,6 +227,7 @@ impl <E:Clone+Eq+PartialEq+Debug+Clone+Integer+AddAssign+MulAssign,M:Copy+Intege
 
 /// A generating function whose i^th element is the number of elements in the set with multiplicity i+1.
 #[derive(Clone,Eq, PartialEq,Debug)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 pub struct GeneratingFunctionSplitByMultiplicity<E:Integer>(pub Vec<E>);
 
 impl <E:Clone+Eq+PartialEq+Debug+Clone+Integer+AddAssign> GeneratingFunction for GeneratingFunctionSplitByMultiplicity<E> {
@@ -142,7 +255,7 @@ impl <E:Clone+Eq+PartialEq+Debug+Clone+Integer+AddAssign> GeneratingFunction for
 impl <E:Clone+Eq+PartialEq+Debug+Clone+Integer+AddAssign,M:Copy+Integer+TryInto<u64>> GeneratingFunctionWithMultiplicity<M> for GeneratingFunctionSplitByMultiplicity<E> {
     fn multiply(self, multiple: M) -> Self {
         let multiple : u64 = multiple.try_into().map_err(|_|()).expect("Could not convert multiplicity into u64");
-        if multiple > 0 && self.0.len()>0 {
+        if multiple > 0 && !self.0.is_empty() {
             // want position i-1 to go to position multiple*i-1. So insert multiple-1 zeros before each element.
             let mut res = vec![];
             for e in self.0 {
@@ -156,6 +269,7 @@ impl <E:Clone+Eq+PartialEq+Debug+Clone+Integer+AddAssign,M:Copy+Integer+TryInto<
 
 
 #[derive(Clone,Eq, PartialEq,Debug)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 /// a generating function with a fixed maximum length.
 /// Like SingleVariableGeneratingFunction but discard all values higher than a given size.
 pub struct SingleVariableGeneratingFunctionFixedLength<const L:usize>(pub Vec<u64>);
@@ -182,8 +296,36 @@ impl <const L:usize> GeneratingFunction for SingleVariableGeneratingFunctionFixe
     /// shift up by one
     fn variable_set(self, _variable: VariableIndex) -> Self {
         let SingleVariableGeneratingFunctionFixedLength(mut res) = self;
-        if res.len()>0 { res.insert(0,0); }
+        if !res.is_empty() { res.insert(0,0); }
         if res.len()>L { res.pop(); }
         SingleVariableGeneratingFunctionFixedLength::<L>(res)
     }
 }
+
+/// Generating functions already worked out for the nodes of one factory, to be reused by
+/// [crate::DecisionDiagramFactory::number_solutions_cached] so that counting many diagrams sharing subdiagrams only
+/// processes each node once.
+///
+/// The values depend on the factory's nodes, so a cache should only be used with one factory, and with one generating function type.
+/// After a gc, call [CountingCache::rename] with the renaming gc returned. After anything else that changes
+/// existing nodes, such as [crate::DecisionDiagramFactory::swap_adjacent_levels], call [CountingCache::clear].
+#[derive(Clone,Debug)]
+pub struct CountingCache<A:NodeAddress,G> {
+    pub(crate) values : HashMap<A,G>,
+}
+
+impl <A:NodeAddress,G> Default for CountingCache<A,G> {
+    fn default() -> Self { CountingCache{ values: HashMap::new() } }
+}
+
+impl <A:NodeAddress,G> CountingCache<A,G> {
+    /// The number of nodes whose generating function is remembered.
+    pub fn len(&self) -> usize { self.values.len() }
+    pub fn is_empty(&self) -> bool { self.values.is_empty() }
+    /// Forget everything.
+    pub fn clear(&mut self) { self.values.clear(); }
+    /// Follow the nodes to their new addresses after a gc, forgetting the ones that were not kept.
+    pub fn rename(&mut self, renaming:&NodeRenaming<A>) {
+        self.values = std::mem::take(&mut self.values).into_iter().filter_map(|(address,g)|renaming.rename_address(address).map(|address|(address,g))).collect();
+    }
+}
@@ -0,0 +1,277 @@
+//! Families of subsets of a graph: independent sets, cliques and matchings.
+//!
+//! These are the standard examples of ZDDs in combinatorics, see Knuth, "The Art of Computer Programming"
+//! volume 4 fascicle 1, section 7.1.4. Independent sets and cliques are sets of vertices, so vertex v
+//! is variable v. Matchings are sets of edges, so the edge added i'th (counting from 0) is variable i.
+//!
+//! The diagrams are built as the "and" of one small constraint per edge or vertex, and work with
+//! any factory, though a ZDD is usually much smaller as most vertices are absent from most solutions.
+//!
+//! Simple paths between two vertices and simple cycles are sets of edges too. These are built directly as a
+//! ZDD by Knuth's Simpath algorithm ([Graph::simple_paths], [Graph::simple_cycles]), which adds the edges one
+//! at a time keeping track of how the vertices on the "frontier" are connected so far. The frontier is the set
+//! of vertices with edges both before and after the current one, so the work depends heavily on the order of
+//! the edges; adding edges in an order that sweeps across the graph (as [Graph::grid] does) works well.
+//!
+//! # Example
+//! ```
+//! use xdd::{DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+//! use xdd::graphs::Graph;
+//! let cycle = Graph::cycle(5);
+//! let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(5);
+//! let independent_sets = cycle.independent_sets(&mut factory);
+//! assert_eq!(11,factory.number_solutions::<u64>(independent_sets)); // {}, 5 singletons and 5 non-adjacent pairs.
+//! let cliques = cycle.cliques(&mut factory);
+//! assert_eq!(11,factory.number_solutions::<u64>(cliques)); // {}, 5 singletons and 5 edges.
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use crate::{DecisionDiagramFactory, Multiplicity, Node, NodeAddress, NodeIndex, VariableIndex, ZDDFactory};
+use crate::xdd_with_multiplicity::XDDBase;
+use crate::typed_variables::Var;
+
+/// The index of a vertex, from 0 to [Graph::num_vertices] exclusive.
+pub type Vertex = usize;
+/// The index of an edge in [Graph::edges].
+pub type EdgeIndex = usize;
+/// Marker for variables that represent vertices; see [crate::typed_variables].
+pub enum VertexTag {}
+/// The variable that is true iff a vertex is in the set.
+pub type VertexVar = Var<VertexTag>;
+/// Marker for variables that represent edges; see [crate::typed_variables].
+pub enum EdgeTag {}
+/// The variable that is true iff an edge is in the set.
+pub type EdgeVar = Var<EdgeTag>;
+
+/// An undirected graph without self loops. Parallel edges are allowed; they only matter for matchings.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct Graph {
+    pub num_vertices : usize,
+    /// Each edge as (smaller vertex, larger vertex).
+    pub edges : Vec<(Vertex,Vertex)>,
+}
+
+impl Graph {
+    /// A graph with no edges.
+    pub fn new(num_vertices:usize) -> Self { Graph{num_vertices,edges:vec![]} }
+
+    /// A graph with the given edges. Panics if an edge is a loop or uses a vertex not less than num_vertices.
+    pub fn from_edges(num_vertices:usize,edges:&[(Vertex,Vertex)]) -> Self {
+        let mut graph = Graph::new(num_vertices);
+        for &(a,b) in edges { graph.add_edge(a,b); }
+        graph
+    }
+
+    /// The cycle on n vertices, with edges (0,1), (1,2) ... (n-1,0).
+    pub fn cycle(n:usize) -> Self {
+        Graph::from_edges(n,&(0..n).map(|i|(i,(i+1)%n)).collect::<Vec<_>>())
+    }
+
+    /// The complete graph on n vertices.
+    pub fn complete(n:usize) -> Self {
+        Graph::from_edges(n,&(0..n).flat_map(|a|(a+1..n).map(move |b|(a,b))).collect::<Vec<_>>())
+    }
+
+    /// The grid graph with width times height vertices, vertex (x,y) being x+width*y. The edges are in an order
+    /// suited to [Graph::simple_paths]: going through the vertices in order, the edge to the right and then the edge below each.
+    pub fn grid(width:usize,height:usize) -> Self {
+        let mut graph = Graph::new(width*height);
+        for y in 0..height {
+            for x in 0..width {
+                let v = x+width*y;
+                if x+1<width { graph.add_edge(v,v+1); }
+                if y+1<height { graph.add_edge(v,v+width); }
+            }
+        }
+        graph
+    }
+
+    /// Add an edge, returning its index. Panics if it is a loop or uses a vertex not less than num_vertices.
+    pub fn add_edge(&mut self,a:Vertex,b:Vertex) -> EdgeIndex {
+        assert!(a<self.num_vertices && b<self.num_vertices,"Edge ({},{}) used in a graph with only {} vertices",a,b,self.num_vertices);
+        assert_ne!(a,b,"Self loops are not supported");
+        self.edges.push((a.min(b),a.max(b)));
+        self.edges.len()-1
+    }
+
+    /// The variable for a vertex in [Graph::independent_sets] and [Graph::cliques].
+    pub fn vertex_variable(vertex:Vertex) -> VertexVar { VertexVar::new(vertex as u16) }
+    /// The variable for an edge in [Graph::matchings].
+    pub fn edge_variable(edge:EdgeIndex) -> EdgeVar { EdgeVar::new(edge as u16) }
+
+    /// Make a diagram of the sets of vertices no two of which are joined by an edge.
+    /// The factory must have at least [Graph::num_vertices] variables; any others are unconstrained.
+    pub fn independent_sets<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F) -> NodeIndex<A,M> {
+        let pairs : HashSet<(Vertex,Vertex)> = self.edges.iter().cloned().collect();
+        Self::no_two_of(factory,pairs.into_iter().map(|(a,b)|vec![a,b]))
+    }
+
+    /// Make a diagram of the sets of vertices every two of which are joined by an edge.
+    /// The factory must have at least [Graph::num_vertices] variables; any others are unconstrained.
+    pub fn cliques<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F) -> NodeIndex<A,M> {
+        let pairs : HashSet<(Vertex,Vertex)> = self.edges.iter().cloned().collect();
+        let n = self.num_vertices;
+        let non_edges = (0..n).flat_map(|a|(a+1..n).map(move |b|(a,b))).filter(|p|!pairs.contains(p));
+        Self::no_two_of(factory,non_edges.map(|(a,b)|vec![a,b]))
+    }
+
+    /// Make a diagram of the sets of edges no two of which share a vertex. Edge i is variable i.
+    /// The factory must have at least as many variables as there are edges; any others are unconstrained.
+    pub fn matchings<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F) -> NodeIndex<A,M> {
+        let mut incident : Vec<Vec<EdgeIndex>> = vec![vec![];self.num_vertices];
+        for (i,&(a,b)) in self.edges.iter().enumerate() {
+            incident[a].push(i);
+            incident[b].push(i);
+        }
+        Self::no_two_of(factory,incident.into_iter().filter(|edges|edges.len()>1))
+    }
+
+    /// Make a ZDD of the sets of edges that form a simple path from s to t. Edge i is variable i.
+    /// The factory must have at least as many variables as there are edges; any others are unconstrained.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+    /// use xdd::graphs::Graph;
+    /// let grid = Graph::grid(3,3);
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(grid.edges.len() as u16);
+    /// let paths = grid.simple_paths(&mut factory,0,8); // from one corner to the opposite one.
+    /// assert_eq!(12,factory.number_solutions::<u64>(paths));
+    /// ```
+    pub fn simple_paths<A:NodeAddress,M:Multiplicity>(&self,factory:&mut ZDDFactory<A,M>,s:Vertex,t:Vertex) -> NodeIndex<A,M> {
+        assert!(s<self.num_vertices && t<self.num_vertices,"Terminals {} and {} used in a graph with only {} vertices",s,t,self.num_vertices);
+        assert_ne!(s,t,"The terminals of a path must be different");
+        Simpath::new(self,Some((s,t))).build(factory)
+    }
+
+    /// Make a ZDD of the sets of edges that form a simple cycle. Edge i is variable i.
+    /// The factory must have at least as many variables as there are edges; any others are unconstrained.
+    pub fn simple_cycles<A:NodeAddress,M:Multiplicity>(&self,factory:&mut ZDDFactory<A,M>) -> NodeIndex<A,M> {
+        Simpath::new(self,None).build(factory)
+    }
+
+    /// The "and" of constraints that at most one of each group of variables (each sorted) is true.
+    fn no_two_of<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(factory:&mut F,groups:impl Iterator<Item=Vec<usize>>) -> NodeIndex<A,M> {
+        let mut groups : Vec<Vec<usize>> = groups.collect();
+        groups.sort(); // so the result does not depend on the iteration order of a HashSet.
+        groups.reverse(); // much faster to merge later constraints first.
+        let constraints : Vec<NodeIndex<A,M>> = groups.iter().map(|group|{
+            factory.at_most_n_of(&group.iter().map(|&v|VariableIndex(v as u16)).collect::<Vec<_>>(),1)
+        }).collect();
+        factory.poly_and(&constraints).unwrap_or_else(||factory.not(NodeIndex::FALSE))
+    }
+}
+
+/// The mate of a vertex with two chosen edges, which can not be extended.
+const SATURATED : usize = usize::MAX;
+
+/// What choosing an edge leads to.
+enum Step {
+    /// No solution.
+    Reject,
+    /// A solution, as long as no later edges are chosen.
+    Accept,
+    /// The new mates.
+    Continue(Vec<usize>),
+}
+
+/// Knuth's Simpath algorithm for the ZDD of simple paths or cycles.
+///
+/// The state after deciding some edges is the "mate" of each vertex: itself if no chosen edge touches it,
+/// [SATURATED] if two do, and otherwise the other end of the path it is an end of. A vertex is reset to itself
+/// once all its edges are decided, so states that only differ in the past are shared.
+struct Simpath<'a> {
+    graph : &'a Graph,
+    /// The ends of the path, or None for cycles. These are never reset.
+    terminals : Option<(Vertex,Vertex)>,
+    /// last_edge[v] is the index of the last edge touching v, if any.
+    last_edge : Vec<Option<EdgeIndex>>,
+}
+
+impl <'a> Simpath<'a> {
+    fn new(graph:&'a Graph,terminals:Option<(Vertex,Vertex)>) -> Self {
+        let mut last_edge = vec![None;graph.num_vertices];
+        for (i,&(a,b)) in graph.edges.iter().enumerate() {
+            last_edge[a]=Some(i);
+            last_edge[b]=Some(i);
+        }
+        Simpath{graph,terminals,last_edge}
+    }
+
+    fn is_terminal(&self,v:Vertex) -> bool { self.terminals.is_some_and(|(s,t)|v==s || v==t) }
+
+    fn build<A:NodeAddress,M:Multiplicity>(&self,factory:&mut ZDDFactory<A,M>) -> NodeIndex<A,M> {
+        let num_edges = self.graph.edges.len();
+        assert!(num_edges<=factory.num_variables as usize,"A graph with {} edges needs at least that many variables, not {}",num_edges,factory.num_variables);
+        // later variables are unconstrained.
+        let accept = factory.nodes.true_regardless_of_variables_below_zdd(VariableIndex(num_edges as u16),factory.num_variables);
+        let mate : Vec<usize> = (0..self.graph.num_vertices).collect();
+        self.build_from(&mut factory.nodes,0,mate,accept,&mut HashMap::new())
+    }
+
+    /// The ZDD for the edges from position onwards, given the mates from the earlier ones.
+    fn build_from<A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>>(&self,nodes:&mut X,position:EdgeIndex,mate:Vec<usize>,accept:NodeIndex<A,M>,memo:&mut HashMap<(EdgeIndex,Vec<usize>),NodeIndex<A,M>>) -> NodeIndex<A,M> {
+        if position==self.graph.edges.len() { return NodeIndex::FALSE; } // any solution has already been accepted.
+        if let Some(&res) = memo.get(&(position,mate.clone())) { return res; }
+        let lo = match self.finish_vertices(position,mate.clone()) {
+            Some(next) => self.build_from(nodes,position+1,next,accept,memo),
+            None => NodeIndex::FALSE,
+        };
+        let hi = match self.choose(position,&mate) {
+            Step::Reject => NodeIndex::FALSE,
+            Step::Accept => accept,
+            Step::Continue(next) => match self.finish_vertices(position,next) {
+                Some(next) => self.build_from(nodes,position+1,next,accept,memo),
+                None => NodeIndex::FALSE,
+            },
+        };
+        let res = if hi.is_false() { lo } else { nodes.add_node_if_not_present(Node {variable:VariableIndex(position as u16),lo,hi}) };
+        memo.insert((position,mate),res);
+        res
+    }
+
+    /// The degree (0, 1 or 2) of a vertex in the chosen edges.
+    fn degree(mate:&[usize],v:Vertex) -> usize {
+        if mate[v]==v { 0 } else if mate[v]==SATURATED { 2 } else { 1 }
+    }
+
+    /// The result of choosing the edge at position.
+    fn choose(&self,position:EdgeIndex,mate:&[usize]) -> Step {
+        let (u,v) = self.graph.edges[position];
+        for w in [u,v] {
+            let degree = Self::degree(mate,w);
+            if degree==2 || (degree==1 && self.is_terminal(w)) { return Step::Reject; }
+        }
+        let (a,b) = (mate[u],mate[v]);
+        let mut next = mate.to_vec();
+        if Self::degree(mate,u)==1 { next[u]=SATURATED; }
+        if Self::degree(mate,v)==1 { next[v]=SATURATED; }
+        let complete = if a==v { // closes a cycle.
+            if self.terminals.is_some() { return Step::Reject; }
+            true
+        } else {
+            next[a]=b;
+            next[b]=a;
+            self.terminals.is_some_and(|(s,t)|next[s]==t)
+        };
+        if !complete { Step::Continue(next) }
+        // any other partial path could never be finished.
+        else if (0..next.len()).all(|w|self.is_terminal(w) || Self::degree(&next,w)!=1) { Step::Accept }
+        else { Step::Reject }
+    }
+
+    /// Reset the vertices whose last edge is at position, or None if one of them is left as the end of a path.
+    fn finish_vertices(&self,position:EdgeIndex,mut mate:Vec<usize>) -> Option<Vec<usize>> {
+        let (u,v) = self.graph.edges[position];
+        for w in [u,v] {
+            if self.last_edge[w]==Some(position) {
+                match Self::degree(&mate,w) {
+                    0 if self.is_terminal(w) => return None,
+                    1 if !self.is_terminal(w) => return None,
+                    _ if !self.is_terminal(w) => mate[w]=w,
+                    _ => {}
+                }
+            }
+        }
+        Some(mate)
+    }
+}
@@ -0,0 +1,245 @@
+//! A JSON representation of diagrams, for web based visualizers and post-processing scripts in other languages.
+//!
+//! The format is an object with fields
+//! * `num_variables` : the number of variables in the factory.
+//! * `roots` : an array of objects with an `id` and an optional `name`, and a `multiplicity` if multiplicities are used.
+//! * `nodes` : an array of objects with `id`, `variable`, `name` (of the variable), `lo` and `hi` (ids of other nodes), and
+//!   `lo_multiplicity` and `hi_multiplicity` if multiplicities are used. Children come before their parents.
+//!
+//! Ids 0 and 1 are the FALSE and TRUE sinks, which are not listed in `nodes`. Other ids are addresses in the
+//! factory that wrote the file, and are only used to connect nodes together.
+//!
+//! This is written and parsed directly so as not to need extra dependencies.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{ErrorKind, Read, Write};
+use std::str::FromStr;
+use std::time::Instant;
+use crate::{BDDFactory, Multiplicity, Node, NodeAddress, NodeIndex, VariableIndex, ZDDFactory};
+use crate::statistics::OperationClass;
+use crate::xdd_with_multiplicity::XDDBase;
+
+/// Roots of diagrams with optional names, as read from a file.
+pub type NamedRoots<A,M> = Vec<(NodeIndex<A,M>,Option<String>)>;
+
+/// Write a string as a JSON string literal.
+pub(crate) fn quote(s:&str) -> String {
+    let mut res = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            c if (c as u32)<0x20 => res.push_str(&format!("\\u{:04x}",c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Write the diagrams starting at start_nodes (with optional names) in the JSON format described in [crate::json].
+pub fn write_json<A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>,W:Write,F:Fn(VariableIndex)->String>(nodes:&X, writer:&mut W, num_variables:u16, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+    let multiplicity = |name:&str,m:M| if M::MULTIPLICITIES_IRRELEVANT { String::new() } else { format!(",\"{}\":{}",name,m) };
+    writeln!(writer,"{{\"num_variables\":{},",num_variables)?;
+    writeln!(writer," \"roots\":[")?;
+    for (i,(root,name)) in start_nodes.iter().enumerate() {
+        let name = name.as_ref().map(|n|format!(",\"name\":{}",quote(n))).unwrap_or_default();
+        writeln!(writer,"  {{\"id\":{}{}{}}}{}",root.address(),name,multiplicity("multiplicity",root.multiplicity()),if i+1<start_nodes.len() {","} else {""})?;
+    }
+    writeln!(writer," ],")?;
+    writeln!(writer," \"nodes\":[")?;
+    let reachable = nodes.reachable(start_nodes.iter().map(|(root,_)|*root));
+    let mut first = true;
+    for address in (2..reachable.len()).filter(|&a|reachable[a]) {
+        let node = nodes.node(address.try_into().map_err(|_|()).unwrap());
+        if !first { writeln!(writer,",")?; }
+        first=false;
+        write!(writer,"  {{\"id\":{},\"variable\":{},\"name\":{},\"lo\":{},\"hi\":{}{}{}}}",address,node.variable,quote(&namer(node.variable)),node.lo.address(),node.hi.address(),multiplicity("lo_multiplicity",node.lo.multiplicity()),multiplicity("hi_multiplicity",node.hi.multiplicity()))?;
+    }
+    if !first { writeln!(writer)?; }
+    writeln!(writer," ]")?;
+    writeln!(writer,"}}")?;
+    Ok(())
+}
+
+/// A parsed JSON value. Numbers are kept as text so they can be parsed into whatever type is wanted.
+#[derive(Clone,Debug,PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String,Json)>),
+}
+
+fn invalid(message:impl Display) -> std::io::Error { std::io::Error::new(ErrorKind::InvalidData,format!("invalid JSON diagram: {}",message)) }
+
+struct Parser<'a> {
+    text : &'a [u8],
+    position : usize,
+}
+
+impl <'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.position<self.text.len() && self.text[self.position].is_ascii_whitespace() { self.position+=1; }
+    }
+    fn peek(&mut self) -> std::io::Result<u8> {
+        self.skip_whitespace();
+        self.text.get(self.position).cloned().ok_or_else(||invalid("unexpected end of file"))
+    }
+    fn expect(&mut self,c:u8) -> std::io::Result<()> {
+        if self.peek()?==c { self.position+=1; Ok(()) } else { Err(invalid(format!("expected {} at byte {}",c as char,self.position))) }
+    }
+    fn literal(&mut self,word:&str,value:Json) -> std::io::Result<Json> {
+        if self.text[self.position..].starts_with(word.as_bytes()) { self.position+=word.len(); Ok(value) } else { Err(invalid(format!("unexpected text at byte {}",self.position))) }
+    }
+    fn string(&mut self) -> std::io::Result<String> {
+        self.expect(b'"')?;
+        let mut res = vec![];
+        loop {
+            let c = *self.text.get(self.position).ok_or_else(||invalid("unterminated string"))?;
+            self.position+=1;
+            match c {
+                b'"' => return String::from_utf8(res).map_err(invalid),
+                b'\\' => {
+                    let escape = *self.text.get(self.position).ok_or_else(||invalid("unterminated string"))?;
+                    self.position+=1;
+                    let decoded = match escape {
+                        b'n' => '\n', b't' => '\t', b'r' => '\r', b'b' => '\u{8}', b'f' => '\u{c}',
+                        b'u' => {
+                            let hex = self.text.get(self.position..self.position+4).ok_or_else(||invalid("bad \\u escape"))?;
+                            self.position+=4;
+                            let code = u32::from_str_radix(std::str::from_utf8(hex).map_err(invalid)?,16).map_err(invalid)?;
+                            char::from_u32(code).unwrap_or('\u{fffd}') // surrogate pairs are not needed for names.
+                        }
+                        c => c as char,
+                    };
+                    let mut buffer = [0;4];
+                    res.extend_from_slice(decoded.encode_utf8(&mut buffer).as_bytes());
+                }
+                c => res.push(c),
+            }
+        }
+    }
+    fn value(&mut self) -> std::io::Result<Json> {
+        match self.peek()? {
+            b'{' => {
+                self.position+=1;
+                let mut fields = vec![];
+                if self.peek()?==b'}' { self.position+=1; return Ok(Json::Object(fields)); }
+                loop {
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key,self.value()?));
+                    if self.peek()?==b',' { self.position+=1; } else { self.expect(b'}')?; return Ok(Json::Object(fields)); }
+                }
+            }
+            b'[' => {
+                self.position+=1;
+                let mut elements = vec![];
+                if self.peek()?==b']' { self.position+=1; return Ok(Json::Array(elements)); }
+                loop {
+                    elements.push(self.value()?);
+                    if self.peek()?==b',' { self.position+=1; } else { self.expect(b']')?; return Ok(Json::Array(elements)); }
+                }
+            }
+            b'"' => Ok(Json::String(self.string()?)),
+            b't' => self.literal("true",Json::Bool(true)),
+            b'f' => self.literal("false",Json::Bool(false)),
+            b'n' => self.literal("null",Json::Null),
+            _ => {
+                let start = self.position;
+                while self.position<self.text.len() && matches!(self.text[self.position],b'-'|b'+'|b'.'|b'e'|b'E'|b'0'..=b'9') { self.position+=1; }
+                if start==self.position { return Err(invalid(format!("unexpected character at byte {}",start))); }
+                Ok(Json::Number(String::from_utf8_lossy(&self.text[start..self.position]).to_string()))
+            }
+        }
+    }
+}
+
+impl Json {
+    fn field(&self,name:&str) -> Option<&Json> {
+        if let Json::Object(fields) = self { fields.iter().find(|(k,_)|k==name).map(|(_,v)|v) } else { None }
+    }
+    fn required(&self,name:&str) -> std::io::Result<&Json> { self.field(name).ok_or_else(||invalid(format!("missing field {}",name))) }
+    fn array(&self) -> std::io::Result<&[Json]> { if let Json::Array(a) = self { Ok(a) } else { Err(invalid("expected an array")) } }
+    fn number<T:FromStr>(&self) -> std::io::Result<T> {
+        if let Json::Number(n) = self { n.parse().map_err(|_|invalid(format!("number {} out of range",n))) } else { Err(invalid("expected a number")) }
+    }
+    /// Get an optional multiplicity field, defaulting to 1.
+    fn multiplicity<M:Multiplicity+FromStr>(&self,name:&str) -> std::io::Result<M> {
+        match self.field(name) {
+            None => Ok(M::ONE),
+            Some(m) => m.number(),
+        }
+    }
+}
+
+/// Read diagrams written by [write_json], adding the nodes to the given nodes.
+/// Returns the number of variables in the file and the roots with their names.
+pub fn read_json<A:NodeAddress,M:Multiplicity+FromStr,X:XDDBase<A,M>,R:Read>(nodes:&mut X, reader:&mut R) -> std::io::Result<(u16,NamedRoots<A,M>)> {
+    let mut text = vec![];
+    reader.read_to_end(&mut text)?;
+    let mut parser = Parser{text:&text,position:0};
+    let json = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position!=text.len() { return Err(invalid("extra text after the diagram")); }
+    let num_variables : u16 = json.required("num_variables")?.number()?;
+    let mut read : HashMap<u64,NodeIndex<A,M>> = HashMap::new();
+    read.insert(0,NodeIndex::FALSE);
+    read.insert(1,NodeIndex::TRUE);
+    let lookup = |read:&HashMap<u64,NodeIndex<A,M>>,json:&Json,name:&str| -> std::io::Result<NodeIndex<A,M>> {
+        let id : u64 = json.required(name)?.number()?;
+        read.get(&id).cloned().ok_or_else(||invalid(format!("reference to node {} before it is defined",id)))
+    };
+    for node in json.required("nodes")?.array()? {
+        let id : u64 = node.required("id")?.number()?;
+        let variable = VariableIndex(node.required("variable")?.number()?);
+        if variable.0>=num_variables { return Err(invalid(format!("variable {} but only {} variables",variable,num_variables))); }
+        let lo = lookup(&read,node,"lo")?.multiply(node.multiplicity("lo_multiplicity")?);
+        let hi = lookup(&read,node,"hi")?.multiply(node.multiplicity("hi_multiplicity")?);
+        for child in [lo,hi] {
+            if !child.is_sink() && nodes.node(child.address()).variable<=variable { return Err(invalid(format!("node {} is not above its children in the variable order",id))); }
+        }
+        if read.contains_key(&id) { return Err(invalid(format!("node {} is defined more than once",id))); }
+        read.insert(id,nodes.add_node_if_not_present(Node{variable,lo,hi}));
+    }
+    let mut roots = vec![];
+    for root in json.required("roots")?.array()? {
+        let index = lookup(&read,root,"id")?.multiply(root.multiplicity("multiplicity")?);
+        let name = match root.field("name") {
+            None|Some(Json::Null) => None,
+            Some(Json::String(s)) => Some(s.clone()),
+            _ => return Err(invalid("root names should be strings")),
+        };
+        roots.push((index,name));
+    }
+    Ok((num_variables,roots))
+}
+
+impl <A:NodeAddress,M:Multiplicity+FromStr> BDDFactory<A,M> {
+    /// Read diagrams written by [crate::DecisionDiagramFactory::make_json_file], returning the roots and their names.
+    /// They must not use more variables than this factory has.
+    pub fn read_json_file<R:Read>(&mut self,reader:&mut R) -> std::io::Result<NamedRoots<A,M>> {
+        let start = Instant::now();
+        let (num_variables,roots) = read_json(&mut self.nodes,reader)?;
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        if num_variables>self.num_variables { return Err(invalid(format!("file has {} variables but the factory only has {}",num_variables,self.num_variables))); }
+        Ok(roots)
+    }
+}
+
+impl <A:NodeAddress,M:Multiplicity+FromStr> ZDDFactory<A,M> {
+    /// Read diagrams written by [crate::DecisionDiagramFactory::make_json_file], returning the roots and their names.
+    /// They must use the same number of variables as this factory, as variables not mentioned in a ZDD are false.
+    pub fn read_json_file<R:Read>(&mut self,reader:&mut R) -> std::io::Result<NamedRoots<A,M>> {
+        let start = Instant::now();
+        let (num_variables,roots) = read_json(&mut self.nodes,reader)?;
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        if num_variables!=self.num_variables { return Err(invalid(format!("file has {} variables but the factory has {}",num_variables,self.num_variables))); }
+        Ok(roots)
+    }
+}
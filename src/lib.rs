@@ -6,26 +6,63 @@
 //! It also uses external factories for generating xDDs, which improves efficiency for generating structures with lots of reuse which tends to arise in combinatorics.
 //! It also supports ZDDs as well as BDDs.
 //!
-//! It supports 16 bits for variables and 32 bits for pointers, limiting it to trees of 4 billion nodes.
-//! This may be changed in a newer version to a larger number.
+//! It supports 16 bits for variables. Pointers are generic, typically u32, limiting it to trees of 4 billion nodes.
+//! For more, use u64 pointers; [xdd_with_multiplicity::ChunkedNodeList] is a node store suited to such huge numbers of nodes.
+//!
+//! Factories are Send and Sync, and the queries taking `&self`, such as [DecisionDiagramFactory::number_solutions],
+//! [DecisionDiagramFactory::evaluate] and [DecisionDiagramFactory::solutions], only read the nodes. So once the diagrams are built,
+//! a factory can be shared by reference between threads (such as with [std::thread::scope]) to analyse them in parallel.
 //!
 
 pub mod generating_function;
 pub mod permutation_diagrams;
-pub(crate) mod xdd_with_multiplicity;
+pub mod xdd_with_multiplicity;
 pub mod util;
 pub mod permutation;
+pub mod tiling;
+pub mod oeis;
+pub mod statistics;
+pub mod typed_variables;
+pub mod serialization;
+pub mod json;
+pub mod cnf;
+pub mod variable_registry;
+pub mod roots;
+pub mod graphs;
+pub mod relations;
+pub mod tropical;
+pub mod function_vector;
+pub mod constraints;
+pub mod exact_cover;
+pub mod permutation_matrices;
+pub mod sharding;
+#[cfg(feature="parallel")]
+pub mod parallel;
+#[cfg(feature="mmap")]
+pub mod mapped;
 
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::convert::Infallible;
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
+use std::num::TryFromIntError;
 use std::io::Write;
 use std::ops::Rem;
+use std::str::FromStr;
+use std::time::Instant;
 use num::{Integer, Unsigned, Zero};
+use num::rational::Ratio;
+use rand::Rng;
 use crate::generating_function::GeneratingFunctionWithMultiplicity;
+use crate::variable_registry::VariableRegistry;
+use crate::roots::{Handle, RootRegistry};
+use crate::xdd_with_multiplicity::{ApplyCache, BinaryOperationCache, DotOptions, FamilyCache, OperationCache, PostOrder, RestrictCache, TernaryOperationCache, UnaryFamilyCache, VariableOperationCache};
+use crate::statistics::{CacheStatistics, DiagramProfile, FactoryStatistics, OperationClass};
 
 /// The identifier of a variable. Variable 0 is the highest one in the diagram.
 #[derive(Copy, Clone,Eq, PartialEq,Hash,Ord, PartialOrd,Debug)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 pub struct VariableIndex(pub u16);
 
 
@@ -35,6 +72,175 @@ impl Display for VariableIndex {
     }
 }
 
+/// Any of the 16 boolean functions of two arguments, given by its truth table, for use with [DecisionDiagramFactory::apply].
+///
+/// Bit 2a+b of the table is the result for arguments a and b. The common operators are provided as constants,
+/// and others can be made with [BooleanOperator::from_fn].
+#[derive(Copy, Clone,Eq, PartialEq,Hash,Debug)]
+pub struct BooleanOperator(pub u8);
+
+impl BooleanOperator {
+    pub const AND : Self = BooleanOperator(0b1000);
+    pub const OR : Self = BooleanOperator(0b1110);
+    pub const XOR : Self = BooleanOperator(0b0110);
+    pub const NAND : Self = BooleanOperator(0b0111);
+    pub const NOR : Self = BooleanOperator(0b0001);
+    /// a ⇔ b, a.k.a. XNOR
+    pub const EQUIVALENT : Self = BooleanOperator(0b1001);
+    /// a ⇒ b
+    pub const IMPLIES : Self = BooleanOperator(0b1011);
+    /// a and not b, a.k.a. set difference.
+    pub const AND_NOT : Self = BooleanOperator(0b0100);
+
+    /// Make an operator from a function, e.g. `BooleanOperator::from_fn(|a,b|a||!b)`.
+    pub fn from_fn(f:impl Fn(bool,bool)->bool) -> Self {
+        let mut table = 0;
+        for a in [false,true] { for b in [false,true] { if f(a,b) { table|=1<<(2*(a as u8)+(b as u8)); } } }
+        BooleanOperator(table)
+    }
+    /// The result of the operator for arguments a and b.
+    pub fn evaluate(self,a:bool,b:bool) -> bool { (self.0>>(2*(a as u8)+(b as u8)))&1==1 }
+    /// The operator producing the opposite result.
+    pub fn negate(self) -> Self { BooleanOperator(!self.0&0b1111) }
+    /// The operator with its arguments swapped.
+    pub fn swap_arguments(self) -> Self { BooleanOperator::from_fn(|a,b|self.evaluate(b,a)) }
+}
+
+/// How a sum is compared to a bound in [DecisionDiagramFactory::linear_constraint].
+#[derive(Copy,Clone,Debug,Eq,PartialEq,Hash)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// The inclusive range of sums that satisfy the comparison with bound.
+    fn sum_range(self,bound:i64) -> (i128,i128) {
+        let bound = bound as i128;
+        match self {
+            Comparison::Less => (i128::MIN,bound-1),
+            Comparison::LessOrEqual => (i128::MIN,bound),
+            Comparison::Equal => (bound,bound),
+            Comparison::GreaterOrEqual => (bound,i128::MAX),
+            Comparison::Greater => (bound+1,i128::MAX),
+        }
+    }
+}
+
+/// Errors from operations on a [DecisionDiagramFactory] that would otherwise panic.
+#[derive(Copy,Clone,Debug,Eq,PartialEq,Hash)]
+pub enum XDDError {
+    /// More nodes were needed than can be addressed by the [NodeAddress] type, such as more than 4 billion nodes for u32.
+    CapacityExceeded,
+    /// The [statistics::CancellationToken] given to [DecisionDiagramFactory::set_cancellation_token] was cancelled.
+    Cancelled,
+}
+
+impl std::error::Error for XDDError {}
+
+impl Display for XDDError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XDDError::CapacityExceeded => write!(f,"Too many nodes for the NodeAddress type"),
+            XDDError::Cancelled => write!(f,"The operation was cancelled"),
+        }
+    }
+}
+
+/// The most variables for [DecisionDiagramFactory::truth_table] and [DecisionDiagramFactory::minterms], giving 16 million entries.
+pub const MAX_TRUTH_TABLE_VARIABLES : u16 = 24;
+
+/// The result of [DecisionDiagramFactory::evaluate_partial].
+#[derive(Copy,Clone,Debug,Eq,PartialEq,Hash)]
+pub enum PartialEvaluation<A:NodeAddress,M:Multiplicity> {
+    /// The function has this value whatever the unassigned variables are.
+    Determined(bool),
+    /// The function with the assigned variables fixed, which depends on some of the others.
+    Undetermined(NodeIndex<A,M>),
+}
+
+impl <A:NodeAddress,M:Multiplicity> PartialEvaluation<A,M> {
+    /// The value, if it does not depend on the unassigned variables.
+    pub fn determined(self) -> Option<bool> { if let PartialEvaluation::Determined(value) = self { Some(value) } else { None } }
+}
+
+/// Whether [DecisionDiagramFactory::find_optimal_solution] looks for the least or greatest total weight.
+#[derive(Copy,Clone,Debug,Eq,PartialEq,Hash)]
+pub enum Objective {
+    Minimize,
+    Maximize,
+}
+
+/// How a function depends on one variable, from [DecisionDiagramFactory::unateness].
+#[derive(Copy,Clone,Debug,Eq,PartialEq,Hash)]
+pub enum Unateness {
+    /// Both positive and negative unate, so the function does not depend on the variable (ignoring multiplicities).
+    Independent,
+    /// Changing the variable from false to true never changes a solution into a non-solution.
+    Positive,
+    /// Changing the variable from true to false never changes a solution into a non-solution.
+    Negative,
+    /// Neither positive nor negative unate.
+    Binate,
+}
+
+/// A way of writing a solution, for [DecisionDiagramFactory::solutions_as]. Fixed width integers and other
+/// types not needing an allocation make enumerating huge numbers of solutions much cheaper than a Vec per solution.
+pub trait SolutionFormat : Sized {
+    /// Panic if a solution in a factory with this many variables can not be represented.
+    fn check_capacity(_num_variables:u16) {}
+    /// The solution with the given sorted list of true variables, in a factory with num_variables variables.
+    fn from_solution(true_variables:&[VariableIndex],num_variables:u16) -> Self;
+}
+
+/// A bit mask, with bit v set iff variable v is true. Limited to 32 variables.
+impl SolutionFormat for u32 {
+    fn check_capacity(num_variables:u16) { assert!(num_variables<=32,"A u32 can not hold a solution with {} variables",num_variables); }
+    fn from_solution(true_variables:&[VariableIndex],_num_variables:u16) -> Self { true_variables.iter().fold(0,|mask,v|mask|1<<v.0) }
+}
+
+/// A bit mask, with bit v set iff variable v is true. Limited to 64 variables.
+impl SolutionFormat for u64 {
+    fn check_capacity(num_variables:u16) { assert!(num_variables<=64,"A u64 can not hold a solution with {} variables",num_variables); }
+    fn from_solution(true_variables:&[VariableIndex],_num_variables:u16) -> Self { true_variables.iter().fold(0,|mask,v|mask|1<<v.0) }
+}
+
+/// A bit mask, with bit v set iff variable v is true. Limited to 128 variables.
+impl SolutionFormat for u128 {
+    fn check_capacity(num_variables:u16) { assert!(num_variables<=128,"A u128 can not hold a solution with {} variables",num_variables); }
+    fn from_solution(true_variables:&[VariableIndex],_num_variables:u16) -> Self { true_variables.iter().fold(0,|mask,v|mask|1<<v.0) }
+}
+
+/// The sorted list of true variables, as from [DecisionDiagramFactory::solutions].
+impl SolutionFormat for Vec<VariableIndex> {
+    fn from_solution(true_variables:&[VariableIndex],_num_variables:u16) -> Self { true_variables.to_vec() }
+}
+
+/// The value of every variable.
+impl SolutionFormat for Vec<bool> {
+    fn from_solution(true_variables:&[VariableIndex],num_variables:u16) -> Self {
+        let mut res = vec![false;num_variables as usize];
+        for v in true_variables { res[v.0 as usize]=true; }
+        res
+    }
+}
+
+/// How [DecisionDiagramFactory::write_all_solutions] writes solutions.
+#[derive(Copy,Clone,Debug,Eq,PartialEq,Hash)]
+pub enum SolutionTextFormat {
+    /// A header line of variable names, then a line for each solution with 0 or 1 for each variable, separated by commas.
+    Csv,
+    /// A line for each solution, being a JSON array of the names of the variables that are true.
+    JsonLines,
+}
+
+/// Write a field of a CSV file, quoted if needed.
+fn csv_field(s:&str) -> String {
+    if s.contains([',','"','\n','\r']) { format!("\"{}\"",s.replace('"',"\"\"")) } else { s.to_string() }
+}
 
 /// The identifier of a node on the tree (effectively a pointer), along with an associated multiplicity (number of times represented, for a multiset).
 ///
@@ -45,6 +251,7 @@ impl Display for VariableIndex {
 /// A is the type of addresses, typically u32 or usize.
 /// M is the type of multiplicies, typically some unsigned integer
 #[derive(Copy, Clone,Eq, PartialEq,Hash,Debug)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 pub struct NodeIndex<A:NodeAddress,M:Multiplicity> {
     address : A,
     multiplicity : M,
@@ -59,6 +266,10 @@ impl <A:NodeAddress,M:Multiplicity> NodeIndex<A,M> {
     /// Do not use this node index for anything else.
     pub const TRUE : Self = NodeIndex {address:A::TRUE,multiplicity:M::ONE};
 
+    /// A pointer to the node at address, as returned by [xdd_with_multiplicity::XDDBase::add_node], when building a diagram by hand.
+    /// Such a diagram may not be canonical; see [DecisionDiagramFactory::reduce].
+    pub fn new(address:A,multiplicity:M) -> Self { NodeIndex {address,multiplicity} }
+
     /// See if the node index is one of the two special sink nodes.
     pub fn is_sink(self) -> bool { self.is_false()||self.is_true() } // could be made more efficient by <2, but requires further restrictions on A.
     /// See if the node index is the special FALSE sink node.
@@ -66,6 +277,11 @@ impl <A:NodeAddress,M:Multiplicity> NodeIndex<A,M> {
     /// See if the node index is the special TRUE sink node.
     pub fn is_true(self) -> bool { self.address==A::TRUE }
 
+    /// The address of the node pointed to.
+    pub fn address(self) -> A { self.address }
+    /// The multiplicity (number of times represented) associated with this pointer.
+    pub fn multiplicity(self) -> M { self.multiplicity }
+
     pub fn multiply(self,m:M) -> Self { NodeIndex {address:self.address,multiplicity:M::multiply(self.multiplicity, m)}}
 }
 
@@ -102,6 +318,68 @@ impl NodeAddress for u64 {
     const ZERO: Self = 0;
     const ONE: Self = 1;
 }
+impl NodeAddress for u16 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+}
+impl NodeAddress for U48 {
+    const ZERO: Self = U48([0;6]);
+    const ONE: Self = U48([0,0,0,0,0,1]);
+}
+
+/// A 48 bit unsigned integer stored in 6 bytes with no alignment requirement, for use as a compact [NodeAddress].
+///
+/// A `Node<U48,NoMultiplicity>` takes 14 bytes, compared to 24 for u64, yet allows 2^48 nodes, more than fits in current memories.
+/// The bytes are big endian so that the derived ordering is numerical.
+#[derive(Copy,Clone,Eq,PartialEq,Ord,PartialOrd,Hash,Default)]
+pub struct U48([u8;6]);
+
+impl U48 {
+    pub const MAX : U48 = U48([255;6]);
+}
+
+impl From<U48> for u64 {
+    fn from(value: U48) -> Self {
+        let mut bytes = [0;8];
+        bytes[2..].copy_from_slice(&value.0);
+        u64::from_be_bytes(bytes)
+    }
+}
+impl TryFrom<u64> for U48 {
+    type Error = TryFromIntError;
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        if value>>48!=0 { return Err(u8::try_from(u16::MAX).unwrap_err()); } // TryFromIntError has no public constructor.
+        let mut bytes = [0;6];
+        bytes.copy_from_slice(&value.to_be_bytes()[2..]);
+        Ok(U48(bytes))
+    }
+}
+impl TryFrom<usize> for U48 {
+    type Error = TryFromIntError;
+    fn try_from(value: usize) -> Result<Self, Self::Error> { U48::try_from(u64::try_from(value)?) }
+}
+impl TryFrom<U48> for usize {
+    type Error = TryFromIntError;
+    fn try_from(value: U48) -> Result<Self, Self::Error> { usize::try_from(u64::from(value)) }
+}
+impl Display for U48 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f,"{}",u64::from(*self)) }
+}
+impl Debug for U48 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f,"{}",u64::from(*self)) }
+}
+/// Serialized as a u64.
+#[cfg(feature="serde")]
+impl serde::Serialize for U48 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> { serializer.serialize_u64(u64::from(*self)) }
+}
+#[cfg(feature="serde")]
+impl <'de> serde::Deserialize<'de> for U48 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u64::deserialize(deserializer)?;
+        U48::try_from(value).map_err(|_|serde::de::Error::custom(format!("{} does not fit in 48 bits",value)))
+    }
+}
 
 pub trait Multiplicity : Copy+Eq+Hash+Display+Debug {
     const ONE : Self;
@@ -113,17 +391,31 @@ pub trait Multiplicity : Copy+Eq+Hash+Display+Debug {
     fn combine_or(a:Self,b:Self) -> Self;
     fn multiply(a:Self,b:Self) -> Self;
     /// Given a and b, compute g=gcd(a,b) and return (a/g,b/g,g).
+    /// For signed multiplicities, g has the sign of a, so that a/g is positive.
     fn gcd(a:Self,b:Self) -> (Self,Self,Self);
     fn is_unity(self) -> bool { self==Self::ONE }
+    /// True iff this is zero, which can only come from combine_or with signed multiplicities cancelling.
+    /// Something with multiplicity zero is not present, so is replaced by FALSE.
+    fn is_zero(self) -> bool { false }
+    /// a-b if that is positive, otherwise None meaning not present. Used by [DecisionDiagramFactory::subtract].
+    /// Only defined for multiplicities that cannot be negative; the default panics.
+    fn saturating_subtract(_a:Self,_b:Self) -> Option<Self> { panic!("Saturating subtraction is not defined for this type of multiplicity") }
 }
 
 #[derive(Copy, Clone,Eq, PartialEq,Hash,Debug,Default)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 pub struct NoMultiplicity {}
 
 impl Display for NoMultiplicity {
     fn fmt(&self, _f: &mut Formatter<'_>) -> std::fmt::Result { Ok(()) }
 }
 
+/// Any multiplicity is accepted and ignored, so files written with multiplicities can be read as plain sets.
+impl FromStr for NoMultiplicity {
+    type Err = Infallible;
+    fn from_str(_s: &str) -> Result<Self, Self::Err> { Ok(NoMultiplicity{}) }
+}
+
 impl Multiplicity for NoMultiplicity {
     const ONE: Self = NoMultiplicity{};
     const MULTIPLICITIES_IRRELEVANT: bool = true;
@@ -132,6 +424,7 @@ impl Multiplicity for NoMultiplicity {
     fn multiply(_a: Self, _b: Self) -> Self { NoMultiplicity{} }
     fn gcd(_a: Self, _b: Self) -> (Self, Self, Self) { (NoMultiplicity{},NoMultiplicity{},NoMultiplicity{}) }
     fn is_unity(self) -> bool { true }
+    fn saturating_subtract(_a: Self, _b: Self) -> Option<Self> { None }
 }
 
 fn compute_gcd<T:Rem<T,Output=T>+Ord+Copy+Unsigned+Integer+Zero>(a:T,b:T) -> T {
@@ -144,18 +437,91 @@ fn compute_gcd<T:Rem<T,Output=T>+Ord+Copy+Unsigned+Integer+Zero>(a:T,b:T) -> T {
     max
 }
 
-impl Multiplicity for u32 {
-    const ONE: Self = 1;
-    const MULTIPLICITIES_IRRELEVANT: bool = false;
+/// Conversions between a multiplicity type and NoMultiplicity, used by [BDDFactory::import_from].
+macro_rules! no_multiplicity_conversions {
+    ($t:ty) => { no_multiplicity_conversions!($t,1); };
+    ($t:ty,$one:expr) => {
+        /// Drop multiplicities, so a multiset becomes the set of its elements.
+        impl From<$t> for NoMultiplicity {
+            fn from(_value: $t) -> Self { NoMultiplicity{} }
+        }
+
+        /// Each element of a set is in it once.
+        impl From<NoMultiplicity> for $t {
+            fn from(_value: NoMultiplicity) -> Self { $one }
+        }
+    }
+}
+
+/// Integer multiplicities, with sums and products that panic on overflow in debug builds. The larger types
+/// allow multisets with very large multiplicities, such as those from composing permutation diagrams. There is no arbitrary
+/// precision multiplicity as multiplicities are stored in each node, and so must be Copy.
+macro_rules! integer_multiplicity {
+    ($t:ty) => {
+        impl Multiplicity for $t {
+            const ONE: Self = 1;
+            const MULTIPLICITIES_IRRELEVANT: bool = false;
+
+            fn combine_or(a: Self, b: Self) -> Self { a+b }
+            fn multiply(a: Self, b: Self) -> Self { a*b }
+            fn gcd(a: Self, b: Self) -> (Self, Self, Self) {
+                let g = compute_gcd(a,b);
+                (a/g,b/g,g)
+            }
+            fn saturating_subtract(a: Self, b: Self) -> Option<Self> { if a>b { Some(a-b) } else { None } }
+        }
+        no_multiplicity_conversions!($t);
+    }
+}
+
+integer_multiplicity!(u32);
+integer_multiplicity!(u64);
+integer_multiplicity!(u128);
+
+/// Signed integer multiplicities, allowing negative weights and inclusion-exclusion style cancellation within a diagram.
+/// A solution whose multiplicities sum to zero is removed. $u is the unsigned type of the same size, used for the gcd.
+macro_rules! signed_integer_multiplicity {
+    ($t:ty,$u:ty) => {
+        impl Multiplicity for $t {
+            const ONE: Self = 1;
+            const MULTIPLICITIES_IRRELEVANT: bool = false;
+
+            fn combine_or(a: Self, b: Self) -> Self { a+b }
+            fn multiply(a: Self, b: Self) -> Self { a*b }
+            fn gcd(a: Self, b: Self) -> (Self, Self, Self) {
+                let g = compute_gcd::<$u>(a.unsigned_abs(),b.unsigned_abs()) as $t;
+                let g = if a<0 { -g } else { g };
+                (a/g,b/g,g)
+            }
+            fn is_zero(self) -> bool { self==0 }
+        }
+        no_multiplicity_conversions!($t);
+    }
+}
+
+signed_integer_multiplicity!(i64,u64);
+signed_integer_multiplicity!(i128,u128);
 
-    fn combine_or(a: Self, b: Self) -> Self { a+b }
-    fn multiply(a: Self, b: Self) -> Self { a*b }
-    fn gcd(a: Self, b: Self) -> (Self, Self, Self) {
-        let g = compute_gcd(a,b);
-        (a/g,b/g,g)
+/// Rational multiplicities, such as probabilities, so that number_solutions (counting with the same type) gives an exact
+/// total weight. Every nonzero rational divides every other, so the "gcd" taken out of a node is just its lo multiplicity.
+macro_rules! rational_multiplicity {
+    ($t:ty) => {
+        impl Multiplicity for Ratio<$t> {
+            const ONE: Self = Ratio::new_raw(1,1);
+            const MULTIPLICITIES_IRRELEVANT: bool = false;
+
+            fn combine_or(a: Self, b: Self) -> Self { a+b }
+            fn multiply(a: Self, b: Self) -> Self { a*b }
+            fn gcd(a: Self, b: Self) -> (Self, Self, Self) { (Self::ONE,b/a,a) }
+            fn is_zero(self) -> bool { Zero::is_zero(&self) }
+        }
+        no_multiplicity_conversions!(Ratio<$t>,Ratio::new_raw(1,1));
     }
 }
 
+rational_multiplicity!(i64);
+rational_multiplicity!(i128);
+
 
 impl <A:NodeAddress,M:Multiplicity> Display for NodeIndex<A,M> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -175,7 +541,13 @@ impl <A:NodeAddress,M:Multiplicity> Display for NodeIndex<A,M> {
 ///
 /// # Meaning
 /// If the variable is true, go to the hi node, else go to the low node.
+///
+/// # Size
+/// There is no padding when the address and multiplicity types have alignment at most 2, so
+/// `Node<u16,NoMultiplicity>` is 6 bytes and `Node<U48,NoMultiplicity>` is 14 bytes. Wider
+/// addresses are padded to their alignment, e.g. `Node<u32,NoMultiplicity>` is 12 bytes.
 #[derive(Copy, Clone,Eq, PartialEq,Hash)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 pub struct Node<A:NodeAddress,M:Multiplicity> {
     pub variable : VariableIndex,
     pub lo : NodeIndex<A,M>,
@@ -190,32 +562,770 @@ pub trait DecisionDiagramFactory<A:NodeAddress,M:Multiplicity> {
     fn and(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M>;
     /// Compute a diagram being the logical or of index1 and index2.
     fn or(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M>;
+    /// Compute a diagram being the exclusive or of index1 and index2.
+    /// This is more efficient than building it from and, or and not.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let v1 = factory.single_variable(VariableIndex(1));
+    /// let xor = factory.xor(v0,v1);
+    /// assert_eq!(xor,factory.exactly_one_of(&[VariableIndex(0),VariableIndex(1)]));
+    /// ```
+    fn xor(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M>;
+    /// Compute a diagram being op applied to index1 and index2, for any of the 16 boolean operators.
+    /// Multiplicities are ignored other than whether they are zero, and the result has unit multiplicities;
+    /// use and, or and xor if multiplicities matter.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, BooleanOperator, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let v1 = factory.single_variable(VariableIndex(1));
+    /// let implies = factory.apply(v0,v1,BooleanOperator::IMPLIES);
+    /// assert_eq!(3,factory.number_solutions::<u64>(implies));
+    /// let not_v0 = factory.not(v0);
+    /// assert_eq!(factory.or(not_v0,v1),implies);
+    /// ```
+    fn apply(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, op:BooleanOperator) -> NodeIndex<A,M>;
+    /// Compute a diagram being if f then g else h. This is (f and g) or (not f and h), computed in one pass with its own cache.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let select = factory.single_variable(VariableIndex(0));
+    /// let a = factory.single_variable(VariableIndex(1));
+    /// let b = factory.single_variable(VariableIndex(2));
+    /// let multiplexer = factory.ite(select,a,b);
+    /// let not_select = factory.not(select);
+    /// let select_a = factory.and(select,a);
+    /// let not_select_b = factory.and(not_select,b);
+    /// assert_eq!(multiplexer,factory.or(select_a,not_select_b));
+    /// ```
+    fn ite(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>) -> NodeIndex<A,M>;
+    /// Substitute the function g for the variable in f (Shannon composition).
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let v1 = factory.single_variable(VariableIndex(1));
+    /// let v2 = factory.single_variable(VariableIndex(2));
+    /// let v0_and_v1 = factory.and(v0,v1);
+    /// let v1_or_v2 = factory.or(v1,v2);
+    /// let composed = factory.compose(v0_and_v1,VariableIndex(0),v1_or_v2);
+    /// assert_eq!(v1,composed);
+    /// ```
+    fn compose(&mut self, f: NodeIndex<A,M>, variable: VariableIndex, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        self.vector_compose(f,&[(variable,g)])
+    }
+    /// Substitute functions for several variables in f at once. The substitutions are simultaneous, so a substituted
+    /// function may mention a variable that is itself being substituted; this can be used to swap variables.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let v1 = factory.single_variable(VariableIndex(1));
+    /// let not_v1 = factory.not(v1);
+    /// let f = factory.and(v0,not_v1);
+    /// let swapped = factory.vector_compose(f,&[(VariableIndex(0),v1),(VariableIndex(1),v0)]);
+    /// let not_v0 = factory.not(v0);
+    /// assert_eq!(factory.and(v1,not_v0),swapped);
+    /// ```
+    fn vector_compose(&mut self, f: NodeIndex<A,M>, substitutions:&[(VariableIndex,NodeIndex<A,M>)]) -> NodeIndex<A,M>;
+    /// Compute the cofactor of f with the variable fixed to value. The result does not depend on the variable, so
+    /// its number of solutions counts both values of the variable; it is twice the number of solutions of f with
+    /// the variable fixed.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let one_of = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(1),VariableIndex(2)]);
+    /// let v0_true = factory.restrict(one_of,VariableIndex(0),true);
+    /// assert_eq!(2,factory.number_solutions::<u64>(v0_true)); // v1 and v2 false, v0 either.
+    /// let v0_false = factory.restrict(one_of,VariableIndex(0),false);
+    /// assert_eq!(4,factory.number_solutions::<u64>(v0_false));
+    /// ```
+    fn restrict(&mut self, f: NodeIndex<A,M>, variable: VariableIndex, value: bool) -> NodeIndex<A,M>;
+    /// Compute f with each variable in literals fixed to its value, in any order, like a sequence of [DecisionDiagramFactory::restrict]
+    /// but in a single pass over f. Panics if a variable is given twice.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(5);
+    /// let two = factory.exactly_n_of(&variables,2);
+    /// let res = factory.restrict_cube(two,&[(VariableIndex(3),true),(VariableIndex(0),false)]);
+    /// assert_eq!(factory.exactly_one_of(&[VariableIndex(1),VariableIndex(2),VariableIndex(4)]),res);
+    /// ```
+    fn restrict_cube(&mut self, f: NodeIndex<A,M>, literals:&[(VariableIndex,bool)]) -> NodeIndex<A,M>;
+    /// The multiplicity of the solution of f given by assignment, where assignment[v] is the value of variable v (false past its end),
+    /// or None if it is not a solution. This only reads the diagram, so can be done from many threads at once.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..3).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,u32>::new(3);
+    /// let one = factory.exactly_one_of(&variables);
+    /// let at_most_one = factory.at_most_n_of(&variables,1);
+    /// let f = factory.or(one,at_most_one);
+    /// assert_eq!(Some(2),factory.evaluate(f,&[false,true,false]));
+    /// assert_eq!(Some(1),factory.evaluate(f,&[]));
+    /// assert_eq!(None,factory.evaluate(f,&[true,true]));
+    /// ```
+    fn evaluate(&self, f: NodeIndex<A,M>, assignment:&[bool]) -> Option<M>;
+    /// Evaluate f with only some of the variables known: assignment[v] is the value of variable v, or None if it is not known,
+    /// as are any variables past the end of assignment. If the value is then forced, it is given, otherwise f restricted
+    /// to the known values (see [DecisionDiagramFactory::restrict]). Multiplicities are ignored.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, PartialEvaluation, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let at_least_two = factory.at_least_n_of(&variables,2);
+    /// assert_eq!(Some(true),factory.evaluate_partial(at_least_two,&[Some(true),None,Some(true)]).determined());
+    /// assert_eq!(Some(false),factory.evaluate_partial(at_least_two,&[Some(false),Some(false),Some(false)]).determined());
+    /// let at_least_one = factory.at_least_n_of(&variables[1..],1);
+    /// assert_eq!(PartialEvaluation::Undetermined(at_least_one),factory.evaluate_partial(at_least_two,&[Some(true)]));
+    /// ```
+    fn evaluate_partial(&mut self, f: NodeIndex<A,M>, assignment:&[Option<bool>]) -> PartialEvaluation<A,M> {
+        let literals : Vec<(VariableIndex,bool)> = assignment.iter().enumerate().filter_map(|(variable,value)|value.map(|value|(VariableIndex(variable as u16),value))).collect();
+        let res = self.restrict_cube(f,&literals);
+        if res.is_false() { PartialEvaluation::Determined(false) }
+        else if self.not(res).is_false() { PartialEvaluation::Determined(true) }
+        else { PartialEvaluation::Undetermined(res) }
+    }
+    /// Replace each variable v in f by mapping(v), such as shifting all variables along by some amount, or spreading
+    /// them out to interleave with another set of variables. The mapping must keep the order of the variables that
+    /// are used in f (typically it is increasing); it is only called for those variables.
+    ///
+    /// This just relabels the nodes. For a ZDD, thought of as a family of sets, the elements of each set are renamed
+    /// so variables that are not mapped to are absent.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    /// let low = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(1),VariableIndex(2)]);
+    /// let high = factory.rename_variables(low,|v|VariableIndex(v.0+3));
+    /// assert_eq!(factory.exactly_one_of(&[VariableIndex(3),VariableIndex(4),VariableIndex(5)]),high);
+    /// ```
+    fn rename_variables(&mut self, f: NodeIndex<A,M>, mapping:impl Fn(VariableIndex)->VariableIndex) -> NodeIndex<A,M>;
+    /// The canonical equivalent of root, for a diagram not built by this factory's operations, such as one read from a file
+    /// written by another tool, that may contain redundant or duplicate nodes. Diagrams built by the factory are
+    /// already canonical, so for them this just returns root. See [xdd_with_multiplicity::XDDBase::reduce].
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// // variable 0, with a needless test of variable 1 whose children are both TRUE.
+    /// let text = r#"{"num_variables":2,"roots":[{"id":3}],"nodes":[{"id":2,"variable":1,"lo":1,"hi":1},{"id":3,"variable":0,"lo":0,"hi":2}]}"#;
+    /// let f = factory.read_json_file(&mut text.as_bytes()).unwrap()[0].0;
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// assert_ne!(v0,f);
+    /// assert_eq!(v0,factory.reduce(f));
+    /// assert_eq!(v0,factory.reduce(v0));
+    /// ```
+    fn reduce(&mut self, root: NodeIndex<A,M>) -> NodeIndex<A,M>;
+    /// Check that root is a diagram the operations of this factory could have made, reporting every way it is not.
+    /// See [xdd_with_multiplicity::XDDBase::validate].
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// use xdd::xdd_with_multiplicity::DiagramProblem;
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// // node 2 is redundant.
+    /// let text = r#"{"num_variables":2,"roots":[{"id":3}],"nodes":[{"id":2,"variable":1,"lo":1,"hi":1},{"id":3,"variable":0,"lo":0,"hi":2}]}"#;
+    /// let f = factory.read_json_file(&mut text.as_bytes()).unwrap()[0].0;
+    /// let problems = factory.validate(f).unwrap_err().problems;
+    /// assert!(matches!(problems[..],[DiagramProblem::Redundant{..}]));
+    /// let f = factory.reduce(f);
+    /// assert!(factory.validate(f).is_ok());
+    /// ```
+    fn validate(&self, root: NodeIndex<A,M>) -> Result<(),xdd_with_multiplicity::InvalidDiagram<A>>;
+    /// Whether every solution of f is also a solution of g, ignoring multiplicities. Unlike checking that f and not g is FALSE,
+    /// this makes no new nodes, so leaves nothing to be garbage collected.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let two = factory.exactly_n_of(&variables,2);
+    /// let at_least_one = factory.at_least_n_of(&variables,1);
+    /// let len = factory.len();
+    /// assert!(factory.implies(two,at_least_one));
+    /// assert!(!factory.implies(at_least_one,two));
+    /// assert_eq!(len,factory.len());
+    /// ```
+    fn implies(&self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> bool;
+    /// The variables that f depends on, in increasing order: those for which some assignment gives a different value (or multiplicity)
+    /// when just that variable is changed. Found in one pass over f. Not to be confused with [DecisionDiagramFactory::support],
+    /// the set of solutions of f.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let f = factory.exactly_one_of(&variables[1..3]);
+    /// assert_eq!(variables[1..3].to_vec(),factory.dependent_variables(f));
+    /// assert_eq!(variables,factory.dependent_variables(NodeIndex::TRUE)); // just the empty set, so all variables must be false.
+    /// ```
+    fn dependent_variables(&self, f: NodeIndex<A,M>) -> Vec<VariableIndex>;
+    /// Partition the variables into classes of variables that are interchangeable in f: swapping any two in the same class
+    /// leaves f (and its multiplicities) unchanged. The classes are in order of their first variable. No nodes are made.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(5);
+    /// let two_of_first_three = factory.exactly_n_of(&variables[..3],2);
+    /// let v3 = factory.single_variable(VariableIndex(3));
+    /// let f = factory.xor(two_of_first_three,v3);
+    /// assert_eq!(vec![variables[..3].to_vec(),vec![variables[3]],vec![variables[4]]],factory.symmetric_variables(f));
+    /// ```
+    fn symmetric_variables(&self, f: NodeIndex<A,M>) -> Vec<Vec<VariableIndex>>;
+    /// Whether f is positive unate (monotone increasing) in variable: whether making variable true in a solution always gives a solution.
+    /// Multiplicities are ignored. The two cofactors of f for variable are compared in one traversal, without making any nodes.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// let [v0,v1] = [0,1].map(|v|factory.single_variable(VariableIndex(v)));
+    /// let not_v1 = factory.not(v1);
+    /// let f = factory.and(v0,not_v1);
+    /// assert!(factory.is_positive_unate(f,VariableIndex(0)));
+    /// assert!(!factory.is_positive_unate(f,VariableIndex(1)));
+    /// assert!(factory.is_negative_unate(f,VariableIndex(1)));
+    /// ```
+    fn is_positive_unate(&self, f: NodeIndex<A,M>, variable: VariableIndex) -> bool;
+    /// Whether f is negative unate (monotone decreasing) in variable: whether making variable false in a solution always gives a solution.
+    /// See [DecisionDiagramFactory::is_positive_unate].
+    fn is_negative_unate(&self, f: NodeIndex<A,M>, variable: VariableIndex) -> bool;
+    /// How f depends on each variable. f is monotone increasing iff none are [Unateness::Negative] or [Unateness::Binate].
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, Unateness, VariableIndex, ZDDFactory};
+    /// let variables : Vec<VariableIndex> = (0..3).map(VariableIndex).collect();
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&variables[..2]);
+    /// assert_eq!(vec![Unateness::Binate,Unateness::Binate,Unateness::Independent],factory.unateness(f));
+    /// ```
+    fn unateness(&self, f: NodeIndex<A,M>) -> Vec<Unateness> {
+        (0..self.num_variables()).map(VariableIndex).map(|v|match (self.is_positive_unate(f,v),self.is_negative_unate(f,v)) {
+            (true,true) => Unateness::Independent,
+            (true,false) => Unateness::Positive,
+            (false,true) => Unateness::Negative,
+            (false,false) => Unateness::Binate,
+        }).collect()
+    }
+    /// Whether f and g have the same solutions, ignoring multiplicities. Without multiplicities this is just f==g, as diagrams are canonical;
+    /// with them it checks [DecisionDiagramFactory::implies] both ways, making no new nodes. To compare diagrams in different factories,
+    /// see [BDDFactory::equivalent].
+    fn is_equivalent(&self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> bool {
+        f==g || (!M::MULTIPLICITIES_IRRELEVANT && self.implies(f,g) && self.implies(g,f))
+    }
+    /// Compute "there exist values of the quantified variables such that f and g", in one pass without making the
+    /// (often large) "and" of f and g. This is the image computation used in reachability analysis, where f is a
+    /// set of states and g is a transition relation; see [relations::StatePairing].
+    /// The quantified variables must be sorted, smallest to highest.
+    ///
+    /// With multiplicities, "and" multiplies and "or" adds, so the result's multiplicities are summed over the
+    /// values of the quantified variables.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let v1 = factory.single_variable(VariableIndex(1));
+    /// let v2 = factory.single_variable(VariableIndex(2));
+    /// let f = factory.xor(v0,v1);
+    /// let g = factory.and(v1,v2);
+    /// let res = factory.relational_product(f,g,&[VariableIndex(1)]);
+    /// let not_v0 = factory.not(v0);
+    /// assert_eq!(factory.and(not_v0,v2),res);
+    /// ```
+    fn relational_product(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, quantified:&[VariableIndex]) -> NodeIndex<A,M>;
+    /// Compute "there exist values of the quantified variables such that f". The quantified variables must be sorted.
+    /// See [DecisionDiagramFactory::relational_product].
+    fn exists(&mut self, f: NodeIndex<A,M>, quantified:&[VariableIndex]) -> NodeIndex<A,M> {
+        let always = self.not(NodeIndex::FALSE);
+        self.relational_product(f,always,quantified)
+    }
+    /// The projection of f onto variables: an assignment to variables is a solution iff some values of the other variables extend
+    /// it to a solution of f. Multiplicities are dropped. The result has no nodes for the other variables; a BDD
+    /// then allows any value of them, while for a ZDD, as a family of sets, they are absent from every set.
+    /// Use [DecisionDiagramFactory::number_projected_solutions] to count just the assignments to variables.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let f = factory.exactly_n_of(&variables,2);
+    /// let projected = factory.project(f,&variables[..2]);
+    /// let sets : Vec<Vec<VariableIndex>> = factory.solutions(projected).collect();
+    /// assert_eq!(vec![vec![],vec![variables[1]],vec![variables[0]],vec![variables[0],variables[1]]],sets);
+    /// ```
+    fn project(&mut self, f: NodeIndex<A,M>, variables:&[VariableIndex]) -> NodeIndex<A,M>;
+    /// The number of assignments to just variables that extend to a solution of f, that is, the number of solutions of
+    /// [DecisionDiagramFactory::project] not counting the other variables.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    /// let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    /// let f = factory.exactly_n_of(&variables,2);
+    /// assert_eq!(15,factory.number_solutions::<u64>(f));
+    /// assert_eq!(7,factory.number_projected_solutions::<u64>(f,&variables[..3])); // at most 2 of the first 3.
+    /// ```
+    fn number_projected_solutions<G:GeneratingFunctionWithMultiplicity<M>>(&mut self, f: NodeIndex<A,M>, variables:&[VariableIndex]) -> G;
+    /// Multiply the multiplicity of every solution of f by m. This is constant time, as it just changes the multiplicity of the root.
+    /// Gives FALSE if m is zero.
+    fn scale(&mut self, f: NodeIndex<A,M>, m:M) -> NodeIndex<A,M> {
+        if f.is_false() || m.is_zero() { NodeIndex::FALSE } else { f.multiply(m) }
+    }
+    /// Compute the sum of m_i times f_i for each (f_i,m_i) in terms, like a sequence of [DecisionDiagramFactory::or] and
+    /// [DecisionDiagramFactory::scale] but in a single pass without building the intermediate diagrams.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,i64>::new(3);
+    /// let [v0,v1,v2] = [0,1,2].map(|v|factory.single_variable(VariableIndex(v)));
+    /// let f = factory.linear_combination(&[(v0,1),(v1,2),(v2,4)]); // the binary number v2v1v0.
+    /// assert_eq!((0..8).sum::<i64>(),factory.number_solutions::<i64>(f));
+    /// let g = factory.linear_combination(&[(f,1),(v1,-2)]);
+    /// assert_eq!(factory.linear_combination(&[(v0,1),(v2,4)]),g);
+    /// ```
+    fn linear_combination(&mut self, terms:&[(NodeIndex<A,M>,M)]) -> NodeIndex<A,M>;
+    /// Compute the multiset difference of f and g: the multiplicity of a solution is its multiplicity in f minus its
+    /// multiplicity in g, or absent if that is not positive. Without multiplicities this is f and not g.
+    /// Panics for multiplicities that may be negative, such as i64; use [DecisionDiagramFactory::linear_combination] instead.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,u32>::new(3);
+    /// let variables = [VariableIndex(0),VariableIndex(1),VariableIndex(2)];
+    /// let one = factory.exactly_one_of(&variables);
+    /// let two = factory.exactly_n_of(&variables,2);
+    /// let at_most_two = factory.or(one,two);
+    /// let f = factory.or(at_most_two,one); // each set of size 1 twice, and of size 2 once.
+    /// let g = factory.subtract(f,two);
+    /// assert_eq!(factory.or(one,one),g);
+    /// let h = factory.subtract(g,one);
+    /// assert_eq!(one,h);
+    /// ```
+    fn subtract(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M>;
+    /// The underlying set of f: the same solutions, each with multiplicity 1. To drop the multiplicities
+    /// from the type as well, import into a factory without them, such as with [BDDFactory::import_from].
+    fn support(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M>;
+    /// Keep just the solutions of f whose multiplicity satisfies predicate, with their multiplicities unchanged.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,u32>::new(3);
+    /// let variables = [VariableIndex(0),VariableIndex(1),VariableIndex(2)];
+    /// let one = factory.exactly_one_of(&variables);
+    /// let at_most_one = factory.at_most_n_of(&variables,1);
+    /// let f = factory.or(one,at_most_one); // each set of size 1 twice, and the empty set once.
+    /// let repeated = factory.filter_multiplicity(f,|m|m>=2);
+    /// assert_eq!(factory.or(one,one),repeated);
+    /// assert_eq!(factory.support(repeated),one);
+    /// assert_eq!(factory.support(f),at_most_one);
+    /// ```
+    fn filter_multiplicity(&mut self, f: NodeIndex<A,M>, predicate:impl Fn(M)->bool) -> NodeIndex<A,M>;
     /// Compute a diagram being the logical not of index1 and index2.
     fn not(&mut self, index: NodeIndex<A,M>) -> NodeIndex<A,M>;
+    /// Compute the dual of f, f<sup>d</sup>(x) = ¬f(¬x). For a ZDD, as a family of sets, this is the sets whose complements are not in f.
+    /// f is self dual iff it equals its dual, and a monotone f has as dual the function whose minimal solutions are the minimal
+    /// transversals of those of f. Multiplicities are dropped, as for [DecisionDiagramFactory::not].
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..3).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let and = factory.exactly_n_of(&variables,3);
+    /// let or = factory.at_least_n_of(&variables,1);
+    /// assert_eq!(or,factory.dual(and));
+    /// let majority = factory.at_least_n_of(&variables,2);
+    /// assert_eq!(majority,factory.dual(majority)); // self dual.
+    /// ```
+    fn dual(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M>;
     /// Enumerate the solutions to the given generating function.
+    ///
+    /// A factory with no variables is allowed; NodeIndex::TRUE then has exactly 1 solution (the empty assignment).
     fn number_solutions<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>) -> G;
+    /// The same as [Self::number_solutions] for each of roots, but in a single pass over the nodes reachable from any of them,
+    /// so shared subdiagrams are only worked on once.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let variables : Vec<VariableIndex> = (0..10).map(VariableIndex).collect();
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(10);
+    /// let roots : Vec<_> = (0..=10).map(|k|factory.exactly_n_of(&variables,k)).collect();
+    /// let counts = factory.number_solutions_many::<u64>(&roots);
+    /// assert_eq!(vec![1,10,45,120,210,252,210,120,45,10,1],counts);
+    /// ```
+    fn number_solutions_many<G:GeneratingFunctionWithMultiplicity<M>>(&self, roots:&[NodeIndex<A,M>]) -> Vec<G>;
+    /// The number of solutions of index in which each of the given variables has the given value, as a generating function like [Self::number_solutions].
+    /// This is the number of solutions of `index` and the literals, but found in one pass over index without making any new nodes, so
+    /// is cheap to do for many different conditions, such as when working out conditional probabilities.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(5);
+    /// let two = factory.exactly_n_of(&variables,2);
+    /// assert_eq!(4,factory.number_solutions_given::<u64>(two,&[(VariableIndex(1),true)]));
+    /// assert_eq!(3,factory.number_solutions_given::<u64>(two,&[(VariableIndex(1),true),(VariableIndex(4),false)]));
+    /// // so the probability that variable 4 is false given that variable 1 is true is 3/4.
+    /// ```
+    fn number_solutions_given<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, literals:&[(VariableIndex,bool)]) -> G;
+    /// For each variable, the number of solutions of index in which it is true, weighted by multiplicity. Dividing by
+    /// [Self::number_solutions] gives the probability of each variable being true in a solution chosen at random.
+    /// This is the same as [Self::number_solutions_given] for each variable being true, but all found in time proportional to the size of the diagram.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let two = factory.exactly_n_of(&variables[..3],2); // variable 3 is free.
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let f = factory.or(two,v0);
+    /// assert_eq!(vec![8,6,6,5],factory.variable_marginals::<u64>(f));
+    /// ```
+    fn variable_marginals<G:GeneratingFunctionWithMultiplicity<M>+std::ops::Mul<Output=G>+std::ops::Sub<Output=G>>(&self, index: NodeIndex<A,M>) -> Vec<G>;
+    /// Weighted model counting: the sum over solutions of index of the product of the weights of the variables that are true
+    /// in it, times its multiplicity. weights has one entry per variable. With all weights one this is [Self::number_solutions].
+    /// Counting the permutation matrices using cell variables with weights the entries of a matrix gives its permanent;
+    /// see [permutation_matrices::PermutationMatrices::permanent].
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]); // variable 1 is free.
+    /// assert_eq!(2*(1+3)+5*(1+3),factory.weighted_count::<i64>(f,&[2,3,5]));
+    /// assert_eq!(4,factory.weighted_count::<i64>(f,&[1,1,1]));
+    /// ```
+    fn weighted_count<G:GeneratingFunctionWithMultiplicity<M>+std::ops::Mul<Output=G>>(&self, index: NodeIndex<A,M>, weights:&[G]) -> G;
+    /// For each variable, the entropy in bits of its value in a solution of index chosen at random (weighted by multiplicity).
+    /// This is 0 for a variable with the same value in every solution, and 1 for one true in exactly half the solutions.
+    /// All 0 if there are no solutions. Found from [Self::variable_marginals] with approximate counts, so a single pass.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// assert_eq!(vec![1.0,1.0,1.0],factory.variable_entropies(f));
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let f = factory.and(f,v0);
+    /// assert_eq!(vec![0.0,1.0,0.0],factory.variable_entropies(f));
+    /// ```
+    fn variable_entropies(&self, index:NodeIndex<A,M>) -> Vec<f64> where f64:GeneratingFunctionWithMultiplicity<M> {
+        let total : f64 = self.number_solutions(index);
+        self.variable_marginals::<f64>(index).into_iter().map(|count|{
+            if total<=0.0 { return 0.0; }
+            let p = (count/total).clamp(0.0,1.0);
+            [p,1.0-p].iter().filter(|&&q|q>0.0).map(|q|-q*q.log2()).sum()
+        }).collect()
+    }
+    /// The correlation (Pearson's, treating each variable as 0 or 1) between each pair of variables in a solution of index chosen at random,
+    /// weighted by multiplicity. Entry \[i\]\[j\] is between -1 and 1, and is 0 if either variable has the same value in every solution.
+    /// This takes one pass over index, and one [Self::and] and pass for each variable that is not constant.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// let correlations = factory.variable_correlations(f);
+    /// assert_eq!(vec![1.0,0.0,-1.0],correlations[0]);
+    /// assert_eq!(vec![0.0,1.0,0.0],correlations[1]);
+    /// ```
+    fn variable_correlations(&mut self, index:NodeIndex<A,M>) -> Vec<Vec<f64>> where f64:GeneratingFunctionWithMultiplicity<M> {
+        let n = self.num_variables() as usize;
+        let total : f64 = self.number_solutions(index);
+        let mut res = vec![vec![0.0;n];n];
+        if total<=0.0 { return res; }
+        let p : Vec<f64> = self.variable_marginals::<f64>(index).into_iter().map(|count|count/total).collect();
+        let spread : Vec<f64> = p.iter().map(|&p|(p*(1.0-p)).max(0.0).sqrt()).collect();
+        for i in 0..n {
+            if spread[i]==0.0 { continue; }
+            let variable = self.single_variable(VariableIndex(i as u16));
+            let with_i = self.and(index,variable);
+            let both = self.variable_marginals::<f64>(with_i);
+            for j in 0..n {
+                if spread[j]==0.0 { continue; }
+                res[i][j] = ((both[j]/total-p[i]*p[j])/(spread[i]*spread[j])).clamp(-1.0,1.0);
+            }
+        }
+        res
+    }
+    /// Find the number of solutions with exactly k variables true, weighted by multiplicity.
+    /// This is coefficient k of [Self::number_solutions] with a [generating_function::SingleVariableGeneratingFunction], but uses memory
+    /// proportional to k rather than the number of variables for each node being worked on, which matters for diagrams with many variables.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(10000);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let v9999 = factory.single_variable(VariableIndex(9999));
+    /// let f = factory.or(v0,v9999);
+    /// assert_eq!(2,factory.count_solutions_with_k_true::<u128>(f,1)); // {0} and {9999}.
+    /// assert_eq!(2*9998+1,factory.count_solutions_with_k_true::<u128>(f,2));
+    /// ```
+    fn count_solutions_with_k_true<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, k:usize) -> G;
+    /// Like [Self::number_solutions], but remembering the generating function of each node visited in cache, and
+    /// using the ones already there, so counting many diagrams that share subdiagrams only processes each node once.
+    /// See [generating_function::CountingCache] for keeping the cache valid through gc.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// use xdd::generating_function::CountingCache;
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(20);
+    /// let variables : Vec<VariableIndex> = (0..20).map(VariableIndex).collect();
+    /// let mut cache = CountingCache::default();
+    /// for k in 0..=20 {
+    ///     let f = factory.at_most_n_of(&variables,k);
+    ///     assert_eq!(factory.number_solutions::<u64>(f),factory.number_solutions_cached::<u64>(f,&mut cache));
+    /// }
+    /// ```
+    fn number_solutions_cached<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, cache:&mut generating_function::CountingCache<A,G>) -> G;
+    /// Count the solutions like [DecisionDiagramFactory::number_solutions], but with an error rather than a wrong answer if
+    /// the count does not fit in T. See [generating_function::CheckedCount].
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,u64>::new(40);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let f = factory.scale(v0,1<<30); // 2^39 solutions, each with multiplicity 2^30.
+    /// assert_eq!(Ok(1<<39),factory.checked_number_solutions::<u64>(v0));
+    /// assert!(factory.checked_number_solutions::<u64>(f).is_err());
+    /// assert_eq!(Ok(1<<69),factory.checked_number_solutions::<u128>(f));
+    /// ```
+    fn checked_number_solutions<T>(&self, index: NodeIndex<A,M>) -> Result<T,generating_function::CountOverflow> where generating_function::CheckedCount<T>:GeneratingFunctionWithMultiplicity<M> {
+        self.number_solutions::<generating_function::CheckedCount<T>>(index).into_result()
+    }
     /// Produce a DD that describes a single variable. That is, a DD that has all variables having no effect other than just that variable leading to TRUE iff variable is true.
+    /// Panics if the variable is not less than the number of variables given to new().
     fn single_variable(&mut self,variable:VariableIndex) -> NodeIndex<A,M>;
+    /// The number of variables given to new().
+    fn num_variables(&self) -> u16;
     /// Get the number of nodes in the DD.
     fn len(&self) -> usize;
+    /// True iff there are no nodes in the DD other than the two sinks.
+    fn is_empty(&self) -> bool { self.len()==0 }
     /// Do garbage collection. Provide the items one wants to keep, and get rid of anything not in the transitive dependencies of keep.
     /// Returns a vector v such that v[old_node.0] is what v maps in to. If nothing, then map into NodeIndex::JUNK.
+    /// Cached results of operations are kept if they only refer to kept nodes, so work done before a gc is not repeated.
+    /// The diagrams of live [roots::Handle]s are always kept, and the handles updated; see [DecisionDiagramFactory::gc_roots].
     fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A>;
+    /// Exchange variable and the one after it, the basic step in changing the variable order.
+    ///
+    /// Each diagram in keep is changed to one with the two variables swapped, and the names in the
+    /// [DecisionDiagramFactory::variable_registry] are swapped to match. So if variable 3 was "x" and variable 4 was "y",
+    /// afterwards variable 3 is "y", variable 4 is "x", and the diagrams are the same functions of x and y as before.
+    /// Only nodes for the two variables and those above them change, though the changed nodes are added to the end of
+    /// the factory, so like [DecisionDiagramFactory::gc] everything not in keep is discarded and the returned renaming
+    /// gives the new diagrams for the nodes in keep (but not for their descendants).
+    /// The diagrams of live [roots::Handle]s are swapped too, and the handles updated.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// factory.variable_registry_mut().set_names(&["x","y","z"]);
+    /// let x = factory.single_variable(VariableIndex(0));
+    /// let z = factory.single_variable(VariableIndex(2));
+    /// let f = factory.and(x,z);
+    /// let renaming = factory.swap_adjacent_levels(VariableIndex(0),[f]);
+    /// let f = renaming.rename(f).unwrap();
+    /// assert_eq!(VariableIndex(1),factory.variable_registry().variable("x").unwrap());
+    /// let solutions : Vec<String> = factory.solutions(f).map(|s|factory.format_solution(&s)).collect();
+    /// assert_eq!(vec!["{x, z}","{y, x, z}"],solutions);
+    /// ```
+    fn swap_adjacent_levels(&mut self, variable:VariableIndex, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A>;
     /// Produce a DD which is true iff exactly 1 of the given variables is true, regardless of other variables.
-    /// The variables array must be sorted, smallest to highest.
+    /// The variables array must be sorted, smallest to highest. If it is empty the result is NodeIndex::FALSE.
     fn exactly_one_of(&mut self,variables:&[VariableIndex]) -> NodeIndex<A,M>;
-    /// Do an "and" of lots of functions.
+    /// Produce a DD which is true iff between at_least and at_most (inclusive) of the given variables are true, regardless of other variables.
+    /// The variables array must be sorted, smallest to highest. The size is linear in at_most times the number of variables.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(5);
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let two_or_three = factory.between_n_and_m_of(&variables,2,3);
+    /// assert_eq!(2*(6+4),factory.number_solutions::<u64>(two_or_three)); // variable 4 does not matter.
+    /// ```
+    fn between_n_and_m_of(&mut self,variables:&[VariableIndex],at_least:usize,at_most:usize) -> NodeIndex<A,M>;
+    /// Produce a DD which is true iff at most n of the given variables are true. See [DecisionDiagramFactory::between_n_and_m_of].
+    fn at_most_n_of(&mut self,variables:&[VariableIndex],n:usize) -> NodeIndex<A,M> { self.between_n_and_m_of(variables,0,n) }
+    /// Produce a DD which is true iff at least n of the given variables are true. See [DecisionDiagramFactory::between_n_and_m_of].
+    fn at_least_n_of(&mut self,variables:&[VariableIndex],n:usize) -> NodeIndex<A,M> { self.between_n_and_m_of(variables,n,variables.len()) }
+    /// Produce a DD which is true iff exactly n of the given variables are true, regardless of other variables.
+    /// The variables array must be sorted, smallest to highest.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(5);
+    /// let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    /// let two = factory.exactly_n_of(&variables,2);
+    /// assert_eq!(10,factory.number_solutions::<u64>(two));
+    /// ```
+    fn exactly_n_of(&mut self,variables:&[VariableIndex],n:usize) -> NodeIndex<A,M>;
+    /// Produce a DD which is true iff the sum of coefficient times variable (as 0 or 1) over the terms compares to bound
+    /// as given, regardless of other variables. This is a pseudo-boolean or knapsack constraint.
+    /// The terms must be sorted by variable, smallest to highest. Coefficients may be negative or zero.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, Comparison, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let weights = [(VariableIndex(0),5),(VariableIndex(1),4),(VariableIndex(2),3),(VariableIndex(3),-2)];
+    /// let fits = factory.linear_constraint(&weights,Comparison::LessOrEqual,5);
+    /// // {}, {0}, {1}, {2}, {3}, {0,3}, {1,3}, {2,3} and {1,2,3}.
+    /// assert_eq!(9,factory.number_solutions::<u64>(fits));
+    /// ```
+    fn linear_constraint(&mut self,terms:&[(VariableIndex,i64)],comparison:Comparison,bound:i64) -> NodeIndex<A,M>;
+    /// Produce a DD which is true iff the options that are true form an exact cover, regardless of other variables.
+    /// Variable i is true iff options\[i\], a list of items, is chosen. Each primary item (those less than num_primary_items) must
+    /// be in exactly one chosen option, and each other (secondary) item in at most one. This is the problem solved by Knuth's
+    /// Algorithm X; see also [exact_cover::ExactCoverProblem], which names items and options.
+    ///
+    /// This is much faster than combining an [DecisionDiagramFactory::exactly_one_of] constraint for each item,
+    /// particularly when options containing the same items are close together in the order.
+    /// An option containing an item twice would cover it twice, so can never be chosen; this is a way of forbidding an option.
+    /// Panics if there are more options than variables.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// // cover items 0,1,2 using options {0,1}, {2}, {1,2} and {0}.
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let covers = factory.exact_cover(3,&[vec![0,1],vec![2],vec![1,2],vec![0]]);
+    /// let solutions : Vec<Vec<VariableIndex>> = factory.solutions(covers).collect();
+    /// assert_eq!(vec![vec![VariableIndex(2),VariableIndex(3)],vec![VariableIndex(0),VariableIndex(1)]],solutions);
+    /// ```
+    fn exact_cover(&mut self,num_primary_items:usize,options:&[Vec<usize>]) -> NodeIndex<A,M>;
+    /// Do an "and" of lots of functions. Returns None if indices is empty; the caller will usually want NodeIndex::TRUE in that case.
+    ///
+    /// The two diagrams with the fewest nodes are repeatedly combined (like Huffman coding), which is usually much faster
+    /// than combining them in the order given. See [DecisionDiagramFactory::poly_and_with_progress].
     fn poly_and(&mut self, indices:&[NodeIndex<A,M>]) -> Option<NodeIndex<A,M>> {
-        let mut res : Option<NodeIndex<A,M>> = None;
-        for n in indices {
-            if let Some(ni) = res {
-                res=Some(self.and(*n,ni));
-            } else {
-                res=Some(*n);
-            }
+        self.poly_and_with_progress(indices,|_,_|{})
+    }
+    /// Like poly_and, but call progress(size,remaining) after each combination, where size is the number of nodes in the
+    /// diagram just made, and remaining is the number of diagrams left to combine.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    /// let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    /// let pairs : Vec<_> = (0..5).map(|i|factory.at_most_n_of(&variables[i..i+2],1)).collect();
+    /// let mut sizes = vec![];
+    /// let no_adjacent = factory.poly_and_with_progress(&pairs,|size,_remaining|sizes.push(size)).unwrap();
+    /// assert_eq!(21,factory.number_solutions::<u64>(no_adjacent));
+    /// assert_eq!(4,sizes.len());
+    /// ```
+    fn poly_and_with_progress<P:FnMut(usize,usize)>(&mut self, indices:&[NodeIndex<A,M>], mut progress:P) -> Option<NodeIndex<A,M>> {
+        if indices.iter().any(|i|i.is_false()) { return Some(NodeIndex::FALSE); }
+        let mut pending : Vec<NodeIndex<A,M>> = indices.to_vec();
+        // (size,position in pending), smallest first. The position breaks ties so the order is deterministic.
+        let mut queue : BinaryHeap<Reverse<(usize,usize)>> = pending.iter().enumerate().map(|(i,&index)|Reverse((self.post_order(index).count(),i))).collect();
+        while let Some(Reverse((_,first))) = queue.pop() {
+            let Some(Reverse((_,second))) = queue.pop() else { return Some(pending[first]) };
+            let res = self.and(pending[first],pending[second]);
+            if res.is_false() { return Some(res); }
+            let size = self.post_order(res).count();
+            progress(size,queue.len()+1);
+            queue.push(Reverse((size,pending.len())));
+            pending.push(res);
         }
-        res
+        None
+    }
+    /// Run operation, which may do any number of operations on this factory, returning [XDDError::CapacityExceeded]
+    /// rather than panicking if the factory runs out of node addresses part way through, or [XDDError::Cancelled]
+    /// if the token given to [DecisionDiagramFactory::set_cancellation_token] is cancelled.
+    ///
+    /// After an error the factory is still usable; diagrams made before are unaffected, but nodes made by the failed
+    /// operation are left behind until the next [DecisionDiagramFactory::gc]. A long-running job can then save its
+    /// progress, or gc and continue. Operations are not interrupted by running out, so a huge operation still runs to
+    /// completion (without making new nodes) before the error is returned; cancellation stops them promptly.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex, XDDError};
+    /// let mut factory = BDDFactory::<u16,NoMultiplicity>::new(1000); // at most 65534 nodes.
+    /// let variables : Vec<VariableIndex> = (0..1000).map(VariableIndex).collect();
+    /// let small = factory.try_operation(|f|f.exactly_n_of(&variables[..100],50)).unwrap();
+    /// assert_eq!(Err(XDDError::CapacityExceeded),factory.try_operation(|f|f.exactly_n_of(&variables,500)));
+    /// assert_eq!(small,factory.exactly_n_of(&variables[..100],50)); // still usable.
+    /// ```
+    fn try_operation<R>(&mut self, operation:impl FnOnce(&mut Self)->R) -> Result<R,XDDError>;
+    /// Like [DecisionDiagramFactory::and], but with an error rather than a panic if there are too many nodes.
+    /// See [DecisionDiagramFactory::try_operation].
+    fn try_and(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> Result<NodeIndex<A,M>,XDDError> {
+        self.try_operation(|f|f.and(index1,index2))
+    }
+    /// Like [DecisionDiagramFactory::or], but with an error rather than a panic if there are too many nodes.
+    fn try_or(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> Result<NodeIndex<A,M>,XDDError> {
+        self.try_operation(|f|f.or(index1,index2))
+    }
+    /// Like [DecisionDiagramFactory::xor], but with an error rather than a panic if there are too many nodes.
+    fn try_xor(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> Result<NodeIndex<A,M>,XDDError> {
+        self.try_operation(|f|f.xor(index1,index2))
+    }
+    /// Like [DecisionDiagramFactory::not], but with an error rather than a panic if there are too many nodes.
+    fn try_not(&mut self, index: NodeIndex<A,M>) -> Result<NodeIndex<A,M>,XDDError> {
+        self.try_operation(|f|f.not(index))
+    }
+    /// Like [DecisionDiagramFactory::apply], but with an error rather than a panic if there are too many nodes.
+    fn try_apply(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, op:BooleanOperator) -> Result<NodeIndex<A,M>,XDDError> {
+        self.try_operation(|f|f.apply(index1,index2,op))
+    }
+    /// Like [DecisionDiagramFactory::ite], but with an error rather than a panic if there are too many nodes.
+    fn try_ite(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>) -> Result<NodeIndex<A,M>,XDDError> {
+        self.try_operation(|factory|factory.ite(f,g,h))
+    }
+    /// Like [DecisionDiagramFactory::poly_and], but with an error rather than a panic if there are too many nodes.
+    fn try_poly_and(&mut self, indices:&[NodeIndex<A,M>]) -> Result<Option<NodeIndex<A,M>>,XDDError> {
+        self.try_operation(|f|f.poly_and(indices))
     }
+    /// Call callback with the [statistics::Progress] so far every interval nodes made, replacing any previous callback.
+    /// Cache hits and misses, and nodes made, are counted from now. Nothing is called while an operation only finds
+    /// nodes that already exist.
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(40);
+    /// let reports = Arc::new(Mutex::new(vec![]));
+    /// let reports_in_callback = reports.clone();
+    /// factory.set_progress_callback(100,move |progress|reports_in_callback.lock().unwrap().push(*progress));
+    /// let variables : Vec<VariableIndex> = (0..40).map(VariableIndex).collect();
+    /// let f = factory.exactly_n_of(&variables,20);
+    /// let g = factory.at_most_n_of(&variables[10..],12);
+    /// factory.xor(f,g);
+    /// factory.clear_progress_callback();
+    /// let reports = reports.lock().unwrap();
+    /// assert_eq!(factory.len() as u64/100,reports.len() as u64);
+    /// assert_eq!(100,reports[0].nodes_created);
+    /// assert!(reports.last().unwrap().cache_hit_rate()>0.0);
+    /// ```
+    fn set_progress_callback(&mut self, interval:u64, callback:impl FnMut(&statistics::Progress)+Send+'static);
+    /// Stop calling the callback given to [DecisionDiagramFactory::set_progress_callback].
+    fn clear_progress_callback(&mut self);
+    /// Make operations run by [DecisionDiagramFactory::try_operation] (such as [DecisionDiagramFactory::try_and]) stop with
+    /// [XDDError::Cancelled] once token is cancelled, or None to not be cancellable. Other operations ignore the token.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex, XDDError};
+    /// use xdd::statistics::CancellationToken;
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(200);
+    /// let token = CancellationToken::new();
+    /// factory.set_cancellation_token(Some(token.clone()));
+    /// let stop = token.clone();
+    /// factory.set_progress_callback(1000,move |progress|if progress.nodes>5000 { stop.cancel() });
+    /// let variables : Vec<VariableIndex> = (0..200).map(VariableIndex).collect();
+    /// assert_eq!(Err(XDDError::Cancelled),factory.try_operation(|f|f.exactly_n_of(&variables,100))); // would need 10201 nodes.
+    /// assert_eq!(6000,factory.len());
+    /// ```
+    fn set_cancellation_token(&mut self, token:Option<statistics::CancellationToken>);
     /// write a graph file to the given writer with a given name showing the DD starting from start_nodes.
     /// Requires
     /// * a writer for where to store the result
@@ -223,17 +1333,370 @@ pub trait DecisionDiagramFactory<A:NodeAddress,M:Multiplicity> {
     /// * a slice of nodes and optional associated names for the start points of interest for the diagram. Often there is just one of these, but often more are useful.
     /// * a namer function from a VariableIndex to a String.
     fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()>;
+    /// Like make_dot_file, but with options to limit the number of nodes drawn, collapse chains of variables that do not matter,
+    /// and put nodes with the same variable at the same height.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// use xdd::xdd_with_multiplicity::DotOptions;
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(100);
+    /// let v = factory.single_variable(VariableIndex(50));
+    /// let options = DotOptions{ max_nodes: Some(10), collapse_dont_care_chains: true, rank_by_variable: true };
+    /// let mut dot = vec![];
+    /// factory.make_dot_file_with_options(&mut dot,"v50",&[(v,None)],|v|v.to_string(),&options).unwrap();
+    /// assert!(String::from_utf8(dot).unwrap().contains("\"0 … 49\""));
+    /// ```
+    fn make_dot_file_with_options<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F, options:&DotOptions) -> std::io::Result<()>;
+    /// Like make_dot_file, but write JSON in the format described in [crate::json], for web based visualizers or scripts in other languages.
+    /// It can be read back with `read_json_file`.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// let mut json = vec![];
+    /// factory.make_json_file(&mut json,&[(f,Some("f".to_string()))],|v|format!("x{}",v)).unwrap();
+    /// let mut other = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let roots = other.read_json_file(&mut json.as_slice()).unwrap();
+    /// assert_eq!(Some("f".to_string()),roots[0].1);
+    /// assert_eq!(4,other.number_solutions::<u64>(roots[0].0));
+    /// ```
+    fn make_json_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()>;
+    /// The names of variables, used by [DecisionDiagramFactory::make_dot_file_default_names],
+    /// [DecisionDiagramFactory::make_json_file_default_names] and [DecisionDiagramFactory::format_solution]. Initially empty.
+    fn variable_registry(&self) -> &VariableRegistry;
+    /// Change the names of variables. See [crate::variable_registry].
+    fn variable_registry_mut(&mut self) -> &mut VariableRegistry;
+    /// The diagrams that every gc keeps. See [crate::roots].
+    fn root_registry(&self) -> &RootRegistry<A,M>;
+    /// Change the diagrams that every gc keeps. Usually [DecisionDiagramFactory::root] is all that is needed.
+    fn root_registry_mut(&mut self) -> &mut RootRegistry<A,M>;
+    /// Keep index through every gc, updating the returned handle to its new address, until the handle and all its clones are dropped.
+    fn root(&mut self, index:NodeIndex<A,M>) -> Handle<A,M> { self.root_registry_mut().register(index) }
+    /// The diagrams of the live handles returned by [DecisionDiagramFactory::root].
+    fn roots(&self) -> Vec<NodeIndex<A,M>> { self.root_registry().indices().collect() }
+    /// Do garbage collection keeping just the diagrams of live handles returned by [DecisionDiagramFactory::root].
+    /// Unlike [DecisionDiagramFactory::gc] there is no list of diagrams to keep to get wrong.
+    fn gc_roots(&mut self) -> NodeRenaming<A> { self.gc(std::iter::empty()) }
+    /// Like make_dot_file, but variables are named by [DecisionDiagramFactory::variable_registry].
+    fn make_dot_file_default_names<W:Write>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)]) -> std::io::Result<()> {
+        self.make_dot_file(writer,name,start_nodes,|v|self.variable_registry().name(v))
+    }
+    /// Like make_json_file, but variables are named by [DecisionDiagramFactory::variable_registry].
+    fn make_json_file_default_names<W:Write>(&self, writer:&mut W, start_nodes:&[(NodeIndex<A,M>, Option<String>)]) -> std::io::Result<()> {
+        self.make_json_file(writer,start_nodes,|v|self.variable_registry().name(v))
+    }
+    /// Show a solution, such as one from [DecisionDiagramFactory::solutions], as the set of variables that are true,
+    /// named by [DecisionDiagramFactory::variable_registry]. For example `{a, c}`.
+    fn format_solution(&self, solution:&[VariableIndex]) -> String {
+        let names : Vec<String> = solution.iter().map(|&v|self.variable_registry().name(v)).collect();
+        format!("{{{}}}",names.join(", "))
+    }
+    /// Write every solution of index to writer, in the order of [DecisionDiagramFactory::solutions], naming variables with namer.
+    /// This is for passing solutions to other programs, so writes one line per solution as it goes.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, SolutionTextFormat, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// factory.variable_registry_mut().set_names(&["rain","sprinkler","wet"]);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// let mut csv = vec![];
+    /// factory.write_all_solutions(f,&mut csv,SolutionTextFormat::Csv,|v|factory.variable_registry().name(v)).unwrap();
+    /// assert_eq!("rain,sprinkler,wet\n0,0,1\n0,1,1\n1,0,0\n1,1,0\n",String::from_utf8(csv).unwrap());
+    /// let mut json = vec![];
+    /// factory.write_all_solutions(f,&mut json,SolutionTextFormat::JsonLines,|v|format!("x{}",v)).unwrap();
+    /// assert_eq!("[\"x2\"]\n[\"x1\",\"x2\"]\n[\"x0\"]\n[\"x0\",\"x1\"]\n",String::from_utf8(json).unwrap());
+    /// ```
+    fn write_all_solutions<W:Write,F:Fn(VariableIndex)->String>(&self, index:NodeIndex<A,M>, writer:&mut W, format:SolutionTextFormat, namer:F) -> std::io::Result<()> {
+        let num_variables = self.num_variables();
+        let names : Vec<String> = (0..num_variables).map(|v|namer(VariableIndex(v))).collect();
+        let mut result = Ok(());
+        if format==SolutionTextFormat::Csv {
+            let header : Vec<String> = names.iter().map(|n|csv_field(n)).collect();
+            result = writeln!(writer,"{}",header.join(","));
+        }
+        let mut line = String::new();
+        self.for_each_solution(index,|solution|{
+            if result.is_err() { return; }
+            line.clear();
+            match format {
+                SolutionTextFormat::Csv => {
+                    let mut true_variables = solution.iter().peekable();
+                    for v in 0..num_variables {
+                        if v>0 { line.push(','); }
+                        line.push(if true_variables.next_if(|t|t.0==v).is_some() { '1' } else { '0' });
+                    }
+                }
+                SolutionTextFormat::JsonLines => {
+                    line.push('[');
+                    for (i,v) in solution.iter().enumerate() {
+                        if i>0 { line.push(','); }
+                        line.push_str(&json::quote(&names[v.0 as usize]));
+                    }
+                    line.push(']');
+                }
+            }
+            result = writeln!(writer,"{}",line);
+        });
+        result
+    }
+    /// Get the node at the given address, which must not be a sink.
+    fn node(&self, address:A) -> Node<A,M>;
+    /// Iterate over the addresses of the nodes reachable from index, children before parents, without recursion.
+    /// Sinks are not included. This is a convenient basis for analyses of a diagram. The time taken depends on the
+    /// number of reachable nodes, not the size of the factory.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, NodeIndex, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let one = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(1),VariableIndex(2)]);
+    /// let mut seen = vec![];
+    /// for address in factory.post_order(one) {
+    ///     let node = factory.node(address);
+    ///     for child in [node.lo,node.hi] { assert!(child.is_sink() || seen.contains(&child.address())); }
+    ///     seen.push(address);
+    /// }
+    /// assert_eq!(Some(&one.address()),seen.last());
+    /// assert!(factory.post_order(NodeIndex::TRUE).next().is_none());
+    /// ```
+    fn post_order(&self, index:NodeIndex<A,M>) -> PostOrder<A>;
+    /// The number of nodes reachable from index at each level, along with the number of edges, the width (the largest
+    /// number of nodes at one level) and the length of the longest path. Printable via Display.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let two = factory.exactly_n_of(&variables,2);
+    /// let profile = factory.profile(two);
+    /// assert_eq!(vec![1,2,3,2],profile.nodes_per_level);
+    /// assert_eq!(8,profile.nodes());
+    /// assert_eq!(3,profile.width);
+    /// assert_eq!(4,profile.longest_path);
+    /// ```
+    fn profile(&self, index:NodeIndex<A,M>) -> DiagramProfile;
+    /// Iterate over the solutions of index, each being the sorted list of variables that are true.
+    /// Solutions come in lexicographic order of their assignments, with false before true. Multiplicities are ignored.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// let solutions : Vec<Vec<VariableIndex>> = factory.solutions(f).collect();
+    /// assert_eq!(vec![vec![VariableIndex(2)],vec![VariableIndex(1),VariableIndex(2)],vec![VariableIndex(0)],vec![VariableIndex(0),VariableIndex(1)]],solutions);
+    /// ```
+    fn solutions(&self, index:NodeIndex<A,M>) -> impl Iterator<Item=Vec<VariableIndex>>+'_;
+    /// Like [DecisionDiagramFactory::solutions], but call f with each solution, borrowed, instead of allocating a Vec for each.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let variables : Vec<VariableIndex> = (0..20).map(VariableIndex).collect();
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(20);
+    /// let three = factory.exactly_n_of(&variables,3);
+    /// let mut with_variable_0 = 0;
+    /// factory.for_each_solution(three,|solution|if solution[0]==VariableIndex(0) { with_variable_0+=1; });
+    /// assert_eq!(171,with_variable_0); // 19 choose 2.
+    /// ```
+    fn for_each_solution(&self, index:NodeIndex<A,M>, f:impl FnMut(&[VariableIndex]));
+    /// Like [DecisionDiagramFactory::solutions], but giving each solution in some other form, such as a u64 bit mask.
+    /// Panics if the format can not hold solutions with this many variables.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// assert_eq!(vec![0b100,0b110,0b001,0b011],factory.solutions_as::<u64>(f).collect::<Vec<_>>());
+    /// assert_eq!(Some(vec![false,false,true]),factory.solutions_as::<Vec<bool>>(f).next());
+    /// ```
+    fn solutions_as<S:SolutionFormat>(&self, index:NodeIndex<A,M>) -> impl Iterator<Item=S>+'_;
+    /// The first solution of index after the solution given by its true variables, in the order of [DecisionDiagramFactory::solutions],
+    /// or None if there are no more. The given assignment need not itself be a solution. This goes straight to the answer
+    /// in time proportional to the number of variables, so solutions can be read a page at a time, continuing from
+    /// the last one read, without keeping an iterator or counting through all the earlier solutions.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// assert_eq!(Some(vec![VariableIndex(1),VariableIndex(2)]),factory.next_solution_after(f,&[VariableIndex(2)]));
+    /// assert_eq!(Some(vec![VariableIndex(0)]),factory.next_solution_after(f,&[VariableIndex(1),VariableIndex(2)]));
+    /// assert_eq!(Some(vec![VariableIndex(1),VariableIndex(2)]),factory.next_solution_after(f,&[VariableIndex(1)])); // not a solution itself.
+    /// assert_eq!(None,factory.next_solution_after(f,&[VariableIndex(0),VariableIndex(1)]));
+    /// ```
+    fn next_solution_after(&self, index:NodeIndex<A,M>, assignment:&[VariableIndex]) -> Option<Vec<VariableIndex>>;
+    /// The value of index for every assignment, for small numbers of variables. Entry i is for the assignment in which
+    /// variable v is true iff bit v of i is set. Multiplicities are ignored. Panics if there are more than
+    /// [MAX_TRUTH_TABLE_VARIABLES] variables.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// let f = factory.single_variable(VariableIndex(1));
+    /// assert_eq!(vec![false,false,true,true],factory.truth_table(f));
+    /// assert_eq!(vec![2,3],factory.minterms(f));
+    /// assert_eq!(vec![vec![None,Some(true)]],factory.cubes(f));
+    /// ```
+    fn truth_table(&self, index:NodeIndex<A,M>) -> Vec<bool> {
+        let num_variables = self.num_variables();
+        assert!(num_variables<=MAX_TRUTH_TABLE_VARIABLES,"A truth table for {} variables would be too large",num_variables);
+        let mut res = vec![false;1<<num_variables];
+        for minterm in self.minterms(index) { res[minterm]=true; }
+        res
+    }
+    /// The assignments for which index is true, as positions in [DecisionDiagramFactory::truth_table], in increasing order.
+    fn minterms(&self, index:NodeIndex<A,M>) -> Vec<usize> {
+        let num_variables = self.num_variables();
+        assert!(num_variables<=MAX_TRUTH_TABLE_VARIABLES,"Minterms for {} variables would be too large",num_variables);
+        let mut res : Vec<usize> = self.solutions(index).map(|solution|solution.iter().map(|v|1<<v.0).sum()).collect();
+        res.sort_unstable();
+        res
+    }
+    /// The paths from index to TRUE, each as the value of every variable, or None if the variable does not matter.
+    /// Each can be given to [DecisionDiagramFactory::evaluate_partial]. See [xdd_with_multiplicity::XDDBase::cubes].
+    fn cubes(&self, index:NodeIndex<A,M>) -> Vec<Vec<Option<bool>>>;
+    /// An endless iterator of solutions of index chosen uniformly at random, each being the sorted list of variables that are true.
+    /// Multiplicities act as weights. It is empty if index is FALSE. The counts needed are computed once, so this is
+    /// much faster than repeated calls to [DecisionDiagramFactory::sample_solution] when many samples are wanted.
+    /// Limited to diagrams with fewer than 2^128 solutions.
+    /// # Example
+    /// ```
+    /// use rand::SeedableRng;
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let two = factory.exactly_n_of(&variables,2);
+    /// let rng = rand::rngs::StdRng::seed_from_u64(42);
+    /// for solution in factory.random_solutions(two,rng).take(10) {
+    ///     assert_eq!(2,solution.len());
+    /// }
+    /// ```
+    fn random_solutions<R:Rng>(&self, index:NodeIndex<A,M>, rng:R) -> impl Iterator<Item=Vec<VariableIndex>> where u128:GeneratingFunctionWithMultiplicity<M>;
+    /// Find the k solutions of index with the greatest total weight, where weights has one entry per variable, and each variable that is true adds its weight.
+    /// Returned best first, each as the total weight and the sorted list of variables that are true. Multiplicities are ignored.
+    /// The solution with the fewest true variables is the best solution with all weights -1.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let variables = [VariableIndex(0),VariableIndex(1),VariableIndex(2)];
+    /// let at_most_two = factory.at_most_n_of(&variables,2);
+    /// let best = factory.best_solutions(at_most_two,&[5,-1,3],2);
+    /// assert_eq!(vec![(8,vec![VariableIndex(0),VariableIndex(2)]),(5,vec![VariableIndex(0)])],best);
+    /// ```
+    fn best_solutions<W:Copy+PartialOrd+std::ops::Add<Output=W>+Zero>(&self, index:NodeIndex<A,M>, weights:&[W], k:usize) -> Vec<(W,Vec<VariableIndex>)>;
+    /// Find a solution of index with the least or greatest total weight, where weights has one entry per variable, and each variable that is true adds its weight.
+    /// Returns the total weight and the sorted list of variables that are true, or None if there are no solutions. Ties are broken arbitrarily.
+    /// With all weights 1, minimizing finds a solution with the fewest true variables. Sums are done in i128, so cannot overflow part way through.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, Objective, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let variables = [VariableIndex(0),VariableIndex(1),VariableIndex(2)];
+    /// let at_least_two = factory.at_least_n_of(&variables,2);
+    /// assert_eq!(Some((-2,vec![VariableIndex(1),VariableIndex(2)])),factory.find_optimal_solution(at_least_two,&[4,-1,-1],Objective::Minimize));
+    /// assert_eq!(Some((7,vec![VariableIndex(0),VariableIndex(2)])),factory.find_optimal_solution(at_least_two,&[4,-2,3],Objective::Maximize));
+    /// ```
+    fn find_optimal_solution(&self, index:NodeIndex<A,M>, weights:&[i64], objective:Objective) -> Option<(i128,Vec<VariableIndex>)> {
+        let sign : i128 = match objective { Objective::Minimize => -1, Objective::Maximize => 1 };
+        let weights : Vec<i128> = weights.iter().map(|&w|sign*w as i128).collect();
+        self.best_solutions(index,&weights,1).pop().map(|(w,variables)|(sign*w,variables))
+    }
+    /// Choose one solution of index uniformly at random, with multiplicities acting as weights, or None if there are no solutions.
+    /// See [DecisionDiagramFactory::random_solutions].
+    fn sample_solution<R:Rng>(&self, index:NodeIndex<A,M>, rng:&mut R) -> Option<Vec<VariableIndex>> where u128:GeneratingFunctionWithMultiplicity<M> {
+        self.random_solutions(index,rng).next()
+    }
+    /// Get statistics about the work done by this factory since it was created, such as peak node count,
+    /// gc activity, cache hit rates and time spent in each class of operation. Printable via Display.
+    /// The default only knows the current number of nodes and the cache statistics; factories that keep track of
+    /// their work override it.
+    fn summary(&self) -> FactoryStatistics {
+        let mut res = FactoryStatistics::default();
+        res.note_len(self.len());
+        res.caches = self.cache_stats();
+        res
+    }
+    /// Statistics for each operation cache, by name. These are also included in [DecisionDiagramFactory::summary].
+    fn cache_stats(&self) -> Vec<(&'static str,CacheStatistics)>;
+    /// Limit the total number of results remembered by the operation caches, shared equally between them, or None for no limit
+    /// (the default). With a limit, old results are forgotten to make room for new ones, so long computations use bounded memory
+    /// without needing a gc to clear the caches. This forgets all currently remembered results.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(20);
+    /// factory.set_cache_capacity(Some(8000));
+    /// let variables : Vec<VariableIndex> = (0..20).map(VariableIndex).collect();
+    /// let f = factory.exactly_n_of(&variables,10);
+    /// let g = factory.at_most_n_of(&variables[5..],3);
+    /// factory.or(f,g);
+    /// let or_cache = factory.cache_stats().into_iter().find(|(name,_)|*name=="or").unwrap().1;
+    /// assert_eq!(Some(1000),or_cache.capacity);
+    /// assert!(or_cache.entries<=1000);
+    /// ```
+    fn set_cache_capacity(&mut self, capacity:Option<usize>);
+    /// The approximate number of bytes of heap memory used by the nodes, the table for finding them, and the operation caches.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, Node, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(20);
+    /// let variables : Vec<VariableIndex> = (0..20).map(VariableIndex).collect();
+    /// let f = factory.exactly_n_of(&variables,10);
+    /// let g = factory.at_most_n_of(&variables,3);
+    /// let h = factory.or(f,g);
+    /// assert!(factory.memory_usage()>factory.len()*size_of::<Node<u32,NoMultiplicity>>());
+    /// let h = factory.gc([h]).rename(h).unwrap();
+    /// let before = factory.memory_usage();
+    /// factory.shrink_to_fit();
+    /// assert!(factory.memory_usage()<before);
+    /// let len = factory.len();
+    /// factory.reserve(100000);
+    /// assert!(factory.memory_usage()>100000*size_of::<Node<u32,NoMultiplicity>>());
+    /// assert_eq!(len,factory.len());
+    /// assert_eq!(184756+1351,factory.number_solutions::<u64>(h)); // 20 choose 10, and at most 3 of 20.
+    /// ```
+    fn memory_usage(&self) -> usize;
+    /// Free memory allocated but not used, such as by the nodes and cache entries discarded by [DecisionDiagramFactory::gc].
+    fn shrink_to_fit(&mut self);
+    /// Allocate room for at least additional more nodes, to avoid repeatedly growing the node store, and the
+    /// time and temporary doubling of memory that entails, when the eventual size is known.
+    fn reserve(&mut self, additional:usize);
 }
 
 
 
+/// Panic with a helpful message unless the variables are sorted, distinct, and less than num_variables.
+/// Otherwise the factories would silently produce diagrams with meaningless counts.
+fn check_variables(variables:&[VariableIndex],num_variables:u16) {
+    for v in variables {
+        assert!(v.0<num_variables,"Variable {} used in a factory with only {} variables",v,num_variables);
+    }
+    assert!(variables.windows(2).all(|w|w[0]<w[1]),"Variables must be sorted and distinct");
+}
+
+/// The literals sorted by variable, after checking as for [check_variables] that each variable is valid and given only once.
+fn sorted_literals(literals:&[(VariableIndex,bool)],num_variables:u16) -> Vec<(VariableIndex,bool)> {
+    let mut res = literals.to_vec();
+    res.sort_by_key(|(v,_)|*v);
+    check_variables(&res.iter().map(|(v,_)|*v).collect::<Vec<_>>(),num_variables);
+    res
+}
+
 /// A factory that can do efficient operations on BDDs.
 pub struct BDDFactory<A:NodeAddress,M:Multiplicity> {
     nodes : xdd_with_multiplicity::NodeListWithFastLookup<A,M>,
-    and_cache : HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>,
-    or_cache : HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>,
-    not_cache : HashMap<A,A>,
+    and_cache : BinaryOperationCache<A,M>,
+    or_cache : BinaryOperationCache<A,M>,
+    xor_cache : BinaryOperationCache<A,M>,
+    subtract_cache : BinaryOperationCache<A,M>,
+    apply_cache : ApplyCache<A,M>,
+    ite_cache : TernaryOperationCache<A,M>,
+    restrict_cache : RestrictCache<A,M>,
+    not_cache : OperationCache<A,A>,
     num_variables : u16,
+    statistics : FactoryStatistics,
+    variable_registry : VariableRegistry,
+    roots : RootRegistry<A,M>,
 }
 
 impl <A:NodeAddress+Default,M:Multiplicity> DecisionDiagramFactory<A,M> for BDDFactory<A,M> {
@@ -243,136 +1706,1557 @@ impl <A:NodeAddress+Default,M:Multiplicity> DecisionDiagramFactory<A,M> for BDDF
             nodes: Default::default(),
             and_cache: Default::default(),
             or_cache: Default::default(),
+            xor_cache: Default::default(),
+            subtract_cache: Default::default(),
+            apply_cache: Default::default(),
+            ite_cache: Default::default(),
+            restrict_cache: Default::default(),
             not_cache: Default::default(),
-            num_variables
+            num_variables,
+            statistics: Default::default(),
+            variable_registry: Default::default(),
+            roots: Default::default(),
         }
     }
     fn and(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.mul_bdd(index1,index2,&mut self.and_cache)
+        let start = Instant::now();
+        let res = self.nodes.mul_bdd(index1,index2,&mut self.and_cache);
+        self.statistics.record(OperationClass::And,start,self.nodes.len());
+        res
     }
 
     fn or(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.sum_bdd(index1,index2,&mut self.or_cache)
+        let start = Instant::now();
+        let res = self.nodes.sum_bdd(index1,index2,&mut self.or_cache);
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
     }
 
-    fn not(&mut self, index: NodeIndex<A,M>) -> NodeIndex<A,M> {
+    fn xor(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.not_bdd(index,&mut self.not_cache)
+        let start = Instant::now();
+        let res = self.nodes.xor_bdd(index1,index2,&mut self.xor_cache);
+        self.statistics.record(OperationClass::Xor,start,self.nodes.len());
+        res
     }
 
-    fn number_solutions<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>) -> G {
+    fn apply(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, op: BooleanOperator) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.number_solutions::<G,true>(index,self.num_variables)
+        let start = Instant::now();
+        let res = self.nodes.apply_bdd(op,index1,index2,&mut self.apply_cache);
+        self.statistics.record(OperationClass::Apply,start,self.nodes.len());
+        res
     }
 
-    fn single_variable(&mut self, variable: VariableIndex) -> NodeIndex<A,M> {
+    fn ite(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.single_variable(variable)
+        let start = Instant::now();
+        let res = self.nodes.ite_bdd(f,g,h,&mut self.ite_cache);
+        self.statistics.record(OperationClass::Ite,start,self.nodes.len());
+        res
     }
 
-    fn len(&self) -> usize {
+    fn vector_compose(&mut self, f: NodeIndex<A,M>, substitutions: &[(VariableIndex, NodeIndex<A,M>)]) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.len()
+        let mut variables : Vec<VariableIndex> = substitutions.iter().map(|(v,_)|*v).collect();
+        variables.sort();
+        check_variables(&variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.vector_compose_bdd(f,substitutions,&mut self.ite_cache);
+        self.statistics.record(OperationClass::Compose,start,self.nodes.len());
+        res
     }
 
-    fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
-        self.and_cache.clear();
-        self.or_cache.clear();
-        self.not_cache.clear();
+    fn restrict(&mut self, f: NodeIndex<A,M>, variable: VariableIndex, value: bool) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.gc(keep)
+        check_variables(&[variable],self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.restrict_bdd(f,variable,value,&mut self.restrict_cache);
+        self.statistics.record(OperationClass::Restrict,start,self.nodes.len());
+        res
     }
 
-    fn exactly_one_of(&mut self, variables: &[VariableIndex]) -> NodeIndex<A,M> {
+    fn restrict_cube(&mut self, f: NodeIndex<A,M>, literals: &[(VariableIndex,bool)]) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.exactly_one_of_bdd(variables)
+        let literals = sorted_literals(literals,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.restrict_cube::<true>(f,&literals,self.num_variables);
+        self.statistics.record(OperationClass::Restrict,start,self.nodes.len());
+        res
     }
 
-    fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+    fn implies(&self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> bool {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.make_dot_file(writer,name,start_nodes,namer)
+        self.nodes.implies::<true>(f,g,&mut HashMap::new())
     }
-}
 
-/// A factory that can do efficient operations on BDDs.
-pub struct ZDDFactory<A:NodeAddress,M:Multiplicity> {
-    nodes : xdd_with_multiplicity::NodeListWithFastLookup<A,M>,
-    and_cache : HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>,
-    or_cache : HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>,
-    not_cache : HashMap<(A,VariableIndex),A>,
-    num_variables : u16,
-}
+    fn symmetric_variables(&self, f: NodeIndex<A,M>) -> Vec<Vec<VariableIndex>> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.symmetric_classes::<true>(f,self.num_variables)
+    }
 
-impl <A:NodeAddress,M:Multiplicity> DecisionDiagramFactory<A,M> for ZDDFactory<A,M> {
+    fn dependent_variables(&self, f: NodeIndex<A,M>) -> Vec<VariableIndex> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.dependent_variables::<true>(f,self.num_variables)
+    }
 
-    fn new(num_variables:u16) -> Self {
-        ZDDFactory {
-            nodes: Default::default(),
-            and_cache: Default::default(),
-            or_cache: Default::default(),
-            not_cache: Default::default(),
-            num_variables
-        }
+    fn is_positive_unate(&self, f: NodeIndex<A,M>, variable: VariableIndex) -> bool {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        self.nodes.unate_in::<true>(f,variable,true)
     }
-    fn and(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
+
+    fn is_negative_unate(&self, f: NodeIndex<A,M>, variable: VariableIndex) -> bool {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.mul_zdd(index1,index2,&mut self.and_cache)
+        check_variables(&[variable],self.num_variables);
+        self.nodes.unate_in::<true>(f,variable,false)
     }
 
-    fn or(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
+    fn evaluate(&self, f: NodeIndex<A,M>, assignment: &[bool]) -> Option<M> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(assignment.len()<=self.num_variables as usize,"Assignment to {} variables in a factory with only {} variables",assignment.len(),self.num_variables);
+        self.nodes.evaluate::<true>(f,assignment)
+    }
+
+    fn next_solution_after(&self, index: NodeIndex<A,M>, assignment: &[VariableIndex]) -> Option<Vec<VariableIndex>> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(assignment,self.num_variables);
+        let mut values = vec![false;self.num_variables as usize];
+        for v in assignment { values[v.0 as usize]=true; }
+        self.nodes.next_solution_after::<true>(index,&values,self.num_variables)
+    }
+
+    fn relational_product(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, quantified: &[VariableIndex]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(quantified,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.relational_product_bdd(f,g,quantified,&mut self.or_cache);
+        self.statistics.record(OperationClass::Quantify,start,self.nodes.len());
+        res
+    }
+
+    fn linear_combination(&mut self, terms: &[(NodeIndex<A,M>, M)]) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.sum_zdd(index1,index2,&mut self.or_cache)
+        let start = Instant::now();
+        let res = self.nodes.linear_combination_bdd(terms);
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
+    }
+
+    fn subtract(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.subtract_bdd(f,g,&mut self.subtract_cache);
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
+    }
+
+    fn support(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.support::<true>(f);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn filter_multiplicity(&mut self, f: NodeIndex<A,M>, predicate: impl Fn(M) -> bool) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.filter_multiplicity::<_,true>(f,predicate);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn rename_variables(&mut self, f: NodeIndex<A,M>, mapping: impl Fn(VariableIndex) -> VariableIndex) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.rename_variables(f,mapping,self.num_variables);
+        self.statistics.record(OperationClass::Compose,start,self.nodes.len());
+        res
+    }
+
+    fn swap_adjacent_levels(&mut self, variable: VariableIndex, keep: impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(variable.0+1<self.num_variables,"Can not swap variable {} with the next in a factory with only {} variables",variable,self.num_variables);
+        let start = Instant::now();
+        let old_len = self.nodes.len();
+        let mut memo = HashMap::new();
+        let keep : Vec<NodeIndex<A,M>> = keep.into_iter().collect();
+        let swapped : Vec<NodeIndex<A,M>> = keep.iter().map(|&f|self.nodes.swap_adjacent_variables::<true>(f,variable,&mut memo)).collect();
+        self.roots.update(|f|self.nodes.swap_adjacent_variables::<true>(f,variable,&mut memo));
+        self.statistics.record(OperationClass::Compose,start,self.nodes.len());
+        let renaming = self.gc(swapped.iter().cloned());
+        self.variable_registry.swap(variable,VariableIndex(variable.0+1));
+        NodeRenaming::for_transformed(old_len,&keep,&swapped,&renaming)
+    }
+
+    fn reduce(&mut self, root: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.reduce_bdd(root);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn validate(&self, root: NodeIndex<A,M>) -> Result<(),xdd_with_multiplicity::InvalidDiagram<A>> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.validate::<true>(root,self.num_variables)
     }
 
     fn not(&mut self, index: NodeIndex<A,M>) -> NodeIndex<A,M> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.not_zdd(index,VariableIndex(0),self.num_variables,&mut self.not_cache)
+        let start = Instant::now();
+        let res = self.nodes.not_bdd(index,&mut self.not_cache);
+        self.statistics.record(OperationClass::Not,start,self.nodes.len());
+        res
+    }
 
+    fn dual(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let negated = self.nodes.negate_variables_bdd(f);
+        self.statistics.record(OperationClass::Compose,start,self.nodes.len());
+        self.not(negated)
     }
 
     fn number_solutions<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>) -> G {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.number_solutions::<G,false>(index,self.num_variables)
+        self.nodes.number_solutions::<G,true>(index,self.num_variables)
     }
 
-    fn single_variable(&mut self, variable: VariableIndex) -> NodeIndex<A,M> {
+    fn number_solutions_many<G: GeneratingFunctionWithMultiplicity<M>>(&self, roots: &[NodeIndex<A,M>]) -> Vec<G> {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.single_variable_zdd(variable,self.num_variables) // TODO
+        self.nodes.number_solutions_many::<G,true>(roots,self.num_variables)
     }
 
-    fn len(&self) -> usize {
+    fn number_solutions_given<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, literals: &[(VariableIndex, bool)]) -> G {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.len()
+        let mut fixed = vec![None;self.num_variables as usize];
+        for (v,value) in sorted_literals(literals,self.num_variables) { fixed[v.0 as usize]=Some(value); }
+        self.nodes.number_solutions_given::<G,true>(index,&fixed)
     }
 
-    fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
-        self.and_cache.clear();
-        self.or_cache.clear();
-        self.not_cache.clear();
+    fn variable_marginals<G: GeneratingFunctionWithMultiplicity<M>+std::ops::Mul<Output=G>+std::ops::Sub<Output=G>>(&self, index: NodeIndex<A,M>) -> Vec<G> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.variable_marginals::<G,true>(index,self.num_variables)
+    }
+    fn weighted_count<G: GeneratingFunctionWithMultiplicity<M>+std::ops::Mul<Output=G>>(&self, index: NodeIndex<A,M>, weights:&[G]) -> G {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.gc(keep)
+        self.nodes.weighted_count::<G,true>(index,self.num_variables,weights)
     }
 
-    fn exactly_one_of(&mut self, variables: &[VariableIndex]) -> NodeIndex<A,M> {
+    fn count_solutions_with_k_true<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, k: usize) -> G {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.exactly_one_of_zdd(variables,self.num_variables)
+        self.nodes.number_solutions_with_k_true::<G,true>(index,self.num_variables,k)
     }
 
-    fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+    fn number_solutions_cached<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, cache: &mut generating_function::CountingCache<A,G>) -> G {
         use xdd_with_multiplicity::XDDBase;
-        self.nodes.make_dot_file(writer,name,start_nodes,namer)
+        self.nodes.number_solutions_memoized::<G,true>(index,self.num_variables,&mut cache.values)
+    }
+
+    fn project(&mut self, f: NodeIndex<A,M>, variables: &[VariableIndex]) -> NodeIndex<A,M> {
+        check_variables(variables,self.num_variables);
+        let others : Vec<VariableIndex> = (0..self.num_variables).map(VariableIndex).filter(|v|!variables.contains(v)).collect();
+        let res = self.exists(f,&others);
+        self.support(res)
+    }
+
+    fn number_projected_solutions<G: GeneratingFunctionWithMultiplicity<M>>(&mut self, f: NodeIndex<A,M>, variables: &[VariableIndex]) -> G {
+        use xdd_with_multiplicity::XDDBase;
+        let projected = self.project(f,variables);
+        let mut counted = vec![false;self.num_variables as usize];
+        for v in variables { counted[v.0 as usize]=true; }
+        self.nodes.number_solutions_bdd_over::<G>(projected,&counted)
     }
-}
 
+    fn single_variable(&mut self, variable: VariableIndex) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.single_variable(variable);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
 
+    fn num_variables(&self) -> u16 { self.num_variables }
 
-pub struct NodeRenaming<A:NodeAddress>(Vec<A>);
+    fn len(&self) -> usize {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.len()
+    }
 
-impl <A:NodeAddress> NodeRenaming<A> {
-    pub fn rename<M:Multiplicity>(&self, index: NodeIndex<A,M>) -> Option<NodeIndex<A,M>> {
-        let res = self.0[index.address.as_usize()];
-        if res==A::FALSE && index.address!=A::FALSE { None } else { Some(NodeIndex {address:res,multiplicity:index.multiplicity}) }
+    fn cubes(&self, index: NodeIndex<A,M>) -> Vec<Vec<Option<bool>>> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.cubes::<true>(index,self.num_variables)
+    }
+
+    fn try_operation<R>(&mut self, operation: impl FnOnce(&mut Self) -> R) -> Result<R,XDDError> {
+        let was_fallible = std::mem::replace(&mut self.nodes.fallible,true);
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(||operation(self)));
+        self.nodes.fallible = was_fallible;
+        if std::mem::take(&mut self.nodes.capacity_exceeded) {
+            self.clear_caches(); // may contain results computed after running out.
+            if res.is_ok() { return Err(XDDError::CapacityExceeded); }
+        }
+        match res {
+            Ok(res) => Ok(res),
+            Err(payload) if payload.is::<statistics::Cancelled>() => Err(XDDError::Cancelled),
+            Err(payload) => std::panic::resume_unwind(payload),
+        }
+    }
+
+    fn set_progress_callback(&mut self, interval: u64, callback: impl FnMut(&statistics::Progress)+Send+'static) {
+        let counts = self.nodes.monitor.set_callback(interval,callback);
+        self.share_cache_counts(Some(counts));
+    }
+
+    fn clear_progress_callback(&mut self) {
+        self.nodes.monitor.clear_callback();
+        self.share_cache_counts(None);
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<statistics::CancellationToken>) { self.nodes.monitor.set_cancellation_token(token); }
+
+    fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        self.statistics.note_len(self.nodes.len());
+        let res = self.nodes.gc(keep.into_iter().chain(self.roots.indices()));
+        self.rename_caches(&res);
+        self.roots.rename(&res);
+        self.statistics.record_gc(start,self.nodes.len());
+        res
+    }
+
+    fn exactly_one_of(&mut self, variables: &[VariableIndex]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.exactly_one_of_bdd(variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn between_n_and_m_of(&mut self, variables: &[VariableIndex], at_least: usize, at_most: usize) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.between_n_and_m_of_bdd(variables,at_least,at_most);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn exactly_n_of(&mut self, variables: &[VariableIndex], n: usize) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.exactly_n_of_bdd(variables,n);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn linear_constraint(&mut self, terms: &[(VariableIndex, i64)], comparison: Comparison, bound: i64) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&terms.iter().map(|t|t.0).collect::<Vec<_>>(),self.num_variables);
+        let start = Instant::now();
+        let (at_least,at_most) = comparison.sum_range(bound);
+        let res = self.nodes.linear_constraint_bdd(terms,at_least,at_most);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn exact_cover(&mut self, num_primary_items: usize, options: &[Vec<usize>]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(options.len()<=self.num_variables as usize,"{} options in a factory with only {} variables",options.len(),self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.exact_cover::<true>(num_primary_items,options,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.make_dot_file(writer,name,start_nodes,namer)
+    }
+
+    fn make_dot_file_with_options<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F, options:&DotOptions) -> std::io::Result<()> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.make_dot_file_with_options(writer,name,start_nodes,namer,options)
+    }
+
+    fn make_json_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+        json::write_json(&self.nodes,writer,self.num_variables,start_nodes,namer)
+    }
+
+    fn variable_registry(&self) -> &VariableRegistry { &self.variable_registry }
+    fn variable_registry_mut(&mut self) -> &mut VariableRegistry { &mut self.variable_registry }
+    fn root_registry(&self) -> &RootRegistry<A,M> { &self.roots }
+    fn root_registry_mut(&mut self) -> &mut RootRegistry<A,M> { &mut self.roots }
+
+    fn node(&self, address: A) -> Node<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.node(address)
+    }
+
+    fn post_order(&self, index: NodeIndex<A,M>) -> PostOrder<A> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.post_order(index)
+    }
+
+    fn profile(&self, index: NodeIndex<A,M>) -> DiagramProfile {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.profile(index,self.num_variables)
+    }
+
+    fn solutions(&self, index: NodeIndex<A,M>) -> impl Iterator<Item=Vec<VariableIndex>>+'_ {
+        xdd_with_multiplicity::Solutions::new(&self.nodes,index,self.num_variables,false)
+    }
+
+    fn for_each_solution(&self, index: NodeIndex<A,M>, mut f: impl FnMut(&[VariableIndex])) {
+        let mut solutions = xdd_with_multiplicity::Solutions::new(&self.nodes,index,self.num_variables,false);
+        while let Some(solution) = solutions.next_solution() { f(solution); }
+    }
+
+    fn solutions_as<S:SolutionFormat>(&self, index: NodeIndex<A,M>) -> impl Iterator<Item=S>+'_ {
+        S::check_capacity(self.num_variables);
+        let mut solutions = xdd_with_multiplicity::Solutions::new(&self.nodes,index,self.num_variables,false);
+        std::iter::from_fn(move||solutions.next_solution().map(|solution|S::from_solution(solution,self.num_variables)))
+    }
+
+    fn best_solutions<W:Copy+PartialOrd+std::ops::Add<Output=W>+Zero>(&self, index: NodeIndex<A,M>, weights: &[W], k: usize) -> Vec<(W,Vec<VariableIndex>)> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.best_solutions::<W,true>(index,self.num_variables,weights,k)
+    }
+
+    fn random_solutions<R:Rng>(&self, index: NodeIndex<A,M>, rng: R) -> impl Iterator<Item=Vec<VariableIndex>> where u128:GeneratingFunctionWithMultiplicity<M> {
+        xdd_with_multiplicity::RandomSolutions::new(&self.nodes,index,self.num_variables,false,rng)
+    }
+
+    fn summary(&self) -> FactoryStatistics {
+        use xdd_with_multiplicity::XDDBase;
+        let mut res = self.statistics.clone();
+        res.note_len(self.nodes.len());
+        res.caches = self.cache_stats();
+        res
+    }
+
+    fn cache_stats(&self) -> Vec<(&'static str,CacheStatistics)> {
+        vec![("and",CacheStatistics::of(&self.and_cache)),("or",CacheStatistics::of(&self.or_cache)),("xor",CacheStatistics::of(&self.xor_cache)),("subtract",CacheStatistics::of(&self.subtract_cache)),("apply",CacheStatistics::of(&self.apply_cache)),("ite",CacheStatistics::of(&self.ite_cache)),("restrict",CacheStatistics::of(&self.restrict_cache)),("not",CacheStatistics::of(&self.not_cache))]
+    }
+
+    fn set_cache_capacity(&mut self, capacity: Option<usize>) {
+        let each = capacity.map(|c|c/self.cache_stats().len());
+        self.and_cache.set_capacity(each);
+        self.or_cache.set_capacity(each);
+        self.xor_cache.set_capacity(each);
+        self.subtract_cache.set_capacity(each);
+        self.apply_cache.set_capacity(each);
+        self.ite_cache.set_capacity(each);
+        self.restrict_cache.set_capacity(each);
+        self.not_cache.set_capacity(each);
+    }
+
+    fn memory_usage(&self) -> usize {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.memory_usage()+self.cache_stats().iter().map(|(_,cache)|cache.memory).sum::<usize>()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.shrink_to_fit();
+        self.and_cache.shrink_to_fit();
+        self.or_cache.shrink_to_fit();
+        self.xor_cache.shrink_to_fit();
+        self.subtract_cache.shrink_to_fit();
+        self.apply_cache.shrink_to_fit();
+        self.ite_cache.shrink_to_fit();
+        self.restrict_cache.shrink_to_fit();
+        self.not_cache.shrink_to_fit();
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.reserve(additional);
+    }
+}
+
+impl <A:NodeAddress,M:Multiplicity> BDDFactory<A,M> {
+    fn clear_caches(&mut self) {
+        self.and_cache.clear();
+        self.or_cache.clear();
+        self.xor_cache.clear();
+        self.subtract_cache.clear();
+        self.apply_cache.clear();
+        self.ite_cache.clear();
+        self.restrict_cache.clear();
+        self.not_cache.clear();
+    }
+
+    /// Keep the cached results that only refer to nodes that survived garbage collection, at their new addresses.
+    fn rename_caches(&mut self, renaming:&NodeRenaming<A>) {
+        self.and_cache.rename(renaming);
+        self.or_cache.rename(renaming);
+        self.xor_cache.rename(renaming);
+        self.subtract_cache.rename(renaming);
+        self.apply_cache.rename(renaming);
+        self.ite_cache.rename(renaming);
+        self.restrict_cache.rename(renaming);
+        self.not_cache.rename(renaming);
+    }
+
+    /// Make every operation cache also count its hits and misses in counts, for a progress callback.
+    fn share_cache_counts(&mut self, counts:Option<std::sync::Arc<statistics::SharedCacheCounts>>) {
+        self.and_cache.set_shared_counts(counts.clone());
+        self.or_cache.set_shared_counts(counts.clone());
+        self.xor_cache.set_shared_counts(counts.clone());
+        self.subtract_cache.set_shared_counts(counts.clone());
+        self.apply_cache.set_shared_counts(counts.clone());
+        self.ite_cache.set_shared_counts(counts.clone());
+        self.restrict_cache.set_shared_counts(counts.clone());
+        self.not_cache.set_shared_counts(counts.clone());
+    }
+
+    /// Whether f in this factory and g in other represent the same function. Within one factory this is just f==g,
+    /// but other may be a different factory, possibly with a different address type, such as when checking
+    /// a diagram built two ways. They are compared in a single traversal, in time proportional to the size of f.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let mut factory1 = BDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let two = factory1.exactly_n_of(&variables,2);
+    /// let mut factory2 = BDDFactory::<u16,NoMultiplicity>::new(4);
+    /// let at_least = factory2.at_least_n_of(&variables,2);
+    /// let at_most = factory2.at_most_n_of(&variables,2);
+    /// let also_two = factory2.and(at_least,at_most);
+    /// assert!(factory1.equivalent(two,&factory2,also_two));
+    /// assert!(!factory1.equivalent(two,&factory2,at_least));
+    /// ```
+    pub fn equivalent<A2:NodeAddress>(&self, f: NodeIndex<A,M>, other:&BDDFactory<A2,M>, g: NodeIndex<A2,M>) -> bool {
+        use xdd_with_multiplicity::XDDBase;
+        self.num_variables==other.num_variables && self.nodes.equivalent(f,&other.nodes,g,&mut HashMap::new())
+    }
+
+    /// Copy the diagram root in other into this factory, returning the new root. Only the nodes reachable from root are copied,
+    /// so moving the results of a long computation into a new factory leaves behind dead nodes and full caches.
+    /// The other factory may have a different address type or multiplicity type; importing from a MBDD into a BDD
+    /// drops the multiplicities, giving the set of solutions. Panics if other has more variables than this factory.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let mut multiset = BDDFactory::<u32,u32>::new(4);
+    /// let one = multiset.exactly_one_of(&variables);
+    /// let at_most_one = multiset.at_most_n_of(&variables,1);
+    /// let f = multiset.or(one,at_most_one); // each of the 4 solutions with one variable has multiplicity 2.
+    /// assert_eq!(9,multiset.number_solutions::<u64>(f));
+    /// let mut set = BDDFactory::<u64,NoMultiplicity>::new(4);
+    /// let g = set.import_from(&multiset,f);
+    /// assert_eq!(5,set.number_solutions::<u64>(g));
+    /// assert_eq!(set.at_most_n_of(&variables,1),g);
+    /// ```
+    pub fn import_from<A2:NodeAddress,M2:Multiplicity>(&mut self, other:&BDDFactory<A2,M2>, root: NodeIndex<A2,M2>) -> NodeIndex<A,M> where M:From<M2> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(other.num_variables<=self.num_variables,"Can not import from a factory with {} variables into one with only {}",other.num_variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.import_bdd(&other.nodes,root);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    /// Minimize f given that only its values where care is true matter, with the generalized cofactor (Coudert and Madre's constrain).
+    /// The result agrees with f, multiplicities included, wherever care is true; elsewhere it takes whatever values make it small.
+    /// It may depend on variables f does not; see [BDDFactory::simplify]. The multiplicities of care are ignored.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    /// let [v0,v1] = [0,1].map(|v|factory.single_variable(VariableIndex(v)));
+    /// let f = factory.and(v0,v1);
+    /// assert_eq!(v0,factory.constrain(f,v1)); // when v1 is true, f is just v0.
+    /// let g = factory.constrain(f,v1);
+    /// assert_eq!(f,factory.and(g,v1));
+    /// ```
+    pub fn constrain(&mut self, f: NodeIndex<A,M>, care: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.constrain_bdd(f,care,&mut HashMap::new());
+        self.statistics.record(OperationClass::Restrict,start,self.nodes.len());
+        res
+    }
+
+    /// Minimize f given that only its values where care is true matter, with Coudert and Madre's restrict heuristic (called
+    /// simplify in BuDDy to distinguish it from restricting a single variable). Like [BDDFactory::constrain], but the result never
+    /// depends on a variable that f does not, and is usually smaller.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let [v0,v1,v2] = [0,1,2].map(|v|factory.single_variable(VariableIndex(v)));
+    /// let f = factory.xor(v1,v2);
+    /// let care = factory.xor(v0,v2);
+    /// let simplified = factory.simplify(f,care);
+    /// assert_eq!(factory.and(simplified,care),factory.and(f,care));
+    /// assert_eq!(f,simplified); // v0 is not introduced.
+    /// ```
+    pub fn simplify(&mut self, f: NodeIndex<A,M>, care: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.simplify_bdd(f,care,&mut HashMap::new(),&mut self.or_cache);
+        self.statistics.record(OperationClass::Restrict,start,self.nodes.len());
+        res
+    }
+
+    /// Make a BDD for the same function as the ZDD root in zdd, which must have the same number of variables.
+    /// The ZDD factory may have different address and multiplicity types, as for [BDDFactory::import_from].
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, NodeIndex, VariableIndex, ZDDFactory};
+    /// let mut zdd = ZDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = zdd.change(NodeIndex::TRUE,VariableIndex(1)); // the family {{1}}
+    /// let mut bdd = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let g = bdd.bdd_from_zdd(&zdd,f); // !v0 & v1 & !v2
+    /// let [v0,v1,v2] = [0,1,2].map(|v|bdd.single_variable(VariableIndex(v)));
+    /// let not_v0 = bdd.not(v0);
+    /// let not_v2 = bdd.not(v2);
+    /// let only_v1 = bdd.and(not_v0,v1);
+    /// assert_eq!(bdd.and(only_v1,not_v2),g);
+    /// assert_eq!(f,zdd.zdd_from_bdd(&bdd,g));
+    /// ```
+    pub fn bdd_from_zdd<A2:NodeAddress,M2:Multiplicity>(&mut self, zdd:&ZDDFactory<A2,M2>, root: NodeIndex<A2,M2>) -> NodeIndex<A,M> where M:From<M2> {
+        use xdd_with_multiplicity::XDDBase;
+        assert_eq!(zdd.num_variables,self.num_variables,"Can only convert between factories with the same number of variables");
+        let start = Instant::now();
+        let res = self.nodes.convert_from::<A2,M2,_,true>(&zdd.nodes,root,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+}
+
+/// A factory that can do efficient operations on BDDs.
+pub struct ZDDFactory<A:NodeAddress,M:Multiplicity> {
+    nodes : xdd_with_multiplicity::NodeListWithFastLookup<A,M>,
+    and_cache : BinaryOperationCache<A,M>,
+    or_cache : BinaryOperationCache<A,M>,
+    xor_cache : BinaryOperationCache<A,M>,
+    subtract_cache : BinaryOperationCache<A,M>,
+    apply_cache : ApplyCache<A,M>,
+    ite_cache : TernaryOperationCache<A,M>,
+    restrict_cache : RestrictCache<A,M>,
+    not_cache : OperationCache<(A,VariableIndex),A>,
+    union_cache : FamilyCache<A,M>,
+    intersection_cache : FamilyCache<A,M>,
+    difference_cache : FamilyCache<A,M>,
+    join_cache : FamilyCache<A,M>,
+    meet_cache : FamilyCache<A,M>,
+    quotient_cache : FamilyCache<A,M>,
+    remainder_cache : FamilyCache<A,M>,
+    offset_cache : VariableOperationCache<A,M>,
+    onset_cache : VariableOperationCache<A,M>,
+    change_cache : VariableOperationCache<A,M>,
+    minimal_cache : UnaryFamilyCache<A,M>,
+    maximal_cache : UnaryFamilyCache<A,M>,
+    nonsubsets_cache : FamilyCache<A,M>,
+    nonsupersets_cache : FamilyCache<A,M>,
+    subsets_cache : FamilyCache<A,M>,
+    supersets_cache : FamilyCache<A,M>,
+    num_variables : u16,
+    statistics : FactoryStatistics,
+    variable_registry : VariableRegistry,
+    roots : RootRegistry<A,M>,
+}
+
+impl <A:NodeAddress,M:Multiplicity> DecisionDiagramFactory<A,M> for ZDDFactory<A,M> {
+
+    fn new(num_variables:u16) -> Self {
+        ZDDFactory {
+            nodes: Default::default(),
+            and_cache: Default::default(),
+            or_cache: Default::default(),
+            xor_cache: Default::default(),
+            subtract_cache: Default::default(),
+            apply_cache: Default::default(),
+            ite_cache: Default::default(),
+            restrict_cache: Default::default(),
+            not_cache: Default::default(),
+            union_cache: Default::default(),
+            intersection_cache: Default::default(),
+            difference_cache: Default::default(),
+            join_cache: Default::default(),
+            meet_cache: Default::default(),
+            quotient_cache: Default::default(),
+            remainder_cache: Default::default(),
+            offset_cache: Default::default(),
+            onset_cache: Default::default(),
+            change_cache: Default::default(),
+            minimal_cache: Default::default(),
+            maximal_cache: Default::default(),
+            nonsubsets_cache: Default::default(),
+            nonsupersets_cache: Default::default(),
+            subsets_cache: Default::default(),
+            supersets_cache: Default::default(),
+            num_variables,
+            statistics: Default::default(),
+            variable_registry: Default::default(),
+            roots: Default::default(),
+        }
+    }
+    fn and(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.mul_zdd(index1,index2,&mut self.and_cache);
+        self.statistics.record(OperationClass::And,start,self.nodes.len());
+        res
+    }
+
+    fn or(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.sum_zdd(index1,index2,&mut self.or_cache);
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
+    }
+
+    fn xor(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.xor_zdd(index1,index2,&mut self.xor_cache);
+        self.statistics.record(OperationClass::Xor,start,self.nodes.len());
+        res
+    }
+
+    fn apply(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, op: BooleanOperator) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.apply_zdd(op,index1,index2,self.num_variables,&mut self.apply_cache,&mut self.not_cache);
+        self.statistics.record(OperationClass::Apply,start,self.nodes.len());
+        res
+    }
+
+    fn ite(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.ite_zdd(f,g,h,&mut self.ite_cache);
+        self.statistics.record(OperationClass::Ite,start,self.nodes.len());
+        res
+    }
+
+    fn vector_compose(&mut self, f: NodeIndex<A,M>, substitutions: &[(VariableIndex, NodeIndex<A,M>)]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let mut variables : Vec<VariableIndex> = substitutions.iter().map(|(v,_)|*v).collect();
+        variables.sort();
+        check_variables(&variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.vector_compose_zdd(f,substitutions,self.num_variables,&mut self.ite_cache);
+        self.statistics.record(OperationClass::Compose,start,self.nodes.len());
+        res
+    }
+
+    fn restrict(&mut self, f: NodeIndex<A,M>, variable: VariableIndex, value: bool) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.restrict_zdd(f,variable,value,&mut self.restrict_cache);
+        self.statistics.record(OperationClass::Restrict,start,self.nodes.len());
+        res
+    }
+
+    fn restrict_cube(&mut self, f: NodeIndex<A,M>, literals: &[(VariableIndex,bool)]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let literals = sorted_literals(literals,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.restrict_cube::<false>(f,&literals,self.num_variables);
+        self.statistics.record(OperationClass::Restrict,start,self.nodes.len());
+        res
+    }
+
+    fn implies(&self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> bool {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.implies::<false>(f,g,&mut HashMap::new())
+    }
+
+    fn symmetric_variables(&self, f: NodeIndex<A,M>) -> Vec<Vec<VariableIndex>> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.symmetric_classes::<false>(f,self.num_variables)
+    }
+
+    fn dependent_variables(&self, f: NodeIndex<A,M>) -> Vec<VariableIndex> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.dependent_variables::<false>(f,self.num_variables)
+    }
+
+    fn is_positive_unate(&self, f: NodeIndex<A,M>, variable: VariableIndex) -> bool {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        self.nodes.unate_in::<false>(f,variable,true)
+    }
+
+    fn is_negative_unate(&self, f: NodeIndex<A,M>, variable: VariableIndex) -> bool {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        self.nodes.unate_in::<false>(f,variable,false)
+    }
+
+    fn evaluate(&self, f: NodeIndex<A,M>, assignment: &[bool]) -> Option<M> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(assignment.len()<=self.num_variables as usize,"Assignment to {} variables in a factory with only {} variables",assignment.len(),self.num_variables);
+        self.nodes.evaluate::<false>(f,assignment)
+    }
+
+    fn next_solution_after(&self, index: NodeIndex<A,M>, assignment: &[VariableIndex]) -> Option<Vec<VariableIndex>> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(assignment,self.num_variables);
+        let mut values = vec![false;self.num_variables as usize];
+        for v in assignment { values[v.0 as usize]=true; }
+        self.nodes.next_solution_after::<false>(index,&values,self.num_variables)
+    }
+
+    fn relational_product(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, quantified: &[VariableIndex]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(quantified,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.relational_product_zdd(f,g,quantified,&mut self.or_cache);
+        self.statistics.record(OperationClass::Quantify,start,self.nodes.len());
+        res
+    }
+
+    fn linear_combination(&mut self, terms: &[(NodeIndex<A,M>, M)]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.linear_combination_zdd(terms);
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
+    }
+
+    fn subtract(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.subtract_zdd(f,g,&mut self.subtract_cache);
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
+    }
+
+    fn support(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.support::<false>(f);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn filter_multiplicity(&mut self, f: NodeIndex<A,M>, predicate: impl Fn(M) -> bool) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.filter_multiplicity::<_,false>(f,predicate);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn rename_variables(&mut self, f: NodeIndex<A,M>, mapping: impl Fn(VariableIndex) -> VariableIndex) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.rename_variables(f,mapping,self.num_variables);
+        self.statistics.record(OperationClass::Compose,start,self.nodes.len());
+        res
+    }
+
+    fn swap_adjacent_levels(&mut self, variable: VariableIndex, keep: impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(variable.0+1<self.num_variables,"Can not swap variable {} with the next in a factory with only {} variables",variable,self.num_variables);
+        let start = Instant::now();
+        let old_len = self.nodes.len();
+        let mut memo = HashMap::new();
+        let keep : Vec<NodeIndex<A,M>> = keep.into_iter().collect();
+        let swapped : Vec<NodeIndex<A,M>> = keep.iter().map(|&f|self.nodes.swap_adjacent_variables::<false>(f,variable,&mut memo)).collect();
+        self.roots.update(|f|self.nodes.swap_adjacent_variables::<false>(f,variable,&mut memo));
+        self.statistics.record(OperationClass::Compose,start,self.nodes.len());
+        let renaming = self.gc(swapped.iter().cloned());
+        self.variable_registry.swap(variable,VariableIndex(variable.0+1));
+        NodeRenaming::for_transformed(old_len,&keep,&swapped,&renaming)
+    }
+
+    fn reduce(&mut self, root: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.reduce_zdd(root);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn validate(&self, root: NodeIndex<A,M>) -> Result<(),xdd_with_multiplicity::InvalidDiagram<A>> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.validate::<false>(root,self.num_variables)
+    }
+
+    fn not(&mut self, index: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.not_zdd(index,VariableIndex(0),self.num_variables,&mut self.not_cache);
+        self.statistics.record(OperationClass::Not,start,self.nodes.len());
+        res
+
+    }
+
+    fn dual(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        // negating the variables changes which are skipped, so is not a simple relabelling as it is for a BDD.
+        let substitutions : Vec<(VariableIndex,NodeIndex<A,M>)> = (0..self.num_variables).map(|v|{
+            let variable = self.single_variable(VariableIndex(v));
+            (VariableIndex(v),self.not(variable))
+        }).collect();
+        let negated = self.vector_compose(f,&substitutions);
+        self.not(negated)
+    }
+
+    fn number_solutions<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>) -> G {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.number_solutions::<G,false>(index,self.num_variables)
+    }
+
+    fn number_solutions_many<G: GeneratingFunctionWithMultiplicity<M>>(&self, roots: &[NodeIndex<A,M>]) -> Vec<G> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.number_solutions_many::<G,false>(roots,self.num_variables)
+    }
+
+    fn number_solutions_given<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, literals: &[(VariableIndex, bool)]) -> G {
+        use xdd_with_multiplicity::XDDBase;
+        let mut fixed = vec![None;self.num_variables as usize];
+        for (v,value) in sorted_literals(literals,self.num_variables) { fixed[v.0 as usize]=Some(value); }
+        self.nodes.number_solutions_given::<G,false>(index,&fixed)
+    }
+
+    fn variable_marginals<G: GeneratingFunctionWithMultiplicity<M>+std::ops::Mul<Output=G>+std::ops::Sub<Output=G>>(&self, index: NodeIndex<A,M>) -> Vec<G> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.variable_marginals::<G,false>(index,self.num_variables)
+    }
+    fn weighted_count<G: GeneratingFunctionWithMultiplicity<M>+std::ops::Mul<Output=G>>(&self, index: NodeIndex<A,M>, weights:&[G]) -> G {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.weighted_count::<G,false>(index,self.num_variables,weights)
+    }
+
+    fn count_solutions_with_k_true<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, k: usize) -> G {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.number_solutions_with_k_true::<G,false>(index,self.num_variables,k)
+    }
+
+    fn number_solutions_cached<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, cache: &mut generating_function::CountingCache<A,G>) -> G {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.number_solutions_memoized::<G,false>(index,self.num_variables,&mut cache.values)
+    }
+
+    fn project(&mut self, f: NodeIndex<A,M>, variables: &[VariableIndex]) -> NodeIndex<A,M> {
+        check_variables(variables,self.num_variables);
+        let others : Vec<VariableIndex> = (0..self.num_variables).map(VariableIndex).filter(|v|!variables.contains(v)).collect();
+        let mut res = self.exists(f,&others);
+        for &v in &others { res = self.offset(res,v); } // they are now free, so just take the sets without them.
+        self.support(res)
+    }
+
+    fn number_projected_solutions<G: GeneratingFunctionWithMultiplicity<M>>(&mut self, f: NodeIndex<A,M>, variables: &[VariableIndex]) -> G {
+        let projected = self.project(f,variables);
+        self.number_solutions(projected)
+    }
+
+    fn single_variable(&mut self, variable: VariableIndex) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.single_variable_zdd(variable,self.num_variables); // TODO
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn num_variables(&self) -> u16 { self.num_variables }
+
+    fn len(&self) -> usize {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.len()
+    }
+
+    fn cubes(&self, index: NodeIndex<A,M>) -> Vec<Vec<Option<bool>>> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.cubes::<false>(index,self.num_variables)
+    }
+
+    fn try_operation<R>(&mut self, operation: impl FnOnce(&mut Self) -> R) -> Result<R,XDDError> {
+        let was_fallible = std::mem::replace(&mut self.nodes.fallible,true);
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(||operation(self)));
+        self.nodes.fallible = was_fallible;
+        if std::mem::take(&mut self.nodes.capacity_exceeded) {
+            self.clear_caches(); // may contain results computed after running out.
+            if res.is_ok() { return Err(XDDError::CapacityExceeded); }
+        }
+        match res {
+            Ok(res) => Ok(res),
+            Err(payload) if payload.is::<statistics::Cancelled>() => Err(XDDError::Cancelled),
+            Err(payload) => std::panic::resume_unwind(payload),
+        }
+    }
+
+    fn set_progress_callback(&mut self, interval: u64, callback: impl FnMut(&statistics::Progress)+Send+'static) {
+        let counts = self.nodes.monitor.set_callback(interval,callback);
+        self.share_cache_counts(Some(counts));
+    }
+
+    fn clear_progress_callback(&mut self) {
+        self.nodes.monitor.clear_callback();
+        self.share_cache_counts(None);
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<statistics::CancellationToken>) { self.nodes.monitor.set_cancellation_token(token); }
+
+    fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        self.statistics.note_len(self.nodes.len());
+        let res = self.nodes.gc(keep.into_iter().chain(self.roots.indices()));
+        self.rename_caches(&res);
+        self.roots.rename(&res);
+        self.statistics.record_gc(start,self.nodes.len());
+        res
+    }
+
+    fn exactly_one_of(&mut self, variables: &[VariableIndex]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.exactly_one_of_zdd(variables,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn between_n_and_m_of(&mut self, variables: &[VariableIndex], at_least: usize, at_most: usize) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.between_n_and_m_of_zdd(variables,at_least,at_most,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn exactly_n_of(&mut self, variables: &[VariableIndex], n: usize) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.exactly_n_of_zdd(variables,n,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn linear_constraint(&mut self, terms: &[(VariableIndex, i64)], comparison: Comparison, bound: i64) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&terms.iter().map(|t|t.0).collect::<Vec<_>>(),self.num_variables);
+        let start = Instant::now();
+        let (at_least,at_most) = comparison.sum_range(bound);
+        let res = self.nodes.linear_constraint_zdd(terms,at_least,at_most,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn exact_cover(&mut self, num_primary_items: usize, options: &[Vec<usize>]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(options.len()<=self.num_variables as usize,"{} options in a factory with only {} variables",options.len(),self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.exact_cover::<false>(num_primary_items,options,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+
+    fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.make_dot_file(writer,name,start_nodes,namer)
+    }
+
+    fn make_dot_file_with_options<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F, options:&DotOptions) -> std::io::Result<()> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.make_dot_file_with_options(writer,name,start_nodes,namer,options)
+    }
+
+    fn make_json_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+        json::write_json(&self.nodes,writer,self.num_variables,start_nodes,namer)
+    }
+
+    fn variable_registry(&self) -> &VariableRegistry { &self.variable_registry }
+    fn variable_registry_mut(&mut self) -> &mut VariableRegistry { &mut self.variable_registry }
+    fn root_registry(&self) -> &RootRegistry<A,M> { &self.roots }
+    fn root_registry_mut(&mut self) -> &mut RootRegistry<A,M> { &mut self.roots }
+
+    fn node(&self, address: A) -> Node<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.node(address)
+    }
+
+    fn post_order(&self, index: NodeIndex<A,M>) -> PostOrder<A> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.post_order(index)
+    }
+
+    fn profile(&self, index: NodeIndex<A,M>) -> DiagramProfile {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.profile(index,self.num_variables)
+    }
+
+    fn solutions(&self, index: NodeIndex<A,M>) -> impl Iterator<Item=Vec<VariableIndex>>+'_ {
+        xdd_with_multiplicity::Solutions::new(&self.nodes,index,self.num_variables,true)
+    }
+
+    fn for_each_solution(&self, index: NodeIndex<A,M>, mut f: impl FnMut(&[VariableIndex])) {
+        let mut solutions = xdd_with_multiplicity::Solutions::new(&self.nodes,index,self.num_variables,true);
+        while let Some(solution) = solutions.next_solution() { f(solution); }
+    }
+
+    fn solutions_as<S:SolutionFormat>(&self, index: NodeIndex<A,M>) -> impl Iterator<Item=S>+'_ {
+        S::check_capacity(self.num_variables);
+        let mut solutions = xdd_with_multiplicity::Solutions::new(&self.nodes,index,self.num_variables,true);
+        std::iter::from_fn(move||solutions.next_solution().map(|solution|S::from_solution(solution,self.num_variables)))
+    }
+
+    fn best_solutions<W:Copy+PartialOrd+std::ops::Add<Output=W>+Zero>(&self, index: NodeIndex<A,M>, weights: &[W], k: usize) -> Vec<(W,Vec<VariableIndex>)> {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.best_solutions::<W,false>(index,self.num_variables,weights,k)
+    }
+
+    fn random_solutions<R:Rng>(&self, index: NodeIndex<A,M>, rng: R) -> impl Iterator<Item=Vec<VariableIndex>> where u128:GeneratingFunctionWithMultiplicity<M> {
+        xdd_with_multiplicity::RandomSolutions::new(&self.nodes,index,self.num_variables,true,rng)
+    }
+
+    fn summary(&self) -> FactoryStatistics {
+        use xdd_with_multiplicity::XDDBase;
+        let mut res = self.statistics.clone();
+        res.note_len(self.nodes.len());
+        res.caches = self.cache_stats();
+        res
+    }
+
+    fn cache_stats(&self) -> Vec<(&'static str,CacheStatistics)> {
+        vec![("and",CacheStatistics::of(&self.and_cache)),("or",CacheStatistics::of(&self.or_cache)),("xor",CacheStatistics::of(&self.xor_cache)),("subtract",CacheStatistics::of(&self.subtract_cache)),("apply",CacheStatistics::of(&self.apply_cache)),("ite",CacheStatistics::of(&self.ite_cache)),("restrict",CacheStatistics::of(&self.restrict_cache)),("not",CacheStatistics::of(&self.not_cache)),("union",CacheStatistics::of(&self.union_cache)),("intersection",CacheStatistics::of(&self.intersection_cache)),("difference",CacheStatistics::of(&self.difference_cache)),("join",CacheStatistics::of(&self.join_cache)),("meet",CacheStatistics::of(&self.meet_cache)),("quotient",CacheStatistics::of(&self.quotient_cache)),("remainder",CacheStatistics::of(&self.remainder_cache)),("offset",CacheStatistics::of(&self.offset_cache)),("onset",CacheStatistics::of(&self.onset_cache)),("change",CacheStatistics::of(&self.change_cache)),("minimal",CacheStatistics::of(&self.minimal_cache)),("maximal",CacheStatistics::of(&self.maximal_cache)),("nonsubsets",CacheStatistics::of(&self.nonsubsets_cache)),("nonsupersets",CacheStatistics::of(&self.nonsupersets_cache)),("subsets",CacheStatistics::of(&self.subsets_cache)),("supersets",CacheStatistics::of(&self.supersets_cache))]
+    }
+
+    fn set_cache_capacity(&mut self, capacity: Option<usize>) {
+        let each = capacity.map(|c|c/self.cache_stats().len());
+        self.and_cache.set_capacity(each);
+        self.or_cache.set_capacity(each);
+        self.xor_cache.set_capacity(each);
+        self.subtract_cache.set_capacity(each);
+        self.apply_cache.set_capacity(each);
+        self.ite_cache.set_capacity(each);
+        self.restrict_cache.set_capacity(each);
+        self.not_cache.set_capacity(each);
+        self.union_cache.set_capacity(each);
+        self.intersection_cache.set_capacity(each);
+        self.difference_cache.set_capacity(each);
+        self.join_cache.set_capacity(each);
+        self.meet_cache.set_capacity(each);
+        self.quotient_cache.set_capacity(each);
+        self.remainder_cache.set_capacity(each);
+        self.offset_cache.set_capacity(each);
+        self.onset_cache.set_capacity(each);
+        self.change_cache.set_capacity(each);
+        self.minimal_cache.set_capacity(each);
+        self.maximal_cache.set_capacity(each);
+        self.nonsubsets_cache.set_capacity(each);
+        self.nonsupersets_cache.set_capacity(each);
+        self.subsets_cache.set_capacity(each);
+        self.supersets_cache.set_capacity(each);
+    }
+
+    fn memory_usage(&self) -> usize {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.memory_usage()+self.cache_stats().iter().map(|(_,cache)|cache.memory).sum::<usize>()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.shrink_to_fit();
+        self.and_cache.shrink_to_fit();
+        self.or_cache.shrink_to_fit();
+        self.xor_cache.shrink_to_fit();
+        self.subtract_cache.shrink_to_fit();
+        self.apply_cache.shrink_to_fit();
+        self.ite_cache.shrink_to_fit();
+        self.restrict_cache.shrink_to_fit();
+        self.not_cache.shrink_to_fit();
+        self.union_cache.shrink_to_fit();
+        self.intersection_cache.shrink_to_fit();
+        self.difference_cache.shrink_to_fit();
+        self.join_cache.shrink_to_fit();
+        self.meet_cache.shrink_to_fit();
+        self.quotient_cache.shrink_to_fit();
+        self.remainder_cache.shrink_to_fit();
+        self.offset_cache.shrink_to_fit();
+        self.onset_cache.shrink_to_fit();
+        self.change_cache.shrink_to_fit();
+        self.minimal_cache.shrink_to_fit();
+        self.maximal_cache.shrink_to_fit();
+        self.nonsubsets_cache.shrink_to_fit();
+        self.nonsupersets_cache.shrink_to_fit();
+        self.subsets_cache.shrink_to_fit();
+        self.supersets_cache.shrink_to_fit();
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        use xdd_with_multiplicity::XDDBase;
+        self.nodes.reserve(additional);
+    }
+}
+
+/// The family algebra of ZDDs, treating a ZDD as a family of sets of variables (each solution being the set of variables that are true), as described by Minato.
+///
+/// These are set operations: multiplicities are ignored, and the results have unit multiplicities. For sets, union and intersection
+/// are the same as or and and, but have their own caches.
+///
+/// # Example
+/// ```
+/// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+/// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(3);
+/// let variables = [VariableIndex(0),VariableIndex(1),VariableIndex(2)];
+/// let singletons = factory.exactly_one_of(&variables); // {{0},{1},{2}}
+/// let pairs = factory.exactly_n_of(&variables,2); // {{0,1},{0,2},{1,2}}
+/// let joined = factory.join(singletons,singletons);
+/// assert_eq!(factory.union(singletons,pairs),joined);
+/// let met = factory.meet(pairs,pairs);
+/// assert_eq!(joined,met); // any two pairs overlap
+/// let has_0 = factory.single_variable(VariableIndex(0));
+/// let just_0 = factory.intersection(singletons,has_0); // {{0}}
+/// let quotient = factory.quotient(pairs,just_0); // {{1},{2}}
+/// let remainder = factory.remainder(pairs,just_0); // {{1,2}}
+/// assert_eq!(factory.difference(singletons,just_0),quotient);
+/// assert_eq!(1,factory.number_solutions::<u64>(remainder));
+/// ```
+impl <A:NodeAddress,M:Multiplicity> ZDDFactory<A,M> {
+    fn clear_caches(&mut self) {
+        self.and_cache.clear();
+        self.or_cache.clear();
+        self.xor_cache.clear();
+        self.subtract_cache.clear();
+        self.apply_cache.clear();
+        self.ite_cache.clear();
+        self.restrict_cache.clear();
+        self.not_cache.clear();
+        self.union_cache.clear();
+        self.intersection_cache.clear();
+        self.difference_cache.clear();
+        self.join_cache.clear();
+        self.meet_cache.clear();
+        self.quotient_cache.clear();
+        self.remainder_cache.clear();
+        self.offset_cache.clear();
+        self.onset_cache.clear();
+        self.change_cache.clear();
+        self.minimal_cache.clear();
+        self.maximal_cache.clear();
+        self.nonsubsets_cache.clear();
+        self.nonsupersets_cache.clear();
+        self.subsets_cache.clear();
+        self.supersets_cache.clear();
+    }
+
+    /// Keep the cached results that only refer to nodes that survived garbage collection, at their new addresses.
+    fn rename_caches(&mut self, renaming:&NodeRenaming<A>) {
+        self.and_cache.rename(renaming);
+        self.or_cache.rename(renaming);
+        self.xor_cache.rename(renaming);
+        self.subtract_cache.rename(renaming);
+        self.apply_cache.rename(renaming);
+        self.ite_cache.rename(renaming);
+        self.restrict_cache.rename(renaming);
+        self.not_cache.rename(renaming);
+        self.union_cache.rename(renaming);
+        self.intersection_cache.rename(renaming);
+        self.difference_cache.rename(renaming);
+        self.join_cache.rename(renaming);
+        self.meet_cache.rename(renaming);
+        self.quotient_cache.rename(renaming);
+        self.remainder_cache.rename(renaming);
+        self.offset_cache.rename(renaming);
+        self.onset_cache.rename(renaming);
+        self.change_cache.rename(renaming);
+        self.minimal_cache.rename(renaming);
+        self.maximal_cache.rename(renaming);
+        self.nonsubsets_cache.rename(renaming);
+        self.nonsupersets_cache.rename(renaming);
+        self.subsets_cache.rename(renaming);
+        self.supersets_cache.rename(renaming);
+    }
+
+    /// Make every operation cache also count its hits and misses in counts, for a progress callback.
+    fn share_cache_counts(&mut self, counts:Option<std::sync::Arc<statistics::SharedCacheCounts>>) {
+        self.and_cache.set_shared_counts(counts.clone());
+        self.or_cache.set_shared_counts(counts.clone());
+        self.xor_cache.set_shared_counts(counts.clone());
+        self.subtract_cache.set_shared_counts(counts.clone());
+        self.apply_cache.set_shared_counts(counts.clone());
+        self.ite_cache.set_shared_counts(counts.clone());
+        self.restrict_cache.set_shared_counts(counts.clone());
+        self.not_cache.set_shared_counts(counts.clone());
+        self.union_cache.set_shared_counts(counts.clone());
+        self.intersection_cache.set_shared_counts(counts.clone());
+        self.difference_cache.set_shared_counts(counts.clone());
+        self.join_cache.set_shared_counts(counts.clone());
+        self.meet_cache.set_shared_counts(counts.clone());
+        self.quotient_cache.set_shared_counts(counts.clone());
+        self.remainder_cache.set_shared_counts(counts.clone());
+        self.offset_cache.set_shared_counts(counts.clone());
+        self.onset_cache.set_shared_counts(counts.clone());
+        self.change_cache.set_shared_counts(counts.clone());
+        self.minimal_cache.set_shared_counts(counts.clone());
+        self.maximal_cache.set_shared_counts(counts.clone());
+        self.nonsubsets_cache.set_shared_counts(counts.clone());
+        self.nonsupersets_cache.set_shared_counts(counts.clone());
+        self.subsets_cache.set_shared_counts(counts.clone());
+        self.supersets_cache.set_shared_counts(counts.clone());
+    }
+
+    /// Whether f in this factory and g in other represent the same family of sets. Within one factory this is just f==g,
+    /// but other may be a different factory, possibly with a different address type. See [BDDFactory::equivalent].
+    pub fn equivalent<A2:NodeAddress>(&self, f: NodeIndex<A,M>, other:&ZDDFactory<A2,M>, g: NodeIndex<A2,M>) -> bool {
+        use xdd_with_multiplicity::XDDBase;
+        self.num_variables==other.num_variables && self.nodes.equivalent(f,&other.nodes,g,&mut HashMap::new())
+    }
+    /// Copy the family root in other into this factory, returning the new root. See [BDDFactory::import_from].
+    pub fn import_from<A2:NodeAddress,M2:Multiplicity>(&mut self, other:&ZDDFactory<A2,M2>, root: NodeIndex<A2,M2>) -> NodeIndex<A,M> where M:From<M2> {
+        use xdd_with_multiplicity::XDDBase;
+        assert!(other.num_variables<=self.num_variables,"Can not import from a factory with {} variables into one with only {}",other.num_variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.import_zdd(&other.nodes,root);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+    /// Make a ZDD for the same function as the BDD root in bdd, which must have the same number of variables.
+    /// See [BDDFactory::bdd_from_zdd].
+    pub fn zdd_from_bdd<A2:NodeAddress,M2:Multiplicity>(&mut self, bdd:&BDDFactory<A2,M2>, root: NodeIndex<A2,M2>) -> NodeIndex<A,M> where M:From<M2> {
+        use xdd_with_multiplicity::XDDBase;
+        assert_eq!(bdd.num_variables,self.num_variables,"Can only convert between factories with the same number of variables");
+        let start = Instant::now();
+        let res = self.nodes.convert_from::<A2,M2,_,false>(&bdd.nodes,root,self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+    /// The sets in either f or g.
+    pub fn union(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.union_zdd(f,g,&mut self.union_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in both f and g.
+    pub fn intersection(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.intersection_zdd(f,g,&mut self.intersection_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in f but not g.
+    pub fn difference(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.difference_zdd(f,g,&mut self.difference_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The unions α ∪ β of a set α in f with a set β in g. Knuth writes this f ⊔ g.
+    pub fn join(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.join_zdd(f,g,&mut self.join_cache,&mut self.union_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The intersections α ∩ β of a set α in f with a set β in g. Knuth writes this f ⊓ g.
+    pub fn meet(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.meet_zdd(f,g,&mut self.meet_cache,&mut self.union_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets α disjoint from every set β in g such that α ∪ β is in f for every such β. This is the largest q such that
+    /// join(g,q) is contained in f, so it acts like division. If g is empty (FALSE), the result is empty.
+    pub fn quotient(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.quotient_zdd(f,g,&mut self.quotient_cache,&mut self.intersection_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// What is left of f after division by g, being the difference of f and join(g,quotient(f,g)).
+    pub fn remainder(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::{Cache, XDDBase};
+        let start = Instant::now();
+        let key = (f.address,g.address);
+        let res = if let Some(res) = self.remainder_cache.get(&key) { res } else {
+            let quotient = self.nodes.quotient_zdd(f,g,&mut self.quotient_cache,&mut self.intersection_cache);
+            let multiple = self.nodes.join_zdd(g,quotient,&mut self.join_cache,&mut self.union_cache);
+            let res = self.nodes.difference_zdd(f,multiple,&mut self.difference_cache);
+            self.remainder_cache.insert(key,res);
+            res
+        };
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The family of all sets of variables, which is every subset of all the variables.
+    /// This is what [DecisionDiagramFactory::not] of FALSE gives, as NodeIndex::TRUE is just the empty set.
+    pub fn universe(&mut self) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.true_regardless_of_variables_below_zdd(VariableIndex(0),self.num_variables);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+    /// The family of every subset of the given variables, which must be sorted. Other variables are never in a set.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(5);
+    /// let variables = [VariableIndex(1),VariableIndex(3),VariableIndex(4)];
+    /// let power_set = factory.all_subsets_of(&variables);
+    /// assert_eq!(8,factory.number_solutions::<u64>(power_set));
+    /// let pairs = factory.all_subsets_of_size_k(&variables,2);
+    /// assert_eq!(3,factory.number_solutions::<u64>(pairs));
+    /// assert_eq!(NodeIndex::TRUE,factory.all_subsets_of(&[]));
+    /// let universe = factory.universe();
+    /// assert_eq!(32,factory.number_solutions::<u64>(universe));
+    /// ```
+    pub fn all_subsets_of(&mut self, variables:&[VariableIndex]) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.subsets_of_size_zdd(variables,0,variables.len());
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+    /// The family of subsets of the given variables, which must be sorted, with exactly k members. Other variables are never in a set.
+    /// Compare [DecisionDiagramFactory::exactly_n_of], in which other variables do not matter.
+    pub fn all_subsets_of_size_k(&mut self, variables:&[VariableIndex], k:usize) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(variables,self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.subsets_of_size_zdd(variables,k,k);
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        res
+    }
+    /// The sets in f that do not contain any other set in f, so no set in the result contains another (an antichain).
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let at_least_two = factory.at_least_n_of(&variables,2);
+    /// let minimal = factory.minimal_elements(at_least_two);
+    /// assert_eq!(factory.exactly_n_of(&variables,2),minimal);
+    /// let maximal = factory.maximal_elements(at_least_two);
+    /// assert_eq!(factory.exactly_n_of(&variables,4),maximal);
+    /// ```
+    pub fn minimal_elements(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.minimal_zdd(f,&mut self.minimal_cache,&mut self.nonsupersets_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in f that are not contained in any other set in f, so no set in the result contains another (an antichain).
+    pub fn maximal_elements(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.maximal_zdd(f,&mut self.maximal_cache,&mut self.nonsubsets_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in f that are a subset of some set in g.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let variables : Vec<VariableIndex> = (0..4).map(VariableIndex).collect();
+    /// let pairs = factory.all_subsets_of_size_k(&variables,2);
+    /// let first_three = factory.all_subsets_of_size_k(&variables[..3],3); // {{0,1,2}}
+    /// let within = factory.subsets_of(pairs,first_three);
+    /// assert_eq!(3,factory.number_solutions::<u64>(within));
+    /// let containing = factory.supersets_of(first_three,pairs);
+    /// assert_eq!(first_three,containing);
+    /// ```
+    pub fn subsets_of(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.subsets_zdd(f,g,&mut self.subsets_cache,&mut self.union_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in f that are a superset of some set in g.
+    pub fn supersets_of(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        let start = Instant::now();
+        let res = self.nodes.supersets_zdd(f,g,&mut self.supersets_cache,&mut self.union_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in f that do not contain variable. Minato calls this subset0.
+    /// Unlike the operations above, this, onset and change keep multiplicities.
+    pub fn offset(&mut self, f: NodeIndex<A,M>, variable: VariableIndex) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.offset_zdd(f,variable,&mut self.offset_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in f that contain variable, with variable removed from them. Minato calls this subset1.
+    pub fn onset(&mut self, f: NodeIndex<A,M>, variable: VariableIndex) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.onset_zdd(f,variable,&mut self.onset_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+    /// The sets in f with variable toggled: added to those without it and removed from those with it.
+    /// # Example
+    /// ```
+    /// use xdd::{DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+    /// let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let just_1 = factory.change(NodeIndex::TRUE,VariableIndex(1)); // {{1}}
+    /// let both = factory.change(just_1,VariableIndex(2)); // {{1,2}}
+    /// let family = factory.union(just_1,both); // {{1},{1,2}}
+    /// assert_eq!(just_1,factory.offset(family,VariableIndex(2)));
+    /// assert_eq!(just_1,factory.onset(family,VariableIndex(2)));
+    /// assert_eq!(family,factory.change(family,VariableIndex(2)));
+    /// assert_eq!(NodeIndex::TRUE,factory.onset(just_1,VariableIndex(1))); // {∅}
+    /// ```
+    pub fn change(&mut self, f: NodeIndex<A,M>, variable: VariableIndex) -> NodeIndex<A,M> {
+        use xdd_with_multiplicity::XDDBase;
+        check_variables(&[variable],self.num_variables);
+        let start = Instant::now();
+        let res = self.nodes.change_zdd(f,variable,&mut self.change_cache);
+        self.statistics.record(OperationClass::Family,start,self.nodes.len());
+        res
+    }
+}
+
+
+
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
+pub struct NodeRenaming<A:NodeAddress>(Vec<A>);
+
+impl <A:NodeAddress> NodeRenaming<A> {
+    /// The renaming from old nodes to the gc'd results of a transformation of them, such as swapping variables.
+    fn for_transformed<M:Multiplicity>(old_len:usize,old:&[NodeIndex<A,M>],transformed:&[NodeIndex<A,M>],gc:&NodeRenaming<A>) -> Self {
+        let mut map = vec![A::FALSE;old_len+2];
+        map[1] = A::TRUE;
+        for (old,new) in old.iter().zip(transformed) {
+            map[old.address.as_usize()] = gc.rename(*new).expect("gc should keep transformed nodes").address;
+        }
+        NodeRenaming(map)
+    }
+    pub fn rename<M:Multiplicity>(&self, index: NodeIndex<A,M>) -> Option<NodeIndex<A,M>> {
+        self.rename_address(index.address).map(|address|NodeIndex {address,multiplicity:index.multiplicity})
+    }
+    /// Like [NodeRenaming::rename] for just an address.
+    pub fn rename_address(&self, address: A) -> Option<A> {
+        let res = self.0[address.as_usize()];
+        if res==A::FALSE && address!=A::FALSE { None } else { Some(res) }
     }
 }
@@ -0,0 +1,145 @@
+//! A read only node store memory mapped from a file saved by [NodeList::save] or a factory's `save`. Requires the `mmap` feature.
+//!
+//! Nodes are decoded from the file as they are asked for, so a huge precomputed diagram can be queried (evaluated,
+//! counted, enumerated...) using the [XDDBase] methods that only read nodes, without first loading it all into memory;
+//! the operating system pages in the parts used. Methods that add nodes panic.
+//!
+//! Opening only checks the header and the file size. The file is trusted to hold a well formed diagram, as it will if
+//! it was written by this library; use [XDDBase::validate] to check a file from elsewhere. As with any memory mapped file,
+//! the file must not be changed while it is mapped, which the compiler can not check, so opening is unsafe.
+//!
+//! # Example
+//! ```
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+//! use xdd::mapped::MappedNodeList;
+//! use xdd::xdd_with_multiplicity::{Solutions, XDDBase};
+//! let variables : Vec<VariableIndex> = (0..10).map(VariableIndex).collect();
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(10);
+//! let f = factory.exactly_n_of(&variables,3);
+//! let path = std::env::temp_dir().join("xdd_mapped_doctest.bin");
+//! factory.save(&path,&[f]).unwrap();
+//! // Safety: nothing else changes the file while it is mapped.
+//! let (nodes,saved) = unsafe { MappedNodeList::<u32,NoMultiplicity>::open_factory(&path) }.unwrap();
+//! assert!(saved.is_bdd);
+//! let root = saved.roots[0];
+//! assert_eq!(120,nodes.number_solutions::<u64,true>(root,saved.num_variables));
+//! assert_eq!(Some(NoMultiplicity{}),nodes.evaluate::<true>(root,&[true,false,true,true]));
+//! assert_eq!(vec![VariableIndex(7),VariableIndex(8),VariableIndex(9)],Solutions::new(&nodes,root,10,false).next().unwrap());
+//! ```
+
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::marker::PhantomData;
+use std::path::Path;
+use memmap2::Mmap;
+use crate::{Multiplicity, Node, NodeAddress, NodeIndex, NodeRenaming, VariableIndex};
+use crate::serialization::{check_header, check_name, invalid_data, read_index, FixedWidth, FACTORY_MAGIC, NODE_LIST_MAGIC};
+use crate::xdd_with_multiplicity::{NodeList, XDDBase};
+
+/// Nodes read directly from a memory mapped file in the format of [crate::serialization].
+pub struct MappedNodeList<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> {
+    map : Mmap,
+    /// The offset in the file of the first node.
+    start : usize,
+    /// The number of bytes each node takes.
+    record : usize,
+    len : usize,
+    phantom : PhantomData<(A,M)>,
+}
+
+/// What a saved factory holds besides the nodes, from [MappedNodeList::open_factory].
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct MappedFactory<A:NodeAddress,M:Multiplicity> {
+    /// Whether it was saved from a [crate::BDDFactory] rather than a [crate::ZDDFactory].
+    pub is_bdd : bool,
+    pub num_variables : u16,
+    /// The roots saved with the factory.
+    pub roots : Vec<NodeIndex<A,M>>,
+}
+
+impl <A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> MappedNodeList<A,M> {
+    /// Map a file saved by [NodeList::save].
+    /// # Safety
+    /// The file must not be changed, truncated or deleted, by this or any other process, until the result is dropped.
+    /// Otherwise the nodes read may change under the caller, or reading them may crash the program.
+    pub unsafe fn open(path:impl AsRef<Path>) -> std::io::Result<Self> {
+        let map = unsafe { Self::map(path)? };
+        Self::from_map(map,0)
+    }
+
+    /// Map a file saved by [crate::BDDFactory::save] or [crate::ZDDFactory::save], also returning what else was saved.
+    /// # Safety
+    /// As for [MappedNodeList::open], the file must not be changed until the result is dropped.
+    pub unsafe fn open_factory(path:impl AsRef<Path>) -> std::io::Result<(Self,MappedFactory<A,M>)> {
+        let map = unsafe { Self::map(path)? };
+        let mut reader : &[u8] = &map;
+        check_header(&mut reader,FACTORY_MAGIC)?;
+        let mut kind = [0u8];
+        reader.read_exact(&mut kind)?;
+        if kind[0]!=b'B' && kind[0]!=b'Z' { return Err(invalid_data(format!("unknown kind of diagram {}",kind[0] as char))); }
+        let num_variables = u16::read_from(&mut reader)?;
+        let num_roots = u64::read_from(&mut reader)?;
+        let roots = (0..num_roots).map(|_|read_index(&mut reader,usize::MAX)).collect::<std::io::Result<Vec<NodeIndex<A,M>>>>()?;
+        let start = map.len()-reader.len();
+        let nodes = Self::from_map(map,start)?;
+        if let Some(root) = roots.iter().find(|root|root.address().as_usize()>=nodes.len+2) {
+            return Err(invalid_data(format!("root {} refers to a node that does not exist",root.address())));
+        }
+        Ok((nodes,MappedFactory{is_bdd:kind[0]==b'B',num_variables,roots}))
+    }
+
+    /// # Safety
+    /// The file must not be changed while the map exists, as required of the callers of [MappedNodeList::open].
+    unsafe fn map(path:impl AsRef<Path>) -> std::io::Result<Mmap> {
+        let file = File::open(path)?;
+        // Safety: the file is only read, and the caller promises it is not changed while it is mapped.
+        unsafe { Mmap::map(&file) }
+    }
+
+    /// The node list in map starting at offset.
+    fn from_map(map:Mmap,offset:usize) -> std::io::Result<Self> {
+        let mut reader : &[u8] = &map[offset..];
+        check_header(&mut reader,NODE_LIST_MAGIC)?;
+        check_name(&mut reader,A::NAME,"address")?;
+        check_name(&mut reader,M::NAME,"multiplicity")?;
+        let len = u64::read_from(&mut reader)?;
+        let start = map.len()-reader.len();
+        let mut child = vec![];
+        NodeIndex::<A,M>::TRUE.address().write_to(&mut child)?;
+        M::ONE.write_to(&mut child)?;
+        let record = 2+2*child.len();
+        if (reader.len() as u64)/(record as u64)<len { return Err(std::io::Error::new(ErrorKind::UnexpectedEof,format!("file is too short for {} nodes",len))); }
+        Ok(MappedNodeList{ map, start, record, len:len as usize, phantom:PhantomData })
+    }
+
+    /// Copy all the nodes into memory, such as to then build more diagrams from them.
+    pub fn to_node_list(&self) -> NodeList<A,M> {
+        let mut res = NodeList::default();
+        for i in 0..self.len { res.add_node(self.node((i+2).try_into().map_err(|_|()).expect("Too many nodes for the NodeAddress type"))); }
+        res
+    }
+}
+
+impl <A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> XDDBase<A,M> for MappedNodeList<A,M> {
+    fn node(&self, index: A) -> Node<A,M> {
+        assert!(!index.is_sink(),"Address {} is a sink, which has no node",index);
+        let i = index.as_usize()-2;
+        assert!(i<self.len,"No node at address {}",index);
+        let mut reader = &self.map[self.start+i*self.record..];
+        let variable = VariableIndex(u16::read_from(&mut reader).unwrap());
+        let lo = read_index(&mut reader,usize::MAX).unwrap();
+        let hi = read_index(&mut reader,usize::MAX).unwrap();
+        Node{variable,lo,hi}
+    }
+    /// Searches all the nodes, so is slow; this store is meant for queries that only read nodes.
+    fn find_node_index(&self, node: Node<A,M>) -> Option<A> {
+        (0..self.len).map(|i|(i+2).try_into().map_err(|_|()).expect("Too many nodes for the NodeAddress type")).find(|&address|self.node(address)==node)
+    }
+    fn add_node(&mut self, _node: Node<A,M>) -> A { panic!("A memory mapped node list is read only") }
+    fn len(&self) -> usize { self.len }
+    /// The mapped file is not counted, as it is not heap memory.
+    fn memory_usage(&self) -> usize { 0 }
+    fn shrink_to_fit(&mut self) {}
+    fn reserve(&mut self, _additional: usize) {}
+    fn gc(&mut self, _keep: impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> { panic!("A memory mapped node list is read only") }
+}
@@ -0,0 +1,145 @@
+//! Output of sequences in forms useful for the [OEIS](https://oeis.org).
+//!
+//! Computing each term of a sequence can take hours, so the b-file writer appends
+//! terms as they finish and can resume a partially written file.
+
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::Path;
+
+/// An OEIS b-file being appended to. Each line is `index value`; lines starting with `#` are comments.
+pub struct BFile {
+    file : File,
+    first_index : i64,
+    /// The length of the file before the first term, which is where any comments at the start end.
+    start : u64,
+    /// term_ends[i] is the length of the file up to the end of the line for term i.
+    term_ends : Vec<u64>,
+}
+
+impl BFile {
+    /// Open a b-file for appending, creating it if it doesn't exist, whose first index is first_index.
+    ///
+    /// If the file already contains terms, they are checked to be consecutive from first_index,
+    /// and new terms will follow them. Use [BFile::len] to find how many terms are already present
+    /// so that a computation can resume where it left off. A last line without a newline was only
+    /// partly written, so is removed.
+    /// # Example
+    /// ```
+    /// use xdd::oeis::BFile;
+    /// let path = std::env::temp_dir().join("xdd_bfile_doctest.txt");
+    /// let _ = std::fs::remove_file(&path);
+    /// let mut b = BFile::open_for_append(&path,1).unwrap();
+    /// b.append(&[1,1,2]).unwrap();
+    /// let mut b = BFile::open_for_append(&path,1).unwrap(); // resume
+    /// assert_eq!(3,b.len());
+    /// b.append(&[5]).unwrap();
+    /// assert_eq!("1 1\n2 1\n3 2\n4 5\n",std::fs::read_to_string(&path).unwrap());
+    /// std::fs::write(&path,"1 1\n2 1\n3 2\n4 5\n5 1").unwrap(); // as if a crash happened writing 5 14.
+    /// assert_eq!(4,BFile::open_for_append(&path,1).unwrap().len());
+    /// assert_eq!("1 1\n2 1\n3 2\n4 5\n",std::fs::read_to_string(&path).unwrap());
+    /// ```
+    pub fn open_for_append(path:impl AsRef<Path>,first_index:i64) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let mut term_ends = vec![];
+        let mut complete = 0; // the length of the file up to the end of the last complete line.
+        let mut start = None;
+        match File::open(path) {
+            Ok(existing) => {
+                let mut reader = BufReader::new(existing);
+                let mut line = String::new();
+                while reader.read_line(&mut line)?>0 {
+                    if !line.ends_with('\n') { break; } // partly written.
+                    complete+=line.len() as u64;
+                    let trimmed = line.trim();
+                    if !(trimmed.is_empty() || trimmed.starts_with('#')) {
+                        if start.is_none() { start = Some(complete-line.len() as u64); }
+                        let next_index = first_index+term_ends.len() as i64;
+                        let index = trimmed.split_whitespace().next().and_then(|i|i.parse::<i64>().ok());
+                        if index!=Some(next_index) {
+                            return Err(std::io::Error::new(ErrorKind::InvalidData,format!("b-file {} has line '{}' where index {} was expected",path.display(),trimmed,next_index)));
+                        }
+                        term_ends.push(complete);
+                    }
+                    line.clear();
+                }
+            }
+            Err(e) if e.kind()==ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len()>complete {
+            file.set_len(complete)?;
+            file.sync_data()?;
+        }
+        Ok(BFile{file,first_index,start:start.unwrap_or(complete),term_ends})
+    }
+
+    /// The number of terms in the file.
+    pub fn len(&self) -> usize { self.term_ends.len() }
+    /// True iff there are no terms in the file.
+    pub fn is_empty(&self) -> bool { self.term_ends.is_empty() }
+    /// The index the next appended term will have.
+    pub fn next_index(&self) -> i64 { self.first_index+self.len() as i64 }
+
+    /// Append some terms, syncing them to disk afterwards so that they survive a crash.
+    pub fn append<T:Display>(&mut self,values:&[T]) -> std::io::Result<()> {
+        let mut lines = String::new();
+        let mut end = self.file.metadata()?.len();
+        let mut ends = Vec::with_capacity(values.len());
+        for (i,v) in values.iter().enumerate() {
+            let line = format!("{} {}\n",self.next_index()+i as i64,v);
+            end+=line.len() as u64;
+            ends.push(end);
+            lines.push_str(&line);
+        }
+        self.file.write_all(lines.as_bytes())?; // one write, so a row is less likely to be partially written.
+        self.file.sync_data()?;
+        self.term_ends.extend(ends);
+        Ok(())
+    }
+
+    /// Remove all but the first len terms, such as a row that was only partly written before a crash.
+    /// Anything after them, including comments, is also removed, but comments before the first term are kept. Does nothing if there are no more than len terms.
+    /// # Example
+    /// ```
+    /// use xdd::oeis::BFile;
+    /// let path = std::env::temp_dir().join("xdd_bfile_truncate_doctest.txt");
+    /// let _ = std::fs::remove_file(&path);
+    /// let mut b = BFile::open_for_append(&path,0).unwrap();
+    /// b.append(&[1,2,3]).unwrap();
+    /// b.truncate(1).unwrap();
+    /// b.append(&[7]).unwrap();
+    /// assert_eq!("0 1\n1 7\n",std::fs::read_to_string(&path).unwrap());
+    /// ```
+    pub fn truncate(&mut self,len:usize) -> std::io::Result<()> {
+        if len<self.len() {
+            let end = if len==0 { self.start } else { self.term_ends[len-1] };
+            self.file.set_len(end)?;
+            self.file.sync_data()?;
+            self.term_ends.truncate(len);
+        }
+        Ok(())
+    }
+}
+
+/// Write a non-negative integer (typically a large one) in scientific notation with the given number of significant figures.
+/// Numbers with no more digits than that are written exactly. The last digit is truncated rather than rounded.
+/// # Example
+/// ```
+/// use xdd::oeis::scientific_notation;
+/// assert_eq!("123",scientific_notation(&123u32,4));
+/// assert_eq!("1.234e5",scientific_notation(&123456u32,4));
+/// assert_eq!("2.65e32",scientific_notation(&xdd::permutation_diagrams::factorial::<num::BigUint>(30),3));
+/// ```
+pub fn scientific_notation<T:Display>(value:&T,significant_figures:usize) -> String {
+    let digits = value.to_string();
+    let significant_figures = significant_figures.max(1);
+    if digits.len()<=significant_figures { digits } else {
+        let mantissa = &digits[..significant_figures];
+        let exponent = digits.len()-1;
+        if significant_figures==1 { format!("{}e{}",mantissa,exponent) }
+        else { format!("{}.{}e{}",&mantissa[..1],&mantissa[1..],exponent) }
+    }
+}
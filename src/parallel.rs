@@ -0,0 +1,182 @@
+//! Parallel versions of poly_and and poly_or, using rayon. Requires the `parallel` feature.
+//!
+//! The operands are combined in a tree, with different subtrees computed on different threads.
+//! All threads add nodes to a single [ConcurrentNodeStore], so the results are canonical and
+//! can be combined. Each thread has its own operation cache.
+//!
+//! Because a parent node can only be created after its children, and addresses are allocated in
+//! order of creation, the nodes stay topologically sorted, as [XDDBase] requires.
+//!
+//! Progress callbacks and cancellation work as for the sequential operations, with nodes made by any thread counted.
+
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Mutex, PoisonError, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use rayon::prelude::*;
+use crate::{BDDFactory, Multiplicity, Node, NodeAddress, NodeIndex, NodeRenaming, ZDDFactory};
+use crate::statistics::{Cancelled, Monitor, OperationClass};
+use crate::xdd_with_multiplicity::{hash_map_memory, shard_for, NodeList, NodeListWithFastLookup, XDDBase};
+
+/// A node store that can be added to by many threads at once. Use through [SharedNodes].
+///
+/// The lookup from nodes to addresses is split into shards by hash, each with its own lock,
+/// so threads adding different nodes rarely wait for each other.
+pub struct ConcurrentNodeStore<A:NodeAddress,M:Multiplicity> {
+    nodes : RwLock<Vec<Node<A,M>>>,
+    lookup : Vec<Mutex<HashMap<Node<A,M>,A>>>,
+    /// Copied from the [NodeListWithFastLookup]; if set, running out of addresses sets capacity_exceeded rather than panicking.
+    fallible : bool,
+    capacity_exceeded : AtomicBool,
+    /// Copied from the [NodeListWithFastLookup], and told of nodes made by all threads.
+    monitor : Mutex<Monitor>,
+}
+
+impl <A:NodeAddress,M:Multiplicity> ConcurrentNodeStore<A,M> {
+    const LOOKUP_SHARDS : usize = 256;
+
+    /// Something that can be used to access the store from one thread.
+    pub fn shared(&self) -> SharedNodes<'_,A,M> { SharedNodes{store:self} }
+}
+
+impl <A:NodeAddress,M:Multiplicity> From<NodeListWithFastLookup<A,M>> for ConcurrentNodeStore<A,M> {
+    fn from(nodes: NodeListWithFastLookup<A,M>) -> Self {
+        let mut lookup : Vec<HashMap<Node<A,M>,A>> = (0..Self::LOOKUP_SHARDS).map(|_|HashMap::new()).collect();
+        for (node,address) in nodes.node_to_index {
+            lookup[shard_for(&node,Self::LOOKUP_SHARDS)].insert(node,address);
+        }
+        ConcurrentNodeStore{ nodes: RwLock::new(nodes.nodes.nodes), lookup: lookup.into_iter().map(Mutex::new).collect(), fallible: nodes.fallible, capacity_exceeded: AtomicBool::new(nodes.capacity_exceeded), monitor: Mutex::new(nodes.monitor) }
+    }
+}
+
+impl <A:NodeAddress,M:Multiplicity> From<ConcurrentNodeStore<A,M>> for NodeListWithFastLookup<A,M> {
+    fn from(store: ConcurrentNodeStore<A,M>) -> Self {
+        // a poisoned lock just means an operation panicked (perhaps in a progress callback); the nodes are still consistent.
+        let mut node_to_index = HashMap::new();
+        for shard in store.lookup { node_to_index.extend(shard.into_inner().unwrap_or_else(PoisonError::into_inner)); }
+        let nodes = NodeList{ nodes: store.nodes.into_inner().unwrap_or_else(PoisonError::into_inner) };
+        NodeListWithFastLookup{ nodes, node_to_index, fallible: store.fallible, capacity_exceeded: store.capacity_exceeded.into_inner(), monitor: store.monitor.into_inner().unwrap_or_else(PoisonError::into_inner) }
+    }
+}
+
+/// A handle to a [ConcurrentNodeStore] usable from one thread. Copy it to give to other threads.
+#[derive(Clone,Copy)]
+pub struct SharedNodes<'a,A:NodeAddress,M:Multiplicity> {
+    store : &'a ConcurrentNodeStore<A,M>,
+}
+
+impl <'a,A:NodeAddress,M:Multiplicity> XDDBase<A,M> for SharedNodes<'a,A,M> {
+    fn node(&self, index: A) -> Node<A,M> { self.store.nodes.read().unwrap()[index.as_usize()-2] }
+    fn find_node_index(&self, node: Node<A,M>) -> Option<A> {
+        self.store.lookup[shard_for(&node,ConcurrentNodeStore::<A,M>::LOOKUP_SHARDS)].lock().unwrap().get(&node).cloned()
+    }
+    /// Add a node, unless another thread added the same node since find_node_index was called, in which case its address is returned.
+    /// If fallible and there is no address for the new node, nothing is added and FALSE is returned as a placeholder.
+    /// If fallible and cancelled, unwind back to [crate::DecisionDiagramFactory::try_operation].
+    fn add_node(&mut self, node: Node<A,M>) -> A {
+        if self.store.fallible && self.store.monitor.lock().unwrap().is_cancelled() { std::panic::resume_unwind(Box::new(Cancelled)); }
+        let mut shard = self.store.lookup[shard_for(&node,ConcurrentNodeStore::<A,M>::LOOKUP_SHARDS)].lock().unwrap();
+        if let Some(&address) = shard.get(&node) { return address; }
+        let mut nodes = self.store.nodes.write().unwrap();
+        let Ok(address) = A::try_from(2+nodes.len()) else {
+            // release the locks first so a panic does not poison them.
+            drop(nodes);
+            drop(shard);
+            if self.store.fallible {
+                self.store.capacity_exceeded.store(true,Ordering::Relaxed);
+                return A::FALSE;
+            }
+            panic!("Too many nodes for the NodeAddress type");
+        };
+        nodes.push(node);
+        let len = nodes.len();
+        drop(nodes);
+        shard.insert(node,address);
+        drop(shard);
+        self.store.monitor.lock().unwrap().node_made(node.variable.0,len);
+        address
+    }
+    fn len(&self) -> usize { self.store.nodes.read().unwrap().len() }
+    fn memory_usage(&self) -> usize {
+        self.store.nodes.read().unwrap().capacity()*size_of::<Node<A,M>>()+self.store.lookup.iter().map(|shard|hash_map_memory(&shard.lock().unwrap())).sum::<usize>()
+    }
+    fn shrink_to_fit(&mut self) {
+        self.store.nodes.write().unwrap().shrink_to_fit();
+        for shard in &self.store.lookup { shard.lock().unwrap().shrink_to_fit(); }
+    }
+    fn reserve(&mut self, additional: usize) {
+        self.store.nodes.write().unwrap().reserve(additional);
+        for shard in &self.store.lookup { shard.lock().unwrap().reserve(additional.div_ceil(ConcurrentNodeStore::<A,M>::LOOKUP_SHARDS)); }
+    }
+    /// Not possible while other threads may be using the nodes. Convert the store back to a [NodeListWithFastLookup] first.
+    fn gc(&mut self, _keep: impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
+        panic!("Can not garbage collect a ConcurrentNodeStore while it is shared")
+    }
+}
+
+/// Run operation on nodes converted to a [ConcurrentNodeStore], converting them back afterwards even if it unwinds, as a
+/// cancelled operation does.
+fn with_concurrent_store<A:NodeAddress,M:Multiplicity,R>(nodes:&mut NodeListWithFastLookup<A,M>,operation:impl FnOnce(&ConcurrentNodeStore<A,M>)->R) -> R {
+    let store = ConcurrentNodeStore::from(std::mem::take(nodes));
+    let res = std::panic::catch_unwind(AssertUnwindSafe(||operation(&store)));
+    *nodes = store.into();
+    res.unwrap_or_else(|payload|std::panic::resume_unwind(payload))
+}
+
+/// Combine the operands in parallel with op, which must be associative. None if there are no operands.
+fn par_combine<A:NodeAddress+Send+Sync,M:Multiplicity+Send+Sync,F:Fn(&mut SharedNodes<A,M>,NodeIndex<A,M>,NodeIndex<A,M>,&mut HashMap<(NodeIndex<A,M>,NodeIndex<A,M>),NodeIndex<A,M>>)->NodeIndex<A,M>+Sync>(store:&ConcurrentNodeStore<A,M>,indices:&[NodeIndex<A,M>],op:F) -> Option<NodeIndex<A,M>> {
+    indices.par_iter().map(|&index|Some(index)).reduce(||None,|a,b|{
+        match (a,b) {
+            (Some(a),Some(b)) => Some(op(&mut store.shared(),a,b,&mut HashMap::new())),
+            (a,None) => a,
+            (None,b) => b,
+        }
+    })
+}
+
+impl <A:NodeAddress+Default+Send+Sync,M:Multiplicity+Send+Sync> BDDFactory<A,M> {
+    /// Like [crate::DecisionDiagramFactory::poly_and], but using multiple threads.
+    ///
+    /// The node store is converted to and from a [ConcurrentNodeStore], which takes time proportional to the number of
+    /// nodes, so this is only worthwhile for large computations.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(10);
+    /// let variables : Vec<VariableIndex> = (0..10).map(VariableIndex).collect();
+    /// let constraints : Vec<_> = (0..8).map(|i|factory.at_most_n_of(&variables[i..i+3],1)).collect();
+    /// let all = factory.par_poly_and(&constraints).unwrap();
+    /// assert_eq!(Some(all),factory.poly_and(&constraints));
+    /// ```
+    pub fn par_poly_and(&mut self, indices:&[NodeIndex<A,M>]) -> Option<NodeIndex<A,M>> {
+        let start = Instant::now();
+        let res = with_concurrent_store(&mut self.nodes,|store|par_combine(store,indices,|nodes,a,b,cache|nodes.mul_bdd(a,b,cache)));
+        self.statistics.record(OperationClass::And,start,self.nodes.len());
+        res
+    }
+    /// Like [BDDFactory::par_poly_and], but or rather than and.
+    pub fn par_poly_or(&mut self, indices:&[NodeIndex<A,M>]) -> Option<NodeIndex<A,M>> {
+        let start = Instant::now();
+        let res = with_concurrent_store(&mut self.nodes,|store|par_combine(store,indices,|nodes,a,b,cache|nodes.sum_bdd(a,b,cache)));
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
+    }
+}
+
+impl <A:NodeAddress+Send+Sync,M:Multiplicity+Send+Sync> ZDDFactory<A,M> {
+    /// Like [crate::DecisionDiagramFactory::poly_and], but using multiple threads. See [BDDFactory::par_poly_and].
+    pub fn par_poly_and(&mut self, indices:&[NodeIndex<A,M>]) -> Option<NodeIndex<A,M>> {
+        let start = Instant::now();
+        let res = with_concurrent_store(&mut self.nodes,|store|par_combine(store,indices,|nodes,a,b,cache|nodes.mul_zdd(a,b,cache)));
+        self.statistics.record(OperationClass::And,start,self.nodes.len());
+        res
+    }
+    /// Like [ZDDFactory::par_poly_and], but or rather than and.
+    pub fn par_poly_or(&mut self, indices:&[NodeIndex<A,M>]) -> Option<NodeIndex<A,M>> {
+        let start = Instant::now();
+        let res = with_concurrent_store(&mut self.nodes,|store|par_combine(store,indices,|nodes,a,b,cache|nodes.sum_zdd(a,b,cache)));
+        self.statistics.record(OperationClass::Or,start,self.nodes.len());
+        res
+    }
+}
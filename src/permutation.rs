@@ -8,6 +8,7 @@ pub type PermutedItem = u32;
 /// A permutation π = (π(1),π(2),…,π(n)) on n elements
 /// Note that indices are 1 based to match general convention for permutations!
 #[derive(Clone,Debug,Eq, PartialEq)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 pub struct Permutation {
     /// The representation permutation of the integers 1..n
     pub sequence : Vec<PermutedItem>
@@ -43,6 +44,20 @@ impl Permutation {
         }
     }
 
+    /// The inverse permutation π⁻¹, such that π·π⁻¹ is the identity.
+    /// # Example
+    /// ```
+    /// use xdd::permutation::Permutation;
+    /// let x = Permutation { sequence: vec![4,5,2,1,3] };
+    /// assert_eq!(vec![4,3,5,1,2],x.inverse().sequence);
+    /// assert_eq!(vec![1,2,3,4,5],x.compose(&x.inverse()).sequence);
+    /// ```
+    pub fn inverse(&self) -> Permutation {
+        let mut sequence = vec![0;self.n()];
+        for (i,&e) in self.sequence.iter().enumerate() { sequence[e as usize-1]=(i+1) as PermutedItem; }
+        Permutation{sequence}
+    }
+
     /// Check to see if this is a valid permutation.
     ///
     /// That means it must contain each number 1..n once.
@@ -12,16 +12,19 @@
 
 
 use std::collections::HashMap;
-
-
-use std::fmt::{Display, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use std::io::Write;
 use std::marker::PhantomData;
-use std::ops::{Div, Index, MulAssign};
-use num::Num;
-use crate::{DecisionDiagramFactory, Node, NodeIndex, NodeRenaming, VariableIndex, ZDDFactory, NodeAddress, Multiplicity, GeneratingFunctionWithMultiplicity};
-use crate::permutation::PermutedItem;
-use crate::xdd_with_multiplicity::XDDBase;
+use std::ops::{AddAssign, Div, Index, MulAssign};
+use num::{Integer, Num};
+use crate::{BooleanOperator, Comparison, DecisionDiagramFactory, Node, NodeIndex, NodeRenaming, VariableIndex, ZDDFactory, NodeAddress, Multiplicity, GeneratingFunctionWithMultiplicity};
+pub use crate::permutation::PermutedItem;
+use crate::permutation::Permutation;
+use crate::xdd_with_multiplicity::{BinaryOperationCache, Cache, OperationCache, PostOrder, XDDBase};
+use crate::statistics::{CacheStatistics, DiagramProfile, FactoryStatistics};
+use crate::typed_variables::Var;
+use crate::roots::Handle;
+use crate::generating_function::{CheckedCount, CountOverflow, GeneratingFunction, SingleVariableGeneratingFunction};
 
 
 
@@ -83,6 +86,11 @@ pub struct Swap {}
 #[derive(Copy, Clone,Eq, PartialEq,Debug)]
 pub struct LeftRotation {}
 
+/// A variable in a πDD, representing a transposition (swap).
+pub type TranspositionVar = Var<Swap>;
+/// A variable in a Rot-πDD, representing a left rotation.
+pub type RotationVar = Var<LeftRotation>;
+
 /// A permutation can be encoded as a set of variables by defining a basis of permutations
 /// such that each permutations is encoded by exactly one composition of a set of such
 /// variables ordered in a canonical manner.
@@ -108,6 +116,32 @@ impl <I> PermutationElement<I> {
     }
 }
 
+impl PermutationElement<Swap> {
+    /// Get a collection of transpositions that produce the given permutation, being its canonical decomposition.
+    /// panics if the permutation argument is not a permutation of 1..n.
+    /// # Example
+    /// ```
+    /// use xdd::permutation_diagrams::{PermutationElement, Swap};
+    /// let res = PermutationElement::<Swap>::get_permutation(&[4, 3, 1, 5, 2]);
+    /// assert_eq!(res,vec![PermutationElement::new(2,5),PermutationElement::new(2,4),PermutationElement::new(1,3),PermutationElement::new(1,2)])
+    /// ```
+    pub fn get_permutation(permutation:&[PermutedItem]) -> Vec<Self> {
+        let n = permutation.len();
+        let mut res = Vec::new();
+        let mut sofar : Vec<PermutedItem> = (1..=n as PermutedItem).collect();
+        for j in (0..n).rev() {
+            // make sure element j is correct.
+            if permutation[j]!=sofar[j] {
+                let position = sofar.iter().position(|&e|e==permutation[j]).expect("Input was not a permutation");
+                assert!(position<j);
+                res.push(PermutationElement::new((position+1) as PermutedItem,(j+1) as PermutedItem)); // +1 as PermutedItem is 1 based, and position and j are 0 based.
+                sofar.swap(position,j);
+            }
+        }
+        res
+    }
+}
+
 impl PermutationElement<LeftRotation> {
     /// Get a collection of LeftRotations that produce the given permutation.
     /// panics if the permutation argument is not a permutation of 1..n.
@@ -209,6 +243,60 @@ impl <I> PermutationEncodingAsVariables<I> {
         let elements_in_rows = (self.n-1+self.n-rows)*rows/2; // the number of elements in the skipped rows.
         VariableIndex((i-1+elements_in_rows) as u16)
     }
+    /// Like [PermutationEncodingAsVariables::variable] except tagged with the interpretation, so that
+    /// it cannot be accidentally used with an unrelated factory.
+    /// # Example
+    /// ```
+    /// use xdd::permutation_diagrams::{PermutationEncodingAsVariables, Swap, TranspositionVar};
+    /// let enc = PermutationEncodingAsVariables::<Swap>::new(4);
+    /// assert_eq!(enc.typed_variable(2,3),TranspositionVar::new(4));
+    /// ```
+    pub fn typed_variable(&self,i:PermutedItem,j:PermutedItem) -> Var<I> {
+        Var::new(self.variable(i,j).0)
+    }
+}
+
+impl PermutationEncodingAsVariables<Swap> {
+    /// The permutation represented by a solution of a πDD, being the sorted list of variables that are true.
+    /// Starting from the identity, the swaps are applied to the positions in order of variable.
+    /// # Example
+    /// ```
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{PermutationEncodingAsVariables, Swap};
+    /// let enc = PermutationEncodingAsVariables::<Swap>::new(4);
+    /// assert_eq!(Permutation{sequence:vec![1,2,3,4]},enc.decode(&[]));
+    /// assert_eq!(Permutation{sequence:vec![2,4,3,1]},enc.decode(&[enc.variable(1,4),enc.variable(1,2)]));
+    /// ```
+    pub fn decode(&self,solution:&[VariableIndex]) -> Permutation {
+        let mut sequence : Vec<PermutedItem> = (1..=self.n).collect();
+        for &v in solution {
+            let e = self[v];
+            sequence.swap((e.elem1-1) as usize,(e.elem2-1) as usize);
+        }
+        Permutation{sequence}
+    }
+}
+
+impl PermutationEncodingAsVariables<LeftRotation> {
+    /// The permutation represented by a solution of a Rot-πDD, being the sorted list of variables that are true.
+    /// The inverse of [PermutationElement::<LeftRotation>::get_permutation].
+    /// # Example (see example 4.1.2 in Yuma Inoue's thesis)
+    /// ```
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{LeftRotation, PermutationEncodingAsVariables};
+    /// let enc = PermutationEncodingAsVariables::<LeftRotation>::new(5);
+    /// let solution = [enc.variable(2,5),enc.variable(1,3),enc.variable(1,2)];
+    /// assert_eq!(Permutation{sequence:vec![4,3,1,5,2]},enc.decode(&solution));
+    /// ```
+    pub fn decode(&self,solution:&[VariableIndex]) -> Permutation {
+        let mut sequence : Vec<PermutedItem> = (1..=self.n).collect();
+        for &v in solution {
+            let e = self[v];
+            let extracted = sequence.remove((e.elem1-1) as usize);
+            sequence.insert((e.elem2-1) as usize,extracted);
+        }
+        Permutation{sequence}
+    }
 }
 
 impl <I> Index<VariableIndex> for PermutationEncodingAsVariables<I> {
@@ -222,8 +310,9 @@ impl <I> Index<VariableIndex> for PermutationEncodingAsVariables<I> {
 pub struct PermutationDecisionDiagramFactory<I,A:NodeAddress,M:Multiplicity> {
     pub zdd : ZDDFactory<A,M>,
     pub vars : PermutationEncodingAsVariables<I>,
-    i_cache : HashMap<(NodeIndex<A,M>, VariableIndex), NodeIndex<A,M>>, // cache of the "I" operation
-    compose_cache : HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>, // cache of the compose/cross product operation
+    i_cache : OperationCache<(NodeIndex<A,M>, VariableIndex), NodeIndex<A,M>>, // cache of the "I" operation
+    compose_cache : BinaryOperationCache<A,M>, // cache of the compose/cross product operation
+    inverse_cache : OperationCache<NodeIndex<A,M>, NodeIndex<A,M>>, // cache of the inverse operation
 }
 
 impl <I,A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<I,A,M> {
@@ -232,23 +321,132 @@ impl <I,A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<I,A,M> {
     /// total number of variables will be (num_elements_in_permutation-1)(num_elements_in_permutation-2)/2.
     pub fn new(num_elements_in_permutation: u16) -> Self {
         let vars = PermutationEncodingAsVariables::new(num_elements_in_permutation as PermutedItem);
-        PermutationDecisionDiagramFactory{ zdd: ZDDFactory::new(vars.num_variables()), vars, i_cache:Default::default(), compose_cache: Default::default() }
+        PermutationDecisionDiagramFactory{ zdd: ZDDFactory::new(vars.num_variables()), vars, i_cache:Default::default(), compose_cache: Default::default(), inverse_cache: Default::default() }
     }
 
     // Standard DD operations just delegate to the underlying ZDD. But does not implement DecisionDiagramFactory as it is not really one.
 
     pub fn and(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.and(index1, index2) }
     pub fn or(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.or(index1, index2) }
+    pub fn xor(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.xor(index1, index2) }
+    pub fn apply(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, op:BooleanOperator) -> NodeIndex<A,M> { self.zdd.apply(index1, index2, op) }
+    pub fn scale(&mut self, f: NodeIndex<A,M>, m:M) -> NodeIndex<A,M> { self.zdd.scale(f, m) }
+    pub fn linear_combination(&mut self, terms:&[(NodeIndex<A,M>,M)]) -> NodeIndex<A,M> { self.zdd.linear_combination(terms) }
+    pub fn subtract(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.subtract(f, g) }
+    pub fn support(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.support(f) }
+    pub fn filter_multiplicity(&mut self, f: NodeIndex<A,M>, predicate: impl Fn(M) -> bool) -> NodeIndex<A,M> { self.zdd.filter_multiplicity(f, predicate) }
     pub fn not(&mut self, index: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.not(index) }
+    pub fn reduce(&mut self, root: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.reduce(root) }
+    pub fn validate(&self, root: NodeIndex<A,M>) -> Result<(),crate::xdd_with_multiplicity::InvalidDiagram<A>> { self.zdd.validate(root) }
+    pub fn ite(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>) -> NodeIndex<A,M> { self.zdd.ite(f, g, h) }
+    pub fn restrict(&mut self, f: NodeIndex<A,M>, variable: VariableIndex, value: bool) -> NodeIndex<A,M> { self.zdd.restrict(f, variable, value) }
     pub fn number_solutions<G: GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>) -> G { self.zdd.number_solutions::<G>(index) }
+    pub fn number_solutions_many<G: GeneratingFunctionWithMultiplicity<M>>(&self, roots: &[NodeIndex<A,M>]) -> Vec<G> { self.zdd.number_solutions_many::<G>(roots) }
+    pub fn checked_number_solutions<T>(&self, index: NodeIndex<A,M>) -> Result<T,CountOverflow> where CheckedCount<T>: GeneratingFunctionWithMultiplicity<M> { self.zdd.checked_number_solutions::<T>(index) }
     pub fn single_variable(&mut self, variable: VariableIndex) -> NodeIndex<A,M> { self.zdd.single_variable(variable) }
     pub fn len(&self) -> usize { self.zdd.len() }
+    pub fn is_empty(&self) -> bool { self.zdd.is_empty() }
     pub fn exactly_one_of(&mut self, variables: &[VariableIndex]) -> NodeIndex<A,M> { self.zdd.exactly_one_of(variables) }
+    pub fn exactly_n_of(&mut self, variables: &[VariableIndex], n: usize) -> NodeIndex<A,M> { self.zdd.exactly_n_of(variables, n) }
+    pub fn between_n_and_m_of(&mut self, variables: &[VariableIndex], at_least: usize, at_most: usize) -> NodeIndex<A,M> { self.zdd.between_n_and_m_of(variables, at_least, at_most) }
+    pub fn at_most_n_of(&mut self, variables: &[VariableIndex], n: usize) -> NodeIndex<A,M> { self.zdd.at_most_n_of(variables, n) }
+    pub fn at_least_n_of(&mut self, variables: &[VariableIndex], n: usize) -> NodeIndex<A,M> { self.zdd.at_least_n_of(variables, n) }
+    pub fn linear_constraint(&mut self, terms: &[(VariableIndex, i64)], comparison: Comparison, bound: i64) -> NodeIndex<A,M> { self.zdd.linear_constraint(terms, comparison, bound) }
+    pub fn node(&self, address: A) -> Node<A,M> { self.zdd.node(address) }
+    pub fn post_order(&self, index: NodeIndex<A,M>) -> PostOrder<A> { self.zdd.post_order(index) }
+    pub fn profile(&self, index: NodeIndex<A,M>) -> DiagramProfile { self.zdd.profile(index) }
 
     pub fn gc(&mut self, keep: impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
-        self.i_cache.clear();
-        self.compose_cache.clear();
-        self.zdd.gc(keep)
+        let renaming = self.zdd.gc(keep);
+        self.i_cache.rename(&renaming);
+        self.compose_cache.rename(&renaming);
+        self.inverse_cache.rename(&renaming);
+        renaming
+    }
+    /// Like [DecisionDiagramFactory::root].
+    pub fn root(&mut self, index: NodeIndex<A,M>) -> Handle<A,M> { self.zdd.root(index) }
+    /// Like [DecisionDiagramFactory::gc_roots], including the permutation specific caches.
+    pub fn gc_roots(&mut self) -> NodeRenaming<A> { self.gc(std::iter::empty()) }
+    /// Statistics about the work done, including the permutation specific caches.
+    /// # Example
+    /// ```
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,u32>::new(4);
+    /// let all = factory.construct_all_permutations();
+    /// factory.compose(all,all);
+    /// let summary = factory.summary();
+    /// assert_eq!(factory.len(),summary.current_nodes);
+    /// assert!(summary.caches.iter().any(|(name,cache)|*name=="compose" && cache.misses>0));
+    /// println!("{}",summary);
+    /// ```
+    pub fn summary(&self) -> FactoryStatistics {
+        let mut res = self.zdd.summary();
+        res.caches = self.cache_stats();
+        res
+    }
+    /// Statistics for each operation cache, including the permutation specific ones.
+    pub fn cache_stats(&self) -> Vec<(&'static str,CacheStatistics)> {
+        let mut res = self.zdd.cache_stats();
+        res.push(("permute",CacheStatistics::of(&self.i_cache)));
+        res.push(("compose",CacheStatistics::of(&self.compose_cache)));
+        res.push(("inverse",CacheStatistics::of(&self.inverse_cache)));
+        res
+    }
+    /// Like [DecisionDiagramFactory::set_cache_capacity], sharing the capacity with the permutation specific caches.
+    pub fn set_cache_capacity(&mut self, capacity:Option<usize>) {
+        let zdd_caches = self.zdd.cache_stats().len();
+        let each = capacity.map(|c|c/(zdd_caches+3));
+        self.zdd.set_cache_capacity(each.map(|e|e*zdd_caches));
+        self.i_cache.set_capacity(each);
+        self.compose_cache.set_capacity(each);
+        self.inverse_cache.set_capacity(each);
+    }
+    /// Like [DecisionDiagramFactory::memory_usage], including the permutation specific caches.
+    pub fn memory_usage(&self) -> usize {
+        self.zdd.memory_usage()+self.i_cache.memory_usage()+self.compose_cache.memory_usage()+self.inverse_cache.memory_usage()
+    }
+    /// Like [DecisionDiagramFactory::shrink_to_fit], including the permutation specific caches.
+    pub fn shrink_to_fit(&mut self) {
+        self.zdd.shrink_to_fit();
+        self.i_cache.shrink_to_fit();
+        self.compose_cache.shrink_to_fit();
+        self.inverse_cache.shrink_to_fit();
+    }
+    pub fn reserve(&mut self, additional: usize) { self.zdd.reserve(additional) }
+
+    /// Count the permutations in p separated by a statistic, the sum of weight(e) over the elements e (transpositions
+    /// or rotations) in its decomposition. Element i of the result is the number of permutations with statistic i.
+    ///
+    /// A [GeneratingFunction] can not do this as it has no way to be told the weights. With every weight 1, this is the same
+    /// as [PermutationDecisionDiagramFactory::number_solutions] with a [SingleVariableGeneratingFunction], the number
+    /// of elements used. For a Rot-πDD, the weight r-l for ρ(l,r) gives the number of inversions; see
+    /// [PermutationDecisionDiagramFactory::<LeftRotation,A,M>::inversions_generating_function].
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::generating_function::SingleVariableGeneratingFunction;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(4);
+    /// let s_n = factory.construct_all_permutations();
+    /// // The number of transpositions used is n less the number of cycles, counted by the Stirling numbers of the first kind.
+    /// let transpositions = factory.statistic_generating_function::<u64>(s_n,|_|1);
+    /// assert_eq!(SingleVariableGeneratingFunction(vec![1,6,11,6]),transpositions);
+    /// assert_eq!(transpositions,factory.number_solutions(s_n));
+    /// ```
+    pub fn statistic_generating_function<E:Clone+Eq+Debug+Integer+AddAssign>(&self, p: NodeIndex<A,M>, weight:impl Fn(&PermutationElement<I>)->usize) -> SingleVariableGeneratingFunction<E> where SingleVariableGeneratingFunction<E>:GeneratingFunctionWithMultiplicity<M> {
+        let mut done : HashMap<A,SingleVariableGeneratingFunction<E>> = HashMap::new();
+        let value = |done:&HashMap<A,SingleVariableGeneratingFunction<E>>,index:NodeIndex<A,M>| {
+            let found = if index.is_false() { SingleVariableGeneratingFunction::zero() } else if index.is_true() { SingleVariableGeneratingFunction::one() } else { done[&index.address].clone() };
+            found.multiply(index.multiplicity)
+        };
+        for address in self.post_order(p) {
+            let node = self.node(address);
+            let lo = value(&done,node.lo);
+            let mut hi = value(&done,node.hi);
+            for _ in 0..weight(&self.vars[node.variable]) { hi = hi.variable_set(node.variable); }
+            done.insert(address,lo.add(hi));
+        }
+        value(&done,p)
     }
     /*
         fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self,writer:&mut W,name:impl Display,start_nodes:&[(NodeIndex,Option<String>)],namer:F) -> std::io::Result<()> {
@@ -268,6 +466,10 @@ impl <I,A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<I,A,M> w
     pub fn make_dot_file_default_names<W:Write>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)]) -> std::io::Result<()> {
         self.zdd.make_dot_file(writer,name,start_nodes,|v|self.vars[v].to_string())
     }
+    /// Like make_dot_file_default_names, but JSON. See [DecisionDiagramFactory::make_json_file].
+    pub fn make_json_file_default_names<W:Write>(&self, writer:&mut W, start_nodes:&[(NodeIndex<A,M>, Option<String>)]) -> std::io::Result<()> {
+        self.zdd.make_json_file(writer,start_nodes,|v|self.vars[v].to_string())
+    }
 }
 impl <A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<Swap,A,M> {
     /// Perform the SWAP operation on a πDD. That is, convert the permutations
@@ -300,7 +502,7 @@ impl <A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<Swap,A,M>
                 if node_variable.elem2 < j { self.create(variable, NodeIndex::FALSE, node_index) } // this is something lower down the diagram than the variable.
                 else {
                     let cache_key = (node_index, variable);
-                    if let Some(cached_answer) = self.i_cache.get(&cache_key) { *cached_answer } else {
+                    if let Some(cached_answer) = self.i_cache.get(&cache_key) { cached_answer } else {
                         let lo = self.swap(node.lo, i, j); // if we don't use node_variable, simple.
                         let hi1 = self.swap(node.hi, i, if j == node_variable.elem2 { node_variable.elem1 } else { j });
                         let hi = self.swap(hi1, if node_variable.elem1 == j { i } else if node_variable.elem1 == i { j } else { node_variable.elem1 }, node_variable.elem2);
@@ -339,7 +541,7 @@ impl <A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<Swap,A,M>
     pub fn compose(&mut self, p: NodeIndex<A,M>, q: NodeIndex<A,M>) -> NodeIndex<A,M> {
         if p.is_false() || q.is_false() { NodeIndex::FALSE } else if p.is_true() { q.multiply(p.multiplicity) } else if q.is_true() { p.multiply(q.multiplicity) } else {
             let cache_key = (p,q);
-            if let Some(cached_answer) = self.compose_cache.get(&cache_key) { *cached_answer } else {
+            if let Some(cached_answer) = self.compose_cache.get(&cache_key) { cached_answer } else {
                 let q_node = self.zdd.nodes.node_incorporating_multiplicity(q);
                 let q_var = self.vars[q_node.variable];
                 let lo = self.compose(p, q_node.lo);
@@ -352,6 +554,38 @@ impl <A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<Swap,A,M>
         }
     }
 
+    /// Compute the inverses of the permutations in a πDD. That is, if p represents a set of permutations P,
+    /// then make { π⁻¹ | π∈P }, with the same multiplicities.
+    ///
+    /// If P = P_0 ∪ P_1·τ where τ is the top variable, then the inverses are P_0⁻¹ ∪ τ·P_1⁻¹, as τ is its own inverse.
+    /// # Example
+    /// ```
+    /// use xdd::{NodeIndex, NoMultiplicity};
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(4);
+    /// let swap12 = factory.swap(NodeIndex::TRUE,1,2);
+    /// let swap12_23 = factory.swap(swap12,2,3); // τ₁,₂·τ₂,₃
+    /// let swap23 = factory.swap(NodeIndex::TRUE,2,3);
+    /// let swap23_12 = factory.swap(swap23,1,2); // τ₂,₃·τ₁,₂
+    /// assert_eq!(swap23_12,factory.inverse(swap12_23));
+    /// assert_eq!(NodeIndex::TRUE,factory.compose(swap12_23,swap23_12));
+    /// let s_n = factory.construct_all_permutations();
+    /// assert_eq!(s_n,factory.inverse(s_n));
+    /// ```
+    pub fn inverse(&mut self, p: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        if p.is_sink() { p } else if let Some(cached_answer) = self.inverse_cache.get(&p) { cached_answer } else {
+            let node = self.zdd.nodes.node_incorporating_multiplicity(p);
+            let var = self.vars[node.variable];
+            let lo = self.inverse(node.lo);
+            let hi = self.inverse(node.hi);
+            let element = self.swap(NodeIndex::TRUE, var.elem1, var.elem2);
+            let hi = self.compose(element, hi);
+            let res = self.or(lo, hi);
+            self.inverse_cache.insert(p,res);
+            res
+        }
+    }
+
     /// Construct the set of all permutations.
     /// # Example
     /// ```
@@ -373,6 +607,315 @@ impl <A:NodeAddress,M:Multiplicity> PermutationDecisionDiagramFactory<Swap,A,M>
         }
         res
     }
+
+    /// Get a set containing the single specified permutation.
+    /// panics if the permutation argument is not a permutation of 1..n.
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(5);
+    /// let p = factory.compute_for_single_permutation(&[4,3,1,5,2]);
+    /// assert_eq!(1,factory.number_solutions::<u64>(p));
+    /// assert_eq!(Some(Permutation{sequence:vec![4,3,1,5,2]}),factory.permutations(p).next());
+    /// ```
+    pub fn compute_for_single_permutation(&mut self,permutation:&[PermutedItem]) -> NodeIndex<A,M> {
+        let decomposition = PermutationElement::<Swap>::get_permutation(permutation);
+        let mut res = NodeIndex::TRUE;
+        for e in decomposition.iter().rev() {
+            res = self.swap(res,e.elem1,e.elem2);
+        }
+        res
+    }
+
+    /// Iterate over the permutations in a πDD, in the order of [DecisionDiagramFactory::solutions]. Multiplicities are ignored.
+    /// # Example
+    /// ```
+    /// use xdd::{NodeIndex, NoMultiplicity};
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(3);
+    /// let swap12 = factory.swap(NodeIndex::TRUE,1,2);
+    /// let swap12_23 = factory.swap(swap12,2,3);
+    /// let both = factory.or(NodeIndex::TRUE,swap12_23);
+    /// let permutations : Vec<Permutation> = factory.permutations(both).collect();
+    /// assert_eq!(vec![Permutation{sequence:vec![1,2,3]},Permutation{sequence:vec![3,1,2]}],permutations); // τ₁,₂·τ₂,₃
+    /// ```
+    pub fn permutations(&self, p: NodeIndex<A,M>) -> impl Iterator<Item=Permutation>+'_ {
+        self.zdd.solutions(p).map(|solution|self.vars.decode(&solution))
+    }
+
+    /// The n rotations (1,2,…,n), (2,3,…,n,1), …, (n,1,…,n-1), being the cyclic group C_n.
+    pub fn cyclic_group(&mut self) -> NodeIndex<A,M> {
+        let n = self.vars.n;
+        let mut res = NodeIndex::FALSE;
+        for k in 0..n.max(1) { // the group on 0 elements is just the identity.
+            let rotation : Vec<PermutedItem> = (0..n).map(|i|(i+k)%n+1).collect();
+            let rotation = self.compute_for_single_permutation(&rotation);
+            res = self.or(res,rotation);
+        }
+        res
+    }
+
+    /// The symmetries of a regular n-gon with vertices numbered in order, being the dihedral group D_n,
+    /// which is the rotations and their compositions with the reversal (n,…,2,1). Each has multiplicity 1.
+    pub fn dihedral_group(&mut self) -> NodeIndex<A,M> {
+        let rotations = self.cyclic_group();
+        let reversal : Vec<PermutedItem> = (1..=self.vars.n).rev().collect();
+        let reversal = self.compute_for_single_permutation(&reversal);
+        let reflections = self.compose(rotations,reversal);
+        let res = self.or(rotations,reflections);
+        self.support(res) // for n<=2 some reflections are rotations.
+    }
+
+    /// The even permutations, being the alternating group A_n.
+    /// Each variable is a transposition, so a permutation is even iff it has an even number of variables.
+    pub fn even_permutations(&mut self) -> NodeIndex<A,M> {
+        let all = self.construct_all_permutations();
+        let mut odd = NodeIndex::FALSE;
+        for v in 0..self.vars.num_variables() {
+            let variable = VariableIndex(v);
+            let x = self.single_variable(variable);
+            odd = self.xor(odd,x);
+        }
+        let even = self.not(odd);
+        self.and(all,even)
+    }
+
+    /// The smallest set of permutations containing the identity and generators, and closed under composition. This is the
+    /// group generated by generators, found by composing with the generators until nothing new is found. Each has multiplicity 1.
+    pub fn closure_under_composition(&mut self, generators: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let generators = self.support(generators);
+        let mut res = self.or(NodeIndex::TRUE,generators);
+        res = self.support(res);
+        loop {
+            let extended = self.compose(res,generators);
+            let extended = self.or(res,extended);
+            let extended = self.support(extended);
+            if extended==res { return res; }
+            res = extended;
+        }
+    }
+
+    /// The group generated by the given permutations of 1..n. See [PermutationDecisionDiagramFactory::<Swap,A,M>::closure_under_composition].
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation_diagrams::{factorial, PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(5);
+    /// let cyclic = factory.cyclic_group();
+    /// assert_eq!(5,factory.number_solutions::<u64>(cyclic));
+    /// assert_eq!(cyclic,factory.group_generated_by(&[&[2,3,4,5,1]]));
+    /// let dihedral = factory.dihedral_group();
+    /// assert_eq!(10,factory.number_solutions::<u64>(dihedral));
+    /// assert_eq!(dihedral,factory.group_generated_by(&[&[2,3,4,5,1],&[5,4,3,2,1]]));
+    /// let alternating = factory.even_permutations();
+    /// assert_eq!(60,factory.number_solutions::<u64>(alternating));
+    /// assert_eq!(alternating,factory.group_generated_by(&[&[2,3,1,4,5],&[2,3,4,5,1]])); // a 3-cycle and a 5-cycle.
+    /// let all = factory.construct_all_permutations();
+    /// assert_eq!(all,factory.group_generated_by(&[&[2,1,3,4,5],&[2,3,4,5,1]]));
+    /// ```
+    pub fn group_generated_by(&mut self, generators: &[&[PermutedItem]]) -> NodeIndex<A,M> {
+        let mut set = NodeIndex::FALSE;
+        for generator in generators {
+            let generator = self.compute_for_single_permutation(generator);
+            set = self.or(set,generator);
+        }
+        self.closure_under_composition(set)
+    }
+
+    /// Compute the right coset P·g of the set of permutations p by the permutation g of 1..n, { π·g | π∈P }.
+    /// This is the same as composing with the set containing just g, but just applies the transpositions in the decomposition of g in turn.
+    pub fn right_coset(&mut self, p: NodeIndex<A,M>, g: &[PermutedItem]) -> NodeIndex<A,M> {
+        let decomposition = PermutationElement::<Swap>::get_permutation(g);
+        let mut res = p;
+        for e in decomposition.iter().rev() {
+            res = self.swap(res,e.elem1,e.elem2);
+        }
+        res
+    }
+
+    /// Compute the left coset g·P of the set of permutations p by the permutation g of 1..n, { g·π | π∈P }.
+    /// This is the same as composing the set containing just g with p, but with g fixed the work done for each node
+    /// only depends on the node.
+    pub fn left_coset(&mut self, g: &[PermutedItem], p: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        fn left<A:NodeAddress,M:Multiplicity>(factory:&mut PermutationDecisionDiagramFactory<Swap,A,M>,g:NodeIndex<A,M>,p:NodeIndex<A,M>,done:&mut HashMap<NodeIndex<A,M>,NodeIndex<A,M>>) -> NodeIndex<A,M> {
+            if p.is_false() { NodeIndex::FALSE } else if p.is_true() { g.multiply(p.multiplicity) } else if let Some(res) = done.get(&p) { res } else {
+                let node = factory.zdd.nodes.node_incorporating_multiplicity(p);
+                let element = factory.vars[node.variable];
+                let lo = left(factory,g,node.lo,done);
+                let hi = left(factory,g,node.hi,done);
+                let hi = factory.swap(hi,element.elem1,element.elem2);
+                let res = factory.or(lo,hi);
+                done.insert(p,res);
+                res
+            }
+        }
+        let g = self.compute_for_single_permutation(g);
+        left(self,g,p,&mut HashMap::new())
+    }
+
+    /// Compute the conjugate of the set of permutations p by the permutation g of 1..n, { g⁻¹·π·g | π∈P }.
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(4);
+    /// let p = factory.compute_for_single_permutation(&[2,3,1,4]);
+    /// let g = [4,3,2,1];
+    /// let conjugate = factory.conjugate(&g,p);
+    /// let g = Permutation{sequence:g.to_vec()};
+    /// let expected = g.inverse().compose(&Permutation{sequence:vec![2,3,1,4]}).compose(&g);
+    /// assert_eq!(vec![expected],factory.permutations(conjugate).collect::<Vec<_>>());
+    /// let s_n = factory.construct_all_permutations();
+    /// assert_eq!(s_n,factory.conjugate(&[2,1,4,3],s_n));
+    /// assert_eq!(s_n,factory.left_coset(&[2,1,4,3],s_n));
+    /// ```
+    pub fn conjugate(&mut self, g: &[PermutedItem], p: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let g_inverse = Permutation{sequence:g.to_vec()}.inverse();
+        let left = self.left_coset(&g_inverse.sequence,p);
+        self.right_coset(left,g)
+    }
+
+    /// The permutations π·σ for π in p and σ permuting just the given points, each of which must be in 1..n.
+    fn extend_by_symmetric_group(&mut self, p: NodeIndex<A,M>, points: &[PermutedItem]) -> NodeIndex<A,M> {
+        let mut res = p;
+        for (k,&i) in points.iter().enumerate() {
+            let prev = res;
+            for &j in &points[..k] {
+                let mut transposition : Vec<PermutedItem> = (1..=self.vars.n).collect();
+                transposition.swap((i-1) as usize,(j-1) as usize);
+                let extras = self.right_coset(prev,&transposition);
+                res = self.or(res,extras);
+            }
+        }
+        res
+    }
+
+    /// The permutations π with π(i)=i for each of the given points.
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(5);
+    /// let fixing = factory.permutations_fixing(&[2,4]);
+    /// assert_eq!(6,factory.number_solutions::<u64>(fixing));
+    /// assert!(factory.permutations(fixing).all(|p|p[2]==2 && p[4]==4));
+    /// let mapping = factory.permutations_mapping(&[2,4],&[5,1]);
+    /// assert_eq!(6,factory.number_solutions::<u64>(mapping));
+    /// assert!(factory.permutations(mapping).all(|p|p[2]==5 && p[4]==1));
+    /// let onto = factory.permutations_mapping_set(&[2,4],&[5,1]); // π(2),π(4) are 1 and 5 in either order.
+    /// assert_eq!(12,factory.number_solutions::<u64>(onto));
+    /// let stabilizer = factory.permutations_mapping_set(&[2,4],&[2,4]);
+    /// assert_eq!(stabilizer,factory.closure_under_composition(stabilizer));
+    /// ```
+    pub fn permutations_fixing(&mut self, points: &[PermutedItem]) -> NodeIndex<A,M> {
+        check_points(self.vars.n,points);
+        let moved : Vec<PermutedItem> = (1..=self.vars.n).filter(|i|!points.contains(i)).collect();
+        self.extend_by_symmetric_group(NodeIndex::TRUE,&moved)
+    }
+
+    /// The permutations π with π(from[k])=to[k] for each k. See [PermutationDecisionDiagramFactory::<Swap,A,M>::permutations_fixing].
+    pub fn permutations_mapping(&mut self, from: &[PermutedItem], to: &[PermutedItem]) -> NodeIndex<A,M> {
+        let g = extend_to_permutation(self.vars.n,from,to);
+        let fixing = self.permutations_fixing(from);
+        self.right_coset(fixing,&g)
+    }
+
+    /// The permutations π mapping the set of points from onto the set of points to, in any order.
+    /// With from the same as to, this is the stabilizer of the set. See [PermutationDecisionDiagramFactory::<Swap,A,M>::permutations_fixing].
+    pub fn permutations_mapping_set(&mut self, from: &[PermutedItem], to: &[PermutedItem]) -> NodeIndex<A,M> {
+        let g = extend_to_permutation(self.vars.n,from,to);
+        let others : Vec<PermutedItem> = (1..=self.vars.n).filter(|i|!from.contains(i)).collect();
+        let within_from = self.extend_by_symmetric_group(NodeIndex::TRUE,from);
+        let stabilizer = self.extend_by_symmetric_group(within_from,&others);
+        self.right_coset(stabilizer,&g)
+    }
+
+    /// Compose each permutation in a πDD with the left rotation ρ(l,r), which is the composition of the adjacent
+    /// transpositions τ(r-1,r)·…·τ(l+1,l+2)·τ(l,l+1). This allows the Rot-πDD algorithms to be used with πDDs.
+    /// # Example
+    /// ```
+    /// use xdd::{NodeIndex, NoMultiplicity};
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+    /// let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(4);
+    /// let rot13 = factory.left_rot(NodeIndex::TRUE,1,3);
+    /// assert_eq!(Some(Permutation{sequence:vec![2,3,1,4]}),factory.permutations(rot13).next());
+    /// ```
+    pub fn left_rot(&mut self, node_index: NodeIndex<A,M>, l: PermutedItem, r: PermutedItem) -> NodeIndex<A,M> {
+        if l > r { return self.left_rot(node_index, r, l); }
+        let mut res = node_index;
+        for i in (l..r).rev() { res = self.swap(res, i, i+1); }
+        res
+    }
+
+    /// Compute the set of permutations that distribute the k-prefix of a permutation π over the n choose k possible positions in the first n elements of π.
+    /// The same as the Rot-πDD version, [PermutationDecisionDiagramFactory::<LeftRotation,A,M>::permutations_distributing_k_prefix_over_n_elements].
+    pub fn permutations_distributing_k_prefix_over_n_elements(&mut self, n:PermutedItem, k:PermutedItem) -> NodeIndex<A,M> {
+        assert!(k<=n);
+        assert!(n<=self.vars.n);
+        let mut p_j_minus_1 = vec![NodeIndex::TRUE; n as usize+1]; // P_{i,0} = TRUE for 0<=i<=n.
+        for j in 1..=k {
+            let mut p_j = vec![NodeIndex::FALSE; j as usize]; // P_{i,j} = p_j[i].
+            for i in j..=n {
+                let term2 = self.left_rot(p_j_minus_1[(i-1) as usize],j,i);
+                let p_ij = self.or(p_j[(i-1) as usize],term2);
+                p_j.push(p_ij);
+            }
+            p_j_minus_1=p_j;
+        }
+        p_j_minus_1[n as usize]
+    }
+
+    /// Compute the set of all permutations whose k-prefix is ordered in increasing order.
+    /// The same as the Rot-πDD version, [PermutationDecisionDiagramFactory::<LeftRotation,A,M>::permutations_with_ordered_k_prefix].
+    pub fn permutations_with_ordered_k_prefix(&mut self, n:PermutedItem, k:PermutedItem) -> NodeIndex<A,M> {
+        assert!(k<=n);
+        assert!(n<=self.vars.n);
+        let mut i_i_minus_1 = NodeIndex::TRUE;
+        for i in k+1..=n {
+            let mut i_i = i_i_minus_1;
+            for j in 1..i {
+                let rot = self.left_rot(i_i_minus_1,j,i);
+                i_i = self.or(i_i,rot);
+            }
+            i_i_minus_1=i_i;
+        }
+        i_i_minus_1
+    }
+
+    /// Compute the permutations containing a given pattern, using the algorithm for Rot-πDDs,
+    /// [PermutationDecisionDiagramFactory::<LeftRotation,A,M>::permutations_containing_a_given_pattern].
+    ///
+    /// # Example - compute the number of patterns avoiding 1324 [OEIS](https://oeis.org/A061552)
+    ///
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation_diagrams::{factorial, PermutationDecisionDiagramFactory, PermutedItem, Swap};
+    /// fn num_avoiding_1324(n:PermutedItem) -> u64 {
+    ///     let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(n as u16);
+    ///     let containing = factory.permutations_containing_a_given_pattern(&[1,3,2,4]);
+    ///     let num_containing : u64 = factory.number_solutions(containing);
+    ///     factorial::<u64>(n as u32)-num_containing
+    /// }
+    /// assert_eq!(1,num_avoiding_1324(1));
+    /// assert_eq!(23,num_avoiding_1324(4));
+    /// assert_eq!(513,num_avoiding_1324(6));
+    /// assert_eq!(15793,num_avoiding_1324(8));
+    /// ```
+    pub fn permutations_containing_a_given_pattern(&mut self,permutation:&[PermutedItem]) -> NodeIndex<A,M> {
+        let n = self.vars.n;
+        if n < permutation.len() as PermutedItem { return NodeIndex::FALSE; }
+        let k = permutation.len() as PermutedItem;
+        let a = self.permutations_with_ordered_k_prefix(n, k);
+        let b = self.compute_for_single_permutation(permutation);
+        let c = self.permutations_distributing_k_prefix_over_n_elements(n, k);
+        let b_cross_a = self.compose(b,a);
+        self.compose(c,b_cross_a)
+    }
 }
 
 impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotation,A,M> {
@@ -412,7 +955,7 @@ impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotat
                 if node_variable.elem2 < r { self.create(variable, NodeIndex::FALSE, node_index) } // this is something lower down the diagram than the variable.
                 else {
                     let cache_key = (node_index, variable);
-                    if let Some(cached_answer) = self.i_cache.get(&cache_key) { *cached_answer } else {
+                    if let Some(cached_answer) = self.i_cache.get(&cache_key) { cached_answer } else {
                         // Let P = ( ρ_x,y , P_0 , P_1 ). = P_0 + P_1.left_rot(x,y)
                         // so result = P_0.left_rot(l,r) + P_1.left_rot(x,y).left_rot(l,r)
                         // theorem 4.1.1 in Yuma Inoue's theis says ρ_x,y ρ_l,r can be transformed into the form of ρ_l′,r′ ρ_x′,y with r′< y
@@ -459,7 +1002,7 @@ impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotat
     pub fn compose(&mut self, p: NodeIndex<A,M>, q: NodeIndex<A,M>) -> NodeIndex<A,M> {
         if p.is_false() || q.is_false() { NodeIndex::FALSE } else if p.is_true() { q.multiply(p.multiplicity) } else if q.is_true() { p.multiply(q.multiplicity) } else {
             let cache_key = (p,q);
-            if let Some(cached_answer) = self.compose_cache.get(&cache_key) { *cached_answer } else {
+            if let Some(cached_answer) = self.compose_cache.get(&cache_key) { cached_answer } else {
                 let q_node = self.zdd.nodes.node_incorporating_multiplicity(q);
                 let q_var = self.vars[q_node.variable];
                 let lo = self.compose(p, q_node.lo);
@@ -472,6 +1015,38 @@ impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotat
         }
     }
 
+    /// Compute the inverses of the permutations in a Rot-πDD. That is, if p represents a set of permutations P,
+    /// then make { π⁻¹ | π∈P }, with the same multiplicities.
+    ///
+    /// If P = P_0 ∪ P_1·ρ where ρ is the top variable, then the inverses are P_0⁻¹ ∪ ρ⁻¹·P_1⁻¹. The inverse of the
+    /// left rotation ρ(l,r) is the right rotation, which is ρ(l,r) applied r-l times.
+    /// # Example
+    /// ```
+    /// use xdd::{NodeIndex, NoMultiplicity};
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, LeftRotation};
+    /// let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(4);
+    /// let p = factory.compute_for_single_permutation(&[4,3,1,2]);
+    /// let p_inverse = factory.compute_for_single_permutation(&[3,4,2,1]);
+    /// assert_eq!(p_inverse,factory.inverse(p));
+    /// assert_eq!(NodeIndex::TRUE,factory.compose(p,p_inverse));
+    /// let s_n = factory.construct_all_permutations();
+    /// assert_eq!(s_n,factory.inverse(s_n));
+    /// ```
+    pub fn inverse(&mut self, p: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        if p.is_sink() { p } else if let Some(cached_answer) = self.inverse_cache.get(&p) { cached_answer } else {
+            let node = self.zdd.nodes.node_incorporating_multiplicity(p);
+            let var = self.vars[node.variable];
+            let lo = self.inverse(node.lo);
+            let hi = self.inverse(node.hi);
+            let mut element = NodeIndex::TRUE;
+            for _ in var.elem1..var.elem2 { element = self.left_rot(element, var.elem1, var.elem2); }
+            let hi = self.compose(element, hi);
+            let res = self.or(lo, hi);
+            self.inverse_cache.insert(p,res);
+            res
+        }
+    }
+
     /// Construct the set of all permutations.
     /// # Example
     /// ```
@@ -494,6 +1069,229 @@ impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotat
         res
     }
 
+    /// Iterate over the permutations in a Rot-πDD, in the order of [DecisionDiagramFactory::solutions]. Multiplicities are ignored.
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, LeftRotation};
+    /// let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(5);
+    /// let p = factory.compute_for_single_permutation(&[4,3,1,5,2]);
+    /// let permutations : Vec<Permutation> = factory.permutations(p).collect();
+    /// assert_eq!(vec![Permutation{sequence:vec![4,3,1,5,2]}],permutations);
+    /// ```
+    pub fn permutations(&self, p: NodeIndex<A,M>) -> impl Iterator<Item=Permutation>+'_ {
+        self.zdd.solutions(p).map(|solution|self.vars.decode(&solution))
+    }
+
+    /// Compute the right coset P·g of the set of permutations p by the permutation g of 1..n, { π·g | π∈P }.
+    /// This is the same as composing with the set containing just g, but just applies the rotations in the decomposition of g in turn.
+    pub fn right_coset(&mut self, p: NodeIndex<A,M>, g: &[PermutedItem]) -> NodeIndex<A,M> {
+        let decomposition = PermutationElement::<LeftRotation>::get_permutation(g);
+        let mut res = p;
+        for e in decomposition.iter().rev() {
+            res = self.left_rot(res,e.elem1,e.elem2);
+        }
+        res
+    }
+
+    /// Compute the left coset g·P of the set of permutations p by the permutation g of 1..n, { g·π | π∈P }.
+    /// This is the same as composing the set containing just g with p, but with g fixed the work done for each node
+    /// only depends on the node.
+    pub fn left_coset(&mut self, g: &[PermutedItem], p: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        fn left<A:NodeAddress,M:Multiplicity>(factory:&mut PermutationDecisionDiagramFactory<LeftRotation,A,M>,g:NodeIndex<A,M>,p:NodeIndex<A,M>,done:&mut HashMap<NodeIndex<A,M>,NodeIndex<A,M>>) -> NodeIndex<A,M> {
+            if p.is_false() { NodeIndex::FALSE } else if p.is_true() { g.multiply(p.multiplicity) } else if let Some(res) = done.get(&p) { res } else {
+                let node = factory.zdd.nodes.node_incorporating_multiplicity(p);
+                let element = factory.vars[node.variable];
+                let lo = left(factory,g,node.lo,done);
+                let hi = left(factory,g,node.hi,done);
+                let hi = factory.left_rot(hi,element.elem1,element.elem2);
+                let res = factory.or(lo,hi);
+                done.insert(p,res);
+                res
+            }
+        }
+        let g = self.compute_for_single_permutation(g);
+        left(self,g,p,&mut HashMap::new())
+    }
+
+    /// Compute the conjugate of the set of permutations p by the permutation g of 1..n, { g⁻¹·π·g | π∈P }.
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation::Permutation;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, LeftRotation};
+    /// let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(4);
+    /// let p = factory.compute_for_single_permutation(&[2,3,1,4]);
+    /// let g = [4,3,2,1];
+    /// let conjugate = factory.conjugate(&g,p);
+    /// let g = Permutation{sequence:g.to_vec()};
+    /// let expected = g.inverse().compose(&Permutation{sequence:vec![2,3,1,4]}).compose(&g);
+    /// assert_eq!(vec![expected],factory.permutations(conjugate).collect::<Vec<_>>());
+    /// let s_n = factory.construct_all_permutations();
+    /// assert_eq!(s_n,factory.conjugate(&[2,1,4,3],s_n));
+    /// assert_eq!(s_n,factory.left_coset(&[2,1,4,3],s_n));
+    /// ```
+    pub fn conjugate(&mut self, g: &[PermutedItem], p: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let g_inverse = Permutation{sequence:g.to_vec()}.inverse();
+        let left = self.left_coset(&g_inverse.sequence,p);
+        self.right_coset(left,g)
+    }
+
+    /// The permutations π·σ for π in p and σ permuting just the given points, each of which must be in 1..n.
+    fn extend_by_symmetric_group(&mut self, p: NodeIndex<A,M>, points: &[PermutedItem]) -> NodeIndex<A,M> {
+        let mut res = p;
+        for (k,&i) in points.iter().enumerate() {
+            let prev = res;
+            for &j in &points[..k] {
+                let mut transposition : Vec<PermutedItem> = (1..=self.vars.n).collect();
+                transposition.swap((i-1) as usize,(j-1) as usize);
+                let extras = self.right_coset(prev,&transposition);
+                res = self.or(res,extras);
+            }
+        }
+        res
+    }
+
+    /// The permutations π with π(i)=i for each of the given points.
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, LeftRotation};
+    /// let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(5);
+    /// let fixing = factory.permutations_fixing(&[2,4]);
+    /// assert_eq!(6,factory.number_solutions::<u64>(fixing));
+    /// assert!(factory.permutations(fixing).all(|p|p[2]==2 && p[4]==4));
+    /// let mapping = factory.permutations_mapping(&[2,4],&[5,1]);
+    /// assert_eq!(6,factory.number_solutions::<u64>(mapping));
+    /// assert!(factory.permutations(mapping).all(|p|p[2]==5 && p[4]==1));
+    /// let onto = factory.permutations_mapping_set(&[2,4],&[5,1]); // π(2),π(4) are 1 and 5 in either order.
+    /// assert_eq!(12,factory.number_solutions::<u64>(onto));
+    /// let stabilizer = factory.permutations_mapping_set(&[2,4],&[2,4]);
+    /// assert_eq!(stabilizer,factory.closure_under_composition(stabilizer));
+    /// ```
+    pub fn permutations_fixing(&mut self, points: &[PermutedItem]) -> NodeIndex<A,M> {
+        check_points(self.vars.n,points);
+        let moved : Vec<PermutedItem> = (1..=self.vars.n).filter(|i|!points.contains(i)).collect();
+        self.extend_by_symmetric_group(NodeIndex::TRUE,&moved)
+    }
+
+    /// The permutations π with π(from[k])=to[k] for each k. See [PermutationDecisionDiagramFactory::<LeftRotation,A,M>::permutations_fixing].
+    pub fn permutations_mapping(&mut self, from: &[PermutedItem], to: &[PermutedItem]) -> NodeIndex<A,M> {
+        let g = extend_to_permutation(self.vars.n,from,to);
+        let fixing = self.permutations_fixing(from);
+        self.right_coset(fixing,&g)
+    }
+
+    /// The permutations π mapping the set of points from onto the set of points to, in any order.
+    /// With from the same as to, this is the stabilizer of the set. See [PermutationDecisionDiagramFactory::<LeftRotation,A,M>::permutations_fixing].
+    pub fn permutations_mapping_set(&mut self, from: &[PermutedItem], to: &[PermutedItem]) -> NodeIndex<A,M> {
+        let g = extend_to_permutation(self.vars.n,from,to);
+        let others : Vec<PermutedItem> = (1..=self.vars.n).filter(|i|!from.contains(i)).collect();
+        let within_from = self.extend_by_symmetric_group(NodeIndex::TRUE,from);
+        let stabilizer = self.extend_by_symmetric_group(within_from,&others);
+        self.right_coset(stabilizer,&g)
+    }
+
+    /// Count the permutations in p by their number of inversions, the pairs i<j with π(i)>π(j). The rotation ρ(l,r) in the
+    /// canonical decomposition places an element after the r-l larger elements not yet placed, so contributes r-l inversions.
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::generating_function::SingleVariableGeneratingFunction;
+    /// use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, LeftRotation};
+    /// let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(4);
+    /// let s_n = factory.construct_all_permutations();
+    /// assert_eq!(SingleVariableGeneratingFunction(vec![1u64,3,5,6,5,3,1]),factory.inversions_generating_function(s_n)); // Mahonian numbers
+    /// let p = factory.compute_for_single_permutation(&[4,3,1,2]);
+    /// assert_eq!(SingleVariableGeneratingFunction(vec![0u64,0,0,0,0,1]),factory.inversions_generating_function(p));
+    /// ```
+    pub fn inversions_generating_function<E:Clone+Eq+Debug+Integer+AddAssign>(&self, p: NodeIndex<A,M>) -> SingleVariableGeneratingFunction<E> where SingleVariableGeneratingFunction<E>:GeneratingFunctionWithMultiplicity<M> {
+        self.statistic_generating_function(p,|e|(e.elem2-e.elem1) as usize)
+    }
+
+    /// The n rotations (1,2,…,n), (2,3,…,n,1), …, (n,1,…,n-1), being the cyclic group C_n.
+    pub fn cyclic_group(&mut self) -> NodeIndex<A,M> {
+        let n = self.vars.n;
+        let mut res = NodeIndex::FALSE;
+        for k in 0..n.max(1) { // the group on 0 elements is just the identity.
+            let rotation : Vec<PermutedItem> = (0..n).map(|i|(i+k)%n+1).collect();
+            let rotation = self.compute_for_single_permutation(&rotation);
+            res = self.or(res,rotation);
+        }
+        res
+    }
+
+    /// The symmetries of a regular n-gon with vertices numbered in order, being the dihedral group D_n,
+    /// which is the rotations and their compositions with the reversal (n,…,2,1). Each has multiplicity 1.
+    pub fn dihedral_group(&mut self) -> NodeIndex<A,M> {
+        let rotations = self.cyclic_group();
+        let reversal : Vec<PermutedItem> = (1..=self.vars.n).rev().collect();
+        let reversal = self.compute_for_single_permutation(&reversal);
+        let reflections = self.compose(rotations,reversal);
+        let res = self.or(rotations,reflections);
+        self.support(res) // for n<=2 some reflections are rotations.
+    }
+
+    /// The even permutations, being the alternating group A_n.
+    /// The variable ρ(l,r) is an (r-l+1)-cycle, so a permutation is even iff it has an even number of variables with r-l odd.
+    pub fn even_permutations(&mut self) -> NodeIndex<A,M> {
+        let all = self.construct_all_permutations();
+        let mut odd = NodeIndex::FALSE;
+        for v in 0..self.vars.num_variables() {
+            let variable = VariableIndex(v);
+            let element = self.vars[variable];
+            if (element.elem2-element.elem1).is_multiple_of(2) { continue; } // an odd length cycle is an even permutation.
+            let x = self.single_variable(variable);
+            odd = self.xor(odd,x);
+        }
+        let even = self.not(odd);
+        self.and(all,even)
+    }
+
+    /// The smallest set of permutations containing the identity and generators, and closed under composition. This is the
+    /// group generated by generators, found by composing with the generators until nothing new is found. Each has multiplicity 1.
+    pub fn closure_under_composition(&mut self, generators: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let generators = self.support(generators);
+        let mut res = self.or(NodeIndex::TRUE,generators);
+        res = self.support(res);
+        loop {
+            let extended = self.compose(res,generators);
+            let extended = self.or(res,extended);
+            let extended = self.support(extended);
+            if extended==res { return res; }
+            res = extended;
+        }
+    }
+
+    /// The group generated by the given permutations of 1..n. See [PermutationDecisionDiagramFactory::<LeftRotation,A,M>::closure_under_composition].
+    /// # Example
+    /// ```
+    /// use xdd::NoMultiplicity;
+    /// use xdd::permutation_diagrams::{factorial, PermutationDecisionDiagramFactory, LeftRotation};
+    /// let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(5);
+    /// let cyclic = factory.cyclic_group();
+    /// assert_eq!(5,factory.number_solutions::<u64>(cyclic));
+    /// assert_eq!(cyclic,factory.group_generated_by(&[&[2,3,4,5,1]]));
+    /// let dihedral = factory.dihedral_group();
+    /// assert_eq!(10,factory.number_solutions::<u64>(dihedral));
+    /// assert_eq!(dihedral,factory.group_generated_by(&[&[2,3,4,5,1],&[5,4,3,2,1]]));
+    /// let alternating = factory.even_permutations();
+    /// assert_eq!(60,factory.number_solutions::<u64>(alternating));
+    /// assert_eq!(alternating,factory.group_generated_by(&[&[2,3,1,4,5],&[2,3,4,5,1]])); // a 3-cycle and a 5-cycle.
+    /// let all = factory.construct_all_permutations();
+    /// assert_eq!(all,factory.group_generated_by(&[&[2,1,3,4,5],&[2,3,4,5,1]]));
+    /// ```
+    pub fn group_generated_by(&mut self, generators: &[&[PermutedItem]]) -> NodeIndex<A,M> {
+        let mut set = NodeIndex::FALSE;
+        for generator in generators {
+            let generator = self.compute_for_single_permutation(generator);
+            set = self.or(set,generator);
+        }
+        self.closure_under_composition(set)
+    }
+
+
     /// Get a set containing the single specified permutation.
     /// panics if the permutation argument is not a permutation of 1..n.
     pub fn compute_for_single_permutation(&mut self,permutation:&[PermutedItem]) -> NodeIndex<A,M> {
@@ -543,7 +1341,7 @@ impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotat
     pub fn permutations_distributing_k_prefix_over_n_elements(&mut self, n:PermutedItem, k:PermutedItem) -> NodeIndex<A,M> {
         assert!(k<=n);
         assert!(n<=self.vars.n);
-        let mut p_j_minus_1 = vec![NodeIndex::TRUE; n as usize];
+        let mut p_j_minus_1 = vec![NodeIndex::TRUE; n as usize+1]; // P_{i,0} = TRUE for 0<=i<=n.
         for j in 1..=k {
             let mut p_j = vec![NodeIndex::FALSE; j as usize]; // P_{i,j} = p_j[i].
             for i in j..=n {
@@ -647,8 +1445,7 @@ impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotat
         let b = self.compute_for_single_permutation(permutation);
         let c = self.permutations_distributing_k_prefix_over_n_elements(n, k);
         let b_cross_a = self.compose(b,a);
-        let c_cross_b_cross_a = self.compose(c,b_cross_a);
-        c_cross_b_cross_a
+        self.compose(c,b_cross_a)
     }
 
 }
@@ -657,6 +1454,26 @@ impl <A:NodeAddress,M:Multiplicity>  PermutationDecisionDiagramFactory<LeftRotat
 // General utility functions to do with permutations
 //
 
+/// Panic unless points are distinct and in 1..=n.
+fn check_points(n:PermutedItem,points:&[PermutedItem]) {
+    for (k,&point) in points.iter().enumerate() {
+        assert!(point>=1 && point<=n,"Point {} is not in 1..={}",point,n);
+        assert!(!points[..k].contains(&point),"Point {} is repeated",point);
+    }
+}
+
+/// A permutation g of 1..n with g(from[k])=to[k], with the other points mapped in increasing order to the unused values.
+fn extend_to_permutation(n:PermutedItem,from:&[PermutedItem],to:&[PermutedItem]) -> Vec<PermutedItem> {
+    assert_eq!(from.len(),to.len(),"Must map to as many points as are mapped from");
+    check_points(n,from);
+    check_points(n,to);
+    let mut unused = (1..=n).filter(|i|!to.contains(i));
+    (1..=n).map(|i|match from.iter().position(|&f|f==i) {
+        Some(k) => to[k],
+        None => unused.next().unwrap(),
+    }).collect()
+}
+
 
 /// Compute the factorial of a number.
 ///
@@ -0,0 +1,120 @@
+//! Permutation matrices: n×n 0/1 matrices with exactly one 1 in each row and each column, optionally only using allowed cells.
+//!
+//! These are the perfect matchings of a bipartite graph between rows and columns, or the solutions of an assignment problem.
+//! Each cell has its own variable, true iff that cell is 1, so the diagram has n² variables, in row major order.
+//! Counting the solutions gives the permanent of the 0/1 matrix of allowed cells, and weighting each cell variable by an entry
+//! of a matrix ([DecisionDiagramFactory::weighted_count]) gives the permanent of that matrix. This complements
+//! [crate::permutation_diagrams], which encodes permutations with fewer variables, as a cross check on counts.
+//!
+//! # Example
+//! Derangements are permutations with no fixed points, that is, permutation matrices with nothing on the diagonal.
+//! ```
+//! use xdd::{DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+//! use xdd::permutation_matrices::PermutationMatrices;
+//! let n = 4;
+//! let derangements = PermutationMatrices::with_allowed((0..n).map(|row|(0..n).map(|column|row!=column).collect()).collect());
+//! let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(derangements.num_variables());
+//! let matrices = derangements.matrices(&mut factory);
+//! assert_eq!(9,factory.number_solutions::<u64>(matrices));
+//! let first = factory.solutions(matrices).next().unwrap();
+//! assert_eq!(vec![4,3,2,1],derangements.permutation(&first).sequence);
+//! // weighting cells by the entries of a 2×2 matrix.
+//! assert_eq!(1*4+2*3,PermutationMatrices::permanent(&[vec![1i64,2],vec![3,4]]));
+//! assert_eq!(1*4-2*3,PermutationMatrices::determinant(&[vec![1i64,2],vec![3,4]]));
+//! ```
+
+use std::ops::Mul;
+use num::Zero;
+use crate::{DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use crate::generating_function::{GeneratingFunction, GeneratingFunctionWithMultiplicity};
+use crate::permutation::{Permutation, PermutedItem};
+
+/// The permutation matrices of a given size using only allowed cells.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct PermutationMatrices {
+    /// The number of rows and columns.
+    pub n : usize,
+    /// allowed\[row\]\[column\] is whether that cell may be 1.
+    pub allowed : Vec<Vec<bool>>,
+}
+
+impl PermutationMatrices {
+    /// All n×n permutation matrices, of which there are n!.
+    pub fn new(n:usize) -> Self { PermutationMatrices{n,allowed:vec![vec![true;n];n]} }
+    /// The permutation matrices only using cells for which allowed\[row\]\[column\] is true. Panics if allowed is not square.
+    pub fn with_allowed(allowed:Vec<Vec<bool>>) -> Self {
+        let n = allowed.len();
+        assert!(allowed.iter().all(|row|row.len()==n),"Allowed cells should be a square matrix");
+        PermutationMatrices{n,allowed}
+    }
+    /// The number of variables needed, n².
+    pub fn num_variables(&self) -> u16 { u16::try_from(self.n*self.n).expect("Too many cells") }
+    /// The variable that is true iff the given cell is 1.
+    pub fn cell_variable(&self,row:usize,column:usize) -> VariableIndex { VariableIndex((row*self.n+column) as u16) }
+    /// Make a diagram that is true iff the cells that are 1 form a permutation matrix using only allowed cells.
+    /// The factory must have at least [Self::num_variables] variables; any others do not matter.
+    /// This is an exact cover of the rows and columns, each cell covering its row and column.
+    pub fn matrices<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F) -> NodeIndex<A,M> {
+        let n = self.n;
+        // a disallowed cell covers its row twice, so can never be chosen.
+        let options : Vec<Vec<usize>> = (0..n).flat_map(|row|(0..n).map(move |column|(row,column)))
+            .map(|(row,column)|if self.allowed[row][column] { vec![row,n+column] } else { vec![row,row] }).collect();
+        factory.exact_cover(2*n,&options)
+    }
+    /// The permutation corresponding to a solution of [Self::matrices], mapping row i to the column of the 1 in it (both 1 based).
+    pub fn permutation(&self,solution:&[VariableIndex]) -> Permutation {
+        let mut sequence = vec![0;self.n];
+        for v in solution {
+            let cell = v.0 as usize;
+            if cell<self.n*self.n { sequence[cell/self.n] = (cell%self.n+1) as PermutedItem; }
+        }
+        Permutation{sequence}
+    }
+    /// The same permutation matrices, each with multiplicity its sign: 1 for an even permutation, -1 for an odd one.
+    /// The sign is worked out by negating the matrices with each inversion, a pair of 1s with the lower one to the left,
+    /// so this takes about n⁴/4 operations and is meant for small n.
+    pub fn signed_matrices<A:NodeAddress,F:DecisionDiagramFactory<A,i64>>(&self,factory:&mut F) -> NodeIndex<A,i64> {
+        let n = self.n;
+        let mut signed = self.matrices(factory);
+        for row1 in 0..n {
+            for column1 in (0..n).filter(|&c|self.allowed[row1][c]) {
+                let cell1 = factory.single_variable(self.cell_variable(row1,column1));
+                for row2 in row1+1..n {
+                    for column2 in (0..column1).filter(|&c|self.allowed[row2][c]) {
+                        let cell2 = factory.single_variable(self.cell_variable(row2,column2));
+                        let both = factory.and(cell1,cell2);
+                        let inverted = factory.and(signed,both);
+                        signed = factory.linear_combination(&[(signed,1),(inverted,-2)]);
+                    }
+                }
+            }
+        }
+        signed
+    }
+    /// The weight of each cell variable, from a n×n matrix, for [DecisionDiagramFactory::weighted_count]. Panics if the matrix is not n×n.
+    pub fn cell_weights<G:Clone>(&self,matrix:&[Vec<G>]) -> Vec<G> {
+        assert!(matrix.len()==self.n && matrix.iter().all(|row|row.len()==self.n),"Matrix should be {}×{}",self.n,self.n);
+        matrix.iter().flatten().cloned().collect()
+    }
+    /// The permutation matrices using just the cells where matrix is nonzero. Panics if matrix is not square.
+    pub fn nonzero<G:Zero>(matrix:&[Vec<G>]) -> Self {
+        Self::with_allowed(matrix.iter().map(|row|row.iter().map(|e|!e.is_zero()).collect()).collect())
+    }
+    /// The permanent of a square matrix, the sum over permutations π of the product of matrix\[i\]\[π(i)\].
+    /// This is the weighted count of the permutation matrices using its nonzero cells. Panics if matrix is not square.
+    pub fn permanent<G:GeneratingFunction+Mul<Output=G>+Zero>(matrix:&[Vec<G>]) -> G {
+        let problem = Self::nonzero(matrix);
+        let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(problem.num_variables());
+        let matrices = problem.matrices(&mut factory);
+        factory.weighted_count(matrices,&problem.cell_weights(matrix))
+    }
+    /// The determinant of a square matrix, like the permanent but with each permutation's term multiplied by its sign.
+    /// This is the weighted count of [Self::signed_matrices], so is only practical for small matrices; it is a check on
+    /// signed counting rather than a way of computing determinants. Panics if matrix is not square.
+    pub fn determinant<G:GeneratingFunctionWithMultiplicity<i64>+Mul<Output=G>+Zero>(matrix:&[Vec<G>]) -> G {
+        let problem = Self::nonzero(matrix);
+        let mut factory = ZDDFactory::<u32,i64>::new(problem.num_variables());
+        let signed = problem.signed_matrices(&mut factory);
+        factory.weighted_count(signed,&problem.cell_weights(matrix))
+    }
+}
@@ -0,0 +1,108 @@
+//! Transition systems: sets of states, and relations between a state and the next state.
+//!
+//! A state is an assignment to some "current" variables. A transition relation is a diagram over both the
+//! current variables and a matching set of "next" variables, true iff the system can go from the current state to
+//! the next state in one step. The image of a set of states (the states reachable in one step) is then the
+//! [DecisionDiagramFactory::relational_product] of the states and the relation, quantifying away the current
+//! variables, with the next variables then renamed to the current ones. A [StatePairing] records which variables
+//! are paired, and does this renaming.
+//!
+//! Sets of states are functions of the current variables only, so the next variables do not matter. This
+//! means [DecisionDiagramFactory::number_solutions] counts each state once for each value of the next variables;
+//! [StatePairing::number_states] allows for this.
+//!
+//! # Example
+//! ```
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity};
+//! use xdd::relations::StatePairing;
+//! // Two bits a and b. The transitions are a:=!a, and b:=a. From a,b both false all states can be reached.
+//! let pairing = StatePairing::interleaved(2);
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(pairing.num_variables());
+//! let [a,b] = [0,1].map(|i|factory.single_variable(pairing.current[i]));
+//! let [a_next,b_next] = [0,1].map(|i|factory.single_variable(pairing.next[i]));
+//! let flip = factory.xor(a,a_next); // a_next = !a
+//! let b_unchanged = factory.xor(b,b_next);
+//! let b_unchanged = factory.not(b_unchanged);
+//! let copy = factory.xor(a,b_next);
+//! let copy = factory.not(copy); // b_next = a
+//! let t1 = factory.and(flip,b_unchanged);
+//! let a_unchanged = factory.xor(a,a_next);
+//! let a_unchanged = factory.not(a_unchanged);
+//! let t2 = factory.and(copy,a_unchanged);
+//! let transition = factory.or(t1,t2);
+//! let not_a = factory.not(a);
+//! let not_b = factory.not(b);
+//! let initial = factory.and(not_a,not_b);
+//! let reachable = pairing.reachable(&mut factory,initial,transition);
+//! assert_eq!(4,pairing.number_states(&factory,reachable));
+//! ```
+
+use crate::{DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, VariableIndex};
+use crate::generating_function::GeneratingFunctionWithMultiplicity;
+
+/// Which "next state" variable goes with each "current state" variable.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct StatePairing {
+    /// The current state variables, sorted.
+    pub current : Vec<VariableIndex>,
+    /// next[i] is the next state variable for current[i]. Also sorted.
+    pub next : Vec<VariableIndex>,
+}
+
+impl StatePairing {
+    /// Pair up the variables. Panics unless they are the same length, each sorted, and have no variables in common.
+    pub fn new(current:Vec<VariableIndex>,next:Vec<VariableIndex>) -> Self {
+        assert_eq!(current.len(),next.len(),"There must be a next state variable for each current state variable");
+        assert!(current.windows(2).all(|w|w[0]<w[1]) && next.windows(2).all(|w|w[0]<w[1]),"Variables must be sorted and distinct");
+        assert!(current.iter().all(|v|!next.contains(v)),"A variable can not be both a current and a next state variable");
+        StatePairing{current,next}
+    }
+
+    /// A state of n bits, with current state bit i being variable 2i and next state bit i being variable 2i+1.
+    /// Keeping the pairs adjacent usually keeps transition relations small.
+    pub fn interleaved(n:u16) -> Self {
+        StatePairing::new((0..n).map(|i|VariableIndex(2*i)).collect(),(0..n).map(|i|VariableIndex(2*i+1)).collect())
+    }
+
+    /// The number of variables a factory needs.
+    pub fn num_variables(&self) -> u16 {
+        self.current.iter().chain(self.next.iter()).map(|v|v.0+1).max().unwrap_or(0)
+    }
+
+    /// Rename the next state variables in f to the current ones, or vice versa if to_next.
+    fn rename<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,f:NodeIndex<A,M>,to_next:bool) -> NodeIndex<A,M> {
+        let (from,to) = if to_next { (&self.current,&self.next) } else { (&self.next,&self.current) };
+        let substitutions : Vec<(VariableIndex,NodeIndex<A,M>)> = from.iter().zip(to.iter()).map(|(&f,&t)|(f,factory.single_variable(t))).collect();
+        factory.vector_compose(f,&substitutions)
+    }
+
+    /// The states reachable from states in one transition.
+    pub fn image<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,states:NodeIndex<A,M>,transition:NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let next_states = factory.relational_product(states,transition,&self.current);
+        self.rename(factory,next_states,false)
+    }
+
+    /// The states from which one transition reaches one of states.
+    pub fn preimage<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,states:NodeIndex<A,M>,transition:NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let as_next = self.rename(factory,states,true);
+        factory.relational_product(as_next,transition,&self.next)
+    }
+
+    /// The states reachable from initial in any number (including 0) of transitions, found by breadth first search.
+    pub fn reachable<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&mut F,initial:NodeIndex<A,M>,transition:NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let mut reached = initial;
+        let mut frontier = initial;
+        loop {
+            let image = self.image(factory,frontier,transition);
+            let not_reached = factory.not(reached);
+            frontier = factory.and(image,not_reached);
+            if frontier.is_false() { return reached; }
+            reached = factory.or(reached,frontier);
+        }
+    }
+
+    /// The number of states in a set of states, allowing for the next state variables not mattering.
+    pub fn number_states<A:NodeAddress,M:Multiplicity,F:DecisionDiagramFactory<A,M>>(&self,factory:&F,states:NodeIndex<A,M>) -> u128 where u128:GeneratingFunctionWithMultiplicity<M> {
+        factory.number_solutions::<u128>(states)>>self.next.len()
+    }
+}
@@ -0,0 +1,81 @@
+//! Diagrams a factory keeps through garbage collection without them being passed to every gc.
+//!
+//! [crate::DecisionDiagramFactory::root] registers a diagram with the factory's [RootRegistry] and returns a [Handle].
+//! Every gc keeps the diagrams of live handles, and updates the handles to the new addresses, so
+//! [crate::DecisionDiagramFactory::gc_roots] can collect garbage with no other arguments. A diagram stops being
+//! a root when the last clone of its handle is dropped.
+//!
+//! # Example
+//! ```
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+//! let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+//! let f = factory.exactly_n_of(&variables,3);
+//! let f = factory.root(f);
+//! let g = factory.at_least_n_of(&variables,2);
+//! let g = factory.root(g);
+//! let junk = factory.at_most_n_of(&variables[1..],1);
+//! factory.or(junk,g.index());
+//! drop(g);
+//! factory.gc_roots();
+//! assert_eq!(factory.exactly_n_of(&variables,3),f.index()); // no new nodes needed.
+//! assert_eq!(20,factory.number_solutions::<u64>(f.index()));
+//! assert_eq!(vec![f.index()],factory.roots());
+//! ```
+
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, Weak};
+use crate::{Multiplicity, NodeAddress, NodeIndex, NodeRenaming};
+
+/// A diagram that is kept by garbage collection for as long as this, or a clone of it, exists.
+/// The diagram's address may change with each gc, so get it from [Handle::index] when needed rather than keeping it.
+#[derive(Clone)]
+pub struct Handle<A:NodeAddress,M:Multiplicity> {
+    index : Arc<Mutex<NodeIndex<A,M>>>,
+}
+
+impl <A:NodeAddress,M:Multiplicity> Handle<A,M> {
+    /// The diagram, at its current address.
+    pub fn index(&self) -> NodeIndex<A,M> { *self.index.lock().unwrap() }
+}
+
+impl <A:NodeAddress,M:Multiplicity> Debug for Handle<A,M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f,"Handle({:?})",self.index()) }
+}
+
+/// The diagrams a factory must keep through garbage collection, as a weak reference to each live [Handle].
+pub struct RootRegistry<A:NodeAddress,M:Multiplicity> {
+    roots : Vec<Weak<Mutex<NodeIndex<A,M>>>>,
+}
+
+impl <A:NodeAddress,M:Multiplicity> Default for RootRegistry<A,M> {
+    fn default() -> Self { RootRegistry{ roots: vec![] } }
+}
+
+impl <A:NodeAddress,M:Multiplicity> RootRegistry<A,M> {
+    /// Make index a root, until the returned handle and all its clones are dropped.
+    pub fn register(&mut self,index:NodeIndex<A,M>) -> Handle<A,M> {
+        self.roots.retain(|root|root.strong_count()>0);
+        let handle = Handle{ index: Arc::new(Mutex::new(index)) };
+        self.roots.push(Arc::downgrade(&handle.index));
+        handle
+    }
+    /// The diagrams of the live handles. The same diagram may appear more than once if it was registered more than once.
+    pub fn indices(&self) -> impl Iterator<Item=NodeIndex<A,M>> + '_ {
+        self.roots.iter().filter_map(|root|root.upgrade()).map(|index|*index.lock().unwrap())
+    }
+    /// Change each live handle to the diagram given by new_index, and forget those that have been dropped.
+    pub(crate) fn update(&mut self,mut new_index:impl FnMut(NodeIndex<A,M>)->NodeIndex<A,M>) {
+        self.roots.retain(|root|{
+            if let Some(index) = root.upgrade() {
+                let mut index = index.lock().unwrap();
+                *index = new_index(*index);
+                true
+            } else { false }
+        });
+    }
+    /// Change each live handle to its address after a gc, which should have kept it.
+    pub(crate) fn rename(&mut self,renaming:&NodeRenaming<A>) {
+        self.update(|index|renaming.rename(index).expect("gc should keep roots"));
+    }
+}
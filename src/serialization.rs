@@ -0,0 +1,654 @@
+//! A compact binary format for saving node stores and factories, so that diagrams that took hours
+//! to compute can be reloaded in a later run.
+//!
+//! All integers are little endian. A node list is
+//! * the magic bytes `XDDN` and a format version byte
+//! * the names of the address and multiplicity types (a length byte then UTF-8), checked on loading
+//! * the number of nodes, as a u64
+//! * each node as its variable (u16), then lo and hi, each an address followed by a multiplicity.
+//!
+//! A factory is the magic bytes `XDDF`, a version byte, `B` or `Z` for the kind of diagram, the number of
+//! variables (u16), the number of roots (u64), each root (address then multiplicity), then a node list.
+//!
+//! A checkpoint is the magic bytes `XDDC`, a version byte, the number of roots (u64) and each root's name and checksum
+//! (u64), the number of named variables (u64) and each as its variable (u16) and name, then a factory with the roots in the
+//! same order. Strings are a length (u64) then UTF-8. A root's checksum is its number of solutions, ignoring multiplicities,
+//! modulo 2<sup>64</sup>. Checkpoints are written to a temporary file that is then renamed, so a crash while saving leaves
+//! the previous checkpoint intact. [BDDFactory::verify_checkpoint] checks a checkpoint more thoroughly than loading it does,
+//! so that a long pipeline can check its inputs before starting.
+//!
+//! BDDs can also be exchanged with other packages in the text format of BuDDy's `bdd_save`; see
+//! [BDDFactory::write_buddy] and [BDDFactory::read_buddy].
+//!
+//! Loading checks that nodes are topologically sorted with variables increasing towards the sinks,
+//! so a corrupted or mismatched file is reported as an error of kind [ErrorKind::InvalidData] rather than
+//! producing nonsense later.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::{BDDFactory, DecisionDiagramFactory, Multiplicity, Node, NodeAddress, NodeIndex, NoMultiplicity, U48, VariableIndex, ZDDFactory};
+use num::rational::Ratio;
+use crate::statistics::OperationClass;
+use crate::xdd_with_multiplicity::{InvalidDiagram, NodeList, NodeListWithFastLookup, XDDBase};
+
+pub(crate) const NODE_LIST_MAGIC : &[u8;4] = b"XDDN";
+pub(crate) const FACTORY_MAGIC : &[u8;4] = b"XDDF";
+const CHECKPOINT_MAGIC : &[u8;4] = b"XDDC";
+const FORMAT_VERSION : u8 = 1;
+
+/// A type that can be written in a fixed number of bytes. Implemented for the address and multiplicity types.
+pub trait FixedWidth : Sized {
+    /// A name for the type, stored in the file so that it is not read back as a different type.
+    const NAME : &'static str;
+    fn write_to<W:Write>(self,writer:&mut W) -> std::io::Result<()>;
+    fn read_from<R:Read>(reader:&mut R) -> std::io::Result<Self>;
+}
+
+macro_rules! fixed_width_integer {
+    ($t:ty,$name:literal) => {
+        impl FixedWidth for $t {
+            const NAME: &'static str = $name;
+            fn write_to<W: Write>(self, writer: &mut W) -> std::io::Result<()> { writer.write_all(&self.to_le_bytes()) }
+            fn read_from<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+                let mut bytes = [0u8;std::mem::size_of::<$t>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    }
+}
+
+fixed_width_integer!(u16,"u16");
+fixed_width_integer!(u32,"u32");
+fixed_width_integer!(u64,"u64");
+fixed_width_integer!(u128,"u128");
+fixed_width_integer!(i64,"i64");
+fixed_width_integer!(i128,"i128");
+
+macro_rules! fixed_width_ratio {
+    ($t:ty,$name:literal) => {
+        /// The numerator then the denominator.
+        impl FixedWidth for Ratio<$t> {
+            const NAME: &'static str = $name;
+            fn write_to<W: Write>(self, writer: &mut W) -> std::io::Result<()> {
+                self.numer().write_to(writer)?;
+                self.denom().write_to(writer)
+            }
+            fn read_from<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+                let numer = <$t>::read_from(reader)?;
+                let denom = <$t>::read_from(reader)?;
+                if denom<=0 { return Err(invalid_data(format!("denominator {} should be positive",denom))); }
+                Ok(Ratio::new(numer,denom))
+            }
+        }
+    }
+}
+
+fixed_width_ratio!(i64,"Ratio<i64>");
+fixed_width_ratio!(i128,"Ratio<i128>");
+
+/// Stored as a u64 so that files are portable between machines with different sizes of usize.
+impl FixedWidth for usize {
+    const NAME: &'static str = "usize";
+    fn write_to<W: Write>(self, writer: &mut W) -> std::io::Result<()> { (self as u64).write_to(writer) }
+    fn read_from<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        usize::try_from(u64::read_from(reader)?).map_err(|_|invalid_data("value too large for usize on this machine".to_string()))
+    }
+}
+
+/// Stored as 6 little endian bytes, like the other integers.
+impl FixedWidth for U48 {
+    const NAME: &'static str = "U48";
+    fn write_to<W: Write>(self, writer: &mut W) -> std::io::Result<()> { writer.write_all(&u64::from(self).to_le_bytes()[..6]) }
+    fn read_from<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut bytes = [0u8;8];
+        reader.read_exact(&mut bytes[..6])?;
+        Ok(U48::try_from(u64::from_le_bytes(bytes)).unwrap())
+    }
+}
+
+/// Takes no space.
+impl FixedWidth for NoMultiplicity {
+    const NAME: &'static str = "NoMultiplicity";
+    fn write_to<W: Write>(self, _writer: &mut W) -> std::io::Result<()> { Ok(()) }
+    fn read_from<R: Read>(_reader: &mut R) -> std::io::Result<Self> { Ok(NoMultiplicity{}) }
+}
+
+pub(crate) fn invalid_data(message:String) -> std::io::Error { std::io::Error::new(ErrorKind::InvalidData,message) }
+
+fn write_name<W:Write>(writer:&mut W,name:&str) -> std::io::Result<()> {
+    writer.write_all(&[name.len() as u8])?;
+    writer.write_all(name.as_bytes())
+}
+
+pub(crate) fn check_name<R:Read>(reader:&mut R,expected:&str,what:&str) -> std::io::Result<()> {
+    let mut len = [0u8];
+    reader.read_exact(&mut len)?;
+    let mut name = vec![0u8;len[0] as usize];
+    reader.read_exact(&mut name)?;
+    if name!=expected.as_bytes() { Err(invalid_data(format!("file has {} type {} but {} was expected",what,String::from_utf8_lossy(&name),expected))) } else { Ok(()) }
+}
+
+pub(crate) fn check_header<R:Read>(reader:&mut R,magic:&[u8;4]) -> std::io::Result<()> {
+    let mut found = [0u8;5];
+    reader.read_exact(&mut found)?;
+    if &found[..4]!=magic { return Err(invalid_data(format!("file does not start with {}",String::from_utf8_lossy(magic)))); }
+    if found[4]!=FORMAT_VERSION { return Err(invalid_data(format!("file has format version {} but only {} is supported",found[4],FORMAT_VERSION))); }
+    Ok(())
+}
+
+fn write_index<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth,W:Write>(index:NodeIndex<A,M>,writer:&mut W) -> std::io::Result<()> {
+    index.address().write_to(writer)?;
+    index.multiplicity().write_to(writer)
+}
+
+/// Read a node index, checking it is less than limit.
+pub(crate) fn read_index<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth,R:Read>(reader:&mut R,limit:usize) -> std::io::Result<NodeIndex<A,M>> {
+    let address = A::read_from(reader)?;
+    let multiplicity = M::read_from(reader)?;
+    if address.as_usize()>=limit { return Err(invalid_data(format!("reference to node {} which is not before {}",address,limit))); }
+    Ok(NodeIndex{address,multiplicity})
+}
+
+impl <A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> NodeList<A,M> {
+    /// Write in the binary format described in [crate::serialization].
+    pub fn write_to<W:Write>(&self,writer:&mut W) -> std::io::Result<()> {
+        writer.write_all(NODE_LIST_MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        write_name(writer,A::NAME)?;
+        write_name(writer,M::NAME)?;
+        (self.nodes.len() as u64).write_to(writer)?;
+        for node in &self.nodes {
+            node.variable.0.write_to(writer)?;
+            write_index(node.lo,writer)?;
+            write_index(node.hi,writer)?;
+        }
+        Ok(())
+    }
+
+    /// Read something written by [NodeList::write_to], checking that it is well formed.
+    pub fn read_from<R:Read>(reader:&mut R) -> std::io::Result<Self> {
+        check_header(reader,NODE_LIST_MAGIC)?;
+        check_name(reader,A::NAME,"address")?;
+        check_name(reader,M::NAME,"multiplicity")?;
+        let len = u64::read_from(reader)?;
+        let mut res = NodeList::default();
+        for i in 0..len {
+            let address = (i as usize)+2;
+            let variable = VariableIndex(u16::read_from(reader)?);
+            let lo = read_index(reader,address)?;
+            let hi = read_index(reader,address)?;
+            for child in [lo,hi] {
+                if !child.is_sink() && res.node(child.address()).variable<=variable {
+                    return Err(invalid_data(format!("node {} has variable {} but its child {} does not have a later variable",address,variable,child.address())));
+                }
+            }
+            res.add_node(Node{variable,lo,hi});
+        }
+        Ok(res)
+    }
+
+    /// Save to a file. See [NodeList::write_to].
+    /// # Example
+    /// ```
+    /// use xdd::xdd_with_multiplicity::{NodeList, XDDBase};
+    /// use xdd::{NoMultiplicity, VariableIndex};
+    /// let mut nodes = NodeList::<u32,NoMultiplicity>::default();
+    /// let v = nodes.single_variable(VariableIndex(3));
+    /// let path = std::env::temp_dir().join("xdd_node_list_doctest.bin");
+    /// nodes.save(&path).unwrap();
+    /// let loaded = NodeList::<u32,NoMultiplicity>::load(&path).unwrap();
+    /// assert!(loaded==nodes);
+    /// assert_eq!(VariableIndex(3),loaded.node(v.address()).variable);
+    /// assert!(NodeList::<u64,NoMultiplicity>::load(&path).is_err()); // wrong address type.
+    /// ```
+    pub fn save(&self,path:impl AsRef<Path>) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Load from a file. See [NodeList::read_from].
+    pub fn load(path:impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+impl <A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> NodeListWithFastLookup<A,M> {
+    /// Write in the binary format described in [crate::serialization]. The lookup table is not stored.
+    pub fn write_to<W:Write>(&self,writer:&mut W) -> std::io::Result<()> { self.nodes.write_to(writer) }
+    /// Read something written by [NodeListWithFastLookup::write_to] or [NodeList::write_to], rebuilding the lookup table.
+    pub fn read_from<R:Read>(reader:&mut R) -> std::io::Result<Self> { Ok(NodeList::read_from(reader)?.into()) }
+    pub fn save(&self,path:impl AsRef<Path>) -> std::io::Result<()> { self.nodes.save(path) }
+    pub fn load(path:impl AsRef<Path>) -> std::io::Result<Self> { Ok(NodeList::load(path)?.into()) }
+}
+
+fn write_factory<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth,W:Write>(writer:&mut W,kind:u8,num_variables:u16,roots:&[NodeIndex<A,M>],nodes:&NodeListWithFastLookup<A,M>) -> std::io::Result<()> {
+    writer.write_all(FACTORY_MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION,kind])?;
+    num_variables.write_to(writer)?;
+    (roots.len() as u64).write_to(writer)?;
+    for &root in roots { write_index(root,writer)?; }
+    nodes.write_to(writer)
+}
+
+/// The parts of a factory that are stored.
+struct SavedFactory<A:NodeAddress,M:Multiplicity> {
+    num_variables : u16,
+    roots : Vec<NodeIndex<A,M>>,
+    nodes : NodeListWithFastLookup<A,M>,
+}
+
+/// Read the parts of a factory written by [write_factory], checking the roots are valid and the variables are in range.
+fn read_factory<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth,R:Read>(reader:&mut R,kind:u8) -> std::io::Result<SavedFactory<A,M>> {
+    check_header(reader,FACTORY_MAGIC)?;
+    let mut found_kind = [0u8];
+    reader.read_exact(&mut found_kind)?;
+    if found_kind[0]!=kind { return Err(invalid_data(format!("file is for a {}DD but a {}DD was expected",found_kind[0] as char,kind as char))); }
+    let num_variables = u16::read_from(reader)?;
+    let num_roots = u64::read_from(reader)?;
+    let mut roots : Vec<NodeIndex<A,M>> = vec![];
+    for _ in 0..num_roots { roots.push(read_index(reader,usize::MAX)?); }
+    let nodes = NodeListWithFastLookup::<A,M>::read_from(reader)?;
+    if nodes.node_to_index.len()!=nodes.len() { return Err(invalid_data("the same node is stored more than once".to_string())); }
+    for root in &roots {
+        if root.address().as_usize()>=nodes.len()+2 { return Err(invalid_data(format!("root {} refers to a node that does not exist",root.address()))); }
+    }
+    if let Some(last) = nodes.nodes.nodes.iter().map(|n|n.variable).max() {
+        if last.0>=num_variables { return Err(invalid_data(format!("variable {} used in a factory with only {} variables",last,num_variables))); }
+    }
+    Ok(SavedFactory{num_variables,roots,nodes})
+}
+
+impl <A:NodeAddress+Default+FixedWidth,M:Multiplicity+FixedWidth> BDDFactory<A,M> {
+    /// Write the nodes and number of variables, and the given roots, in the format described in [crate::serialization].
+    /// The caches and statistics are not saved.
+    pub fn write_to<W:Write>(&self,writer:&mut W,roots:&[NodeIndex<A,M>]) -> std::io::Result<()> {
+        write_factory(writer,b'B',self.num_variables,roots,&self.nodes)
+    }
+    /// Read something written by [BDDFactory::write_to], returning the factory and the roots.
+    pub fn read_from<R:Read>(reader:&mut R) -> std::io::Result<(Self,Vec<NodeIndex<A,M>>)> {
+        let SavedFactory{num_variables,roots,nodes} = read_factory(reader,b'B')?;
+        let mut factory = Self::new(num_variables);
+        factory.statistics.note_len(nodes.len());
+        factory.nodes=nodes;
+        Ok((factory,roots))
+    }
+    /// Save to a file. See [BDDFactory::write_to].
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(4);
+    /// let v0 = factory.single_variable(VariableIndex(0));
+    /// let v3 = factory.single_variable(VariableIndex(3));
+    /// let f = factory.or(v0,v3);
+    /// let path = std::env::temp_dir().join("xdd_bdd_factory_doctest.bin");
+    /// factory.save(&path,&[f]).unwrap();
+    /// let (loaded,roots) = BDDFactory::<u32,NoMultiplicity>::load(&path).unwrap();
+    /// assert_eq!(vec![f],roots);
+    /// assert_eq!(12,loaded.number_solutions::<u64>(roots[0]));
+    /// ```
+    pub fn save(&self,path:impl AsRef<Path>,roots:&[NodeIndex<A,M>]) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer,roots)?;
+        writer.flush()
+    }
+    /// Load from a file. See [BDDFactory::read_from].
+    pub fn load(path:impl AsRef<Path>) -> std::io::Result<(Self,Vec<NodeIndex<A,M>>)> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+impl <A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> ZDDFactory<A,M> {
+    /// Write the nodes and number of variables, and the given roots, in the format described in [crate::serialization].
+    /// The caches and statistics are not saved.
+    pub fn write_to<W:Write>(&self,writer:&mut W,roots:&[NodeIndex<A,M>]) -> std::io::Result<()> {
+        write_factory(writer,b'Z',self.num_variables,roots,&self.nodes)
+    }
+    /// Read something written by [ZDDFactory::write_to], returning the factory and the roots.
+    pub fn read_from<R:Read>(reader:&mut R) -> std::io::Result<(Self,Vec<NodeIndex<A,M>>)> {
+        let SavedFactory{num_variables,roots,nodes} = read_factory(reader,b'Z')?;
+        let mut factory = Self::new(num_variables);
+        factory.statistics.note_len(nodes.len());
+        factory.nodes=nodes;
+        Ok((factory,roots))
+    }
+    /// Save to a file. See [ZDDFactory::write_to].
+    pub fn save(&self,path:impl AsRef<Path>,roots:&[NodeIndex<A,M>]) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer,roots)?;
+        writer.flush()
+    }
+    /// Load from a file. See [ZDDFactory::read_from].
+    pub fn load(path:impl AsRef<Path>) -> std::io::Result<(Self,Vec<NodeIndex<A,M>>)> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+/// The named roots saved in a checkpoint, in the order they were given.
+pub type CheckpointRoots<A,M> = Vec<(String,NodeIndex<A,M>)>;
+
+fn write_string<W:Write>(writer:&mut W,s:&str) -> std::io::Result<()> {
+    (s.len() as u64).write_to(writer)?;
+    writer.write_all(s.as_bytes())
+}
+
+fn read_string<R:Read>(reader:&mut R) -> std::io::Result<String> {
+    let len = u64::read_from(reader)?;
+    let mut bytes = vec![];
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64!=len { return Err(std::io::Error::new(ErrorKind::UnexpectedEof,"file ends part way through a string")); }
+    String::from_utf8(bytes).map_err(|_|invalid_data("a string is not valid UTF-8".to_string()))
+}
+
+/// Write to a temporary file next to path, synced to disk, then rename it to path, so path always holds a complete file.
+/// The directory is then synced so the rename itself survives a crash. If anything fails the temporary file is removed.
+fn save_atomically(path:&Path,write:impl FnOnce(&mut BufWriter<File>)->std::io::Result<()>) -> std::io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".partial");
+    let temporary = PathBuf::from(temporary);
+    let written = File::create(&temporary).and_then(|file|{
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()
+    });
+    if let Err(error) = written.and_then(|()|std::fs::rename(&temporary,path)) {
+        let _ = std::fs::remove_file(&temporary); // it may never have been made; the original error is more useful.
+        return Err(error);
+    }
+    sync_directory(path)
+}
+
+/// Sync the directory containing path to disk, so that a file just renamed into it is not lost in a crash.
+#[cfg(unix)]
+fn sync_directory(path:&Path) -> std::io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(directory)?.sync_all()
+}
+
+/// Directories can not be opened to sync them on this platform, which makes renames durable itself.
+#[cfg(not(unix))]
+fn sync_directory(_path:&Path) -> std::io::Result<()> { Ok(()) }
+
+/// Write a checkpoint of a factory, as described in [crate::serialization].
+fn write_checkpoint<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth,F:DecisionDiagramFactory<A,M>,W:Write,const BDD:bool>(writer:&mut W,kind:u8,factory:&F,nodes:&NodeListWithFastLookup<A,M>,roots:&[(&str,NodeIndex<A,M>)]) -> std::io::Result<()> {
+    writer.write_all(CHECKPOINT_MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION])?;
+    (roots.len() as u64).write_to(writer)?;
+    for &(name,root) in roots {
+        write_string(writer,name)?;
+        solution_checksum::<A,M,BDD>(nodes,root,factory.num_variables()).write_to(writer)?;
+    }
+    let registry = factory.variable_registry();
+    let named : Vec<(u16,&str)> = (0..factory.num_variables()).filter_map(|v|registry.get_name(VariableIndex(v)).map(|name|(v,name))).collect();
+    (named.len() as u64).write_to(writer)?;
+    for (v,name) in named {
+        v.write_to(writer)?;
+        write_string(writer,name)?;
+    }
+    let indices : Vec<NodeIndex<A,M>> = roots.iter().map(|(_,root)|*root).collect();
+    write_factory(writer,kind,factory.num_variables(),&indices,nodes)
+}
+
+/// The number of solutions of root ignoring multiplicities, modulo 2^64, stored in a checkpoint to detect corruption.
+/// Each solution is one path to TRUE, so this is a count of paths, with variables skipped in a BDD doubling it.
+fn solution_checksum<A:NodeAddress,M:Multiplicity,const BDD:bool>(nodes:&NodeListWithFastLookup<A,M>,root:NodeIndex<A,M>,num_variables:u16) -> u64 {
+    let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { nodes.node(index.address()).variable.0 };
+    let mut counts : HashMap<A,u64> = HashMap::new();
+    counts.insert(A::FALSE,0);
+    counts.insert(A::TRUE,1);
+    // the count for index, including the variables from from_level.
+    let from = |counts:&HashMap<A,u64>,index:NodeIndex<A,M>,from_level:u16| {
+        let count = counts[&index.address()];
+        if BDD { count.checked_shl((level(index)-from_level) as u32).unwrap_or(0) } else { count }
+    };
+    for address in nodes.post_order(root) {
+        let node = nodes.node(address);
+        let count = from(&counts,node.lo,node.variable.0+1).wrapping_add(from(&counts,node.hi,node.variable.0+1));
+        counts.insert(address,count);
+    }
+    from(&counts,root,0)
+}
+
+/// The parts of a checkpoint, as written by [write_checkpoint].
+struct SavedCheckpoint<A:NodeAddress,M:Multiplicity> {
+    factory : SavedFactory<A,M>,
+    root_names : Vec<String>,
+    /// The checksum of each root, see [solution_checksum].
+    checksums : Vec<u64>,
+    variable_names : Vec<(VariableIndex,String)>,
+}
+
+fn read_checkpoint<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth,R:Read>(reader:&mut R,kind:u8) -> std::io::Result<SavedCheckpoint<A,M>> {
+    check_header(reader,CHECKPOINT_MAGIC)?;
+    let num_roots = u64::read_from(reader)?;
+    let mut root_names = vec![];
+    let mut checksums = vec![];
+    for _ in 0..num_roots {
+        root_names.push(read_string(reader)?);
+        checksums.push(u64::read_from(reader)?);
+    }
+    let num_named = u64::read_from(reader)?;
+    let mut variable_names = vec![];
+    for _ in 0..num_named {
+        let variable = VariableIndex(u16::read_from(reader)?);
+        variable_names.push((variable,read_string(reader)?));
+    }
+    let factory = read_factory(reader,kind)?;
+    if factory.roots.len()!=root_names.len() { return Err(invalid_data(format!("checkpoint has {} root names but {} roots",root_names.len(),factory.roots.len()))); }
+    if let Some((variable,_)) = variable_names.iter().find(|(v,_)|v.0>=factory.num_variables) {
+        return Err(invalid_data(format!("variable {} named in a factory with only {} variables",variable,factory.num_variables)));
+    }
+    Ok(SavedCheckpoint{factory,root_names,checksums,variable_names})
+}
+
+/// Something wrong with a root of a checkpoint, found by [BDDFactory::verify_checkpoint].
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum CheckpointProblem<A:NodeAddress> {
+    /// The named root's diagram is not one the operations could have made; see [XDDBase::validate].
+    Invalid{root:String,problems:InvalidDiagram<A>},
+    /// The named root's number of solutions (modulo 2<sup>64</sup>) is not what it was when saved.
+    WrongChecksum{root:String,stored:u64,found:u64},
+}
+
+impl <A:NodeAddress> Display for CheckpointProblem<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointProblem::Invalid{root,problems} => write!(f,"root {}: {}",root,problems),
+            CheckpointProblem::WrongChecksum{root,stored,found} => write!(f,"root {} has checksum {} but {} was stored",root,found,stored),
+        }
+    }
+}
+
+/// Read a checkpoint, validate each root and compare its checksum to the stored one.
+fn verify_checkpoint<A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth,const BDD:bool>(path:&Path,kind:u8) -> std::io::Result<Vec<CheckpointProblem<A>>> {
+    let SavedCheckpoint{factory:SavedFactory{num_variables,roots,nodes},root_names,checksums,..} = read_checkpoint::<A,M,_>(&mut BufReader::new(File::open(path)?),kind)?;
+    let mut problems = vec![];
+    for (i,(root,name)) in roots.into_iter().zip(root_names).enumerate() {
+        if let Err(invalid) = nodes.validate::<BDD>(root,num_variables) {
+            problems.push(CheckpointProblem::Invalid{root:name,problems:invalid});
+        } else {
+            let found = solution_checksum::<A,M,BDD>(&nodes,root,num_variables);
+            if found!=checksums[i] { problems.push(CheckpointProblem::WrongChecksum{root:name,stored:checksums[i],found}); }
+        }
+    }
+    Ok(problems)
+}
+
+impl <A:NodeAddress+Default+FixedWidth,M:Multiplicity+FixedWidth> BDDFactory<A,M> {
+    /// Save the nodes, variable names and the given named roots to path, so a long computation can later be resumed with
+    /// [BDDFactory::load_checkpoint]. The file is replaced atomically, so if the program dies while saving, the previous
+    /// checkpoint is still there. Operation caches are not saved; they are just rebuilt as they are used after resuming.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    /// factory.variable_registry_mut().set_name(VariableIndex(0),"first");
+    /// let done = factory.exactly_n_of(&variables,3);
+    /// let partial = factory.at_most_n_of(&variables[2..],1);
+    /// let path = std::env::temp_dir().join("xdd_bdd_checkpoint_doctest.bin");
+    /// factory.save_checkpoint(&path,&[("done",done),("partial",partial)]).unwrap();
+    /// let (mut resumed,roots) = BDDFactory::<u32,NoMultiplicity>::load_checkpoint(&path).unwrap();
+    /// assert_eq!(vec![("done".to_string(),done),("partial".to_string(),partial)],roots);
+    /// assert_eq!(Some(VariableIndex(0)),resumed.variable_registry().variable("first"));
+    /// let more = resumed.and(roots[0].1,roots[1].1);
+    /// assert_eq!(factory.and(done,partial),more);
+    /// ```
+    pub fn save_checkpoint(&self,path:impl AsRef<Path>,roots:&[(&str,NodeIndex<A,M>)]) -> std::io::Result<()> {
+        save_atomically(path.as_ref(),|writer|write_checkpoint::<A,M,_,_,true>(writer,b'B',self,&self.nodes,roots))
+    }
+    /// Resume from a file written by [BDDFactory::save_checkpoint], returning the factory and the named roots in the order saved.
+    pub fn load_checkpoint(path:impl AsRef<Path>) -> std::io::Result<(Self,CheckpointRoots<A,M>)> {
+        let SavedCheckpoint{factory:SavedFactory{num_variables,roots,nodes},root_names,variable_names,..} = read_checkpoint(&mut BufReader::new(File::open(path)?),b'B')?;
+        let mut factory = Self::new(num_variables);
+        factory.statistics.note_len(nodes.len());
+        factory.nodes=nodes;
+        for (variable,name) in variable_names { factory.variable_registry_mut().set_name(variable,name); }
+        Ok((factory,root_names.into_iter().zip(roots).collect()))
+    }
+    /// Check a file written by [BDDFactory::save_checkpoint] without loading it into a factory: each root is checked with
+    /// [XDDBase::validate], and its number of solutions compared to the checksum stored when it was saved. Returns the
+    /// problems found, so an empty list means the checkpoint is healthy; a file that cannot be read at all is an error.
+    /// Not every corruption can be found, but this catches most before a long computation uses a bad input.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    /// let three = factory.exactly_n_of(&variables,3);
+    /// let path = std::env::temp_dir().join("xdd_bdd_verify_doctest.bin");
+    /// factory.save_checkpoint(&path,&[("three",three)]).unwrap();
+    /// assert!(BDDFactory::<u32,NoMultiplicity>::verify_checkpoint(&path).unwrap().is_empty());
+    /// ```
+    pub fn verify_checkpoint(path:impl AsRef<Path>) -> std::io::Result<Vec<CheckpointProblem<A>>> {
+        verify_checkpoint::<A,M,true>(path.as_ref(),b'B')
+    }
+}
+
+impl <A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> ZDDFactory<A,M> {
+    /// Save the nodes, variable names and the given named roots to path; see [BDDFactory::save_checkpoint].
+    pub fn save_checkpoint(&self,path:impl AsRef<Path>,roots:&[(&str,NodeIndex<A,M>)]) -> std::io::Result<()> {
+        save_atomically(path.as_ref(),|writer|write_checkpoint::<A,M,_,_,false>(writer,b'Z',self,&self.nodes,roots))
+    }
+    /// Resume from a file written by [ZDDFactory::save_checkpoint], returning the factory and the named roots in the order saved.
+    pub fn load_checkpoint(path:impl AsRef<Path>) -> std::io::Result<(Self,CheckpointRoots<A,M>)> {
+        let SavedCheckpoint{factory:SavedFactory{num_variables,roots,nodes},root_names,variable_names,..} = read_checkpoint(&mut BufReader::new(File::open(path)?),b'Z')?;
+        let mut factory = Self::new(num_variables);
+        factory.statistics.note_len(nodes.len());
+        factory.nodes=nodes;
+        for (variable,name) in variable_names { factory.variable_registry_mut().set_name(variable,name); }
+        Ok((factory,root_names.into_iter().zip(roots).collect()))
+    }
+    /// Check a file written by [ZDDFactory::save_checkpoint]; see [BDDFactory::verify_checkpoint].
+    pub fn verify_checkpoint(path:impl AsRef<Path>) -> std::io::Result<Vec<CheckpointProblem<A>>> {
+        verify_checkpoint::<A,M,false>(path.as_ref(),b'Z')
+    }
+}
+
+impl <A:NodeAddress+Default,M:Multiplicity> BDDFactory<A,M> {
+    /// Write the BDD starting at root in the text format of BuDDy's `bdd_save`, so it can be read by other packages.
+    ///
+    /// This is a line giving the number of nodes and variables, a line giving the level of each variable
+    /// (here always the identity ordering), then a line `address variable lo hi` for each node, children first.
+    /// A sink root is written as `0 0 address`. The format has no place for multiplicities, so an error of kind
+    /// InvalidInput is returned if any are not unity.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+    /// let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    /// let mut text = vec![];
+    /// factory.write_buddy(&mut text,f).unwrap();
+    /// let mut other = BDDFactory::<u32,NoMultiplicity>::new(3);
+    /// let g = other.read_buddy(&mut text.as_slice()).unwrap();
+    /// assert_eq!(4,other.number_solutions::<u64>(g));
+    /// ```
+    pub fn write_buddy<W:Write>(&self,writer:&mut W,root:NodeIndex<A,M>) -> std::io::Result<()> {
+        let not_unity = ||std::io::Error::new(ErrorKind::InvalidInput,"multiplicities can not be written in BuDDy format");
+        if !root.multiplicity().is_unity() { return Err(not_unity()); }
+        if root.is_sink() { return writeln!(writer,"0 0 {}",root.address()); }
+        let nodes : Vec<A> = self.nodes.post_order(root).collect();
+        writeln!(writer,"{} {}",nodes.len(),self.num_variables)?;
+        writeln!(writer,"{}",(0..self.num_variables).map(|v|v.to_string()).collect::<Vec<_>>().join(" "))?;
+        for address in nodes {
+            let node = self.nodes.node(address);
+            if !(node.lo.multiplicity().is_unity() && node.hi.multiplicity().is_unity()) { return Err(not_unity()); }
+            writeln!(writer,"{} {} {} {}",address,node.variable.0,node.lo.address(),node.hi.address())?;
+        }
+        Ok(())
+    }
+    /// Read a BDD in BuDDy's `bdd_save` format (see [BDDFactory::write_buddy]), returning its root.
+    /// It must not use more variables than this factory has.
+    ///
+    /// The variables here are the levels in the file, so a file with a non-identity variable ordering is read as
+    /// the diagram with its variables renamed into that order. Any problem with the file, including a node whose
+    /// children are the same (which BuDDy never writes), is reported as an error of kind InvalidData. The whole file
+    /// is checked before any nodes are added, so a bad file leaves the factory unchanged.
+    pub fn read_buddy<R:Read>(&mut self,reader:&mut R) -> std::io::Result<NodeIndex<A,M>> {
+        let start = Instant::now();
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let mut tokens = text.split_whitespace();
+        let mut next = ||->std::io::Result<usize> {
+            let token = tokens.next().ok_or_else(||invalid_data("unexpected end of BuDDy file".to_string()))?;
+            token.parse().map_err(|_|invalid_data(format!("expected a non-negative integer in BuDDy file, found {}",token)))
+        };
+        let num_nodes = next()?;
+        let num_variables = next()?;
+        if num_variables>self.num_variables as usize { return Err(invalid_data(format!("file has {} variables but the factory only has {}",num_variables,self.num_variables))); }
+        if num_nodes==0 {
+            return match next()? {
+                0 => Ok(NodeIndex::FALSE),
+                1 => Ok(NodeIndex::TRUE),
+                root => Err(invalid_data(format!("constant root {} should be 0 or 1",root))),
+            };
+        }
+        let mut level_used = vec![false;num_variables];
+        let mut var_to_level = vec![];
+        for _ in 0..num_variables {
+            let level = next()?;
+            if level>=level_used.len() || level_used[level] { return Err(invalid_data(format!("variable levels are not a permutation at level {}",level))); }
+            level_used[level]=true;
+            var_to_level.push(VariableIndex(level as u16));
+        }
+        // Parse and check every node before adding any. A node is referred to by its position: 0 and 1 for the sinks,
+        // then 2 onwards for the distinct nodes in the file. Identical nodes get the same position, so a node whose
+        // children are the same can be found here rather than after it has been added.
+        let mut position_of_id : HashMap<usize,usize> = HashMap::from([(0,0),(1,1)]);
+        let mut position_of_node : HashMap<(VariableIndex,usize,usize),usize> = HashMap::new();
+        let mut parsed : Vec<(VariableIndex,usize,usize)> = vec![];
+        let mut root = 0;
+        for _ in 0..num_nodes {
+            let id = next()?;
+            let variable = next()?;
+            let variable = *var_to_level.get(variable).ok_or_else(||invalid_data(format!("node {} uses variable {} but there are only {} variables",id,variable,num_variables)))?;
+            let child = |child_id:usize| {
+                let position = *position_of_id.get(&child_id).ok_or_else(||invalid_data(format!("node {} refers to {} which has not been defined yet",id,child_id)))?;
+                if position>=2 && parsed[position-2].0<=variable { return Err(invalid_data(format!("node {} is not above its child {} in the variable order",id,child_id))); }
+                Ok(position)
+            };
+            let lo = child(next()?)?;
+            let hi = child(next()?)?;
+            if lo==hi { return Err(invalid_data(format!("node {} has both children the same",id))); }
+            if id<2 || position_of_id.contains_key(&id) { return Err(invalid_data(format!("node {} is defined more than once",id))); }
+            root = *position_of_node.entry((variable,lo,hi)).or_insert_with(||{ parsed.push((variable,lo,hi)); parsed.len()+1 });
+            position_of_id.insert(id,root);
+        }
+        let mut indices : Vec<NodeIndex<A,M>> = vec![NodeIndex::FALSE,NodeIndex::TRUE];
+        for (variable,lo,hi) in parsed {
+            let index = self.nodes.add_node_if_not_present(Node{variable,lo:indices[lo],hi:indices[hi]});
+            indices.push(index);
+        }
+        self.statistics.record(OperationClass::Construct,start,self.nodes.len());
+        Ok(indices[root])
+    }
+}
@@ -0,0 +1,117 @@
+//! Building the conjunction of a very large number of constraints with several processes.
+//!
+//! The constraints are numbered 0 to n-1 and split into shards of consecutive constraints, consecutive constraints usually
+//! sharing variables and so making small partial diagrams. A worker process builds the [DecisionDiagramFactory::poly_and]
+//! of one shard's constraints in its own factory, and saves it as a checkpoint (see [crate::serialization]) in a directory
+//! shared with the coordinator. The coordinator then checks each checkpoint with [ShardFactory::verify_checkpoint],
+//! imports the partial diagrams into one factory and combines them with [DecisionDiagramFactory::poly_and].
+//!
+//! The processes communicate only through these files, so the workers can be on any machines sharing the directory.
+//! Usually the workers are the same program as the coordinator, started with different arguments, for example
+//! with [Sharding::run_workers]. Each must make the same constraint given the same number.
+//!
+//! # Example
+//! Here the workers are run one after another in one process, as a test; normally each would be a separate process.
+//! ```
+//! use xdd::{DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+//! use xdd::sharding::Sharding;
+//! // no two adjacent variables of 20 are both true.
+//! let constraint = |factory:&mut ZDDFactory<u32,NoMultiplicity>,i:usize|factory.at_most_n_of(&[VariableIndex(i as u16),VariableIndex(i as u16+1)],1);
+//! let sharding = Sharding::new(std::env::temp_dir().join("xdd_sharding_doctest"),3);
+//! std::fs::create_dir_all(&sharding.directory).unwrap();
+//! for shard in 0..3 { sharding.run_worker(shard,20,19,constraint).unwrap(); }
+//! let (factory,no_adjacent) = sharding.merge::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>(20).unwrap();
+//! assert_eq!(17711,factory.number_solutions::<u64>(no_adjacent.unwrap()));
+//! ```
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, ZDDFactory};
+use crate::serialization::{invalid_data, CheckpointProblem, CheckpointRoots, FixedWidth};
+
+/// A factory whose diagrams can be passed between processes as checkpoints, as needed by [Sharding].
+/// This just gives access to the checkpoint functions of [BDDFactory] and [ZDDFactory] in generic code.
+pub trait ShardFactory<A:NodeAddress,M:Multiplicity> : DecisionDiagramFactory<A,M>+Sized {
+    /// See [BDDFactory::save_checkpoint].
+    fn save_checkpoint(&self,path:&Path,roots:&[(&str,NodeIndex<A,M>)]) -> std::io::Result<()>;
+    /// See [BDDFactory::load_checkpoint].
+    fn load_checkpoint(path:&Path) -> std::io::Result<(Self,CheckpointRoots<A,M>)>;
+    /// See [BDDFactory::verify_checkpoint].
+    fn verify_checkpoint(path:&Path) -> std::io::Result<Vec<CheckpointProblem<A>>>;
+    /// See [BDDFactory::import_from].
+    fn import_from(&mut self,other:&Self,root:NodeIndex<A,M>) -> NodeIndex<A,M>;
+}
+
+impl <A:NodeAddress+Default+FixedWidth,M:Multiplicity+FixedWidth> ShardFactory<A,M> for BDDFactory<A,M> {
+    fn save_checkpoint(&self, path: &Path, roots: &[(&str, NodeIndex<A, M>)]) -> std::io::Result<()> { BDDFactory::save_checkpoint(self,path,roots) }
+    fn load_checkpoint(path: &Path) -> std::io::Result<(Self, CheckpointRoots<A, M>)> { BDDFactory::load_checkpoint(path) }
+    fn verify_checkpoint(path: &Path) -> std::io::Result<Vec<CheckpointProblem<A>>> { BDDFactory::<A,M>::verify_checkpoint(path) }
+    fn import_from(&mut self, other: &Self, root: NodeIndex<A, M>) -> NodeIndex<A, M> { BDDFactory::import_from(self,other,root) }
+}
+
+impl <A:NodeAddress+FixedWidth,M:Multiplicity+FixedWidth> ShardFactory<A,M> for ZDDFactory<A,M> {
+    fn save_checkpoint(&self, path: &Path, roots: &[(&str, NodeIndex<A, M>)]) -> std::io::Result<()> { ZDDFactory::save_checkpoint(self,path,roots) }
+    fn load_checkpoint(path: &Path) -> std::io::Result<(Self, CheckpointRoots<A, M>)> { ZDDFactory::load_checkpoint(path) }
+    fn verify_checkpoint(path: &Path) -> std::io::Result<Vec<CheckpointProblem<A>>> { ZDDFactory::<A,M>::verify_checkpoint(path) }
+    fn import_from(&mut self, other: &Self, root: NodeIndex<A, M>) -> NodeIndex<A, M> { ZDDFactory::import_from(self,other,root) }
+}
+
+/// How a computation is split into shards, and where the shards' results are kept.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Sharding {
+    /// The directory holding each shard's checkpoint. It must already exist.
+    pub directory : PathBuf,
+    /// The number of shards, and so of worker processes.
+    pub num_shards : usize,
+}
+
+impl Sharding {
+    /// Split a computation into num_shards shards, keeping the results in directory. Panics if num_shards is 0.
+    pub fn new(directory:impl Into<PathBuf>,num_shards:usize) -> Self {
+        assert!(num_shards>0,"There should be at least one shard");
+        Sharding{directory:directory.into(),num_shards}
+    }
+    /// The numbers of the constraints, of num_constraints, that the given shard combines. These are consecutive, and
+    /// the shards' sizes differ by at most one. A shard has no constraints if there are fewer constraints than shards.
+    pub fn constraints(&self,shard:usize,num_constraints:usize) -> Range<usize> {
+        shard*num_constraints/self.num_shards..(shard+1)*num_constraints/self.num_shards
+    }
+    /// The file holding the result of the given shard.
+    pub fn shard_path(&self,shard:usize) -> PathBuf { self.directory.join(format!("shard{}.bin",shard)) }
+    /// Do the work of one shard: make each of its constraints with constraint(factory,number) in a new factory with
+    /// num_variables variables, combine them with [DecisionDiagramFactory::poly_and], and save the result to [Self::shard_path].
+    pub fn run_worker<A:NodeAddress,M:Multiplicity,F:ShardFactory<A,M>>(&self,shard:usize,num_variables:u16,num_constraints:usize,mut constraint:impl FnMut(&mut F,usize)->NodeIndex<A,M>) -> std::io::Result<()> {
+        let mut factory = F::new(num_variables);
+        let constraints : Vec<NodeIndex<A,M>> = self.constraints(shard,num_constraints).map(|i|constraint(&mut factory,i)).collect();
+        let roots : Vec<(&str,NodeIndex<A,M>)> = factory.poly_and(&constraints).map(|root|("shard",root)).into_iter().collect();
+        factory.save_checkpoint(&self.shard_path(shard),&roots)
+    }
+    /// Start a worker process for each shard, with the command made by command(shard), and wait for them all to finish.
+    /// Returns an error if any worker could not be started or did not succeed.
+    pub fn run_workers(&self,mut command:impl FnMut(usize)->Command) -> std::io::Result<()> {
+        let mut workers = (0..self.num_shards).map(|shard|command(shard).spawn()).collect::<std::io::Result<Vec<_>>>()?;
+        let mut failed = vec![];
+        for (shard,worker) in workers.iter_mut().enumerate() {
+            let status = worker.wait()?;
+            if !status.success() { failed.push(format!("shard {} {}",shard,status)); }
+        }
+        if failed.is_empty() { Ok(()) } else { Err(std::io::Error::other(format!("workers failed: {}",failed.join(", ")))) }
+    }
+    /// Combine the results of all the shards into a new factory with num_variables variables, returning the factory and the
+    /// conjunction of all the constraints, or None if there were no constraints. Each shard's checkpoint is verified first,
+    /// and any problem found is an error.
+    pub fn merge<A:NodeAddress,M:Multiplicity,F:ShardFactory<A,M>>(&self,num_variables:u16) -> std::io::Result<(F,Option<NodeIndex<A,M>>)> {
+        let mut factory = F::new(num_variables);
+        let mut parts = vec![];
+        for shard in 0..self.num_shards {
+            let path = self.shard_path(shard);
+            let problems = F::verify_checkpoint(&path)?;
+            if let Some(problem) = problems.first() { return Err(invalid_data(format!("shard {}: {}",shard,problem))); }
+            let (worker,roots) = F::load_checkpoint(&path)?;
+            for (_,root) in roots { parts.push(factory.import_from(&worker,root)); }
+        }
+        let res = factory.poly_and(&parts);
+        Ok((factory,res))
+    }
+}
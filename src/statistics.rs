@@ -0,0 +1,269 @@
+//! Statistics about the work done by a factory, so a long run can report what happened.
+//!
+//! Factories keep running totals as they work; [crate::DecisionDiagramFactory::summary] collates them
+//! into a [FactoryStatistics] which can be printed. For feedback during a long operation, a factory can
+//! also call back with its [Progress] (see [crate::DecisionDiagramFactory::set_progress_callback]), and
+//! be stopped with a [CancellationToken].
+
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use crate::xdd_with_multiplicity::OperationCache;
+
+/// The classes of factory operation that are timed.
+#[derive(Copy, Clone,Eq, PartialEq,Hash,Debug)]
+pub enum OperationClass {
+    And,
+    Or,
+    Xor,
+    /// An arbitrary boolean operator
+    Apply,
+    Not,
+    /// if-then-else
+    Ite,
+    /// Substitution of functions for variables
+    Compose,
+    /// Fixing a variable to a constant
+    Restrict,
+    /// Existential quantification, including the relational product
+    Quantify,
+    /// The ZDD family algebra, such as union, join and quotient
+    Family,
+    /// Construction of simple diagrams such as single_variable or exactly_one_of.
+    Construct,
+    Gc,
+}
+
+impl OperationClass {
+    pub const ALL : [OperationClass;12] = [OperationClass::And,OperationClass::Or,OperationClass::Xor,OperationClass::Apply,OperationClass::Not,OperationClass::Ite,OperationClass::Compose,OperationClass::Restrict,OperationClass::Quantify,OperationClass::Family,OperationClass::Construct,OperationClass::Gc];
+}
+
+impl Display for OperationClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{}",match self {
+            OperationClass::And => "and",
+            OperationClass::Or => "or",
+            OperationClass::Xor => "xor",
+            OperationClass::Apply => "apply",
+            OperationClass::Not => "not",
+            OperationClass::Ite => "ite",
+            OperationClass::Compose => "compose",
+            OperationClass::Restrict => "restrict",
+            OperationClass::Quantify => "quantify",
+            OperationClass::Family => "family",
+            OperationClass::Construct => "construct",
+            OperationClass::Gc => "gc",
+        })
+    }
+}
+
+/// The number of calls to, and wall clock time spent in, one class of operation.
+#[derive(Copy, Clone,Eq, PartialEq,Debug,Default)]
+pub struct OperationTiming {
+    pub calls : u64,
+    pub time : Duration,
+}
+
+/// How useful an operation cache has been.
+#[derive(Copy, Clone,Eq, PartialEq,Debug,Default)]
+pub struct CacheStatistics {
+    /// The number of results currently remembered.
+    pub entries : usize,
+    /// The maximum number of results remembered, or None if unbounded.
+    pub capacity : Option<usize>,
+    pub hits : u64,
+    pub misses : u64,
+    /// The number of results forgotten to make room for others. Always 0 if unbounded.
+    pub evictions : u64,
+    /// The approximate number of bytes of heap memory used.
+    pub memory : usize,
+}
+
+impl CacheStatistics {
+    pub fn of<K,V>(cache:&OperationCache<K,V>) -> Self {
+        CacheStatistics{ entries: cache.len(), capacity: cache.capacity(), hits: cache.hits(), misses: cache.misses(), evictions: cache.evictions(), memory: cache.memory_usage() }
+    }
+    /// The proportion of lookups that were hits, or 0 if there were no lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits+self.misses;
+        if lookups==0 { 0.0 } else { self.hits as f64/lookups as f64 }
+    }
+}
+
+/// A summary of the work done by a factory since it was created.
+#[derive(Clone,Debug,Default)]
+pub struct FactoryStatistics {
+    /// The number of nodes in the factory now.
+    pub current_nodes : usize,
+    /// The largest number of nodes the factory has held.
+    pub peak_nodes : usize,
+    /// The total number of nodes ever created, including ones since removed by gc.
+    pub nodes_created : u64,
+    pub gc_count : u64,
+    /// The total number of nodes removed by gc.
+    pub gc_reclaimed_nodes : u64,
+    /// Timing for each class of operation, indexed in the order of [OperationClass::ALL].
+    pub timings : [OperationTiming;OperationClass::ALL.len()],
+    /// The name and statistics of each operation cache.
+    pub caches : Vec<(&'static str,CacheStatistics)>,
+}
+
+impl FactoryStatistics {
+    pub fn timing(&self,class:OperationClass) -> OperationTiming {
+        self.timings[class as usize]
+    }
+    /// Note that the factory now has len nodes. Any increase is assumed to be newly created nodes.
+    pub(crate) fn note_len(&mut self,len:usize) {
+        if len>self.current_nodes { self.nodes_created+=(len-self.current_nodes) as u64; }
+        self.current_nodes=len;
+        self.peak_nodes=self.peak_nodes.max(len);
+    }
+    /// Note that an operation of the given class, started at start, has finished leaving the factory with len nodes.
+    pub(crate) fn record(&mut self,class:OperationClass,start:Instant,len:usize) {
+        let timing = &mut self.timings[class as usize];
+        timing.calls+=1;
+        timing.time+=start.elapsed();
+        self.note_len(len);
+    }
+    /// Note that a gc, started at start, has finished leaving the factory with len nodes.
+    pub(crate) fn record_gc(&mut self,start:Instant,len:usize) {
+        self.gc_count+=1;
+        self.gc_reclaimed_nodes+=self.current_nodes.saturating_sub(len) as u64;
+        self.current_nodes=len;
+        self.record(OperationClass::Gc,start,len);
+    }
+}
+
+impl Display for FactoryStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"Nodes: {} now, {} peak, {} created",self.current_nodes,self.peak_nodes,self.nodes_created)?;
+        writeln!(f,"GC: {} runs reclaiming {} nodes",self.gc_count,self.gc_reclaimed_nodes)?;
+        for (name,cache) in &self.caches {
+            write!(f,"Cache {}: {} entries, {} hits, {} misses ({:.1}% hits)",name,cache.entries,cache.hits,cache.misses,100.0*cache.hit_rate())?;
+            if let Some(capacity) = cache.capacity { write!(f,", capacity {}, {} evictions",capacity,cache.evictions)?; }
+            writeln!(f)?;
+        }
+        for class in OperationClass::ALL {
+            let timing = self.timing(class);
+            if timing.calls>0 { writeln!(f,"Time in {}: {:.3}s over {} calls",class,timing.time.as_secs_f64(),timing.calls)?; }
+        }
+        Ok(())
+    }
+}
+
+/// The shape of the diagram reachable from one root, as produced by [crate::DecisionDiagramFactory::profile].
+/// Useful for seeing which levels a bad variable order makes wide. Sinks are not counted as nodes.
+#[derive(Clone,Debug,Eq,PartialEq,Default)]
+pub struct DiagramProfile {
+    /// nodes_per_level\[v\] is the number of reachable nodes testing variable v. One entry per variable in the factory.
+    pub nodes_per_level : Vec<usize>,
+    /// The number of edges out of reachable nodes, not counting edges to the FALSE sink.
+    pub edges : usize,
+    /// The largest entry in nodes_per_level.
+    pub width : usize,
+    /// The largest number of nodes on a path from the root to a sink.
+    pub longest_path : usize,
+}
+
+impl DiagramProfile {
+    /// The total number of reachable nodes.
+    pub fn nodes(&self) -> usize { self.nodes_per_level.iter().sum() }
+}
+
+impl Display for DiagramProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"{} nodes, {} edges, width {}, longest path {}",self.nodes(),self.edges,self.width,self.longest_path)?;
+        for (variable,&nodes) in self.nodes_per_level.iter().enumerate() {
+            if nodes>0 { writeln!(f,"Level {}: {} nodes",variable,nodes)?; }
+        }
+        Ok(())
+    }
+}
+
+/// How far a factory has got with its current work, given to the callback set by [crate::DecisionDiagramFactory::set_progress_callback].
+#[derive(Copy, Clone,Eq, PartialEq,Debug,Default)]
+pub struct Progress {
+    /// The number of nodes in the factory now.
+    pub nodes : usize,
+    /// The number of nodes made since the callback was set.
+    pub nodes_created : u64,
+    /// Operation cache lookups that found a result, since the callback was set.
+    pub cache_hits : u64,
+    /// Operation cache lookups that did not find a result, since the callback was set.
+    pub cache_misses : u64,
+    /// The variable of the node just made. Operations recurse one variable at a time, so this is how deep the recursion is.
+    pub depth : u16,
+}
+
+impl Progress {
+    /// The proportion of cache lookups that were hits, or 0 if there were no lookups.
+    pub fn cache_hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits+self.cache_misses;
+        if lookups==0 { 0.0 } else { self.cache_hits as f64/lookups as f64 }
+    }
+}
+
+/// A flag to stop operations run by [crate::DecisionDiagramFactory::try_operation], which then return [crate::XDDError::Cancelled].
+///
+/// Clones share the flag, so one can be given to a factory with [crate::DecisionDiagramFactory::set_cancellation_token]
+/// and another kept by a user interface, another thread, or a progress callback, to cancel a runaway computation.
+/// Operations check the flag each time they make a node.
+#[derive(Clone,Debug,Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self { Self::default() }
+    /// Stop operations using this token. This can not be undone.
+    pub fn cancel(&self) { self.0.store(true,Ordering::Relaxed) }
+    pub fn is_cancelled(&self) -> bool { self.0.load(Ordering::Relaxed) }
+}
+
+/// Hits and misses shared by all the operation caches of a factory with a progress callback.
+#[derive(Debug,Default)]
+pub(crate) struct SharedCacheCounts {
+    hits : AtomicU64,
+    misses : AtomicU64,
+}
+
+impl SharedCacheCounts {
+    pub(crate) fn record(&self,hit:bool) {
+        (if hit { &self.hits } else { &self.misses }).fetch_add(1,Ordering::Relaxed);
+    }
+}
+
+type ProgressCallback = Arc<Mutex<dyn FnMut(&Progress)+Send>>;
+
+/// The unwinding payload used to abandon a cancelled operation.
+pub(crate) struct Cancelled;
+
+/// The progress callback and cancellation token of a factory, which its node store consults whenever it makes a node.
+#[derive(Clone,Default)]
+pub(crate) struct Monitor {
+    callback : Option<(u64,ProgressCallback,Arc<SharedCacheCounts>)>,
+    nodes_created : u64,
+    cancellation : Option<CancellationToken>,
+}
+
+impl Monitor {
+    /// Call callback every interval nodes made, returning the counts the caches should add to.
+    pub(crate) fn set_callback(&mut self,interval:u64,callback:impl FnMut(&Progress)+Send+'static) -> Arc<SharedCacheCounts> {
+        let counts = Arc::new(SharedCacheCounts::default());
+        self.callback=Some((interval.max(1),Arc::new(Mutex::new(callback)),counts.clone()));
+        self.nodes_created=0;
+        counts
+    }
+    pub(crate) fn clear_callback(&mut self) { self.callback=None; }
+    pub(crate) fn set_cancellation_token(&mut self,token:Option<CancellationToken>) { self.cancellation=token; }
+    pub(crate) fn is_cancelled(&self) -> bool { self.cancellation.as_ref().is_some_and(|token|token.is_cancelled()) }
+    /// Note that a node for the given variable was made, leaving nodes nodes.
+    pub(crate) fn node_made(&mut self,variable:u16,nodes:usize) {
+        if let Some((interval,callback,counts)) = &self.callback {
+            self.nodes_created+=1;
+            if self.nodes_created.is_multiple_of(*interval) {
+                let progress = Progress{ nodes, nodes_created: self.nodes_created, cache_hits: counts.hits.load(Ordering::Relaxed), cache_misses: counts.misses.load(Ordering::Relaxed), depth: variable };
+                (callback.lock().unwrap())(&progress);
+            }
+        }
+    }
+}
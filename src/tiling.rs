@@ -0,0 +1,493 @@
+//! Tiling problems: cover a region exactly with copies of some tiles.
+//!
+//! This is an exact cover problem. Each possible placement of a tile is a variable, and each
+//! site of the region must be covered by exactly one placed tile. See Knuth, "The Art of Computer
+//! Programming" volume 4 fascicle 1, section 7.1.4, p119-120, for the classic example of
+//! covering a chessboard with dominoes.
+//!
+//! Tile shapes and regions can be described textually, either as an ASCII grid or as a list of coordinates.
+//!
+//! # ASCII grids
+//! Each line is a row, with y increasing downwards. A `#`, `X` or `*` is a cell; a `.` or space is not.
+//! A region like a chessboard with two opposite corners removed could be written
+//! ```text
+//! .#######
+//! ########
+//! ########
+//! ########
+//! ########
+//! ########
+//! ########
+//! #######.
+//! ```
+//!
+//! # Coordinate lists
+//! A list of `x,y` pairs separated by whitespace or semicolons, optionally in parentheses,
+//! such as `(0,0) (1,0) (0,1)` for an L tromino.
+//!
+//! # Symmetry
+//! Tilings of a symmetric board can also be counted up to the symmetries of the board (rotations and
+//! reflections) using Burnside's lemma: the number of orbits is the average over the symmetry group of
+//! the number of tilings fixed by each symmetry. See [TilingProblem::count_tilings_up_to_symmetry].
+//!
+//! # Example
+//! ```
+//! use xdd::{DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+//! use xdd::tiling::{Orientations, Region, Shape, TilingProblem};
+//! let region : Region = "####\n####\n####\n####".parse().unwrap();
+//! let domino : Shape = "##".parse().unwrap();
+//! let mut problem = TilingProblem::from_region(&region);
+//! problem.add_all_placements(&[domino],Orientations::RotationsAndReflections);
+//! let (factory,solution) = problem.find_tiling_solution::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>();
+//! assert_eq!(36,factory.number_solutions::<u64>(solution));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::{DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex};
+use crate::typed_variables::Var;
+use num::{Integer, One, Zero};
+use crate::generating_function::GeneratingFunctionWithMultiplicity;
+
+/// A location on the board, (x,y).
+pub type Site = [usize;2];
+/// The index of a site in [TilingProblem::sites].
+pub type SiteIndex = usize;
+/// A placed tile, as a list of the sites it covers.
+pub type Tile = Vec<SiteIndex>;
+/// The index of a tile in [TilingProblem::tiles]. This is also the variable used for that tile.
+pub type TileIndex = usize;
+/// Marker for variables that represent tiles; see [crate::typed_variables].
+pub enum TileTag {}
+/// The variable that is true iff a tile is used.
+pub type TileVar = Var<TileTag>;
+
+/// A set of sites to be covered, and a set of tiles that may be used to cover them.
+#[derive(Default)]
+pub struct TilingProblem {
+    pub sites : Vec<Site>,
+    pub site_index_by_site : HashMap<Site,SiteIndex>,
+    pub tiles : Vec<Tile>,
+    /// tiles_covering_a_site[site_index] is a list containing tile_index iff tiles[tile_index] contains site_index.
+    pub tiles_covering_a_site : Vec<Vec<TileIndex>>,
+}
+
+impl TilingProblem {
+    /// Make a problem with a site for each site in the region, and no tiles.
+    pub fn from_region(region:&Region) -> Self {
+        let mut problem = TilingProblem::default();
+        for &s in &region.sites { problem.add_site(s); }
+        problem
+    }
+    pub fn add_site(&mut self,s:Site) -> SiteIndex {
+        let index = self.sites.len();
+        self.sites.push(s);
+        self.site_index_by_site.insert(s,index);
+        self.tiles_covering_a_site.push(Vec::new());
+        index
+    }
+    pub fn add_tile(&mut self,tile:Tile) {
+        let index = self.tiles.len();
+        for &s in &tile {
+            self.tiles_covering_a_site[s].push(index);
+            self.tiles_covering_a_site[s].sort();
+        }
+        self.tiles.push(tile);
+    }
+    /// If all the sites on the tile exist, add it and return true. Otherwise return false.
+    pub fn add_tile_containing_sites(&mut self,sites:&[Site]) -> bool {
+        let mut tile = Vec::new();
+        for s in sites {
+            if let Some(index) = self.site_index_by_site.get(s) { tile.push(*index); } else { return false; }
+        }
+        self.add_tile(tile);
+        true
+    }
+    /// Add a tile for every placement of each of the shapes (in the given orientations) that lies entirely within the sites of this problem.
+    /// Returns the number of tiles added.
+    ///
+    /// Placements are ordered by position first and shape second, as keeping tiles that cover
+    /// nearby sites close in the variable order keeps the diagram narrow. This is why all the shapes
+    /// should be added in one call rather than one call per shape.
+    pub fn add_all_placements(&mut self,shapes:&[Shape],orientations:Orientations) -> usize {
+        let Some(max_x) = self.sites.iter().map(|s|s[0]).max() else { return 0; };
+        let max_y = self.sites.iter().map(|s|s[1]).max().unwrap_or(0);
+        let shapes : Vec<Shape> = shapes.iter().flat_map(|s|s.orientations(orientations)).collect();
+        let mut added = 0;
+        for y in 0..=max_y {
+            for x in 0..=max_x {
+                for shape in &shapes {
+                    let placed : Vec<Site> = shape.cells.iter().map(|c|[c[0]+x,c[1]+y]).collect();
+                    if self.add_tile_containing_sites(&placed) { added+=1; }
+                }
+            }
+        }
+        added
+    }
+    /// The variable used for the given tile. Panics if the tile index does not fit in a variable.
+    pub fn tile_variable(tile:TileIndex) -> TileVar { TileVar::new(u16::try_from(tile).expect("Too many tiles; there can be at most 65535")) }
+    /// Make a factory with one variable per tile, and a diagram that is true iff each site is covered by exactly one tile.
+    /// The sites are the items and the tiles the options of an exact cover problem; see [DecisionDiagramFactory::exact_cover].
+    /// Panics if there are more tiles than the 65535 variables a factory can have.
+    pub fn find_tiling_solution<A:NodeAddress,M:Multiplicity,F: DecisionDiagramFactory<A,M>>(&self) -> (F, NodeIndex<A,M>) {
+        let mut factory = F::new(u16::try_from(self.tiles.len()).expect("Too many tiles; there can be at most 65535"));
+        let node = factory.exact_cover(self.sites.len(),&self.tiles);
+        (factory,node)
+    }
+    /// The symmetries of the problem: those elements of the dihedral group of the square that map the sites onto themselves
+    /// (after translation) and map the tiles onto tiles. The identity is always first.
+    ///
+    /// If the tiles were not added with [Orientations::RotationsAndReflections] then some symmetries of the board
+    /// may not map tiles to tiles, and so will not be symmetries of the problem.
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        let tile_index_by_sites : HashMap<Vec<SiteIndex>,TileIndex> = self.tiles.iter().enumerate().map(|(i,t)|{
+            let mut t = t.clone();
+            t.sort();
+            (t,i)
+        }).collect();
+        let mut res = Vec::new();
+        'transform: for transform in PlaneTransform::ALL {
+            let transformed : Vec<(isize,isize)> = self.sites.iter().map(|s|transform.apply(s[0] as isize,s[1] as isize)).collect();
+            let offset_x = self.sites.iter().map(|s|s[0] as isize).min().unwrap_or(0)-transformed.iter().map(|s|s.0).min().unwrap_or(0);
+            let offset_y = self.sites.iter().map(|s|s[1] as isize).min().unwrap_or(0)-transformed.iter().map(|s|s.1).min().unwrap_or(0);
+            let mut site_map = Vec::with_capacity(self.sites.len());
+            for (x,y) in transformed {
+                let site = [(x+offset_x) as usize,(y+offset_y) as usize];
+                if let Some(&index) = self.site_index_by_site.get(&site) { site_map.push(index); } else { continue 'transform; }
+            }
+            let mut tile_map = Vec::with_capacity(self.tiles.len());
+            for tile in &self.tiles {
+                let mut image : Vec<SiteIndex> = tile.iter().map(|&s|site_map[s]).collect();
+                image.sort();
+                if let Some(&index) = tile_index_by_sites.get(&image) { tile_map.push(index); } else { continue 'transform; }
+            }
+            res.push(Symmetry{transform,site_map,tile_map});
+        }
+        res
+    }
+
+    /// Count the tilings, both in total and up to the given symmetries (typically from [TilingProblem::symmetries]), using Burnside's lemma.
+    ///
+    /// Counts are in G, so can be as large as G allows, such as [num::BigUint]. The symmetries must form a group,
+    /// which is checked first, otherwise Burnside's lemma does not apply.
+    /// # Example
+    /// ```
+    /// use xdd::{BDDFactory, NoMultiplicity};
+    /// use xdd::tiling::{Orientations, Region, TilingProblem};
+    /// // A 2 wide, 3 high rectangle can be tiled by dominoes in 3 ways, two of which are mirror images.
+    /// let mut problem = TilingProblem::from_region(&Region::rectangle(2,3));
+    /// problem.add_all_placements(&["##".parse().unwrap()],Orientations::Rotations);
+    /// let symmetries = problem.symmetries();
+    /// assert_eq!(4,symmetries.len());
+    /// let counts = problem.count_tilings_up_to_symmetry::<u32,NoMultiplicity,BDDFactory<u32,NoMultiplicity>,u64>(&symmetries).unwrap();
+    /// assert_eq!(3,counts.total);
+    /// assert_eq!(2,counts.up_to_symmetry);
+    /// assert_eq!(vec![3,1,3,1],counts.fixed_by_each_symmetry); // identity, rotation by 180°, left-right and top-bottom reflections.
+    /// ```
+    pub fn count_tilings_up_to_symmetry<A:NodeAddress,M:Multiplicity,F: DecisionDiagramFactory<A,M>,G:GeneratingFunctionWithMultiplicity<M>+Integer>(&self,symmetries:&[Symmetry]) -> Result<SymmetryReducedCount<G>,SymmetryGroupError> {
+        self.check_group(symmetries)?;
+        let (mut factory,solution) = self.find_tiling_solution::<A,M,F>();
+        let mut fixed_by_each_symmetry = Vec::with_capacity(symmetries.len());
+        for symmetry in symmetries {
+            // a tiling is fixed by the symmetry iff each tile is present exactly when its image is.
+            let mut constraints = Vec::new();
+            for (tile,&image) in symmetry.tile_map.iter().enumerate() {
+                if tile<image {
+                    let v1 = factory.single_variable(Self::tile_variable(tile).into());
+                    let v2 = factory.single_variable(Self::tile_variable(image).into());
+                    let both = factory.and(v1,v2);
+                    let not_v1 = factory.not(v1);
+                    let not_v2 = factory.not(v2);
+                    let neither = factory.and(not_v1,not_v2);
+                    constraints.push(factory.or(both,neither));
+                }
+            }
+            constraints.reverse();
+            let fixed = match factory.poly_and(&constraints) {
+                Some(constraint) => factory.and(constraint,solution),
+                None => solution,
+            };
+            fixed_by_each_symmetry.push(factory.number_solutions::<G>(fixed));
+        }
+        let identity = symmetries.iter().position(|s|s.is_identity()).unwrap(); // checked above.
+        let total = fixed_by_each_symmetry[identity].clone();
+        let sum = fixed_by_each_symmetry.iter().cloned().fold(<G as Zero>::zero(),|a,b|a+b);
+        let order = symmetries.iter().fold(<G as Zero>::zero(),|a,_|a+<G as One>::one());
+        let (up_to_symmetry,remainder) = sum.div_rem(&order);
+        if !remainder.is_zero() { return Err(SymmetryGroupError::NotDivisible); }
+        Ok(SymmetryReducedCount{ total, up_to_symmetry, fixed_by_each_symmetry })
+    }
+
+    /// Check that symmetries are distinct permutations of the sites and tiles of this problem that form a group:
+    /// one is the identity, and the composition of any two is also one of them.
+    pub fn check_group(&self,symmetries:&[Symmetry]) -> Result<(),SymmetryGroupError> {
+        let is_permutation = |map:&[usize],n:usize| map.len()==n && { let mut seen = vec![false;n]; map.iter().all(|&i|i<n && !std::mem::replace(&mut seen[i],true)) };
+        let mut index_by_maps : HashMap<(&[SiteIndex],&[TileIndex]),usize> = HashMap::new();
+        for (i,symmetry) in symmetries.iter().enumerate() {
+            if !(is_permutation(&symmetry.site_map,self.sites.len()) && is_permutation(&symmetry.tile_map,self.tiles.len())) { return Err(SymmetryGroupError::NotAPermutation(i)); }
+            if let Some(&j) = index_by_maps.get(&(&symmetry.site_map[..],&symmetry.tile_map[..])) { return Err(SymmetryGroupError::Repeated(j,i)); }
+            index_by_maps.insert((&symmetry.site_map,&symmetry.tile_map),i);
+        }
+        if !symmetries.iter().any(|s|s.is_identity()) { return Err(SymmetryGroupError::NoIdentity); }
+        for (i,first) in symmetries.iter().enumerate() {
+            for (j,second) in symmetries.iter().enumerate() {
+                let site_map : Vec<SiteIndex> = first.site_map.iter().map(|&s|second.site_map[s]).collect();
+                let tile_map : Vec<TileIndex> = first.tile_map.iter().map(|&t|second.tile_map[t]).collect();
+                if !index_by_maps.contains_key(&(&site_map[..],&tile_map[..])) { return Err(SymmetryGroupError::NotClosed(i,j)); }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One of the 8 symmetries of a square: a rotation by some multiple of 90°, optionally preceded by a left-right reflection.
+#[derive(Copy, Clone,Eq, PartialEq,Debug)]
+pub struct PlaneTransform {
+    /// reflect x before rotating.
+    pub reflect : bool,
+    /// the number of 90° rotations, 0..4.
+    pub rotations : u8,
+}
+
+impl PlaneTransform {
+    /// All 8 elements of the dihedral group of the square, identity first.
+    pub const ALL : [PlaneTransform;8] = [
+        PlaneTransform{reflect:false,rotations:0},PlaneTransform{reflect:false,rotations:1},PlaneTransform{reflect:false,rotations:2},PlaneTransform{reflect:false,rotations:3},
+        PlaneTransform{reflect:true,rotations:0},PlaneTransform{reflect:true,rotations:1},PlaneTransform{reflect:true,rotations:2},PlaneTransform{reflect:true,rotations:3},
+    ];
+    /// Apply to a point, in the same sense as [Shape::rotate] and [Shape::reflect].
+    pub fn apply(self,x:isize,y:isize) -> (isize,isize) {
+        let (mut x,mut y) = if self.reflect { (-x,y) } else { (x,y) };
+        for _ in 0..self.rotations { (x,y) = (-y,x); }
+        (x,y)
+    }
+}
+
+/// A symmetry of a tiling problem.
+#[derive(Clone,Eq, PartialEq,Debug)]
+pub struct Symmetry {
+    pub transform : PlaneTransform,
+    /// site_map[s] is the image of site s.
+    pub site_map : Vec<SiteIndex>,
+    /// tile_map[t] is the image of tile t.
+    pub tile_map : Vec<TileIndex>,
+}
+
+impl Symmetry {
+    /// Whether this leaves every site and tile where it is.
+    pub fn is_identity(&self) -> bool {
+        self.site_map.iter().enumerate().all(|(i,&s)|i==s) && self.tile_map.iter().enumerate().all(|(i,&t)|i==t)
+    }
+}
+
+/// The result of [TilingProblem::count_tilings_up_to_symmetry].
+#[derive(Clone,Eq, PartialEq,Debug)]
+pub struct SymmetryReducedCount<G> {
+    /// The number of tilings, not taking symmetry into account.
+    pub total : G,
+    /// The number of orbits of tilings under the symmetry group.
+    pub up_to_symmetry : G,
+    /// For each symmetry, the number of tilings it leaves unchanged.
+    pub fixed_by_each_symmetry : Vec<G>,
+}
+
+/// Why symmetries given to [TilingProblem::count_tilings_up_to_symmetry] could not be used.
+#[derive(Clone,Debug,Eq, PartialEq)]
+pub enum SymmetryGroupError {
+    /// The symmetry with this index does not permute the sites and tiles of the problem.
+    NotAPermutation(usize),
+    /// The symmetries with these indices are the same.
+    Repeated(usize,usize),
+    /// None of the symmetries is the identity.
+    NoIdentity,
+    /// Applying the symmetries with these indices in turn gives something that is not one of the symmetries.
+    NotClosed(usize,usize),
+    /// The counts of fixed tilings do not add up to a multiple of the number of symmetries, as Burnside's lemma says they
+    /// must. This can only happen if multiplicities are not preserved by the symmetries.
+    NotDivisible,
+}
+
+impl Error for SymmetryGroupError { }
+
+impl Display for SymmetryGroupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymmetryGroupError::NotAPermutation(i) => write!(f,"Symmetry {} does not permute the sites and tiles",i),
+            SymmetryGroupError::Repeated(i,j) => write!(f,"Symmetries {} and {} are the same",i,j),
+            SymmetryGroupError::NoIdentity => write!(f,"The symmetries do not include the identity"),
+            SymmetryGroupError::NotClosed(i,j) => write!(f,"Symmetry {} followed by symmetry {} is not one of the symmetries",i,j),
+            SymmetryGroupError::NotDivisible => write!(f,"Burnside's lemma failed; the multiplicities are not symmetric"),
+        }
+    }
+}
+
+/// Which transformations of a shape may be used when placing it.
+#[derive(Copy, Clone,Eq, PartialEq,Debug)]
+pub enum Orientations {
+    /// Only use the shape as given.
+    AsGiven,
+    /// Allow the shape to be rotated by multiples of 90°.
+    Rotations,
+    /// Allow the shape to be rotated and reflected (the full dihedral group of order 8).
+    RotationsAndReflections,
+}
+
+/// The shape of a tile, as a set of cells. Normalized so that the minimum x and y are both zero, and cells are sorted.
+#[derive(Clone,Eq, PartialEq,Hash,Debug)]
+pub struct Shape {
+    pub cells : Vec<Site>,
+}
+
+impl Shape {
+    /// Make a shape from arbitrary (possibly negative) coordinates, normalizing them.
+    /// # Example
+    /// ```
+    /// use xdd::tiling::Shape;
+    /// let shape = Shape::new(&[(3,-1),(3,0),(4,-1)]);
+    /// assert_eq!(vec![[0,0],[1,0],[0,1]],shape.cells);
+    /// ```
+    pub fn new(cells:&[(isize,isize)]) -> Self {
+        let min_x = cells.iter().map(|c|c.0).min().unwrap_or(0);
+        let min_y = cells.iter().map(|c|c.1).min().unwrap_or(0);
+        let mut cells : Vec<Site> = cells.iter().map(|c|[(c.0-min_x) as usize,(c.1-min_y) as usize]).collect();
+        cells.sort_by_key(|c|(c[1],c[0]));
+        cells.dedup();
+        Shape{cells}
+    }
+    fn transform(&self,f:impl Fn(isize,isize)->(isize,isize)) -> Self {
+        Shape::new(&self.cells.iter().map(|c|f(c[0] as isize,c[1] as isize)).collect::<Vec<_>>())
+    }
+    /// This shape rotated by 90°.
+    pub fn rotate(&self) -> Self { self.transform(|x,y|(-y,x)) }
+    /// This shape reflected left to right.
+    pub fn reflect(&self) -> Self { self.transform(|x,y|(-x,y)) }
+    /// The distinct shapes obtainable from this shape by the allowed transformations.
+    /// # Example
+    /// ```
+    /// use xdd::tiling::{Orientations, Shape};
+    /// let l_tetromino : Shape = "#.\n#.\n##".parse().unwrap();
+    /// assert_eq!(1,l_tetromino.orientations(Orientations::AsGiven).len());
+    /// assert_eq!(4,l_tetromino.orientations(Orientations::Rotations).len());
+    /// assert_eq!(8,l_tetromino.orientations(Orientations::RotationsAndReflections).len());
+    /// let square : Shape = "##\n##".parse().unwrap();
+    /// assert_eq!(1,square.orientations(Orientations::RotationsAndReflections).len());
+    /// ```
+    pub fn orientations(&self,orientations:Orientations) -> Vec<Shape> {
+        let mut res = vec![self.clone()];
+        let mut add = |s:Shape| if !res.contains(&s) { res.push(s) };
+        if orientations!=Orientations::AsGiven {
+            let mut s = self.clone();
+            for _ in 0..3 { s=s.rotate(); add(s.clone()); }
+            if orientations==Orientations::RotationsAndReflections {
+                let mut s = self.reflect();
+                add(s.clone());
+                for _ in 0..3 { s=s.rotate(); add(s.clone()); }
+            }
+        }
+        res
+    }
+}
+
+/// A shape is parsed as either an ASCII grid or a coordinate list, as described in the module documentation.
+/// # Example
+/// ```
+/// use xdd::tiling::Shape;
+/// let grid : Shape = ".#\n##".parse().unwrap();
+/// let list : Shape = "(1,0) (0,1) (1,1)".parse().unwrap();
+/// assert_eq!(grid,list);
+/// ```
+impl FromStr for Shape {
+    type Err = ParseShapeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = parse_cells(s)?;
+        Ok(Shape::new(&cells.iter().map(|c|(c[0] as isize,c[1] as isize)).collect::<Vec<_>>()))
+    }
+}
+
+/// A region of the plane to be tiled. Unlike a [Shape] it is not normalized, so holes and non-rectangular boards keep their position.
+#[derive(Clone,Eq, PartialEq,Debug)]
+pub struct Region {
+    pub sites : Vec<Site>,
+}
+
+impl Region {
+    /// A width by height rectangle.
+    pub fn rectangle(width:usize,height:usize) -> Self {
+        let mut sites = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                sites.push([x,y]);
+            }
+        }
+        Region{sites}
+    }
+    /// This region with the given sites removed.
+    /// # Example
+    /// ```
+    /// use xdd::tiling::Region;
+    /// let mutilated = Region::rectangle(8,8).without(&[[0,0],[7,7]]);
+    /// let parsed : Region = ".#######\n########\n########\n########\n########\n########\n########\n#######.".parse().unwrap();
+    /// assert_eq!(mutilated,parsed);
+    /// ```
+    pub fn without(&self,holes:&[Site]) -> Self {
+        Region{sites:self.sites.iter().filter(|s|!holes.contains(s)).cloned().collect()}
+    }
+}
+
+/// A region is parsed as either an ASCII grid or a coordinate list, as described in the module documentation.
+impl FromStr for Region {
+    type Err = ParseShapeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Region{sites:parse_cells(s)?})
+    }
+}
+
+/// Parse a list of cells as either an ASCII grid or a coordinate list. Cells are in row major order for a grid, and in the given order for a list, with duplicates removed.
+fn parse_cells(s:&str) -> Result<Vec<Site>,ParseShapeError> {
+    let mut cells : Vec<Site> = Vec::new();
+    if s.contains(',') { // coordinate list
+        for pair in s.split(|c:char|c.is_whitespace()||c==';').filter(|p|!p.is_empty()) {
+            let pair = pair.trim_start_matches('(').trim_end_matches(')');
+            let (x,y) = pair.split_once(',').ok_or_else(||ParseShapeError::NumberFormat(pair.to_string()))?;
+            let parse = |v:&str| v.trim().parse::<usize>().map_err(|_|ParseShapeError::NumberFormat(v.to_string()));
+            cells.push([parse(x)?,parse(y)?]);
+        }
+    } else { // ASCII grid
+        for (y,line) in s.lines().enumerate() {
+            for (x,c) in line.chars().enumerate() {
+                match c {
+                    '#' | 'X' | '*' => cells.push([x,y]),
+                    '.' | ' ' => {}
+                    _ => return Err(ParseShapeError::UnexpectedCharacter(c)),
+                }
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    cells.retain(|c|seen.insert(*c));
+    if cells.is_empty() { Err(ParseShapeError::Empty) } else { Ok(cells) }
+}
+
+#[derive(Clone,Debug,Eq, PartialEq)]
+pub enum ParseShapeError {
+    /// A character in an ASCII grid other than `#`, `X`, `*`, `.` or space.
+    UnexpectedCharacter(char),
+    /// A coordinate that is not a pair of non-negative integers.
+    NumberFormat(String),
+    /// There were no cells.
+    Empty,
+}
+
+impl Error for ParseShapeError { }
+
+impl Display for ParseShapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseShapeError::UnexpectedCharacter(c) => write!(f,"Unexpected character '{}' in grid; use # for a cell and . for no cell",c),
+            ParseShapeError::NumberFormat(s) => write!(f,"Could not interpret '{}' as a coordinate pair x,y",s),
+            ParseShapeError::Empty => write!(f,"No cells"),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Tropical multiplicities, where "or" takes the maximum (or minimum) and "and" adds.
+//!
+//! A [crate::Multiplicity] supplies the semiring used by a diagram: [crate::Multiplicity::combine_or] is the semiring
+//! addition and [crate::Multiplicity::multiply] the semiring multiplication. With [MaxPlus], the multiplicity of a
+//! solution of f∧g is the sum of its multiplicities in f and g, and of f∨g the larger of them. So a weight can be
+//! given to each variable being true, combined by "and" into a diagram giving each assignment its total weight,
+//! and then [crate::DecisionDiagramFactory::number_solutions] with the same type finds the largest total weight of
+//! a solution, rather than counting them. [MinPlus] does the same for the smallest.
+//!
+//! As a generating function, [MaxPlus::NONE] (or [MinPlus::NONE]) means there are no solutions.
+//!
+//! Weights are i64, and every weight worked out, including the differences between weights stored in nodes, must
+//! fit in an i64 other than the one used for NONE; in practice weights should stay within ±2<sup>62</sup>. Going out
+//! of this range panics rather than silently giving a wrong answer.
+//!
+//! # Example
+//! ```
+//! use xdd::{DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+//! use xdd::graphs::Graph;
+//! use xdd::tropical::MaxPlus;
+//! // The heaviest independent set in a 5-cycle with these vertex weights.
+//! let weights = [3,1,4,1,5];
+//! let mut factory = ZDDFactory::<u32,MaxPlus>::new(5);
+//! let independent_sets = Graph::cycle(5).independent_sets(&mut factory);
+//! let mut weighted = independent_sets;
+//! for (v,&w) in weights.iter().enumerate() {
+//!     let x = factory.single_variable(VariableIndex(v as u16));
+//!     let not_x = factory.not(x);
+//!     let weight = factory.or(x.multiply(MaxPlus(w)),not_x); // w if x is true, 0 otherwise.
+//!     weighted = factory.and(weighted,weight);
+//! }
+//! assert_eq!(MaxPlus(9),factory.number_solutions::<MaxPlus>(weighted)); // vertices 2 and 4.
+//! assert_eq!(MaxPlus::NONE,factory.number_solutions::<MaxPlus>(NodeIndex::FALSE));
+//! ```
+
+use std::fmt::{Display, Formatter};
+use crate::{Multiplicity, NoMultiplicity, VariableIndex};
+use crate::generating_function::{GeneratingFunction, GeneratingFunctionWithMultiplicity};
+
+macro_rules! tropical {
+    ($name:ident,$combine:ident,$none:expr,$doc:literal) => {
+        #[doc=$doc]
+        #[derive(Copy,Clone,Eq,PartialEq,Hash,Debug)]
+        #[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
+        pub struct $name(pub i64);
+
+        impl $name {
+            /// As a generating function, no solutions. Not a valid multiplicity.
+            pub const NONE : $name = $name($none);
+            /// The weight a+b, or a-b if subtract, panicking if it is out of range. See [crate::tropical].
+            fn checked(a:i64,b:i64,subtract:bool) -> Self {
+                let res = if subtract { a.checked_sub(b) } else { a.checked_add(b) };
+                match res {
+                    Some(w) if w!=$none => $name(w),
+                    _ => panic!("Tropical weight out of range {} {} {}",a,if subtract { "-" } else { "+" },b),
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f,"{}",self.0) }
+        }
+
+        /// Every element of a (tropical) semiring is invertible under multiplication (addition of the weights),
+        /// so the "gcd" taken out of a node is just its lo multiplicity.
+        impl Multiplicity for $name {
+            const ONE: Self = $name(0);
+            const MULTIPLICITIES_IRRELEVANT: bool = false;
+
+            fn combine_or(a: Self, b: Self) -> Self { $name(a.0.$combine(b.0)) }
+            fn multiply(a: Self, b: Self) -> Self { $name::checked(a.0,b.0,false) }
+            fn gcd(a: Self, b: Self) -> (Self, Self, Self) { (Self::ONE,$name::checked(b.0,a.0,true),a) }
+        }
+
+        /// Drop the weights.
+        impl From<$name> for NoMultiplicity {
+            fn from(_value: $name) -> Self { NoMultiplicity{} }
+        }
+
+        /// Each element of a set has weight 0.
+        impl From<NoMultiplicity> for $name {
+            fn from(_value: NoMultiplicity) -> Self { $name::ONE }
+        }
+
+        impl GeneratingFunction for $name {
+            fn zero() -> Self { $name::NONE }
+            fn one() -> Self { $name::ONE }
+            fn add(self, other: Self) -> Self { $name::combine_or(self,other) }
+            fn variable_set(self, _variable: VariableIndex) -> Self { self }
+        }
+
+        impl GeneratingFunctionWithMultiplicity<$name> for $name {
+            fn multiply(self, multiple: $name) -> Self { if self==$name::NONE { self } else { $name::checked(self.0,multiple.0,false) } }
+        }
+    }
+}
+
+tropical!(MaxPlus,max,i64::MIN,"A weight, where \"or\" takes the maximum and \"and\" adds. See [crate::tropical].");
+tropical!(MinPlus,min,i64::MAX,"A weight, where \"or\" takes the minimum and \"and\" adds. See [crate::tropical].");
@@ -0,0 +1,211 @@
+//! Variables tagged with the problem domain they belong to.
+//!
+//! A program using several encodings at once (say tiles in one factory and transpositions in another)
+//! can easily pass a variable from one to the other, as both are just a [VariableIndex]. A [Var] carries
+//! a tag type identifying its domain, and a [TypedFactory] only accepts variables with its own tag,
+//! so such mix-ups become compile errors.
+//!
+//! # Example
+//! ```compile_fail
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity};
+//! use xdd::typed_variables::{TypedFactory, Var};
+//! enum Site {}
+//! enum Colour {}
+//! let mut factory = TypedFactory::<Site,BDDFactory<u32,NoMultiplicity>>::new(BDDFactory::new(4));
+//! factory.single_variable(Var::<Colour>::new(0)); // does not compile - a colour is not a site.
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use crate::{BooleanOperator, Comparison, DecisionDiagramFactory, Multiplicity, NodeAddress, NodeIndex, NodeRenaming, VariableIndex};
+use crate::variable_registry::VariableRegistry;
+use crate::roots::Handle;
+use crate::generating_function::GeneratingFunctionWithMultiplicity;
+
+/// A variable belonging to the domain identified by Tag. Tag is just a marker; it is typically an empty enum.
+pub struct Var<Tag> {
+    index : VariableIndex,
+    tag : PhantomData<fn() -> Tag>,
+}
+
+impl <Tag> Var<Tag> {
+    pub const fn new(index:u16) -> Self { Var{index:VariableIndex(index),tag:PhantomData} }
+    /// The untyped variable, for use with an untyped factory.
+    pub fn index(self) -> VariableIndex { self.index }
+}
+
+// Implemented by hand as derive would require Tag to implement these too.
+impl <Tag> Clone for Var<Tag> { fn clone(&self) -> Self { *self } }
+impl <Tag> Copy for Var<Tag> {}
+impl <Tag> PartialEq for Var<Tag> { fn eq(&self, other: &Self) -> bool { self.index==other.index } }
+impl <Tag> Eq for Var<Tag> {}
+impl <Tag> Hash for Var<Tag> { fn hash<H: Hasher>(&self, state: &mut H) { self.index.hash(state) } }
+impl <Tag> PartialOrd for Var<Tag> { fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) } }
+impl <Tag> Ord for Var<Tag> { fn cmp(&self, other: &Self) -> Ordering { self.index.cmp(&other.index) } }
+impl <Tag> Debug for Var<Tag> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f,"Var<{}>({})",std::any::type_name::<Tag>(),self.index.0) }
+}
+impl <Tag> Display for Var<Tag> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f,"{}",self.index) }
+}
+
+impl <Tag> From<Var<Tag>> for VariableIndex {
+    fn from(value: Var<Tag>) -> Self { value.index }
+}
+
+/// A factory whose variables all belong to the domain identified by Tag.
+///
+/// Operations that take variables only accept a [Var] with the right tag. Read only
+/// operations such as `len` and `number_solutions` are available through Deref to the underlying factory.
+///
+/// # Example
+/// ```
+/// use xdd::{DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+/// use xdd::typed_variables::{TypedFactory, Var};
+/// enum Site {}
+/// type SiteVar = Var<Site>;
+/// let mut factory = TypedFactory::<Site,ZDDFactory<u32,NoMultiplicity>>::new(ZDDFactory::new(3));
+/// let one_site = factory.exactly_one_of(&[SiteVar::new(0),SiteVar::new(1),SiteVar::new(2)]);
+/// let site_1 = factory.single_variable(SiteVar::new(1));
+/// let not_site_1 = factory.not(site_1);
+/// let res = factory.and(one_site,not_site_1);
+/// assert_eq!(2,factory.number_solutions::<u64>(res));
+/// ```
+pub struct TypedFactory<Tag,F> {
+    factory : F,
+    tag : PhantomData<fn() -> Tag>,
+}
+
+impl <Tag,F> TypedFactory<Tag,F> {
+    /// Wrap an existing (typically new) factory.
+    pub fn new(factory:F) -> Self { TypedFactory{factory,tag:PhantomData} }
+    /// Get the untyped factory back.
+    pub fn into_inner(self) -> F { self.factory }
+
+    pub fn single_variable<A:NodeAddress,M:Multiplicity>(&mut self,variable:Var<Tag>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.single_variable(variable.index)
+    }
+    /// Produce a DD which is true iff exactly 1 of the given variables is true. The variables must be sorted.
+    pub fn exactly_one_of<A:NodeAddress,M:Multiplicity>(&mut self,variables:&[Var<Tag>]) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.exactly_one_of(&variables.iter().map(|v|v.index).collect::<Vec<_>>())
+    }
+    /// Produce a DD which is true iff exactly n of the given variables are true. The variables must be sorted.
+    pub fn exactly_n_of<A:NodeAddress,M:Multiplicity>(&mut self,variables:&[Var<Tag>],n:usize) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.exactly_n_of(&variables.iter().map(|v|v.index).collect::<Vec<_>>(),n)
+    }
+    /// Produce a DD which is true iff between at_least and at_most of the given variables are true. The variables must be sorted.
+    pub fn between_n_and_m_of<A:NodeAddress,M:Multiplicity>(&mut self,variables:&[Var<Tag>],at_least:usize,at_most:usize) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.between_n_and_m_of(&variables.iter().map(|v|v.index).collect::<Vec<_>>(),at_least,at_most)
+    }
+    pub fn at_most_n_of<A:NodeAddress,M:Multiplicity>(&mut self,variables:&[Var<Tag>],n:usize) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.between_n_and_m_of(variables,0,n)
+    }
+    pub fn at_least_n_of<A:NodeAddress,M:Multiplicity>(&mut self,variables:&[Var<Tag>],n:usize) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.between_n_and_m_of(variables,n,variables.len())
+    }
+    /// Produce a DD which is true iff the sum of coefficient times variable over the terms compares to bound as given. The terms must be sorted by variable.
+    pub fn linear_constraint<A:NodeAddress,M:Multiplicity>(&mut self,terms:&[(Var<Tag>,i64)],comparison:Comparison,bound:i64) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.linear_constraint(&terms.iter().map(|(v,c)|(v.index,*c)).collect::<Vec<_>>(),comparison,bound)
+    }
+    pub fn and<A:NodeAddress,M:Multiplicity>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.and(index1,index2)
+    }
+    pub fn or<A:NodeAddress,M:Multiplicity>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.or(index1,index2)
+    }
+    pub fn xor<A:NodeAddress,M:Multiplicity>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.xor(index1,index2)
+    }
+    pub fn apply<A:NodeAddress,M:Multiplicity>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, op:BooleanOperator) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.apply(index1,index2,op)
+    }
+    pub fn scale<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, m:M) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.scale(f,m)
+    }
+    pub fn linear_combination<A:NodeAddress,M:Multiplicity>(&mut self, terms:&[(NodeIndex<A,M>,M)]) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.linear_combination(terms)
+    }
+    pub fn subtract<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.subtract(f,g)
+    }
+    pub fn support<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.support(f)
+    }
+    pub fn filter_multiplicity<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, predicate:impl Fn(M)->bool) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.filter_multiplicity(f,predicate)
+    }
+    pub fn not<A:NodeAddress,M:Multiplicity>(&mut self, index: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.not(index)
+    }
+    pub fn ite<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.ite(f,g,h)
+    }
+    pub fn compose<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, variable: Var<Tag>, g: NodeIndex<A,M>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.compose(f,variable.index,g)
+    }
+    pub fn vector_compose<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, substitutions:&[(Var<Tag>,NodeIndex<A,M>)]) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.vector_compose(f,&substitutions.iter().map(|(v,g)|(v.index,*g)).collect::<Vec<_>>())
+    }
+    pub fn restrict<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, variable: Var<Tag>, value: bool) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.restrict(f,variable.index,value)
+    }
+    pub fn restrict_cube<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, literals:&[(Var<Tag>,bool)]) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.restrict_cube(f,&literals.iter().map(|(v,value)|(v.index,*value)).collect::<Vec<_>>())
+    }
+    pub fn number_solutions_given<A:NodeAddress,M:Multiplicity,G:GeneratingFunctionWithMultiplicity<M>>(&self, f: NodeIndex<A,M>, literals:&[(Var<Tag>,bool)]) -> G where F:DecisionDiagramFactory<A,M> {
+        self.factory.number_solutions_given(f,&literals.iter().map(|(v,value)|(v.index,*value)).collect::<Vec<_>>())
+    }
+    pub fn rename_variables<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, mapping:impl Fn(Var<Tag>)->Var<Tag>) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.rename_variables(f,|v|mapping(Var::new(v.0)).index)
+    }
+    pub fn relational_product<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, quantified:&[Var<Tag>]) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.relational_product(f,g,&quantified.iter().map(|v|v.index).collect::<Vec<_>>())
+    }
+    pub fn exists<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, quantified:&[Var<Tag>]) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.exists(f,&quantified.iter().map(|v|v.index).collect::<Vec<_>>())
+    }
+    pub fn project<A:NodeAddress,M:Multiplicity>(&mut self, f: NodeIndex<A,M>, variables:&[Var<Tag>]) -> NodeIndex<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.project(f,&variables.iter().map(|v|v.index).collect::<Vec<_>>())
+    }
+    pub fn number_projected_solutions<A:NodeAddress,M:Multiplicity,G:GeneratingFunctionWithMultiplicity<M>>(&mut self, f: NodeIndex<A,M>, variables:&[Var<Tag>]) -> G where F:DecisionDiagramFactory<A,M> {
+        self.factory.number_projected_solutions(f,&variables.iter().map(|v|v.index).collect::<Vec<_>>())
+    }
+    pub fn poly_and<A:NodeAddress,M:Multiplicity>(&mut self, indices:&[NodeIndex<A,M>]) -> Option<NodeIndex<A,M>> where F:DecisionDiagramFactory<A,M> {
+        self.factory.poly_and(indices)
+    }
+    pub fn poly_and_with_progress<A:NodeAddress,M:Multiplicity,P:FnMut(usize,usize)>(&mut self, indices:&[NodeIndex<A,M>], progress:P) -> Option<NodeIndex<A,M>> where F:DecisionDiagramFactory<A,M> {
+        self.factory.poly_and_with_progress(indices,progress)
+    }
+    pub fn gc<A:NodeAddress,M:Multiplicity>(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> where F:DecisionDiagramFactory<A,M> {
+        self.factory.gc(keep)
+    }
+    pub fn root<A:NodeAddress,M:Multiplicity>(&mut self, index:NodeIndex<A,M>) -> Handle<A,M> where F:DecisionDiagramFactory<A,M> {
+        self.factory.root(index)
+    }
+    pub fn gc_roots<A:NodeAddress,M:Multiplicity>(&mut self) -> NodeRenaming<A> where F:DecisionDiagramFactory<A,M> {
+        self.factory.gc_roots()
+    }
+    pub fn set_cache_capacity<A:NodeAddress,M:Multiplicity>(&mut self, capacity:Option<usize>) where F:DecisionDiagramFactory<A,M> {
+        self.factory.set_cache_capacity(capacity)
+    }
+    pub fn variable_registry_mut<A:NodeAddress,M:Multiplicity>(&mut self) -> &mut VariableRegistry where F:DecisionDiagramFactory<A,M> {
+        self.factory.variable_registry_mut()
+    }
+    /// Like [DecisionDiagramFactory::make_dot_file] except the namer takes a typed variable.
+    pub fn make_dot_file<A:NodeAddress,M:Multiplicity,W:Write,N:Fn(Var<Tag>)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:N) -> std::io::Result<()> where F:DecisionDiagramFactory<A,M> {
+        self.factory.make_dot_file(writer,name,start_nodes,|v|namer(Var::new(v.0)))
+    }
+    /// Like [DecisionDiagramFactory::make_json_file] except the namer takes a typed variable.
+    pub fn make_json_file<A:NodeAddress,M:Multiplicity,W:Write,N:Fn(Var<Tag>)->String>(&self, writer:&mut W, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:N) -> std::io::Result<()> where F:DecisionDiagramFactory<A,M> {
+        self.factory.make_json_file(writer,start_nodes,|v|namer(Var::new(v.0)))
+    }
+}
+
+impl <Tag,F> Deref for TypedFactory<Tag,F> {
+    type Target = F;
+    fn deref(&self) -> &F { &self.factory }
+}
@@ -0,0 +1,67 @@
+//! Names for variables, so diagrams and solutions can be shown in terms of the problem rather than variable numbers.
+//!
+//! Each factory owns a [VariableRegistry], available through [crate::DecisionDiagramFactory::variable_registry].
+//! It starts empty; variables without a name are shown as their number.
+//!
+//! # Example
+//! ```
+//! use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex};
+//! let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+//! factory.variable_registry_mut().set_names(&["rain","sprinkler","wet"]);
+//! let wet = factory.variable_registry().variable("wet").unwrap();
+//! let f = factory.single_variable(wet);
+//! let rain = factory.single_variable(VariableIndex(0));
+//! let f = factory.and(f,rain);
+//! let solutions : Vec<String> = factory.solutions(f).map(|s|factory.format_solution(&s)).collect();
+//! assert_eq!(vec!["{rain, wet}","{rain, sprinkler, wet}"],solutions);
+//! ```
+
+use std::collections::HashMap;
+use crate::VariableIndex;
+
+/// A two way mapping between variables and names.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct VariableRegistry {
+    names : Vec<Option<String>>,
+    variables : HashMap<String,VariableIndex>,
+}
+
+impl VariableRegistry {
+    /// Give a variable a name, replacing any name it had before. Panics if the name is already used for a different variable.
+    pub fn set_name(&mut self,variable:VariableIndex,name:impl Into<String>) {
+        let name = name.into();
+        if let Some(&existing) = self.variables.get(&name) {
+            assert_eq!(existing,variable,"The name {} is already used for variable {}",name,existing);
+        }
+        let index = variable.0 as usize;
+        if self.names.len()<=index { self.names.resize(index+1,None); }
+        if let Some(old) = self.names[index].replace(name.clone()) { self.variables.remove(&old); }
+        self.variables.insert(name,variable);
+    }
+    /// Exchange the names of two variables.
+    pub fn swap(&mut self,a:VariableIndex,b:VariableIndex) {
+        let len = self.names.len().max(a.0 as usize+1).max(b.0 as usize+1);
+        self.names.resize(len,None);
+        self.names.swap(a.0 as usize,b.0 as usize);
+        for variable in [a,b] {
+            if let Some(name) = &self.names[variable.0 as usize] { self.variables.insert(name.clone(),variable); }
+        }
+    }
+    /// Name variables 0, 1, ... in order.
+    pub fn set_names<S:AsRef<str>>(&mut self,names:&[S]) {
+        for (i,name) in names.iter().enumerate() { self.set_name(VariableIndex(i as u16),name.as_ref()); }
+    }
+    /// The variable with the given name, if any.
+    pub fn variable(&self,name:&str) -> Option<VariableIndex> { self.variables.get(name).cloned() }
+    /// The name given to a variable, if any.
+    pub fn get_name(&self,variable:VariableIndex) -> Option<&str> {
+        self.names.get(variable.0 as usize).and_then(|n|n.as_deref())
+    }
+    /// The name of a variable, or its number if it has no name.
+    pub fn name(&self,variable:VariableIndex) -> String {
+        self.get_name(variable).map(|n|n.to_string()).unwrap_or_else(||variable.to_string())
+    }
+    /// The number of named variables.
+    pub fn len(&self) -> usize { self.variables.len() }
+    pub fn is_empty(&self) -> bool { self.variables.is_empty() }
+}
@@ -3,13 +3,390 @@
 //! Where XDDs represent a set, the equivalent version with multiplicities represents a multiset.
 //!
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::hash_map::RandomState;
 use std::fmt::Display;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 use std::io::Write;
-use std::ops::Range;
-use crate::{Node, NodeIndex, VariableIndex, NodeAddress, Multiplicity, NodeRenaming};
-use crate::generating_function::{GeneratingFunctionWithMultiplicity};
+use std::sync::Arc;
+use std::ops::{Add, Mul, Range, Sub};
+use crate::{BooleanOperator, Node, NodeIndex, VariableIndex, NodeAddress, Multiplicity, NodeRenaming};
+use num::Zero;
+use rand::Rng;
+use crate::generating_function::{GeneratingFunction, GeneratingFunctionWithMultiplicity};
+use crate::statistics::{Cancelled, DiagramProfile, Monitor, SharedCacheCounts};
+
+/// Somewhere to remember the results of an operation on nodes.
+/// This is implemented by a plain HashMap, and by [OperationCache] which also keeps statistics.
+pub trait Cache<K,V> {
+    /// Get the remembered result for key, if any.
+    fn get(&mut self,key:&K) -> Option<V>;
+    /// Remember the result for key.
+    fn insert(&mut self,key:K,value:V);
+}
+
+impl <K:Eq+Hash,V:Copy> Cache<K,V> for HashMap<K,V> {
+    fn get(&mut self, key: &K) -> Option<V> { HashMap::get(self,key).cloned() }
+    fn insert(&mut self, key: K, value: V) { HashMap::insert(self,key,value); }
+}
+
+/// Something stored in a cache that may refer to nodes, so it can be kept through [crate::DecisionDiagramFactory::gc].
+pub trait Renamable<A:NodeAddress> : Sized {
+    /// The equivalent after the renaming, or None if it refers to a node that no longer exists.
+    fn renamed(self,renaming:&NodeRenaming<A>) -> Option<Self>;
+}
+
+impl <A:NodeAddress> Renamable<A> for A {
+    fn renamed(self, renaming: &NodeRenaming<A>) -> Option<Self> { renaming.rename_address(self) }
+}
+impl <A:NodeAddress,M:Multiplicity> Renamable<A> for NodeIndex<A,M> {
+    fn renamed(self, renaming: &NodeRenaming<A>) -> Option<Self> { renaming.rename(self) }
+}
+impl <A:NodeAddress> Renamable<A> for VariableIndex {
+    fn renamed(self, _renaming: &NodeRenaming<A>) -> Option<Self> { Some(self) }
+}
+impl <A:NodeAddress> Renamable<A> for bool {
+    fn renamed(self, _renaming: &NodeRenaming<A>) -> Option<Self> { Some(self) }
+}
+impl <A:NodeAddress> Renamable<A> for BooleanOperator {
+    fn renamed(self, _renaming: &NodeRenaming<A>) -> Option<Self> { Some(self) }
+}
+impl <A:NodeAddress,X:Renamable<A>,Y:Renamable<A>> Renamable<A> for (X,Y) {
+    fn renamed(self, renaming: &NodeRenaming<A>) -> Option<Self> { Some((self.0.renamed(renaming)?,self.1.renamed(renaming)?)) }
+}
+impl <A:NodeAddress,X:Renamable<A>,Y:Renamable<A>,Z:Renamable<A>> Renamable<A> for (X,Y,Z) {
+    fn renamed(self, renaming: &NodeRenaming<A>) -> Option<Self> { Some((self.0.renamed(renaming)?,self.1.renamed(renaming)?,self.2.renamed(renaming)?)) }
+}
+
+/// A cache of the results of an operation, that counts how often it is useful.
+///
+/// By default this grows without limit until cleared. With [OperationCache::set_capacity] it becomes a fixed size
+/// table where each key has one possible slot, chosen by its hash, and inserting replaces whatever was in that slot.
+/// This is effectively random eviction, and needs no bookkeeping beyond the table itself.
+#[derive(Clone,Debug)]
+pub struct OperationCache<K,V> {
+    map : HashMap<K,V>,
+    /// Used instead of map if the capacity is bounded.
+    slots : Vec<Option<(K,V)>>,
+    /// The number of occupied slots.
+    used_slots : usize,
+    hasher : RandomState,
+    hits : u64,
+    misses : u64,
+    evictions : u64,
+    /// Also counts hits and misses here, if there is a progress callback.
+    shared_counts : Option<Arc<SharedCacheCounts>>,
+}
+
+impl <K,V> Default for OperationCache<K,V> {
+    fn default() -> Self { OperationCache{ map: HashMap::new(), slots: vec![], used_slots: 0, hasher: RandomState::new(), hits: 0, misses: 0, evictions: 0, shared_counts: None } }
+}
+
+impl <K,V> OperationCache<K,V> {
+    /// Forget all remembered results. The hit, miss and eviction counts are kept.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        for slot in &mut self.slots { *slot=None; }
+        self.used_slots=0;
+    }
+    /// The number of remembered results.
+    pub fn len(&self) -> usize { if self.slots.is_empty() { self.map.len() } else { self.used_slots } }
+    pub fn is_empty(&self) -> bool { self.len()==0 }
+    /// The number of times get found a result.
+    pub fn hits(&self) -> u64 { self.hits }
+    /// The number of times get did not find a result.
+    pub fn misses(&self) -> u64 { self.misses }
+    /// The number of results forgotten to make room for new ones.
+    pub fn evictions(&self) -> u64 { self.evictions }
+    /// The maximum number of results remembered, or None if unbounded.
+    pub fn capacity(&self) -> Option<usize> { if self.slots.is_empty() { None } else { Some(self.slots.len()) } }
+    pub(crate) fn set_shared_counts(&mut self,counts:Option<Arc<SharedCacheCounts>>) { self.shared_counts=counts; }
+    /// The approximate number of bytes of heap memory used.
+    pub fn memory_usage(&self) -> usize { hash_map_memory(&self.map)+self.slots.capacity()*size_of::<Option<(K,V)>>() }
+    /// Free unused memory in an unbounded cache, such as after [OperationCache::clear]. A bounded cache is already as small as it can be.
+    pub fn shrink_to_fit(&mut self) where K:Eq+Hash { self.map.shrink_to_fit(); }
+    /// Limit the number of results remembered (at least 1), or None for no limit. This forgets all remembered results.
+    pub fn set_capacity(&mut self,capacity:Option<usize>) {
+        self.map=HashMap::new();
+        self.slots=match capacity {
+            None => vec![],
+            Some(capacity) => (0..capacity.max(1)).map(|_|None).collect(),
+        };
+        self.used_slots=0;
+    }
+}
+
+impl <K:Eq+Hash,V:Copy> OperationCache<K,V> {
+    fn slot(&self,key:&K) -> usize { (self.hasher.hash_one(key)%self.slots.len() as u64) as usize }
+}
+
+impl <K:Eq+Hash,V:Copy> OperationCache<K,V> {
+    /// Rewrite the remembered results for the nodes' new addresses after garbage collection,
+    /// forgetting those that refer to a node that was discarded. The hit, miss and eviction counts are kept.
+    pub fn rename<A:NodeAddress>(&mut self,renaming:&NodeRenaming<A>) where K:Renamable<A>, V:Renamable<A> {
+        let rename = |(key,value):(K,V)| Some((key.renamed(renaming)?,value.renamed(renaming)?));
+        if self.slots.is_empty() {
+            self.map = std::mem::take(&mut self.map).into_iter().filter_map(rename).collect();
+        } else {
+            let entries : Vec<(K,V)> = self.slots.iter_mut().filter_map(|slot|slot.take()).filter_map(rename).collect();
+            self.used_slots=0;
+            for (key,value) in entries { // may land in a different slot, possibly already taken.
+                let slot = self.slot(&key);
+                if self.slots[slot].is_none() { self.used_slots+=1; } else { self.evictions+=1; }
+                self.slots[slot]=Some((key,value));
+            }
+        }
+    }
+}
+
+impl <K:Eq+Hash,V:Copy> Cache<K,V> for OperationCache<K,V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        let res = if self.slots.is_empty() { self.map.get(key).cloned() } else {
+            match &self.slots[self.slot(key)] {
+                Some((k,v)) if k==key => Some(*v),
+                _ => None,
+            }
+        };
+        if res.is_some() { self.hits+=1 } else { self.misses+=1 }
+        if let Some(counts) = &self.shared_counts { counts.record(res.is_some()); }
+        res
+    }
+    fn insert(&mut self, key: K, value: V) {
+        if self.slots.is_empty() { self.map.insert(key,value); } else {
+            let slot = self.slot(&key);
+            match &self.slots[slot] {
+                None => self.used_slots+=1,
+                Some((k,_)) if *k!=key => self.evictions+=1,
+                _ => {}
+            }
+            self.slots[slot]=Some((key,value));
+        }
+    }
+}
+
+/// An iterator over the addresses of the nodes in a sub-DAG, in increasing order of address.
+/// As nodes are topologically sorted, this means children come before parents.
+/// Sinks are not included.
+pub struct PostOrder<A> {
+    addresses : std::vec::IntoIter<A>,
+}
+
+impl <A:NodeAddress> Iterator for PostOrder<A> {
+    type Item = A;
+    fn next(&mut self) -> Option<A> { self.addresses.next() }
+    fn size_hint(&self) -> (usize, Option<usize>) { self.addresses.size_hint() }
+}
+
+impl <A:NodeAddress> ExactSizeIterator for PostOrder<A> {}
+
+/// An iterator over the solutions of a diagram, each being the sorted list of variables that are true.
+/// This is a depth first search with an explicit stack, so each solution costs time proportional to the
+/// number of variables rather than a new traversal from the root. Multiplicities are ignored.
+pub struct Solutions<'a,A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>> {
+    nodes : &'a X,
+    stack : Vec<SolutionStep<A,M>>,
+    chosen : Vec<VariableIndex>,
+    num_variables : u16,
+    is_zdd : bool,
+}
+
+/// Work still to do for [Solutions].
+struct SolutionStep<A:NodeAddress,M:Multiplicity> {
+    index : NodeIndex<A,M>,
+    /// The next variable to decide.
+    level : u16,
+    /// The length to cut chosen back to.
+    len : usize,
+    /// A variable to then make true.
+    add : Option<VariableIndex>,
+}
+
+impl <'a,A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>> Solutions<'a,A,M,X> {
+    /// The solutions of the diagram starting at index, interpreted as a ZDD if is_zdd, otherwise as a BDD.
+    pub fn new(nodes:&'a X,index:NodeIndex<A,M>,num_variables:u16,is_zdd:bool) -> Self {
+        Solutions{ nodes, stack: vec![SolutionStep{index,level:0,len:0,add:None}], chosen: vec![], num_variables, is_zdd }
+    }
+
+    /// The next solution, borrowed rather than copied into a new Vec as [Iterator::next] does, or None if there are no more.
+    pub fn next_solution(&mut self) -> Option<&[VariableIndex]> {
+        while let Some(SolutionStep{index,level,len,add}) = self.stack.pop() {
+            self.chosen.truncate(len);
+            if let Some(variable) = add { self.chosen.push(variable); }
+            if index.is_false() { continue; }
+            let node = if index.is_true() { None } else { Some(self.nodes.node(index.address)) };
+            let node_level = node.map(|n|n.variable.0).unwrap_or(self.num_variables);
+            let len = self.chosen.len();
+            if level<node_level && !self.is_zdd { // a variable that does not matter in a BDD; try both values.
+                self.stack.push(SolutionStep{index,level:level+1,len,add:Some(VariableIndex(level))});
+                self.stack.push(SolutionStep{index,level:level+1,len,add:None});
+            } else if let Some(node) = node { // variables skipped in a ZDD are false.
+                self.stack.push(SolutionStep{index:node.hi,level:node_level+1,len,add:Some(node.variable)});
+                self.stack.push(SolutionStep{index:node.lo,level:node_level+1,len,add:None});
+            } else { return Some(&self.chosen); }
+        }
+        None
+    }
+}
+
+impl <'a,A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>> Iterator for Solutions<'a,A,M,X> {
+    type Item = Vec<VariableIndex>;
+    fn next(&mut self) -> Option<Self::Item> { self.next_solution().map(|solution|solution.to_vec()) }
+}
+
+/// An endless iterator of solutions of a diagram chosen at random, each being the sorted list of variables that are true.
+/// Each solution is equally likely, or for diagrams with multiplicities, proportional to its multiplicity.
+/// The counts needed are computed once when this is created, so each solution then costs time proportional to the number of variables.
+/// There are no solutions if the diagram is FALSE. Counts are u128, so this is limited to diagrams with fewer than 2^128 solutions.
+pub struct RandomSolutions<'a,A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>,R:Rng> {
+    nodes : &'a X,
+    /// The number of solutions below each node, as in [XDDBase::all_number_solutions].
+    counts : Vec<u128>,
+    root : NodeIndex<A,M>,
+    num_variables : u16,
+    is_zdd : bool,
+    rng : R,
+}
+
+impl <'a,A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>,R:Rng> RandomSolutions<'a,A,M,X,R> where u128:GeneratingFunctionWithMultiplicity<M> {
+    /// Random solutions of the diagram starting at index, interpreted as a ZDD if is_zdd, otherwise as a BDD.
+    pub fn new(nodes:&'a X,index:NodeIndex<A,M>,num_variables:u16,is_zdd:bool,rng:R) -> Self {
+        let length = index.address.as_usize()+1;
+        let counts = if is_zdd { nodes.all_number_solutions::<u128,false>(length,num_variables) } else { nodes.all_number_solutions::<u128,true>(length,num_variables) };
+        RandomSolutions{ nodes, counts, root: index, num_variables, is_zdd, rng }
+    }
+}
+
+impl <'a,A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>,R:Rng> Iterator for RandomSolutions<'a,A,M,X,R> where u128:GeneratingFunctionWithMultiplicity<M> {
+    type Item = Vec<VariableIndex>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.root.is_false() { return None; }
+        let mut res = vec![];
+        let mut index = self.root;
+        let mut level = 0;
+        loop {
+            let node_level = if index.is_sink() { self.num_variables } else { self.nodes.node(index.address).variable.0 };
+            if !self.is_zdd { // variables that do not matter in a BDD are equally likely either way.
+                for v in level..node_level { if self.rng.gen::<bool>() { res.push(VariableIndex(v)); } }
+            }
+            if index.is_sink() { return Some(res); }
+            let node = self.nodes.node(index.address);
+            let weight = |child:NodeIndex<A,M>| {
+                let count = self.counts[child.address.as_usize()].multiply(child.multiplicity);
+                if self.is_zdd { count } else {
+                    let child_level = if child.is_sink() { VariableIndex(self.num_variables) } else { self.nodes.node(child.address).variable };
+                    count.deal_with_variable_range_being_indeterminate(VariableIndex(node_level+1),child_level)
+                }
+            };
+            let lo_weight = weight(node.lo);
+            let hi_weight = weight(node.hi);
+            if self.rng.gen_range(0..lo_weight+hi_weight)<lo_weight { index=node.lo; } else {
+                res.push(node.variable);
+                index=node.hi;
+            }
+            level=node_level+1;
+        }
+    }
+}
+
+/// Options for [XDDBase::make_dot_file_with_options], to make drawings of medium to large diagrams readable.
+/// The default draws every node, the same as make_dot_file.
+#[derive(Clone,Debug,Default)]
+pub struct DotOptions {
+    /// Draw at most this many nodes. Further nodes are drawn as an elision marker "…" without their descendants.
+    pub max_nodes : Option<usize>,
+    /// Draw a chain of two or more nodes whose lo and hi are the same (variables that do not matter, common in ZDDs) as a single node.
+    pub collapse_dont_care_chains : bool,
+    /// Put nodes with the same variable at the same height.
+    pub rank_by_variable : bool,
+}
+
+/// A cache for a binary operation on two nodes, such as and or or.
+pub type BinaryOperationCache<A,M> = OperationCache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>;
+
+/// A cache for a ternary operation on three nodes, such as if-then-else.
+pub type TernaryOperationCache<A,M> = OperationCache<(NodeIndex<A,M>, NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>;
+
+/// A cache for applying an arbitrary boolean operator to two nodes. Multiplicities are ignored, so the key is just the addresses.
+pub type ApplyCache<A,M> = OperationCache<(BooleanOperator, A, A), NodeIndex<A,M>>;
+
+/// A cache for a ZDD family algebra operation such as union or join. Multiplicities are ignored, so the key is just the addresses.
+pub type FamilyCache<A,M> = OperationCache<(A, A), NodeIndex<A,M>>;
+
+/// A cache for a ZDD family operation on one node, such as finding the minimal sets. Multiplicities are ignored, so the key is just the address.
+pub type UnaryFamilyCache<A,M> = OperationCache<A, NodeIndex<A,M>>;
+
+/// A cache for a ZDD operation on a node and a variable, such as onset.
+pub type VariableOperationCache<A,M> = OperationCache<(NodeIndex<A,M>, VariableIndex), NodeIndex<A,M>>;
+
+/// A cache for fixing a variable to a constant.
+pub type RestrictCache<A,M> = OperationCache<(NodeIndex<A,M>, VariableIndex, bool), NodeIndex<A,M>>;
+
+/// A way a diagram breaks the rules the operations rely on, found by [XDDBase::validate]. Addresses are of nodes in the diagram.
+#[derive(Copy,Clone,Debug,Eq,PartialEq)]
+pub enum DiagramProblem<A:NodeAddress> {
+    /// An edge to an address with no node. parent is None for the root.
+    DanglingAddress{parent:Option<A>,child:A},
+    /// A node whose variable is not less than the number of variables.
+    VariableOutOfRange{address:A,variable:VariableIndex},
+    /// A child whose variable does not come after its parent's.
+    OutOfOrder{parent:A,child:A},
+    /// A node that should have been skipped: for a BDD one with both children the same, for a ZDD one whose hi child is FALSE.
+    Redundant{address:A},
+    /// A node the same as another, so equal functions may not have equal addresses.
+    Duplicate{address:A,duplicate_of:A},
+    /// A node whose children's multiplicities have a common factor, or an edge to FALSE with a multiplicity other than one.
+    /// parent is None for the root.
+    UncanonicalMultiplicity{parent:Option<A>},
+}
+
+impl <A:NodeAddress> Display for DiagramProblem<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parent_name = |parent:&Option<A>|parent.map(|p|format!("node {}",p)).unwrap_or_else(||"the root".to_string());
+        match self {
+            DiagramProblem::DanglingAddress{parent,child} => write!(f,"{} refers to address {} which has no node",parent_name(parent),child),
+            DiagramProblem::VariableOutOfRange{address,variable} => write!(f,"node {} has variable {} which is out of range",address,variable),
+            DiagramProblem::OutOfOrder{parent,child} => write!(f,"node {} does not have a variable before its child {}",parent,child),
+            DiagramProblem::Redundant{address} => write!(f,"node {} is redundant",address),
+            DiagramProblem::Duplicate{address,duplicate_of} => write!(f,"node {} is a duplicate of node {}",address,duplicate_of),
+            DiagramProblem::UncanonicalMultiplicity{parent} => write!(f,"{} has multiplicities that are not canonical",parent_name(parent)),
+        }
+    }
+}
+
+/// The problems with a diagram found by [XDDBase::validate], in the order found.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct InvalidDiagram<A:NodeAddress> {
+    pub problems : Vec<DiagramProblem<A>>,
+}
+
+impl <A:NodeAddress> std::error::Error for InvalidDiagram<A> {}
+
+impl <A:NodeAddress> Display for InvalidDiagram<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f,"Invalid diagram: ")?;
+        for (i,problem) in self.problems.iter().enumerate() {
+            if i>0 { write!(f,"; ")?; }
+            write!(f,"{}",problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// What to do with a pair of arguments of a binary operation done by [XDDBase::apply_iteratively].
+pub enum ApplyStep<A:NodeAddress,M:Multiplicity,K> {
+    /// The result is known, from a terminal case or the cache.
+    Done(NodeIndex<A,M>),
+    /// The result is a node for variable whose children are the results for the lo and hi pairs, to be cached under key.
+    Split { key:K, variable:VariableIndex, lo:(NodeIndex<A,M>,NodeIndex<A,M>), hi:(NodeIndex<A,M>,NodeIndex<A,M>) },
+}
+
+/// The explicit stack used by [XDDBase::apply_iteratively].
+enum ApplyTask<A:NodeAddress,M:Multiplicity,K> {
+    /// Work out the result for a pair of arguments, pushing it onto the results.
+    Visit(NodeIndex<A,M>,NodeIndex<A,M>),
+    /// Replace the lo and hi results on top of the results by the node made from them.
+    Combine(K,VariableIndex),
+}
 
 /// Functions that any representation of an XDD must have, although some representations
 /// will execute this more quickly than others, at the cost of more memory capacity.
@@ -24,6 +401,14 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     fn add_node(&mut self, node: Node<A,M>) -> A;
     /// The number of nodes in this tree, not counting the two special node indices.
     fn len(&self) -> usize;
+    /// True iff there are no nodes in this tree other than the two special node indices.
+    fn is_empty(&self) -> bool { self.len()==0 }
+    /// The approximate number of bytes of heap memory used, including any lookup tables.
+    fn memory_usage(&self) -> usize;
+    /// Free memory allocated but not used, such as after [XDDBase::gc].
+    fn shrink_to_fit(&mut self);
+    /// Allocate room for at least additional more nodes, so adding them does not need to reallocate.
+    fn reserve(&mut self, additional:usize);
 
     /// Like add_node, but first check with find_node_index to see if it is already there. Also canonicalize multiplicities by removing gcd.
     fn add_node_if_not_present(&mut self, node: Node<A,M>) -> NodeIndex<A,M> {
@@ -66,12 +451,12 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     /// Produce a BDD which is true iff exactly 1 of the given variables is true, regardless of other variables.
     /// The variables array must be sorted, smallest to highest.
     fn exactly_one_of_bdd(&mut self,variables:&[VariableIndex]) -> NodeIndex<A,M> {
-        if variables.len()==0 { NodeIndex::FALSE } else {
+        if variables.is_empty() { NodeIndex::FALSE } else {
             let mut right = NodeIndex::TRUE;
             let mut left = NodeIndex::FALSE;
             // The diagram that is needed has two parallel diagonal lines, one right, one left.
             // One is on the right if one has had exactly 1 variable, one is on the left if one has had 0 variables.
-            for &variable in variables.into_iter().rev() {
+            for &variable in variables.iter().rev() {
                 left = self.add_node_if_not_present(Node {variable,lo:left,hi:right});
                 if variable==variables[0] { return left; }
                 right = self.add_node_if_not_present(Node {variable,lo:right,hi: NodeIndex::FALSE});
@@ -91,24 +476,235 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     /// Produce a ZDD which is true iff exactly 1 of the given variables is true, regardless of other variables.
     /// The variables array must be sorted, smallest to highest.
     fn exactly_one_of_zdd(&mut self,variables:&[VariableIndex],total_num_variables:u16) -> NodeIndex<A,M> {
-        if variables.len()==0 { NodeIndex::FALSE } else {
+        if variables.is_empty() { NodeIndex::FALSE } else {
             let mut right = NodeIndex::TRUE;
             let mut left = NodeIndex::FALSE;
             let mut dealt_with = total_num_variables;
             // The diagram that is needed has two parallel diagonal lines, one right, one left.
             // One is on the right if one has had exactly 1 variable, one is on the left if one has had 0 variables.
-            for &variable in variables.into_iter().rev() {
+            for &variable in variables.iter().rev() {
                 left = self.zdd_variables_in_range_dont_matter(left,variable.0+1..dealt_with);
                 right = self.zdd_variables_in_range_dont_matter(right,variable.0+1..dealt_with);
                 dealt_with = variable.0;
                 left = self.add_node_if_not_present(Node {variable,lo:left,hi:right});
                 if variable==variables[0] { return self.zdd_variables_in_range_dont_matter(left,0..dealt_with); }
-                right = self.add_node_if_not_present(Node {variable,lo:right,hi: NodeIndex::FALSE});
+                // right stays the same, as in a ZDD a node whose hi is FALSE is just its lo.
             }
             panic!("Never got to the first variable.");
         }
     }
 
+    /// The states used when building a cardinality constraint, and the diagram for each state after the last variable.
+    /// State k means k of the variables so far are true. If at_most is at least the number of variables it is no
+    /// constraint, so counts of at_least or more are merged into one absorbing state to keep the diagram small.
+    /// Returns (the final layer, whether the last state is absorbing).
+    fn cardinality_final_layer(num_variables:usize,at_least:usize,at_most:usize) -> (Vec<NodeIndex<A,M>>,bool) {
+        let absorbing = at_most>=num_variables;
+        let last_state = if absorbing { at_least } else { at_most };
+        ((0..=last_state).map(|k|if k>=at_least { NodeIndex::TRUE } else { NodeIndex::FALSE }).collect(),absorbing)
+    }
+
+    /// Produce a BDD which is true iff between at_least and at_most (inclusive) of the given variables are true, regardless of other variables.
+    /// The variables array must be sorted, smallest to highest. The number of nodes is at most (at_most+1) times the number of variables.
+    fn between_n_and_m_of_bdd(&mut self,variables:&[VariableIndex],at_least:usize,at_most:usize) -> NodeIndex<A,M> {
+        if at_least>at_most || at_least>variables.len() { return NodeIndex::FALSE; }
+        let (mut layer,absorbing) = Self::cardinality_final_layer(variables.len(),at_least,at_most);
+        for (position,&variable) in variables.iter().enumerate().rev() {
+            // only states up to position are reachable here.
+            layer = (0..layer.len().min(position+1)).map(|k|{
+                let lo = layer[k];
+                let hi = if k+1<layer.len() { layer[k+1] } else if absorbing { layer[k] } else { NodeIndex::FALSE };
+                if lo==hi { lo } else { self.add_node_if_not_present(Node {variable,lo,hi}) }
+            }).collect();
+        }
+        layer[0]
+    }
+
+    /// Produce a ZDD which is true iff between at_least and at_most (inclusive) of the given variables are true, regardless of other variables.
+    /// The variables array must be sorted, smallest to highest.
+    fn between_n_and_m_of_zdd(&mut self,variables:&[VariableIndex],at_least:usize,at_most:usize,total_num_variables:u16) -> NodeIndex<A,M> {
+        if at_least>at_most || at_least>variables.len() { return NodeIndex::FALSE; }
+        let (mut layer,absorbing) = Self::cardinality_final_layer(variables.len(),at_least,at_most);
+        let mut dealt_with = total_num_variables;
+        let dont_matter = |s:&mut Self,index:NodeIndex<A,M>,range:Range<u16>| if index.is_false() { index } else { s.zdd_variables_in_range_dont_matter(index,range) };
+        for (position,&variable) in variables.iter().enumerate().rev() {
+            for index in layer.iter_mut() { *index = dont_matter(self,*index,variable.0+1..dealt_with); }
+            dealt_with = variable.0;
+            layer = (0..layer.len().min(position+1)).map(|k|{
+                let lo = layer[k];
+                let hi = if k+1<layer.len() { layer[k+1] } else if absorbing { layer[k] } else { NodeIndex::FALSE };
+                if hi.is_false() { lo } else { self.add_node_if_not_present(Node {variable,lo,hi}) }
+            }).collect();
+        }
+        dont_matter(self,layer[0],0..dealt_with)
+    }
+
+    /// Produce a ZDD whose sets are the subsets of the given variables with between at_least and at_most (inclusive) members.
+    /// Unlike [XDDBase::between_n_and_m_of_zdd], other variables must be false. The variables array must be sorted, smallest to highest.
+    fn subsets_of_size_zdd(&mut self,variables:&[VariableIndex],at_least:usize,at_most:usize) -> NodeIndex<A,M> {
+        if at_least>at_most || at_least>variables.len() { return NodeIndex::FALSE; }
+        let (mut layer,absorbing) = Self::cardinality_final_layer(variables.len(),at_least,at_most);
+        for (position,&variable) in variables.iter().enumerate().rev() {
+            layer = (0..layer.len().min(position+1)).map(|k|{
+                let lo = layer[k];
+                let hi = if k+1<layer.len() { layer[k+1] } else if absorbing { layer[k] } else { NodeIndex::FALSE };
+                if hi.is_false() { lo } else { self.add_node_if_not_present(Node {variable,lo,hi}) }
+            }).collect();
+        }
+        layer[0]
+    }
+
+    /// Produce a BDD which is true iff exactly n of the given variables are true, regardless of other variables.
+    /// The variables array must be sorted, smallest to highest.
+    fn exactly_n_of_bdd(&mut self,variables:&[VariableIndex],n:usize) -> NodeIndex<A,M> {
+        self.between_n_and_m_of_bdd(variables,n,n)
+    }
+
+    /// Produce a ZDD which is true iff exactly n of the given variables are true, regardless of other variables.
+    /// The variables array must be sorted, smallest to highest.
+    fn exactly_n_of_zdd(&mut self,variables:&[VariableIndex],n:usize,total_num_variables:u16) -> NodeIndex<A,M> {
+        self.between_n_and_m_of_zdd(variables,n,n,total_num_variables)
+    }
+
+    /// Produce a BDD which is true iff the sum of coefficient times variable over the terms is between at_least and at_most (inclusive),
+    /// regardless of other variables. The terms must be sorted by variable, smallest to highest.
+    fn linear_constraint_bdd(&mut self,terms:&[(VariableIndex,i64)],at_least:i128,at_most:i128) -> NodeIndex<A,M> {
+        self.linear_constraint::<true>(terms,at_least,at_most,0)
+    }
+
+    /// Produce a ZDD which is true iff the sum of coefficient times variable over the terms is between at_least and at_most (inclusive),
+    /// regardless of other variables. The terms must be sorted by variable, smallest to highest.
+    fn linear_constraint_zdd(&mut self,terms:&[(VariableIndex,i64)],at_least:i128,at_most:i128,total_num_variables:u16) -> NodeIndex<A,M> {
+        self.linear_constraint::<false>(terms,at_least,at_most,total_num_variables)
+    }
+
+    /// The work for [XDDBase::linear_constraint_bdd] and [XDDBase::linear_constraint_zdd].
+    ///
+    /// The state after some terms is the partial sum so far. Many partial sums lead to the same diagram, so rather than
+    /// remembering the diagram for each partial sum, each diagram is remembered along with the interval of partial
+    /// sums that lead to it. The work is then proportional to the size of the result rather than the range of sums.
+    fn linear_constraint<const BDD:bool>(&mut self,terms:&[(VariableIndex,i64)],at_least:i128,at_most:i128,total_num_variables:u16) -> NodeIndex<A,M> {
+        /// Memo for a position in terms; the diagram for partial sums from the key to the first element of the value.
+        type Memo<A,M> = Vec<BTreeMap<i128,(i128,NodeIndex<A,M>)>>;
+        /// The diagram for the terms from position onwards given the partial sum of the earlier ones,
+        /// along with the inclusive interval of partial sums that give the same diagram.
+        #[allow(clippy::too_many_arguments)]
+        fn build<A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>+?Sized,const BDD:bool>(x:&mut X,terms:&[(VariableIndex,i64)],position:usize,sum:i128,at_least:i128,at_most:i128,total_num_variables:u16,memo:&mut Memo<A,M>) -> (i128,i128,NodeIndex<A,M>) {
+            if position==terms.len() {
+                return if sum<at_least { (i128::MIN,at_least-1,NodeIndex::FALSE) } else if sum>at_most { (at_most+1,i128::MAX,NodeIndex::FALSE) } else { (at_least,at_most,NodeIndex::TRUE) };
+            }
+            if let Some((&low,&(high,index))) = memo[position].range(..=sum).next_back() {
+                if sum<=high { return (low,high,index); }
+            }
+            let (variable,coefficient) = terms[position];
+            let coefficient = coefficient as i128;
+            let (low_lo,high_lo,mut lo) = build::<A,M,X,BDD>(x,terms,position+1,sum,at_least,at_most,total_num_variables,memo);
+            let (low_hi,high_hi,mut hi) = build::<A,M,X,BDD>(x,terms,position+1,sum+coefficient,at_least,at_most,total_num_variables,memo);
+            let low = low_lo.max(low_hi.saturating_sub(coefficient));
+            let high = high_lo.min(high_hi.saturating_sub(coefficient));
+            let index = if BDD {
+                if lo==hi { lo } else { x.add_node_if_not_present(Node {variable,lo,hi}) }
+            } else {
+                let next = terms.get(position+1).map(|t|t.0.0).unwrap_or(total_num_variables);
+                if !lo.is_false() { lo = x.zdd_variables_in_range_dont_matter(lo,variable.0+1..next); }
+                if !hi.is_false() { hi = x.zdd_variables_in_range_dont_matter(hi,variable.0+1..next); }
+                if hi.is_false() { lo } else { x.add_node_if_not_present(Node {variable,lo,hi}) }
+            };
+            memo[position].insert(low,(high,index));
+            (low,high,index)
+        }
+        if at_least>at_most { return NodeIndex::FALSE; }
+        let mut memo : Memo<A,M> = vec![BTreeMap::new();terms.len()];
+        let (_,_,res) = build::<A,M,Self,BDD>(self,terms,0,0,at_least,at_most,total_num_variables,&mut memo);
+        if BDD || res.is_false() { res } else {
+            let first = terms.first().map(|t|t.0.0).unwrap_or(total_num_variables);
+            self.zdd_variables_in_range_dont_matter(res,0..first)
+        }
+    }
+
+    /// Produce a DD which is true iff the options (variable i being options[i]) that are true cover each primary item
+    /// (those less than num_primary_items) exactly once, and each other item at most once, regardless of other variables.
+    ///
+    /// This is a frontier based construction going through the options in order. The state before an option is the set
+    /// of items covered so far that are in some later option; items in no later option either were covered or make it impossible.
+    /// Each item is given a bit, reused once no later option contains it, so a state is as many bits as the widest frontier.
+    /// An option containing an item twice would cover it twice, so can never be chosen.
+    fn exact_cover<const BDD:bool>(&mut self,num_primary_items:usize,options:&[Vec<usize>],total_num_variables:u16) -> NodeIndex<A,M> {
+        let num_items = options.iter().flatten().map(|&i|i+1).max().unwrap_or(0).max(num_primary_items);
+        let mut last : Vec<Option<usize>> = vec![None;num_items];
+        let mut repeats = vec![false;options.len()];
+        for (option,items) in options.iter().enumerate() {
+            for &item in items {
+                if last[item]==Some(option) { repeats[option]=true; }
+                last[item]=Some(option);
+            }
+        }
+        if last[..num_primary_items].iter().any(|l|l.is_none()) { return NodeIndex::FALSE; }
+        // allocate bits to items while they are in the frontier.
+        let mut bit : Vec<usize> = vec![usize::MAX;num_items];
+        let mut free_bits : Vec<usize> = vec![];
+        let mut width = 0;
+        for (option,items) in options.iter().enumerate() {
+            for &item in items {
+                if bit[item]==usize::MAX { bit[item] = free_bits.pop().unwrap_or_else(||{ width+=1; width-1 }); }
+            }
+            for (k,&item) in items.iter().enumerate() {
+                if last[item]==Some(option) && !items[..k].contains(&item) { free_bits.push(bit[item]); } // only free a repeated item's bit once.
+            }
+        }
+        let words = width.div_ceil(64).max(1);
+        let mask = |items:&mut dyn Iterator<Item=usize>| {
+            let mut mask = vec![0u64;words];
+            for item in items { mask[bit[item]/64] |= 1<<(bit[item]%64); }
+            mask
+        };
+        // Go forwards finding the distinct states before each option, and the lo and hi children of each (IMPOSSIBLE if there are no solutions).
+        const IMPOSSIBLE : u32 = u32::MAX;
+        let mut states : Vec<Vec<u64>> = vec![vec![0;words]];
+        let mut children : Vec<Vec<[u32;2]>> = Vec::with_capacity(options.len());
+        let mut covered = vec![0u64;words];
+        for (option,items) in options.iter().enumerate() {
+            let taken = mask(&mut items.iter().copied());
+            let leaving = mask(&mut items.iter().copied().filter(|&i|last[i]==Some(option)));
+            let leaving_primary = mask(&mut items.iter().copied().filter(|&i|i<num_primary_items && last[i]==Some(option)));
+            let mut next_state_index : HashMap<Vec<u64>,u32> = HashMap::new();
+            let mut level = Vec::with_capacity(states.len());
+            for state in &states {
+                let mut child = [IMPOSSIBLE,IMPOSSIBLE];
+                for take in [false,true] {
+                    if take && (repeats[option] || state.iter().zip(&taken).any(|(s,t)|s&t!=0)) { continue; }
+                    for w in 0..words { covered[w] = if take { state[w]|taken[w] } else { state[w] }; }
+                    if covered.iter().zip(&leaving_primary).any(|(c,l)|c&l!=*l) { continue; }
+                    for w in 0..words { covered[w] &= !leaving[w]; }
+                    let index = match next_state_index.get(&covered) {
+                        Some(&index) => index,
+                        None => {
+                            let index = u32::try_from(next_state_index.len()).ok().filter(|&i|i!=IMPOSSIBLE).expect("Too many states");
+                            next_state_index.insert(covered.clone(),index);
+                            index
+                        }
+                    };
+                    child[take as usize] = index;
+                }
+                level.push(child);
+            }
+            children.push(level);
+            states = vec![vec![];next_state_index.len()];
+            for (state,index) in next_state_index { states[index as usize] = state; }
+        }
+        // Then go backwards making the nodes. After the last option the only possible state is that nothing is left.
+        let end = if BDD { NodeIndex::TRUE } else { self.zdd_variables_in_range_dont_matter(NodeIndex::TRUE,options.len() as u16..total_num_variables) };
+        let mut diagrams = vec![end;states.len()];
+        for (option,level) in children.iter().enumerate().rev() {
+            let variable = VariableIndex(option as u16);
+            diagrams = level.iter().map(|child|{
+                let [lo,hi] = child.map(|c|if c==IMPOSSIBLE { NodeIndex::FALSE } else { diagrams[c as usize] });
+                if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { self.add_node_if_not_present(Node{variable,lo,hi}) }
+            }).collect();
+        }
+        diagrams[0]
+    }
+
     /// make a function that is true if starting evaluating a ZDD starting from upto.
     /// This is a long chain of variables from upto (inclusive) to total_num_variables (exclusive)
     /// where each elememt points to the next with both hi and lo, and the final field is NodeIndex::TRUE
@@ -116,7 +712,7 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     fn true_regardless_of_variables_below_zdd(&mut self,upto:VariableIndex,total_num_variables:u16) -> NodeIndex<A,M> {
         let mut index = NodeIndex::TRUE;
         for i in (upto.0..total_num_variables).rev() {
-            let v = VariableIndex(i as u16);
+            let v = VariableIndex(i);
             index = self.add_node_if_not_present(Node {
                 variable : v,
                 lo: index,
@@ -185,24 +781,59 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
             index
         }
     */
+    /// Make a BDD for f with every variable negated, that is g(x)=f(¬x), by swapping the children of each node. Multiplicities are kept.
+    /// Swapping children can not make a node redundant or a duplicate, so this is a single pass over the nodes reachable from f.
+    fn negate_variables_bdd(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let mut done : HashMap<A,NodeIndex<A,M>> = HashMap::new();
+        let child = |done:&HashMap<A,NodeIndex<A,M>>,index:NodeIndex<A,M>| if index.is_sink() { index } else { done[&index.address].multiply(index.multiplicity) };
+        for address in self.post_order(f) {
+            let node = self.node(address);
+            let res = self.add_node_if_not_present(Node{variable:node.variable,lo:child(&done,node.hi),hi:child(&done,node.lo)});
+            done.insert(address,res);
+        }
+        child(&done,f)
+    }
+
     /// Make a node representing the negation of the function represented by the input node interpreted as a BDD. A.k.a. ~ or !.
     ///
     /// Multiplicity of all terms in result is 1.
-    fn not_bdd(&mut self, index: NodeIndex<A,M>, cache : &mut HashMap<A,A>) -> NodeIndex<A,M> {
-        if index.is_true() { NodeIndex::FALSE }
-        else if index.is_false() { NodeIndex::TRUE }
-        else if let Some(&res) = cache.get(&index.address) { NodeIndex {address:res,multiplicity:M::ONE} }
-        else {
-            let node = self.node(index.address);
-            let newnode = Node {
-                variable: node.variable,
-                lo: self.not_bdd(node.lo,cache),
-                hi: self.not_bdd(node.hi,cache),
-            };
-            let res = self.add_node_if_not_present(newnode);
-            cache.insert(index.address,res.address);
-            res
+    fn not_bdd<C:Cache<A,A>>(&mut self, index: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        // An explicit stack rather than recursion, so deep diagrams can not overflow the call stack.
+        // Visit(i) means work out the negation of i; Combine(i) means make it from the negations of i's children, which are on top of results.
+        enum Task<A> { Visit(A), Combine(A) }
+        let mut tasks = vec![Task::Visit(index.address)];
+        let mut results : Vec<A> = vec![];
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Visit(address) => {
+                    if address.is_true() { results.push(A::FALSE) }
+                    else if address.is_false() { results.push(A::TRUE) }
+                    else if let Some(res) = cache.get(&address) { results.push(res) }
+                    else {
+                        let node = self.node(address);
+                        tasks.push(Task::Combine(address));
+                        tasks.push(Task::Visit(node.hi.address));
+                        tasks.push(Task::Visit(node.lo.address));
+                    }
+                }
+                Task::Combine(address) => {
+                    let hi = results.pop().unwrap();
+                    let lo = results.pop().unwrap();
+                    // children differing only in multiplicity have the same negation.
+                    let res = if lo==hi { lo } else {
+                        let newnode = Node {
+                            variable: self.node(address).variable,
+                            lo: NodeIndex {address:lo,multiplicity:M::ONE},
+                            hi: NodeIndex {address:hi,multiplicity:M::ONE},
+                        };
+                        self.add_node_if_not_present(newnode).address
+                    };
+                    cache.insert(address,res);
+                    results.push(res);
+                }
+            }
         }
+        NodeIndex {address:results.pop().unwrap(),multiplicity:M::ONE}
     }
 
     /// Make a node representing the negation of the function represented by the input node interpreted as a ZDD. A.k.a. ~ or !.
@@ -210,11 +841,11 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     /// TODO extend caching.
     ///
     /// Multiplicity of all terms in result is 1.
-    fn not_zdd(&mut self, index: NodeIndex<A,M>, upto:VariableIndex, total_number_variables:u16, cache : &mut HashMap<(A, VariableIndex),A>) -> NodeIndex<A,M> {
+    fn not_zdd<C:Cache<(A, VariableIndex),A>>(&mut self, index: NodeIndex<A,M>, upto:VariableIndex, total_number_variables:u16, cache : &mut C) -> NodeIndex<A,M> {
         //println!("not_zdd({},{},{})",index,upto,total_number_variables);
         // else if index.is_true() { self.create_zdd_any_variables_below_given_variable_true(upto,total_number_variables) }
         let key = (index.address,upto);
-        if let Some(&res) = cache.get(&key) { NodeIndex {address:res,multiplicity:M::ONE} }
+        if let Some(res) = cache.get(&key) { NodeIndex {address:res,multiplicity:M::ONE} }
         else {
             let res={
                 let mut upper_bound = total_number_variables;
@@ -251,35 +882,64 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
 
     /// Create a node for a zdd (or find existing) for variable variable with lo and hi choices, and store it in the provided cache.
     /// Uniqueifies - sees if the hi and lo are same, in which case just produce lo, and looks for existing nodes.
-    fn create_node_bdd<K:Eq+Hash>(&mut self, lo: NodeIndex<A,M>, hi: NodeIndex<A,M>, variable:VariableIndex, key:K, cache:&mut HashMap<K, NodeIndex<A,M>>) -> NodeIndex<A,M> {
-        let res = if lo==hi { lo } else {
+    fn create_node_bdd<K,C:Cache<K,NodeIndex<A,M>>>(&mut self, lo: NodeIndex<A,M>, hi: NodeIndex<A,M>, variable:VariableIndex, key:K, cache:&mut C) -> NodeIndex<A,M> {
+        let res = if lo.is_false() && hi.is_false() { NodeIndex::FALSE } // the multiplicity of FALSE is irrelevant, so may differ.
+        else if lo==hi { lo } else {
             self.add_node_if_not_present(Node {variable,lo,hi})
         };
         cache.insert(key,res);
         res
     }
 
+    /// Do a binary operation where each pair of arguments either has a known result, or is split by step into
+    /// lo and hi pairs for the top variable whose results become the children of a new node, remembered in cache under key.
+    ///
+    /// This uses an explicit stack rather than recursion, so very deep diagrams (such as long chains over tens of
+    /// thousands of variables) can not overflow the call stack. The work is done in the same order as the obvious recursion.
+    fn apply_iteratively<K,C:Cache<K,NodeIndex<A,M>>,const BDD:bool>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C, step:impl Fn(&mut Self,&mut C,NodeIndex<A,M>,NodeIndex<A,M>)->ApplyStep<A,M,K>) -> NodeIndex<A,M> {
+        let mut tasks = vec![ApplyTask::Visit(index1,index2)];
+        let mut results : Vec<NodeIndex<A,M>> = vec![];
+        while let Some(task) = tasks.pop() {
+            match task {
+                ApplyTask::Visit(index1,index2) => match step(self,cache,index1,index2) {
+                    ApplyStep::Done(res) => results.push(res),
+                    ApplyStep::Split{key,variable,lo,hi} => {
+                        tasks.push(ApplyTask::Combine(key,variable));
+                        tasks.push(ApplyTask::Visit(hi.0,hi.1));
+                        tasks.push(ApplyTask::Visit(lo.0,lo.1));
+                    }
+                },
+                ApplyTask::Combine(key,variable) => {
+                    let hi = results.pop().unwrap();
+                    let lo = results.pop().unwrap();
+                    let res = if BDD { self.create_node_bdd(lo,hi,variable,key,cache) } else { self.create_node_zdd(lo,hi,variable,key,cache) };
+                    results.push(res);
+                }
+            }
+        }
+        results.pop().unwrap()
+    }
+
     /// Make a node representing index1 and index2 (and in the logical sense, a.k.a. ∧ or &&)
     ///
     /// If multiplicities are involved, this is a Product operation. That is, the multiplicity of a value in the result is the product of the multiplicities of the value in the inputs.
-    fn mul_bdd(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>) -> NodeIndex<A,M> {
-        if index1.is_false() || index2.is_false() { NodeIndex::FALSE }
-        else if index1.is_true() { index2.multiply(index1.multiplicity) }
-        else if index2.is_true() { index1.multiply(index2.multiplicity) }
-        else if M::MULTIPLICITIES_IRRELEVANT && index1.address==index2.address { index1.multiply(index2.multiplicity) } // a&a is not a in presence of multiplicities. Or even a multiple of a.
-        else {
-            let key = if index1.address < index2.address {(index1,index2)} else {(index2,index1)};
-            if let Some(&res) = cache.get(&key) { res }
+    fn mul_bdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.apply_iteratively::<_,_,true>(index1,index2,cache,|nodes,cache,index1,index2|{
+            if index1.is_false() || index2.is_false() { ApplyStep::Done(NodeIndex::FALSE) }
+            else if index1.is_true() { ApplyStep::Done(index2.multiply(index1.multiplicity)) }
+            else if index2.is_true() || (M::MULTIPLICITIES_IRRELEVANT && index1.address==index2.address) { ApplyStep::Done(index1.multiply(index2.multiplicity)) } // a&a is not a in presence of multiplicities. Or even a multiple of a.
             else {
-                let node1 = self.node_incorporating_multiplicity(index1);
-                let node2 = self.node_incorporating_multiplicity(index2);
-                let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1,index1)};
-                let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2,index2)};
-                let lo = self.mul_bdd(lo1,lo2,cache);
-                let hi = self.mul_bdd(hi1,hi2,cache);
-                self.create_node_bdd(lo,hi,if node1.variable <= node2.variable { node1.variable } else {node2.variable},key,cache)
+                let key = if index1.address < index2.address {(index1,index2)} else {(index2,index1)};
+                if let Some(res) = cache.get(&key) { ApplyStep::Done(res) }
+                else {
+                    let node1 = nodes.node_incorporating_multiplicity(index1);
+                    let node2 = nodes.node_incorporating_multiplicity(index2);
+                    let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1,index1)};
+                    let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2,index2)};
+                    ApplyStep::Split { key, variable: node1.variable.min(node2.variable), lo: (lo1,lo2), hi: (hi1,hi2) }
+                }
             }
-        }
+        })
     }
 
     fn node_incorporating_multiplicity(&self, index: NodeIndex<A,M>) -> Node<A,M> {
@@ -295,34 +955,141 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     /// For non-trivial multiplicities, this is the *Sum* operator, not the *Union* operator.
     ///
     /// In particular, the sum_bdd(f,g)(x) has multiplicity equal to the sum of the multiplicity of f(x) and g(x).
-    fn sum_bdd(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>) -> NodeIndex<A,M> {
-        if index1.address==index2.address { NodeIndex {address:index1.address,multiplicity:M::combine_or(index1.multiplicity, index2.multiplicity)} }
-        else if index1.is_false() { index2 }
-        else if index2.is_false() { index1 }
-        else if M::MULTIPLICITIES_IRRELEVANT && (index1.is_true() || index2.is_true()) { NodeIndex::TRUE }
-            // if one of the two is true, then need to add true to both sides of the other to get multiplicities correct. The above line is just an optimization for that case.
+    fn sum_bdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.apply_iteratively::<_,_,true>(index1,index2,cache,|nodes,cache,index1,index2|{
+            if index1.is_false() && index2.is_false() { ApplyStep::Done(NodeIndex::FALSE) } // either may be FALSE with a meaningless multiplicity.
+            else if index1.is_false() { ApplyStep::Done(index2) }
+            else if index2.is_false() { ApplyStep::Done(index1) }
+            else if index1.address==index2.address {
+                let multiplicity = M::combine_or(index1.multiplicity, index2.multiplicity);
+                ApplyStep::Done(if multiplicity.is_zero() { NodeIndex::FALSE } else { NodeIndex {address:index1.address,multiplicity} })
+            }
+            else if M::MULTIPLICITIES_IRRELEVANT && (index1.is_true() || index2.is_true()) { ApplyStep::Done(NodeIndex::TRUE) }
+                // if one of the two is true, then need to add true to both sides of the other to get multiplicities correct. The above line is just an optimization for that case.
+            else {
+                let (index1,index2) = if (M::SYMMETRIC_OR && index1.address < index2.address) || index1.address.is_true() {(index2,index1)} else {(index1,index2)};
+                let key = (index1,index2);
+                if let Some(res) = cache.get(&key) { ApplyStep::Done(res) }
+                else {
+                    let node1 = nodes.node_incorporating_multiplicity(index1);
+                    let node2 = if index2.is_true() {
+                        Node {
+                        variable: node1.variable,
+                        lo: NodeIndex { address: A::TRUE, multiplicity: index2.multiplicity },
+                        hi: NodeIndex { address: A::TRUE, multiplicity: index2.multiplicity }
+                    }} else {nodes.node_incorporating_multiplicity(index2)};
+                    let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1,index1)};
+                    let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2,index2)};
+                    ApplyStep::Split { key, variable: node1.variable.min(node2.variable), lo: (lo1,lo2), hi: (hi1,hi2) }
+                }
+            }
+        })
+    }
+
+
+    /// Make a node representing index1 xor index2 (exclusive or, a.k.a. ⊕ or symmetric difference)
+    ///
+    /// If multiplicities are involved, a value in the result has the multiplicity it had in whichever input contained it.
+    fn xor_bdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.apply_iteratively::<_,_,true>(index1,index2,cache,|nodes,cache,index1,index2|{
+            if index1.address==index2.address { ApplyStep::Done(NodeIndex::FALSE) }
+            else if index1.is_false() { ApplyStep::Done(index2) }
+            else if index2.is_false() { ApplyStep::Done(index1) }
+            else {
+                // at most one can be TRUE, and it will be index1 as TRUE has the lowest address of any non-false node.
+                let key = if index1.address < index2.address {(index1,index2)} else {(index2,index1)};
+                let (index1,index2) = key;
+                if let Some(res) = cache.get(&key) { ApplyStep::Done(res) }
+                else {
+                    let node2 = nodes.node_incorporating_multiplicity(index2);
+                    let node1 = if index1.is_true() { Node { variable: node2.variable, lo: index1, hi: index1 } } else { nodes.node_incorporating_multiplicity(index1) };
+                    let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1,index1)};
+                    let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2,index2)};
+                    ApplyStep::Split { key, variable: node1.variable.min(node2.variable), lo: (lo1,lo2), hi: (hi1,hi2) }
+                }
+            }
+        })
+    }
+
+
+    /// Get the variable at the top of whichever of the indices are not sinks. There must be at least one such index.
+    fn top_variable(&self, indices:&[NodeIndex<A,M>]) -> VariableIndex {
+        indices.iter().filter(|i|!i.is_sink()).map(|i|self.node(i.address).variable).min().expect("top_variable needs a non-sink index")
+    }
+
+    /// Make a node representing if f then g else h, interpreted as BDDs. This is (f and g) or (not f and h), but done in one pass.
+    ///
+    /// If multiplicities are involved, the multiplicity of f is ignored (other than whether it is zero), and
+    /// a value in the result has its multiplicity from g or h.
+    fn ite_bdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if f.is_true() { g }
+        else if f.is_false() { h }
+        else if g==h { g }
         else {
-            let (index1,index2) = if (M::SYMMETRIC_OR && index1.address < index2.address) || index1.address.is_true() {(index2,index1)} else {(index1,index2)};
-            let key = (index1,index2);
-            if let Some(&res) = cache.get(&key) { res }
+            let f = NodeIndex{address:f.address,multiplicity:M::ONE};
+            let key = (f,g,h);
+            if let Some(res) = cache.get(&key) { res }
             else {
-                let node1 = self.node_incorporating_multiplicity(index1);
-                let node2 = if index2.is_true() {
-                    Node {
-                    variable: node1.variable,
-                    lo: NodeIndex { address: A::TRUE, multiplicity: index2.multiplicity },
-                    hi: NodeIndex { address: A::TRUE, multiplicity: index2.multiplicity }
-                }} else {self.node_incorporating_multiplicity(index2)};
-                let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1,index1)};
-                let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2,index2)};
-                let lo = self.sum_bdd(lo1,lo2,cache);
-                let hi = self.sum_bdd(hi1,hi2,cache);
-                self.create_node_bdd(lo,hi,if node1.variable <= node2.variable { node1.variable } else {node2.variable},key,cache)
+                let variable = self.top_variable(&[f,g,h]);
+                let cofactors = |s:&Self,index:NodeIndex<A,M>| if index.is_sink() || s.node(index.address).variable!=variable { (index,index) } else {
+                    let node = s.node_incorporating_multiplicity(index);
+                    let canonical = |i:NodeIndex<A,M>| if i.is_false() { NodeIndex::FALSE } else { i }; // multiplying FALSE is not canonical.
+                    (canonical(node.lo),canonical(node.hi))
+                };
+                let (f0,f1) = cofactors(self,f);
+                let (g0,g1) = cofactors(self,g);
+                let (h0,h1) = cofactors(self,h);
+                let lo = self.ite_bdd(f0,g0,h0,cache);
+                let hi = self.ite_bdd(f1,g1,h1,cache);
+                self.create_node_bdd(lo,hi,variable,key,cache)
             }
         }
     }
 
 
+    /// If the result of op applied to index1 and index2 does not need recursion, get it.
+    /// This is when both are sinks, or the result is a constant or (if multiplicities are irrelevant) one of the arguments.
+    /// For a ZDD, TRUE is not a constant function, so only FALSE is treated as a constant argument.
+    fn apply_trivial(op:BooleanOperator, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, is_zdd:bool) -> Option<NodeIndex<A,M>> {
+        let constant = |v:bool| if v { NodeIndex::TRUE } else { NodeIndex::FALSE };
+        let is_constant = |index:NodeIndex<A,M>| if is_zdd { index.is_false() } else { index.is_sink() };
+        // the result as a function of the non-constant argument, as (result when it is false,result when it is true).
+        let as_function_of_other = if index1.is_sink() && index2.is_sink() { return Some(constant(op.evaluate(index1.is_true(),index2.is_true()))) }
+            else if is_constant(index1) { Some(((op.evaluate(index1.is_true(),false),op.evaluate(index1.is_true(),true)),index2)) }
+            else if is_constant(index2) { Some(((op.evaluate(false,index2.is_true()),op.evaluate(true,index2.is_true())),index1)) }
+            else if index1.address==index2.address { Some(((op.evaluate(false,false),op.evaluate(true,true)),index1)) }
+            else { None };
+        match as_function_of_other {
+            Some(((false,false),_)) => Some(NodeIndex::FALSE),
+            Some(((true,true),_)) if !is_zdd => Some(NodeIndex::TRUE),
+            Some(((false,true),other)) if M::MULTIPLICITIES_IRRELEVANT => Some(other),
+            _ => None,
+        }
+    }
+
+    /// Make a node representing op applied to index1 and index2, interpreted as BDDs. This gives all 16 binary operators with one function and cache.
+    ///
+    /// This is a purely boolean operation: multiplicities are ignored other than whether they are zero, and the result has unit multiplicities.
+    /// Use mul_bdd, sum_bdd or xor_bdd for and, or or xor taking multiplicities into account.
+    fn apply_bdd<C:Cache<(BooleanOperator, A, A), NodeIndex<A,M>>>(&mut self, op:BooleanOperator, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if let Some(res) = Self::apply_trivial(op,index1,index2,false) { return res; }
+        // make the key canonical for symmetric operators.
+        let key = if index1.address<index2.address && op==op.swap_arguments() { (op,index2.address,index1.address) } else { (op,index1.address,index2.address) };
+        if let Some(res) = cache.get(&key) { res }
+        else {
+            let variable = self.top_variable(&[index1,index2]);
+            let cofactors = |s:&Self,index:NodeIndex<A,M>| if index.is_sink() || s.node(index.address).variable!=variable { (index,index) } else {
+                let node = s.node(index.address);
+                (node.lo,node.hi)
+            };
+            let (f0,f1) = cofactors(self,index1);
+            let (g0,g1) = cofactors(self,index2);
+            let lo = self.apply_bdd(op,f0,g0,cache);
+            let hi = self.apply_bdd(op,f1,g1,cache);
+            self.create_node_bdd(lo,hi,variable,key,cache)
+        }
+    }
+
     /// compute index as a ZDD anded with NodeIndex::TRUE, which means take all lo branches on index1.
     fn and_zdd_true(&mut self, index: NodeIndex<A,M>) -> NodeIndex<A,M> {
         let mut index = index;
@@ -334,8 +1101,8 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
 
     /// Create a node for a zdd (or find existing) for variable variable with lo and hi choices, and store it in the provided cache.
     /// Uniqueifies - sees if the hi is false, in which case just produce lo, and looks for existing nodes.
-    fn create_node_zdd<K:Eq+Hash>(&mut self, lo: NodeIndex<A,M>, hi: NodeIndex<A,M>, variable:VariableIndex, key:K, cache:&mut HashMap<K, NodeIndex<A,M>>) -> NodeIndex<A,M> {
-        let res = if hi.is_false() { lo } else {
+    fn create_node_zdd<K,C:Cache<K,NodeIndex<A,M>>>(&mut self, lo: NodeIndex<A,M>, hi: NodeIndex<A,M>, variable:VariableIndex, key:K, cache:&mut C) -> NodeIndex<A,M> {
+        let res = if hi.is_false() { if lo.is_false() { NodeIndex::FALSE } else { lo } } else {
             self.add_node_if_not_present(Node {variable,lo,hi})
         };
         cache.insert(key,res);
@@ -344,24 +1111,24 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     /// Make a node representing index1 and index2 (and in the logical sense, a.k.a. ∧ or &&)
     ///
     /// If multiplicities are involved, this is a Product operation. That is, the multiplicity of a value in the result is the product of the multiplicities of the value in the inputs.
-    fn mul_zdd(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>) -> NodeIndex<A,M> {
-        if index1.is_false() || index2.is_false() { NodeIndex::FALSE }
-        else if index1.is_true() { self.and_zdd_true(index2).multiply(index1.multiplicity) }
-        else if index2.is_true() { self.and_zdd_true(index1).multiply(index2.multiplicity) }
-        else if M::MULTIPLICITIES_IRRELEVANT && index1==index2 { index1.multiply(index2.multiplicity) } // a&a is not a in presence of multiplicities. Or even a multiple of a.
-        else {
-            let key = if index1.address < index2.address {(index1,index2)} else {(index2,index1)};
-            if let Some(&res) = cache.get(&key) { res }
+    fn mul_zdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.apply_iteratively::<_,_,false>(index1,index2,cache,|nodes,cache,index1,index2|{
+            if index1.is_false() || index2.is_false() { ApplyStep::Done(NodeIndex::FALSE) }
+            else if index1.is_true() { ApplyStep::Done(nodes.and_zdd_true(index2).multiply(index1.multiplicity)) }
+            else if index2.is_true() { ApplyStep::Done(nodes.and_zdd_true(index1).multiply(index2.multiplicity)) }
+            else if M::MULTIPLICITIES_IRRELEVANT && index1==index2 { ApplyStep::Done(index1.multiply(index2.multiplicity)) } // a&a is not a in presence of multiplicities. Or even a multiple of a.
             else {
-                let node1 = self.node_incorporating_multiplicity(index1);
-                let node2 = self.node_incorporating_multiplicity(index2);
-                let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1, NodeIndex::FALSE)};
-                let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2, NodeIndex::FALSE)};
-                let lo = self.mul_zdd(lo1,lo2,cache);
-                let hi = self.mul_zdd(hi1,hi2,cache);
-                self.create_node_zdd(lo,hi,if node1.variable <= node2.variable { node1.variable } else {node2.variable},key,cache)
+                let key = if index1.address < index2.address {(index1,index2)} else {(index2,index1)};
+                if let Some(res) = cache.get(&key) { ApplyStep::Done(res) }
+                else {
+                    let node1 = nodes.node_incorporating_multiplicity(index1);
+                    let node2 = nodes.node_incorporating_multiplicity(index2);
+                    let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1, NodeIndex::FALSE)};
+                    let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2, NodeIndex::FALSE)};
+                    ApplyStep::Split { key, variable: node1.variable.min(node2.variable), lo: (lo1,lo2), hi: (hi1,hi2) }
+                }
             }
-        }
+        })
     }
 
 
@@ -370,87 +1137,1576 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
     ///
     /// In particular, the sum_bdd(f,g)(x) has multiplicity equal to the sum of the multiplicity of f(x) and g(x).
     /// Make a node representing index1 and index2 (and in the logical sense, a.k.a. ∧ or &&)
-    fn sum_zdd(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut HashMap<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>) -> NodeIndex<A,M> {
-        if index1.address==index2.address { NodeIndex {address:index1.address,multiplicity:M::combine_or(index1.multiplicity, index2.multiplicity)} }
-        else if index1.is_false() { index2 }
-        else if index2.is_false() { index1 }
-        // if one of the two is true, then need to add true to both sides of the other to get multiplicities correct. The above line is just an optimization for that case.
+    fn sum_zdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.apply_iteratively::<_,_,false>(index1,index2,cache,|nodes,cache,index1,index2|{
+            if index1.is_false() && index2.is_false() { ApplyStep::Done(NodeIndex::FALSE) } // either may be FALSE with a meaningless multiplicity.
+            else if index1.is_false() { ApplyStep::Done(index2) }
+            else if index2.is_false() { ApplyStep::Done(index1) }
+            else if index1.address==index2.address {
+                let multiplicity = M::combine_or(index1.multiplicity, index2.multiplicity);
+                ApplyStep::Done(if multiplicity.is_zero() { NodeIndex::FALSE } else { NodeIndex {address:index1.address,multiplicity} })
+            }
+            // if one of the two is true, then need to add true to both sides of the other to get multiplicities correct. The above line is just an optimization for that case.
+            else {
+                let (index1,index2) = if (M::SYMMETRIC_OR && index1.address < index2.address) || index1.address.is_true() {(index2,index1)} else {(index1,index2)};
+                let key = (index1,index2);
+                if let Some(res) = cache.get(&key) { ApplyStep::Done(res) }
+                else {
+                    let node1 = nodes.node_incorporating_multiplicity(index1);
+                    let node2 = if index2.is_true() {
+                        Node {
+                        variable: node1.variable,
+                        lo: NodeIndex { address: A::TRUE, multiplicity: index2.multiplicity },
+                        hi: NodeIndex::FALSE
+                    }} else {nodes.node_incorporating_multiplicity(index2)};
+                    let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1, NodeIndex::FALSE)};
+                    let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2, NodeIndex::FALSE)};
+                    ApplyStep::Split { key, variable: node1.variable.min(node2.variable), lo: (lo1,lo2), hi: (hi1,hi2) }
+                }
+            }
+        })
+    }
+
+
+    /// Make a node representing index1 xor index2 (exclusive or, a.k.a. ⊕ or symmetric difference)
+    ///
+    /// If multiplicities are involved, a value in the result has the multiplicity it had in whichever input contained it.
+    fn xor_zdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.apply_iteratively::<_,_,false>(index1,index2,cache,|nodes,cache,index1,index2|{
+            if index1.address==index2.address { ApplyStep::Done(NodeIndex::FALSE) }
+            else if index1.is_false() { ApplyStep::Done(index2) }
+            else if index2.is_false() { ApplyStep::Done(index1) }
+            else {
+                // at most one can be TRUE, and it will be index1 as TRUE has the lowest address of any non-false node.
+                let key = if index1.address < index2.address {(index1,index2)} else {(index2,index1)};
+                let (index1,index2) = key;
+                if let Some(res) = cache.get(&key) { ApplyStep::Done(res) }
+                else {
+                    let node2 = nodes.node_incorporating_multiplicity(index2);
+                    let node1 = if index1.is_true() { Node { variable: node2.variable, lo: index1, hi: NodeIndex::FALSE } } else { nodes.node_incorporating_multiplicity(index1) };
+                    let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1, NodeIndex::FALSE)};
+                    let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2, NodeIndex::FALSE)};
+                    ApplyStep::Split { key, variable: node1.variable.min(node2.variable), lo: (lo1,lo2), hi: (hi1,hi2) }
+                }
+            }
+        })
+    }
+
+
+    /// Make a node representing op applied to index1 and index2, interpreted as ZDDs. This gives all 16 binary operators with one function and cache.
+    ///
+    /// Operators that are true when both arguments are false (such as NAND) are computed as the negation of the opposite operator,
+    /// using not_cache, as their result contains sets with variables not mentioned in either argument.
+    ///
+    /// This is a purely boolean operation: multiplicities are ignored other than whether they are zero, and the result has unit multiplicities.
+    /// Use mul_zdd, sum_zdd or xor_zdd for and, or or xor taking multiplicities into account.
+    fn apply_zdd<C:Cache<(BooleanOperator, A, A), NodeIndex<A,M>>,NC:Cache<(A, VariableIndex),A>>(&mut self, op:BooleanOperator, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, total_number_variables:u16, cache : &mut C, not_cache : &mut NC) -> NodeIndex<A,M> {
+        if op.evaluate(false,false) {
+            let negated = self.apply_zdd(op.negate(),index1,index2,total_number_variables,cache,not_cache);
+            return self.not_zdd(negated,VariableIndex(0),total_number_variables,not_cache);
+        }
+        if let Some(res) = Self::apply_trivial(op,index1,index2,true) { return res; }
+        let key = if index1.address<index2.address && op==op.swap_arguments() { (op,index2.address,index1.address) } else { (op,index1.address,index2.address) };
+        if let Some(res) = cache.get(&key) { res }
+        else {
+            let variable = self.top_variable(&[index1,index2]);
+            let cofactors = |s:&Self,index:NodeIndex<A,M>| if index.is_sink() || s.node(index.address).variable!=variable { (index,NodeIndex::FALSE) } else {
+                let node = s.node(index.address);
+                (node.lo,node.hi)
+            };
+            let (f0,f1) = cofactors(self,index1);
+            let (g0,g1) = cofactors(self,index2);
+            let lo = self.apply_zdd(op,f0,g0,total_number_variables,cache,not_cache);
+            let hi = self.apply_zdd(op,f1,g1,total_number_variables,cache,not_cache);
+            self.create_node_zdd(lo,hi,variable,key,cache)
+        }
+    }
+
+    /// Make a node representing if f then g else h, interpreted as ZDDs. This is (f ∩ g) ∪ (h minus f), but done in one pass.
+    ///
+    /// If multiplicities are involved, the multiplicity of f is ignored (other than whether it is zero), and
+    /// a value in the result has its multiplicity from g or h.
+    fn ite_zdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, h: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if f.is_false() { h }
+        else if g==h || (f.is_true() && g.is_sink() && h.is_sink()) { g } // Note that f TRUE is just the empty set, so other cases need recursion.
         else {
-            let (index1,index2) = if (M::SYMMETRIC_OR && index1.address < index2.address) || index1.address.is_true() {(index2,index1)} else {(index1,index2)};
-            let key = (index1,index2);
-            if let Some(&res) = cache.get(&key) { res }
+            let f = NodeIndex{address:f.address,multiplicity:M::ONE};
+            let key = (f,g,h);
+            if let Some(res) = cache.get(&key) { res }
             else {
-                let node1 = self.node_incorporating_multiplicity(index1);
-                let node2 = if index2.is_true() {
-                    Node {
-                    variable: node1.variable,
-                    lo: NodeIndex { address: A::TRUE, multiplicity: index2.multiplicity },
-                    hi: NodeIndex::FALSE
-                }} else {self.node_incorporating_multiplicity(index2)};
-                let (lo1,hi1) = if node1.variable <= node2.variable { (node1.lo,node1.hi)} else {(index1, NodeIndex::FALSE)};
-                let (lo2,hi2) = if node2.variable <= node1.variable { (node2.lo,node2.hi)} else {(index2, NodeIndex::FALSE)};
-                let lo = self.sum_zdd(lo1,lo2,cache);
-                let hi = self.sum_zdd(hi1,hi2,cache);
-                self.create_node_zdd(lo,hi,if node1.variable <= node2.variable { node1.variable } else {node2.variable},key,cache)
+                let variable = self.top_variable(&[f,g,h]);
+                let cofactors = |s:&Self,index:NodeIndex<A,M>| if index.is_sink() || s.node(index.address).variable!=variable { (index,NodeIndex::FALSE) } else {
+                    let node = s.node_incorporating_multiplicity(index);
+                    let canonical = |i:NodeIndex<A,M>| if i.is_false() { NodeIndex::FALSE } else { i }; // multiplying FALSE is not canonical.
+                    (canonical(node.lo),canonical(node.hi))
+                };
+                let (f0,f1) = cofactors(self,f);
+                let (g0,g1) = cofactors(self,g);
+                let (h0,h1) = cofactors(self,h);
+                let lo = self.ite_zdd(f0,g0,h0,cache);
+                let hi = self.ite_zdd(f1,g1,h1,cache);
+                self.create_node_zdd(lo,hi,variable,key,cache)
             }
         }
     }
 
 
+    /// The sets in index, interpreted as a ZDD, without and with variable, which must not be below the top variable of index.
+    /// Those with variable have it removed. Multiplicities are ignored.
+    fn family_cofactors(&self, index:NodeIndex<A,M>, variable:VariableIndex) -> (NodeIndex<A,M>,NodeIndex<A,M>) {
+        if index.is_sink() { return (index,NodeIndex::FALSE); }
+        let node = self.node(index.address);
+        if node.variable==variable { (node.lo,node.hi) } else { (index,NodeIndex::FALSE) }
+    }
 
-    /// Create generating functions for nodes 0 inclusive to length exclusive.
-    /// This is easy because of the topological sort.
-    /// Return an array such that res[node] = the variable used at the time and the generating function.
-    fn all_number_solutions<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self,length:usize,num_variables:u16) -> Vec<G> {
-        let mut res = Vec::new();
-        res.push(G::zero());
-        res.push(G::one());
-        for i in 2..length {
-            let node = self.node(i.try_into().map_err(|_|()).unwrap());
-            let next_variable = VariableIndex(node.variable.0+1);
-            //println!("Computing {} lo={} hi={} variable={}",i,node.lo,node.hi,node.variable);
-            let lo_g = res[node.lo.address.as_usize()].clone();
-            let lo_g = if M::MULTIPLICITIES_IRRELEVANT || node.lo.multiplicity.is_unity() { lo_g } else { lo_g.multiply(node.lo.multiplicity) };
-            let lo_level = if node.lo.is_sink() { VariableIndex(num_variables) } else { self.node(node.lo.address).variable };
-            //println!("   lo_g={:?}, lo_level={}",lo_g,lo_level);
-            let lo = if BDD {lo_g.deal_with_variable_range_being_indeterminate(next_variable,lo_level)} else {lo_g};
-            let lo = lo.variable_not_set(node.variable);
-            let hi_g = res[node.hi.address.as_usize()].clone();
-            let hi_g = if M::MULTIPLICITIES_IRRELEVANT || node.hi.multiplicity.is_unity() { hi_g } else { hi_g.multiply(node.hi.multiplicity) };
-            let hi_level = if node.hi.is_sink() { VariableIndex(num_variables) } else { self.node(node.hi.address).variable };
-            //println!("   hi_g={:?}, hi_level={}",hi_g,hi_level);
-            let hi = if BDD {hi_g.deal_with_variable_range_being_indeterminate(next_variable,hi_level)} else {hi_g};
-            let hi = hi.variable_set(node.variable);
-            //println!(" GF lo = {:?},   GF hi = {:?}",lo,hi);
-            res.push(lo.add(hi));
+    /// Make a node representing the union of the families of sets index1 and index2, interpreted as ZDDs.
+    ///
+    /// This and the other family algebra operations are set operations: multiplicities are ignored other than whether they are zero, and the result has unit multiplicities.
+    /// Use sum_zdd for the multiset sum.
+    fn union_zdd<C:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if index1.is_sink() && index2.is_sink() { return if index1.is_true() || index2.is_true() { NodeIndex::TRUE } else { NodeIndex::FALSE }; }
+        if M::MULTIPLICITIES_IRRELEVANT {
+            if index1.is_false() || index1.address==index2.address { return index2; }
+            if index2.is_false() { return index1; }
         }
-        //println!("{:?}",res);
-        res
+        let key = if index1.address<index2.address { (index1.address,index2.address) } else { (index2.address,index1.address) };
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo = self.union_zdd(f0,g0,cache);
+        let hi = self.union_zdd(f1,g1,cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
     }
 
-    fn number_solutions<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16) -> G {
-        let work = self.all_number_solutions::<G,BDD>(index.address.as_usize()+1,num_variables);
-        let found = work[index.address.as_usize()].clone();
-        let before_multiplicity = if BDD {
-            let level = if index.is_sink() { VariableIndex(num_variables) } else { self.node(index.address).variable };
-            found.deal_with_variable_range_being_indeterminate(VariableIndex(0),level)
-        } else { found };
-        before_multiplicity.multiply(index.multiplicity)
+    /// Make a node representing the sets in both index1 and index2, interpreted as ZDDs. See [XDDBase::union_zdd] about multiplicities.
+    fn intersection_zdd<C:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if index1.is_false() || index2.is_false() { return NodeIndex::FALSE; }
+        if index1.is_true() && index2.is_true() { return NodeIndex::TRUE; }
+        if M::MULTIPLICITIES_IRRELEVANT && index1.address==index2.address { return index1; }
+        let key = if index1.address<index2.address { (index1.address,index2.address) } else { (index2.address,index1.address) };
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo = self.intersection_zdd(f0,g0,cache);
+        let hi = self.intersection_zdd(f1,g1,cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
     }
 
-    fn number_solutions_bdd<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, num_variables:u16) -> G { self.number_solutions::<G,true>(index, num_variables) }
-    fn number_solutions_zdd<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, num_variables:u16) -> G { self.number_solutions::<G,false>(index, num_variables) }
-
-    /// Do garbage collection. Provide the items one wants to keep, and get rid of anything not in the transitive dependencies of keep.
-    /// Returns a renamer from old nodes to new nodes.
-    fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A>;
+    /// Make a node representing the sets in index1 but not index2, interpreted as ZDDs. See [XDDBase::union_zdd] about multiplicities.
+    fn difference_zdd<C:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if index1.is_false() || index1.address==index2.address { return NodeIndex::FALSE; }
+        if index1.is_sink() && index2.is_sink() { return NodeIndex::TRUE; } // must be TRUE minus FALSE.
+        if M::MULTIPLICITIES_IRRELEVANT && index2.is_false() { return index1; }
+        let key = (index1.address,index2.address);
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo = self.difference_zdd(f0,g0,cache);
+        let hi = self.difference_zdd(f1,g1,cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
+    }
 
-    fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
-        //let namer = |i:VariableIndex| i.to_string();
-        fn munge_label(s:&str) -> String { // see if html label.
-            if s.starts_with('<') && s.ends_with('>') {s.to_string()} else { format!("\"{}\"",s) }
+    /// Make a node representing the join of index1 and index2, interpreted as ZDDs, which is every union of a set from index1 with a set from index2.
+    /// See [XDDBase::union_zdd] about multiplicities, and for union_cache.
+    fn join_zdd<C:Cache<(A,A),NodeIndex<A,M>>,UC:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C, union_cache : &mut UC) -> NodeIndex<A,M> {
+        if index1.is_false() || index2.is_false() { return NodeIndex::FALSE; }
+        if index1.is_true() && index2.is_true() { return NodeIndex::TRUE; }
+        if M::MULTIPLICITIES_IRRELEVANT {
+            if index1.is_true() { return index2; }
+            if index2.is_true() { return index1; }
         }
-        writeln!(writer,"digraph {} {{",name)?;
+        let key = if index1.address<index2.address { (index1.address,index2.address) } else { (index2.address,index1.address) };
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo = self.join_zdd(f0,g0,cache,union_cache);
+        let hi10 = self.join_zdd(f1,g0,cache,union_cache);
+        let hi11 = self.join_zdd(f1,g1,cache,union_cache);
+        let hi01 = self.join_zdd(f0,g1,cache,union_cache);
+        let hi = self.union_zdd(hi10,hi11,union_cache);
+        let hi = self.union_zdd(hi,hi01,union_cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
+    }
+
+    /// Make a node representing the meet of index1 and index2, interpreted as ZDDs, which is every intersection of a set from index1 with a set from index2.
+    /// See [XDDBase::union_zdd] about multiplicities, and for union_cache.
+    fn meet_zdd<C:Cache<(A,A),NodeIndex<A,M>>,UC:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C, union_cache : &mut UC) -> NodeIndex<A,M> {
+        if index1.is_false() || index2.is_false() { return NodeIndex::FALSE; }
+        if index1.is_true() || index2.is_true() { return NodeIndex::TRUE; } // the other is not empty.
+        let key = if index1.address<index2.address { (index1.address,index2.address) } else { (index2.address,index1.address) };
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo00 = self.meet_zdd(f0,g0,cache,union_cache);
+        let lo01 = self.meet_zdd(f0,g1,cache,union_cache);
+        let lo10 = self.meet_zdd(f1,g0,cache,union_cache);
+        let hi = self.meet_zdd(f1,g1,cache,union_cache);
+        let lo = self.union_zdd(lo00,lo01,union_cache);
+        let lo = self.union_zdd(lo,lo10,union_cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
+    }
+
+    /// Make a node representing the quotient index1/index2, interpreted as ZDDs. This is the sets α such that for every β in index2,
+    /// α and β are disjoint and α ∪ β is in index1. It is the largest family q such that the join of index2 and q is in index1, when
+    /// index2 is not empty. Dividing by the empty family gives the empty family.
+    /// See [XDDBase::union_zdd] about multiplicities. intersection_cache is as for [XDDBase::intersection_zdd].
+    fn quotient_zdd<C:Cache<(A,A),NodeIndex<A,M>>,IC:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C, intersection_cache : &mut IC) -> NodeIndex<A,M> {
+        if index1.is_false() || index2.is_false() { return NodeIndex::FALSE; }
+        if index1.address==index2.address { return NodeIndex::TRUE; }
+        if index1.is_true() { return NodeIndex::FALSE; } // index2 has a non-empty set.
+        if M::MULTIPLICITIES_IRRELEVANT && index2.is_true() { return index1; }
+        let key = (index1.address,index2.address);
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        if g1.is_false() { // variable is in no set of index2, so may be in α.
+            let lo = self.quotient_zdd(f0,g0,cache,intersection_cache);
+            let hi = self.quotient_zdd(f1,g0,cache,intersection_cache);
+            self.create_node_zdd(lo,hi,variable,key,cache)
+        } else { // variable is in some set of index2, so may not be in α.
+            let mut res = self.quotient_zdd(f1,g1,cache,intersection_cache);
+            if !(g0.is_false() || res.is_false()) {
+                let without = self.quotient_zdd(f0,g0,cache,intersection_cache);
+                res = self.intersection_zdd(res,without,intersection_cache);
+            }
+            cache.insert(key,res);
+            res
+        }
+    }
+
+    /// Make a node representing the sets in index1, interpreted as a ZDD, that are a subset of some set in index2.
+    /// See [XDDBase::union_zdd] about multiplicities, and for union_cache.
+    fn subsets_zdd<C:Cache<(A,A),NodeIndex<A,M>>,UC:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C, union_cache : &mut UC) -> NodeIndex<A,M> {
+        if index1.is_false() || index2.is_false() { return NodeIndex::FALSE; }
+        if index1.is_true() { return NodeIndex::TRUE; } // the empty set is a subset of the sets in index2.
+        if M::MULTIPLICITIES_IRRELEVANT && index1.address==index2.address { return index1; }
+        let key = (index1.address,index2.address);
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo0 = self.subsets_zdd(f0,g0,cache,union_cache);
+        let lo1 = self.subsets_zdd(f0,g1,cache,union_cache);
+        let lo = self.union_zdd(lo0,lo1,union_cache);
+        let hi = self.subsets_zdd(f1,g1,cache,union_cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
+    }
+
+    /// Make a node representing the sets in index1, interpreted as a ZDD, that are a superset of some set in index2.
+    /// See [XDDBase::union_zdd] about multiplicities, and for union_cache.
+    fn supersets_zdd<C:Cache<(A,A),NodeIndex<A,M>>,UC:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C, union_cache : &mut UC) -> NodeIndex<A,M> {
+        if index1.is_false() || index2.is_false() { return NodeIndex::FALSE; }
+        if index1.is_true() && index2.is_true() { return NodeIndex::TRUE; }
+        if M::MULTIPLICITIES_IRRELEVANT && (index1.address==index2.address || index2.is_true()) { return index1; }
+        let key = (index1.address,index2.address);
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo = self.supersets_zdd(f0,g0,cache,union_cache);
+        let hi0 = self.supersets_zdd(f1,g0,cache,union_cache);
+        let hi1 = self.supersets_zdd(f1,g1,cache,union_cache);
+        let hi = self.union_zdd(hi0,hi1,union_cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
+    }
+
+    /// Make a node representing the sets in index1, interpreted as a ZDD, that are not a subset of any set in index2.
+    /// See [XDDBase::union_zdd] about multiplicities.
+    fn nonsubsets_zdd<C:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if index1.is_false() || index1.address==index2.address { return NodeIndex::FALSE; }
+        if index2.is_false() {
+            if index1.is_true() { return NodeIndex::TRUE; }
+            if M::MULTIPLICITIES_IRRELEVANT { return index1; }
+        }
+        if index1.is_true() { return NodeIndex::FALSE; } // the empty set is a subset of the sets in index2.
+        let key = (index1.address,index2.address);
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo = self.nonsubsets_zdd(f0,g0,cache);
+        let lo = self.nonsubsets_zdd(lo,g1,cache);
+        let hi = self.nonsubsets_zdd(f1,g1,cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
+    }
+
+    /// Make a node representing the sets in index1, interpreted as a ZDD, that are not a superset of any set in index2.
+    /// See [XDDBase::union_zdd] about multiplicities.
+    fn nonsupersets_zdd<C:Cache<(A,A),NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if index1.is_false() || index2.is_true() || index1.address==index2.address { return NodeIndex::FALSE; } // every set is a superset of the empty set.
+        if index2.is_false() {
+            if index1.is_true() { return NodeIndex::TRUE; }
+            if M::MULTIPLICITIES_IRRELEVANT { return index1; }
+        }
+        let key = (index1.address,index2.address);
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let (f0,f1) = self.family_cofactors(index1,variable);
+        let (g0,g1) = self.family_cofactors(index2,variable);
+        let lo = self.nonsupersets_zdd(f0,g0,cache);
+        let hi = self.nonsupersets_zdd(f1,g0,cache);
+        let hi = self.nonsupersets_zdd(hi,g1,cache);
+        self.create_node_zdd(lo,hi,variable,key,cache)
+    }
+
+    /// Make a node representing the sets in f, interpreted as a ZDD, that do not contain any other set in f.
+    /// See [XDDBase::union_zdd] about multiplicities. nonsupersets_cache is as for [XDDBase::nonsupersets_zdd].
+    fn minimal_zdd<C:Cache<A,NodeIndex<A,M>>,NC:Cache<(A,A),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, cache : &mut C, nonsupersets_cache : &mut NC) -> NodeIndex<A,M> {
+        if f.is_sink() { return if f.is_true() { NodeIndex::TRUE } else { NodeIndex::FALSE }; }
+        if let Some(res) = cache.get(&f.address) { return res; }
+        let node = self.node(f.address);
+        let lo = self.minimal_zdd(node.lo,cache,nonsupersets_cache);
+        let hi = self.minimal_zdd(node.hi,cache,nonsupersets_cache);
+        let hi = self.nonsupersets_zdd(hi,lo,nonsupersets_cache); // a set with the variable may contain one without it.
+        self.create_node_zdd(lo,hi,node.variable,f.address,cache)
+    }
+
+    /// Make a node representing the sets in f, interpreted as a ZDD, that are not contained in any other set in f.
+    /// See [XDDBase::union_zdd] about multiplicities. nonsubsets_cache is as for [XDDBase::nonsubsets_zdd].
+    fn maximal_zdd<C:Cache<A,NodeIndex<A,M>>,NC:Cache<(A,A),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, cache : &mut C, nonsubsets_cache : &mut NC) -> NodeIndex<A,M> {
+        if f.is_sink() { return if f.is_true() { NodeIndex::TRUE } else { NodeIndex::FALSE }; }
+        if let Some(res) = cache.get(&f.address) { return res; }
+        let node = self.node(f.address);
+        let lo = self.maximal_zdd(node.lo,cache,nonsubsets_cache);
+        let hi = self.maximal_zdd(node.hi,cache,nonsubsets_cache);
+        let lo = self.nonsubsets_zdd(lo,hi,nonsubsets_cache); // a set without the variable may be contained in one with it.
+        self.create_node_zdd(lo,hi,node.variable,f.address,cache)
+    }
+
+    /// Make a node representing the sets in f, interpreted as a ZDD, that do not contain variable. Minato calls this subset0.
+    /// Multiplicities are kept.
+    fn offset_zdd<C:Cache<(NodeIndex<A,M>,VariableIndex),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, variable:VariableIndex, cache : &mut C) -> NodeIndex<A,M> {
+        if f.is_sink() { return f; }
+        let node = self.node_incorporating_multiplicity(f);
+        if node.variable>variable { f }
+        else if node.variable==variable { node.lo }
+        else {
+            let key = (f,variable);
+            if let Some(res) = cache.get(&key) { res }
+            else {
+                let lo = self.offset_zdd(node.lo,variable,cache);
+                let hi = self.offset_zdd(node.hi,variable,cache);
+                self.create_node_zdd(lo,hi,node.variable,key,cache)
+            }
+        }
+    }
+
+    /// Make a node representing the sets in f, interpreted as a ZDD, that contain variable, with variable removed from them. Minato calls this subset1.
+    /// Multiplicities are kept.
+    fn onset_zdd<C:Cache<(NodeIndex<A,M>,VariableIndex),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, variable:VariableIndex, cache : &mut C) -> NodeIndex<A,M> {
+        if f.is_sink() { return NodeIndex::FALSE; }
+        let node = self.node_incorporating_multiplicity(f);
+        if node.variable>variable { NodeIndex::FALSE }
+        else if node.variable==variable { node.hi }
+        else {
+            let key = (f,variable);
+            if let Some(res) = cache.get(&key) { res }
+            else {
+                let lo = self.onset_zdd(node.lo,variable,cache);
+                let hi = self.onset_zdd(node.hi,variable,cache);
+                self.create_node_zdd(lo,hi,node.variable,key,cache)
+            }
+        }
+    }
+
+    /// Make a node representing the sets in f, interpreted as a ZDD, with variable added to those without it and removed from those with it.
+    /// Multiplicities are kept.
+    fn change_zdd<C:Cache<(NodeIndex<A,M>,VariableIndex),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, variable:VariableIndex, cache : &mut C) -> NodeIndex<A,M> {
+        if f.is_false() { return f; }
+        let node = if f.is_sink() { None } else { Some(self.node_incorporating_multiplicity(f)) };
+        match node {
+            Some(node) if node.variable<variable => {
+                let key = (f,variable);
+                if let Some(res) = cache.get(&key) { res }
+                else {
+                    let lo = self.change_zdd(node.lo,variable,cache);
+                    let hi = self.change_zdd(node.hi,variable,cache);
+                    self.create_node_zdd(lo,hi,node.variable,key,cache)
+                }
+            }
+            Some(node) if node.variable==variable => if node.lo.is_false() { node.hi } else { self.add_node_if_not_present(Node{variable,lo:node.hi,hi:node.lo}) },
+            _ => self.add_node_if_not_present(Node{variable,lo:NodeIndex::FALSE,hi:f}), // no set contains variable.
+        }
+    }
+
+    /// Compute the cofactor of f, interpreted as a BDD, with the given variable fixed to value.
+    /// The result does not depend on variable.
+    fn restrict_bdd<C:Cache<(NodeIndex<A,M>,VariableIndex,bool),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, variable:VariableIndex, value:bool, cache : &mut C) -> NodeIndex<A,M> {
+        if f.is_sink() { return f; }
+        let node = self.node_incorporating_multiplicity(f);
+        if node.variable>variable { f }
+        else if node.variable==variable { if value { node.hi } else { node.lo } }
+        else {
+            let key = (f,variable,value);
+            if let Some(res) = cache.get(&key) { res }
+            else {
+                let lo = self.restrict_bdd(node.lo,variable,value,cache);
+                let hi = self.restrict_bdd(node.hi,variable,value,cache);
+                self.create_node_bdd(lo,hi,node.variable,key,cache)
+            }
+        }
+    }
+
+    /// The top variable of f and c, and their lo and hi children for it, in the order f_lo, f_hi, c_lo, c_hi, where a BDD not testing the variable has itself as both children.
+    fn cofactors_bdd(&self, f: NodeIndex<A,M>, c: NodeIndex<A,M>) -> (VariableIndex,[NodeIndex<A,M>;4]) {
+        let node_f = if f.is_sink() { None } else { Some(self.node_incorporating_multiplicity(f)) };
+        let node_c = if c.is_sink() { None } else { Some(self.node(c.address)) }; // only where c is FALSE matters, so not its multiplicities.
+        let variable = node_f.iter().chain(node_c.iter()).map(|node|node.variable).min().unwrap();
+        let children = |index:NodeIndex<A,M>,node:Option<Node<A,M>>| match node { Some(node) if node.variable==variable => (node.lo,node.hi), _ => (index,index) };
+        let ((f_lo,f_hi),(c_lo,c_hi)) = (children(f,node_f),children(c,node_c));
+        (variable,[f_lo,f_hi,c_lo,c_hi])
+    }
+
+    /// Compute the generalized cofactor of f, interpreted as a BDD, with respect to the care set c (Coudert and Madre's constrain).
+    /// The result agrees with f, including multiplicities, wherever c is true, and is often smaller where it is not;
+    /// an assignment where c is false gets the value of f at a nearby assignment where c is true. The multiplicities of c are ignored.
+    /// Gives FALSE if c is FALSE.
+    fn constrain_bdd<C:Cache<(NodeIndex<A,M>,NodeIndex<A,M>),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, c: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if c.is_false() { return NodeIndex::FALSE; }
+        if c.is_true() || f.is_sink() { return f; }
+        let key = (f,c);
+        if let Some(res) = cache.get(&key) { return res; }
+        let (variable,[f_lo,f_hi,c_lo,c_hi]) = self.cofactors_bdd(f,c);
+        if c_lo.is_false() {
+            let res = self.constrain_bdd(f_hi,c_hi,cache);
+            cache.insert(key,res);
+            res
+        } else if c_hi.is_false() {
+            let res = self.constrain_bdd(f_lo,c_lo,cache);
+            cache.insert(key,res);
+            res
+        } else {
+            let lo = self.constrain_bdd(f_lo,c_lo,cache);
+            let hi = self.constrain_bdd(f_hi,c_hi,cache);
+            self.create_node_bdd(lo,hi,variable,key,cache)
+        }
+    }
+
+    /// Like [XDDBase::constrain_bdd], but never introduces a variable that f does not depend on, by replacing the care set
+    /// with the or of its children when it tests a variable f skips (Coudert and Madre's restrict). So the result is usually smaller still,
+    /// and never depends on more variables than f. or_cache is as for [XDDBase::sum_bdd].
+    fn simplify_bdd<C1:Cache<(NodeIndex<A,M>,NodeIndex<A,M>),NodeIndex<A,M>>,C2:Cache<(NodeIndex<A,M>,NodeIndex<A,M>),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, c: NodeIndex<A,M>, cache : &mut C1, or_cache : &mut C2) -> NodeIndex<A,M> {
+        if c.is_false() { return NodeIndex::FALSE; }
+        if c.is_true() || f.is_sink() { return f; }
+        let key = (f,c);
+        if let Some(res) = cache.get(&key) { return res; }
+        let (variable,[f_lo,f_hi,c_lo,c_hi]) = self.cofactors_bdd(f,c);
+        let res = if f_lo==f && f_hi==f { // f does not test variable, so nor should the result.
+            let either = self.sum_bdd(c_lo,c_hi,or_cache);
+            self.simplify_bdd(f,either,cache,or_cache)
+        } else if c_lo.is_false() { self.simplify_bdd(f_hi,c_hi,cache,or_cache) }
+        else if c_hi.is_false() { self.simplify_bdd(f_lo,c_lo,cache,or_cache) }
+        else {
+            let lo = self.simplify_bdd(f_lo,c_lo,cache,or_cache);
+            let hi = self.simplify_bdd(f_hi,c_hi,cache,or_cache);
+            return self.create_node_bdd(lo,hi,variable,key,cache);
+        };
+        cache.insert(key,res);
+        res
+    }
+
+    /// Compute the cofactor of f, interpreted as a ZDD, with the given variable fixed to value.
+    /// As for [XDDBase::restrict_bdd], the result does not depend on variable, so it has explicit nodes for it.
+    fn restrict_zdd<C:Cache<(NodeIndex<A,M>,VariableIndex,bool),NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, variable:VariableIndex, value:bool, cache : &mut C) -> NodeIndex<A,M> {
+        if f.is_false() { return f; }
+        let key = (f,variable,value);
+        if let Some(res) = cache.get(&key) { return res; }
+        let node = if f.is_sink() { None } else { Some(self.node_incorporating_multiplicity(f)) };
+        let res = match node {
+            Some(node) if node.variable<variable => {
+                let lo = self.restrict_zdd(node.lo,variable,value,cache);
+                let hi = self.restrict_zdd(node.hi,variable,value,cache);
+                if hi.is_false() { lo } else { self.add_node_if_not_present(Node{variable:node.variable,lo,hi}) }
+            }
+            Some(node) if node.variable==variable => {
+                let child = if value { node.hi } else { node.lo };
+                if child.is_false() { child } else { self.add_node_if_not_present(Node{variable,lo:child,hi:child}) }
+            }
+            _ => if value { NodeIndex::FALSE } else { self.add_node_if_not_present(Node{variable,lo:f,hi:f}) } // variable skipped, so it was false.
+        };
+        cache.insert(key,res);
+        res
+    }
+
+    /// Compute the cofactor of f with each of the variables in literals fixed to the given value, which must be sorted by variable
+    /// with no variable repeated. This is the same as a sequence of [XDDBase::restrict_bdd] or [XDDBase::restrict_zdd], but in a single
+    /// pass over the nodes reachable from f, which does not touch nodes below the last variable fixed.
+    fn restrict_cube<const BDD:bool>(&mut self, f: NodeIndex<A,M>, literals:&[(VariableIndex,bool)], num_variables:u16) -> NodeIndex<A,M> {
+        let Some(&(last_fixed,_)) = literals.last() else { return f; };
+        let level = |nodes:&Self,index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { nodes.node(index.address).variable.0 };
+        // In a ZDD the result does not depend on the fixed variables, so needs a node for each of them with both children the same.
+        let free = |nodes:&mut Self,variable:VariableIndex,index:NodeIndex<A,M>| if index.is_false() { index } else { nodes.add_node_if_not_present(Node{variable,lo:index,hi:index}) };
+        let mut done : HashMap<A,NodeIndex<A,M>> = HashMap::new();
+        // The result for index, accounting for variables from from_level that are skipped on the way to it.
+        let edge = |nodes:&mut Self,done:&HashMap<A,NodeIndex<A,M>>,index:NodeIndex<A,M>,from_level:u16| {
+            let mut res = done.get(&index.address).map(|r|if r.is_false() { NodeIndex::FALSE } else { r.multiply(index.multiplicity) }).unwrap_or(index);
+            if !BDD { // a skipped variable is false, so fixing it to true gives FALSE.
+                let to_level = level(nodes,index);
+                let skipped = &literals[literals.partition_point(|(v,_)|v.0<from_level)..literals.partition_point(|(v,_)|v.0<to_level)];
+                for &(variable,value) in skipped.iter().rev() {
+                    res = if value { NodeIndex::FALSE } else { free(nodes,variable,res) };
+                }
+            }
+            res
+        };
+        for address in self.post_order(f) {
+            let node = self.node(address);
+            if node.variable>last_fixed { continue; } // unchanged, as are its descendants.
+            let lo = edge(self,&done,node.lo,node.variable.0+1);
+            let hi = edge(self,&done,node.hi,node.variable.0+1);
+            let res = match literals.binary_search_by_key(&node.variable,|(v,_)|*v) {
+                Ok(i) => {
+                    let child = if literals[i].1 { hi } else { lo };
+                    if BDD { child } else { free(self,node.variable,child) }
+                }
+                Err(_) if BDD && lo==hi => lo,
+                Err(_) if hi.is_false() && (!BDD || lo.is_false()) => if BDD { NodeIndex::FALSE } else { lo },
+                Err(_) => self.add_node_if_not_present(Node{variable:node.variable,lo,hi}),
+            };
+            done.insert(address,res);
+        }
+        edge(self,&done,f,0)
+    }
+
+
+    /// Substitute functions for variables in f, interpreted as a BDD. All substitutions happen simultaneously, so
+    /// a substituted function may mention a variable that is itself being substituted.
+    ///
+    /// substitutions is a list of (variable, function to put in its place). ite_cache is as for [XDDBase::ite_bdd].
+    fn vector_compose_bdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, substitutions:&[(VariableIndex,NodeIndex<A,M>)], ite_cache : &mut C) -> NodeIndex<A,M> {
+        let replacements : HashMap<VariableIndex,NodeIndex<A,M>> = substitutions.iter().cloned().collect();
+        let last_substituted = substitutions.iter().map(|(v,_)|*v).max();
+        let mut done : HashMap<NodeIndex<A,M>,NodeIndex<A,M>> = HashMap::new();
+        // Work through the nodes children first so there is no recursion other than in ite.
+        for address in self.post_order(f) {
+            let node = self.node(address);
+            if Some(node.variable)>last_substituted { continue; } // unchanged, as are its descendants.
+            let child = |index:NodeIndex<A,M>| done.get(&NodeIndex{address:index.address,multiplicity:M::ONE}).map(|r|r.multiply(index.multiplicity)).unwrap_or(index);
+            let lo = child(node.lo);
+            let hi = child(node.hi);
+            let condition = match replacements.get(&node.variable) { Some(&g) => g, None => self.single_variable(node.variable) };
+            let res = self.ite_bdd(condition,hi,lo,ite_cache);
+            done.insert(NodeIndex{address,multiplicity:M::ONE},res);
+        }
+        done.get(&NodeIndex{address:f.address,multiplicity:M::ONE}).map(|r|r.multiply(f.multiplicity)).unwrap_or(f)
+    }
+
+    /// Substitute functions for variables in f, interpreted as a ZDD. See [XDDBase::vector_compose_bdd].
+    fn vector_compose_zdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, substitutions:&[(VariableIndex,NodeIndex<A,M>)], total_num_variables:u16, ite_cache : &mut C) -> NodeIndex<A,M> {
+        let replacements : HashMap<VariableIndex,NodeIndex<A,M>> = substitutions.iter().cloned().collect();
+        let Some(last_substituted) = substitutions.iter().map(|(v,_)|v.0).max() else { return f; };
+        // In a ZDD a skipped variable is false, and that changes to the substituted function being false, so every
+        // level down to the last substituted variable has to be visited. work[(node,level)] is the result for the
+        // sub-diagram starting at node when about to consider variable level; it does not depend on variables above level
+        // other than through substituted functions.
+        let mut work : HashMap<(NodeIndex<A,M>,u16),NodeIndex<A,M>> = HashMap::new();
+        let mut stack = vec![(NodeIndex{address:f.address,multiplicity:M::ONE},0u16)];
+        while let Some(&(index,level)) = stack.last() {
+            if work.contains_key(&(index,level)) { stack.pop(); continue; }
+            if level>last_substituted || index.is_false() {
+                let res = if index.is_false() { index } else { self.zdd_variables_in_range_dont_matter(index,0..level) };
+                work.insert((index,level),res);
+                stack.pop();
+                continue;
+            }
+            let (lo,hi) = if !index.is_sink() && self.node(index.address).variable.0==level { let node = self.node(index.address); (node.lo,node.hi) } else { (index,NodeIndex::FALSE) };
+            let strip = |i:NodeIndex<A,M>| NodeIndex{address:i.address,multiplicity:M::ONE};
+            match (work.get(&(strip(lo),level+1)),work.get(&(strip(hi),level+1))) {
+                (Some(&lo_res),Some(&hi_res)) => {
+                    let variable = VariableIndex(level);
+                    let condition = match replacements.get(&variable) { Some(&g) => g, None => self.single_variable_zdd(variable,total_num_variables) };
+                    let res = self.ite_zdd(condition,hi_res.multiply(hi.multiplicity),lo_res.multiply(lo.multiplicity),ite_cache);
+                    work.insert((index,level),res);
+                    stack.pop();
+                }
+                (lo_res,hi_res) => {
+                    if lo_res.is_none() { stack.push((strip(lo),level+1)); }
+                    if hi_res.is_none() { stack.push((strip(hi),level+1)); }
+                }
+            }
+        }
+        work[&(NodeIndex{address:f.address,multiplicity:M::ONE},0)].multiply(f.multiplicity)
+    }
+
+    /// Compute there exists the quantified variables such that f and g, interpreted as BDDs, in one pass
+    /// without making the "and" of f and g. The quantified variables must be sorted, smallest to highest.
+    ///
+    /// With multiplicities "and" multiplies and "or" adds, so the multiplicity of an element of the result is the
+    /// sum over values of the quantified variables of the product of the multiplicities in f and g.
+    /// The sum uses or_cache; results that depend on the quantified variables are only remembered during this call.
+    fn relational_product_bdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, quantified:&[VariableIndex], or_cache : &mut C) -> NodeIndex<A,M> {
+        self.relational_product::<C,true>(f,g,quantified,or_cache)
+    }
+
+    /// Compute there exists the quantified variables such that f and g, interpreted as ZDDs. See [XDDBase::relational_product_bdd].
+    fn relational_product_zdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, quantified:&[VariableIndex], or_cache : &mut C) -> NodeIndex<A,M> {
+        self.relational_product::<C,false>(f,g,quantified,or_cache)
+    }
+
+    /// The work for [XDDBase::relational_product_bdd] and [XDDBase::relational_product_zdd].
+    ///
+    /// A quantified variable that neither f nor g mentions still matters: in a ZDD it is false, which changes to
+    /// either value once quantified, and with multiplicities both of its values are summed. So every quantified
+    /// variable is visited (other than for a BDD without multiplicities), and the state is the pair of nodes along with
+    /// the number of quantified variables passed.
+    fn relational_product<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>,const BDD:bool>(&mut self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, quantified:&[VariableIndex], or_cache : &mut C) -> NodeIndex<A,M> {
+        type Memo<A,M> = HashMap<(NodeIndex<A,M>,NodeIndex<A,M>,usize),NodeIndex<A,M>>;
+        #[allow(clippy::too_many_arguments)]
+        fn product<A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>+?Sized,C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>,const BDD:bool>(x:&mut X,f: NodeIndex<A,M>, g: NodeIndex<A,M>,quantified:&[VariableIndex],mut passed:usize,memo:&mut Memo<A,M>,or_cache:&mut C) -> NodeIndex<A,M> {
+            if f.is_false() || g.is_false() { return NodeIndex::FALSE; }
+            let multiplicity = M::multiply(f.multiplicity,g.multiplicity);
+            if BDD && M::MULTIPLICITIES_IRRELEVANT { // skipped variables can be ignored.
+                if f.is_sink() && g.is_sink() { return NodeIndex::TRUE; }
+                let top = x.top_variable(&[f,g]);
+                while quantified.get(passed).is_some_and(|&q|q<top) { passed+=1; }
+            }
+            let next_quantified = quantified.get(passed).cloned();
+            if f.is_true() && g.is_true() && next_quantified.is_none() { return NodeIndex::TRUE.multiply(multiplicity); }
+            let scale = |res:NodeIndex<A,M>| if res.is_false() { res } else { res.multiply(multiplicity) };
+            let f = NodeIndex{address:f.address,multiplicity:M::ONE};
+            let g = NodeIndex{address:g.address,multiplicity:M::ONE};
+            if let Some(res) = memo.get(&(f,g,passed)) { return scale(res); }
+            let variable = if f.is_sink() && g.is_sink() { next_quantified.unwrap() } else {
+                let top = x.top_variable(&[f,g]);
+                next_quantified.map(|q|q.min(top)).unwrap_or(top)
+            };
+            let cofactors = |x:&X,index:NodeIndex<A,M>| if BDD {
+                if index.is_sink() || x.node(index.address).variable!=variable { (index,index) } else { let node = x.node(index.address); (node.lo,node.hi) }
+            } else { x.family_cofactors(index,variable) };
+            let (f0,f1) = cofactors(x,f);
+            let (g0,g1) = cofactors(x,g);
+            let res = if Some(variable)==next_quantified {
+                let lo = product::<A,M,X,C,BDD>(x,f0,g0,quantified,passed+1,memo,or_cache);
+                if BDD && M::MULTIPLICITIES_IRRELEVANT && lo.is_true() { lo } // no need to look at the other branch.
+                else {
+                    let hi = product::<A,M,X,C,BDD>(x,f1,g1,quantified,passed+1,memo,or_cache);
+                    if BDD { x.sum_bdd(lo,hi,or_cache) } else {
+                        let either = x.sum_zdd(lo,hi,or_cache);
+                        if either.is_false() { either } else { x.add_node_if_not_present(Node {variable,lo:either,hi:either}) }
+                    }
+                }
+            } else {
+                let lo = product::<A,M,X,C,BDD>(x,f0,g0,quantified,passed,memo,or_cache);
+                let hi = product::<A,M,X,C,BDD>(x,f1,g1,quantified,passed,memo,or_cache);
+                if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { x.add_node_if_not_present(Node {variable,lo,hi}) }
+            };
+            memo.insert((f,g,passed),res);
+            scale(res)
+        }
+        product::<A,M,Self,C,BDD>(self,f,g,quantified,0,&mut HashMap::new(),or_cache)
+    }
+
+    /// Make a node representing the multiset difference index1 - index2, interpreted as BDDs. The multiplicity of a value is its
+    /// multiplicity in index1 less that in index2, with the value absent if that is not positive. See [Multiplicity::saturating_subtract].
+    fn subtract_bdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.subtract::<C,true>(index1,index2,cache)
+    }
+    /// Make a node representing the multiset difference index1 - index2, interpreted as ZDDs. See [XDDBase::subtract_bdd].
+    fn subtract_zdd<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        self.subtract::<C,false>(index1,index2,cache)
+    }
+    /// The work for [XDDBase::subtract_bdd] and [XDDBase::subtract_zdd].
+    fn subtract<C:Cache<(NodeIndex<A,M>, NodeIndex<A,M>), NodeIndex<A,M>>,const BDD:bool>(&mut self, index1: NodeIndex<A,M>, index2: NodeIndex<A,M>, cache : &mut C) -> NodeIndex<A,M> {
+        if index1.is_false() { return NodeIndex::FALSE; }
+        if index2.is_false() { return index1; }
+        if index1.address==index2.address { // the same function with different multiplicities, which are never negative.
+            return match M::saturating_subtract(index1.multiplicity,index2.multiplicity) {
+                Some(multiplicity) => NodeIndex{address:index1.address,multiplicity},
+                None => NodeIndex::FALSE,
+            };
+        }
+        let key = (index1,index2);
+        if let Some(res) = cache.get(&key) { return res; }
+        let variable = self.top_variable(&[index1,index2]);
+        let cofactors = |s:&Self,index:NodeIndex<A,M>| if index.is_sink() || s.node(index.address).variable!=variable {
+            (index,if BDD { index } else { NodeIndex::FALSE })
+        } else {
+            let node = s.node_incorporating_multiplicity(index);
+            (node.lo,node.hi)
+        };
+        let (f0,f1) = cofactors(self,index1);
+        let (g0,g1) = cofactors(self,index2);
+        let lo = self.subtract::<C,BDD>(f0,g0,cache);
+        let hi = self.subtract::<C,BDD>(f1,g1,cache);
+        if BDD { self.create_node_bdd(lo,hi,variable,key,cache) } else { self.create_node_zdd(lo,hi,variable,key,cache) }
+    }
+
+    /// Make a node with the same solutions as f, each with multiplicity 1, interpreted as a BDD if BDD or a ZDD otherwise.
+    /// Dropping the multiplicities may make a node redundant, so nodes are remade rather than copied.
+    fn support<const BDD:bool>(&mut self, f: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let mut done : HashMap<A,NodeIndex<A,M>> = HashMap::new();
+        let child = |done:&HashMap<A,NodeIndex<A,M>>,index:NodeIndex<A,M>| if index.is_sink() { NodeIndex{address:index.address,multiplicity:M::ONE} } else { done[&index.address] };
+        for address in self.post_order(f) {
+            let node = self.node(address);
+            let lo = child(&done,node.lo);
+            let hi = child(&done,node.hi);
+            let res = if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { self.add_node_if_not_present(Node{variable:node.variable,lo,hi}) };
+            done.insert(address,res);
+        }
+        if f.is_false() { NodeIndex::FALSE } else { child(&done,f) }
+    }
+
+    /// Make a node with just the solutions of f whose multiplicity satisfies predicate, interpreted as a BDD if BDD or a ZDD otherwise.
+    /// The multiplicity of a solution is the product of the multiplicities along its path, so this is memoized on the
+    /// node and the multiplicity accumulated so far.
+    fn filter_multiplicity<P:Fn(M)->bool,const BDD:bool>(&mut self, f: NodeIndex<A,M>, predicate:P) -> NodeIndex<A,M> {
+        fn filter<A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>+?Sized,P:Fn(M)->bool,const BDD:bool>(x:&mut X,index:NodeIndex<A,M>,predicate:&P,memo:&mut HashMap<NodeIndex<A,M>,NodeIndex<A,M>>) -> NodeIndex<A,M> {
+            if index.is_false() { return NodeIndex::FALSE; }
+            if index.is_true() { return if predicate(index.multiplicity) { index } else { NodeIndex::FALSE }; }
+            if let Some(res) = memo.get(&index) { return res; }
+            let node = x.node_incorporating_multiplicity(index);
+            let lo = filter::<A,M,X,P,BDD>(x,node.lo,predicate,memo);
+            let hi = filter::<A,M,X,P,BDD>(x,node.hi,predicate,memo);
+            let res = if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { x.add_node_if_not_present(Node{variable:node.variable,lo,hi}) };
+            memo.insert(index,res);
+            res
+        }
+        filter::<A,M,Self,P,BDD>(self,f,&predicate,&mut HashMap::new())
+    }
+
+    /// Compute the sum of each f_i with its multiplicities multiplied by m_i, interpreted as BDDs.
+    /// This is done in a single pass over all the terms, rather than a sequence of sums each building a new diagram.
+    fn linear_combination_bdd(&mut self, terms:&[(NodeIndex<A,M>,M)]) -> NodeIndex<A,M> { self.linear_combination::<true>(terms) }
+    /// Compute the sum of each f_i with its multiplicities multiplied by m_i, interpreted as ZDDs. See [XDDBase::linear_combination_bdd].
+    fn linear_combination_zdd(&mut self, terms:&[(NodeIndex<A,M>,M)]) -> NodeIndex<A,M> { self.linear_combination::<false>(terms) }
+
+    /// The work for [XDDBase::linear_combination_bdd] and [XDDBase::linear_combination_zdd]. The state is the list of
+    /// (scaled) nodes still to be added, sorted by address with terms for the same address combined, so that
+    /// equivalent states are found in the memo.
+    fn linear_combination<const BDD:bool>(&mut self, terms:&[(NodeIndex<A,M>,M)]) -> NodeIndex<A,M> {
+        type Memo<A,M> = HashMap<Vec<NodeIndex<A,M>>,NodeIndex<A,M>>;
+        fn canonical<A:NodeAddress,M:Multiplicity>(terms:impl Iterator<Item=NodeIndex<A,M>>) -> Vec<NodeIndex<A,M>> {
+            let mut terms : Vec<NodeIndex<A,M>> = terms.filter(|t|!t.is_false()).collect();
+            terms.sort_by_key(|t|t.address.as_usize());
+            let mut res : Vec<NodeIndex<A,M>> = Vec::with_capacity(terms.len());
+            for t in terms {
+                match res.last_mut() {
+                    Some(last) if last.address==t.address => { last.multiplicity=M::combine_or(last.multiplicity,t.multiplicity); }
+                    _ => res.push(t),
+                }
+            }
+            res.retain(|t|!t.multiplicity.is_zero());
+            res
+        }
+        fn sum<A:NodeAddress,M:Multiplicity,X:XDDBase<A,M>+?Sized,const BDD:bool>(x:&mut X,terms:Vec<NodeIndex<A,M>>,memo:&mut Memo<A,M>) -> NodeIndex<A,M> {
+            match terms.len() {
+                0 => return NodeIndex::FALSE,
+                1 => return terms[0],
+                _ => {}
+            }
+            if let Some(res) = memo.get(&terms) { return res; }
+            let variable = x.top_variable(&terms);
+            let cofactors : Vec<(NodeIndex<A,M>,NodeIndex<A,M>)> = terms.iter().map(|&t| if t.is_sink() || x.node(t.address).variable!=variable {
+                (t,if BDD { t } else { NodeIndex::FALSE })
+            } else {
+                let node = x.node_incorporating_multiplicity(t);
+                (node.lo,node.hi)
+            }).collect();
+            let lo = sum::<A,M,X,BDD>(x,canonical(cofactors.iter().map(|c|c.0)),memo);
+            let hi = sum::<A,M,X,BDD>(x,canonical(cofactors.iter().map(|c|c.1)),memo);
+            let res = if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { x.add_node_if_not_present(Node {variable,lo,hi}) };
+            memo.insert(terms,res);
+            res
+        }
+        let terms = canonical(terms.iter().map(|&(f,m)|f.multiply(m)));
+        sum::<A,M,Self,BDD>(self,terms,&mut HashMap::new())
+    }
+
+    /// Replace each variable v in f by mapping(v), keeping the structure of the diagram. This is the same for BDDs and ZDDs.
+    /// Panics if mapping does not keep the order of variables along each path, or gives a variable not less than total_num_variables.
+    fn rename_variables<F:Fn(VariableIndex)->VariableIndex>(&mut self, f: NodeIndex<A,M>, mapping:F, total_num_variables:u16) -> NodeIndex<A,M> {
+        let mut done : HashMap<A,NodeIndex<A,M>> = HashMap::new();
+        for address in self.post_order(f) {
+            let node = self.node(address);
+            let variable = mapping(node.variable);
+            assert!(variable.0<total_num_variables,"Variable {} was renamed to {} in a factory with only {} variables",node.variable,variable,total_num_variables);
+            let child = |index:NodeIndex<A,M>| if index.is_sink() { index } else {
+                let renamed = done[&index.address];
+                assert!(self.node(renamed.address).variable>variable,"Renaming variable {} to {} does not keep the order of the variables",node.variable,variable);
+                renamed.multiply(index.multiplicity)
+            };
+            let lo = child(node.lo);
+            let hi = child(node.hi);
+            let renamed = self.add_node_if_not_present(Node {variable,lo,hi});
+            done.insert(address,renamed);
+        }
+        if f.is_sink() { f } else { done[&f.address].multiply(f.multiplicity) }
+    }
+
+    /// Make the diagram for f with variable and the one after it exchanged, interpreted as a BDD if BDD and otherwise as a ZDD.
+    /// Only nodes for variables up to the one after variable change. memo remembers the result for such nodes, and can be
+    /// shared between calls for the same variable.
+    fn swap_adjacent_variables<const BDD:bool>(&mut self, f:NodeIndex<A,M>, variable:VariableIndex, memo:&mut HashMap<A,NodeIndex<A,M>>) -> NodeIndex<A,M> {
+        if f.is_sink() { return f; }
+        let node = self.node(f.address);
+        let below = VariableIndex(variable.0+1);
+        if node.variable>below { return f; }
+        let res = if let Some(res) = memo.get(&f.address) { res } else {
+            let make = |s:&mut Self,variable:VariableIndex,lo:NodeIndex<A,M>,hi:NodeIndex<A,M>| {
+                if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { s.add_node_if_not_present(Node {variable,lo,hi}) }
+            };
+            let res = if node.variable<variable {
+                let lo = self.swap_adjacent_variables::<BDD>(node.lo,variable,memo);
+                let hi = self.swap_adjacent_variables::<BDD>(node.hi,variable,memo);
+                make(self,node.variable,lo,hi)
+            } else if node.variable==below { // does not depend on variable (BDD) or has it false (ZDD), so just moves up.
+                make(self,variable,node.lo,node.hi)
+            } else {
+                // the four cofactors, fij being f with variable=i and below=j.
+                let cofactors = |s:&Self,index:NodeIndex<A,M>| if index.is_sink() || s.node(index.address).variable!=below {
+                    (index,if BDD { index } else { NodeIndex::FALSE })
+                } else {
+                    let node = s.node_incorporating_multiplicity(index);
+                    let canonical = |i:NodeIndex<A,M>| if i.is_false() { NodeIndex::FALSE } else { i }; // multiplying FALSE is not canonical.
+                    (canonical(node.lo),canonical(node.hi))
+                };
+                let (f00,f01) = cofactors(self,node.lo);
+                let (f10,f11) = cofactors(self,node.hi);
+                let lo = make(self,below,f00,f10);
+                let hi = make(self,below,f01,f11);
+                make(self,variable,lo,hi)
+            };
+            memo.insert(f.address,res);
+            res
+        };
+        if res.is_false() { res } else { res.multiply(f.multiplicity) }
+    }
+
+
+
+    /// Create generating functions for nodes 0 inclusive to length exclusive.
+    /// This is easy because of the topological sort.
+    /// Return an array such that res[node] = the variable used at the time and the generating function.
+    fn all_number_solutions<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self,length:usize,num_variables:u16) -> Vec<G> {
+        let mut res = Vec::new();
+        res.push(G::zero());
+        res.push(G::one());
+        for i in 2..length {
+            let node = self.node(i.try_into().map_err(|_|()).unwrap());
+            let lo_g = res[node.lo.address.as_usize()].clone();
+            let hi_g = res[node.hi.address.as_usize()].clone();
+            res.push(self.node_number_solutions::<G,BDD>(node,num_variables,lo_g,hi_g));
+        }
+        //println!("{:?}",res);
+        res
+    }
+
+    /// The generating function for a node, given those of its children.
+    fn node_number_solutions<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self,node:Node<A,M>,num_variables:u16,lo_g:G,hi_g:G) -> G {
+        let next_variable = VariableIndex(node.variable.0+1);
+        //println!("Computing lo={} hi={} variable={}",node.lo,node.hi,node.variable);
+        let lo_g = if M::MULTIPLICITIES_IRRELEVANT || node.lo.multiplicity.is_unity() { lo_g } else { lo_g.multiply(node.lo.multiplicity) };
+        let lo_level = if node.lo.is_sink() { VariableIndex(num_variables) } else { self.node(node.lo.address).variable };
+        //println!("   lo_g={:?}, lo_level={}",lo_g,lo_level);
+        let lo = if BDD {lo_g.deal_with_variable_range_being_indeterminate(next_variable,lo_level)} else {lo_g};
+        let lo = lo.variable_not_set(node.variable);
+        let hi_g = if M::MULTIPLICITIES_IRRELEVANT || node.hi.multiplicity.is_unity() { hi_g } else { hi_g.multiply(node.hi.multiplicity) };
+        let hi_level = if node.hi.is_sink() { VariableIndex(num_variables) } else { self.node(node.hi.address).variable };
+        //println!("   hi_g={:?}, hi_level={}",hi_g,hi_level);
+        let hi = if BDD {hi_g.deal_with_variable_range_being_indeterminate(next_variable,hi_level)} else {hi_g};
+        let hi = hi.variable_set(node.variable);
+        //println!(" GF lo = {:?},   GF hi = {:?}",lo,hi);
+        lo.add(hi)
+    }
+
+    /// The generating function for index, given the one found for its address.
+    fn root_number_solutions<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16, found:G) -> G {
+        let before_multiplicity = if BDD {
+            let level = if index.is_sink() { VariableIndex(num_variables) } else { self.node(index.address).variable };
+            found.deal_with_variable_range_being_indeterminate(VariableIndex(0),level)
+        } else { found };
+        before_multiplicity.multiply(index.multiplicity)
+    }
+
+    /// The generating function of index. Only the nodes reachable from index are worked out, in post order so children come first;
+    /// unlike [XDDBase::all_number_solutions], dead intermediate results with smaller addresses are not visited.
+    fn number_solutions<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16) -> G {
+        self.number_solutions_many::<G,BDD>(&[index],num_variables).pop().unwrap()
+    }
+
+    /// The generating function of each of roots, found in a single pass over the nodes reachable from any of them,
+    /// so nodes they share are only worked out once.
+    fn number_solutions_many<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self, roots:&[NodeIndex<A,M>], num_variables:u16) -> Vec<G> {
+        let mut work : HashMap<A,G> = HashMap::new();
+        work.insert(A::FALSE,G::zero());
+        work.insert(A::TRUE,G::one());
+        for address in self.post_order_many(roots.iter().cloned()) {
+            let node = self.node(address);
+            let found = self.node_number_solutions::<G,BDD>(node,num_variables,work[&node.lo.address].clone(),work[&node.hi.address].clone());
+            work.insert(address,found);
+        }
+        roots.iter().map(|&index|self.root_number_solutions::<G,BDD>(index,num_variables,work[&index.address].clone())).collect()
+    }
+
+    /// Like number_solutions, but only visiting nodes reachable from index that are not already in memo, and adding them to it,
+    /// so repeated queries on different roots only process each shared node once. memo maps an address to its generating function
+    /// before the root's skipped variables and multiplicity are taken into account.
+    fn number_solutions_memoized<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16, memo:&mut HashMap<A,G>) -> G {
+        let value = |memo:&HashMap<A,G>,address:A| if address==A::FALSE { G::zero() } else if address==A::TRUE { G::one() } else { memo[&address].clone() };
+        let needed = |memo:&HashMap<A,G>,address:A| address!=A::FALSE && address!=A::TRUE && !memo.contains_key(&address);
+        let mut stack = vec![];
+        if needed(memo,index.address) { stack.push(index.address); }
+        while let Some(&address) = stack.last() {
+            let node = self.node(address);
+            let pending : Vec<A> = [node.lo.address,node.hi.address].into_iter().filter(|&child|needed(memo,child)).collect();
+            if pending.is_empty() {
+                stack.pop();
+                if needed(memo,address) { // may have been done already if it was pushed twice.
+                    let found = self.node_number_solutions::<G,BDD>(node,num_variables,value(memo,node.lo.address),value(memo,node.hi.address));
+                    memo.insert(address,found);
+                }
+            } else { stack.extend(pending); }
+        }
+        self.root_number_solutions::<G,BDD>(index,num_variables,value(memo,index.address))
+    }
+
+    /// Like number_solutions_bdd, but only the variables v with counted[v] are counted; the others must not be used by index,
+    /// and are treated as having just one value rather than being free.
+    fn number_solutions_bdd_over<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, counted:&[bool]) -> G {
+        let num_variables = counted.len() as u16;
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { self.node(index.address).variable.0 };
+        // the generating function of index, including the free variables from from_level.
+        let from = |work:&HashMap<A,G>,index:NodeIndex<A,M>,from_level:u16| {
+            let mut g = work[&index.address].clone();
+            for v in (from_level..level(index)).rev() {
+                if counted[v as usize] { g = g.deal_with_variable_being_indeterminate(VariableIndex(v)); }
+            }
+            g.multiply(index.multiplicity)
+        };
+        let mut work : HashMap<A,G> = HashMap::new();
+        work.insert(A::FALSE,G::zero());
+        work.insert(A::TRUE,G::one());
+        for address in self.post_order(index) {
+            let node = self.node(address);
+            debug_assert!(counted[node.variable.0 as usize],"The diagram uses a variable that is not counted");
+            let next_level = node.variable.0+1;
+            let lo = from(&work,node.lo,next_level).variable_not_set(node.variable);
+            let hi = from(&work,node.hi,next_level).variable_set(node.variable);
+            work.insert(address,lo.add(hi));
+        }
+        from(&work,index,0)
+    }
+
+    /// The generating function of the solutions of index in which each variable v with fixed[v] not None has that value.
+    /// fixed has one entry per variable. This is one pass over the nodes reachable from index, with no new nodes made.
+    fn number_solutions_given<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self, index: NodeIndex<A,M>, fixed:&[Option<bool>]) -> G {
+        let num_variables = fixed.len() as u16;
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { self.node(index.address).variable.0 };
+        // the generating function of index, including the variables skipped from from_level.
+        let from = |work:&HashMap<A,G>,index:NodeIndex<A,M>,from_level:u16| {
+            let mut g = work[&index.address].clone();
+            for v in (from_level..level(index)).rev() {
+                g = match fixed[v as usize] {
+                    None if BDD => g.deal_with_variable_being_indeterminate(VariableIndex(v)),
+                    Some(true) if BDD => g.variable_set(VariableIndex(v)),
+                    Some(true) => return G::zero(), // skipped variables are false in a ZDD.
+                    _ => g.variable_not_set(VariableIndex(v)),
+                };
+            }
+            g.multiply(index.multiplicity)
+        };
+        let mut work : HashMap<A,G> = HashMap::new();
+        work.insert(A::FALSE,G::zero());
+        work.insert(A::TRUE,G::one());
+        for address in self.post_order(index) {
+            let node = self.node(address);
+            let next_level = node.variable.0+1;
+            let value = fixed[node.variable.0 as usize];
+            let lo = if value==Some(true) { G::zero() } else { from(&work,node.lo,next_level).variable_not_set(node.variable) };
+            let hi = if value==Some(false) { G::zero() } else { from(&work,node.hi,next_level).variable_set(node.variable) };
+            work.insert(address,lo.add(hi));
+        }
+        from(&work,index,0)
+    }
+
+    /// For each variable, the number of solutions of index in which it is true, weighted by multiplicity.
+    ///
+    /// The number of solutions below each node is found children first as for number_solutions, then the number of paths to each
+    /// node parents first. A node for variable v adds the paths to it times the solutions below its hi child to the count for v.
+    /// In a BDD an edge skipping variables adds half the solutions through it to each variable skipped; these ranges are summed
+    /// with a running total over the variables at the end, so the time is proportional to the number of nodes plus variables.
+    fn variable_marginals<G:GeneratingFunctionWithMultiplicity<M>+Mul<Output=G>+Sub<Output=G>,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16) -> Vec<G> {
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { self.node(index.address).variable.0 };
+        let skip = |g:G,from:u16,to:u16| if BDD { g.deal_with_variable_range_being_indeterminate(VariableIndex(from),VariableIndex(to)) } else { g };
+        let order : Vec<A> = self.post_order(index).collect();
+        let mut below : HashMap<A,G> = HashMap::new();
+        below.insert(A::FALSE,G::zero());
+        below.insert(A::TRUE,G::one());
+        let from = |below:&HashMap<A,G>,index:NodeIndex<A,M>,from_level:u16| skip(below[&index.address].clone(),from_level,level(index)).multiply(index.multiplicity);
+        for &address in &order {
+            let node = self.node(address);
+            let next_level = node.variable.0+1;
+            let lo = from(&below,node.lo,next_level).variable_not_set(node.variable);
+            let hi = from(&below,node.hi,next_level).variable_set(node.variable);
+            below.insert(address,lo.add(hi));
+        }
+        let mut marginals = vec![G::zero();num_variables as usize];
+        let mut starts = vec![G::zero();num_variables as usize]; // solutions added to each variable in a range starting here.
+        let mut ends = vec![G::zero();num_variables as usize]; // solutions added to each variable in a range ending here.
+        let mut above : HashMap<A,G> = HashMap::new();
+        // paths is the weight of the ways of reaching the start of an edge to child, just before variable from_level.
+        let mut edge = |above:&mut HashMap<A,G>,paths:G,from_level:u16,child:NodeIndex<A,M>| {
+            if child.is_false() { return; }
+            let paths = paths.multiply(child.multiplicity);
+            let child_level = level(child);
+            if BDD && child_level>from_level {
+                let half = paths.clone()*skip(below[&child.address].clone(),from_level+1,child_level);
+                starts[from_level as usize] = std::mem::replace(&mut starts[from_level as usize],G::zero()).add(half.clone());
+                ends[child_level as usize-1] = std::mem::replace(&mut ends[child_level as usize-1],G::zero()).add(half);
+            }
+            if !child.is_sink() {
+                let paths = skip(paths,from_level,child_level);
+                let total = match above.remove(&child.address) { Some(existing) => existing.add(paths), None => paths };
+                above.insert(child.address,total);
+            }
+        };
+        edge(&mut above,G::one(),0,index);
+        for &address in order.iter().rev() { // parents before children.
+            let paths = above.remove(&address).unwrap();
+            let node = self.node(address);
+            let v = node.variable.0;
+            let hi = paths.clone()*from(&below,node.hi,v+1);
+            marginals[v as usize] = std::mem::replace(&mut marginals[v as usize],G::zero()).add(hi);
+            edge(&mut above,paths.clone(),v+1,node.lo);
+            edge(&mut above,paths,v+1,node.hi);
+        }
+        let mut running = G::zero();
+        for v in 0..num_variables as usize {
+            running = running.add(std::mem::replace(&mut starts[v],G::zero()));
+            marginals[v] = std::mem::replace(&mut marginals[v],G::zero()).add(running.clone());
+            running = running-std::mem::replace(&mut ends[v],G::zero());
+        }
+        marginals
+    }
+
+    /// The sum over solutions of index of the product of the weights of the variables true in it, times its multiplicity.
+    /// A variable skipped in a BDD may take either value, so contributes one plus its weight.
+    fn weighted_count<G:GeneratingFunctionWithMultiplicity<M>+Mul<Output=G>,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16, weights:&[G]) -> G {
+        assert_eq!(weights.len(),num_variables as usize,"There should be one weight per variable");
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { self.node(index.address).variable.0 };
+        let skip = |g:G,from:u16,to:u16| if BDD { (from..to).fold(g,|g,v|g.clone().add(g*weights[v as usize].clone())) } else { g };
+        let mut below : HashMap<A,G> = HashMap::new();
+        below.insert(A::FALSE,G::zero());
+        below.insert(A::TRUE,G::one());
+        let from = |below:&HashMap<A,G>,index:NodeIndex<A,M>,from_level:u16| skip(below[&index.address].clone(),from_level,level(index)).multiply(index.multiplicity);
+        for address in self.post_order(index) {
+            let node = self.node(address);
+            let next_level = node.variable.0+1;
+            let lo = from(&below,node.lo,next_level);
+            let hi = from(&below,node.hi,next_level)*weights[node.variable.0 as usize].clone();
+            below.insert(address,lo.add(hi));
+        }
+        from(&below,index,0)
+    }
+
+    fn number_solutions_bdd<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, num_variables:u16) -> G { self.number_solutions::<G,true>(index, num_variables) }
+    fn number_solutions_zdd<G:GeneratingFunctionWithMultiplicity<M>>(&self, index: NodeIndex<A,M>, num_variables:u16) -> G { self.number_solutions::<G,false>(index, num_variables) }
+
+    /// The number of solutions of index with exactly k variables true, counted with multiplicity.
+    ///
+    /// This is the k coefficient of number_solutions with a [crate::generating_function::SingleVariableGeneratingFunction], but only keeps
+    /// coefficients up to k for the nodes reachable from index, and drops each node's coefficients once all its parents are done.
+    fn number_solutions_with_k_true<G:GeneratingFunctionWithMultiplicity<M>,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16, k:usize) -> G {
+        // coefficients[i] is the number of ways with i variables true, for i<=k.
+        let set = |mut coefficients:Vec<G>| { coefficients.insert(0,G::zero()); coefficients.truncate(k+1); coefficients };
+        let add = |a:Vec<G>,b:Vec<G>| {
+            let (mut long,short) = if a.len()>=b.len() { (a,b) } else { (b,a) };
+            for (i,v) in short.into_iter().enumerate() { long[i] = long[i].clone().add(v); }
+            long
+        };
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { self.node(index.address).variable.0 };
+        // the coefficients for index, including variables from from_level.
+        let from = |done:&HashMap<A,Vec<G>>,index:NodeIndex<A,M>,from_level:u16| {
+            let mut coefficients = if index.is_false() { vec![] } else if index.is_true() { vec![G::one()] } else { done[&index.address].clone() };
+            if BDD { // variables skipped in a BDD may take either value.
+                for _ in from_level..level(index) { coefficients = add(set(coefficients.clone()),coefficients); }
+            }
+            if M::MULTIPLICITIES_IRRELEVANT || index.multiplicity.is_unity() { coefficients } else { coefficients.into_iter().map(|c|c.multiply(index.multiplicity)).collect() }
+        };
+        let mut parents_left : HashMap<A,usize> = HashMap::new();
+        for address in self.post_order(index) {
+            let node = self.node(address);
+            for child in [node.lo,node.hi] { if !child.is_sink() { *parents_left.entry(child.address).or_insert(0)+=1; } }
+        }
+        let mut done : HashMap<A,Vec<G>> = HashMap::new();
+        for address in self.post_order(index) {
+            let node = self.node(address);
+            let next_level = node.variable.0+1;
+            let coefficients = add(from(&done,node.lo,next_level),set(from(&done,node.hi,next_level)));
+            for child in [node.lo,node.hi] {
+                if !child.is_sink() {
+                    let left = parents_left.get_mut(&child.address).unwrap();
+                    *left-=1;
+                    if *left==0 { done.remove(&child.address); }
+                }
+            }
+            done.insert(address,coefficients);
+        }
+        from(&done,index,0).into_iter().nth(k).unwrap_or_else(G::zero)
+    }
+
+    /// Find the k solutions of index with the greatest total weight, where each variable that is true adds its weight.
+    /// Returned best first, each as the total weight and the sorted list of variables that are true. Fewer than k are returned if there are not k solutions.
+    /// Ties are broken arbitrarily. Multiplicities are ignored.
+    ///
+    /// This works up from the sinks keeping the best k ways of completing each node, so takes time proportional to
+    /// the number of nodes times k (times the number of skipped variables for a BDD).
+    fn best_solutions<W:Copy+PartialOrd+Add<Output=W>+Zero,const BDD:bool>(&self, index: NodeIndex<A,M>, num_variables:u16, weights:&[W], k:usize) -> Vec<(W,Vec<VariableIndex>)> {
+        assert_eq!(weights.len(),num_variables as usize,"There should be one weight per variable");
+        // partial solutions have variables in decreasing order, as they are built from the bottom up.
+        fn best_k<W:Copy+PartialOrd>(mut options:Vec<(W,Vec<VariableIndex>)>,k:usize) -> Vec<(W,Vec<VariableIndex>)> {
+            options.sort_by(|a,b|b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+            options.truncate(k);
+            options
+        }
+        let with_variable = |options:&[(W,Vec<VariableIndex>)],variable:VariableIndex| options.iter().map(|(w,v)|{
+            let mut v = v.clone();
+            v.push(variable);
+            (*w+weights[variable.0 as usize],v)
+        }).collect::<Vec<_>>();
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { self.node(index.address).variable.0 };
+        let mut best : HashMap<A,Vec<(W,Vec<VariableIndex>)>> = HashMap::new();
+        best.insert(A::FALSE,vec![]);
+        best.insert(A::TRUE,if k==0 { vec![] } else { vec![(W::zero(),vec![])] });
+        // The best solutions through index, including variables from from_level.
+        let best_from = |best:&HashMap<A,Vec<(W,Vec<VariableIndex>)>>,index:NodeIndex<A,M>,from_level:u16| {
+            let mut options = best[&index.address].clone();
+            if BDD { // variables skipped in a BDD may take either value.
+                for v in (from_level..level(index)).rev() {
+                    let mut both = with_variable(&options,VariableIndex(v));
+                    both.extend(options);
+                    options = best_k(both,k);
+                }
+            }
+            options
+        };
+        for address in self.post_order(index) {
+            let node = self.node(address);
+            let mut options = best_from(&best,node.lo,node.variable.0+1);
+            options.extend(with_variable(&best_from(&best,node.hi,node.variable.0+1),node.variable));
+            best.insert(address,best_k(options,k));
+        }
+        best_from(&best,index,0).into_iter().map(|(w,mut v)|{ v.reverse(); (w,v) }).collect()
+    }
+
+    /// Find which addresses are reachable from any of the roots: res[address] is true iff it is.
+    /// The result is only long enough to include the highest root.
+    ///
+    /// This does not recurse; because of the topological sort, a single scan down from the highest root suffices.
+    fn reachable(&self, roots:impl IntoIterator<Item=NodeIndex<A,M>>) -> Vec<bool> {
+        let roots : Vec<usize> = roots.into_iter().map(|r|r.address.as_usize()).collect();
+        let mut res = vec![false;roots.iter().map(|&r|r+1).max().unwrap_or(0)];
+        for r in roots { res[r]=true; }
+        for address in (2..res.len()).rev() {
+            if res[address] {
+                let node = self.node(address.try_into().map_err(|_|()).unwrap());
+                res[node.lo.address.as_usize()]=true;
+                res[node.hi.address.as_usize()]=true;
+            }
+        }
+        res
+    }
+
+    /// Iterate over the addresses of the (non-sink) nodes reachable from index, children before parents.
+    /// See [crate::DecisionDiagramFactory::post_order].
+    fn post_order(&self, index: NodeIndex<A,M>) -> PostOrder<A> { self.post_order_many([index]) }
+
+    /// Iterate over the addresses of the (non-sink) nodes reachable from any of roots, children before parents.
+    /// Unlike [XDDBase::reachable], the time and memory taken depend on the number of reachable nodes rather than
+    /// the highest address, so this is cheap for a small diagram in a large factory.
+    fn post_order_many(&self, roots:impl IntoIterator<Item=NodeIndex<A,M>>) -> PostOrder<A> {
+        let mut stack : Vec<A> = roots.into_iter().map(|r|r.address).filter(|a|!a.is_sink()).collect();
+        let mut found : HashSet<A> = stack.iter().cloned().collect();
+        while let Some(address) = stack.pop() {
+            let node = self.node(address);
+            for child in [node.lo.address,node.hi.address] {
+                if !child.is_sink() && found.insert(child) { stack.push(child); }
+            }
+        }
+        let mut addresses : Vec<A> = found.into_iter().collect();
+        // as nodes are topologically sorted, increasing address means children before parents.
+        addresses.sort_unstable_by_key(|a|a.as_usize());
+        PostOrder{ addresses: addresses.into_iter() }
+    }
+
+    /// Whether f in this node store has the same structure as g in other, which may use a different address type.
+    /// As both are reduced, this is true iff they represent the same function (or family) given the same number of variables.
+    /// memo records pairs of addresses already found to match.
+    fn equivalent<A2:NodeAddress,X2:XDDBase<A2,M>>(&self, f: NodeIndex<A,M>, other:&X2, g: NodeIndex<A2,M>, memo:&mut HashMap<A,A2>) -> bool {
+        if f.is_false() || g.is_false() { return f.is_false() && g.is_false(); } // multiplicity is irrelevant for FALSE.
+        if f.multiplicity!=g.multiplicity { return false; }
+        if f.is_true() || g.is_true() { return f.is_true() && g.is_true(); }
+        if let Some(matched) = memo.get(&f.address) { return matched==g.address; }
+        let node_f = self.node(f.address);
+        let node_g = other.node(g.address);
+        let res = node_f.variable==node_g.variable && self.equivalent(node_f.lo,other,node_g.lo,memo) && self.equivalent(node_f.hi,other,node_g.hi,memo);
+        if res { memo.insert(f.address,g.address); }
+        res
+    }
+
+    /// The multiplicity of the assignment in f, or None if it is not a solution. assignment[v] is the value of variable v,
+    /// with any variables past its end false. Just follows one path, reading the nodes.
+    fn evaluate<const BDD:bool>(&self, f: NodeIndex<A,M>, assignment:&[bool]) -> Option<M> {
+        let value = |v:u16| assignment.get(v as usize).cloned().unwrap_or(false);
+        let mut index = f;
+        let mut multiplicity = M::ONE;
+        let mut next_variable = 0;
+        loop {
+            if index.is_false() { return None; }
+            multiplicity = M::multiply(multiplicity,index.multiplicity);
+            let variable = if index.is_true() { assignment.len().max(next_variable as usize) as u16 } else { self.node(index.address).variable.0 };
+            if !BDD && (next_variable..variable).any(value) { return None; } // skipped variables are false in a ZDD.
+            if index.is_true() { return Some(multiplicity); }
+            let node = self.node(index.address);
+            index = if value(variable) { node.hi } else { node.lo };
+            next_variable = variable+1;
+        }
+    }
+
+    /// The lexicographically smallest solution of f (comparing assignments with variable 0 first, false before true) that is
+    /// strictly greater than assignment, as the sorted list of variables that are true, or None if there is none.
+    /// assignment[v] is the value of variable v, false past its end. Multiplicities are ignored.
+    ///
+    /// This follows the path for assignment from the root, noting the last variable that is false in assignment but
+    /// could be made true with solutions still possible. The answer is the assignment up to that variable, then that variable
+    /// true, then the smallest solution below; so the time is proportional to the number of variables.
+    fn next_solution_after<const BDD:bool>(&self, f: NodeIndex<A,M>, assignment:&[bool], num_variables:u16) -> Option<Vec<VariableIndex>> {
+        let value = |v:u16| assignment.get(v as usize).cloned().unwrap_or(false);
+        let variable_of = |index:NodeIndex<A,M>| if index.is_true() { num_variables } else { self.node(index.address).variable.0 };
+        let mut index = f;
+        let mut branch : Option<(u16,NodeIndex<A,M>)> = None; // the variable to make true, and what is below it then.
+        for v in 0..num_variables {
+            if index.is_false() { break; }
+            if variable_of(index)>v { // a skipped variable, free in a BDD and false in a ZDD.
+                if BDD && !value(v) { branch=Some((v,index)); }
+                if !BDD && value(v) { index=NodeIndex::FALSE; }
+            } else {
+                let node = self.node(index.address);
+                if !value(v) && !node.hi.is_false() { branch=Some((v,node.hi)); }
+                index = if value(v) { node.hi } else { node.lo };
+            }
+        }
+        let (branch_variable,mut index) = branch?;
+        let mut res : Vec<VariableIndex> = (0..branch_variable).filter(|&v|value(v)).map(VariableIndex).collect();
+        res.push(VariableIndex(branch_variable));
+        // the smallest solution below, taking each variable false when possible.
+        while !index.is_true() {
+            let node = self.node(index.address);
+            if node.lo.is_false() {
+                res.push(node.variable);
+                index = node.hi;
+            } else { index = node.lo; }
+        }
+        Some(res)
+    }
+
+    /// Whether every solution of f is a solution of g, ignoring multiplicities. This only reads the nodes, never making new ones.
+    /// memo records pairs of addresses already compared.
+    fn implies<const BDD:bool>(&self, f: NodeIndex<A,M>, g: NodeIndex<A,M>, memo:&mut HashMap<(A,A),bool>) -> bool {
+        if f.is_false() || f.address==g.address { return true; }
+        if g.is_false() { return false; }
+        if BDD && g.is_true() { return true; }
+        if f.is_true() && g.is_true() { return true; }
+        let key = (f.address,g.address);
+        if let Some(res) = memo.get(&key) { return res; }
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { None } else { Some(self.node(index.address).variable) };
+        let variable = match (level(f),level(g)) {
+            (Some(a),Some(b)) => a.min(b),
+            (a,b) => a.or(b).unwrap(),
+        };
+        // the children of index for variable; a skipped variable is free in a BDD and false in a ZDD.
+        let cofactors = |index:NodeIndex<A,M>| if level(index)==Some(variable) { let node = self.node(index.address); (node.lo,node.hi) } else if BDD { (index,index) } else { (index,NodeIndex::FALSE) };
+        let (f_lo,f_hi) = cofactors(f);
+        let (g_lo,g_hi) = cofactors(g);
+        let res = self.implies::<BDD>(f_lo,g_lo,memo) && self.implies::<BDD>(f_hi,g_hi,memo);
+        memo.insert(key,res);
+        res
+    }
+
+    /// The lo and hi children of index for variable, which must not be after index's variable, with index's multiplicity included.
+    /// If index does not test variable, it is both children for a BDD, while for a ZDD the variable must be false.
+    fn cofactors<const BDD:bool>(&self, index: NodeIndex<A,M>, variable:VariableIndex) -> (NodeIndex<A,M>,NodeIndex<A,M>) {
+        if !index.is_sink() {
+            let node = self.node_incorporating_multiplicity(index);
+            if node.variable==variable { return (node.lo,node.hi); }
+        }
+        if BDD { (index,index) } else { (index,NodeIndex::FALSE) }
+    }
+
+    /// Whether f is unchanged by swapping variables i and j, where i<j. That is, whether f with i false and j true is the same as
+    /// f with i true and j false. This reads the nodes without making any, in a traversal down to level i and then a traversal
+    /// of pairs of subdiagrams between levels i and j, remembering the pairs already compared.
+    fn symmetric_in<const BDD:bool>(&self, f: NodeIndex<A,M>, i:VariableIndex, j:VariableIndex) -> bool {
+        assert!(i<j,"The variables should be in order");
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { None } else { Some(self.node(index.address).variable) };
+        let same = |a:NodeIndex<A,M>,b:NodeIndex<A,M>| a==b || (a.is_false() && b.is_false());
+        // whether g with j true is the same as h with j false.
+        let mut pair_memo = HashMap::<(NodeIndex<A,M>,NodeIndex<A,M>),bool>::new();
+        let pairs = |g:NodeIndex<A,M>,h:NodeIndex<A,M>| {
+            let mut stack = vec![(g,h)];
+            while let Some((g,h)) = stack.pop() {
+                if pair_memo.contains_key(&(g,h)) { continue; }
+                let variable = [level(g),level(h)].into_iter().flatten().min().filter(|&v|v<j);
+                let ok = match variable {
+                    None => same(self.cofactors::<BDD>(g,j).1,self.cofactors::<BDD>(h,j).0),
+                    Some(v) => {
+                        let ((g0,g1),(h0,h1)) = (self.cofactors::<BDD>(g,v),self.cofactors::<BDD>(h,v));
+                        match (pair_memo.get(&(g0,h0)),pair_memo.get(&(g1,h1))) {
+                            (Some(&false),_) | (_,Some(&false)) => false,
+                            (Some(&true),Some(&true)) => true,
+                            (done0,done1) => { // work out the children first, then come back.
+                                stack.push((g,h));
+                                if done0.is_none() { stack.push((g0,h0)); }
+                                if done1.is_none() { stack.push((g1,h1)); }
+                                continue;
+                            }
+                        }
+                    }
+                };
+                pair_memo.insert((g,h),ok);
+            }
+            pair_memo[&(g,h)]
+        };
+        self.every_cofactor_pair::<BDD>(f,i,pairs)
+    }
+
+    /// Whether f is positive unate (monotone increasing) in variable if positive, or negative unate if not: whether every solution
+    /// with variable false remains one when it is made true, or vice versa. Multiplicities are ignored. The two cofactors of each
+    /// subdiagram reaching variable are compared with [XDDBase::implies], sharing its memo, so no nodes are made.
+    fn unate_in<const BDD:bool>(&self, f: NodeIndex<A,M>, variable:VariableIndex, positive:bool) -> bool {
+        let mut memo = HashMap::new();
+        self.every_cofactor_pair::<BDD>(f,variable,|lo,hi|if positive { self.implies::<BDD>(lo,hi,&mut memo) } else { self.implies::<BDD>(hi,lo,&mut memo) })
+    }
+
+    /// Whether check(lo,hi) is true for each way of reaching variable from f, where lo and hi are the cofactors for variable of
+    /// the subdiagram reached (see [XDDBase::cofactors]), up to a constant multiple. Stops at the first that is false. No nodes are made.
+    fn every_cofactor_pair<const BDD:bool>(&self, f: NodeIndex<A,M>, variable:VariableIndex, mut check:impl FnMut(NodeIndex<A,M>,NodeIndex<A,M>)->bool) -> bool {
+        let mut visited : HashSet<A> = HashSet::new();
+        let mut stack = vec![f.address];
+        while let Some(address) = stack.pop() {
+            if !visited.insert(address) { continue; }
+            let index = NodeIndex{address,multiplicity:M::ONE};
+            if !index.is_sink() && self.node(address).variable<variable {
+                let node = self.node(address);
+                stack.push(node.hi.address);
+                stack.push(node.lo.address);
+            } else {
+                let (lo,hi) = self.cofactors::<BDD>(index,variable);
+                if !check(lo,hi) { return false; }
+            }
+        }
+        true
+    }
+
+    /// The variables that f depends on, in increasing order, found in one pass over the nodes reachable from f.
+    ///
+    /// For a BDD these are just the variables of those nodes. For a ZDD a node whose children are the same does not
+    /// depend on its variable, while an edge that skips a variable, other than to FALSE, requires it to be false, so does depend on it.
+    fn dependent_variables<const BDD:bool>(&self, f: NodeIndex<A,M>, num_variables:u16) -> Vec<VariableIndex> {
+        let mut dependent = vec![false;num_variables as usize];
+        // for a ZDD, skipped[v] is the number of edges that start skipping at v less the number that stop.
+        let mut skipped = vec![0isize;num_variables as usize+1];
+        let level = |index:NodeIndex<A,M>| if index.is_sink() { num_variables } else { self.node(index.address).variable.0 };
+        let mut skip = |from:u16,to:NodeIndex<A,M>| if !to.is_false() { skipped[from as usize]+=1; skipped[level(to) as usize]-=1; };
+        if !BDD { skip(0,f); }
+        for address in self.post_order(f) {
+            let node = self.node(address);
+            if BDD || node.lo!=node.hi { dependent[node.variable.0 as usize]=true; }
+            if !BDD {
+                skip(node.variable.0+1,node.lo);
+                skip(node.variable.0+1,node.hi);
+            }
+        }
+        let mut skipping = 0;
+        for v in 0..num_variables as usize {
+            skipping+=skipped[v];
+            if skipping>0 { dependent[v]=true; }
+        }
+        (0..num_variables).filter(|&v|dependent[v as usize]).map(VariableIndex).collect()
+    }
+
+    /// Partition the variables into classes that f is symmetric in, each in increasing order. As symmetry is transitive,
+    /// each variable only needs comparing with the first of each class.
+    fn symmetric_classes<const BDD:bool>(&self, f: NodeIndex<A,M>, num_variables:u16) -> Vec<Vec<VariableIndex>> {
+        let mut classes : Vec<Vec<VariableIndex>> = vec![];
+        for variable in (0..num_variables).map(VariableIndex) {
+            match classes.iter_mut().find(|class|self.symmetric_in::<BDD>(f,class[0],variable)) {
+                Some(class) => class.push(variable),
+                None => classes.push(vec![variable]),
+            }
+        }
+        classes
+    }
+
+    /// Copy the nodes reachable from root in other, which may have different address and multiplicity types, into this node store.
+    /// Converting multiplicities may make a node redundant (such as when they are dropped), so nodes are remade rather than copied.
+    fn import_bdd<A2:NodeAddress,M2:Multiplicity,X2:XDDBase<A2,M2>>(&mut self, other:&X2, root: NodeIndex<A2,M2>) -> NodeIndex<A,M> where M:From<M2> { self.import::<A2,M2,X2,true>(other,root) }
+    fn import_zdd<A2:NodeAddress,M2:Multiplicity,X2:XDDBase<A2,M2>>(&mut self, other:&X2, root: NodeIndex<A2,M2>) -> NodeIndex<A,M> where M:From<M2> { self.import::<A2,M2,X2,false>(other,root) }
+    fn import<A2:NodeAddress,M2:Multiplicity,X2:XDDBase<A2,M2>,const BDD:bool>(&mut self, other:&X2, root: NodeIndex<A2,M2>) -> NodeIndex<A,M> where M:From<M2> {
+        let mut imported : HashMap<A2,NodeIndex<A,M>> = HashMap::new();
+        let convert = |imported:&HashMap<A2,NodeIndex<A,M>>,index:NodeIndex<A2,M2>| {
+            if index.is_false() { NodeIndex::FALSE }
+            else if index.is_true() { NodeIndex{ address: A::TRUE, multiplicity: M::from(index.multiplicity) } }
+            else { imported[&index.address].multiply(M::from(index.multiplicity)) }
+        };
+        for address in other.post_order(root) {
+            let node = other.node(address);
+            let lo = convert(&imported,node.lo);
+            let hi = convert(&imported,node.hi);
+            let res = if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { self.add_node_if_not_present(Node{variable:node.variable,lo,hi}) };
+            imported.insert(address,res);
+        }
+        convert(&imported,root)
+    }
+
+    /// Copy the nodes reachable from root in other, a BDD if TO_BDD is false or a ZDD if TO_BDD is true, into this node store
+    /// as a diagram of the other type representing the same function. A variable skipped by an edge means it does not matter
+    /// in a BDD, but that it is false in a ZDD, so nodes are added for each skipped variable before being reduced.
+    fn convert_from<A2:NodeAddress,M2:Multiplicity,X2:XDDBase<A2,M2>,const TO_BDD:bool>(&mut self, other:&X2, root: NodeIndex<A2,M2>, num_variables:u16) -> NodeIndex<A,M> where M:From<M2> {
+        let mut converted : HashMap<A2,NodeIndex<A,M>> = HashMap::new();
+        // The diagram for index, given that the variables before from have been decided.
+        let convert = |s:&mut Self,converted:&HashMap<A2,NodeIndex<A,M>>,index:NodeIndex<A2,M2>,from:u16| {
+            if index.is_false() { return NodeIndex::FALSE; }
+            let (mut res,upto) = if index.is_true() { (NodeIndex::TRUE,num_variables) } else { (converted[&index.address],other.node(index.address).variable.0) };
+            for v in (from..upto).rev() {
+                let hi = if TO_BDD { NodeIndex::FALSE } else { res };
+                res = s.add_node_if_not_present(Node{variable:VariableIndex(v),lo:res,hi});
+            }
+            res.multiply(M::from(index.multiplicity))
+        };
+        for address in other.post_order(root) {
+            let node = other.node(address);
+            let lo = convert(self,&converted,node.lo,node.variable.0+1);
+            let hi = convert(self,&converted,node.hi,node.variable.0+1);
+            let res = if (TO_BDD && lo==hi) || (!TO_BDD && hi.is_false()) { lo } else { self.add_node_if_not_present(Node{variable:node.variable,lo,hi}) };
+            converted.insert(address,res);
+        }
+        convert(self,&converted,root,0)
+    }
+
+    /// The paths from root to TRUE, each as the value of every variable: Some(value) if the path fixes it, or None
+    /// if it does not matter. Variables skipped in a BDD do not matter; in a ZDD they are false, so every variable is fixed.
+    /// Paths come in the same order as [Solutions]. There may be exponentially many, so this is for small diagrams.
+    fn cubes<const BDD:bool>(&self, root: NodeIndex<A,M>, num_variables:u16) -> Vec<Vec<Option<bool>>> {
+        let mut res = vec![];
+        let mut stack = vec![(root,0,vec![None;num_variables as usize])];
+        while let Some((index,level,mut cube)) = stack.pop() {
+            if index.is_false() { continue; }
+            let variable = if index.is_true() { num_variables } else { self.node(index.address).variable.0 };
+            if !BDD { for v in level..variable { cube[v as usize]=Some(false); } }
+            if index.is_true() { res.push(cube); continue; }
+            let node = self.node(index.address);
+            let mut hi_cube = cube.clone();
+            hi_cube[variable as usize]=Some(true);
+            cube[variable as usize]=Some(false);
+            stack.push((node.hi,variable+1,hi_cube));
+            stack.push((node.lo,variable+1,cube));
+        }
+        res
+    }
+
+    /// Check that the diagram reachable from root is one the operations could have produced: every address has a node,
+    /// variables are less than num_variables and increase along every edge, and there are no redundant (per the BDD or
+    /// ZDD rule) or duplicate nodes or multiplicities with a common factor. A diagram built by hand with add_node may break
+    /// these rules, and then counts and other operations give wrong answers rather than failing; see [XDDBase::reduce]
+    /// to repair all but the first two. Every problem found is reported.
+    fn validate<const BDD:bool>(&self, root: NodeIndex<A,M>, num_variables:u16) -> Result<(),InvalidDiagram<A>> {
+        let mut problems = vec![];
+        let limit = self.len()+2;
+        // whether an edge to index, from parent, is to a node that exists.
+        let check_edge = |problems:&mut Vec<DiagramProblem<A>>,parent:Option<A>,index:NodeIndex<A,M>| {
+            let exists = index.address.as_usize()<limit;
+            if !exists { problems.push(DiagramProblem::DanglingAddress{parent,child:index.address}); }
+            exists
+        };
+        let mut seen : HashMap<Node<A,M>,A> = HashMap::new();
+        let mut visited = vec![false;limit];
+        let mut stack = vec![];
+        if root.is_false() && root!=NodeIndex::FALSE && !M::MULTIPLICITIES_IRRELEVANT { problems.push(DiagramProblem::UncanonicalMultiplicity{parent:None}); }
+        if check_edge(&mut problems,None,root) && !root.is_sink() { stack.push(root.address); }
+        while let Some(address) = stack.pop() {
+            if std::mem::replace(&mut visited[address.as_usize()],true) { continue; }
+            let node = self.node(address);
+            if node.variable.0>=num_variables { problems.push(DiagramProblem::VariableOutOfRange{address,variable:node.variable}); }
+            if (BDD && node.lo==node.hi) || (!BDD && node.hi.is_false()) { problems.push(DiagramProblem::Redundant{address}); }
+            if !M::MULTIPLICITIES_IRRELEVANT { // as made by add_node_if_not_present.
+                let canonical = if node.hi.is_false() || node.lo.is_false() { (M::ONE,M::ONE) } else {
+                    let (lo,hi,_) = M::gcd(node.lo.multiplicity,node.hi.multiplicity);
+                    (lo,hi)
+                };
+                if canonical!=(node.lo.multiplicity,node.hi.multiplicity) { problems.push(DiagramProblem::UncanonicalMultiplicity{parent:Some(address)}); }
+            }
+            if let Some(&duplicate_of) = seen.get(&node) { problems.push(DiagramProblem::Duplicate{address,duplicate_of}); }
+            else { seen.insert(node,address); }
+            for child in [node.hi,node.lo] {
+                if check_edge(&mut problems,Some(address),child) && !child.is_sink() {
+                    if self.node(child.address).variable<=node.variable { problems.push(DiagramProblem::OutOfOrder{parent:address,child:child.address}); }
+                    stack.push(child.address);
+                }
+            }
+        }
+        if problems.is_empty() { Ok(()) } else { Err(InvalidDiagram{problems}) }
+    }
+
+    /// The canonical equivalent of root, which may have been built by hand with add_node, or read from a file, and so
+    /// have redundant nodes (for a BDD both children the same, for a ZDD hi FALSE), duplicate nodes, or multiplicities
+    /// with a common factor. These are all removed, so equal functions give equal results, as from the factory operations.
+    fn reduce_bdd(&mut self, root: NodeIndex<A,M>) -> NodeIndex<A,M> { self.reduce::<true>(root) }
+    fn reduce_zdd(&mut self, root: NodeIndex<A,M>) -> NodeIndex<A,M> { self.reduce::<false>(root) }
+    fn reduce<const BDD:bool>(&mut self, root: NodeIndex<A,M>) -> NodeIndex<A,M> {
+        let mut reduced : HashMap<A,NodeIndex<A,M>> = HashMap::new();
+        let convert = |reduced:&HashMap<A,NodeIndex<A,M>>,index:NodeIndex<A,M>| {
+            if index.is_sink() { return if index.is_false() { NodeIndex::FALSE } else { index }; }
+            let res : NodeIndex<A,M> = reduced[&index.address];
+            if res.is_false() { NodeIndex::FALSE } else { res.multiply(index.multiplicity) } // the multiplicity of FALSE is irrelevant.
+        };
+        for address in self.post_order(root) {
+            let node = self.node(address);
+            let lo = convert(&reduced,node.lo);
+            let hi = convert(&reduced,node.hi);
+            let res = if (BDD && lo==hi) || (!BDD && hi.is_false()) { lo } else { self.add_node_if_not_present(Node{variable:node.variable,lo,hi}) };
+            reduced.insert(address,res);
+        }
+        convert(&reduced,root)
+    }
+
+    /// The number of nodes at each level, and other measures of the shape, of the diagram reachable from index.
+    /// See [crate::DecisionDiagramFactory::profile].
+    fn profile(&self, index: NodeIndex<A,M>, num_variables:u16) -> DiagramProfile {
+        let mut res = DiagramProfile{ nodes_per_level: vec![0;num_variables as usize], ..DiagramProfile::default() };
+        let mut path_length = vec![0;index.address.as_usize()+1]; // the longest path from each node to a sink, in nodes.
+        for address in self.post_order(index) {
+            let node = self.node(address);
+            res.nodes_per_level[node.variable.0 as usize]+=1;
+            res.edges+=[node.lo,node.hi].iter().filter(|child|!child.is_false()).count();
+            path_length[address.as_usize()] = 1+path_length[node.lo.address.as_usize()].max(path_length[node.hi.address.as_usize()]);
+        }
+        res.width = res.nodes_per_level.iter().copied().max().unwrap_or(0);
+        res.longest_path = path_length[index.address.as_usize()];
+        res
+    }
+
+    /// Do garbage collection. Provide the items one wants to keep, and get rid of anything not in the transitive dependencies of keep.
+    /// Returns a renamer from old nodes to new nodes.
+    fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A>;
+
+    fn make_dot_file<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F) -> std::io::Result<()> {
+        self.make_dot_file_with_options(writer,name,start_nodes,namer,&DotOptions::default())
+    }
+
+    /// Like make_dot_file, but with options to make large diagrams readable. See [DotOptions].
+    fn make_dot_file_with_options<W:Write,F:Fn(VariableIndex)->String>(&self, writer:&mut W, name:impl Display, start_nodes:&[(NodeIndex<A,M>, Option<String>)], namer:F, options:&DotOptions) -> std::io::Result<()> {
+        //let namer = |i:VariableIndex| i.to_string();
+        fn munge_label(s:&str) -> String { // see if html label.
+            if s.starts_with('<') && s.ends_with('>') {s.to_string()} else { format!("\"{}\"",s) }
+        }
+        writeln!(writer,"digraph {} {{",name)?;
         let mut pending = Vec::new();
         for (entry_index,(node,nlabel)) in start_nodes.iter().enumerate() {
             writeln!(writer,"  e{} -> n{} [label=\"{}\"]",entry_index,node.address,node.multiplicity)?;
@@ -460,17 +2716,46 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
             }
         }
         let mut done : HashSet<A> = Default::default();
+        let mut drawn : usize = 0;
+        let mut by_variable : BTreeMap<VariableIndex,Vec<A>> = BTreeMap::new();
         while let Some(index)=pending.pop() {
             if !(index.is_sink() || done.contains(&index)) {
+                done.insert(index);
+                if options.max_nodes.is_some_and(|max|drawn>=max) {
+                    writeln!(writer,"  n{} [label=\"…\", shape=none];",index)?;
+                    continue;
+                }
+                drawn+=1;
                 let node = self.node(index);
+                if options.rank_by_variable { by_variable.entry(node.variable).or_default().push(index); }
+                if options.collapse_dont_care_chains && node.lo==node.hi {
+                    // find the end of the chain of nodes whose lo and hi are the same.
+                    let mut last = node;
+                    let mut multiplicity = node.lo.multiplicity;
+                    while !last.lo.is_sink() {
+                        let next = self.node(last.lo.address);
+                        if next.lo!=next.hi { break; }
+                        multiplicity = M::multiply(multiplicity,next.lo.multiplicity);
+                        last = next;
+                    }
+                    if last.variable!=node.variable {
+                        let label = format!("{} … {}",namer(node.variable),namer(last.variable));
+                        writeln!(writer,"  n{} [label={}, xlabel={}, shape=box, style=rounded];",index,munge_label(&label),index)?;
+                        writeln!(writer,"  n{} -> n{} [style=bold,label=\"{}\"];",index,last.lo.address,multiplicity)?;
+                        pending.push(last.lo.address);
+                        continue;
+                    }
+                }
                 writeln!(writer,"  n{} [label={}, xlabel={}];",index,munge_label(&namer(node.variable)),index)?;
                 writeln!(writer,"  n{} -> n{} [style=dotted,label=\"{}\"];",index,node.lo.address,node.lo.multiplicity)?;
                 writeln!(writer,"  n{} -> n{} [label=\"{}\"];",index,node.hi.address,node.hi.multiplicity)?;
-                done.insert(index);
                 pending.push(node.lo.address);
                 pending.push(node.hi.address);
             }
         }
+        for nodes in by_variable.values() {
+            writeln!(writer,"  {{ rank=same; {} }}",nodes.iter().map(|n|format!("n{};",n)).collect::<Vec<_>>().join(" "))?;
+        }
         writeln!(writer,"  n0 [label=\"0\",shape=box]")?;
         writeln!(writer,"  n1 [label=\"1\",shape=box]")?;
         writeln!(writer,"}}")?;
@@ -481,12 +2766,16 @@ pub trait XDDBase<A:NodeAddress,M:Multiplicity> {
 
 
 /// A list of all the nodes.
-/// This is a compact representation of nodes that is all that is needed to serialize/deserialize,
-/// although it is not ideal for many operations that need hash table look-ups.
+/// This is a compact representation of nodes that is all that is needed to serialize/deserialize
+/// (see [crate::serialization]), although it is not ideal for many operations that need hash table look-ups.
 /// In particular find_node_index is slow.
 ///
 /// Note that the two special indices are not explicitly stored.
+///
+/// With the `serde` feature it can be serialized as the list of its nodes. A deserialized
+/// list is not checked, unlike one loaded with [NodeList::load]; use [XDDBase::validate] if it came from somewhere untrusted.
 #[derive(Clone,Eq, PartialEq)]
+#[cfg_attr(feature="serde",derive(serde::Serialize,serde::Deserialize))]
 pub struct NodeList<A:NodeAddress,M:Multiplicity> {
     pub(crate) nodes : Vec<Node<A,M>>,
 }
@@ -504,30 +2793,20 @@ impl <A:NodeAddress,M:Multiplicity> XDDBase<A,M> for NodeList<A,M> {
     }
     fn add_node(&mut self, node: Node<A,M>) -> A {
         self.nodes.push(node);
-        (1+self.nodes.len()).try_into().map_err(|_|()).unwrap()
+        (1+self.nodes.len()).try_into().map_err(|_|()).expect("Too many nodes for the NodeAddress type")
     }
 
     fn len(&self) -> usize { self.nodes.len() }
+    fn memory_usage(&self) -> usize { self.nodes.capacity()*size_of::<Node<A,M>>() }
+    fn shrink_to_fit(&mut self) { self.nodes.shrink_to_fit(); }
+    fn reserve(&mut self, additional: usize) { self.nodes.reserve(additional); }
 
     /// Do garbage collection. Provide the items one wants to keep, and get rid of anything not in the transitive dependencies of keep.
     /// Returns a renamer such that v[old_node.0] is what v maps in to. If nothing, then map into NodeIndex::JUNK.
     fn gc(&mut self, keep:impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
         // First pass. Use map to say what to keep, use A::FALSE as a placeholder meaning the address is not used, and A::TRUE as a placeholder meaning the address is used.
-        let mut map : Vec<A> = vec![A::FALSE;self.len()+2];
-        fn do_keep<A:NodeAddress,M:Multiplicity>(nodes:&Vec<Node<A,M>>, map:&mut Vec<A>, n: NodeIndex<A,M>) {
-            let address = n.address.as_usize();
-            if map[address]!=A::TRUE {
-                map[address]=A::TRUE;
-                let node = nodes[address-2];
-                do_keep(nodes,map,node.lo);
-                do_keep(nodes,map,node.hi);
-            }
-        }
-        map[0]=A::TRUE; // FALSE
-        map[1]=A::TRUE; // TRUE
-        for k in keep.into_iter() {
-            do_keep(&self.nodes,&mut map,k);
-        }
+        let mut map : Vec<A> = self.reachable(keep).into_iter().map(|r|if r { A::TRUE } else { A::FALSE }).collect();
+        map.resize(self.len()+2,A::FALSE);
         // Now set values to actual values rather than dummy boolean placeholders.
         map[0]=A::FALSE;
         map[1]=A::TRUE;
@@ -542,7 +2821,7 @@ impl <A:NodeAddress,M:Multiplicity> XDDBase<A,M> for NodeList<A,M> {
                     lo: NodeIndex { address: map[old_node.lo.address.as_usize()], multiplicity:old_node.lo.multiplicity},
                     hi: NodeIndex { address: map[old_node.hi.address.as_usize()], multiplicity:old_node.hi.multiplicity},
                 };
-                len=len+1;
+                len+=1;
             }
         }
         self.nodes.truncate(len);
@@ -554,15 +2833,35 @@ impl <A:NodeAddress,M:Multiplicity> XDDBase<A,M> for NodeList<A,M> {
 
 /// An extension to NodeList that contains a cache from nodes to indices that is constantly
 /// kept up to date.
-#[derive(Clone,Eq, PartialEq)]
+#[derive(Clone)]
 pub struct NodeListWithFastLookup<A:NodeAddress,M:Multiplicity> {
     pub(crate) nodes : NodeList<A,M>,
     pub(crate) node_to_index : HashMap<Node<A,M>,A>,
+    /// If set, running out of addresses sets capacity_exceeded rather than panicking. See [crate::DecisionDiagramFactory::try_operation].
+    pub(crate) fallible : bool,
+    /// Set when a node could not be added while fallible. Results computed since are meaningless.
+    pub(crate) capacity_exceeded : bool,
+    pub(crate) monitor : Monitor,
 }
 
+/// Equal if they have the same nodes; the lookup table follows from them.
+impl <A:NodeAddress,M:Multiplicity> PartialEq for NodeListWithFastLookup<A,M> {
+    fn eq(&self, other: &Self) -> bool { self.nodes==other.nodes }
+}
+
+impl <A:NodeAddress,M:Multiplicity> Eq for NodeListWithFastLookup<A,M> {}
+
 impl <A:NodeAddress,M:Multiplicity> Default for NodeListWithFastLookup<A,M> {
     fn default() -> Self {
-        NodeListWithFastLookup{ nodes: NodeList::default(), node_to_index: Default::default() }
+        NodeListWithFastLookup{ nodes: NodeList::default(), node_to_index: Default::default(), fallible: false, capacity_exceeded: false, monitor: Default::default() }
+    }
+}
+
+impl <A:NodeAddress,M:Multiplicity> From<NodeList<A,M>> for NodeListWithFastLookup<A,M> {
+    /// Build the lookup table for an existing list of nodes.
+    fn from(nodes: NodeList<A,M>) -> Self {
+        let node_to_index = nodes.nodes.iter().enumerate().map(|(i,node)|(*node,(i+2).try_into().map_err(|_|()).unwrap())).collect();
+        NodeListWithFastLookup{nodes,node_to_index,fallible:false,capacity_exceeded:false,monitor:Default::default()}
     }
 }
 
@@ -572,12 +2871,31 @@ impl <A:NodeAddress,M:Multiplicity> XDDBase<A,M> for NodeListWithFastLookup<A,M>
         self.node_to_index.get(&node).cloned()
     }
 
+    /// If fallible and there is no address for the new node, nothing is added and FALSE is returned as a placeholder.
+    /// If fallible and cancelled, unwind back to [crate::DecisionDiagramFactory::try_operation].
     fn add_node(&mut self, node: Node<A,M>) -> A {
+        if self.fallible {
+            if self.monitor.is_cancelled() { std::panic::resume_unwind(Box::new(Cancelled)); }
+            if A::try_from(2+self.nodes.len()).is_err() {
+                self.capacity_exceeded=true;
+                return A::FALSE;
+            }
+        }
         let res = self.nodes.add_node(node);
         self.node_to_index.insert(node, res);
+        self.monitor.node_made(node.variable.0,self.nodes.len());
         res
     }
     fn len(&self) -> usize { self.nodes.len() }
+    fn memory_usage(&self) -> usize { self.nodes.memory_usage()+hash_map_memory(&self.node_to_index) }
+    fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.node_to_index.shrink_to_fit();
+    }
+    fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+        self.node_to_index.reserve(additional);
+    }
 
     fn gc(&mut self, keep: impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
         let map = self.nodes.gc(keep);
@@ -588,3 +2906,133 @@ impl <A:NodeAddress,M:Multiplicity> XDDBase<A,M> for NodeListWithFastLookup<A,M>
         map
     }
 }
+
+/// The approximate number of bytes of heap memory used by a HashMap, which has one byte of control data per bucket
+/// and keeps at least 1/8 of the buckets empty.
+pub(crate) fn hash_map_memory<K,V>(map:&HashMap<K,V>) -> usize {
+    if map.capacity()==0 { 0 } else { (map.capacity()*8/7).next_power_of_two()*(size_of::<(K,V)>()+1) }
+}
+
+/// Which of num_shards parts of a table split by hash a value belongs in.
+pub(crate) fn shard_for<T:Hash>(value:&T,num_shards:usize) -> usize {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    value.hash(&mut hasher);
+    (std::hash::Hasher::finish(&hasher) as usize)%num_shards
+}
+
+/// A node store for very large diagrams, such as more than 4 billion nodes with u64 addresses.
+///
+/// Like [NodeListWithFastLookup], but the nodes are kept in fixed size chunks rather than one Vec, and the lookup
+/// table is split into shards by hash. Growing therefore never needs to copy all the existing nodes, or to find
+/// a contiguous block of memory for all of them. This is slightly slower than [NodeListWithFastLookup] for small diagrams.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use xdd::{NoMultiplicity, VariableIndex};
+/// use xdd::xdd_with_multiplicity::{ChunkedNodeList, XDDBase};
+/// let mut nodes = ChunkedNodeList::<u64,NoMultiplicity>::with_chunk_size(4);
+/// let variables : Vec<VariableIndex> = (0..10).map(VariableIndex).collect();
+/// let f = nodes.exactly_n_of_zdd(&variables,3,10);
+/// let g = nodes.exactly_n_of_zdd(&variables,5,10);
+/// let h = nodes.sum_zdd(f,g,&mut HashMap::new());
+/// assert_eq!(120+252,nodes.number_solutions_zdd::<u64>(h,10));
+/// let renaming = nodes.gc([h]);
+/// let h = renaming.rename(h).unwrap();
+/// assert_eq!(120+252,nodes.number_solutions_zdd::<u64>(h,10));
+/// ```
+#[derive(Clone)]
+pub struct ChunkedNodeList<A:NodeAddress,M:Multiplicity> {
+    chunks : Vec<Vec<Node<A,M>>>,
+    chunk_size : usize,
+    len : usize,
+    lookup : Vec<HashMap<Node<A,M>,A>>,
+}
+
+impl <A:NodeAddress,M:Multiplicity> ChunkedNodeList<A,M> {
+    /// The default number of nodes in each chunk.
+    pub const DEFAULT_CHUNK_SIZE : usize = 1<<20;
+    /// The number of separate hash tables for looking up nodes.
+    const LOOKUP_SHARDS : usize = 256;
+
+    /// Make an empty store with the given number of nodes in each chunk.
+    pub fn with_chunk_size(chunk_size:usize) -> Self {
+        assert!(chunk_size>0,"chunk size must be positive");
+        ChunkedNodeList{ chunks: vec![], chunk_size, len: 0, lookup: (0..Self::LOOKUP_SHARDS).map(|_|HashMap::new()).collect() }
+    }
+
+    fn shard(&self,node:&Node<A,M>) -> usize { shard_for(node,Self::LOOKUP_SHARDS) }
+
+    /// The node at position i (that is, address i+2).
+    fn at(&self,i:usize) -> &Node<A,M> { &self.chunks[i/self.chunk_size][i%self.chunk_size] }
+    fn at_mut(&mut self,i:usize) -> &mut Node<A,M> { &mut self.chunks[i/self.chunk_size][i%self.chunk_size] }
+
+    fn rebuild_lookup(&mut self) {
+        for shard in &mut self.lookup { *shard=HashMap::new(); }
+        for i in 0..self.len {
+            let node = *self.at(i);
+            let shard = self.shard(&node);
+            self.lookup[shard].insert(node,(i+2).try_into().map_err(|_|()).unwrap());
+        }
+    }
+}
+
+impl <A:NodeAddress,M:Multiplicity> Default for ChunkedNodeList<A,M> {
+    fn default() -> Self { Self::with_chunk_size(Self::DEFAULT_CHUNK_SIZE) }
+}
+
+impl <A:NodeAddress,M:Multiplicity> XDDBase<A,M> for ChunkedNodeList<A,M> {
+    fn node(&self, index: A) -> Node<A,M> { *self.at(index.as_usize()-2) }
+    fn find_node_index(&self, node: Node<A,M>) -> Option<A> {
+        self.lookup[self.shard(&node)].get(&node).cloned()
+    }
+    fn add_node(&mut self, node: Node<A,M>) -> A {
+        if self.len==self.chunks.len()*self.chunk_size { self.chunks.push(Vec::with_capacity(self.chunk_size)); }
+        self.chunks.last_mut().unwrap().push(node);
+        self.len+=1;
+        let res : A = (1+self.len).try_into().map_err(|_|()).expect("Too many nodes for the NodeAddress type");
+        let shard = self.shard(&node);
+        self.lookup[shard].insert(node,res);
+        res
+    }
+    fn len(&self) -> usize { self.len }
+    fn memory_usage(&self) -> usize {
+        self.chunks.iter().map(|chunk|chunk.capacity()*size_of::<Node<A,M>>()).sum::<usize>()+self.chunks.capacity()*size_of::<Vec<Node<A,M>>>()
+            +self.lookup.iter().map(hash_map_memory).sum::<usize>()+self.lookup.capacity()*size_of::<HashMap<Node<A,M>,A>>()
+    }
+    /// Chunks are always allocated whole, so the last one is not shrunk.
+    fn shrink_to_fit(&mut self) {
+        self.chunks.shrink_to_fit();
+        for shard in &mut self.lookup { shard.shrink_to_fit(); }
+    }
+    /// Chunks are still only allocated when needed; this just makes room in the lookup tables.
+    fn reserve(&mut self, additional: usize) {
+        self.chunks.reserve((self.len+additional).div_ceil(self.chunk_size).saturating_sub(self.chunks.len()));
+        for shard in &mut self.lookup { shard.reserve(additional.div_ceil(Self::LOOKUP_SHARDS)); }
+    }
+
+    /// Like [NodeList::gc], compacting the nodes in place and freeing chunks no longer needed.
+    fn gc(&mut self, keep: impl IntoIterator<Item=NodeIndex<A,M>>) -> NodeRenaming<A> {
+        let reachable = self.reachable(keep);
+        let mut map : Vec<A> = vec![A::FALSE;self.len+2];
+        map[1]=A::TRUE;
+        let mut len:usize = 0;
+        for i in 2..reachable.len() {
+            if reachable[i] {
+                map[i]=(len+2).try_into().map_err(|_|()).unwrap();
+                let old_node = *self.at(i-2);
+                *self.at_mut(len) = Node {
+                    variable: old_node.variable,
+                    lo: NodeIndex { address: map[old_node.lo.address.as_usize()], multiplicity:old_node.lo.multiplicity},
+                    hi: NodeIndex { address: map[old_node.hi.address.as_usize()], multiplicity:old_node.hi.multiplicity},
+                };
+                len+=1;
+            }
+        }
+        self.len=len;
+        self.chunks.truncate(len.div_ceil(self.chunk_size));
+        if let Some(last) = self.chunks.last_mut() { last.truncate(len-(len-1)/self.chunk_size*self.chunk_size); }
+        self.rebuild_lookup();
+        NodeRenaming(map)
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)] // truth tables read more clearly as assert_eq!(bool,...)
+
 use std::collections::HashMap;
 //use std::fs::File;
 use xdd::{NodeIndex, NoMultiplicity, VariableIndex};
@@ -106,12 +108,14 @@ fn zdd_basic_ops<F:XDDBase<usize, NoMultiplicity>+Default>() {
     assert_eq!(or_v0_v1,factory.sum_zdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
 
     let xor_v0_v1 = factory.exactly_one_of_zdd(&[VariableIndex(0),VariableIndex(1)],2);
-    assert_eq!(6,factory.len());
+    assert_eq!(5,factory.len());
     assert_eq!(false,factory.evaluate_zdd(xor_v0_v1,&[false,false]));
     assert_eq!(true,factory.evaluate_zdd(xor_v0_v1,&[true,false]));
     assert_eq!(true,factory.evaluate_zdd(xor_v0_v1,&[false,true]));
     assert_eq!(false,factory.evaluate_zdd(xor_v0_v1,&[true,true]));
 
+    assert_eq!(xor_v0_v1,factory.xor_zdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
+    assert_eq!(NodeIndex::FALSE,factory.xor_zdd(or_v0_v1,or_v0_v1,&mut HashMap::new()));
 }
 
 
@@ -123,6 +127,7 @@ fn multiplicities_zdd_with_lookup() { multiplicities_zdd_basic_ops::<NodeListWit
 fn multiplicities_zdd_basic_ops<F:XDDBase<usize, u32>+Default>() {
     let mut factory = F::default();
     assert_eq!(0, factory.len());
+    assert_eq!(NodeIndex::FALSE, factory.sum_zdd(NodeIndex::FALSE, NodeIndex::FALSE, &mut HashMap::new()));
 
     let v0 = factory.single_variable_zdd(VariableIndex(0),2);
     assert_eq!(2, factory.len());
@@ -224,12 +229,14 @@ fn multiplicities_zdd_basic_ops<F:XDDBase<usize, u32>+Default>() {
     assert_ne!(or_v0_v1,factory.sum_zdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
 
     let xor_v0_v1 = factory.exactly_one_of_zdd(&[VariableIndex(0),VariableIndex(1)],2);
-    assert_eq!(8,factory.len());
+    assert_eq!(7,factory.len());
     assert_eq!(false,factory.evaluate_zdd(xor_v0_v1,&[false,false]));
     assert_eq!(true,factory.evaluate_zdd(xor_v0_v1,&[true,false]));
     assert_eq!(true,factory.evaluate_zdd(xor_v0_v1,&[false,true]));
     assert_eq!(false,factory.evaluate_zdd(xor_v0_v1,&[true,true]));
 
+    assert_eq!(xor_v0_v1,factory.xor_zdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
+    assert_eq!(NodeIndex::FALSE,factory.xor_zdd(or_v0_v1,or_v0_v1,&mut HashMap::new()));
 }
 
 #[test]
@@ -341,6 +348,8 @@ fn bdd_basic_ops<F:XDDBase<usize, NoMultiplicity>+Default>() {
     assert_eq!(or_v0_v1,factory.sum_bdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
 
     let xor_v0_v1 = factory.exactly_one_of_bdd(&[VariableIndex(0),VariableIndex(1)]);
+    assert_eq!(xor_v0_v1,factory.xor_bdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
+    assert_eq!(NodeIndex::FALSE,factory.xor_bdd(or_v0_v1,or_v0_v1,&mut HashMap::new()));
     assert_eq!(5,factory.len());
     assert_eq!(false,factory.evaluate_bdd(xor_v0_v1,&[false,false]));
     assert_eq!(true,factory.evaluate_bdd(xor_v0_v1,&[true,false]));
@@ -462,6 +471,8 @@ fn bdd_basic_ops_with_multiplicities<F:XDDBase<usize, u32>+Default>() {
     assert_ne!(or_v0_v1,factory.sum_bdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
 
     let xor_v0_v1 = factory.exactly_one_of_bdd(&[VariableIndex(0),VariableIndex(1)]);
+    assert_eq!(xor_v0_v1,factory.xor_bdd(or_v0_v1,and_v0_v1,&mut HashMap::new()));
+    assert_eq!(NodeIndex::FALSE,factory.xor_bdd(or_v0_v1,or_v0_v1,&mut HashMap::new()));
     assert_eq!(8,factory.len());
     assert_eq!(false,factory.evaluate_bdd(xor_v0_v1,&[false,false]));
     assert_eq!(true,factory.evaluate_bdd(xor_v0_v1,&[true,false]));
@@ -0,0 +1,54 @@
+//! Check converting between BDDs and ZDDs gives the same functions.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use xdd::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::SingleVariableGeneratingFunction;
+use common::random_function;
+
+const NUM_VARIABLES : u16 = 6;
+
+fn check_round_trips<M:Multiplicity>(seed:u64) where SingleVariableGeneratingFunction<u64>:xdd::generating_function::GeneratingFunctionWithMultiplicity<M> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut bdd = BDDFactory::<u32,M>::new(NUM_VARIABLES);
+    let mut zdd = ZDDFactory::<u32,M>::new(NUM_VARIABLES);
+    for _ in 0..50 {
+        let f = random_function(&mut bdd,&mut rng,NUM_VARIABLES,4);
+        let g = zdd.zdd_from_bdd(&bdd,f);
+        assert_eq!(bdd.number_solutions::<SingleVariableGeneratingFunction<u64>>(f),zdd.number_solutions::<SingleVariableGeneratingFunction<u64>>(g));
+        assert_eq!(f,bdd.bdd_from_zdd(&zdd,g));
+        let h = random_function(&mut zdd,&mut rng,NUM_VARIABLES,4);
+        let k = bdd.bdd_from_zdd(&zdd,h);
+        assert_eq!(zdd.number_solutions::<SingleVariableGeneratingFunction<u64>>(h),bdd.number_solutions::<SingleVariableGeneratingFunction<u64>>(k));
+        assert_eq!(h,zdd.zdd_from_bdd(&bdd,k));
+    }
+}
+
+#[test]
+fn round_trips() { check_round_trips::<NoMultiplicity>(1); }
+
+#[test]
+fn round_trips_with_multiplicities() { check_round_trips::<u32>(2); }
+
+#[test]
+fn sinks_and_universe() {
+    let mut bdd = BDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES);
+    let mut zdd = ZDDFactory::<u64,NoMultiplicity>::new(NUM_VARIABLES);
+    assert_eq!(NodeIndex::FALSE,zdd.zdd_from_bdd(&bdd,NodeIndex::FALSE));
+    let universe = zdd.universe();
+    assert_eq!(universe,zdd.zdd_from_bdd(&bdd,NodeIndex::TRUE));
+    assert_eq!(NodeIndex::TRUE,bdd.bdd_from_zdd(&zdd,universe));
+    // the family containing just the empty set is the function that is true iff every variable is false.
+    let none = bdd.at_most_n_of(&(0..NUM_VARIABLES).map(VariableIndex).collect::<Vec<_>>(),0);
+    assert_eq!(none,bdd.bdd_from_zdd(&zdd,NodeIndex::TRUE));
+}
+
+#[test]
+#[should_panic(expected = "Can only convert between factories with the same number of variables")]
+fn different_numbers_of_variables() {
+    let bdd = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let mut zdd = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    zdd.zdd_from_bdd(&bdd,NodeIndex::TRUE);
+}
@@ -0,0 +1,55 @@
+//! Check that running out of node addresses gives an error, and leaves the factory usable.
+
+use num::BigUint;
+use xdd::{BDDFactory, BooleanOperator, Comparison, DecisionDiagramFactory, NoMultiplicity, NodeIndex, VariableIndex, XDDError, ZDDFactory};
+
+const N : u16 = 120;
+
+/// Two diagrams of a few thousand nodes whose conjunction needs well over 65534.
+fn parts<F:DecisionDiagramFactory<u16,NoMultiplicity>>(factory:&mut F) -> (NodeIndex<u16,NoMultiplicity>,NodeIndex<u16,NoMultiplicity>) {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let f = factory.exactly_n_of(&variables,30);
+    let terms : Vec<(VariableIndex,i64)> = variables.iter().map(|&v|(v,1+(v.0 as i64)%7)).collect();
+    let g = factory.linear_constraint(&terms,Comparison::Equal,120);
+    (f,g)
+}
+
+fn check_capacity<F:DecisionDiagramFactory<u16,NoMultiplicity>>(mut factory:F) {
+    let (f,g) = parts(&mut factory);
+    let count_f : BigUint = factory.number_solutions(f);
+    let len = factory.len();
+    assert_eq!(Err(XDDError::CapacityExceeded),factory.try_and(f,g));
+    assert_eq!(Err(XDDError::CapacityExceeded),factory.try_apply(f,g,BooleanOperator::AND));
+    assert_eq!(Err(XDDError::CapacityExceeded),factory.try_operation(|factory|factory.and(f,g)));
+    assert!(factory.len()>len);
+    // still usable, and small operations still work.
+    assert_eq!(count_f,factory.number_solutions::<BigUint>(f));
+    assert_eq!(Ok(f),factory.try_and(f,f));
+    assert_eq!(Ok(f),factory.try_or(f,NodeIndex::FALSE));
+    assert_eq!(Ok(NodeIndex::FALSE),factory.try_xor(g,g));
+    let renaming = factory.gc([f,g]);
+    assert_eq!(len,factory.len());
+    let (f,_) = (renaming.rename(f).unwrap(),renaming.rename(g).unwrap());
+    let not_f = factory.try_not(f).unwrap();
+    assert_eq!(Ok(NodeIndex::FALSE),factory.try_ite(f,NodeIndex::FALSE,NodeIndex::FALSE));
+    assert_eq!(Ok(Some(NodeIndex::FALSE)),factory.try_poly_and(&[f,not_f]));
+}
+
+#[test]
+fn bdd_capacity() { check_capacity(BDDFactory::<u16,NoMultiplicity>::new(N)); }
+
+#[test]
+fn zdd_capacity() { check_capacity(ZDDFactory::<u16,NoMultiplicity>::new(N)); }
+
+#[test]
+#[should_panic(expected = "Too many nodes")]
+fn infallible_still_panics() {
+    let mut factory = BDDFactory::<u16,NoMultiplicity>::new(N);
+    let (f,g) = parts(&mut factory);
+    factory.and(f,g);
+}
+
+#[test]
+fn error_message() {
+    assert_eq!("Too many nodes for the NodeAddress type",XDDError::CapacityExceeded.to_string());
+}
@@ -0,0 +1,63 @@
+//! Check the cardinality constructors exactly_n_of, at_most_n_of, at_least_n_of and between_n_and_m_of by counting.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+
+fn binomial(n:usize,k:usize) -> u64 {
+    if k>n { 0 } else { (0..k).fold(1,|acc,i|acc*(n-i) as u64/(i as u64+1)) }
+}
+
+/// Check against counting on 6 variables, of which 4 are constrained.
+fn check_counts<F:DecisionDiagramFactory<u32,NoMultiplicity>>(hi_may_be_false:bool) {
+    let mut factory = F::new(6);
+    let variables = [VariableIndex(0),VariableIndex(2),VariableIndex(3),VariableIndex(5)];
+    let unconstrained = 1u64<<(6-variables.len());
+    for at_least in 0..=5 {
+        for at_most in 0..=5 {
+            let f = factory.between_n_and_m_of(&variables,at_least,at_most);
+            let expected : u64 = (at_least..=at_most).map(|k|binomial(variables.len(),k)).sum();
+            assert_eq!(expected*unconstrained,factory.number_solutions::<u64>(f),"between {} and {}",at_least,at_most);
+            for address in factory.post_order(f) { // check the result is properly reduced.
+                let node = factory.node(address);
+                assert!(if hi_may_be_false { node.lo!=node.hi } else { !node.hi.is_false() });
+            }
+        }
+        let at_least_f = factory.at_least_n_of(&variables,at_least);
+        let fewer = if at_least==0 { NodeIndex::FALSE } else { factory.at_most_n_of(&variables,at_least-1) };
+        assert_eq!(NodeIndex::FALSE,factory.and(at_least_f,fewer));
+        let either = factory.or(at_least_f,fewer);
+        assert_eq!(64,factory.number_solutions::<u64>(either));
+    }
+}
+
+/// exactly_n_of for n=1 should agree with exactly_one_of, and the counts should be binomial coefficients.
+fn check_exactly<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(7);
+    let variables = [VariableIndex(1),VariableIndex(2),VariableIndex(4),VariableIndex(5),VariableIndex(6)];
+    for n in 0..=6 {
+        let f = factory.exactly_n_of(&variables,n);
+        assert_eq!(4*binomial(variables.len(),n),factory.number_solutions::<u64>(f));
+    }
+    let one = factory.exactly_n_of(&variables,1);
+    let expected = factory.exactly_one_of(&variables);
+    let difference = factory.xor(one,expected);
+    assert_eq!(0,factory.number_solutions::<u64>(difference));
+}
+
+#[test]
+fn exactly_n_bdd() { check_exactly::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn exactly_n_zdd() { check_exactly::<ZDDFactory<u32,NoMultiplicity>>() }
+
+#[test]
+fn cardinality_bdd() { check_counts::<BDDFactory<u32,NoMultiplicity>>(true) }
+#[test]
+fn cardinality_zdd() { check_counts::<ZDDFactory<u32,NoMultiplicity>>(false) }
+
+#[test]
+fn at_least_is_small() {
+    // at_least_n_of should not need a state for every possible count.
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(100);
+    let variables : Vec<VariableIndex> = (0..100).map(VariableIndex).collect();
+    factory.at_least_n_of(&variables,2);
+    assert!(factory.len()<=300);
+}
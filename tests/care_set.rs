@@ -0,0 +1,47 @@
+//! Check that constrain and simplify agree with the original function wherever the care set is true.
+
+mod common;
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex};
+use common::random_counting_function;
+
+const N : u16 = 7;
+
+fn variables_used(factory:&BDDFactory<u32,u32>,f:NodeIndex<u32,u32>) -> HashSet<VariableIndex> {
+    factory.post_order(f).map(|address|factory.node(address).variable).collect()
+}
+
+#[test]
+fn agree_on_care_set() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    for _ in 0..100 {
+        let f = random_counting_function(&mut factory,&mut rng,N);
+        let care = random_counting_function(&mut factory,&mut rng,N);
+        let care_set = factory.support(care);
+        let expected = factory.and(f,care_set);
+        let constrained = factory.constrain(f,care);
+        assert_eq!(expected,factory.and(constrained,care_set));
+        let simplified = factory.simplify(f,care);
+        assert_eq!(expected,factory.and(simplified,care_set));
+        assert!(variables_used(&factory,simplified).is_subset(&variables_used(&factory,f)));
+        let everything = factory.not(NodeIndex::FALSE);
+        assert_eq!(f,factory.constrain(f,everything));
+        assert_eq!(f,factory.simplify(f,everything));
+    }
+}
+
+#[test]
+fn care_set_is_the_function() {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(N);
+    let f = factory.exactly_n_of(&variables,3);
+    assert_eq!(NodeIndex::TRUE,factory.constrain(f,f));
+    assert_eq!(NodeIndex::TRUE,factory.simplify(f,f));
+    let not_f = factory.not(f);
+    assert_eq!(NodeIndex::FALSE,factory.constrain(f,not_f));
+    assert_eq!(NodeIndex::FALSE,factory.simplify(f,NodeIndex::FALSE));
+}
@@ -0,0 +1,47 @@
+//! Check that counting with CheckedCount reports overflow exactly when the count does not fit.
+
+use num::BigUint;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::{CheckedCount, CountOverflow};
+
+/// 2^n solutions, for n around the size of a u64, compared with an exact count.
+fn check_powers_of_two<F:DecisionDiagramFactory<u32,NoMultiplicity>>(new:impl Fn(u16)->F) {
+    for n in 60..70u16 {
+        let mut factory = new(n);
+        let everything = factory.not(NodeIndex::FALSE);
+        let exact : BigUint = factory.number_solutions(everything);
+        let checked = factory.checked_number_solutions::<u64>(everything);
+        assert_eq!(n<64,checked.is_ok());
+        if let Ok(count) = checked { assert_eq!(BigUint::from(count),exact); }
+        assert_eq!(Ok(1u128<<n),factory.checked_number_solutions::<u128>(everything));
+        // one less solution than 2^n.
+        let v0 = factory.single_variable(VariableIndex(n-1));
+        let not_v0 = factory.not(v0);
+        let smaller = factory.or(not_v0,NodeIndex::FALSE);
+        assert_eq!(n<65,factory.checked_number_solutions::<u64>(smaller).is_ok());
+    }
+}
+
+#[test]
+fn powers_of_two_bdd() { check_powers_of_two(BDDFactory::<u32,NoMultiplicity>::new); }
+
+#[test]
+fn powers_of_two_zdd() { check_powers_of_two(ZDDFactory::<u32,NoMultiplicity>::new); }
+
+/// Overflow from multiplying by large multiplicities, and from negative multiplicities with an unsigned count.
+#[test]
+fn multiplicities() {
+    let mut factory = ZDDFactory::<u32,i64>::new(3);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let big = factory.scale(v0,1<<62); // 4 solutions.
+    assert_eq!(Err(CountOverflow{}),factory.checked_number_solutions::<i64>(big));
+    assert_eq!(Ok(1<<64),factory.checked_number_solutions::<i128>(big));
+    let not_so_big = factory.scale(v0,1<<60);
+    assert_eq!(Ok(1<<62),factory.checked_number_solutions::<i64>(not_so_big));
+    let negative = factory.linear_combination(&[(v0,-1)]);
+    assert_eq!(Ok(-4),factory.checked_number_solutions::<i64>(negative));
+    assert!(factory.checked_number_solutions::<u64>(negative).is_err());
+    let count : CheckedCount<u64> = factory.number_solutions(v0);
+    assert!(count.count_is_exact());
+    assert_eq!("The number of solutions is too large for the type used to count them",CountOverflow{}.to_string());
+}
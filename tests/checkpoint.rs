@@ -0,0 +1,152 @@
+//! Check that a computation saved to a checkpoint can be resumed, giving the same results as if it had not stopped.
+
+use std::io::ErrorKind;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::serialization::CheckpointProblem;
+use xdd::xdd_with_multiplicity::DiagramProblem;
+
+const N : u16 = 10;
+
+/// The first half of a computation, returning named intermediate results.
+fn first_half<F:DecisionDiagramFactory<u32,u32>>(factory:&mut F) -> Vec<(&'static str,NodeIndex<u32,u32>)> {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    factory.variable_registry_mut().set_names(&["a","b","c"]);
+    let a = factory.exactly_n_of(&variables,4);
+    let b = factory.at_most_n_of(&variables[3..],2);
+    let b = factory.scale(b,3);
+    vec![("a",a),("b",b),("false",NodeIndex::FALSE)]
+}
+
+/// The rest of the computation.
+fn second_half<F:DecisionDiagramFactory<u32,u32>>(factory:&mut F,roots:&[NodeIndex<u32,u32>]) -> (NodeIndex<u32,u32>,u64) {
+    let c = factory.or(roots[0],roots[1]);
+    let d = factory.xor(c,roots[0]);
+    (d,factory.number_solutions(d))
+}
+
+#[test]
+fn resume_bdd() {
+    let path = std::env::temp_dir().join("xdd_checkpoint_test_bdd.bin");
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    let roots = first_half(&mut factory);
+    factory.save_checkpoint(&path,&roots).unwrap();
+    let indices : Vec<NodeIndex<u32,u32>> = roots.iter().map(|(_,r)|*r).collect();
+    let expected = second_half(&mut factory,&indices);
+    let (mut resumed,loaded) = BDDFactory::<u32,u32>::load_checkpoint(&path).unwrap();
+    assert_eq!(roots.iter().map(|(n,r)|(n.to_string(),*r)).collect::<Vec<_>>(),loaded);
+    assert_eq!(Some(VariableIndex(2)),resumed.variable_registry().variable("c"));
+    assert_eq!(expected,second_half(&mut resumed,&indices));
+    assert!(!std::env::temp_dir().join("xdd_checkpoint_test_bdd.bin.partial").exists());
+    // a checkpoint is not a plain saved factory, nor for the other kind of diagram.
+    assert_eq!(ErrorKind::InvalidData,BDDFactory::<u32,u32>::load(&path).err().unwrap().kind());
+    assert_eq!(ErrorKind::InvalidData,ZDDFactory::<u32,u32>::load_checkpoint(&path).err().unwrap().kind());
+}
+
+#[test]
+fn resume_zdd() {
+    let path = std::env::temp_dir().join("xdd_checkpoint_test_zdd.bin");
+    let mut factory = ZDDFactory::<u32,u32>::new(N);
+    let roots = first_half(&mut factory);
+    factory.save_checkpoint(&path,&roots).unwrap();
+    let indices : Vec<NodeIndex<u32,u32>> = roots.iter().map(|(_,r)|*r).collect();
+    let expected = second_half(&mut factory,&indices);
+    // a later checkpoint replaces the earlier one.
+    factory.save_checkpoint(&path,&[("d",expected.0)]).unwrap();
+    let (resumed,loaded) = ZDDFactory::<u32,u32>::load_checkpoint(&path).unwrap();
+    assert_eq!(vec![("d".to_string(),expected.0)],loaded);
+    assert_eq!(expected.1,resumed.number_solutions::<u64>(loaded[0].1));
+}
+
+/// A checkpoint that can not be saved leaves no temporary file behind.
+#[test]
+fn failed_save() {
+    let path = std::env::temp_dir().join("xdd_checkpoint_test_failed_save");
+    std::fs::create_dir_all(&path).unwrap(); // a directory can not be replaced by a file.
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    let roots = first_half(&mut factory);
+    assert!(factory.save_checkpoint(&path,&roots).is_err());
+    assert!(!std::env::temp_dir().join("xdd_checkpoint_test_failed_save.partial").exists());
+    assert!(path.is_dir());
+    // a relative path, whose directory is the current one.
+    let relative = std::path::Path::new("xdd_checkpoint_test_relative.bin");
+    factory.save_checkpoint(relative,&roots).unwrap();
+    assert_eq!(roots.len(),BDDFactory::<u32,u32>::load_checkpoint(relative).unwrap().1.len());
+    std::fs::remove_file(relative).unwrap();
+}
+
+#[test]
+fn truncated_checkpoint() {
+    let path = std::env::temp_dir().join("xdd_checkpoint_test_truncated.bin");
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    let roots = first_half(&mut factory);
+    factory.save_checkpoint(&path,&roots).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    for len in [3,20,bytes.len()-1] {
+        std::fs::write(&path,&bytes[..len]).unwrap();
+        assert!(BDDFactory::<u32,u32>::load_checkpoint(&path).is_err());
+    }
+}
+
+/// Save exactly 3 of 6 variables as the only root, named "three", returning the file's bytes.
+/// The root is the last node, stored as its variable (2 bytes), lo (4 bytes) and hi (4 bytes) at the end of the file.
+fn save_three(path:&std::path::Path) -> Vec<u8> {
+    let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    let three = factory.exactly_n_of(&variables,3);
+    factory.save_checkpoint(path,&[("three",three)]).unwrap();
+    std::fs::read(path).unwrap()
+}
+
+/// Where the checksum of the only root "three" is stored: after the magic, version, number of roots and name.
+const CHECKSUM_AT : usize = 4+1+8+8+5;
+
+fn verify(path:&std::path::Path) -> Vec<CheckpointProblem<u32>> { BDDFactory::<u32,NoMultiplicity>::verify_checkpoint(path).unwrap() }
+
+#[test]
+fn verify_healthy() {
+    let path = std::env::temp_dir().join("xdd_checkpoint_test_verify_healthy.bin");
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    let roots = first_half(&mut factory);
+    factory.save_checkpoint(&path,&roots).unwrap();
+    assert_eq!(Vec::<CheckpointProblem<u32>>::new(),BDDFactory::<u32,u32>::verify_checkpoint(&path).unwrap());
+    let mut factory = ZDDFactory::<u32,u32>::new(N);
+    let roots = first_half(&mut factory);
+    factory.save_checkpoint(&path,&roots).unwrap();
+    assert_eq!(Vec::<CheckpointProblem<u32>>::new(),ZDDFactory::<u32,u32>::verify_checkpoint(&path).unwrap());
+    assert_eq!(ErrorKind::InvalidData,BDDFactory::<u32,u32>::verify_checkpoint(&path).err().unwrap().kind());
+}
+
+#[test]
+fn verify_corrupted() {
+    let path = std::env::temp_dir().join("xdd_checkpoint_test_verify_corrupted.bin");
+    let bytes = save_three(&path);
+    assert_eq!(20u64.to_le_bytes(),bytes[CHECKSUM_AT..CHECKSUM_AT+8]);
+    assert!(verify(&path).is_empty());
+    // a damaged checksum.
+    let mut damaged = bytes.clone();
+    damaged[CHECKSUM_AT] = 21;
+    std::fs::write(&path,&damaged).unwrap();
+    assert_eq!(vec![CheckpointProblem::WrongChecksum{root:"three".to_string(),stored:21,found:20}],verify(&path));
+    // the root's lo child replaced by FALSE still makes a valid diagram, but with only 10 solutions.
+    let mut damaged = bytes.clone();
+    let lo_at = bytes.len()-8;
+    damaged[lo_at..lo_at+4].copy_from_slice(&0u32.to_le_bytes());
+    std::fs::write(&path,&damaged).unwrap();
+    assert_eq!(vec![CheckpointProblem::WrongChecksum{root:"three".to_string(),stored:20,found:10}],verify(&path));
+    // the root's lo child replaced by its hi child makes it redundant, and it is not counted.
+    let mut damaged = bytes.clone();
+    damaged.copy_within(lo_at+4..lo_at+8,lo_at);
+    std::fs::write(&path,&damaged).unwrap();
+    let problems = verify(&path);
+    assert_eq!(1,problems.len());
+    match &problems[0] {
+        CheckpointProblem::Invalid{root,problems} => {
+            assert_eq!("three",root);
+            assert!(problems.problems.iter().any(|p|matches!(p,DiagramProblem::Redundant{..})));
+        }
+        problem => panic!("Unexpected {}",problem),
+    }
+    // a file that cannot be read is an error rather than a problem.
+    std::fs::write(&path,&bytes[..bytes.len()-1]).unwrap();
+    assert!(BDDFactory::<u32,NoMultiplicity>::verify_checkpoint(&path).is_err());
+}
@@ -1,57 +1,6 @@
-use std::collections::HashMap;
-use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
-
-type Site = [usize;2];
-type SiteIndex = usize;
-type Tile = Vec<SiteIndex>;
-type TileIndex = usize;
-
-#[derive(Default)]
-struct TilingProblem {
-    sites : Vec<Site>,
-    site_index_by_site : HashMap<Site,SiteIndex>,
-    tiles : Vec<Tile>,
-    /// tiles_covering_a_site[site_index] is a list containing tile_index iff tiles[tile_index] contains site_index.
-    tiles_covering_a_site : Vec<Vec<TileIndex>>,
-}
-
-impl TilingProblem {
-    fn add_site(&mut self,s:Site) -> SiteIndex {
-        let index = self.sites.len();
-        self.sites.push(s);
-        self.site_index_by_site.insert(s,index);
-        self.tiles_covering_a_site.push(Vec::new());
-        index
-    }
-    fn add_tile(&mut self,tile:Tile) {
-        let index = self.tiles.len();
-        for &s in &tile {
-            self.tiles_covering_a_site[s].push(index);
-            self.tiles_covering_a_site[s].sort();
-        }
-        self.tiles.push(tile);
-    }
-    /// If all the sites on the tile exist, add it and return true. Otherwise return false.
-    fn add_tile_containing_sites(&mut self,sites:&[Site]) -> bool {
-        let mut tile = Vec::new();
-        for s in sites {
-            if let Some(index) = self.site_index_by_site.get(s) { tile.push(*index); } else { return false; }
-        }
-        self.add_tile(tile);
-        true
-    }
-    fn find_tiling_solution<F: DecisionDiagramFactory<u32,NoMultiplicity>>(&self) -> (F, NodeIndex<u32,NoMultiplicity>) {
-        let mut factory = F::new(self.tiles.len() as u16);
-        let mut constraints = Vec::new();
-        for tiles_covering_site in &self.tiles_covering_a_site {
-            let constraint_for_that_site = factory.exactly_one_of(& tiles_covering_site.iter().map(|t|VariableIndex(*t as u16)).collect::<Vec<_>>());
-            constraints.push(constraint_for_that_site);
-        }
-        constraints.reverse(); // much faster to merge later tiles first.
-        let node = factory.poly_and(&constraints).unwrap();
-        (factory,node)
-    }
-}
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+use num::BigUint;
+use xdd::tiling::{Orientations, Region, Shape, Symmetry, SymmetryGroupError, TilingProblem};
 
 /// Define a tiling problem as a chessboard with dominoes.
 fn setup_chessboard_tiled_with_dominoes(side_length_wanted:usize) -> TilingProblem {
@@ -103,7 +52,7 @@ fn setup_chessboard_tiled_with_up_to_trionimoes(side_length_wanted:usize) -> Til
 
 /// Count using a decision diagram, given a creator function for the factory taking the number of variables.
 fn count_tiling<F: DecisionDiagramFactory<u32, NoMultiplicity>>(problem:TilingProblem) -> u128 {
-    let (mut factory ,solution) = problem.find_tiling_solution::<F>();
+    let (mut factory ,solution) = problem.find_tiling_solution::<u32,NoMultiplicity,F>();
     let original_len = factory.len();
     let renamer = factory.gc([solution]);
     let solution = renamer.rename(solution).unwrap();
@@ -137,3 +86,69 @@ fn count_up_to_trionimoes_zdd() {
     let solutions = count_tiling::<ZDDFactory<u32,NoMultiplicity>>(setup_chessboard_tiled_with_up_to_trionimoes(8));
     assert_eq!(solutions,92109458286284989468604); // See Knuth, "The art of Computer programming Volume 4, Fascicle 1, Binary Decision Diagrams", section 7.1.4, p120
 }
+
+#[test]
+fn count_dominoes_from_text() {
+    let mut problem = TilingProblem::from_region(&Region::rectangle(8,8));
+    let domino : Shape = "##".parse().unwrap();
+    assert_eq!(112,problem.add_all_placements(&[domino],Orientations::Rotations));
+    assert_eq!(12988816,count_tiling::<ZDDFactory<u32,NoMultiplicity>>(problem));
+}
+
+#[test]
+fn count_mutilated_chessboard() {
+    let region : Region = ".#######\n########\n########\n########\n########\n########\n########\n#######.".parse().unwrap();
+    let mut problem = TilingProblem::from_region(&region);
+    problem.add_all_placements(&["##".parse().unwrap()],Orientations::Rotations);
+    assert_eq!(0,count_tiling::<BDDFactory<u32,NoMultiplicity>>(problem));
+}
+
+#[test]
+fn text_shapes_match_explicit_trionimoes() {
+    let mut problem = TilingProblem::from_region(&Region::rectangle(5,5));
+    let shapes : Vec<Shape> = ["#","##","###","##\n#."].iter().map(|s|s.parse().unwrap()).collect();
+    problem.add_all_placements(&shapes,Orientations::RotationsAndReflections);
+    let explicit = setup_chessboard_tiled_with_up_to_trionimoes(5);
+    assert_eq!(explicit.tiles.len(),problem.tiles.len());
+    assert_eq!(count_tiling::<ZDDFactory<u32,NoMultiplicity>>(explicit),count_tiling::<ZDDFactory<u32,NoMultiplicity>>(problem));
+}
+
+#[test]
+fn count_dominoes_up_to_symmetry() {
+    let mut problem = TilingProblem::from_region(&Region::rectangle(4,4));
+    problem.add_all_placements(&["##".parse().unwrap()],Orientations::Rotations);
+    let symmetries = problem.symmetries();
+    assert_eq!(8,symmetries.len());
+    let counts = problem.count_tilings_up_to_symmetry::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>,u128>(&symmetries).unwrap();
+    assert_eq!(36,counts.total);
+    assert_eq!(9,counts.up_to_symmetry);
+    let big = problem.count_tilings_up_to_symmetry::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>,BigUint>(&symmetries).unwrap();
+    assert_eq!(BigUint::from(9u32),big.up_to_symmetry);
+    // the mutilated chessboard only has the symmetries fixing the diagonal.
+    let mut problem = TilingProblem::from_region(&Region::rectangle(6,6).without(&[[0,0],[5,5]]));
+    problem.add_all_placements(&["##".parse().unwrap()],Orientations::Rotations);
+    assert_eq!(4,problem.symmetries().len());
+}
+
+#[test]
+fn symmetries_not_a_group() {
+    let mut problem = TilingProblem::from_region(&Region::rectangle(4,4));
+    problem.add_all_placements(&["##".parse().unwrap()],Orientations::Rotations);
+    let symmetries = problem.symmetries();
+    let count = |symmetries:&[Symmetry]|problem.count_tilings_up_to_symmetry::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>,u128>(symmetries);
+    assert_eq!(Err(SymmetryGroupError::NoIdentity),count(&symmetries[1..]));
+    assert_eq!(Err(SymmetryGroupError::NotClosed(1,1)),count(&symmetries[..2])); // rotating by 90° twice is missing.
+    assert_eq!(Err(SymmetryGroupError::Repeated(0,2)),count(&[symmetries[0].clone(),symmetries[2].clone(),symmetries[0].clone()]));
+    let mut not_a_permutation = symmetries[2].clone();
+    not_a_permutation.tile_map[0] = not_a_permutation.tile_map[1];
+    assert_eq!(Err(SymmetryGroupError::NotAPermutation(1)),count(&[symmetries[0].clone(),not_a_permutation]));
+    assert_eq!(Ok(vec![36,8]),count(&[symmetries[0].clone(),symmetries[2].clone()]).map(|c|c.fixed_by_each_symmetry)); // just rotation by 180°.
+}
+
+#[test]
+#[should_panic(expected="Too many tiles")]
+fn too_many_tiles() {
+    let mut problem = TilingProblem::from_region(&Region::rectangle(256,256));
+    for site in 0..problem.sites.len() { problem.add_tile(vec![site]); } // one more tile than there can be variables.
+    problem.find_tiling_solution::<u32,NoMultiplicity,BDDFactory<u32,NoMultiplicity>>();
+}
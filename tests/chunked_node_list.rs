@@ -0,0 +1,59 @@
+//! Check that ChunkedNodeList behaves exactly like NodeListWithFastLookup, including across chunk boundaries and garbage collection.
+
+use std::collections::HashMap;
+use xdd::{NodeIndex, VariableIndex};
+use xdd::xdd_with_multiplicity::{ChunkedNodeList, NodeListWithFastLookup, XDDBase};
+
+/// Do the same operations on some store, returning some roots.
+fn build<X:XDDBase<u64,u32>>(nodes:&mut X) -> Vec<NodeIndex<u64,u32>> {
+    let variables : Vec<VariableIndex> = (0..12).map(VariableIndex).collect();
+    let mut cache = HashMap::new();
+    let f = nodes.exactly_n_of_zdd(&variables,4,12);
+    let g = nodes.between_n_and_m_of_zdd(&variables[3..],2,6,12);
+    let sum = nodes.sum_zdd(f,g,&mut cache);
+    let mut cache = HashMap::new();
+    let product = nodes.mul_zdd(sum,g,&mut cache);
+    vec![f,g,sum,product]
+}
+
+fn same_nodes<X:XDDBase<u64,u32>,Y:XDDBase<u64,u32>>(x:&X,y:&Y) {
+    assert_eq!(x.len(),y.len());
+    for address in 2..x.len() as u64+2 {
+        assert!(x.node(address)==y.node(address),"node {} differs",address);
+    }
+}
+
+#[test]
+fn same_as_node_list() {
+    for chunk_size in [1,3,64,ChunkedNodeList::<u64,u32>::DEFAULT_CHUNK_SIZE] {
+        let mut reference = NodeListWithFastLookup::<u64,u32>::default();
+        let mut chunked = ChunkedNodeList::<u64,u32>::with_chunk_size(chunk_size);
+        let roots = build(&mut reference);
+        assert_eq!(roots,build(&mut chunked));
+        same_nodes(&reference,&chunked);
+        assert_eq!(roots,build(&mut chunked),"rebuilding should find all the existing nodes");
+        assert_eq!(reference.len(),chunked.len());
+        // garbage collect, keeping only some.
+        let keep = [roots[1],roots[3]];
+        let renaming_reference = reference.gc(keep);
+        let renaming_chunked = chunked.gc(keep);
+        same_nodes(&reference,&chunked);
+        for root in keep {
+            let renamed = renaming_chunked.rename(root).unwrap();
+            assert_eq!(renaming_reference.rename(root),Some(renamed));
+            assert_eq!(reference.number_solutions_zdd::<u64>(renamed,12),chunked.number_solutions_zdd::<u64>(renamed,12));
+        }
+        assert_eq!(renaming_reference.rename(roots[0]),renaming_chunked.rename(roots[0]));
+        // lookups still work after gc.
+        let renamed = renaming_chunked.rename(roots[1]).unwrap();
+        let node = chunked.node(renamed.address());
+        assert_eq!(Some(renamed.address()),chunked.find_node_index(node));
+        // and new nodes can be added.
+        assert_eq!(build(&mut reference),build(&mut chunked));
+        same_nodes(&reference,&chunked);
+        let everything = chunked.gc([]);
+        assert_eq!(0,chunked.len());
+        assert!(everything.rename(renamed).is_none());
+        assert_eq!(roots,build(&mut chunked));
+    }
+}
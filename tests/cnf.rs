@@ -0,0 +1,108 @@
+//! Check reading DIMACS CNF files, building them with each clause order, and the Tseitin gates, against brute force counting.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::cnf::{ClauseOrder, Cnf, CnfBuildOptions, Literal, ParseCnfError};
+
+fn brute_force_count(cnf:&Cnf) -> u64 {
+    (0..1u64<<cnf.num_variables).filter(|assignment|{
+        cnf.clauses.iter().all(|clause|clause.iter().any(|l|((assignment>>l.variable.0)&1==1)!=l.negated))
+    }).count() as u64
+}
+
+fn random_3sat(num_variables:u16,num_clauses:usize,seed:u64) -> Cnf {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut cnf = Cnf::new(num_variables);
+    for _ in 0..num_clauses {
+        let clause : Vec<Literal> = (0..3).map(|_|Literal{variable:VariableIndex(rng.gen_range(0..num_variables)),negated:rng.gen()}).collect();
+        cnf.add_clause(&clause);
+    }
+    cnf
+}
+
+fn all_options() -> Vec<CnfBuildOptions> {
+    let mut res = vec![];
+    for order in [ClauseOrder::SmallestFirst,ClauseOrder::AsGiven,ClauseOrder::BottomUp] {
+        for gc_every in [None,Some(1),Some(5)] { res.push(CnfBuildOptions{order,gc_every}); }
+    }
+    res
+}
+
+fn check_count<F:DecisionDiagramFactory<u32,NoMultiplicity>>(cnf:&Cnf) {
+    let expected = brute_force_count(cnf);
+    for options in all_options() {
+        let mut factory = F::new(cnf.num_variables);
+        let f = cnf.build(&mut factory,&options);
+        assert_eq!(expected,factory.number_solutions::<u64>(f),"{:?}",options);
+    }
+}
+
+#[test]
+fn random_formulas() {
+    for seed in 0..10 {
+        let cnf = random_3sat(10,20+seed as usize*3,seed);
+        check_count::<BDDFactory<u32,NoMultiplicity>>(&cnf);
+        check_count::<ZDDFactory<u32,NoMultiplicity>>(&cnf);
+    }
+    let empty = Cnf::new(4);
+    check_count::<BDDFactory<u32,NoMultiplicity>>(&empty);
+    check_count::<ZDDFactory<u32,NoMultiplicity>>(&empty);
+}
+
+#[test]
+fn pigeonhole_is_unsatisfiable() {
+    // 4 pigeons in 3 holes. Variable 3p+h means pigeon p is in hole h.
+    let mut cnf = Cnf::new(12);
+    let var = |p:u16,h:u16|VariableIndex(3*p+h);
+    for p in 0..4 { cnf.add_clause(&(0..3).map(|h|Literal::positive(var(p,h))).collect::<Vec<_>>()); }
+    for h in 0..3 {
+        for p1 in 0..4 {
+            for p2 in p1+1..4 { cnf.add_clause(&[Literal::negative(var(p1,h)),Literal::negative(var(p2,h))]); }
+        }
+    }
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(12);
+    assert_eq!(NodeIndex::FALSE,cnf.build(&mut factory,&CnfBuildOptions::default()));
+    assert_eq!(NodeIndex::FALSE,cnf.build(&mut factory,&CnfBuildOptions{order:ClauseOrder::AsGiven,gc_every:Some(2)}));
+}
+
+#[test]
+fn tseitin_gates() {
+    // (a xor b) or (c and d) is true for 8+4-2 = 10 of the 16 assignments.
+    let mut cnf = Cnf::new(4);
+    let [a,b,c,d] = [0,1,2,3].map(|v|Literal::positive(VariableIndex(v)));
+    let xor = cnf.xor_gate(a,b);
+    let and = cnf.and_gate(&[c,d]);
+    let or = cnf.or_gate(&[xor,and]);
+    cnf.add_clause(&[or]);
+    assert_eq!(7,cnf.num_variables);
+    assert_eq!(10,brute_force_count(&cnf));
+    check_count::<BDDFactory<u32,NoMultiplicity>>(&cnf);
+    check_count::<ZDDFactory<u32,NoMultiplicity>>(&cnf);
+}
+
+#[test]
+fn round_trip() {
+    let cnf = random_3sat(8,12,99);
+    let text = cnf.to_string();
+    assert!(text.starts_with("p cnf 8 12\n"));
+    assert_eq!(cnf,text.parse().unwrap());
+    assert_eq!(cnf,Cnf::read_from(&mut text.as_bytes()).unwrap());
+    // clauses may span lines, and a % line ends the file.
+    let spread : Cnf = "c comment\np cnf 3 2\n1 -3\n 0 2\n0\n%\n0\n".parse().unwrap();
+    assert_eq!(vec![vec![Literal::positive(VariableIndex(0)),Literal::negative(VariableIndex(2))],vec![Literal::positive(VariableIndex(1))]],spread.clauses);
+}
+
+#[test]
+fn bad_files() {
+    let parse = |s:&str| s.parse::<Cnf>().err();
+    assert_eq!(Some(ParseCnfError::MissingHeader),parse("1 2 0\n"));
+    assert_eq!(Some(ParseCnfError::MissingHeader),parse("c nothing\n"));
+    assert_eq!(Some(ParseCnfError::BadHeader("p dnf 2 1".to_string())),parse("p dnf 2 1\n1 0\n"));
+    assert_eq!(Some(ParseCnfError::TooManyVariables(70000)),parse("p cnf 70000 0\n"));
+    assert_eq!(Some(ParseCnfError::NumberFormat("x".to_string())),parse("p cnf 2 1\n1 x 0\n"));
+    assert_eq!(Some(ParseCnfError::VariableOutOfRange(-3)),parse("p cnf 2 1\n1 -3 0\n"));
+    assert_eq!(Some(ParseCnfError::UnterminatedClause),parse("p cnf 2 1\n1 2\n"));
+    assert_eq!(Some(ParseCnfError::WrongNumberOfClauses{expected:2,found:1}),parse("p cnf 2 2\n1 2 0\n"));
+    assert!(Cnf::read_from(&mut "p cnf 2 2\n".as_bytes()).is_err());
+}
@@ -0,0 +1,70 @@
+//! Random diagrams shared by several of the tests, which check operations against simpler ways of doing the same thing.
+#![allow(dead_code)] // each test uses only some of them.
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use xdd::{DecisionDiagramFactory, Multiplicity, NodeIndex, VariableIndex};
+
+/// A random product of literals of the first num_variables variables, each variable being in it with probability 2/3,
+/// negated half the time.
+fn random_product<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,rng:&mut StdRng,num_variables:u16) -> NodeIndex<u32,M> {
+    let mut product = factory.not(NodeIndex::FALSE);
+    for v in 0..num_variables {
+        let x = factory.single_variable(VariableIndex(v));
+        match rng.gen_range(0..3) {
+            0 => { product = factory.and(product,x); }
+            1 => { let x = factory.not(x); product = factory.and(product,x); }
+            _ => {}
+        }
+    }
+    product
+}
+
+/// A random function of the first num_variables variables, as the or of up to max_terms random products of literals.
+/// With multiplicities "or" adds, so this is a random multiset.
+pub fn random_function<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,rng:&mut StdRng,num_variables:u16,max_terms:usize) -> NodeIndex<u32,M> {
+    let mut res = NodeIndex::FALSE;
+    for _ in 0..rng.gen_range(0..=max_terms) {
+        let product = random_product(factory,rng,num_variables);
+        res = factory.or(res,product);
+    }
+    res
+}
+
+/// Like [random_function], but a signed sum, with each product given a weight from ±1 to ±3.
+pub fn random_signed_function<M:Multiplicity+From<i8>,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,rng:&mut StdRng,num_variables:u16,max_terms:usize) -> NodeIndex<u32,M> {
+    let mut res = NodeIndex::FALSE;
+    for _ in 0..rng.gen_range(0..=max_terms) {
+        let product = random_product(factory,rng,num_variables);
+        let weight : i8 = if rng.gen_bool(0.5) { rng.gen_range(1..4) } else { -rng.gen_range(1..4) };
+        res = factory.or(res,product.multiply(M::from(weight)));
+    }
+    res
+}
+
+/// A random function of the first num_variables variables, as the or of one to three terms, each bounding how many of
+/// a random subset of the variables are true, and sometimes xored with a random variable.
+pub fn random_counting_function<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,rng:&mut StdRng,num_variables:u16) -> NodeIndex<u32,M> {
+    let variables : Vec<VariableIndex> = (0..num_variables).map(VariableIndex).collect();
+    let mut f = NodeIndex::FALSE;
+    for _ in 0..rng.gen_range(1..4) {
+        let subset : Vec<VariableIndex> = variables.iter().cloned().filter(|_|rng.gen_bool(0.5)).collect();
+        let term = factory.between_n_and_m_of(&subset,rng.gen_range(0..2),rng.gen_range(1..4));
+        let v = factory.single_variable(variables[rng.gen_range(0..num_variables as usize)]);
+        let term = if rng.gen_bool(0.5) { factory.xor(term,v) } else { term };
+        f = factory.or(f,term);
+    }
+    f
+}
+
+/// A random function of the first num_variables variables with random multiplicities, some negative, as a linear
+/// combination of five cardinality constraints.
+pub fn random_linear_combination<F:DecisionDiagramFactory<u32,i64>>(factory:&mut F,rng:&mut StdRng,num_variables:u16) -> NodeIndex<u32,i64> {
+    let mut f = NodeIndex::FALSE;
+    for _ in 0..5 {
+        let variables : Vec<VariableIndex> = (0..num_variables).filter(|_|rng.gen_bool(0.5)).map(VariableIndex).collect();
+        let term = factory.exactly_n_of(&variables,rng.gen_range(0..=variables.len()));
+        f = factory.linear_combination(&[(f,1),(term,rng.gen_range(-3..=3))]);
+    }
+    f
+}
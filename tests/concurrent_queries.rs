@@ -0,0 +1,65 @@
+//! Check that a finished factory can be queried from several threads at once, and that evaluate agrees with the solutions.
+
+use std::collections::HashMap;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::generating_function::SingleVariableGeneratingFunction;
+
+const N : u16 = 8;
+
+fn assert_send_sync<T:Send+Sync>() {}
+
+/// Some diagrams with multiplicities.
+fn build<F:DecisionDiagramFactory<u32,u32>>() -> (F,Vec<NodeIndex<u32,u32>>) {
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut diagrams = vec![NodeIndex::FALSE,NodeIndex::TRUE];
+    for k in 0..4 {
+        let a = factory.exactly_n_of(&variables[k..],2);
+        let b = factory.at_most_n_of(&variables[..6],k);
+        diagrams.push(factory.or(a,b));
+    }
+    (factory,diagrams)
+}
+
+/// Things about a diagram that can be found from a shared reference to the factory.
+#[derive(Debug,PartialEq)]
+struct Analysis {
+    count : u64,
+    generating_function : SingleVariableGeneratingFunction<u64>,
+    /// the multiplicity of each assignment.
+    evaluations : Vec<Option<u32>>,
+    solutions : Vec<Vec<VariableIndex>>,
+}
+
+fn analyse<F:DecisionDiagramFactory<u32,u32>>(factory:&F,f:NodeIndex<u32,u32>) -> Analysis {
+    let evaluations = (0..1u32<<N).map(|x|factory.evaluate(f,&(0..N).map(|v|(x>>v)&1==1).collect::<Vec<_>>())).collect();
+    Analysis{ count: factory.number_solutions(f), generating_function: factory.number_solutions(f), evaluations, solutions: factory.solutions(f).collect() }
+}
+
+fn check_concurrent<F:DecisionDiagramFactory<u32,u32>+Sync>() {
+    let (factory,diagrams) = build::<F>();
+    let expected : Vec<_> = diagrams.iter().map(|&f|analyse(&factory,f)).collect();
+    for (f,Analysis{count,evaluations,solutions,..}) in diagrams.iter().zip(&expected) {
+        assert_eq!(*count,evaluations.iter().map(|m|m.unwrap_or(0) as u64).sum::<u64>());
+        let mask = |s:&Vec<VariableIndex>|s.iter().map(|v|1usize<<v.0).sum::<usize>();
+        let multiplicities : HashMap<usize,u32> = solutions.iter().map(|s|(mask(s),factory.evaluate(*f,&(0..N).map(|v|s.contains(&VariableIndex(v))).collect::<Vec<_>>()).unwrap())).collect();
+        for (x,m) in evaluations.iter().enumerate() { assert_eq!(*m,multiplicities.get(&x).cloned()); }
+    }
+    let factory = &factory;
+    std::thread::scope(|scope|{
+        let threads : Vec<_> = (0..4).map(|_|scope.spawn(||diagrams.iter().map(|&f|analyse(factory,f)).collect::<Vec<_>>())).collect();
+        for thread in threads { assert_eq!(expected,thread.join().unwrap()); }
+    });
+}
+
+#[test]
+fn concurrent_bdd() {
+    assert_send_sync::<BDDFactory<u32,u32>>();
+    check_concurrent::<BDDFactory<u32,u32>>();
+}
+
+#[test]
+fn concurrent_zdd() {
+    assert_send_sync::<ZDDFactory<u32,u32>>();
+    check_concurrent::<ZDDFactory<u32,u32>>();
+}
@@ -0,0 +1,49 @@
+//! Check counting solutions with some variables fixed against counting the diagram with the fixed values anded in.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::generating_function::SingleVariableGeneratingFunction;
+
+const N : u16 = 7;
+
+fn check_given<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(95);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut factory = F::new(N);
+    for _ in 0..30 {
+        let k = rng.gen_range(0..=N as usize);
+        let a = factory.at_most_n_of(&variables,k);
+        let b = factory.exactly_n_of(&variables[rng.gen_range(0..N as usize)..],1);
+        let b = factory.scale(b,rng.gen_range(1..4));
+        let f = factory.or(a,b);
+        for _ in 0..10 {
+            let literals : Vec<(VariableIndex,bool)> = variables.iter().filter_map(|&v|if rng.gen_bool(0.3) { Some((v,rng.gen_bool(0.5))) } else { None }).collect();
+            let mut condition = factory.not(NodeIndex::FALSE);
+            for &(v,value) in literals.iter().rev() { // given in reverse order, which is allowed.
+                let literal = factory.exactly_n_of(&[v],value as usize);
+                condition = factory.and(condition,literal);
+            }
+            let conditioned = factory.and(f,condition);
+            let reversed : Vec<(VariableIndex,bool)> = literals.iter().rev().cloned().collect();
+            assert_eq!(factory.number_solutions::<u64>(conditioned),factory.number_solutions_given::<u64>(f,&reversed));
+            assert_eq!(factory.number_solutions::<SingleVariableGeneratingFunction<u64>>(conditioned),factory.number_solutions_given::<SingleVariableGeneratingFunction<u64>>(f,&literals));
+        }
+    }
+    assert_eq!(0,factory.number_solutions_given::<u64>(NodeIndex::FALSE,&[(VariableIndex(0),true)]));
+    let everything = factory.not(NodeIndex::FALSE);
+    assert_eq!(1<<(N-1),factory.number_solutions_given::<u64>(everything,&[(VariableIndex(3),true)]));
+}
+
+#[test]
+fn given_bdd() { check_given::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn given_zdd() { check_given::<ZDDFactory<u32,u32>>(); }
+
+#[test]
+#[should_panic(expected = "sorted and distinct")]
+fn repeated_variable() {
+    let factory = BDDFactory::<u32,u32>::new(3);
+    factory.number_solutions_given::<u64>(NodeIndex::TRUE,&[(VariableIndex(1),true),(VariableIndex(1),false)]);
+}
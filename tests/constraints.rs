@@ -0,0 +1,64 @@
+//! Check constraint files against counting the assignments satisfying them directly.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+use xdd::constraints::{ConstraintProblem, ConstraintSystem, ParseConstraintsError};
+
+const TEXT : &str = "
+# a small system using every kind of statement
+variables a b c
+variables d e f
+clause a !b f
+exactly 3 of a b c d e f
+at_least 1 of b c d
+at_most 1 of e d   # order does not matter
+between 1 2 of a c e
+linear 3*a -2*b c -d 4*e > 0
+";
+
+/// Whether the assignment, with bit i being variable i, satisfies TEXT.
+fn satisfies(x:u32) -> bool {
+    let v = |i:u32|(x>>i)&1;
+    let count = |vars:&[u32]|vars.iter().map(|&i|v(i)).sum::<u32>();
+    (v(0)==1 || v(1)==0 || v(5)==1) && count(&[0,1,2,3,4,5])==3 && count(&[1,2,3])>=1 && count(&[3,4])<=1
+        && (1..=2).contains(&count(&[0,2,4])) && 3*v(0) as i64-2*v(1) as i64+v(2) as i64-v(3) as i64+4*v(4) as i64>0
+}
+
+fn check_system<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let system : ConstraintSystem = TEXT.parse().unwrap();
+    assert_eq!(6,system.num_variables());
+    assert_eq!(6,system.constraints.len());
+    let mut factory = F::new(system.num_variables());
+    let f = system.build(&mut factory);
+    let expected = (0..64u32).filter(|&x|satisfies(x)).count() as u64;
+    assert!(expected>0);
+    assert_eq!(expected,factory.number_solutions::<u64>(f));
+    for x in 0..64u32 { assert_eq!(satisfies(x),factory.evaluate(f,&(0..6).map(|i|(x>>i)&1==1).collect::<Vec<bool>>()).is_some()); }
+    assert_eq!("e",factory.variable_registry().name(xdd::VariableIndex(4)));
+    // no constraints means everything.
+    let empty : ConstraintSystem = "variables x y".parse().unwrap();
+    let mut factory = F::new(2);
+    let f = empty.build(&mut factory);
+    assert_eq!(4,factory.number_solutions::<u64>(f));
+}
+
+#[test]
+fn system_bdd() { check_system::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn system_zdd() { check_system::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn errors() {
+    let problem = |text:&str|text.parse::<ConstraintSystem>().err().unwrap();
+    assert_eq!(ParseConstraintsError{line:2,problem:ConstraintProblem::UnknownVariable("c".to_string())},problem("variables a b\nclause a c"));
+    assert_eq!(ConstraintProblem::DuplicateName("a".to_string()),problem("variables a b\nvariables a").problem);
+    assert_eq!(ConstraintProblem::BadName("-a".to_string()),problem("variables -a").problem);
+    assert_eq!(ConstraintProblem::BadName("7".to_string()),problem("variables 7").problem);
+    assert_eq!(ConstraintProblem::RepeatedVariable("b".to_string()),problem("variables a b\nexactly 1 of b a b").problem);
+    assert_eq!(ConstraintProblem::RepeatedVariable("a".to_string()),problem("variables a b\nlinear a 2*a <= 1").problem);
+    assert_eq!(ConstraintProblem::NumberFormat("two".to_string()),problem("variables a b\nat_most two of a b").problem);
+    assert_eq!(ConstraintProblem::Syntax("exactly 1 a b".to_string()),problem("variables a b\nexactly 1 a b").problem);
+    assert_eq!(ConstraintProblem::Syntax("linear a b != 1".to_string()),problem("variables a b\nlinear a b != 1").problem);
+    assert_eq!(ConstraintProblem::Syntax("forall a".to_string()),problem("variables a\n\n# comment\nforall a").problem);
+    assert_eq!("Line 4: Could not interpret 'forall a'",problem("variables a\n\n# comment\nforall a").to_string());
+}
@@ -0,0 +1,44 @@
+//! Check counting the solutions with exactly k variables true against the full generating function.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::SingleVariableGeneratingFunction;
+
+fn check_against_generating_function<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut factory = F::new(8);
+    let variables : Vec<VariableIndex> = (0..8).map(VariableIndex).collect();
+    let odd : Vec<VariableIndex> = (1..8).step_by(2).map(VariableIndex).collect();
+    let a = factory.exactly_n_of(&variables,3);
+    let b = factory.at_most_n_of(&odd,2);
+    let c = factory.or(a,b); // with multiplicity 2 where both hold.
+    let d = factory.scale(c,3);
+    let v5 = factory.single_variable(VariableIndex(5));
+    let e = factory.xor(d,v5);
+    for f in [NodeIndex::FALSE,NodeIndex::TRUE,a,b,c,d,e] {
+        let SingleVariableGeneratingFunction(expected) = factory.number_solutions::<SingleVariableGeneratingFunction<u64>>(f);
+        for k in 0..=10 {
+            assert_eq!(expected.get(k).cloned().unwrap_or(0),factory.count_solutions_with_k_true::<u64>(f,k),"k={}",k);
+        }
+    }
+}
+
+#[test]
+fn against_generating_function_bdd() { check_against_generating_function::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn against_generating_function_zdd() { check_against_generating_function::<ZDDFactory<u32,u32>>(); }
+
+/// Many variables, where the full generating function would have thousands of entries per node.
+#[test]
+fn many_variables() {
+    const N : u16 = 5000;
+    let variables : Vec<VariableIndex> = (0..N).step_by(50).map(VariableIndex).collect(); // 100 of them.
+    let mut bdd = BDDFactory::<u32,NoMultiplicity>::new(N);
+    let f = bdd.exactly_n_of(&variables,2);
+    assert_eq!(4950,bdd.count_solutions_with_k_true::<u128>(f,2));
+    assert_eq!(4950*(N as u128-100),bdd.count_solutions_with_k_true::<u128>(f,3));
+    assert_eq!(0,bdd.count_solutions_with_k_true::<u128>(f,1));
+    let mut zdd = ZDDFactory::<u32,NoMultiplicity>::new(N);
+    let f = zdd.at_most_n_of(&variables,2);
+    let others = N as u128-100;
+    assert_eq!([1,N as u128,4950+100*others+others*(others-1)/2],[0,1,2].map(|k|zdd.count_solutions_with_k_true::<u128>(f,k)));
+}
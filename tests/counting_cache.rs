@@ -0,0 +1,45 @@
+//! Check that counting with a CountingCache shared by many roots agrees with number_solutions, including after gc.
+
+use std::collections::HashSet;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::generating_function::{CountingCache, SingleVariableGeneratingFunction};
+
+fn check_cache<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut factory = F::new(9);
+    let variables : Vec<VariableIndex> = (0..9).map(VariableIndex).collect();
+    let mut roots = vec![NodeIndex::FALSE,NodeIndex::TRUE];
+    for k in 0..5 {
+        let a = factory.exactly_n_of(&variables[k..],2);
+        let b = factory.at_most_n_of(&variables[..5+k],3);
+        let c = factory.or(a,b);
+        let d = factory.scale(c,k as u32+1);
+        roots.extend([a,b,c,d]);
+    }
+    let mut cache = CountingCache::default();
+    let mut gf_cache = CountingCache::default();
+    for &f in &roots {
+        assert_eq!(factory.number_solutions::<u64>(f),factory.number_solutions_cached::<u64>(f,&mut cache));
+        assert_eq!(factory.number_solutions::<SingleVariableGeneratingFunction<u64>>(f),factory.number_solutions_cached(f,&mut gf_cache));
+    }
+    // each reachable node is remembered once.
+    let reachable : HashSet<u32> = roots.iter().flat_map(|&f|factory.post_order(f)).collect();
+    assert_eq!(reachable.len(),cache.len());
+    // after gc, the kept nodes' values follow them.
+    let keep = [roots[6],roots[13]];
+    let renaming = factory.gc(keep);
+    cache.rename(&renaming);
+    let keep = keep.map(|f|renaming.rename(f).unwrap());
+    let reachable : HashSet<u32> = keep.iter().flat_map(|&f|factory.post_order(f)).collect();
+    assert_eq!(reachable.len(),cache.len());
+    let f = factory.and(keep[0],keep[1]);
+    assert_eq!(factory.number_solutions::<u64>(f),factory.number_solutions_cached::<u64>(f,&mut cache));
+    for f in keep { assert_eq!(factory.number_solutions::<u64>(f),factory.number_solutions_cached::<u64>(f,&mut cache)); }
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn cache_bdd() { check_cache::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn cache_zdd() { check_cache::<ZDDFactory<u32,u32>>(); }
@@ -0,0 +1,41 @@
+//! Check that the core operations work on diagrams far deeper than the call stack could recurse through.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 60000;
+
+/// Long narrow chains: exactly one, at most one, and none of N variables.
+fn check_deep<F:DecisionDiagramFactory<u32,u32>>(mut factory:F,is_bdd:bool) {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let exactly_one = factory.exactly_one_of(&variables);
+    let at_most_one = factory.at_most_n_of(&variables,1);
+    assert_eq!(exactly_one,factory.and(exactly_one,at_most_one));
+    let none = factory.between_n_and_m_of(&variables,0,0);
+    assert_eq!(at_most_one,factory.xor(exactly_one,none));
+    let sum = factory.or(exactly_one,at_most_one); // the solutions with one variable twice.
+    if !is_bdd { assert_eq!(2*N as u64+1,factory.number_solutions::<u64>(sum)); } // counting a deep BDD takes a while.
+    assert_eq!(factory.scale(exactly_one,2),factory.and(sum,exactly_one));
+    if is_bdd {
+        let not_at_most_one = factory.not(at_most_one);
+        assert_eq!(NodeIndex::FALSE,factory.and(not_at_most_one,exactly_one));
+        assert_eq!(factory.at_least_n_of(&variables,2),not_at_most_one);
+    }
+}
+
+#[test]
+fn deep_bdd() { check_deep(BDDFactory::<u32,u32>::new(N),true); }
+
+#[test]
+fn deep_zdd() { check_deep(ZDDFactory::<u32,u32>::new(N),false); }
+
+#[test]
+fn deep_bdd_no_multiplicity() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let exactly_one = factory.exactly_one_of(&variables);
+    let not_exactly_one = factory.not(exactly_one);
+    assert_eq!(NodeIndex::TRUE,factory.or(exactly_one,not_exactly_one));
+    assert_eq!(NodeIndex::FALSE,factory.and(exactly_one,not_exactly_one));
+    assert_eq!(NodeIndex::TRUE,factory.xor(exactly_one,not_exactly_one));
+    assert_eq!(exactly_one,factory.not(not_exactly_one));
+}
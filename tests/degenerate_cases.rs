@@ -0,0 +1,72 @@
+//! Factories at the boundaries of parameter sweeps: no variables, empty variable lists, empty patterns.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::{GeneratingFunctionSplitByMultiplicity, SingleVariableGeneratingFunction};
+use xdd::permutation_diagrams::{factorial, LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+fn constants<F:DecisionDiagramFactory<u32,NoMultiplicity>>(num_variables:u16,true_count:u64) {
+    let mut factory = F::new(num_variables);
+    assert_eq!(true_count,factory.number_solutions::<u64>(NodeIndex::TRUE));
+    assert_eq!(0,factory.number_solutions::<u64>(NodeIndex::FALSE));
+    assert_eq!(SingleVariableGeneratingFunction::<u64>(vec![]),factory.number_solutions(NodeIndex::FALSE));
+    let none = factory.exactly_one_of(&[]);
+    assert_eq!(NodeIndex::FALSE,none);
+    assert_eq!(None,factory.poly_and(&[]));
+    let not_false = factory.not(NodeIndex::FALSE);
+    assert_eq!(1u64<<num_variables,factory.number_solutions::<u64>(not_false));
+    let not_true = factory.not(NodeIndex::TRUE);
+    assert_eq!((1u64<<num_variables)-true_count,factory.number_solutions::<u64>(not_true));
+}
+
+#[test]
+fn no_variables() {
+    constants::<BDDFactory<u32,NoMultiplicity>>(0,1);
+    constants::<ZDDFactory<u32,NoMultiplicity>>(0,1);
+    let factory = BDDFactory::<u32,NoMultiplicity>::new(0);
+    assert_eq!(SingleVariableGeneratingFunction::<u64>(vec![1]),factory.number_solutions(NodeIndex::TRUE));
+    let factory = ZDDFactory::<u32,u32>::new(0);
+    assert_eq!(GeneratingFunctionSplitByMultiplicity::<u64>(vec![1]),factory.number_solutions(NodeIndex::TRUE));
+}
+
+#[test]
+fn few_variables() {
+    for n in 1..3 {
+        constants::<BDDFactory<u32,NoMultiplicity>>(n,1<<n);
+        constants::<ZDDFactory<u32,NoMultiplicity>>(n,1); // TRUE in a ZDD is just the empty set.
+    }
+}
+
+#[test]
+#[should_panic(expected = "only 0 variables")]
+fn variable_out_of_range_bdd() {
+    BDDFactory::<u32,NoMultiplicity>::new(0).single_variable(VariableIndex(0));
+}
+
+#[test]
+#[should_panic(expected = "only 1 variables")]
+fn variable_out_of_range_zdd() {
+    ZDDFactory::<u32,NoMultiplicity>::new(1).exactly_one_of(&[VariableIndex(0),VariableIndex(3)]);
+}
+
+#[test]
+#[should_panic(expected = "sorted")]
+fn unsorted_variables() {
+    BDDFactory::<u32,NoMultiplicity>::new(3).exactly_one_of(&[VariableIndex(2),VariableIndex(1)]);
+}
+
+#[test]
+fn trivial_permutations() {
+    for n in 0..4u16 {
+        let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(n);
+        let all = factory.construct_all_permutations();
+        assert_eq!(factorial::<u64>(n as u32),factory.number_solutions::<u64>(all));
+        let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(n);
+        let all = factory.construct_all_permutations();
+        assert_eq!(factorial::<u64>(n as u32),factory.number_solutions::<u64>(all));
+        // every permutation contains the empty pattern exactly once.
+        let containing = factory.permutations_containing_a_given_pattern(&[]);
+        assert_eq!(GeneratingFunctionSplitByMultiplicity(vec![factorial::<u64>(n as u32)]),factory.number_solutions(containing));
+        let prefix = factory.permutations_distributing_k_prefix_over_n_elements(n as u32,0);
+        assert_eq!(NodeIndex::TRUE,prefix);
+    }
+}
@@ -0,0 +1,37 @@
+//! Check dependent_variables against changing each variable in every assignment.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 7;
+
+fn assignment(x:u32) -> Vec<bool> { (0..N).map(|v|(x>>v)&1==1).collect() }
+
+fn check_dependent<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(31);
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut functions = vec![NodeIndex::FALSE,NodeIndex::TRUE,factory.not(NodeIndex::FALSE)];
+    for _ in 0..60 {
+        let mut f = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(1..3) {
+            let subset : Vec<VariableIndex> = variables.iter().cloned().filter(|_|rng.gen_bool(0.3)).collect();
+            let term = factory.between_n_and_m_of(&subset,rng.gen_range(0..2),rng.gen_range(1..4));
+            let term = if rng.gen_bool(0.3) { let v = factory.single_variable(variables[rng.gen_range(0..N as usize)]); factory.xor(term,v) } else { term };
+            let term = factory.scale(term,rng.gen_range(1..3));
+            f = factory.or(f,term);
+        }
+        functions.push(f);
+    }
+    for f in functions {
+        let expected : Vec<VariableIndex> = variables.iter().cloned().filter(|v|(0..1u32<<N).any(|x|factory.evaluate(f,&assignment(x))!=factory.evaluate(f,&assignment(x^(1<<v.0))))).collect();
+        assert_eq!(expected,factory.dependent_variables(f));
+    }
+}
+
+#[test]
+fn dependent_bdd() { check_dependent::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn dependent_zdd() { check_dependent::<ZDDFactory<u32,u32>>(); }
@@ -0,0 +1,196 @@
+//! Check operations that could be built out of and, or and not against such constructions.
+
+use xdd::{BDDFactory, BooleanOperator, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+
+/// A selection of functions of 3 variables, including the constants.
+fn sample_functions<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F) -> Vec<NodeIndex<u32,NoMultiplicity>> {
+    let v : Vec<_> = (0..3).map(|i|factory.single_variable(VariableIndex(i))).collect();
+    let mut res = vec![NodeIndex::TRUE,NodeIndex::FALSE];
+    res.extend_from_slice(&v);
+    let not_v0 = factory.not(v[0]);
+    res.push(not_v0);
+    res.push(factory.and(v[0],v[2]));
+    res.push(factory.or(v[1],not_v0));
+    res.push(factory.exactly_one_of(&[VariableIndex(0),VariableIndex(1),VariableIndex(2)]));
+    res
+}
+
+/// Check two diagrams represent the same function. They need not be the same node, as exactly_one_of
+/// for ZDDs produces nodes with a FALSE hi branch, which other operations do not.
+fn assert_same<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F,expected:NodeIndex<u32,NoMultiplicity>,found:NodeIndex<u32,NoMultiplicity>) {
+    if expected!=found {
+        let not_expected = factory.not(expected);
+        let not_found = factory.not(found);
+        let extra = factory.and(found,not_expected);
+        let missing = factory.and(expected,not_found);
+        assert_eq!((0,0),(factory.number_solutions::<u64>(extra),factory.number_solutions::<u64>(missing)));
+    }
+}
+
+/// Evaluate a diagram at a given assignment to the 3 variables, by anding with the diagram true just for that assignment.
+fn evaluate<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F,f:NodeIndex<u32,NoMultiplicity>,assignment:[bool;3]) -> bool {
+    let mut minterm = f;
+    for (i,value) in assignment.into_iter().enumerate() {
+        let v = factory.single_variable(VariableIndex(i as u16));
+        let literal = if value { v } else { factory.not(v) };
+        minterm = factory.and(minterm,literal);
+    }
+    factory.number_solutions::<u64>(minterm)>0
+}
+
+const ALL_ASSIGNMENTS : [[bool;3];8] = [[false,false,false],[true,false,false],[false,true,false],[true,true,false],[false,false,true],[true,false,true],[false,true,true],[true,true,true]];
+
+fn check_compose<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(3);
+    let functions = sample_functions(&mut factory);
+    for &f in &functions {
+        for &g0 in &functions {
+            for variable in 0..3 {
+                let composed = factory.compose(f,VariableIndex(variable),g0);
+                for assignment in ALL_ASSIGNMENTS {
+                    let mut substituted = assignment;
+                    substituted[variable as usize] = evaluate(&mut factory,g0,assignment);
+                    assert_eq!(evaluate(&mut factory,f,substituted),evaluate(&mut factory,composed,assignment));
+                }
+            }
+            for &g2 in &functions { // simultaneous substitution of variables 0 and 2.
+                let composed = factory.vector_compose(f,&[(VariableIndex(2),g2),(VariableIndex(0),g0)]);
+                for assignment in ALL_ASSIGNMENTS {
+                    let mut substituted = assignment;
+                    substituted[0] = evaluate(&mut factory,g0,assignment);
+                    substituted[2] = evaluate(&mut factory,g2,assignment);
+                    assert_eq!(evaluate(&mut factory,f,substituted),evaluate(&mut factory,composed,assignment));
+                }
+            }
+        }
+    }
+}
+
+fn check_restrict<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(3);
+    let functions = sample_functions(&mut factory);
+    for &f in &functions {
+        for variable in 0..3 {
+            for value in [false,true] {
+                let restricted = factory.restrict(f,VariableIndex(variable),value);
+                for assignment in ALL_ASSIGNMENTS {
+                    let mut fixed = assignment;
+                    fixed[variable as usize] = value;
+                    assert_eq!(evaluate(&mut factory,f,fixed),evaluate(&mut factory,restricted,assignment));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn restrict_bdd() { check_restrict::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn restrict_zdd() { check_restrict::<ZDDFactory<u32,NoMultiplicity>>() }
+
+#[test]
+fn compose_bdd() { check_compose::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn compose_zdd() { check_compose::<ZDDFactory<u32,NoMultiplicity>>() }
+
+fn check_xor_and_ite<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(3);
+    let functions = sample_functions(&mut factory);
+    for &f in &functions {
+        let not_f = factory.not(f);
+        for &g in &functions {
+            let not_g = factory.not(g);
+            let f_and_not_g = factory.and(f,not_g);
+            let not_f_and_g = factory.and(not_f,g);
+            let expected_xor = factory.or(f_and_not_g,not_f_and_g);
+            let xor = factory.xor(f,g);
+            assert_same(&mut factory,expected_xor,xor);
+            for &h in &functions {
+                let f_and_g = factory.and(f,g);
+                let not_f_and_h = factory.and(not_f,h);
+                let expected_ite = factory.or(f_and_g,not_f_and_h);
+                let ite = factory.ite(f,g,h);
+                assert_same(&mut factory,expected_ite,ite);
+            }
+        }
+    }
+}
+
+#[test]
+fn xor_and_ite_bdd() { check_xor_and_ite::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn xor_and_ite_zdd() { check_xor_and_ite::<ZDDFactory<u32,NoMultiplicity>>() }
+
+fn check_apply<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(3);
+    let functions = sample_functions(&mut factory);
+    for table in 0..16 {
+        let op = BooleanOperator(table);
+        for &f in &functions {
+            for &g in &functions {
+                let applied = factory.apply(f,g,op);
+                for assignment in ALL_ASSIGNMENTS {
+                    let expected = op.evaluate(evaluate(&mut factory,f,assignment),evaluate(&mut factory,g,assignment));
+                    assert_eq!(expected,evaluate(&mut factory,applied,assignment),"{:?}",op);
+                }
+            }
+        }
+    }
+    let f = functions[5];
+    let g = functions[6];
+    let and = factory.and(f,g);
+    let applied = factory.apply(f,g,BooleanOperator::AND);
+    assert_same(&mut factory,and,applied);
+}
+
+#[test]
+fn apply_bdd() { check_apply::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn apply_zdd() { check_apply::<ZDDFactory<u32,NoMultiplicity>>() }
+
+#[test]
+fn boolean_operators() {
+    assert_eq!(BooleanOperator::IMPLIES,BooleanOperator::from_fn(|a,b|!a||b));
+    assert_eq!(BooleanOperator::AND_NOT,BooleanOperator::from_fn(|a,b|a&&!b));
+    assert_eq!(BooleanOperator::NAND,BooleanOperator::AND.negate());
+    assert_eq!(BooleanOperator::EQUIVALENT,BooleanOperator::XOR.negate());
+    assert_eq!(BooleanOperator::from_fn(|a,b|!b||a),BooleanOperator::IMPLIES.swap_arguments());
+    assert_eq!(BooleanOperator::OR,BooleanOperator::OR.swap_arguments());
+}
+
+#[test]
+fn rename_variables_bdd() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(7);
+    let functions = sample_functions(&mut factory);
+    for mapping in [|v:VariableIndex|VariableIndex(v.0+4),|v:VariableIndex|VariableIndex(2*v.0+1)] {
+        for &f in &functions {
+            let renamed = factory.rename_variables(f,mapping);
+            let substitutions : Vec<_> = (0..3).map(|v|(VariableIndex(v),factory.single_variable(mapping(VariableIndex(v))))).collect();
+            assert_eq!(factory.vector_compose(f,&substitutions),renamed);
+        }
+    }
+}
+
+#[test]
+fn rename_variables_zdd() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(7);
+    // families of subsets of the first 3 variables.
+    let variables = [VariableIndex(0),VariableIndex(1),VariableIndex(2)];
+    let mut functions = vec![NodeIndex::TRUE,NodeIndex::FALSE,factory.all_subsets_of(&variables)];
+    for k in 1..3 { functions.push(factory.all_subsets_of_size_k(&variables,k)); }
+    let mapping = |v:VariableIndex|VariableIndex(2*v.0+1);
+    for &f in &functions {
+        let renamed = factory.rename_variables(f,mapping);
+        let expected : Vec<Vec<VariableIndex>> = factory.solutions(f).map(|s|s.into_iter().map(mapping).collect()).collect();
+        let found : Vec<Vec<VariableIndex>> = factory.solutions(renamed).collect();
+        assert_eq!(expected,found);
+    }
+}
+
+#[test]
+#[should_panic(expected="does not keep the order")]
+fn rename_variables_must_keep_order() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(1),VariableIndex(2)]);
+    factory.rename_variables(f,|v|VariableIndex(2-v.0));
+}
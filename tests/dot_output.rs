@@ -0,0 +1,47 @@
+//! Check the options for drawing diagrams with graphviz.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::xdd_with_multiplicity::DotOptions;
+
+fn dot<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&F,root:xdd::NodeIndex<u32,NoMultiplicity>,options:&DotOptions) -> String {
+    let mut res = vec![];
+    factory.make_dot_file_with_options(&mut res,"test",&[(root,None)],|v|format!("v{}",v),options).unwrap();
+    String::from_utf8(res).unwrap()
+}
+
+fn count_nodes_drawn(dot:&str) -> usize { dot.lines().filter(|l|l.contains("xlabel=")).count() }
+
+#[test]
+fn default_options_are_unchanged() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    let f = factory.exactly_n_of(&variables,3);
+    let mut plain = vec![];
+    factory.make_dot_file(&mut plain,"test",&[(f,None)],|v|format!("v{}",v)).unwrap();
+    assert_eq!(String::from_utf8(plain).unwrap(),dot(&factory,f,&DotOptions::default()));
+}
+
+#[test]
+fn max_nodes() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(10);
+    let variables : Vec<VariableIndex> = (0..10).map(VariableIndex).collect();
+    let f = factory.exactly_n_of(&variables,5);
+    assert!(count_nodes_drawn(&dot(&factory,f,&DotOptions::default()))>20);
+    let limited = dot(&factory,f,&DotOptions{max_nodes:Some(20),..Default::default()});
+    assert_eq!(20,count_nodes_drawn(&limited));
+    assert!(limited.contains("label=\"…\""));
+}
+
+#[test]
+fn collapse_and_rank() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(20);
+    let v5 = factory.single_variable(VariableIndex(5));
+    let v15 = factory.single_variable(VariableIndex(15));
+    let f = factory.or(v5,v15);
+    let full = dot(&factory,f,&DotOptions::default());
+    let collapsed = dot(&factory,f,&DotOptions{collapse_dont_care_chains:true,..Default::default()});
+    assert!(count_nodes_drawn(&collapsed)<count_nodes_drawn(&full)/2);
+    assert!(collapsed.contains("\"v0 … v4\""));
+    let ranked = dot(&factory,f,&DotOptions{rank_by_variable:true,..Default::default()});
+    assert_eq!(20,ranked.lines().filter(|l|l.contains("rank=same")).count());
+}
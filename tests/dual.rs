@@ -0,0 +1,45 @@
+//! Check the dual against its definition, f<sup>d</sup>(x) = ¬f(¬x), on every assignment.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+const ALL : u32 = (1<<N)-1;
+
+fn solutions<F:DecisionDiagramFactory<u32,u32>>(factory:&F,f:NodeIndex<u32,u32>) -> HashSet<u32> {
+    factory.solutions(f).map(|s|s.iter().map(|v|1<<v.0).sum()).collect()
+}
+
+fn check_dual<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(17);
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    for _ in 0..50 {
+        let mut f = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(1..4) {
+            let subset : Vec<VariableIndex> = variables.iter().cloned().filter(|_|rng.gen_bool(0.5)).collect();
+            let term = factory.between_n_and_m_of(&subset,rng.gen_range(0..3),rng.gen_range(0..4));
+            let v = factory.single_variable(variables[rng.gen_range(0..N as usize)]);
+            let term = if rng.gen_bool(0.5) { factory.xor(term,v) } else { term };
+            f = factory.or(f,term);
+        }
+        let truth = solutions(&factory,f);
+        let dual = factory.dual(f);
+        let expected : HashSet<u32> = (0..=ALL).filter(|x|!truth.contains(&(ALL^x))).collect();
+        assert_eq!(expected,solutions(&factory,dual));
+        assert_eq!(expected.len() as u64,factory.number_solutions::<u64>(dual));
+        assert_eq!(factory.support(f),factory.dual(dual));
+    }
+    let majority = factory.at_least_n_of(&variables[..5],3);
+    assert_eq!(majority,factory.dual(majority));
+    let everything = factory.not(NodeIndex::FALSE);
+    assert_eq!(NodeIndex::FALSE,factory.dual(everything));
+}
+
+#[test]
+fn dual_bdd() { check_dual::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn dual_zdd() { check_dual::<ZDDFactory<u32,u32>>(); }
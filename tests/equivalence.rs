@@ -0,0 +1,65 @@
+//! Check comparing diagrams in different factories.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, U48, VariableIndex, ZDDFactory};
+
+/// The or of the products x(i)&x(i+1), built in the given order of i.
+fn adjacent_pairs<F:DecisionDiagramFactory<A,M>,A:xdd::NodeAddress,M:xdd::Multiplicity>(factory:&mut F,order:impl Iterator<Item=u16>) -> NodeIndex<A,M> {
+    let mut res = NodeIndex::FALSE;
+    for i in order {
+        let a = factory.single_variable(VariableIndex(i));
+        let b = factory.single_variable(VariableIndex(i+1));
+        let pair = factory.and(a,b);
+        res = factory.or(res,pair);
+    }
+    res
+}
+
+#[test]
+fn bdd_built_two_ways() {
+    let mut factory1 = BDDFactory::<u32,NoMultiplicity>::new(8);
+    let mut factory2 = BDDFactory::<U48,NoMultiplicity>::new(8);
+    let forwards = adjacent_pairs(&mut factory1,0..7);
+    let _unrelated = factory2.exactly_one_of(&[VariableIndex(2),VariableIndex(5)]); // so addresses differ.
+    let backwards = adjacent_pairs(&mut factory2,(0..7).rev());
+    assert!(factory1.equivalent(forwards,&factory2,backwards));
+    assert!(factory2.equivalent(backwards,&factory1,forwards));
+    let fewer = adjacent_pairs(&mut factory2,0..6);
+    assert!(!factory1.equivalent(forwards,&factory2,fewer));
+    let not_forwards = factory1.not(forwards);
+    assert!(!factory1.equivalent(not_forwards,&factory2,backwards));
+    assert!(factory1.equivalent(NodeIndex::TRUE,&factory2,NodeIndex::TRUE));
+    assert!(!factory1.equivalent(NodeIndex::TRUE,&factory2,NodeIndex::FALSE));
+}
+
+#[test]
+fn different_numbers_of_variables_differ() {
+    let factory1 = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let factory2 = BDDFactory::<u32,NoMultiplicity>::new(4);
+    assert!(!factory1.equivalent(NodeIndex::TRUE,&factory2,NodeIndex::TRUE));
+}
+
+#[test]
+fn mbdd_multiplicities_matter() {
+    let mut factory1 = BDDFactory::<u32,u32>::new(8);
+    let mut factory2 = BDDFactory::<u64,u32>::new(8);
+    let forwards = adjacent_pairs(&mut factory1,0..7);
+    let backwards = adjacent_pairs(&mut factory2,(0..7).rev());
+    assert!(factory1.equivalent(forwards,&factory2,backwards));
+    let twice = factory2.or(backwards,backwards);
+    assert!(!factory1.equivalent(forwards,&factory2,twice));
+    let twice_forwards = factory1.or(forwards,forwards);
+    assert!(factory1.equivalent(twice_forwards,&factory2,twice));
+}
+
+#[test]
+fn zdd_family_algebra() {
+    let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    let mut factory1 = ZDDFactory::<u32,NoMultiplicity>::new(5);
+    let two = factory1.all_subsets_of_size_k(&variables,2);
+    let mut factory2 = ZDDFactory::<u16,NoMultiplicity>::new(5);
+    let singletons = factory2.all_subsets_of_size_k(&variables,1);
+    let pairs = factory2.join(singletons,singletons);
+    let pairs = factory2.difference(pairs,singletons);
+    assert!(factory1.equivalent(two,&factory2,pairs));
+    assert!(!factory1.equivalent(two,&factory2,singletons));
+}
@@ -0,0 +1,78 @@
+//! Check exact cover diagrams against combining a constraint for each item, and some known counts.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::exact_cover::ExactCoverProblem;
+
+/// The exact covers, made the slow way from an exactly_one_of or at_most_n_of constraint for each item.
+fn covers_by_items<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F,num_primary_items:usize,num_items:usize,options:&[Vec<usize>]) -> NodeIndex<u32,NoMultiplicity> {
+    let mut res = factory.not(NodeIndex::FALSE);
+    for item in 0..num_items {
+        let containing : Vec<VariableIndex> = (0..options.len()).filter(|&o|options[o].contains(&item)).map(|o|VariableIndex(o as u16)).collect();
+        let constraint = if item<num_primary_items { factory.exactly_one_of(&containing) } else { factory.at_most_n_of(&containing,1) };
+        res = factory.and(res,constraint);
+    }
+    res
+}
+
+fn check_random<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut rng = StdRng::seed_from_u64(91);
+    for _ in 0..200 {
+        let num_items = rng.gen_range(0..8);
+        let num_primary_items = rng.gen_range(0..=num_items);
+        let num_options = rng.gen_range(0..14);
+        let options : Vec<Vec<usize>> = (0..num_options).map(|_|(0..num_items).filter(|_|rng.gen_bool(0.3)).collect()).collect();
+        // some spare variables, which do not matter.
+        let mut factory = F::new(num_options as u16+2);
+        let covers = factory.exact_cover(num_primary_items,&options);
+        assert_eq!(covers_by_items(&mut factory,num_primary_items,num_items,&options),covers);
+    }
+}
+
+#[test]
+fn random_bdd() { check_random::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn random_zdd() { check_random::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+/// n queens, with a primary item for each row and column and a secondary item for each diagonal.
+fn queens(n:usize) -> u64 {
+    let mut problem = ExactCoverProblem::with_items(2*n,4*n-2);
+    for row in 0..n {
+        for column in 0..n { problem.add_option(vec![row,n+column,2*n+row+column,4*n-1+row+n-1-column]); }
+    }
+    let (factory,covers) = problem.build::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>();
+    factory.number_solutions(covers)
+}
+
+#[test]
+fn n_queens() {
+    assert_eq!(vec![1,0,0,2,10,4,40,92],(1..=8).map(queens).collect::<Vec<u64>>());
+}
+
+#[test]
+fn named() {
+    let mut problem = ExactCoverProblem::new(&["x","y"],&["z"]);
+    assert_eq!(Some(2),problem.item("z"));
+    problem.add_named_option(&["x","z"]);
+    problem.add_named_option(&["y","z"]);
+    problem.add_named_option(&["y"]);
+    let (factory,covers) = problem.build::<u32,NoMultiplicity,BDDFactory<u32,NoMultiplicity>>();
+    let solutions : Vec<Vec<Vec<&str>>> = factory.solutions(covers).map(|s|problem.option_names(&s).into_iter().map(|o|o.to_vec()).collect()).collect();
+    assert_eq!(vec![vec![vec!["x","z"],vec!["y"]]],solutions);
+    // an item in no option can not be covered.
+    let problem = ExactCoverProblem::with_items(1,0);
+    assert_eq!(NodeIndex::FALSE,problem.build::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>().1);
+}
+
+/// An option containing an item twice can not be chosen.
+#[test]
+fn repeated_item() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(3);
+    let covers = factory.exact_cover(2,&[vec![0],vec![1,0,1],vec![1]]);
+    let expected = factory.exact_cover(2,&[vec![0],vec![],vec![1]]);
+    let without_1 = factory.exactly_n_of(&[VariableIndex(1)],0);
+    assert_eq!(factory.and(expected,without_1),covers);
+    assert_eq!(1,factory.number_solutions::<u64>(covers));
+}
@@ -0,0 +1,119 @@
+//! Check the ZDD family algebra, the offset, onset and change primitives, and the subset related operations against brute force computation on families of subsets of a few variables, represented as bitmasks.
+
+use std::collections::BTreeSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{DecisionDiagramFactory, Multiplicity, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::GeneratingFunctionWithMultiplicity;
+
+const NUM_VARIABLES : u16 = 5;
+
+type Family = BTreeSet<u32>;
+
+fn random_family(rng:&mut StdRng) -> Family {
+    let size = rng.gen_range(0..8);
+    (0..size).map(|_|rng.gen_range(0..1<<NUM_VARIABLES)).filter(|s:&u32|s.count_ones()<=3).collect()
+}
+
+fn pairs(f:&Family,g:&Family,op:impl Fn(u32,u32)->u32) -> Family {
+    f.iter().flat_map(|&a|g.iter().map(move |&b|(a,b))).map(|(a,b)|op(a,b)).collect()
+}
+
+fn quotient(f:&Family,g:&Family) -> Family {
+    if g.is_empty() { return Family::new(); }
+    (0..1u32<<NUM_VARIABLES).filter(|&a|g.iter().all(|&b|a&b==0 && f.contains(&(a|b)))).collect()
+}
+
+fn build<M:Multiplicity>(factory:&mut ZDDFactory<u32,M>,family:&Family,multiplicity:M) -> NodeIndex<u32,M> {
+    let mut res = NodeIndex::FALSE;
+    for &set in family {
+        let mut member = NodeIndex::TRUE;
+        for v in (0..NUM_VARIABLES).filter(|v|set&(1<<v)!=0) { member = factory.change(member,VariableIndex(v)); }
+        res = factory.union(res,member);
+    }
+    // give every member the given multiplicity, which the set operations should ignore.
+    let all = factory.not(NodeIndex::FALSE).multiply(multiplicity);
+    factory.and(res,all)
+}
+
+fn read<M:Multiplicity>(factory:&ZDDFactory<u32,M>,index:NodeIndex<u32,M>) -> Family {
+    factory.solutions(index).map(|s|s.iter().map(|v|1<<v.0).sum()).collect()
+}
+
+fn check<M:Multiplicity>(multiplicity:M) where u64:GeneratingFunctionWithMultiplicity<M> {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..200 {
+        let mut factory = ZDDFactory::<u32,M>::new(NUM_VARIABLES);
+        let f = random_family(&mut rng);
+        let mut g = random_family(&mut rng);
+        if rng.gen_bool(0.2) { g = pairs(&g,&f,|a,b|a|b); } // so quotients are more interesting.
+        let fi = build(&mut factory,&f,multiplicity);
+        let gi = build(&mut factory,&g,multiplicity);
+        assert_eq!(f,read(&factory,fi));
+        let union = factory.union(fi,gi);
+        assert_eq!(f.union(&g).cloned().collect::<Family>(),read(&factory,union));
+        let intersection = factory.intersection(fi,gi);
+        assert_eq!(f.intersection(&g).cloned().collect::<Family>(),read(&factory,intersection));
+        let difference = factory.difference(fi,gi);
+        assert_eq!(f.difference(&g).cloned().collect::<Family>(),read(&factory,difference));
+        let join = factory.join(fi,gi);
+        assert_eq!(pairs(&f,&g,|a,b|a|b),read(&factory,join));
+        let meet = factory.meet(fi,gi);
+        assert_eq!(pairs(&f,&g,|a,b|a&b),read(&factory,meet));
+        let expected_quotient = quotient(&g,&f);
+        let q = factory.quotient(gi,fi);
+        assert_eq!(expected_quotient,read(&factory,q));
+        let multiple = pairs(&f,&expected_quotient,|a,b|a|b);
+        let remainder = factory.remainder(gi,fi);
+        assert_eq!(g.difference(&multiple).cloned().collect::<Family>(),read(&factory,remainder));
+        let minimal = factory.minimal_elements(gi);
+        assert_eq!(g.iter().filter(|&&a|!g.iter().any(|&b|b!=a && b&a==b)).cloned().collect::<Family>(),read(&factory,minimal));
+        let maximal = factory.maximal_elements(gi);
+        assert_eq!(g.iter().filter(|&&a|!g.iter().any(|&b|b!=a && b&a==a)).cloned().collect::<Family>(),read(&factory,maximal));
+        let subsets = factory.subsets_of(fi,gi);
+        assert_eq!(f.iter().filter(|&&a|g.iter().any(|&b|b&a==a)).cloned().collect::<Family>(),read(&factory,subsets));
+        let supersets = factory.supersets_of(fi,gi);
+        assert_eq!(f.iter().filter(|&&a|g.iter().any(|&b|b&a==b)).cloned().collect::<Family>(),read(&factory,supersets));
+        for result in [union,intersection,difference,join,meet,q,remainder,minimal,maximal,subsets,supersets] {
+            assert_eq!(read(&factory,result).len() as u64,factory.number_solutions::<u64>(result),"result should have unit multiplicities");
+        }
+        let count = factory.number_solutions::<u64>(fi);
+        for v in 0..NUM_VARIABLES {
+            let bit = 1<<v;
+            let offset = factory.offset(fi,VariableIndex(v));
+            assert_eq!(f.iter().filter(|&&s|s&bit==0).cloned().collect::<Family>(),read(&factory,offset));
+            let onset = factory.onset(fi,VariableIndex(v));
+            assert_eq!(f.iter().filter(|&&s|s&bit!=0).map(|s|s^bit).collect::<Family>(),read(&factory,onset));
+            let change = factory.change(fi,VariableIndex(v));
+            assert_eq!(f.iter().map(|s|s^bit).collect::<Family>(),read(&factory,change));
+            // these keep multiplicities.
+            assert_eq!(count,factory.number_solutions::<u64>(offset)+factory.number_solutions::<u64>(onset));
+            assert_eq!(count,factory.number_solutions::<u64>(change));
+            assert_eq!(fi,factory.change(change,VariableIndex(v)));
+        }
+    }
+}
+
+#[test]
+fn no_multiplicity() { check(NoMultiplicity{}) }
+
+#[test]
+fn multiplicities_ignored() { check(3u32) }
+
+#[test]
+fn subsets() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES);
+    let universe = factory.universe();
+    assert_eq!(factory.not(NodeIndex::FALSE),universe);
+    assert_eq!((0..1<<NUM_VARIABLES).collect::<Family>(),read(&factory,universe));
+    for mask in 0..1u32<<NUM_VARIABLES {
+        let variables : Vec<VariableIndex> = (0..NUM_VARIABLES).filter(|v|mask&(1<<v)!=0).map(VariableIndex).collect();
+        let power_set = factory.all_subsets_of(&variables);
+        let expected : Family = (0..=mask).filter(|s|s&!mask==0).collect();
+        assert_eq!(expected,read(&factory,power_set));
+        for k in 0..=variables.len()+1 {
+            let of_size = factory.all_subsets_of_size_k(&variables,k);
+            assert_eq!(expected.iter().filter(|s|s.count_ones() as usize==k).cloned().collect::<Family>(),read(&factory,of_size));
+        }
+    }
+}
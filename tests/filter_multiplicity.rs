@@ -0,0 +1,77 @@
+//! Check support and filter_multiplicity against the multiplicities of individual solutions.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use common::random_function;
+
+const NUM_VARIABLES : u16 = 5;
+
+/// The multiplicity of the assignment given by the bits of assignment, found by restricting each variable.
+fn multiplicity<F:DecisionDiagramFactory<u32,u32>>(factory:&mut F,f:NodeIndex<u32,u32>,assignment:u32) -> u32 {
+    let mut f = f;
+    for v in 0..NUM_VARIABLES {
+        let x = factory.single_variable(VariableIndex(v));
+        let literal = if assignment&(1<<v)!=0 { x } else { factory.not(x) };
+        f = factory.and(f,literal);
+    }
+    factory.number_solutions::<u64>(f) as u32
+}
+
+fn check_filter<F:DecisionDiagramFactory<u32,u32>>(mut factory:F) {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..50 {
+        let f = random_function(&mut factory,&mut rng,NUM_VARIABLES,5);
+        let support = factory.support(f);
+        let repeated = factory.filter_multiplicity(f,|m|m>=2);
+        let odd = factory.filter_multiplicity(f,|m|m%2==1);
+        for assignment in 0..1<<NUM_VARIABLES {
+            let m = multiplicity(&mut factory,f,assignment);
+            assert_eq!(m.min(1),multiplicity(&mut factory,support,assignment));
+            assert_eq!(if m>=2 { m } else { 0 },multiplicity(&mut factory,repeated,assignment));
+            assert_eq!(if m%2==1 { m } else { 0 },multiplicity(&mut factory,odd,assignment));
+        }
+        assert_eq!(f,factory.filter_multiplicity(f,|_|true));
+        assert_eq!(NodeIndex::FALSE,factory.filter_multiplicity(f,|_|false));
+        assert_eq!(support,factory.support(support));
+    }
+}
+
+#[test]
+fn filter_mbdd() { check_filter(BDDFactory::<u32,u32>::new(NUM_VARIABLES)); }
+
+#[test]
+fn filter_mzdd() { check_filter(ZDDFactory::<u32,u32>::new(NUM_VARIABLES)); }
+
+/// The support is the same as dropping the multiplicities by importing into a factory without them.
+#[test]
+fn support_matches_import() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let mut multiset = BDDFactory::<u32,u32>::new(NUM_VARIABLES);
+    let mut set = BDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES);
+    let mut support_set = BDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES);
+    for _ in 0..50 {
+        let f = random_function(&mut multiset,&mut rng,NUM_VARIABLES,5);
+        let support = multiset.support(f);
+        let imported = set.import_from(&multiset,f);
+        assert_eq!(imported,support_set.import_from(&multiset,support));
+        assert_eq!(multiset.number_solutions::<u64>(support),set.number_solutions::<u64>(imported));
+    }
+}
+
+/// Filtering on sign with signed multiplicities.
+#[test]
+fn filter_negative() {
+    let mut factory = ZDDFactory::<u32,i64>::new(3);
+    let [v0,v1,v2] = [0,1,2].map(|v|factory.single_variable(VariableIndex(v)));
+    let f = factory.linear_combination(&[(v0,1),(v1,-2),(v2,4)]);
+    let negative = factory.filter_multiplicity(f,|m|m<0);
+    let positive = factory.filter_multiplicity(f,|m|m>0);
+    assert_eq!(f,factory.linear_combination(&[(negative,1),(positive,1)]));
+    assert!(negative!=NodeIndex::FALSE && positive!=NodeIndex::FALSE);
+    assert_eq!(NodeIndex::FALSE,factory.filter_multiplicity(negative,|m|m>=0));
+    let support = factory.support(f);
+    assert_eq!(support,factory.filter_multiplicity(support,|m|m==1));
+}
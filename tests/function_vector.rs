@@ -0,0 +1,108 @@
+//! Check bitwise operations, comparisons, sums and addition of function vectors against integer arithmetic on every assignment.
+
+use xdd::{BDDFactory, Comparison, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::function_vector::FunctionVector;
+
+const BITS : u16 = 3;
+
+/// The value of a vector for the assignment with variable v true iff bit v of assignment is set.
+fn value<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&F,vector:&FunctionVector<u32,NoMultiplicity>,assignment:u32) -> u64 {
+    vector.bits.iter().enumerate().map(|(i,&b)|if holds(factory,b,assignment) { 1<<i } else { 0 }).sum()
+}
+
+fn holds<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&F,f:NodeIndex<u32,NoMultiplicity>,assignment:u32) -> bool {
+    let assignment : Vec<bool> = (0..2*BITS).map(|v|(assignment>>v)&1==1).collect();
+    factory.evaluate(f,&assignment).is_some()
+}
+
+fn check_vectors<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(2*BITS);
+    let x_variables : Vec<VariableIndex> = (0..BITS).map(VariableIndex).collect();
+    let y_variables : Vec<VariableIndex> = (BITS..2*BITS).map(VariableIndex).collect();
+    let x = FunctionVector::from_variables(&mut factory,&x_variables);
+    let y = FunctionVector::from_variables(&mut factory,&y_variables[..2]); // narrower, so zero extended.
+    let and = x.and(&mut factory,&y);
+    let or = x.or(&mut factory,&y);
+    let xor = x.xor(&mut factory,&y);
+    let not = x.not(&mut factory);
+    let sum = x.add(&mut factory,&y);
+    let wrapped = sum.truncate(BITS as usize);
+    let equal = x.equals(&mut factory,&y);
+    let is_5 = x.equals_constant(&mut factory,5);
+    assert_eq!(4,sum.width());
+    for assignment in 0..1u32<<(2*BITS) {
+        let (a,b) = ((assignment&7) as u64,((assignment>>BITS)&3) as u64);
+        assert_eq!(a&b,value(&factory,&and,assignment));
+        assert_eq!(a|b,value(&factory,&or,assignment));
+        assert_eq!(a^b,value(&factory,&xor,assignment));
+        assert_eq!(!a&7,value(&factory,&not,assignment));
+        assert_eq!(a+b,value(&factory,&sum,assignment));
+        assert_eq!((a+b)&7,value(&factory,&wrapped,assignment));
+        assert_eq!(a==b,holds(&factory,equal,assignment));
+        assert_eq!(a==5,holds(&factory,is_5,assignment));
+    }
+    let five = FunctionVector::constant(&mut factory,5,BITS as usize);
+    assert_eq!(is_5,x.equals(&mut factory,&five));
+    assert_eq!(NodeIndex::FALSE,x.equals_constant(&mut factory,8));
+    let three = FunctionVector::constant(&mut factory,3,2);
+    let eight = five.add(&mut factory,&three);
+    let everything = factory.not(NodeIndex::FALSE);
+    assert_eq!(everything,eight.equals_constant(&mut factory,8));
+}
+
+#[test]
+fn vectors_bdd() { check_vectors::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn vectors_zdd() { check_vectors::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+#[should_panic(expected = "does not fit")]
+fn constant_too_big() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    FunctionVector::constant(&mut factory,4,2);
+}
+
+fn check_constraints<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(2*BITS);
+    let x_variables : Vec<VariableIndex> = (0..BITS).map(VariableIndex).collect();
+    let y_variables : Vec<VariableIndex> = (BITS..2*BITS).map(VariableIndex).collect();
+    let x = FunctionVector::from_variables(&mut factory,&x_variables);
+    let y = FunctionVector::from_variables(&mut factory,&y_variables[..2]);
+    let comparisons = [Comparison::Less,Comparison::LessOrEqual,Comparison::Equal,Comparison::GreaterOrEqual,Comparison::Greater];
+    let expected = |comparison:Comparison,a:u64,b:u64| match comparison {
+        Comparison::Less => a<b, Comparison::LessOrEqual => a<=b, Comparison::Equal => a==b, Comparison::GreaterOrEqual => a>=b, Comparison::Greater => a>b,
+    };
+    let terms = [(VariableIndex(0),3),(VariableIndex(2),5),(VariableIndex(3),1),(VariableIndex(5),4)];
+    let weighted = FunctionVector::weighted_sum(&mut factory,&terms);
+    assert_eq!(4,weighted.width());
+    let three = FunctionVector::constant(&mut factory,3,2);
+    let total = FunctionVector::sum(&mut factory,&[x.clone(),y.clone(),three]);
+    for comparison in comparisons {
+        let vs_y = x.compare(&mut factory,&y,comparison);
+        let vs_constant = x.compare_constant(&mut factory,3,comparison);
+        let vs_big = x.compare_constant(&mut factory,100,comparison);
+        for assignment in 0..1u32<<(2*BITS) {
+            let (a,b) = ((assignment&7) as u64,((assignment>>BITS)&3) as u64);
+            assert_eq!(expected(comparison,a,b),holds(&factory,vs_y,assignment));
+            assert_eq!(expected(comparison,a,3),holds(&factory,vs_constant,assignment));
+            assert_eq!(expected(comparison,a,100),holds(&factory,vs_big,assignment));
+        }
+    }
+    for assignment in 0..1u32<<(2*BITS) {
+        let (a,b) = ((assignment&7) as u64,((assignment>>BITS)&3) as u64);
+        let sum : u64 = terms.iter().filter(|(v,_)|(assignment>>v.0)&1==1).map(|(_,w)|w).sum();
+        assert_eq!(sum,value(&factory,&weighted,assignment));
+        assert_eq!(a+b+3,value(&factory,&total,assignment));
+    }
+    // the same as the direct construction.
+    let signed_terms : Vec<(VariableIndex,i64)> = terms.iter().map(|&(v,w)|(v,w as i64)).collect();
+    let direct = factory.linear_constraint(&signed_terms,Comparison::GreaterOrEqual,7);
+    assert_eq!(direct,weighted.compare_constant(&mut factory,7,Comparison::GreaterOrEqual));
+}
+
+#[test]
+fn constraints_bdd() { check_constraints::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn constraints_zdd() { check_constraints::<ZDDFactory<u32,NoMultiplicity>>(); }
@@ -0,0 +1,83 @@
+//! Check that garbage collection keeps the cached results for surviving nodes, and that they are still right.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::permutation_diagrams::{PermutationDecisionDiagramFactory, Swap};
+use xdd::xdd_with_multiplicity::{Cache, OperationCache};
+
+fn total_entries<F:DecisionDiagramFactory<u32,u32>>(factory:&F) -> usize { factory.cache_stats().iter().map(|(_,s)|s.entries).sum() }
+fn total_hits<F:DecisionDiagramFactory<u32,u32>>(factory:&F) -> u64 { factory.cache_stats().iter().map(|(_,s)|s.hits).sum() }
+
+fn check_gc_keeps_caches<F:DecisionDiagramFactory<u32,u32>>() {
+    let variables : Vec<VariableIndex> = (0..12).map(VariableIndex).collect();
+    let build = |factory:&mut F| {
+        let a = factory.exactly_n_of(&variables,5);
+        let b = factory.at_most_n_of(&variables[3..],4);
+        let c = factory.or(a,b);
+        let d = factory.xor(c,b);
+        (a,b,c,d)
+    };
+    let mut factory = F::new(12);
+    let (a,b,c,d) = build(&mut factory);
+    let junk = factory.at_least_n_of(&variables[1..],9);
+    let junk = factory.or(junk,a);
+    let entries = total_entries(&factory);
+    let renaming = factory.gc([a,b,c,d]);
+    assert_eq!(None,renaming.rename(junk));
+    let [a,b,c,d] = [a,b,c,d].map(|x|renaming.rename(x).unwrap());
+    let kept = total_entries(&factory);
+    assert!(kept>0 && kept<entries,"kept {} of {}",kept,entries);
+    // redoing the work uses the cache, and gets the same answers as a fresh factory.
+    let hits = total_hits(&factory);
+    assert_eq!(c,factory.or(a,b));
+    assert_eq!(d,factory.xor(c,b));
+    assert_eq!(total_hits(&factory),hits+2);
+    let mut fresh = F::new(12);
+    let (fa,fb,fc,fd) = build(&mut fresh);
+    for (x,y) in [(a,fa),(b,fb),(c,fc),(d,fd)] { assert_eq!(fresh.number_solutions::<u64>(y),factory.number_solutions::<u64>(x)); }
+    // and operations after the gc on new nodes still give the right answers.
+    let e = factory.and(d,a);
+    let fe = fresh.and(fd,fa);
+    assert_eq!(fresh.number_solutions::<u64>(fe),factory.number_solutions::<u64>(e));
+    factory.gc([]);
+    assert_eq!(0,total_entries(&factory));
+}
+
+#[test]
+fn gc_keeps_caches_bdd() { check_gc_keeps_caches::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn gc_keeps_caches_zdd() { check_gc_keeps_caches::<ZDDFactory<u32,u32>>(); }
+
+#[test]
+fn bounded_cache_renamed() {
+    let mut factory = BDDFactory::<u32,u32>::new(8);
+    factory.set_cache_capacity(Some(1000));
+    let variables : Vec<VariableIndex> = (0..8).map(VariableIndex).collect();
+    let a = factory.exactly_n_of(&variables,3);
+    let b = factory.at_least_n_of(&variables,2);
+    let c = factory.and(a,b);
+    let renaming = factory.gc([a,b,c]);
+    let [a,b,c] = [a,b,c].map(|x|renaming.rename(x).unwrap());
+    assert!(total_entries(&factory)>0);
+    assert_eq!(c,factory.and(a,b));
+    // a cache on its own, where nothing survives but the sinks.
+    let mut cache : OperationCache<NodeIndex<u32,u32>,NodeIndex<u32,u32>> = OperationCache::default();
+    cache.insert(NodeIndex::TRUE,NodeIndex::FALSE);
+    cache.insert(c,NodeIndex::TRUE);
+    cache.rename(&factory.gc([]));
+    assert_eq!(1,cache.len());
+    assert_eq!(Some(NodeIndex::FALSE),cache.get(&NodeIndex::TRUE));
+}
+
+#[test]
+fn permutation_caches_kept() {
+    let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,u32>::new(5);
+    let all = factory.construct_all_permutations();
+    let cyclic = factory.cyclic_group();
+    let composed = factory.compose(cyclic,all);
+    let renaming = factory.gc([all,cyclic,composed]);
+    let [all,cyclic,composed] = [all,cyclic,composed].map(|x|renaming.rename(x).unwrap());
+    let hits : u64 = factory.cache_stats().iter().map(|(_,s)|s.hits).sum();
+    assert_eq!(composed,factory.compose(cyclic,all));
+    assert!(factory.cache_stats().iter().map(|(_,s)|s.hits).sum::<u64>()>hits);
+}
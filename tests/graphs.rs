@@ -0,0 +1,126 @@
+//! Check the graph families against brute force enumeration on random small graphs.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+use xdd::graphs::Graph;
+
+fn sets(factory:&impl DecisionDiagramFactory<u32,NoMultiplicity>,index:xdd::NodeIndex<u32,NoMultiplicity>) -> Vec<u32> {
+    let mut res : Vec<u32> = factory.solutions(index).map(|s|s.iter().map(|v|1<<v.0).sum()).collect();
+    res.sort();
+    res
+}
+
+fn check<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..50 {
+        let n = rng.gen_range(1..7);
+        let mut graph = Graph::new(n);
+        for a in 0..n {
+            for b in a+1..n {
+                if rng.gen_bool(0.4) { graph.add_edge(a,b); }
+            }
+        }
+        if n>1 && rng.gen_bool(0.3) { graph.add_edge(0,1); } // a parallel edge.
+        let adjacent = |a:usize,b:usize| graph.edges.contains(&(a.min(b),a.max(b)));
+        let pairs = |s:u32| (0..n).flat_map(move |a|(a+1..n).map(move |b|(a,b))).filter(move |&(a,b)|s&(1<<a)!=0 && s&(1<<b)!=0);
+        let mut factory = F::new(n as u16);
+        let independent_sets = graph.independent_sets(&mut factory);
+        assert_eq!((0..1u32<<n).filter(|&s|pairs(s).all(|(a,b)|!adjacent(a,b))).collect::<Vec<_>>(),sets(&factory,independent_sets));
+        let cliques = graph.cliques(&mut factory);
+        assert_eq!((0..1u32<<n).filter(|&s|pairs(s).all(|(a,b)|adjacent(a,b))).collect::<Vec<_>>(),sets(&factory,cliques));
+        let m = graph.edges.len();
+        let mut factory = F::new(m as u16);
+        let matchings = graph.matchings(&mut factory);
+        let covered = |s:u32| graph.edges.iter().enumerate().filter(move |(i,_)|s&(1<<i)!=0).map(|(_,&(a,b))|(1u32<<a)|(1<<b));
+        let expected : Vec<u32> = (0..1u32<<m).filter(|&s|covered(s).map(|c|c.count_ones()).sum::<u32>()==covered(s).fold(0,|x,c|x|c).count_ones()).collect();
+        assert_eq!(expected,sets(&factory,matchings));
+    }
+}
+
+/// The degree of each vertex, and whether the edges are connected, for the subset s of the edges of graph.
+fn degrees_and_connected(graph:&Graph,s:u32) -> (Vec<usize>,bool) {
+    let mut degree = vec![0;graph.num_vertices];
+    let mut component : Vec<usize> = (0..graph.num_vertices).collect();
+    fn root(component:&mut [usize],v:usize) -> usize { if component[v]==v { v } else { let r = root(component,component[v]); component[v]=r; r } }
+    for (i,&(a,b)) in graph.edges.iter().enumerate() {
+        if s&(1<<i)!=0 {
+            degree[a]+=1;
+            degree[b]+=1;
+            let (ra,rb) = (root(&mut component,a),root(&mut component,b));
+            component[ra]=rb;
+        }
+    }
+    let mut roots : Vec<usize> = (0..graph.num_vertices).filter(|&v|degree[v]>0).map(|v|root(&mut component,v)).collect();
+    roots.sort();
+    roots.dedup();
+    (degree,roots.len()<=1)
+}
+
+#[test]
+fn simple_paths_and_cycles() {
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..100 {
+        let n = rng.gen_range(2..7);
+        let mut graph = Graph::new(n);
+        for a in 0..n {
+            for b in a+1..n {
+                if rng.gen_bool(0.5) { graph.add_edge(a,b); }
+            }
+        }
+        if rng.gen_bool(0.3) { graph.add_edge(0,n-1); } // a parallel edge, making a cycle of length 2.
+        let m = graph.edges.len();
+        let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(m as u16);
+        let (s,t) = (rng.gen_range(0..n),rng.gen_range(0..n));
+        if s!=t {
+            let paths = graph.simple_paths(&mut factory,s,t);
+            let expected : Vec<u32> = (0..1u32<<m).filter(|&e|{
+                let (degree,connected) = degrees_and_connected(&graph,e);
+                connected && (0..n).all(|v|degree[v]==if v==s || v==t { 1 } else if degree[v]==0 { 0 } else { 2 })
+            }).collect();
+            assert_eq!(expected,sets(&factory,paths),"{:?} from {} to {}",graph,s,t);
+        }
+        let cycles = graph.simple_cycles(&mut factory);
+        let expected : Vec<u32> = (1..1u32<<m).filter(|&e|{
+            let (degree,connected) = degrees_and_connected(&graph,e);
+            connected && degree.iter().all(|&d|d==0 || d==2)
+        }).collect();
+        assert_eq!(expected,sets(&factory,cycles),"{:?}",graph);
+    }
+}
+
+#[test]
+fn grid_paths_and_cycles() {
+    // OEIS A007764 and A140517.
+    for (n,paths,cycles) in [(2,2,1),(3,12,13),(4,184,213),(5,8512,9349)] {
+        let grid = Graph::grid(n,n);
+        let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(grid.edges.len() as u16+1);
+        let p = grid.simple_paths(&mut factory,0,n*n-1);
+        assert_eq!(2*paths,factory.number_solutions::<u64>(p)); // the last variable is unconstrained.
+        let c = grid.simple_cycles(&mut factory);
+        assert_eq!(2*cycles,factory.number_solutions::<u64>(c));
+    }
+}
+
+#[test]
+fn zdd() { check::<ZDDFactory<u32,NoMultiplicity>>() }
+
+#[test]
+fn bdd() { check::<BDDFactory<u32,NoMultiplicity>>() }
+
+#[test]
+fn known_counts() {
+    let petersen = Graph::from_edges(10,&[(0,1),(1,2),(2,3),(3,4),(4,0),(0,5),(1,6),(2,7),(3,8),(4,9),(5,7),(7,9),(9,6),(6,8),(8,5)]);
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(15);
+    let independent_sets = petersen.independent_sets(&mut factory);
+    // vertices 10 to 14 are unconstrained.
+    assert_eq!(76*32,factory.number_solutions::<u64>(independent_sets));
+    let matchings = petersen.matchings(&mut factory);
+    assert_eq!(1+15+75+145+90+6,factory.number_solutions::<u64>(matchings));
+    let complete = Graph::complete(4);
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(6);
+    let cliques = complete.cliques(&mut factory);
+    assert_eq!(16*4,factory.number_solutions::<u64>(cliques));
+    let matchings = complete.matchings(&mut factory);
+    assert_eq!(10,factory.number_solutions::<u64>(matchings));
+}
@@ -0,0 +1,52 @@
+//! Check implies and is_equivalent against comparing the sets of solutions, and that they make no new nodes.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+
+fn solutions<F:DecisionDiagramFactory<u32,u32>>(factory:&F,f:NodeIndex<u32,u32>) -> HashSet<Vec<VariableIndex>> { factory.solutions(f).collect() }
+
+fn check_implies<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut functions = vec![NodeIndex::FALSE,NodeIndex::TRUE,factory.not(NodeIndex::FALSE)];
+    for _ in 0..30 {
+        let subset : Vec<VariableIndex> = variables.iter().cloned().filter(|_|rng.gen_bool(0.6)).collect();
+        let f = if rng.gen_bool(0.5) { factory.at_least_n_of(&subset,rng.gen_range(0..3)) } else { factory.at_most_n_of(&subset,rng.gen_range(0..3)) };
+        let g = functions[rng.gen_range(0..functions.len())];
+        let f = match rng.gen_range(0..3) { 0 => factory.and(f,g), 1 => factory.or(f,g), _ => f };
+        functions.push(factory.scale(f,rng.gen_range(1..3)));
+    }
+    let len = factory.len();
+    for &f in &functions {
+        for &g in &functions {
+            let (sf,sg) = (solutions(&factory,f),solutions(&factory,g));
+            assert_eq!(sf.is_subset(&sg),factory.implies(f,g));
+            assert_eq!(sf==sg,factory.is_equivalent(f,g));
+        }
+    }
+    assert_eq!(len,factory.len());
+}
+
+#[test]
+fn implies_bdd() { check_implies::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn implies_zdd() { check_implies::<ZDDFactory<u32,u32>>(); }
+
+#[test]
+fn equivalent_without_multiplicities() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(3);
+    let variables = [VariableIndex(0),VariableIndex(1),VariableIndex(2)];
+    let one = factory.exactly_one_of(&variables);
+    let also_one = factory.between_n_and_m_of(&variables,1,1);
+    let at_most_one = factory.at_most_n_of(&variables,1);
+    assert!(factory.is_equivalent(one,also_one));
+    assert!(!factory.is_equivalent(one,at_most_one));
+    assert!(factory.implies(NodeIndex::TRUE,at_most_one)); // the empty set.
+    assert!(!factory.implies(NodeIndex::TRUE,one));
+}
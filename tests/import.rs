@@ -0,0 +1,71 @@
+//! Check copying diagrams between factories.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, U48, VariableIndex, ZDDFactory};
+
+fn variables(n:u16) -> Vec<VariableIndex> { (0..n).map(VariableIndex).collect() }
+
+#[test]
+fn import_leaves_dead_nodes_behind() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(12);
+    let mut f = factory.not(NodeIndex::FALSE);
+    for k in 1..6 { // lots of intermediate results.
+        let window = factory.at_most_n_of(&variables(12)[k..k+6],3);
+        f = factory.and(f,window);
+    }
+    let mut fresh = BDDFactory::<U48,NoMultiplicity>::new(12);
+    let g = fresh.import_from(&factory,f);
+    assert!(factory.equivalent(f,&fresh,g));
+    assert_eq!(factory.post_order(f).count(),fresh.len());
+    assert!(fresh.len()<factory.len());
+    assert_eq!(factory.number_solutions::<u128>(f),fresh.number_solutions::<u128>(g));
+    assert_eq!(NodeIndex::FALSE,fresh.import_from(&factory,NodeIndex::FALSE));
+    assert_eq!(NodeIndex::TRUE,fresh.import_from(&factory,NodeIndex::TRUE));
+}
+
+#[test]
+fn import_into_more_variables() {
+    let mut small = ZDDFactory::<u32,NoMultiplicity>::new(4);
+    let two = small.exactly_n_of(&variables(4),2);
+    let mut large = ZDDFactory::<u64,NoMultiplicity>::new(6);
+    let imported = large.import_from(&small,two);
+    let two_of_first_4 = large.all_subsets_of_size_k(&variables(4),2);
+    assert_eq!(two_of_first_4,imported);
+}
+
+#[test]
+#[should_panic(expected = "Can not import from a factory with 4 variables into one with only 3")]
+fn import_into_fewer_variables() {
+    let small = BDDFactory::<u32,NoMultiplicity>::new(4);
+    let mut smaller = BDDFactory::<u32,NoMultiplicity>::new(3);
+    smaller.import_from(&small,NodeIndex::TRUE);
+}
+
+#[test]
+fn drop_multiplicities() {
+    let mut factory = BDDFactory::<u32,u32>::new(5);
+    let a = factory.at_most_n_of(&variables(5),2);
+    let b = factory.at_least_n_of(&variables(5),2);
+    let c = factory.exactly_one_of(&variables(5)[1..3]);
+    let ab = factory.or(a,b);
+    let f = factory.or(ab,c); // everything, some more than once.
+    assert_eq!(16+26+16,factory.number_solutions::<u64>(f));
+    let mut set = BDDFactory::<u32,NoMultiplicity>::new(5);
+    let g = set.import_from(&factory,f);
+    assert_eq!(NodeIndex::TRUE,g);
+    let mut zdd = ZDDFactory::<u32,u32>::new(5);
+    let h = zdd.exactly_n_of(&variables(5),2);
+    let h = zdd.or(h,h);
+    assert_eq!(20,zdd.number_solutions::<u64>(h));
+    let mut zdd_set = ZDDFactory::<u32,NoMultiplicity>::new(5);
+    let k = zdd_set.import_from(&zdd,h);
+    assert_eq!(10,zdd_set.number_solutions::<u64>(k));
+}
+
+#[test]
+fn add_multiplicities() {
+    let mut set = BDDFactory::<u32,NoMultiplicity>::new(5);
+    let f = set.exactly_n_of(&variables(5),3);
+    let mut multiset = BDDFactory::<u32,u32>::new(5);
+    let g = multiset.import_from(&set,f);
+    assert_eq!(multiset.exactly_n_of(&variables(5),3),g);
+}
@@ -0,0 +1,60 @@
+//! Check that diagrams written as JSON can be read back, with and without multiplicities, and that damaged files are rejected.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+
+fn json<F:DecisionDiagramFactory<u32,M>,M:xdd::Multiplicity>(factory:&F,roots:&[(NodeIndex<u32,M>,Option<String>)]) -> String {
+    let mut res = vec![];
+    factory.make_json_file(&mut res,roots,|v|format!("x\"{}\"",v)).unwrap();
+    String::from_utf8(res).unwrap()
+}
+
+#[test]
+fn round_trip_bdd() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    let three = factory.exactly_n_of(&variables,3);
+    let not_three = factory.not(three);
+    let text = json(&factory,&[(three,Some("three".to_string())),(not_three,None),(NodeIndex::TRUE,Some("all".to_string()))]);
+    assert!(text.contains("\"name\":\"x\\\"5\\\"\""));
+    let mut other = BDDFactory::<u32,NoMultiplicity>::new(8);
+    let roots = other.read_json_file(&mut text.as_bytes()).unwrap();
+    assert_eq!(vec![Some("three".to_string()),None,Some("all".to_string())],roots.iter().map(|(_,n)|n.clone()).collect::<Vec<_>>());
+    assert_eq!(4*20,other.number_solutions::<u64>(roots[0].0));
+    assert_eq!(4*44,other.number_solutions::<u64>(roots[1].0));
+    assert_eq!(NodeIndex::TRUE,roots[2].0);
+    // reading into the same factory gives the same nodes.
+    let same = factory.read_json_file(&mut text.as_bytes()).unwrap();
+    assert_eq!((three,not_three),(same[0].0,same[1].0));
+}
+
+#[test]
+fn round_trip_with_multiplicities() {
+    let mut factory = ZDDFactory::<u32,u32>::new(3);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let v2 = factory.single_variable(VariableIndex(2));
+    let both = factory.and(v0,v2);
+    let f = factory.or(v0,both);
+    let f = factory.or(f,both);
+    let text = json(&factory,&[(f,None)]);
+    assert!(text.contains("multiplicity"));
+    let mut other = ZDDFactory::<u32,u32>::new(3);
+    let g = other.read_json_file(&mut text.as_bytes()).unwrap()[0].0;
+    assert_eq!(factory.number_solutions::<u64>(f),other.number_solutions::<u64>(g));
+    assert_eq!(factory.post_order(f).count(),other.post_order(g).count());
+    // the number of variables must match for a ZDD.
+    assert!(ZDDFactory::<u32,u32>::new(4).read_json_file(&mut text.as_bytes()).is_err());
+}
+
+#[test]
+fn bad_files() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let mut read = |text:&str| factory.read_json_file(&mut text.as_bytes()).map(|roots|roots.len());
+    assert_eq!(1,read(r#"{"num_variables":3,"roots":[{"id":2}],"nodes":[{"id":2,"variable":1,"lo":0,"hi":1}]}"#).unwrap());
+    assert!(read(r#"{"num_variables":3,"roots":[{"id":2}],"nodes":[{"id":2,"variable":1,"lo":0,"hi":1}]"#).is_err()); // truncated
+    assert!(read(r#"{"num_variables":3,"roots":[{"id":3}],"nodes":[{"id":2,"variable":1,"lo":0,"hi":1}]}"#).is_err()); // unknown root
+    assert!(read(r#"{"num_variables":3,"roots":[],"nodes":[{"id":2,"variable":1,"lo":0,"hi":3},{"id":3,"variable":2,"lo":0,"hi":1}]}"#).is_err()); // parent first
+    assert!(read(r#"{"num_variables":3,"roots":[],"nodes":[{"id":2,"variable":1,"lo":0,"hi":1},{"id":3,"variable":1,"lo":0,"hi":2}]}"#).is_err()); // bad order
+    assert!(read(r#"{"num_variables":3,"roots":[],"nodes":[{"id":2,"variable":3,"lo":0,"hi":1}]}"#).is_err()); // too many variables
+    assert!(read(r#"{"num_variables":4,"roots":[],"nodes":[]}"#).is_err());
+    assert!(read(r#"{"roots":[],"nodes":[]}"#).is_err());
+}
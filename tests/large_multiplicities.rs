@@ -0,0 +1,42 @@
+//! Check multiplicities too large for a u32.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use num::BigUint;
+
+/// f or'd with itself n times, so each solution has its multiplicity multiplied by 2^n.
+fn double<F:DecisionDiagramFactory<u32,M>,M:xdd::Multiplicity>(factory:&mut F,mut f:NodeIndex<u32,M>,n:usize) -> NodeIndex<u32,M> {
+    for _ in 0..n { f = factory.or(f,f); }
+    f
+}
+
+#[test]
+fn u64_multiplicities() {
+    let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    let mut factory = ZDDFactory::<u32,u64>::new(6);
+    let two = factory.exactly_n_of(&variables,2);
+    let big = double(&mut factory,two,40);
+    assert_eq!(1u64<<40,big.multiplicity());
+    assert_eq!(15u128<<40,factory.number_solutions::<u128>(big));
+    // the products of multiplicities from different parts of the diagram.
+    let v0 = factory.single_variable(VariableIndex(0));
+    let v0 = double(&mut factory,v0,20);
+    let product = factory.and(big,v0);
+    assert_eq!(5u128<<60,factory.number_solutions::<u128>(product));
+    let mut set = ZDDFactory::<u32,NoMultiplicity>::new(6);
+    let product_set = set.import_from(&factory,product);
+    assert_eq!(5,set.number_solutions::<u64>(product_set));
+}
+
+#[test]
+fn u128_multiplicities() {
+    let variables : Vec<VariableIndex> = (0..3).map(VariableIndex).collect();
+    let mut factory = BDDFactory::<u32,u128>::new(3);
+    let one = factory.exactly_one_of(&variables);
+    let big = double(&mut factory,one,100);
+    assert_eq!(BigUint::from(3u8)<<100,factory.number_solutions::<BigUint>(big));
+    let mut buffer = vec![];
+    factory.write_to(&mut buffer,&[big]).unwrap();
+    let (loaded,roots) = BDDFactory::<u32,u128>::read_from(&mut buffer.as_slice()).unwrap();
+    assert_eq!(vec![big],roots);
+    assert_eq!(BigUint::from(3u8)<<100,loaded.number_solutions::<BigUint>(roots[0]));
+}
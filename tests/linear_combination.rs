@@ -0,0 +1,54 @@
+//! Check linear combinations against repeated sums.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use common::random_function;
+
+const NUM_VARIABLES : u16 = 6;
+
+fn check_against_sums<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(mut factory:F,weights:&[M]) {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..100 {
+        let n = rng.gen_range(0..6);
+        let mut terms = vec![];
+        for _ in 0..n {
+            let f = random_function(&mut factory,&mut rng,NUM_VARIABLES,3);
+            terms.push((f,weights[rng.gen_range(0..weights.len())]));
+        }
+        if n>1 && rng.gen_bool(0.3) { terms.push(terms[0]); } // repeated terms are combined.
+        let mut expected = NodeIndex::FALSE;
+        for &(f,m) in &terms {
+            let scaled = factory.scale(f,m);
+            expected = factory.or(expected,scaled);
+        }
+        assert_eq!(expected,factory.linear_combination(&terms));
+    }
+}
+
+#[test]
+fn linear_combination_bdd() {
+    check_against_sums(BDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES),&[NoMultiplicity{}]);
+    check_against_sums(BDDFactory::<u32,u32>::new(NUM_VARIABLES),&[1,2,3,6]);
+    check_against_sums(BDDFactory::<u32,i64>::new(NUM_VARIABLES),&[1,-1,2,-3,4]);
+}
+
+#[test]
+fn linear_combination_zdd() {
+    check_against_sums(ZDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES),&[NoMultiplicity{}]);
+    check_against_sums(ZDDFactory::<u32,u32>::new(NUM_VARIABLES),&[1,2,3,6]);
+    check_against_sums(ZDDFactory::<u32,i64>::new(NUM_VARIABLES),&[1,-1,2,-3,4]);
+}
+
+#[test]
+fn scale() {
+    let mut factory = ZDDFactory::<u32,i64>::new(NUM_VARIABLES);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let tripled = factory.scale(v0,3);
+    assert_eq!(3*factory.number_solutions::<i64>(v0),factory.number_solutions::<i64>(tripled));
+    assert_eq!(NodeIndex::FALSE,factory.scale(v0,0));
+    assert_eq!(NodeIndex::FALSE,factory.scale(NodeIndex::FALSE,5));
+    assert_eq!(NodeIndex::FALSE,factory.linear_combination(&[(tripled,1),(v0,-3)]));
+}
@@ -0,0 +1,58 @@
+//! Check linear (pseudo-boolean) constraints against brute force evaluation of every assignment.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, Comparison, DecisionDiagramFactory, NoMultiplicity, NodeIndex, VariableIndex, ZDDFactory};
+
+const NUM_VARIABLES : u16 = 7;
+const COMPARISONS : [Comparison;5] = [Comparison::Less,Comparison::LessOrEqual,Comparison::Equal,Comparison::GreaterOrEqual,Comparison::Greater];
+
+fn satisfies(terms:&[(VariableIndex,i64)],comparison:Comparison,bound:i64,assignment:u32) -> bool {
+    let sum : i64 = terms.iter().filter(|(v,_)|assignment&(1<<v.0)!=0).map(|(_,c)|*c).sum();
+    match comparison {
+        Comparison::Less => sum<bound,
+        Comparison::LessOrEqual => sum<=bound,
+        Comparison::Equal => sum==bound,
+        Comparison::GreaterOrEqual => sum>=bound,
+        Comparison::Greater => sum>bound,
+    }
+}
+
+fn check<F:DecisionDiagramFactory<u32,NoMultiplicity>>(make_factory:impl Fn()->F) {
+    let mut rng = StdRng::seed_from_u64(11);
+    for _ in 0..300 {
+        let mut factory = make_factory();
+        let mut terms : Vec<(VariableIndex,i64)> = Vec::new();
+        for v in 0..NUM_VARIABLES {
+            if rng.gen_bool(0.7) { terms.push((VariableIndex(v),rng.gen_range(-6..=6))); }
+        }
+        let bound = rng.gen_range(-10..=10);
+        for comparison in COMPARISONS {
+            let index = factory.linear_constraint(&terms,comparison,bound);
+            let expected : Vec<u32> = (0..1u32<<NUM_VARIABLES).filter(|&a|satisfies(&terms,comparison,bound,a)).collect();
+            assert_eq!(expected.len() as u64,factory.number_solutions::<u64>(index),"{:?} {:?} {}",terms,comparison,bound);
+            let mut found : Vec<u32> = factory.solutions(index).map(|s|s.iter().map(|v|1<<v.0).sum()).collect();
+            found.sort();
+            assert_eq!(expected,found);
+        }
+    }
+}
+
+#[test]
+fn bdd() { check(||BDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES)) }
+
+#[test]
+fn zdd() { check(||ZDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES)) }
+
+#[test]
+fn cardinality_is_a_special_case() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES);
+    let variables : Vec<VariableIndex> = (1..NUM_VARIABLES).step_by(2).map(VariableIndex).collect();
+    let terms : Vec<(VariableIndex,i64)> = variables.iter().map(|&v|(v,1)).collect();
+    for n in 0..5 {
+        assert_eq!(factory.exactly_n_of(&variables,n),factory.linear_constraint(&terms,Comparison::Equal,n as i64));
+        assert_eq!(factory.at_least_n_of(&variables,n),factory.linear_constraint(&terms,Comparison::GreaterOrEqual,n as i64));
+    }
+    assert_eq!(factory.not(NodeIndex::FALSE),factory.linear_constraint(&[],Comparison::Equal,0));
+    assert_eq!(NodeIndex::FALSE,factory.linear_constraint(&[],Comparison::Less,0));
+}
@@ -0,0 +1,112 @@
+//! Check that queries on a memory mapped node store give the same answers as on the factory that saved it.
+#![cfg(feature="mmap")]
+
+use std::io::ErrorKind;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::mapped::MappedNodeList;
+use xdd::xdd_with_multiplicity::{Solutions, XDDBase};
+
+const N : u16 = 9;
+
+/// Map a saved factory. Safety: each test uses its own file, and does not change it while it is mapped.
+fn open_factory<A:xdd::NodeAddress+xdd::serialization::FixedWidth>(path:&std::path::Path) -> std::io::Result<(MappedNodeList<A,u32>,xdd::mapped::MappedFactory<A,u32>)> {
+    unsafe { MappedNodeList::open_factory(path) }
+}
+
+/// Map a saved node list, like [open_factory].
+fn open(path:&std::path::Path) -> std::io::Result<MappedNodeList<u32,u32>> { unsafe { MappedNodeList::open(path) } }
+
+fn build<F:DecisionDiagramFactory<u32,u32>>(factory:&mut F) -> Vec<NodeIndex<u32,u32>> {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let a = factory.exactly_n_of(&variables,4);
+    let b = factory.at_most_n_of(&variables[3..],2);
+    let b = factory.scale(b,5);
+    let c = factory.or(a,b);
+    vec![a,c,NodeIndex::TRUE]
+}
+
+fn check_mapped<F:DecisionDiagramFactory<u32,u32>>(factory:&F,roots:&[NodeIndex<u32,u32>],nodes:&MappedNodeList<u32,u32>,is_bdd:bool) {
+    for &root in roots {
+        let counted : u64 = if is_bdd { nodes.number_solutions::<u64,true>(root,N) } else { nodes.number_solutions::<u64,false>(root,N) };
+        assert_eq!(factory.number_solutions::<u64>(root),counted);
+        let solutions : Vec<Vec<VariableIndex>> = Solutions::new(nodes,root,N,!is_bdd).collect();
+        assert_eq!(factory.solutions(root).collect::<Vec<_>>(),solutions);
+        for assignment in 0..1u32<<N {
+            let values : Vec<bool> = (0..N).map(|v|(assignment>>v)&1==1).collect();
+            let found = if is_bdd { nodes.evaluate::<true>(root,&values) } else { nodes.evaluate::<false>(root,&values) };
+            assert_eq!(factory.evaluate(root,&values),found);
+        }
+        assert!(if is_bdd { nodes.validate::<true>(root,N) } else { nodes.validate::<false>(root,N) }.is_ok());
+    }
+}
+
+#[test]
+fn mapped_bdd() {
+    let path = std::env::temp_dir().join("xdd_mapped_test_bdd.bin");
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    let roots = build(&mut factory);
+    factory.save(&path,&roots).unwrap();
+    let (nodes,saved) = open_factory::<u32>(&path).unwrap();
+    assert!(saved.is_bdd);
+    assert_eq!(N,saved.num_variables);
+    assert_eq!(roots,saved.roots);
+    assert_eq!(factory.len(),nodes.len());
+    check_mapped(&factory,&roots,&nodes,true);
+    assert_eq!(ErrorKind::InvalidData,open_factory::<u64>(&path).err().unwrap().kind()); // wrong address type.
+    assert_eq!(ErrorKind::InvalidData,open(&path).err().unwrap().kind()); // not a bare node list.
+}
+
+#[test]
+fn mapped_zdd() {
+    let path = std::env::temp_dir().join("xdd_mapped_test_zdd.bin");
+    let mut factory = ZDDFactory::<u32,u32>::new(N);
+    let roots = build(&mut factory);
+    factory.save(&path,&roots).unwrap();
+    let (nodes,saved) = open_factory::<u32>(&path).unwrap();
+    assert!(!saved.is_bdd);
+    check_mapped(&factory,&roots,&nodes,false);
+    // copying into memory gives the same nodes.
+    let copy = nodes.to_node_list();
+    for address in 2..nodes.len() as u32+2 { assert!(nodes.node(address)==copy.node(address)); }
+    assert_eq!(Some(5),nodes.find_node_index(nodes.node(5)));
+}
+
+#[test]
+fn mapped_node_list() {
+    let factory_path = std::env::temp_dir().join("xdd_mapped_test_factory.bin");
+    let path = std::env::temp_dir().join("xdd_mapped_test_nodes.bin");
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    let roots = build(&mut factory);
+    factory.save(&factory_path,&roots).unwrap();
+    open_factory::<u32>(&factory_path).unwrap().0.to_node_list().save(&path).unwrap();
+    let nodes = open(&path).unwrap();
+    check_mapped(&factory,&roots,&nodes,true);
+    drop(nodes);
+    // a truncated file is found when opened.
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path,&bytes[..bytes.len()-1]).unwrap();
+    assert_eq!(ErrorKind::UnexpectedEof,open(&path).err().unwrap().kind());
+}
+
+#[test]
+#[should_panic(expected = "read only")]
+fn read_only() {
+    let path = std::env::temp_dir().join("xdd_mapped_test_read_only.bin");
+    let mut factory = ZDDFactory::<u32,u32>::new(N);
+    let roots = build(&mut factory);
+    factory.save(&path,&roots).unwrap();
+    let (mut nodes,_) = open_factory::<u32>(&path).unwrap();
+    let node = nodes.node(2);
+    nodes.add_node(node);
+}
+
+#[test]
+#[should_panic(expected = "sink")]
+fn sink_has_no_node() {
+    let path = std::env::temp_dir().join("xdd_mapped_test_sink.bin");
+    let mut factory = BDDFactory::<u32,u32>::new(N);
+    let roots = build(&mut factory);
+    factory.save(&path,&roots).unwrap();
+    let (nodes,_) = open_factory::<u32>(&path).unwrap();
+    nodes.node(NodeIndex::<u32,u32>::TRUE.address());
+}
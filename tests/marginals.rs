@@ -0,0 +1,51 @@
+//! Check the number of solutions with each variable true against counting with that variable fixed.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 8;
+
+fn check_marginals<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(96);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut factory = F::new(N);
+    for _ in 0..40 {
+        // an or of scaled random cubes, so there are multiplicities and, for a BDD, variables skipped.
+        let mut f = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(0..5) {
+            let mut term = factory.not(NodeIndex::FALSE);
+            for &v in &variables {
+                if rng.gen_bool(0.3) {
+                    let literal = factory.exactly_n_of(&[v],rng.gen_range(0..2));
+                    term = factory.and(term,literal);
+                }
+            }
+            let term = factory.scale(term,rng.gen_range(1..4));
+            f = factory.or(f,term);
+        }
+        let expected : Vec<u64> = variables.iter().map(|&v|factory.number_solutions_given(f,&[(v,true)])).collect();
+        assert_eq!(expected,factory.variable_marginals::<u64>(f));
+        let expected : Vec<u128> = expected.iter().map(|&x|x as u128).collect();
+        assert_eq!(expected,factory.variable_marginals::<u128>(f));
+    }
+    assert_eq!(vec![0;N as usize],factory.variable_marginals::<u64>(NodeIndex::FALSE));
+    let everything = factory.not(NodeIndex::FALSE);
+    assert_eq!(vec![1<<(N-1);N as usize],factory.variable_marginals::<u64>(everything));
+}
+
+#[test]
+fn marginals_bdd() { check_marginals::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn marginals_zdd() { check_marginals::<ZDDFactory<u32,u32>>(); }
+
+#[test]
+fn probabilities() {
+    let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    let mut factory = ZDDFactory::<u32,u32>::new(5);
+    let f = factory.at_most_n_of(&variables,2);
+    let total = factory.number_solutions::<u64>(f) as f64;
+    let probabilities : Vec<f64> = factory.variable_marginals::<u64>(f).iter().map(|&m|m as f64/total).collect();
+    assert_eq!(vec![5.0/16.0;5],probabilities); // 1+4 of the 1+5+10 solutions.
+}
@@ -0,0 +1,77 @@
+//! Check that memory usage reporting tracks growth, reserve and shrink_to_fit for the node stores and factories.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, Node, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::permutation_diagrams::{LeftRotation, PermutationDecisionDiagramFactory};
+use xdd::xdd_with_multiplicity::{ChunkedNodeList, NodeList, NodeListWithFastLookup, XDDBase};
+
+const NODE_SIZE : usize = size_of::<Node<u32,u32>>();
+
+fn check_store<X:XDDBase<u32,u32>+Default>() {
+    let mut nodes = X::default();
+    let empty = nodes.memory_usage();
+    let variables : Vec<VariableIndex> = (0..40).map(VariableIndex).collect();
+    nodes.exactly_n_of_zdd(&variables,20,40);
+    let len = nodes.len();
+    let full = nodes.memory_usage();
+    assert!(full>=empty+len*NODE_SIZE);
+    nodes.reserve(10000);
+    assert_eq!(len,nodes.len());
+    assert!(nodes.memory_usage()>=full);
+    nodes.gc([]);
+    nodes.shrink_to_fit();
+    assert!(nodes.memory_usage()<full);
+    // still works after shrinking.
+    let f = nodes.exactly_n_of_zdd(&variables,20,40);
+    assert_eq!(137846528820,nodes.number_solutions_zdd::<u64>(f,40));
+}
+
+#[test]
+fn node_list() { check_store::<NodeList<u32,u32>>(); }
+
+#[test]
+fn node_list_with_fast_lookup() { check_store::<NodeListWithFastLookup<u32,u32>>(); }
+
+#[test]
+fn chunked_node_list() { check_store::<ChunkedNodeList<u32,u32>>(); }
+
+fn check_factory<F:DecisionDiagramFactory<u32,NoMultiplicity>>(mut factory:F) {
+    let variables : Vec<VariableIndex> = (0..30).map(VariableIndex).collect();
+    let f = factory.exactly_n_of(&variables,15);
+    let g = factory.at_most_n_of(&variables[10..],8);
+    let before = factory.memory_usage();
+    let h = factory.and(f,g);
+    let cache_memory : usize = factory.cache_stats().iter().map(|(_,cache)|cache.memory).sum();
+    assert!(cache_memory>0);
+    assert!(factory.memory_usage()>before);
+    assert!(factory.memory_usage()>=cache_memory+factory.len()*size_of::<Node<u32,NoMultiplicity>>());
+    let h = factory.gc([h]).rename(h).unwrap();
+    let after_gc = factory.memory_usage();
+    factory.shrink_to_fit();
+    assert!(factory.memory_usage()<after_gc);
+    assert!(factory.cache_stats().iter().map(|(_,cache)|cache.memory).sum::<usize>()<cache_memory); // only results for h's nodes are kept.
+    factory.reserve(1000);
+    assert!(factory.memory_usage()>=1000*size_of::<Node<u32,NoMultiplicity>>());
+    let count = factory.number_solutions::<u64>(h);
+    assert!(count>0);
+}
+
+#[test]
+fn bdd_factory() { check_factory(BDDFactory::<u32,NoMultiplicity>::new(30)); }
+
+#[test]
+fn zdd_factory() { check_factory(ZDDFactory::<u32,NoMultiplicity>::new(30)); }
+
+#[test]
+fn permutation_factory() {
+    let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(6);
+    let s_n = factory.construct_all_permutations();
+    let before = factory.memory_usage();
+    let squared = factory.compose(s_n,s_n);
+    let after = factory.memory_usage();
+    assert!(after>before);
+    let cache_memory : usize = factory.cache_stats().iter().map(|(_,cache)|cache.memory).sum();
+    factory.gc([squared]);
+    factory.shrink_to_fit();
+    assert!(factory.memory_usage()<after);
+    assert!(factory.cache_stats().iter().map(|(_,cache)|cache.memory).sum::<usize>()<cache_memory);
+}
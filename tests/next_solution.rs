@@ -0,0 +1,61 @@
+//! Check that stepping from solution to solution with next_solution_after finds the same solutions as iterating over them,
+//! and that the solution after an arbitrary assignment is the first one after it.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+
+const N : u16 = 7;
+
+/// An assignment with variable 0 first, so Vec comparison is the lexicographic order.
+fn as_bools(solution:&[VariableIndex]) -> Vec<bool> {
+    (0..N).map(|v|solution.contains(&VariableIndex(v))).collect()
+}
+
+fn check_next<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut rng = StdRng::seed_from_u64(92);
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    for _ in 0..30 {
+        // a random function, as the or of some random cubes.
+        let mut f = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(0..6) {
+            let literals : Vec<(VariableIndex,bool)> = variables.iter().filter_map(|&v|if rng.gen_bool(0.5) { Some((v,rng.gen_bool(0.5))) } else { None }).collect();
+            let mut term = factory.not(NodeIndex::FALSE);
+            for (v,value) in literals {
+                let literal = factory.exactly_n_of(&[v],value as usize);
+                term = factory.and(term,literal);
+            }
+            f = factory.or(f,term);
+        }
+        let all : Vec<Vec<VariableIndex>> = factory.solutions(f).collect();
+        // stepping through gives the same solutions.
+        let mut stepped = vec![];
+        let mut current = all.first().cloned();
+        while let Some(solution) = current {
+            current = factory.next_solution_after(f,&solution);
+            stepped.push(solution);
+        }
+        assert_eq!(all,stepped);
+        // the solution after an arbitrary assignment.
+        for _ in 0..20 {
+            let assignment : Vec<VariableIndex> = variables.iter().filter(|_|rng.gen_bool(0.5)).cloned().collect();
+            let expected = all.iter().find(|s|as_bools(s)>as_bools(&assignment)).cloned();
+            assert_eq!(expected,factory.next_solution_after(f,&assignment));
+        }
+    }
+    assert_eq!(None,factory.next_solution_after(NodeIndex::FALSE,&[]));
+}
+
+#[test]
+fn next_bdd() { check_next::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn next_zdd() { check_next::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+#[should_panic(expected = "sorted")]
+fn unsorted_assignment() {
+    let factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    factory.next_solution_after(NodeIndex::TRUE,&[VariableIndex(2),VariableIndex(1)]);
+}
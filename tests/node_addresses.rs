@@ -0,0 +1,60 @@
+//! Check the compact node address types u16 and U48 work, and that nodes using them are not padded.
+
+use std::mem::size_of;
+use xdd::{BDDFactory, DecisionDiagramFactory, Node, NodeAddress, NoMultiplicity, U48, VariableIndex, ZDDFactory};
+
+#[test]
+fn node_sizes() {
+    assert_eq!(6,size_of::<Node<u16,NoMultiplicity>>());
+    assert_eq!(14,size_of::<Node<U48,NoMultiplicity>>());
+    assert_eq!(6,size_of::<U48>());
+    assert_eq!(1,std::mem::align_of::<U48>());
+}
+
+#[test]
+fn u48_conversions() {
+    for value in [0u64,1,255,256,0x1234_5678_9abc,(1<<48)-1] {
+        let a = U48::try_from(value).unwrap();
+        assert_eq!(value,u64::from(a));
+        assert_eq!(value.to_string(),a.to_string());
+    }
+    assert!(U48::try_from(1u64<<48).is_err());
+    assert_eq!((1u64<<48)-1,u64::from(U48::MAX));
+    assert!(U48::try_from(256u64).unwrap()>U48::try_from(255u64).unwrap());
+    assert_eq!(U48::TRUE,U48::try_from(1usize).unwrap());
+}
+
+fn count_with<A:NodeAddress,F:DecisionDiagramFactory<A,NoMultiplicity>>() -> u64 {
+    let mut factory = F::new(12);
+    let variables : Vec<VariableIndex> = (0..12).map(VariableIndex).collect();
+    let f = factory.exactly_n_of(&variables,5);
+    let v3 = factory.single_variable(VariableIndex(3));
+    let g = factory.and(f,v3);
+    factory.number_solutions(g)
+}
+
+#[test]
+fn factories_work() {
+    assert_eq!(330,count_with::<u16,BDDFactory<u16,NoMultiplicity>>());
+    assert_eq!(330,count_with::<U48,BDDFactory<U48,NoMultiplicity>>());
+    assert_eq!(330,count_with::<u16,ZDDFactory<u16,NoMultiplicity>>());
+    assert_eq!(330,count_with::<U48,ZDDFactory<U48,NoMultiplicity>>());
+}
+
+#[test]
+fn u48_save_and_load() {
+    let mut factory = BDDFactory::<U48,NoMultiplicity>::new(4);
+    let f = factory.exactly_n_of(&[VariableIndex(0),VariableIndex(1),VariableIndex(3)],2);
+    let mut saved = vec![];
+    factory.write_to(&mut saved,&[f]).unwrap();
+    let (loaded,roots) = BDDFactory::<U48,NoMultiplicity>::read_from(&mut saved.as_slice()).unwrap();
+    assert_eq!(6,loaded.number_solutions::<u64>(roots[0]));
+}
+
+#[test]
+#[should_panic(expected="Too many nodes")]
+fn u16_overflow() {
+    let mut factory = ZDDFactory::<u16,NoMultiplicity>::new(600);
+    let variables : Vec<VariableIndex> = (0..600).map(VariableIndex).collect();
+    factory.exactly_n_of(&variables,300); // about 300*300 nodes.
+}
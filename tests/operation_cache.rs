@@ -0,0 +1,64 @@
+//! Check bounded operation caches give the same answers as unbounded ones, stay within their capacity, and report statistics.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::xdd_with_multiplicity::{Cache, OperationCache};
+
+#[test]
+fn bounded_cache() {
+    let mut cache : OperationCache<u32,u32> = OperationCache::default();
+    assert_eq!(None,cache.capacity());
+    cache.set_capacity(Some(10));
+    assert_eq!(Some(10),cache.capacity());
+    for i in 0..100 { cache.insert(i,i*2); }
+    assert!(cache.len()<=10 && !cache.is_empty());
+    assert_eq!(100,cache.len() as u64+cache.evictions());
+    let found : Vec<u32> = (0..100).filter(|i|cache.get(i).is_some()).collect();
+    assert_eq!(cache.len(),found.len());
+    for i in found { assert_eq!(Some(i*2),cache.get(&i)); }
+    cache.insert(5,11);
+    cache.insert(5,12); // replacing the same key is not an eviction.
+    let evictions = cache.evictions();
+    cache.insert(5,13);
+    assert_eq!(evictions,cache.evictions());
+    assert_eq!(Some(13),cache.get(&5));
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(None,cache.get(&5));
+    cache.set_capacity(None);
+    for i in 0..100 { cache.insert(i,i); }
+    assert_eq!(100,cache.len());
+}
+
+/// Results with tiny caches should be identical to those with unlimited caches.
+fn check_same_results<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let build = |factory:&mut F| {
+        let variables : Vec<VariableIndex> = (0..14).map(VariableIndex).collect();
+        let a = factory.exactly_n_of(&variables,7);
+        let b = factory.at_most_n_of(&variables[2..],4);
+        let c = factory.xor(a,b);
+        let d = factory.or(c,a);
+        let e = factory.not(d);
+        let f = factory.ite(a,b,e);
+        [a,b,c,d,e,f].map(|x|factory.number_solutions::<u64>(x))
+    };
+    let mut unbounded = F::new(14);
+    let expected = build(&mut unbounded);
+    assert!(unbounded.cache_stats().iter().all(|(_,s)|s.capacity.is_none() && s.evictions==0));
+    let mut bounded = F::new(14);
+    let num_caches = unbounded.cache_stats().len();
+    bounded.set_cache_capacity(Some(10*num_caches));
+    assert_eq!(expected,build(&mut bounded));
+    let stats = bounded.cache_stats();
+    assert_eq!(num_caches,stats.len());
+    for (name,s) in &stats {
+        assert_eq!(Some(10),s.capacity,"{}",name);
+        assert!(s.entries<=10,"{}",name);
+    }
+    assert!(stats.iter().map(|(_,s)|s.evictions).sum::<u64>()>0);
+    assert!(bounded.summary().to_string().contains("evictions"));
+}
+
+#[test]
+fn same_results_bdd() { check_same_results::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn same_results_zdd() { check_same_results::<ZDDFactory<u32,NoMultiplicity>>() }
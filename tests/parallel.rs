@@ -0,0 +1,110 @@
+//! Check the parallel poly_and and poly_or give the same results as doing it one at a time.
+#![cfg(feature="parallel")]
+
+use std::sync::{Arc, Mutex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, Comparison, DecisionDiagramFactory, Multiplicity, NodeIndex, NoMultiplicity, VariableIndex, XDDError, ZDDFactory};
+use xdd::statistics::{CancellationToken, Progress};
+
+/// Random clauses, each an "at most one of" or "at least one of" a few variables.
+fn clauses<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,num_variables:u16,num_clauses:usize) -> Vec<NodeIndex<u32,M>> {
+    let mut rng = StdRng::seed_from_u64(3);
+    (0..num_clauses).map(|i|{
+        let mut variables : Vec<VariableIndex> = (0..3).map(|_|VariableIndex(rng.gen_range(0..num_variables))).collect();
+        variables.sort();
+        variables.dedup();
+        if i%2==0 { factory.at_most_n_of(&variables,1) } else { factory.at_least_n_of(&variables,1) }
+    }).collect()
+}
+
+fn sequential_or<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,indices:&[NodeIndex<u32,M>]) -> NodeIndex<u32,M> {
+    indices.iter().fold(NodeIndex::FALSE,|acc,&i|factory.or(acc,i))
+}
+
+#[test]
+fn same_as_sequential_bdd() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(30);
+    let clauses = clauses(&mut factory,30,60);
+    let parallel = factory.par_poly_and(&clauses);
+    assert_eq!(factory.poly_and(&clauses),parallel);
+    let parallel = factory.par_poly_or(&clauses[..20]).unwrap();
+    assert_eq!(sequential_or(&mut factory,&clauses[..20]),parallel);
+    assert_eq!(None,factory.par_poly_and(&[]));
+    assert_eq!(Some(clauses[5]),factory.par_poly_or(&clauses[5..6]));
+}
+
+#[test]
+fn same_as_sequential_zdd() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(30);
+    let clauses = clauses(&mut factory,30,60);
+    let parallel = factory.par_poly_and(&clauses);
+    assert_eq!(factory.poly_and(&clauses),parallel);
+    let parallel = factory.par_poly_or(&clauses[..20]).unwrap();
+    assert_eq!(sequential_or(&mut factory,&clauses[..20]),parallel);
+}
+
+#[test]
+fn same_as_sequential_with_multiplicities() {
+    let mut factory = BDDFactory::<u32,u32>::new(16);
+    let clauses = clauses(&mut factory,16,24);
+    let parallel = factory.par_poly_or(&clauses).unwrap();
+    assert_eq!(sequential_or(&mut factory,&clauses),parallel);
+    let parallel = factory.par_poly_and(&clauses[..8]);
+    assert_eq!(factory.poly_and(&clauses[..8]),parallel);
+}
+
+/// Running out of node addresses in a parallel operation is reported by try_operation, as for the sequential ones.
+fn check_capacity<F:DecisionDiagramFactory<u16,NoMultiplicity>>(factory:&mut F,par_poly_and:impl Fn(&mut F,&[NodeIndex<u16,NoMultiplicity>])->Option<NodeIndex<u16,NoMultiplicity>>) {
+    // two diagrams of a few thousand nodes whose conjunction needs well over 65534.
+    let variables : Vec<VariableIndex> = (0..120).map(VariableIndex).collect();
+    let f = factory.exactly_n_of(&variables,30);
+    let terms : Vec<(VariableIndex,i64)> = variables.iter().map(|&v|(v,1+(v.0 as i64)%7)).collect();
+    let g = factory.linear_constraint(&terms,Comparison::Equal,120);
+    assert_eq!(Err(XDDError::CapacityExceeded),factory.try_operation(|factory|par_poly_and(factory,&[f,g])));
+    // still usable.
+    assert_eq!(Ok(Some(f)),factory.try_operation(|factory|par_poly_and(factory,&[f,f])));
+    let renaming = factory.gc([f]);
+    let f = renaming.rename(f).unwrap();
+    assert_eq!(Ok(Some(NodeIndex::FALSE)),factory.try_operation(|factory|{
+        let not_f = factory.not(f);
+        par_poly_and(factory,&[f,not_f])
+    }));
+}
+
+#[test]
+fn capacity_exceeded_bdd() { check_capacity(&mut BDDFactory::<u16,NoMultiplicity>::new(120),|factory,indices|factory.par_poly_and(indices)); }
+
+#[test]
+fn capacity_exceeded_zdd() { check_capacity(&mut ZDDFactory::<u16,NoMultiplicity>::new(120),|factory,indices|factory.par_poly_and(indices)); }
+
+/// Progress callbacks see the nodes made by every thread, and a cancelled parallel operation leaves the factory usable.
+fn check_monitoring<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F,par_poly_and:impl Fn(&mut F,&[NodeIndex<u32,NoMultiplicity>])->Option<NodeIndex<u32,NoMultiplicity>>) {
+    let clauses = clauses(factory,30,60);
+    let reports : Arc<Mutex<Vec<Progress>>> = Arc::new(Mutex::new(vec![]));
+    let in_callback = reports.clone();
+    factory.set_progress_callback(10,move |progress|in_callback.lock().unwrap().push(*progress));
+    let len = factory.len();
+    par_poly_and(factory,&clauses);
+    let made = factory.len()-len;
+    assert_eq!(made/10,reports.lock().unwrap().len());
+    assert_eq!(Some(10*(made as u64/10)),reports.lock().unwrap().last().map(|p|p.nodes_created));
+    factory.clear_progress_callback();
+    // without the nodes made, so the operation has to make them again.
+    let renaming = factory.gc(clauses.clone());
+    let clauses : Vec<_> = clauses.iter().map(|&c|renaming.rename(c).unwrap()).collect();
+    let token = CancellationToken::new();
+    factory.set_cancellation_token(Some(token.clone()));
+    let stop = token.clone();
+    factory.set_progress_callback(100,move |_|stop.cancel());
+    assert_eq!(Err(XDDError::Cancelled),factory.try_operation(|factory|par_poly_and(factory,&clauses)));
+    assert!(token.is_cancelled());
+    factory.set_cancellation_token(None);
+    assert_eq!(factory.poly_and(&clauses),par_poly_and(factory,&clauses));
+}
+
+#[test]
+fn monitoring_bdd() { check_monitoring(&mut BDDFactory::<u32,NoMultiplicity>::new(30),|factory,indices|factory.par_poly_and(indices)); }
+
+#[test]
+fn monitoring_zdd() { check_monitoring(&mut ZDDFactory::<u32,NoMultiplicity>::new(30),|factory,indices|factory.par_poly_and(indices)); }
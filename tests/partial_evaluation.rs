@@ -0,0 +1,64 @@
+//! Check evaluation with some variables unknown against trying every value of the unknown variables.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, PartialEvaluation, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+
+/// The assignments, as bit masks, for which f is true.
+fn true_assignments<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&F,f:NodeIndex<u32,NoMultiplicity>) -> HashSet<u32> {
+    factory.solutions(f).map(|s|s.iter().map(|v|1<<v.0).sum()).collect()
+}
+
+fn check_partial<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut rng = StdRng::seed_from_u64(81);
+    let mut factory = F::new(N);
+    for _ in 0..40 {
+        // a random function, as the or of some minterms.
+        let mut f = NodeIndex::FALSE;
+        let everything = factory.not(NodeIndex::FALSE);
+        let density = rng.gen_range(0.0..1.0);
+        for minterm in 0..1u32<<N {
+            if rng.gen_bool(density) {
+                let mut term = everything;
+                for v in 0..N {
+                    let literal = factory.exactly_n_of(&[VariableIndex(v)],((minterm>>v)&1) as usize);
+                    term = factory.and(term,literal);
+                }
+                f = factory.or(f,term);
+            }
+        }
+        let truth = true_assignments(&factory,f);
+        for _ in 0..10 {
+            let assignment : Vec<Option<bool>> = (0..rng.gen_range(0..=N)).map(|_|if rng.gen_bool(0.6) { Some(rng.gen_bool(0.5)) } else { None }).collect();
+            let fixed = |x:u32| assignment.iter().enumerate().fold(x,|x,(v,value)|match value { Some(true) => x|1<<v, Some(false) => x&!(1<<v), None => x });
+            let values : HashSet<bool> = (0..1u32<<N).map(|x|truth.contains(&fixed(x))).collect();
+            match factory.evaluate_partial(f,&assignment) {
+                PartialEvaluation::Determined(value) => assert_eq!(HashSet::from([value]),values),
+                PartialEvaluation::Undetermined(restricted) => {
+                    assert_eq!(2,values.len());
+                    let expected : HashSet<u32> = (0..1u32<<N).filter(|&x|truth.contains(&fixed(x))).collect();
+                    assert_eq!(expected,true_assignments(&factory,restricted));
+                }
+            }
+        }
+    }
+    let everything = factory.not(NodeIndex::FALSE);
+    assert_eq!(Some(true),factory.evaluate_partial(everything,&[]).determined());
+    assert_eq!(Some(false),factory.evaluate_partial(NodeIndex::FALSE,&[None]).determined());
+}
+
+#[test]
+fn partial_bdd() { check_partial::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn partial_zdd() { check_partial::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+#[should_panic(expected = "only 2 variables")]
+fn too_many_variables() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(2);
+    factory.evaluate_partial(NodeIndex::TRUE,&[None,None,Some(true)]);
+}
@@ -0,0 +1,49 @@
+//! Check that the pattern containment algorithms give the same permutations for πDDs and Rot-πDDs.
+
+use std::collections::HashSet;
+use xdd::{NodeIndex, NoMultiplicity};
+use xdd::generating_function::GeneratingFunctionSplitByMultiplicity;
+use xdd::permutation::PermutedItem;
+use xdd::permutation_diagrams::{factorial, LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+const N : u16 = 6;
+
+fn check_same_permutations(construct_swap:impl Fn(&mut PermutationDecisionDiagramFactory<Swap,u32,NoMultiplicity>)->NodeIndex<u32,NoMultiplicity>,construct_rot:impl Fn(&mut PermutationDecisionDiagramFactory<LeftRotation,u32,NoMultiplicity>)->NodeIndex<u32,NoMultiplicity>) {
+    let mut swap = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(N);
+    let mut rot = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(N);
+    let s = construct_swap(&mut swap);
+    let r = construct_rot(&mut rot);
+    let from_swap : HashSet<Vec<PermutedItem>> = swap.permutations(s).map(|p|p.sequence).collect();
+    let from_rot : HashSet<Vec<PermutedItem>> = rot.permutations(r).map(|p|p.sequence).collect();
+    assert_eq!(from_rot,from_swap);
+    assert_eq!(swap.number_solutions::<u64>(s),from_swap.len() as u64);
+}
+
+#[test]
+fn prefix_sets_agree() {
+    let n = N as PermutedItem;
+    for k in 0..=n {
+        check_same_permutations(|f|f.permutations_with_ordered_k_prefix(n,k),|f|f.permutations_with_ordered_k_prefix(n,k));
+        check_same_permutations(|f|f.permutations_distributing_k_prefix_over_n_elements(n,k),|f|f.permutations_distributing_k_prefix_over_n_elements(n,k));
+    }
+}
+
+#[test]
+fn containing_patterns_agree() {
+    for pattern in [&[1,2][..],&[2,1],&[1,3,2],&[2,3,1],&[1,3,2,4],&[4,2,3,1],&[2,4,1,3,5]] {
+        check_same_permutations(|f|f.permutations_containing_a_given_pattern(pattern),|f|f.permutations_containing_a_given_pattern(pattern));
+    }
+}
+
+/// Each permutation contains the empty pattern exactly once, and patterns longer than the permutation not at all.
+#[test]
+fn trivial_patterns() {
+    for n in 0..4u16 {
+        let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,u32>::new(n);
+        let containing = factory.permutations_containing_a_given_pattern(&[]);
+        assert_eq!(GeneratingFunctionSplitByMultiplicity(vec![factorial::<u64>(n as u32)]),factory.number_solutions(containing));
+        assert_eq!(NodeIndex::TRUE,factory.permutations_distributing_k_prefix_over_n_elements(n as u32,0));
+        let too_long : Vec<PermutedItem> = (1..=n as PermutedItem+1).collect();
+        assert_eq!(NodeIndex::FALSE,factory.permutations_containing_a_given_pattern(&too_long));
+    }
+}
@@ -0,0 +1,48 @@
+//! Check cosets and conjugation against composition with a single permutation.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use xdd::NodeIndex;
+use xdd::permutation::{Permutation, PermutedItem};
+use xdd::permutation_diagrams::{LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+const N : u16 = 6;
+
+fn random_permutation(rng:&mut StdRng) -> Vec<PermutedItem> {
+    let mut res : Vec<PermutedItem> = (1..=N as PermutedItem).collect();
+    res.shuffle(rng);
+    res
+}
+
+macro_rules! check_cosets {
+    ($name:ident,$interpretation:ty) => {
+        #[test]
+        fn $name() {
+            let mut rng = StdRng::seed_from_u64(41);
+            let mut factory = PermutationDecisionDiagramFactory::<$interpretation,u32,u32>::new(N);
+            let s_n = factory.construct_all_permutations();
+            for _ in 0..30 {
+                let mut set = NodeIndex::FALSE;
+                for _ in 0..rng.gen_range(0..8) {
+                    let p = factory.compute_for_single_permutation(&random_permutation(&mut rng));
+                    set = factory.linear_combination(&[(set,1),(p,rng.gen_range(1..4))]);
+                }
+                let g = random_permutation(&mut rng);
+                let single = factory.compute_for_single_permutation(&g);
+                let inverse = factory.compute_for_single_permutation(&Permutation{sequence:g.clone()}.inverse().sequence);
+                assert_eq!(factory.compose(set,single),factory.right_coset(set,&g));
+                assert_eq!(factory.compose(single,set),factory.left_coset(&g,set));
+                let left = factory.compose(inverse,set);
+                let conjugate = factory.conjugate(&g,set);
+                assert_eq!(factory.compose(left,single),conjugate);
+                assert_eq!(factory.number_solutions::<u64>(set),factory.number_solutions::<u64>(conjugate));
+                assert_eq!(s_n,factory.left_coset(&g,s_n));
+                assert_eq!(s_n,factory.right_coset(s_n,&g));
+            }
+        }
+    }
+}
+
+check_cosets!(swap_cosets,Swap);
+check_cosets!(rotation_cosets,LeftRotation);
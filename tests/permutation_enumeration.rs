@@ -0,0 +1,78 @@
+//! Check that the permutations decoded from πDDs and Rot-πDDs agree with the operations on them.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use xdd::{NodeIndex, NoMultiplicity};
+use xdd::permutation::{Permutation, PermutedItem};
+use xdd::permutation_diagrams::{factorial, LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+const N : u16 = 5;
+
+#[test]
+fn all_permutations_enumerated_once() {
+    let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(N);
+    let all = factory.construct_all_permutations();
+    let swaps : Vec<Permutation> = factory.permutations(all).collect();
+    let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(N);
+    let all = factory.construct_all_permutations();
+    let rotations : Vec<Permutation> = factory.permutations(all).collect();
+    for permutations in [swaps,rotations] {
+        assert_eq!(factorial::<u64>(N as u32),permutations.len() as u64);
+        assert!(permutations.iter().all(|p|p.n()==N as usize && p.is_permutation()));
+        let distinct : HashSet<Vec<PermutedItem>> = permutations.into_iter().map(|p|p.sequence).collect();
+        assert_eq!(factorial::<u64>(N as u32),distinct.len() as u64);
+    }
+}
+
+/// Decoding a single permutation gives it back.
+#[test]
+fn rot_single_permutation_round_trip() {
+    let mut rng = StdRng::seed_from_u64(21);
+    let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,NoMultiplicity>::new(N);
+    for _ in 0..50 {
+        let mut sequence : Vec<PermutedItem> = (1..=N as PermutedItem).collect();
+        sequence.shuffle(&mut rng);
+        let p = factory.compute_for_single_permutation(&sequence);
+        assert_eq!(vec![Permutation{sequence}],factory.permutations(p).collect::<Vec<_>>());
+    }
+}
+
+/// Composing sets of permutations in the diagram is the same as composing each pair of permutations.
+#[test]
+fn swap_compose_matches_permutations() {
+    let mut rng = StdRng::seed_from_u64(22);
+    let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(N);
+    for _ in 0..30 {
+        let mut sets = [NodeIndex::FALSE;2];
+        for set in &mut sets {
+            for _ in 0..rng.gen_range(1..4) {
+                let mut p = NodeIndex::TRUE;
+                for _ in 0..rng.gen_range(0..5) { p = factory.swap(p,rng.gen_range(1..=N as PermutedItem),rng.gen_range(1..=N as PermutedItem)); }
+                *set = factory.or(*set,p);
+            }
+        }
+        let composed = factory.compose(sets[0],sets[1]);
+        let found : HashSet<Vec<PermutedItem>> = factory.permutations(composed).map(|p|p.sequence).collect();
+        let left : Vec<Permutation> = factory.permutations(sets[0]).collect();
+        let right : Vec<Permutation> = factory.permutations(sets[1]).collect();
+        let expected : HashSet<Vec<PermutedItem>> = left.iter().flat_map(|p|right.iter().map(|q|p.compose(q).sequence)).collect();
+        assert_eq!(expected,found);
+    }
+}
+
+/// Decoding a single permutation gives it back, and composing it with its inverse gives the identity.
+#[test]
+fn swap_single_permutation_round_trip() {
+    let mut rng = StdRng::seed_from_u64(23);
+    let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(N);
+    for _ in 0..50 {
+        let mut sequence : Vec<PermutedItem> = (1..=N as PermutedItem).collect();
+        sequence.shuffle(&mut rng);
+        let p = factory.compute_for_single_permutation(&sequence);
+        let inverse = factory.inverse(p);
+        assert_eq!(NodeIndex::TRUE,factory.compose(p,inverse));
+        assert_eq!(vec![Permutation{sequence}],factory.permutations(p).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,56 @@
+//! Check the sets of permutations fixing or mapping points against filtering all permutations.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use xdd::generating_function::GeneratingFunctionSplitByMultiplicity;
+use xdd::permutation::{Permutation, PermutedItem};
+use xdd::permutation_diagrams::{LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+const N : u16 = 6;
+
+fn random_points(rng:&mut StdRng,k:usize) -> Vec<PermutedItem> {
+    let mut points : Vec<PermutedItem> = (1..=N as PermutedItem).collect();
+    points.shuffle(rng);
+    points.truncate(k);
+    points
+}
+
+macro_rules! check_fixing {
+    ($name:ident,$interpretation:ty) => {
+        #[test]
+        fn $name() {
+            let mut rng = StdRng::seed_from_u64(51);
+            let mut factory = PermutationDecisionDiagramFactory::<$interpretation,u32,u32>::new(N);
+            let s_n = factory.construct_all_permutations();
+            let all : Vec<Permutation> = factory.permutations(s_n).collect();
+            let check = |factory:&PermutationDecisionDiagramFactory<$interpretation,u32,u32>,set,wanted:&dyn Fn(&Permutation)->bool| {
+                let expected : HashSet<Vec<PermutedItem>> = all.iter().filter(|p|wanted(p)).map(|p|p.sequence.clone()).collect();
+                let found : HashSet<Vec<PermutedItem>> = factory.permutations(set).map(|p|p.sequence).collect();
+                assert_eq!(expected,found);
+                assert_eq!(GeneratingFunctionSplitByMultiplicity(vec![expected.len() as u64]),factory.number_solutions(set));
+            };
+            for _ in 0..20 {
+                let k = rng.gen_range(0..=N as usize);
+                let from = random_points(&mut rng,k);
+                let to = random_points(&mut rng,k);
+                let fixing = factory.permutations_fixing(&from);
+                check(&factory,fixing,&|p|from.iter().all(|&i|p[i]==i));
+                let mapping = factory.permutations_mapping(&from,&to);
+                check(&factory,mapping,&|p|from.iter().zip(to.iter()).all(|(&i,&j)|p[i]==j));
+                let onto = factory.permutations_mapping_set(&from,&to);
+                check(&factory,onto,&|p|from.iter().all(|&i|to.contains(&p[i])));
+            }
+        }
+    }
+}
+
+check_fixing!(swap_fixing,Swap);
+check_fixing!(rotation_fixing,LeftRotation);
+
+#[test]
+#[should_panic(expected = "repeated")]
+fn repeated_point() {
+    PermutationDecisionDiagramFactory::<Swap,u32,u32>::new(4).permutations_fixing(&[1,2,1]);
+}
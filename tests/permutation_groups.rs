@@ -0,0 +1,47 @@
+//! Check the standard permutation groups for πDDs and Rot-πDDs against their sizes and the decoded permutations.
+
+use xdd::NodeIndex;
+use xdd::generating_function::GeneratingFunctionSplitByMultiplicity;
+use xdd::permutation::Permutation;
+use xdd::permutation_diagrams::{factorial, LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+fn is_even(p:&Permutation) -> bool {
+    let n = p.n();
+    (0..n).flat_map(|i|(i+1..n).map(move |j|(i,j))).filter(|&(i,j)|p.sequence[i]>p.sequence[j]).count().is_multiple_of(2)
+}
+
+/// The size of D_n, which for n<=2 is all n! permutations.
+fn dihedral_size(n:u64) -> u64 { if n<=2 { factorial(n as u32) } else { 2*n } }
+
+macro_rules! check_groups {
+    ($name:ident,$interpretation:ty) => {
+        #[test]
+        fn $name() {
+            for n in 0..7u16 {
+                let mut factory = PermutationDecisionDiagramFactory::<$interpretation,u32,u32>::new(n);
+                let cyclic = factory.cyclic_group();
+                let dihedral = factory.dihedral_group();
+                let alternating = factory.even_permutations();
+                let all = factory.construct_all_permutations();
+                let sizes = [(cyclic,(n as u64).max(1)),(dihedral,dihedral_size(n as u64)),(alternating,factorial::<u64>(n as u32).div_ceil(2)),(all,factorial(n as u32))];
+                for (group,size) in sizes {
+                    // each permutation once, and closed under composition.
+                    assert_eq!(GeneratingFunctionSplitByMultiplicity(vec![size]),factory.number_solutions(group));
+                    assert_eq!(group,factory.closure_under_composition(group));
+                    let squared = factory.compose(group,group);
+                    assert_eq!(group,factory.support(squared));
+                    assert_eq!(group,factory.inverse(group));
+                }
+                assert!(factory.permutations(alternating).all(|p|is_even(&p)));
+                let odd = factory.permutations(all).filter(|p|!is_even(p)).count() as u64;
+                assert_eq!(factorial::<u64>(n as u32)-odd,factory.number_solutions::<u64>(alternating));
+                let within = factory.and(cyclic,dihedral);
+                assert_eq!(cyclic,within);
+                assert_eq!(NodeIndex::TRUE,factory.closure_under_composition(NodeIndex::FALSE));
+            }
+        }
+    }
+}
+
+check_groups!(swap_groups,Swap);
+check_groups!(rotation_groups,LeftRotation);
@@ -0,0 +1,87 @@
+//! Check the inverse of sets of permutations in πDDs and Rot-πDDs.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use xdd::NodeIndex;
+use xdd::permutation::PermutedItem;
+use xdd::permutation_diagrams::{LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+const N : u16 = 5;
+
+fn random_permutation(rng:&mut StdRng) -> Vec<PermutedItem> {
+    let mut res : Vec<PermutedItem> = (1..=N as PermutedItem).collect();
+    res.shuffle(rng);
+    res
+}
+
+fn invert(permutation:&[PermutedItem]) -> Vec<PermutedItem> {
+    let mut res = vec![0;permutation.len()];
+    for (i,&p) in permutation.iter().enumerate() { res[p as usize-1]=(i+1) as PermutedItem; }
+    res
+}
+
+/// Sets of permutations, each given explicitly, along with the set of their inverses.
+#[test]
+fn rot_inverse_of_explicit_permutations() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(N);
+    for _ in 0..30 {
+        let mut set = NodeIndex::FALSE;
+        let mut inverses = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(0..8) {
+            let permutation = random_permutation(&mut rng);
+            let p = factory.compute_for_single_permutation(&permutation);
+            let p_inverse = factory.compute_for_single_permutation(&invert(&permutation));
+            assert_eq!(NodeIndex::TRUE,factory.compose(p,p_inverse));
+            set = factory.or(set,p);
+            inverses = factory.or(inverses,p_inverse);
+        }
+        assert_eq!(inverses,factory.inverse(set));
+        assert_eq!(set,factory.inverse(inverses));
+    }
+}
+
+/// Products of random transpositions, whose inverse is the product in the other order.
+#[test]
+fn swap_inverse_of_products() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,u32>::new(N);
+    for _ in 0..30 {
+        let mut set = NodeIndex::FALSE;
+        let mut inverses = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(0..8) {
+            let swaps : Vec<(PermutedItem,PermutedItem)> = (0..rng.gen_range(0..6)).map(|_|(rng.gen_range(1..=N as PermutedItem),rng.gen_range(1..=N as PermutedItem))).collect();
+            let mut p = NodeIndex::TRUE;
+            for &(i,j) in &swaps { p = factory.swap(p,i,j); }
+            let mut p_inverse = NodeIndex::TRUE;
+            for &(i,j) in swaps.iter().rev() { p_inverse = factory.swap(p_inverse,i,j); }
+            assert_eq!(NodeIndex::TRUE,factory.compose(p,p_inverse));
+            set = factory.or(set,p);
+            inverses = factory.or(inverses,p_inverse);
+        }
+        assert_eq!(inverses,factory.inverse(set));
+        assert_eq!(factory.number_solutions::<u64>(set),factory.number_solutions::<u64>(inverses));
+    }
+}
+
+/// The inverse of all permutations is all permutations, and of a subgroup is the same subgroup.
+#[test]
+fn inverse_of_groups() {
+    let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(N);
+    let all = factory.construct_all_permutations();
+    assert_eq!(all,factory.inverse(all));
+    let ordered = factory.permutations_with_ordered_k_prefix(N as PermutedItem,2); // not a group.
+    let inverse = factory.inverse(ordered);
+    assert_ne!(ordered,inverse);
+    assert_eq!(ordered,factory.inverse(inverse));
+    let cyclic = factory.compute_for_single_permutation(&[2,3,4,5,1]);
+    let mut group = NodeIndex::TRUE;
+    let mut power = NodeIndex::TRUE;
+    for _ in 1..N {
+        power = factory.compose(power,cyclic);
+        group = factory.or(group,power);
+    }
+    assert_eq!(N as u64,factory.number_solutions::<u64>(group));
+    assert_eq!(group,factory.inverse(group));
+}
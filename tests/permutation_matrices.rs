@@ -0,0 +1,106 @@
+//! Check permutation matrices against the permutations enumerated by a πDD.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, ZDDFactory};
+use xdd::permutation::{Permutation, PermutedItem};
+use xdd::permutation_diagrams::{factorial, PermutationDecisionDiagramFactory, Swap};
+use xdd::permutation_matrices::PermutationMatrices;
+
+fn all_permutations(n:usize) -> Vec<Permutation> {
+    let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,NoMultiplicity>::new(n as u16);
+    let all = factory.construct_all_permutations();
+    factory.permutations(all).collect()
+}
+
+fn check_counts<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    for n in 0..=6 {
+        let problem = PermutationMatrices::new(n);
+        let mut factory = F::new(problem.num_variables());
+        let matrices = problem.matrices(&mut factory);
+        assert_eq!(factorial::<u64>(n as u32),factory.number_solutions::<u64>(matrices));
+    }
+    let derangements : Vec<u64> = (0..=6).map(|n|{
+        let problem = PermutationMatrices::with_allowed((0..n).map(|row|(0..n).map(|column|row!=column).collect()).collect());
+        let mut factory = F::new(problem.num_variables());
+        let matrices = problem.matrices(&mut factory);
+        factory.number_solutions::<u64>(matrices)
+    }).collect();
+    assert_eq!(vec![1,0,1,2,9,44,265],derangements);
+}
+
+#[test]
+fn counts_bdd() { check_counts::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn counts_zdd() { check_counts::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+fn check_random<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut rng = StdRng::seed_from_u64(4603);
+    for n in 1..=5 {
+        let permutations = all_permutations(n);
+        for _ in 0..10 {
+            let problem = PermutationMatrices::with_allowed((0..n).map(|_|(0..n).map(|_|rng.gen_bool(0.7)).collect()).collect());
+            let expected : HashSet<Vec<PermutedItem>> = permutations.iter()
+                .filter(|p|(0..n).all(|row|problem.allowed[row][p.sequence[row] as usize-1]))
+                .map(|p|p.sequence.clone()).collect();
+            let mut factory = F::new(problem.num_variables());
+            let matrices = problem.matrices(&mut factory);
+            assert_eq!(expected.len() as u64,factory.number_solutions::<u64>(matrices));
+            let found : HashSet<Vec<PermutedItem>> = factory.solutions(matrices).map(|solution|{
+                assert_eq!(n,solution.len());
+                problem.permutation(&solution).sequence
+            }).collect();
+            assert_eq!(expected,found);
+        }
+    }
+}
+
+#[test]
+fn random_bdd() { check_random::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn random_zdd() { check_random::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+#[should_panic(expected="square")]
+fn not_square() { PermutationMatrices::with_allowed(vec![vec![true,true],vec![true]]); }
+
+/// The number of pairs i<j with π(i)>π(j).
+fn inversions(p:&Permutation) -> usize {
+    (0..p.n()).map(|i|(i+1..p.n()).filter(|&j|p.sequence[i]>p.sequence[j]).count()).sum()
+}
+
+#[test]
+fn permanent_and_determinant() {
+    let mut rng = StdRng::seed_from_u64(4604);
+    for n in 0..=5 {
+        let permutations = all_permutations(n);
+        for _ in 0..10 {
+            let matrix : Vec<Vec<i64>> = (0..n).map(|_|(0..n).map(|_|if rng.gen_bool(0.3) { 0 } else { rng.gen_range(-5..=5) }).collect()).collect();
+            let term = |p:&Permutation|(0..n).map(|row|matrix[row][p.sequence[row] as usize-1]).product::<i64>();
+            let permanent : i64 = permutations.iter().map(term).sum();
+            let determinant : i64 = permutations.iter().map(|p|if inversions(p).is_multiple_of(2) { term(p) } else { -term(p) }).sum();
+            assert_eq!(permanent,PermutationMatrices::permanent(&matrix));
+            assert_eq!(determinant,PermutationMatrices::determinant(&matrix));
+        }
+    }
+    // the permanent of the all ones matrix is n!, of which half are even for n>1.
+    let ones = vec![vec![1i64;6];6];
+    assert_eq!(720,PermutationMatrices::permanent(&ones));
+    assert_eq!(0,PermutationMatrices::determinant(&ones));
+    assert_eq!(1,PermutationMatrices::determinant(&[vec![0i64,0,1],vec![1,0,0],vec![0,1,0]]));
+    assert_eq!(-1,PermutationMatrices::determinant(&[vec![0i64,1,0],vec![1,0,0],vec![0,0,1]]));
+}
+
+/// The signed matrices of a BDD factory, weighted by a user supplied matrix.
+#[test]
+fn signed_bdd() {
+    let problem = PermutationMatrices::new(4);
+    let mut factory = BDDFactory::<u32,i64>::new(problem.num_variables());
+    let signed = problem.signed_matrices(&mut factory);
+    assert_eq!(0,factory.number_solutions::<i64>(signed));
+    let matrix : Vec<Vec<i64>> = (0..4).map(|row|(0..4).map(|column|if row==column { 2 } else if column==row+1 { 7 } else { 0 }).collect()).collect();
+    assert_eq!(16,factory.weighted_count::<i64>(signed,&problem.cell_weights(&matrix)));
+}
@@ -0,0 +1,80 @@
+//! Check permutation statistic generating functions against the decoded permutations.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use xdd::NodeIndex;
+use xdd::generating_function::SingleVariableGeneratingFunction;
+use xdd::permutation::{Permutation, PermutedItem};
+use xdd::permutation_diagrams::{LeftRotation, PermutationDecisionDiagramFactory, Swap};
+
+const N : u16 = 6;
+
+fn inversions(p:&Permutation) -> usize {
+    let n = p.n();
+    (0..n).flat_map(|i|(i+1..n).map(move |j|(i,j))).filter(|&(i,j)|p.sequence[i]>p.sequence[j]).count()
+}
+
+fn cycles(p:&Permutation) -> usize {
+    let mut seen = vec![false;p.n()];
+    let mut res = 0;
+    for start in 0..p.n() {
+        if !seen[start] {
+            res+=1;
+            let mut i = start;
+            while !seen[i] { seen[i]=true; i = p.sequence[i] as usize-1; }
+        }
+    }
+    res
+}
+
+fn distribution(values:impl Iterator<Item=(usize,u64)>) -> SingleVariableGeneratingFunction<u64> {
+    let mut res = vec![];
+    for (value,count) in values {
+        if res.len()<=value { res.resize(value+1,0); }
+        res[value]+=count;
+    }
+    SingleVariableGeneratingFunction(res)
+}
+
+fn random_permutations(rng:&mut StdRng) -> Vec<(Vec<PermutedItem>,u32)> {
+    (0..rng.gen_range(1..10)).map(|_|{
+        let mut sequence : Vec<PermutedItem> = (1..=N as PermutedItem).collect();
+        sequence.shuffle(rng);
+        (sequence,rng.gen_range(1..4))
+    }).collect()
+}
+
+#[test]
+fn rotation_inversions() {
+    let mut rng = StdRng::seed_from_u64(31);
+    let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(N);
+    for _ in 0..30 {
+        let permutations = random_permutations(&mut rng);
+        let mut set = NodeIndex::FALSE;
+        for (sequence,multiplicity) in &permutations {
+            let p = factory.compute_for_single_permutation(sequence);
+            set = factory.linear_combination(&[(set,1),(p,*multiplicity)]);
+        }
+        let expected = distribution(permutations.iter().map(|(sequence,m)|(inversions(&Permutation{sequence:sequence.clone()}),*m as u64)));
+        assert_eq!(expected,factory.inversions_generating_function(set));
+    }
+}
+
+#[test]
+fn swap_transpositions() {
+    let mut rng = StdRng::seed_from_u64(32);
+    let mut factory = PermutationDecisionDiagramFactory::<Swap,u32,u32>::new(N);
+    for _ in 0..30 {
+        let permutations = random_permutations(&mut rng);
+        let mut set = NodeIndex::FALSE;
+        for (sequence,multiplicity) in &permutations {
+            let p = factory.compute_for_single_permutation(sequence);
+            set = factory.linear_combination(&[(set,1),(p,*multiplicity)]);
+        }
+        let expected = distribution(permutations.iter().map(|(sequence,m)|(N as usize-cycles(&Permutation{sequence:sequence.clone()}),*m as u64)));
+        let found : SingleVariableGeneratingFunction<u64> = factory.statistic_generating_function(set,|_|1);
+        assert_eq!(expected,found);
+        assert_eq!(found,factory.number_solutions(set));
+    }
+}
@@ -0,0 +1,43 @@
+//! Check that poly_and, which combines the smallest diagrams first, agrees with combining in the order given.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::GeneratingFunctionWithMultiplicity;
+
+fn fold_and<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,indices:&[NodeIndex<u32,M>]) -> NodeIndex<u32,M> {
+    indices[1..].iter().fold(indices[0],|acc,&i|factory.and(acc,i)) // not TRUE, which in a ZDD means all variables false.
+}
+
+/// Constraints for no two adjacent cells on a 4x4 grid being true, given in an awkward order.
+fn check_grid<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>() where u64:GeneratingFunctionWithMultiplicity<M> {
+    let mut factory = F::new(16);
+    let mut constraints = vec![];
+    for row in 0..4 {
+        for col in 0..4 {
+            let cell = row*4+col;
+            if col<3 { constraints.push(factory.at_most_n_of(&[VariableIndex(cell),VariableIndex(cell+1)],1)); }
+            if row<3 { constraints.push(factory.at_most_n_of(&[VariableIndex(cell),VariableIndex(cell+4)],1)); }
+        }
+    }
+    constraints.reverse();
+    let mut calls = vec![];
+    let res = factory.poly_and_with_progress(&constraints,|size,remaining|calls.push((size,remaining))).unwrap();
+    assert_eq!(fold_and(&mut factory,&constraints),res);
+    assert_eq!(1234,factory.number_solutions::<u64>(res));
+    assert_eq!((1..constraints.len()).rev().collect::<Vec<_>>(),calls.iter().map(|(_,remaining)|*remaining).collect::<Vec<_>>());
+    assert_eq!(factory.post_order(res).count(),calls.last().unwrap().0);
+    assert_eq!(Some(res),factory.poly_and(&constraints));
+    assert_eq!(Some(constraints[3]),factory.poly_and(&constraints[3..4]));
+    // a false operand gives false straight away.
+    let mut with_false = constraints.clone();
+    with_false.insert(5,NodeIndex::FALSE);
+    let mut calls = 0;
+    assert_eq!(Some(NodeIndex::FALSE),factory.poly_and_with_progress(&with_false,|_,_|calls+=1));
+    assert_eq!(0,calls);
+}
+
+#[test]
+fn grid_bdd() { check_grid::<NoMultiplicity,BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn grid_zdd() { check_grid::<NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn grid_mzdd() { check_grid::<u32,ZDDFactory<u32,u32>>() }
@@ -0,0 +1,50 @@
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, NodeIndex, VariableIndex, ZDDFactory};
+
+#[test]
+fn profile_of_sinks_is_empty() {
+    let factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    for sink in [NodeIndex::FALSE,NodeIndex::TRUE] {
+        let profile = factory.profile(sink);
+        assert_eq!(vec![0,0,0],profile.nodes_per_level);
+        assert_eq!(0,profile.edges);
+        assert_eq!(0,profile.width);
+        assert_eq!(0,profile.longest_path);
+    }
+}
+
+#[test]
+fn profile_zdd() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(5);
+    let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    let two = factory.exactly_n_of(&variables,2);
+    let profile = factory.profile(two);
+    assert_eq!(vec![1,2,2,2,1],profile.nodes_per_level); // having both is the TRUE sink, as the rest must be false.
+    assert_eq!(profile.nodes(),factory.post_order(two).count());
+    // the two nodes with no way to get enough variables left have an edge to FALSE.
+    assert_eq!(2*profile.nodes()-2,profile.edges);
+    assert_eq!(2,profile.width);
+    assert_eq!(5,profile.longest_path);
+    assert!(profile.to_string().starts_with("8 nodes, 14 edges, width 2, longest path 5\n"));
+}
+
+#[test]
+fn profile_shows_bad_order() {
+    // x0=x3 & x1=x4 & x2=x5 is much wider than x0=x1 & x2=x3 & x4=x5.
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(6);
+    let mut equal_pairs = |pairs:[(u16,u16);3]| {
+        let mut res = factory.not(NodeIndex::FALSE);
+        for (a,b) in pairs {
+            let a = factory.single_variable(VariableIndex(a));
+            let b = factory.single_variable(VariableIndex(b));
+            let differ = factory.xor(a,b);
+            let same = factory.not(differ);
+            res = factory.and(res,same);
+        }
+        res
+    };
+    let good = equal_pairs([(0,1),(2,3),(4,5)]);
+    let bad = equal_pairs([(0,3),(1,4),(2,5)]);
+    assert_eq!(2,factory.profile(good).width);
+    assert_eq!(8,factory.profile(bad).width);
+    assert_eq!(6,factory.profile(good).longest_path);
+}
@@ -0,0 +1,133 @@
+//! Check progress callbacks and cancellation of long operations.
+
+use std::sync::{Arc, Mutex};
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, NodeIndex, VariableIndex, XDDError, ZDDFactory};
+use xdd::statistics::{CancellationToken, Progress};
+
+const N : u16 = 60;
+
+fn variables() -> Vec<VariableIndex> { (0..N).map(VariableIndex).collect() }
+
+/// Build something of a few thousand nodes, using the caches.
+fn build<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F) -> NodeIndex<u32,NoMultiplicity> {
+    let variables = variables();
+    let f = factory.exactly_n_of(&variables,30);
+    let g = factory.at_most_n_of(&variables[10..],20);
+    factory.xor(f,g)
+}
+
+fn check_progress<F:DecisionDiagramFactory<u32,NoMultiplicity>>(new:impl Fn(u16)->F) {
+    let mut factory = new(N);
+    let reports : Arc<Mutex<Vec<Progress>>> = Arc::new(Mutex::new(vec![]));
+    let in_callback = reports.clone();
+    factory.set_progress_callback(250,move |progress|in_callback.lock().unwrap().push(*progress));
+    let h = build(&mut factory);
+    {
+        let reports = reports.lock().unwrap();
+        assert_eq!(factory.len()/250,reports.len());
+        for (i,progress) in reports.iter().enumerate() {
+            assert_eq!(250*(i as u64+1),progress.nodes_created);
+            assert_eq!(250*(i+1),progress.nodes);
+            assert!(progress.depth<N);
+        }
+        let last = reports.last().unwrap();
+        let hits : u64 = factory.cache_stats().iter().map(|(_,cache)|cache.hits).sum();
+        let misses : u64 = factory.cache_stats().iter().map(|(_,cache)|cache.misses).sum();
+        assert!(last.cache_hits>0 && last.cache_hits<=hits);
+        assert!(last.cache_misses>0 && last.cache_misses<=misses);
+    }
+    // counts start again with a new callback, and stop when cleared.
+    let reports2 : Arc<Mutex<Vec<Progress>>> = Arc::new(Mutex::new(vec![]));
+    let in_callback = reports2.clone();
+    factory.set_progress_callback(10,move |progress|in_callback.lock().unwrap().push(*progress));
+    let len = factory.len();
+    let not_h = factory.not(h);
+    assert_eq!(Some(10),reports2.lock().unwrap().first().map(|p|p.nodes_created));
+    assert_eq!(Some(len+10),reports2.lock().unwrap().first().map(|p|p.nodes));
+    factory.clear_progress_callback();
+    let before = reports2.lock().unwrap().len();
+    let everything = factory.or(h,not_h);
+    assert_eq!(NodeIndex::TRUE,factory.and(everything,NodeIndex::TRUE));
+    factory.gc([]);
+    build(&mut factory);
+    assert_eq!(before,reports2.lock().unwrap().len());
+}
+
+#[test]
+fn bdd_progress() { check_progress(BDDFactory::<u32,NoMultiplicity>::new); }
+
+#[test]
+fn zdd_progress() { check_progress(ZDDFactory::<u32,NoMultiplicity>::new); }
+
+fn check_cancellation<F:DecisionDiagramFactory<u32,NoMultiplicity>>(new:impl Fn(u16)->F) {
+    let mut reference = new(N);
+    let expected = build(&mut reference);
+    let mut factory = new(N);
+    let token = CancellationToken::new();
+    factory.set_cancellation_token(Some(token.clone()));
+    let stop = token.clone();
+    factory.set_progress_callback(100,move |progress|if progress.nodes_created==500 { stop.cancel() });
+    assert_eq!(Err(XDDError::Cancelled),factory.try_operation(build));
+    assert_eq!(500,factory.len());
+    assert!(token.is_cancelled());
+    // nested operations are all cancelled.
+    assert_eq!(Err(XDDError::Cancelled),factory.try_operation(|f|{
+        assert_eq!(Err(XDDError::Cancelled),f.try_operation(build));
+        build(f)
+    }));
+    // only operations run with try_operation are cancelled.
+    let h = build(&mut factory);
+    assert_eq!(reference.number_solutions::<u128>(expected),factory.number_solutions::<u128>(h));
+    // the factory is fine afterwards, with a new token.
+    factory.set_cancellation_token(Some(CancellationToken::new()));
+    factory.gc([]);
+    let h = factory.try_operation(build).unwrap();
+    assert_eq!(reference.number_solutions::<u128>(expected),factory.number_solutions::<u128>(h));
+    assert_eq!(reference.len(),factory.len());
+    factory.set_cancellation_token(None);
+    assert_eq!(Ok(h),factory.try_operation(build));
+}
+
+#[test]
+fn bdd_cancellation() { check_cancellation(BDDFactory::<u32,NoMultiplicity>::new); }
+
+#[test]
+fn zdd_cancellation() { check_cancellation(ZDDFactory::<u32,NoMultiplicity>::new); }
+
+#[test]
+fn cancel_from_another_thread() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(400);
+    let token = CancellationToken::new();
+    factory.set_cancellation_token(Some(token.clone()));
+    let started = Arc::new(Mutex::new(false));
+    let started_in_callback = started.clone();
+    let cancelled = token.clone();
+    // wait in the callback for the other thread, so the operation can not finish first.
+    factory.set_progress_callback(1000,move |_|{
+        *started_in_callback.lock().unwrap()=true;
+        while !cancelled.is_cancelled() { std::thread::yield_now(); }
+    });
+    let canceller = std::thread::spawn(move ||{
+        while !*started.lock().unwrap() { std::thread::yield_now(); }
+        token.cancel();
+    });
+    let variables : Vec<VariableIndex> = (0..400).map(VariableIndex).collect();
+    assert_eq!(Err(XDDError::Cancelled),factory.try_operation(|f|f.exactly_n_of(&variables,200))); // would need 40401 nodes.
+    canceller.join().unwrap();
+    assert_eq!(1000,factory.len());
+}
+
+#[test]
+#[should_panic(expected = "not a cancellation")]
+fn other_panics_propagate() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(N);
+    factory.set_cancellation_token(Some(CancellationToken::new()));
+    let _ = factory.try_operation(|_|panic!("not a cancellation"));
+}
+
+#[test]
+fn error_message() {
+    assert_eq!("The operation was cancelled",XDDError::Cancelled.to_string());
+    assert_eq!(0.0,Progress::default().cache_hit_rate());
+    assert_eq!(0.25,Progress{cache_hits:1,cache_misses:3,..Default::default()}.cache_hit_rate());
+}
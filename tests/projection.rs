@@ -0,0 +1,52 @@
+//! Check projection onto a subset of the variables against projecting each solution.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+
+/// The solutions of f as bit masks.
+fn solutions<F:DecisionDiagramFactory<u32,u32>>(factory:&F,f:NodeIndex<u32,u32>) -> HashSet<u32> {
+    factory.solutions(f).map(|s|s.iter().map(|v|1<<v.0).sum()).collect()
+}
+
+fn check_projection<F:DecisionDiagramFactory<u32,u32>>(is_bdd:bool) {
+    let mut rng = StdRng::seed_from_u64(23);
+    let mut factory = F::new(N);
+    for _ in 0..40 {
+        // a random function with multiplicities, as a sum of some minterms.
+        let mut f = NodeIndex::FALSE;
+        let everything = factory.not(NodeIndex::FALSE);
+        for minterm in 0..1u32<<N {
+            if rng.gen_bool(0.2) {
+                let mut term = everything;
+                for v in 0..N {
+                    let literal = factory.exactly_n_of(&[VariableIndex(v)],((minterm>>v)&1) as usize);
+                    term = factory.and(term,literal);
+                }
+                let term = factory.scale(term,rng.gen_range(1..4));
+                f = factory.or(f,term);
+            }
+        }
+        let variables : Vec<VariableIndex> = (0..N).filter(|_|rng.gen_bool(0.5)).map(VariableIndex).collect();
+        let mask : u32 = variables.iter().map(|v|1<<v.0).sum();
+        let projected : HashSet<u32> = solutions(&factory,f).into_iter().map(|x|x&mask).collect();
+        let res = factory.project(f,&variables);
+        let expected : HashSet<u32> = if is_bdd { (0..1<<N).filter(|x|projected.contains(&(x&mask))).collect() } else { projected.clone() };
+        assert_eq!(expected,solutions(&factory,res));
+        assert_eq!(expected.len() as u64,factory.number_solutions::<u64>(res)); // multiplicities are dropped.
+        assert_eq!(projected.len() as u64,factory.number_projected_solutions::<u64>(f,&variables));
+        for address in factory.post_order(res) { assert!(variables.contains(&factory.node(address).variable)); }
+    }
+    let all : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    assert_eq!(0,factory.number_projected_solutions::<u64>(NodeIndex::FALSE,&all));
+    assert_eq!(1,factory.number_projected_solutions::<u64>(NodeIndex::TRUE,&[]));
+}
+
+#[test]
+fn projection_bdd() { check_projection::<BDDFactory<u32,u32>>(true); }
+
+#[test]
+fn projection_zdd() { check_projection::<ZDDFactory<u32,u32>>(false); }
@@ -0,0 +1,65 @@
+//! Check rational multiplicities, used as probabilities.
+
+use num::rational::{Ratio, Rational64};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+
+const NUM_VARIABLES : u16 = 6;
+
+/// The distribution where each variable is independently true with probability p.
+fn independent<F:DecisionDiagramFactory<u32,Rational64>>(factory:&mut F,p:Rational64) -> NodeIndex<u32,Rational64> {
+    let mut res = factory.not(NodeIndex::FALSE);
+    for v in 0..NUM_VARIABLES {
+        let x = factory.single_variable(VariableIndex(v));
+        let not_x = factory.not(x);
+        let weighted = factory.or(x.multiply(p),not_x.multiply(Ratio::from_integer(1)-p));
+        res = factory.and(res,weighted);
+    }
+    res
+}
+
+fn binomial(n:i64,k:i64) -> i64 { (0..k).fold(1,|acc,i|acc*(n-i)/(i+1)) }
+
+fn check_binomial_distribution<F:DecisionDiagramFactory<u32,Rational64>>(mut factory:F) {
+    let p = Ratio::new(1,3);
+    let distribution = independent(&mut factory,p);
+    assert_eq!(Ratio::from_integer(1),factory.number_solutions::<Rational64>(distribution));
+    let variables : Vec<VariableIndex> = (0..NUM_VARIABLES).map(VariableIndex).collect();
+    for k in 0..=NUM_VARIABLES as i64 {
+        let exactly_k = factory.exactly_n_of(&variables,k as usize);
+        let probability = factory.and(distribution,exactly_k);
+        let expected = Ratio::from_integer(binomial(NUM_VARIABLES as i64,k))*p.pow(k as i32)*(Ratio::from_integer(1)-p).pow(NUM_VARIABLES as i32-k as i32);
+        assert_eq!(expected,factory.number_solutions::<Rational64>(probability));
+    }
+}
+
+#[test]
+fn binomial_distribution_bdd() { check_binomial_distribution(BDDFactory::<u32,Rational64>::new(NUM_VARIABLES)); }
+
+#[test]
+fn binomial_distribution_zdd() { check_binomial_distribution(ZDDFactory::<u32,Rational64>::new(NUM_VARIABLES)); }
+
+#[test]
+fn canonical() {
+    let mut factory = BDDFactory::<u32,Rational64>::new(NUM_VARIABLES);
+    let third = independent(&mut factory,Ratio::new(1,3));
+    let half = independent(&mut factory,Ratio::new(1,2));
+    assert_eq!(Ratio::new(1,64),half.multiplicity()); // all solutions equally likely, so the root just carries the weight.
+    assert_eq!(factory.not(NodeIndex::FALSE).address(),half.address());
+    let quarter = factory.and(half,half);
+    let sum = factory.or(quarter,third.multiply(Ratio::new(-1,1)));
+    let back = factory.or(sum,third);
+    assert_eq!(quarter,back);
+    let as_set = BDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES).import_from(&factory,third);
+    assert_eq!(NodeIndex::TRUE,as_set);
+}
+
+#[test]
+fn save_and_load() {
+    let mut factory = ZDDFactory::<u32,Rational64>::new(NUM_VARIABLES);
+    let f = independent(&mut factory,Ratio::new(2,7));
+    let mut buffer = vec![];
+    factory.write_to(&mut buffer,&[f]).unwrap();
+    let (loaded,roots) = ZDDFactory::<u32,Rational64>::read_from(&mut buffer.as_slice()).unwrap();
+    assert_eq!(vec![f],roots);
+    assert_eq!(Ratio::from_integer(1),loaded.number_solutions::<Rational64>(roots[0]));
+}
@@ -0,0 +1,98 @@
+//! Check that number_solutions only works on the nodes reachable from the root, not dead ones with smaller addresses,
+//! and that number_solutions_many works on each node shared by several roots once.
+
+use std::cell::Cell;
+use std::collections::HashSet;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::GeneratingFunction;
+
+thread_local! {
+    static ADDITIONS : Cell<usize> = const { Cell::new(0) };
+}
+
+/// A count that records how many times it is added, once per node worked on.
+#[derive(Clone,Debug)]
+struct CountingAdditions(u64);
+
+impl GeneratingFunction for CountingAdditions {
+    fn zero() -> Self { CountingAdditions(0) }
+    fn one() -> Self { CountingAdditions(1) }
+    fn add(self, other: Self) -> Self {
+        ADDITIONS.with(|a|a.set(a.get()+1));
+        CountingAdditions(self.0+other.0)
+    }
+    fn variable_set(self, _variable: VariableIndex) -> Self { self }
+    fn deal_with_variable_being_indeterminate(self, _variable: VariableIndex) -> Self { CountingAdditions(2*self.0) }
+}
+
+fn check_reachable<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(30);
+    let variables : Vec<VariableIndex> = (0..30).map(VariableIndex).collect();
+    for k in 0..15 { factory.exactly_n_of(&variables,k); } // lots of dead nodes.
+    let f = factory.at_most_n_of(&variables[20..],1);
+    let reachable = factory.post_order(f).count();
+    assert!(reachable*10<factory.len());
+    ADDITIONS.with(|a|a.set(0));
+    let count = factory.number_solutions::<CountingAdditions>(f);
+    assert_eq!(reachable,ADDITIONS.with(|a|a.get()));
+    assert_eq!(11<<20,count.0);
+}
+
+#[test]
+fn reachable_bdd() { check_reachable::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn reachable_zdd() { check_reachable::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+fn check_many<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(12);
+    let variables : Vec<VariableIndex> = (0..12).map(VariableIndex).collect();
+    let mut roots = vec![NodeIndex::FALSE];
+    for k in 0..6 {
+        let a = factory.exactly_n_of(&variables[k..],3);
+        let b = factory.at_most_n_of(&variables,k);
+        let c = factory.or(a,b);
+        roots.extend([a,c,factory.not(c)]);
+    }
+    let expected : Vec<u64> = roots.iter().map(|&f|factory.number_solutions(f)).collect();
+    assert_eq!(expected,factory.number_solutions_many::<u64>(&roots));
+    assert!(factory.number_solutions_many::<u64>(&[]).is_empty());
+    let reachable : HashSet<u32> = roots.iter().flat_map(|&f|factory.post_order(f)).collect();
+    ADDITIONS.with(|a|a.set(0));
+    factory.number_solutions_many::<CountingAdditions>(&roots);
+    assert_eq!(reachable.len(),ADDITIONS.with(|a|a.get()));
+}
+
+#[test]
+fn many_bdd() { check_many::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn many_zdd() { check_many::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+/// The nodes of a diagram made early in a large factory, found by recursion.
+fn check_post_order<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(30);
+    let variables : Vec<VariableIndex> = (0..30).map(VariableIndex).collect();
+    let f = factory.exactly_n_of(&variables[..5],2);
+    for k in 0..15 { factory.exactly_n_of(&variables,k); } // lots of nodes with higher addresses.
+    fn add(factory:&impl DecisionDiagramFactory<u32,NoMultiplicity>,index:NodeIndex<u32,NoMultiplicity>,found:&mut HashSet<u32>) {
+        if !index.is_sink() && found.insert(index.address()) {
+            let node = factory.node(index.address());
+            add(factory,node.lo,found);
+            add(factory,node.hi,found);
+        }
+    }
+    let mut expected = HashSet::new();
+    add(&factory,f,&mut expected);
+    let post_order = factory.post_order(f);
+    assert_eq!(expected.len(),post_order.len());
+    let found : Vec<u32> = post_order.collect();
+    assert!(found.windows(2).all(|w|w[0]<w[1]));
+    assert_eq!(expected,found.into_iter().collect::<HashSet<u32>>());
+}
+
+#[test]
+fn post_order_bdd() { check_post_order::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn post_order_zdd() { check_post_order::<ZDDFactory<u32,NoMultiplicity>>(); }
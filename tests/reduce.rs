@@ -0,0 +1,90 @@
+//! Check that reduce turns hand built diagrams with redundant and duplicate nodes into canonical ones for the same function.
+
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, Node, NodeIndex, NoMultiplicity, VariableIndex};
+use xdd::xdd_with_multiplicity::{NodeList, XDDBase};
+
+const N : u16 = 6;
+
+fn random_child(rng:&mut StdRng,available:&[NodeIndex<u32,u32>]) -> NodeIndex<u32,u32> {
+    available[rng.gen_range(0..available.len())].multiply(rng.gen_range(1..4)*if rng.gen_bool(0.3) { 2 } else { 1 })
+}
+
+/// A random diagram built with add_node, so nodes may be duplicated, redundant for a BDD or ZDD, or have multiplicities with a common factor.
+fn random_unreduced(rng:&mut StdRng,nodes:&mut NodeList<u32,u32>) -> NodeIndex<u32,u32> {
+    let mut available = vec![NodeIndex::FALSE,NodeIndex::TRUE];
+    let mut below = 2; // the nodes in available with a later variable than the one being built.
+    for v in (0..N).rev() {
+        for _ in 0..rng.gen_range(1..4) {
+            let lo = random_child(rng,&available[..below]);
+            let hi = if rng.gen_bool(0.2) { lo } else { random_child(rng,&available[..below]) };
+            let node = Node{variable:VariableIndex(v),lo,hi};
+            let copies = if rng.gen_bool(0.3) { 2 } else { 1 };
+            for _ in 0..copies { available.push(NodeIndex::new(nodes.add_node(node),1)); }
+        }
+        below = available.len();
+    }
+    available.last().unwrap().multiply(rng.gen_range(1..3))
+}
+
+/// The multiplicity of the given assignment.
+fn evaluate(nodes:&NodeList<u32,u32>,mut index:NodeIndex<u32,u32>,assignment:u32,is_bdd:bool) -> u32 {
+    let mut multiplicity = 1;
+    let mut next_variable = 0;
+    loop {
+        if index.is_false() { return 0; }
+        let variable = if index.is_true() { N } else { nodes.node(index.address()).variable.0 };
+        if !is_bdd && (next_variable..variable).any(|v|assignment&(1<<v)!=0) { return 0; } // skipped variables must be false in a ZDD.
+        multiplicity*=index.multiplicity();
+        if index.is_true() { return multiplicity; }
+        let node = nodes.node(index.address());
+        index = if assignment&(1<<variable)!=0 { node.hi } else { node.lo };
+        next_variable = variable+1;
+    }
+}
+
+fn check_canonical(nodes:&NodeList<u32,u32>,root:NodeIndex<u32,u32>,is_bdd:bool) {
+    let mut seen = HashSet::new();
+    for address in nodes.post_order(root) {
+        let node = nodes.node(address);
+        assert!(seen.insert(node),"duplicate node");
+        if is_bdd { assert_ne!(node.lo,node.hi); } else { assert!(!node.hi.is_false()); }
+        if !node.lo.is_false() && !node.hi.is_false() { assert_eq!(1,num::integer::gcd(node.lo.multiplicity(),node.hi.multiplicity())); }
+        for child in [node.lo,node.hi] { if child.is_false() { assert_eq!(NodeIndex::FALSE,child); } }
+    }
+}
+
+fn check_reduce(is_bdd:bool) {
+    let mut rng = StdRng::seed_from_u64(71);
+    for _ in 0..100 {
+        let mut nodes = NodeList::<u32,u32>::default();
+        let root = random_unreduced(&mut rng,&mut nodes);
+        let reduced = if is_bdd { nodes.reduce_bdd(root) } else { nodes.reduce_zdd(root) };
+        for assignment in 0..1<<N { assert_eq!(evaluate(&nodes,root,assignment,is_bdd),evaluate(&nodes,reduced,assignment,is_bdd)); }
+        check_canonical(&nodes,reduced,is_bdd);
+        assert!(if is_bdd { nodes.validate::<true>(reduced,N) } else { nodes.validate::<false>(reduced,N) }.is_ok());
+        assert_eq!(reduced,if is_bdd { nodes.reduce_bdd(reduced) } else { nodes.reduce_zdd(reduced) });
+    }
+}
+
+#[test]
+fn reduce_bdd() { check_reduce(true); }
+
+#[test]
+fn reduce_zdd() { check_reduce(false); }
+
+/// A diagram read from a file, with a redundant node and a duplicate, reduces to what the factory builds.
+#[test]
+fn reduce_in_factory() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    // x0 and x2, testing x1 needlessly, with node 3 a duplicate of node 2.
+    let text = r#"{"num_variables":3,"roots":[{"id":5}],"nodes":[{"id":2,"variable":2,"lo":0,"hi":1},{"id":3,"variable":2,"lo":0,"hi":1},
+        {"id":4,"variable":1,"lo":2,"hi":3},{"id":5,"variable":0,"lo":0,"hi":4}]}"#;
+    let f = factory.read_json_file(&mut text.as_bytes()).unwrap()[0].0;
+    let variables = [VariableIndex(0),VariableIndex(2)];
+    let expected = factory.between_n_and_m_of(&variables,2,2);
+    assert_eq!(expected,factory.reduce(f));
+    assert_eq!(expected,factory.reduce(expected));
+}
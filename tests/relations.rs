@@ -0,0 +1,88 @@
+//! Check the relational product and existential quantification against brute force, and reachability on a counter.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::GeneratingFunctionWithMultiplicity;
+use xdd::relations::StatePairing;
+use common::random_function;
+
+const NUM_VARIABLES : u16 = 6;
+
+fn assignments<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&F,index:NodeIndex<u32,M>) -> Vec<u32> {
+    let mut res : Vec<u32> = factory.solutions(index).map(|s|s.iter().map(|v|1<<v.0).sum()).collect();
+    res.sort();
+    res
+}
+
+fn check<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(multiplicity:M) where u64:GeneratingFunctionWithMultiplicity<M> {
+    let mut rng = StdRng::seed_from_u64(17);
+    for _ in 0..200 {
+        let mut factory = F::new(NUM_VARIABLES);
+        let f = random_function(&mut factory,&mut rng,NUM_VARIABLES,3);
+        let g = random_function(&mut factory,&mut rng,NUM_VARIABLES,3);
+        let all = factory.not(NodeIndex::FALSE).multiply(multiplicity);
+        let g = factory.and(g,all);
+        let quantified : Vec<VariableIndex> = (0..NUM_VARIABLES).filter(|_|rng.gen_bool(0.4)).map(VariableIndex).collect();
+        let mask : u32 = quantified.iter().map(|v|1<<v.0).sum();
+        let res = factory.relational_product(f,g,&quantified);
+        let and = factory.and(f,g);
+        let true_somewhere = assignments(&factory,and);
+        let expected : Vec<u32> = (0..1u32<<NUM_VARIABLES).filter(|a|true_somewhere.iter().any(|t|t&!mask==a&!mask)).collect();
+        assert_eq!(expected,assignments(&factory,res));
+        if !M::MULTIPLICITIES_IRRELEVANT {
+            assert_eq!(factory.number_solutions::<u64>(and)<<quantified.len(),factory.number_solutions::<u64>(res),"multiplicities should be summed");
+        }
+        let exists = factory.exists(and,&quantified);
+        assert_eq!(res,exists);
+    }
+}
+
+#[test]
+fn bdd() { check::<NoMultiplicity,BDDFactory<u32,NoMultiplicity>>(NoMultiplicity{}) }
+#[test]
+fn zdd() { check::<NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>(NoMultiplicity{}) }
+#[test]
+fn mbdd() { check::<u32,BDDFactory<u32,u32>>(3) }
+#[test]
+fn mzdd() { check::<u32,ZDDFactory<u32,u32>>(3) }
+
+/// A counter of n bits going up by step each transition (modulo 2^n), starting at 0.
+fn counter<F:DecisionDiagramFactory<u32,NoMultiplicity>>(n:u16,step:u32) -> (u128,u128) {
+    let pairing = StatePairing::interleaved(n);
+    let mut factory = F::new(pairing.num_variables());
+    let mut transition = NodeIndex::FALSE;
+    for value in 0..1u32<<n {
+        let next = (value+step)%(1<<n);
+        let mut t = factory.not(NodeIndex::FALSE);
+        for bit in 0..n as usize {
+            for (variable,set) in [(pairing.current[bit],value&(1<<bit)!=0),(pairing.next[bit],next&(1<<bit)!=0)] {
+                let x = factory.single_variable(variable);
+                let x = if set { x } else { factory.not(x) };
+                t = factory.and(t,x);
+            }
+        }
+        transition = factory.or(transition,t);
+    }
+    let mut initial = factory.not(NodeIndex::FALSE);
+    for &v in &pairing.current {
+        let x = factory.single_variable(v);
+        let x = factory.not(x);
+        initial = factory.and(initial,x);
+    }
+    let reachable = pairing.reachable(&mut factory,initial,transition);
+    let image = pairing.image(&mut factory,initial,transition);
+    let back = pairing.preimage(&mut factory,image,transition);
+    assert_eq!(initial,back,"a counter is a bijection");
+    (pairing.number_states(&factory,reachable),pairing.number_states(&factory,image))
+}
+
+#[test]
+fn reachability() {
+    assert_eq!((16,1),counter::<BDDFactory<u32,NoMultiplicity>>(4,1));
+    assert_eq!((8,1),counter::<BDDFactory<u32,NoMultiplicity>>(4,2));
+    assert_eq!((16,1),counter::<ZDDFactory<u32,NoMultiplicity>>(4,3));
+    assert_eq!((4,1),counter::<ZDDFactory<u32,NoMultiplicity>>(4,4));
+}
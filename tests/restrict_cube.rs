@@ -0,0 +1,44 @@
+//! Check restricting by many literals at once against restricting by them one at a time.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 8;
+
+fn check_restrict_cube<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    for _ in 0..100 {
+        // a random function with multiplicities, from a few cardinality constraints on random subsets.
+        let mut f = NodeIndex::FALSE;
+        for _ in 0..3 {
+            let subset : Vec<VariableIndex> = variables.iter().cloned().filter(|_|rng.gen_bool(0.6)).collect();
+            let term = factory.exactly_n_of(&subset,rng.gen_range(0..4));
+            let v = factory.single_variable(variables[rng.gen_range(0..N as usize)]);
+            let term = if rng.gen_bool(0.5) { factory.xor(term,v) } else { term };
+            let term = factory.scale(term,rng.gen_range(1..4));
+            f = factory.or(f,term);
+        }
+        let mut literals : Vec<(VariableIndex,bool)> = variables.iter().filter_map(|&v|if rng.gen_bool(0.4) { Some((v,rng.gen_bool(0.5))) } else { None }).collect();
+        let mut expected = f;
+        for &(v,value) in &literals { expected = factory.restrict(expected,v,value); }
+        literals.reverse(); // order does not matter.
+        assert_eq!(expected,factory.restrict_cube(f,&literals));
+    }
+    assert_eq!(NodeIndex::TRUE,factory.restrict_cube(NodeIndex::TRUE,&[]));
+}
+
+#[test]
+fn restrict_cube_bdd() { check_restrict_cube::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn restrict_cube_zdd() { check_restrict_cube::<ZDDFactory<u32,u32>>(); }
+
+#[test]
+#[should_panic(expected = "sorted and distinct")]
+fn repeated_variable() {
+    let mut factory = BDDFactory::<u32,u32>::new(3);
+    factory.restrict_cube(NodeIndex::TRUE,&[(VariableIndex(1),true),(VariableIndex(1),false)]);
+}
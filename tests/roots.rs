@@ -0,0 +1,71 @@
+//! Check that diagrams with handles survive gc and swapping levels, and stop being kept once the handles are dropped.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::permutation_diagrams::{LeftRotation, PermutationDecisionDiagramFactory};
+
+fn check_roots<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let variables : Vec<VariableIndex> = (0..10).map(VariableIndex).collect();
+    let mut factory = F::new(10);
+    let f = factory.exactly_n_of(&variables,4);
+    let f_count = factory.number_solutions::<u64>(f);
+    let f = factory.root(f);
+    let g = factory.at_most_n_of(&variables[2..],3);
+    let g_count = factory.number_solutions::<u64>(g);
+    let g = factory.root(g);
+    let g2 = g.clone();
+    let h = factory.xor(f.index(),g.index());
+    let h_count = factory.number_solutions::<u64>(h);
+    // h is kept because it is asked for, f and g because they have handles.
+    let renaming = factory.gc([h]);
+    let h = renaming.rename(h).unwrap();
+    assert_eq!(h,factory.xor(f.index(),g.index()));
+    assert_eq!([f_count,g_count,h_count],[f.index(),g.index(),h].map(|x|factory.number_solutions::<u64>(x)));
+    // g is still kept by its clone.
+    drop(g);
+    factory.gc_roots();
+    assert_eq!(g_count,factory.number_solutions::<u64>(g2.index()));
+    assert_eq!(2,factory.roots().len());
+    drop(g2);
+    factory.gc_roots();
+    assert_eq!(vec![f.index()],factory.roots());
+    let mut fresh = F::new(10);
+    let expected = fresh.exactly_n_of(&variables,4);
+    assert_eq!(fresh.len(),factory.len());
+    assert_eq!(expected,f.index());
+    // swapping levels changes the diagram but not the function.
+    let v1 = factory.single_variable(VariableIndex(1));
+    let f_and_v1 = factory.and(f.index(),v1);
+    let and_count = factory.number_solutions::<u64>(f_and_v1);
+    let and_root = factory.root(f_and_v1);
+    factory.swap_adjacent_levels(VariableIndex(1),[]);
+    let v2 = factory.single_variable(VariableIndex(2)); // what was variable 1.
+    assert_eq!(and_root.index(),factory.and(f.index(),v2));
+    assert_eq!(and_count,factory.number_solutions::<u64>(and_root.index()));
+    drop(and_root);
+    drop(f);
+    factory.gc_roots();
+    assert_eq!(0,factory.len());
+    assert!(factory.roots().is_empty());
+}
+
+#[test]
+fn roots_bdd() { check_roots::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn roots_zdd() { check_roots::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn permutation_roots() {
+    let mut factory = PermutationDecisionDiagramFactory::<LeftRotation,u32,u32>::new(5);
+    let all = factory.construct_all_permutations();
+    let all = factory.root(all);
+    let cyclic = factory.cyclic_group();
+    factory.compose(cyclic,all.index());
+    factory.gc_roots();
+    assert_eq!(120,factory.number_solutions::<u64>(all.index()));
+    assert_eq!(all.index(),factory.construct_all_permutations());
+    drop(all);
+    factory.gc_roots();
+    assert_eq!(0,factory.len());
+    assert_ne!(NodeIndex::FALSE,factory.construct_all_permutations());
+}
@@ -0,0 +1,51 @@
+//! Check that the types with serde support survive a round trip through JSON.
+#![cfg(feature="serde")]
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use xdd::{Node, NodeIndex, NoMultiplicity, U48, VariableIndex};
+use xdd::generating_function::{CheckedCount, GeneratingFunctionSplitByMultiplicity, SingleVariableGeneratingFunction, SingleVariableGeneratingFunctionFixedLength};
+use xdd::permutation::Permutation;
+use xdd::tropical::MinPlus;
+use xdd::xdd_with_multiplicity::{NodeList, XDDBase};
+
+fn round_trip<T:Serialize+DeserializeOwned>(value:&T) -> T {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+#[test]
+fn node_list() {
+    let mut nodes = NodeList::<u32,u32>::default();
+    let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    let f = nodes.exactly_n_of_bdd(&variables,3).multiply(5);
+    let copy = round_trip(&nodes);
+    assert!(copy==nodes);
+    assert_eq!(f,round_trip(&f));
+    assert_eq!(100,copy.number_solutions::<u64,true>(f,6));
+    assert!(copy.validate::<true>(f,6).is_ok());
+    let node = nodes.node(f.address());
+    assert!(node==round_trip(&node));
+}
+
+#[test]
+fn addresses_and_multiplicities() {
+    let index : NodeIndex<U48,NoMultiplicity> = NodeIndex::new(U48::try_from(1u64<<40).unwrap(),NoMultiplicity{});
+    assert_eq!(format!("{{\"address\":{},\"multiplicity\":{{}}}}",1u64<<40),serde_json::to_string(&index).unwrap());
+    assert_eq!(index,round_trip(&index));
+    assert!(serde_json::from_str::<U48>(&(1u64<<48).to_string()).is_err());
+    let node = Node{variable:VariableIndex(3),lo:NodeIndex::FALSE,hi:NodeIndex::new(7u16,MinPlus(-2))};
+    assert!(node==round_trip(&node));
+}
+
+#[test]
+fn generating_functions() {
+    let permutation = Permutation{sequence:vec![3,1,2]};
+    assert_eq!(permutation,round_trip(&permutation));
+    let gf = SingleVariableGeneratingFunction::<u64>(vec![1,4,6,4,1]);
+    assert_eq!(gf,round_trip(&gf));
+    let split = GeneratingFunctionSplitByMultiplicity::<u32>(vec![2,0,1]);
+    assert_eq!(split,round_trip(&split));
+    let fixed = SingleVariableGeneratingFunctionFixedLength::<3>(vec![1,2,1]);
+    assert_eq!(fixed,round_trip(&fixed));
+    for count in [CheckedCount(Some(7u64)),CheckedCount(None)] { assert_eq!(count,round_trip(&count)); }
+}
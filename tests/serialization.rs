@@ -0,0 +1,149 @@
+//! Check diagrams survive being saved and loaded, and that damaged files are rejected.
+
+use std::io::ErrorKind;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::xdd_with_multiplicity::{NodeList, NodeListWithFastLookup, XDDBase};
+
+fn build<F:DecisionDiagramFactory<u32,M>,M:xdd::Multiplicity>(factory:&mut F) -> NodeIndex<u32,M> {
+    let one = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2),VariableIndex(4)]);
+    let v1 = factory.single_variable(VariableIndex(1));
+    let v3 = factory.single_variable(VariableIndex(3));
+    let either = factory.or(v1,v3);
+    factory.and(one,either)
+}
+
+#[test]
+fn bdd_round_trip() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(5);
+    let f = build(&mut factory);
+    let mut buffer = vec![];
+    factory.write_to(&mut buffer,&[f,NodeIndex::TRUE]).unwrap();
+    let (mut loaded,roots) = BDDFactory::<u32,NoMultiplicity>::read_from(&mut buffer.as_slice()).unwrap();
+    assert_eq!(vec![f,NodeIndex::TRUE],roots);
+    assert_eq!(factory.len(),loaded.len());
+    assert_eq!(factory.number_solutions::<u64>(f),loaded.number_solutions::<u64>(roots[0]));
+    // the lookup table must have been rebuilt, so constructing the same function again gives the same node.
+    assert_eq!(f,build(&mut loaded));
+    assert_eq!(factory.len(),loaded.len());
+}
+
+#[test]
+fn zdd_round_trip_with_multiplicities() {
+    let mut factory = ZDDFactory::<u32,u32>::new(5);
+    let f = build(&mut factory);
+    let doubled = factory.or(f,f);
+    let mut buffer = vec![];
+    factory.write_to(&mut buffer,&[doubled]).unwrap();
+    let (loaded,roots) = ZDDFactory::<u32,u32>::read_from(&mut buffer.as_slice()).unwrap();
+    assert_eq!(factory.number_solutions::<u64>(doubled),loaded.number_solutions::<u64>(roots[0]));
+    assert_eq!(2,roots[0].multiplicity());
+    // wrong kind of factory, or wrong types.
+    assert_eq!(ErrorKind::InvalidData,BDDFactory::<u32,u32>::read_from(&mut buffer.as_slice()).err().unwrap().kind());
+    assert_eq!(ErrorKind::InvalidData,ZDDFactory::<u32,NoMultiplicity>::read_from(&mut buffer.as_slice()).err().unwrap().kind());
+    assert_eq!(ErrorKind::InvalidData,ZDDFactory::<usize,u32>::read_from(&mut buffer.as_slice()).err().unwrap().kind());
+}
+
+#[test]
+fn node_list_round_trip() {
+    let mut nodes = NodeList::<u64,u32>::default();
+    let v2 = nodes.single_variable(VariableIndex(2));
+    let v1 = nodes.single_variable(VariableIndex(1));
+    let mut cache = xdd::xdd_with_multiplicity::BinaryOperationCache::<u64,u32>::default();
+    nodes.sum_zdd(v1,v2,&mut cache);
+    let mut buffer = vec![];
+    nodes.write_to(&mut buffer).unwrap();
+    assert!(nodes==NodeList::<u64,u32>::read_from(&mut buffer.as_slice()).unwrap());
+    let with_lookup = NodeListWithFastLookup::<u64,u32>::read_from(&mut buffer.as_slice()).unwrap();
+    assert_eq!(nodes.len(),with_lookup.len());
+    let mut rewritten = vec![];
+    with_lookup.write_to(&mut rewritten).unwrap();
+    assert_eq!(buffer,rewritten);
+}
+
+#[test]
+fn damaged_files_are_rejected() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(5);
+    let f = build(&mut factory);
+    let mut buffer = vec![];
+    factory.write_to(&mut buffer,&[f]).unwrap();
+    let load = |bytes:&[u8]| BDDFactory::<u32,NoMultiplicity>::read_from(&mut &bytes[..]).err().map(|e|e.kind());
+    assert_eq!(None,load(&buffer));
+    assert_eq!(Some(ErrorKind::UnexpectedEof),load(&buffer[..buffer.len()-1]));
+    let mut bad_magic = buffer.clone();
+    bad_magic[0]=b'Y';
+    assert_eq!(Some(ErrorKind::InvalidData),load(&bad_magic));
+    let mut bad_version = buffer.clone();
+    bad_version[4]=99;
+    assert_eq!(Some(ErrorKind::InvalidData),load(&bad_version));
+    // the factory header is 4+1+1+2+8 bytes, then one root of 4 bytes.
+    let mut bad_root = buffer.clone();
+    bad_root[16..20].copy_from_slice(&1000u32.to_le_bytes());
+    assert_eq!(Some(ErrorKind::InvalidData),load(&bad_root));
+    let mut too_few_variables = buffer.clone();
+    too_few_variables[6..8].copy_from_slice(&4u16.to_le_bytes());
+    assert_eq!(Some(ErrorKind::InvalidData),load(&too_few_variables));
+    // the last node is its variable (2 bytes) then lo and hi (4 bytes each). Point lo at the node itself.
+    let mut cycle = buffer.clone();
+    let self_address = (factory.len()+1) as u32;
+    let lo = buffer.len()-8;
+    cycle[lo..lo+4].copy_from_slice(&self_address.to_le_bytes());
+    assert_eq!(Some(ErrorKind::InvalidData),load(&cycle));
+}
+
+#[test]
+fn buddy_format() {
+    // x0 & !x1 | x2, as BuDDy would save it with its own node numbering.
+    let file = "3 3\n0 1 2\n7 2 0 1\n9 1 1 7\n12 0 7 9\n";
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let root = factory.read_buddy(&mut file.as_bytes()).unwrap();
+    let v0 = factory.single_variable(VariableIndex(0));
+    let v1 = factory.single_variable(VariableIndex(1));
+    let v2 = factory.single_variable(VariableIndex(2));
+    let not_v1 = factory.not(v1);
+    let v0_not_v1 = factory.and(v0,not_v1);
+    let expected = factory.or(v0_not_v1,v2);
+    assert_eq!(expected,root);
+    let mut written = vec![];
+    factory.write_buddy(&mut written,root).unwrap();
+    let mut other = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let reread = other.read_buddy(&mut written.as_slice()).unwrap();
+    assert_eq!(5,other.number_solutions::<u64>(reread));
+    // constants
+    let mut written = vec![];
+    factory.write_buddy(&mut written,NodeIndex::TRUE).unwrap();
+    assert_eq!("0 0 1\n",String::from_utf8(written).unwrap());
+    assert_eq!(NodeIndex::FALSE,factory.read_buddy(&mut "0 0 0".as_bytes()).unwrap());
+    // a different variable order: variable 0 is at level 1 and variable 1 at level 0, so x0 & !x1 becomes !x0 & x1.
+    let reordered = factory.read_buddy(&mut "2 2\n1 0\n3 0 0 1\n4 1 3 0\n".as_bytes()).unwrap();
+    let not_v0 = factory.not(v0);
+    let expected = factory.and(not_v0,v1);
+    assert_eq!(expected,reordered);
+}
+
+#[test]
+fn bad_buddy_files_are_rejected() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let mut load = |text:&str| factory.read_buddy(&mut text.as_bytes()).err().map(|e|e.kind());
+    assert_eq!(None,load("1 2\n0 1\n5 1 0 1\n"));
+    assert_eq!(Some(ErrorKind::InvalidData),load("1 2\n0 1\n5 1 0\n")); // truncated
+    assert_eq!(Some(ErrorKind::InvalidData),load("1 2\n0 0\n5 1 0 1\n")); // levels not a permutation
+    assert_eq!(Some(ErrorKind::InvalidData),load("1 2\n0 1\n5 1 0 6\n")); // undefined child
+    assert_eq!(Some(ErrorKind::InvalidData),load("2 2\n0 1\n5 1 0 1\n6 1 0 5\n")); // child not below parent
+    assert_eq!(Some(ErrorKind::InvalidData),load("1 4\n0 1 2 3\n5 3 0 1\n")); // too many variables for the factory
+    assert_eq!(Some(ErrorKind::InvalidData),load("0 0 2\n"));
+    assert_eq!(Some(ErrorKind::InvalidData),load("1 x\n"));
+    assert_eq!(Some(ErrorKind::InvalidData),load("1 2\n0 1\n5 1 1 1\n")); // both children the same
+    assert_eq!(Some(ErrorKind::InvalidData),load("3 2\n0 1\n5 1 0 1\n6 1 0 1\n7 0 5 6\n")); // the same after merging duplicates
+    // a bad file adds no nodes, even if the problem is at the end.
+    let len = factory.len();
+    assert!(factory.read_buddy(&mut "3 3\n0 1 2\n5 2 0 1\n6 1 0 5\n7 0 6 8\n".as_bytes()).is_err());
+    assert_eq!(len,factory.len());
+    // identical nodes are merged.
+    let root = factory.read_buddy(&mut "3 2\n0 1\n5 1 0 1\n6 1 0 1\n7 0 5 0\n".as_bytes()).unwrap();
+    assert!(factory.validate(root).is_ok());
+    // multiplicities can't be written.
+    let mut factory = BDDFactory::<u32,u32>::new(2);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let doubled = factory.or(v0,v0);
+    assert_eq!(ErrorKind::InvalidInput,factory.write_buddy(&mut vec![],doubled).err().unwrap().kind());
+}
@@ -0,0 +1,78 @@
+//! Check that building a conjunction in shards and merging them gives the same result as building it in one factory.
+
+use std::io::ErrorKind;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::sharding::{ShardFactory, Sharding};
+
+/// Constraints for no two adjacent cells on a 4x4 grid being true; there are 24 of them.
+fn no_adjacent<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F,i:usize) -> NodeIndex<u32,NoMultiplicity> {
+    let (cell,next) = if i<12 { let cell = (i/3)*4+i%3; (cell,cell+1) } else { (i-12,i-8) };
+    factory.at_most_n_of(&[VariableIndex(cell as u16),VariableIndex(next as u16)],1)
+}
+
+fn sharding(name:&str,num_shards:usize) -> Sharding {
+    let sharding = Sharding::new(std::env::temp_dir().join(format!("xdd_sharding_test_{}",name)),num_shards);
+    std::fs::create_dir_all(&sharding.directory).unwrap();
+    sharding
+}
+
+fn check_merge<F:ShardFactory<u32,NoMultiplicity>>(name:&str) {
+    for num_shards in [1,4,30] {
+        let sharding = sharding(name,num_shards);
+        assert_eq!(24,(0..num_shards).map(|shard|sharding.constraints(shard,24).len()).sum::<usize>());
+        for shard in 0..num_shards { sharding.run_worker(shard,16,24,no_adjacent::<F>).unwrap(); }
+        let (mut factory,merged) = sharding.merge::<u32,NoMultiplicity,F>(16).unwrap();
+        let constraints : Vec<NodeIndex<u32,NoMultiplicity>> = (0..24).map(|i|no_adjacent(&mut factory,i)).collect();
+        assert_eq!(factory.poly_and(&constraints),merged);
+        assert_eq!(1234,factory.number_solutions::<u64>(merged.unwrap()));
+    }
+    // no constraints at all.
+    let sharding = sharding(name,2);
+    for shard in 0..2 { sharding.run_worker(shard,16,0,no_adjacent::<F>).unwrap(); }
+    assert_eq!(None,sharding.merge::<u32,NoMultiplicity,F>(16).unwrap().1);
+}
+
+#[test]
+fn merge_bdd() { check_merge::<BDDFactory<u32,NoMultiplicity>>("bdd"); }
+
+#[test]
+fn merge_zdd() { check_merge::<ZDDFactory<u32,NoMultiplicity>>("zdd"); }
+
+#[test]
+fn bad_shards() {
+    let sharding = sharding("bad",3);
+    for shard in 0..3 { sharding.run_worker(shard,16,24,no_adjacent::<BDDFactory<u32,NoMultiplicity>>).unwrap(); }
+    // the wrong kind of diagram.
+    assert_eq!(ErrorKind::InvalidData,sharding.merge::<u32,NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>(16).err().unwrap().kind());
+    // a corrupted checksum in the last byte of the first root's checksum, after the name "shard".
+    let mut bytes = std::fs::read(sharding.shard_path(1)).unwrap();
+    bytes[4+1+8+8+5+7]^=1;
+    std::fs::write(sharding.shard_path(1),&bytes).unwrap();
+    let error = sharding.merge::<u32,NoMultiplicity,BDDFactory<u32,NoMultiplicity>>(16).err().unwrap();
+    assert_eq!(ErrorKind::InvalidData,error.kind());
+    assert!(error.to_string().starts_with("shard 1: root shard has checksum"));
+    // a missing shard.
+    bytes[4+1+8+8+5+7]^=1;
+    std::fs::write(sharding.shard_path(1),&bytes).unwrap();
+    std::fs::remove_file(sharding.shard_path(2)).unwrap();
+    assert_eq!(ErrorKind::NotFound,sharding.merge::<u32,NoMultiplicity,BDDFactory<u32,NoMultiplicity>>(16).err().unwrap().kind());
+}
+
+#[test]
+#[cfg(unix)]
+fn run_workers() {
+    let sharding = sharding("workers",3);
+    let command = |exit_code:usize|{
+        let mut command = std::process::Command::new("sh");
+        command.arg("-c").arg(format!("exit {}",exit_code));
+        command
+    };
+    sharding.run_workers(|_|command(0)).unwrap();
+    let error = sharding.run_workers(|shard|command(shard%2)).err().unwrap();
+    assert!(error.to_string().contains("shard 1"));
+    assert!(!error.to_string().contains("shard 0"));
+}
+
+#[test]
+#[should_panic(expected="at least one shard")]
+fn no_shards() { Sharding::new(std::env::temp_dir(),0); }
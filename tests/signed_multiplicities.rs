@@ -0,0 +1,62 @@
+//! Check signed multiplicities, where cancellation can happen inside a diagram.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use xdd::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::generating_function::SingleVariableGeneratingFunction;
+use common::random_signed_function;
+
+const NUM_VARIABLES : u16 = 6;
+
+/// Sum the diagrams in two different orders, and check that the results are the same node.
+fn check_cancellation<F:DecisionDiagramFactory<u32,i64>>(mut factory:F) {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..100 {
+        let f = random_signed_function(&mut factory,&mut rng,NUM_VARIABLES,4);
+        let g = random_signed_function(&mut factory,&mut rng,NUM_VARIABLES,4);
+        let minus_f = f.multiply(-1);
+        assert_eq!(NodeIndex::FALSE,factory.or(f,minus_f));
+        let f_plus_g = factory.or(f,g);
+        let g_minus_f = factory.or(g,minus_f);
+        let sum = factory.or(f_plus_g,g_minus_f);
+        assert_eq!(factory.or(g,g),sum);
+        let count_f = factory.number_solutions::<i64>(f);
+        let count_g = factory.number_solutions::<i64>(g);
+        assert_eq!(2*count_g,factory.number_solutions::<i64>(sum));
+        assert_eq!(count_f+count_g,factory.number_solutions::<i64>(f_plus_g));
+        let gf = factory.number_solutions::<SingleVariableGeneratingFunction<i64>>(f_plus_g);
+        assert_eq!(count_f+count_g,gf.0.iter().sum::<i64>());
+    }
+}
+
+#[test]
+fn cancellation_bdd() { check_cancellation(BDDFactory::<u32,i64>::new(NUM_VARIABLES)); }
+
+#[test]
+fn cancellation_zdd() { check_cancellation(ZDDFactory::<u32,i64>::new(NUM_VARIABLES)); }
+
+#[test]
+fn inclusion_exclusion() {
+    // The assignments with neither v0 nor v1 true, as everything - those with v0 - those with v1 + those with both.
+    let mut factory = BDDFactory::<u32,i128>::new(NUM_VARIABLES);
+    let everything = factory.not(NodeIndex::FALSE);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let v1 = factory.single_variable(VariableIndex(1));
+    let both = factory.and(v0,v1);
+    let without_v0 = factory.or(everything,v0.multiply(-1));
+    let without_either = factory.or(without_v0,v1.multiply(-1));
+    let neither = factory.or(without_either,both);
+    let v0_or_v1 = factory.or(v0,v1);
+    let v0_or_v1 = factory.or(v0_or_v1,both.multiply(-1));
+    assert_eq!(factory.not(v0_or_v1),neither);
+    assert_eq!(16,factory.number_solutions::<i128>(neither));
+}
+
+#[test]
+fn gcd_sign() {
+    assert_eq!((1,-2,-3),i64::gcd(-3,6));
+    assert_eq!((1,-2,3),i64::gcd(3,-6));
+    assert_eq!((2,3,5),i128::gcd(10,15));
+}
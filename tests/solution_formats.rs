@@ -0,0 +1,58 @@
+//! Check that solutions given as bit masks, value lists or borrowed slices match the ordinary solutions.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, SolutionFormat, VariableIndex, ZDDFactory};
+
+const N : u16 = 8;
+
+/// Bit v of the result is set iff variable v is true.
+fn mask(solution:&[VariableIndex]) -> u128 { solution.iter().map(|v|1u128<<v.0).sum() }
+
+fn check_formats<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut factory = F::new(N);
+    let a = factory.exactly_n_of(&variables,3);
+    let b = factory.at_most_n_of(&variables[5..],1);
+    let f = factory.xor(a,b);
+    let expected : Vec<Vec<VariableIndex>> = factory.solutions(f).collect();
+    assert!(!expected.is_empty());
+    assert_eq!(expected,factory.solutions_as::<Vec<VariableIndex>>(f).collect::<Vec<_>>());
+    assert_eq!(expected.iter().map(|s|mask(s) as u32).collect::<Vec<_>>(),factory.solutions_as::<u32>(f).collect::<Vec<_>>());
+    assert_eq!(expected.iter().map(|s|mask(s) as u64).collect::<Vec<_>>(),factory.solutions_as::<u64>(f).collect::<Vec<_>>());
+    assert_eq!(expected.iter().map(|s|mask(s)).collect::<Vec<_>>(),factory.solutions_as::<u128>(f).collect::<Vec<_>>());
+    let values : Vec<Vec<bool>> = expected.iter().map(|s|variables.iter().map(|v|s.contains(v)).collect()).collect();
+    assert_eq!(values,factory.solutions_as::<Vec<bool>>(f).collect::<Vec<_>>());
+    let mut borrowed = vec![];
+    factory.for_each_solution(f,|s|borrowed.push(s.to_vec()));
+    assert_eq!(expected,borrowed);
+    let mut calls = 0;
+    factory.for_each_solution(xdd::NodeIndex::FALSE,|_|calls+=1);
+    assert_eq!(0,calls);
+}
+
+#[test]
+fn formats_bdd() { check_formats::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn formats_zdd() { check_formats::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+/// A format of one's own, here the number of true variables.
+struct Size(usize);
+
+impl SolutionFormat for Size {
+    fn from_solution(true_variables:&[VariableIndex],_num_variables:u16) -> Self { Size(true_variables.len()) }
+}
+
+#[test]
+fn custom_format() {
+    let variables : Vec<VariableIndex> = (0..5).map(VariableIndex).collect();
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(5);
+    let f = factory.between_n_and_m_of(&variables,1,2);
+    assert_eq!(5+2*10,factory.solutions_as::<Size>(f).map(|s|s.0).sum::<usize>());
+}
+
+#[test]
+#[should_panic(expected = "can not hold")]
+fn too_many_variables() {
+    let factory = BDDFactory::<u32,NoMultiplicity>::new(40);
+    let _ = factory.solutions_as::<u32>(xdd::NodeIndex::TRUE);
+}
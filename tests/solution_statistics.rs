@@ -0,0 +1,52 @@
+//! Check entropies and correlations of variables in a random solution against the same found by listing the solutions.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+
+/// The solutions of f, each as a bit mask and weight.
+fn weighted_solutions<F:DecisionDiagramFactory<u32,u32>>(factory:&F,f:NodeIndex<u32,u32>) -> Vec<(u32,f64)> {
+    factory.solutions(f).map(|s|{
+        let assignment : Vec<bool> = (0..N).map(|v|s.contains(&VariableIndex(v))).collect();
+        (s.iter().map(|v|1<<v.0).sum(),factory.evaluate(f,&assignment).unwrap() as f64)
+    }).collect()
+}
+
+fn close(a:f64,b:f64) -> bool { (a-b).abs()<1e-9 }
+
+fn check_statistics<F:DecisionDiagramFactory<u32,u32>>() {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut factory = F::new(N);
+    let a = factory.exactly_n_of(&variables[..4],2);
+    let b = factory.at_most_n_of(&variables[2..],1);
+    let b = factory.scale(b,3);
+    let v5 = factory.single_variable(VariableIndex(5));
+    let f = factory.or(a,b);
+    let f = factory.and(f,v5); // so variable 5 is constant.
+    let solutions = weighted_solutions(&factory,f);
+    let total : f64 = solutions.iter().map(|(_,w)|w).sum();
+    let p = |mask:u32| solutions.iter().filter(|(s,_)|s&mask==mask).map(|(_,w)|w).sum::<f64>()/total;
+    let entropies = factory.variable_entropies(f);
+    let correlations = factory.variable_correlations(f);
+    for i in 0..N as usize {
+        let pi = p(1<<i);
+        let expected = if pi==0.0 || pi==1.0 { 0.0 } else { -pi*pi.log2()-(1.0-pi)*(1.0-pi).log2() };
+        assert!(close(expected,entropies[i]),"entropy of {} is {} not {}",i,entropies[i],expected);
+        for (j,&correlation) in correlations[i].iter().enumerate() {
+            let pj = p(1<<j);
+            let spread = (pi*(1.0-pi)*pj*(1.0-pj)).sqrt();
+            let expected = if spread==0.0 { 0.0 } else { (p((1<<i)|(1<<j))-pi*pj)/spread };
+            assert!(close(expected,correlation),"correlation of {},{} is {} not {}",i,j,correlation,expected);
+        }
+    }
+    assert_eq!(0.0,entropies[5]);
+    assert!(close(1.0,correlations[0][0]));
+    assert_eq!(vec![0.0;N as usize],factory.variable_entropies(NodeIndex::FALSE));
+    assert_eq!(vec![vec![0.0;N as usize];N as usize],factory.variable_correlations(NodeIndex::FALSE));
+}
+
+#[test]
+fn statistics_bdd() { check_statistics::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn statistics_zdd() { check_statistics::<ZDDFactory<u32,u32>>(); }
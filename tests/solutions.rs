@@ -0,0 +1,172 @@
+//! Check that iterating over solutions gives each solution exactly once, in order, that random sampling is fair, and that the best solutions are found.
+
+use std::collections::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, Objective, VariableIndex, ZDDFactory};
+use xdd::generating_function::GeneratingFunctionWithMultiplicity;
+
+/// The diagram true just for the given set of true variables.
+fn minterm<F:DecisionDiagramFactory<u32,NoMultiplicity>>(factory:&mut F,num_variables:u16,solution:&[VariableIndex]) -> NodeIndex<u32,NoMultiplicity> {
+    let mut res = NodeIndex::TRUE;
+    for i in 0..num_variables {
+        let v = factory.single_variable(VariableIndex(i));
+        let literal = if solution.contains(&VariableIndex(i)) { v } else { factory.not(v) };
+        res = factory.and(res,literal);
+    }
+    res
+}
+
+fn check_solutions<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(5);
+    let variables : Vec<VariableIndex> = [0,1,3,4].into_iter().map(VariableIndex).collect();
+    let two = factory.exactly_n_of(&variables,2);
+    let v2 = factory.single_variable(VariableIndex(2));
+    let not_v2 = factory.not(v2);
+    let three = factory.exactly_n_of(&variables,3);
+    let three_and_not_v2 = factory.and(three,not_v2);
+    for f in [NodeIndex::FALSE,NodeIndex::TRUE,v2,two,three_and_not_v2] {
+        let solutions : Vec<Vec<VariableIndex>> = factory.solutions(f).collect();
+        assert_eq!(factory.number_solutions::<u64>(f),solutions.len() as u64);
+        let mut sorted = solutions.clone();
+        sorted.sort_by_key(|s|(0..5).map(|i|s.contains(&VariableIndex(i))).collect::<Vec<_>>());
+        assert_eq!(sorted,solutions);
+        for solution in &solutions {
+            assert!(solution.windows(2).all(|w|w[0]<w[1]));
+            let m = minterm(&mut factory,5,solution);
+            assert_eq!(m,factory.and(m,f));
+        }
+    }
+}
+
+#[test]
+fn solutions_bdd() { check_solutions::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn solutions_zdd() { check_solutions::<ZDDFactory<u32,NoMultiplicity>>() }
+
+/// Sample many times, returning how often each solution occurred.
+fn sample_frequencies<F:DecisionDiagramFactory<u32,M>,M:xdd::Multiplicity>(factory:&F,f:NodeIndex<u32,M>,samples:usize) -> HashMap<Vec<VariableIndex>,usize> where u128:GeneratingFunctionWithMultiplicity<M> {
+    let mut res = HashMap::new();
+    for solution in factory.random_solutions(f,StdRng::seed_from_u64(1)).take(samples) {
+        *res.entry(solution).or_insert(0)+=1;
+    }
+    res
+}
+
+fn check_uniform_sampling<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(5);
+    let variables : Vec<VariableIndex> = [0,1,3,4].into_iter().map(VariableIndex).collect();
+    let two = factory.exactly_n_of(&variables,2);
+    let frequencies = sample_frequencies(&factory,two,12000);
+    let all : Vec<Vec<VariableIndex>> = factory.solutions(two).collect();
+    assert_eq!(12,all.len());
+    for solution in all {
+        let count = frequencies[&solution];
+        assert!((800..1200).contains(&count),"{:?} sampled {} times",solution,count);
+    }
+    assert_eq!(12,frequencies.len());
+    assert_eq!(None,factory.sample_solution(NodeIndex::FALSE,&mut StdRng::seed_from_u64(1)));
+    assert!(factory.sample_solution(NodeIndex::TRUE,&mut StdRng::seed_from_u64(1)).is_some());
+}
+
+#[test]
+fn uniform_sampling_bdd() { check_uniform_sampling::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn uniform_sampling_zdd() { check_uniform_sampling::<ZDDFactory<u32,NoMultiplicity>>() }
+
+/// Solutions with multiplicity 2 should be sampled twice as often.
+fn check_weighted_sampling<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut factory = F::new(2);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let v1 = factory.single_variable(VariableIndex(1));
+    let both = factory.and(v0,v1);
+    let f = factory.or(v0,both); // {0} has multiplicity 1, {0,1} has multiplicity 2.
+    let solutions : Vec<Vec<VariableIndex>> = factory.solutions(f).collect();
+    let frequencies = sample_frequencies(&factory,f,9000);
+    let counted = |solution:&Vec<VariableIndex>|frequencies.get(solution).cloned().unwrap_or(0);
+    let total_weight = factory.number_solutions::<u64>(f) as usize;
+    for solution in &solutions {
+        let single = factory.exactly_n_of(solution,solution.len());
+        let others : Vec<VariableIndex> = (0..2).map(VariableIndex).filter(|v|!solution.contains(v)).collect();
+        let none_of_others = factory.exactly_n_of(&others,0);
+        let just_this = factory.and(single,none_of_others);
+        let weighted = factory.and(f,just_this);
+        let weight = factory.number_solutions::<u64>(weighted) as usize;
+        let expected = 9000*weight/total_weight;
+        assert!(counted(solution).abs_diff(expected)<expected/10,"{:?} sampled {} times, expected {}",solution,counted(solution),expected);
+    }
+}
+
+#[test]
+fn weighted_sampling_bdd() { check_weighted_sampling::<BDDFactory<u32,u32>>() }
+#[test]
+fn weighted_sampling_zdd() { check_weighted_sampling::<ZDDFactory<u32,u32>>() }
+
+/// Compare best_solutions with sorting all the solutions by weight.
+fn check_best_solutions<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(6);
+    let weights = [3,-2,7,1,-5,4];
+    let weight = |solution:&[VariableIndex]|solution.iter().map(|v|weights[v.0 as usize]).sum::<i32>();
+    let variables : Vec<VariableIndex> = [0,2,3,5].into_iter().map(VariableIndex).collect();
+    let two_or_three = factory.between_n_and_m_of(&variables,2,3);
+    let v1 = factory.single_variable(VariableIndex(1));
+    let with_v1 = factory.and(two_or_three,v1);
+    for f in [NodeIndex::FALSE,NodeIndex::TRUE,two_or_three,with_v1] {
+        let mut expected : Vec<i32> = factory.solutions(f).map(|s|weight(&s)).collect();
+        expected.sort_by(|a,b|b.cmp(a));
+        for k in [0,1,5,100] {
+            let best = factory.best_solutions(f,&weights,k);
+            assert_eq!(expected.iter().take(k).cloned().collect::<Vec<_>>(),best.iter().map(|(w,_)|*w).collect::<Vec<_>>());
+            for (w,solution) in &best {
+                assert_eq!(*w,weight(solution));
+                assert!(solution.windows(2).all(|w|w[0]<w[1]));
+                let m = minterm(&mut factory,6,solution);
+                assert_eq!(m,factory.and(m,f));
+            }
+        }
+    }
+    // fewest variables.
+    let fewest = factory.best_solutions(with_v1,&[-1.0;6],1);
+    assert_eq!(1,fewest.len());
+    assert_eq!((-3.0,3),(fewest[0].0,fewest[0].1.len()));
+}
+
+#[test]
+fn best_solutions_bdd() { check_best_solutions::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn best_solutions_zdd() { check_best_solutions::<ZDDFactory<u32,NoMultiplicity>>() }
+
+/// Compare find_optimal_solution with the least and greatest weights of all the solutions, with weights big enough that sums overflow i64.
+fn check_optimal_solution<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(6);
+    let weights = [i64::MAX,-3,i64::MAX,i64::MIN,0,8];
+    let weight = |solution:&[VariableIndex]|solution.iter().map(|v|weights[v.0 as usize] as i128).sum::<i128>();
+    let variables : Vec<VariableIndex> = (0..6).map(VariableIndex).collect();
+    for k in 0..=6 {
+        let f = factory.exactly_n_of(&variables,k);
+        let v4 = factory.single_variable(VariableIndex(4));
+        let not_v4 = factory.not(v4);
+        let g = factory.xor(f,not_v4);
+        for f in [f,g] {
+            let all : Vec<i128> = factory.solutions(f).map(|s|weight(&s)).collect();
+            for (objective,expected) in [(Objective::Minimize,all.iter().min()),(Objective::Maximize,all.iter().max())] {
+                let found = factory.find_optimal_solution(f,&weights,objective);
+                assert_eq!(expected.cloned(),found.as_ref().map(|(w,_)|*w));
+                if let Some((w,solution)) = found {
+                    assert_eq!(w,weight(&solution));
+                    let m = minterm(&mut factory,6,&solution);
+                    assert_eq!(m,factory.and(m,f));
+                }
+            }
+        }
+    }
+    assert_eq!(None,factory.find_optimal_solution(NodeIndex::FALSE,&weights,Objective::Minimize));
+    // fewest variables.
+    let at_least_two = factory.at_least_n_of(&variables,2);
+    assert_eq!(2,factory.find_optimal_solution(at_least_two,&[1;6],Objective::Minimize).unwrap().0);
+}
+
+#[test]
+fn optimal_solution_bdd() { check_optimal_solution::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn optimal_solution_zdd() { check_optimal_solution::<ZDDFactory<u32,NoMultiplicity>>() }
@@ -0,0 +1,70 @@
+//! Check multiset subtraction against the multiplicities of individual solutions.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use common::random_function;
+
+const NUM_VARIABLES : u16 = 5;
+
+/// The multiplicity of the assignment given by the bits of assignment, found by restricting each variable.
+fn multiplicity<F:DecisionDiagramFactory<u32,u32>>(factory:&mut F,f:NodeIndex<u32,u32>,assignment:u32) -> u32 {
+    let mut f = f;
+    for v in 0..NUM_VARIABLES {
+        let x = factory.single_variable(VariableIndex(v));
+        let literal = if assignment&(1<<v)!=0 { x } else { factory.not(x) };
+        f = factory.and(f,literal);
+    }
+    factory.number_solutions::<u64>(f) as u32
+}
+
+fn check_subtract<F:DecisionDiagramFactory<u32,u32>>(mut factory:F) {
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..50 {
+        let f = random_function(&mut factory,&mut rng,NUM_VARIABLES,5);
+        let g = random_function(&mut factory,&mut rng,NUM_VARIABLES,5);
+        let difference = factory.subtract(f,g);
+        for assignment in 0..1<<NUM_VARIABLES {
+            let expected = multiplicity(&mut factory,f,assignment).saturating_sub(multiplicity(&mut factory,g,assignment));
+            assert_eq!(expected,multiplicity(&mut factory,difference,assignment));
+        }
+        // difference+g is the larger of f and g, and f-difference the smaller.
+        let larger = factory.or(difference,g);
+        let smaller = factory.subtract(f,difference);
+        assert_eq!(f,factory.or(smaller,difference));
+        assert_eq!(factory.subtract(g,f),factory.subtract(larger,f));
+        assert_eq!(NodeIndex::FALSE,factory.subtract(f,f));
+    }
+}
+
+#[test]
+fn subtract_mbdd() { check_subtract(BDDFactory::<u32,u32>::new(NUM_VARIABLES)); }
+
+#[test]
+fn subtract_mzdd() { check_subtract(ZDDFactory::<u32,u32>::new(NUM_VARIABLES)); }
+
+#[test]
+fn subtract_sets() {
+    let mut rng = StdRng::seed_from_u64(6);
+    let mut bdd = BDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES);
+    let mut zdd = ZDDFactory::<u32,NoMultiplicity>::new(NUM_VARIABLES);
+    for _ in 0..50 {
+        let f = random_function(&mut bdd,&mut rng,NUM_VARIABLES,5);
+        let g = random_function(&mut bdd,&mut rng,NUM_VARIABLES,5);
+        let not_g = bdd.not(g);
+        assert_eq!(bdd.and(f,not_g),bdd.subtract(f,g));
+        let f = random_function(&mut zdd,&mut rng,NUM_VARIABLES,5);
+        let g = random_function(&mut zdd,&mut rng,NUM_VARIABLES,5);
+        assert_eq!(zdd.difference(f,g),zdd.subtract(f,g));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Saturating subtraction is not defined for this type of multiplicity")]
+fn subtract_signed() {
+    let mut factory = BDDFactory::<u32,i64>::new(NUM_VARIABLES);
+    let v0 = factory.single_variable(VariableIndex(0));
+    factory.subtract(v0,v0);
+}
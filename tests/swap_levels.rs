@@ -0,0 +1,53 @@
+//! Check that swapping adjacent variables keeps the functions the same, other than exchanging the variables.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use xdd::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeIndex, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::generating_function::GeneratingFunctionWithMultiplicity;
+use common::random_function;
+
+const NUM_VARIABLES : u16 = 5;
+
+fn assignments<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&F,index:NodeIndex<u32,M>) -> Vec<u32> {
+    let mut res : Vec<u32> = factory.solutions(index).map(|s|s.iter().map(|v|1<<v.0).sum()).collect();
+    res.sort();
+    res
+}
+
+fn check<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>() where u64:GeneratingFunctionWithMultiplicity<M> {
+    let mut rng = StdRng::seed_from_u64(23);
+    for _ in 0..100 {
+        let mut factory = F::new(NUM_VARIABLES);
+        let mut roots : Vec<NodeIndex<u32,M>> = (0..3).map(|_|random_function(&mut factory,&mut rng,NUM_VARIABLES,4)).collect();
+        let renaming = factory.gc(roots.clone());
+        roots = roots.iter().map(|&r|renaming.rename(r).unwrap()).collect();
+        let original_len = factory.len();
+        for v in 0..NUM_VARIABLES-1 {
+            let bits = 3u32<<v;
+            let expected : Vec<(Vec<u32>,u64)> = roots.iter().map(|&r|{
+                let mut swapped : Vec<u32> = assignments(&factory,r).into_iter().map(|a|if (a&bits).count_ones()==1 { a^bits } else { a }).collect();
+                swapped.sort();
+                (swapped,factory.number_solutions::<u64>(r))
+            }).collect();
+            let renaming = factory.swap_adjacent_levels(VariableIndex(v),roots.clone());
+            roots = roots.iter().map(|&r|renaming.rename(r).unwrap()).collect();
+            let found : Vec<(Vec<u32>,u64)> = roots.iter().map(|&r|(assignments(&factory,r),factory.number_solutions::<u64>(r))).collect();
+            assert_eq!(expected,found);
+            // swapping back gives the same diagrams, so the same number of nodes.
+            let renaming = factory.swap_adjacent_levels(VariableIndex(v),roots.clone());
+            roots = roots.iter().map(|&r|renaming.rename(r).unwrap()).collect();
+            assert_eq!(original_len,factory.len());
+        }
+    }
+}
+
+#[test]
+fn bdd() { check::<NoMultiplicity,BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn zdd() { check::<NoMultiplicity,ZDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn mbdd() { check::<u32,BDDFactory<u32,u32>>() }
+#[test]
+fn mzdd() { check::<u32,ZDDFactory<u32,u32>>() }
@@ -0,0 +1,51 @@
+//! Check symmetric_variables against swapping each pair of variables in every assignment.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+
+fn assignment(x:u32) -> Vec<bool> { (0..N).map(|v|(x>>v)&1==1).collect() }
+
+fn swap(x:u32,i:u16,j:u16) -> u32 {
+    let (bi,bj) = ((x>>i)&1,(x>>j)&1);
+    (x&!(1<<i)&!(1<<j))|(bi<<j)|(bj<<i)
+}
+
+fn check_symmetry<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(29);
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    for _ in 0..60 {
+        // functions of the counts of some subsets, so there are some symmetries to find.
+        let mut f = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(1..3) {
+            let subset : Vec<VariableIndex> = variables.iter().cloned().filter(|_|rng.gen_bool(0.6)).collect();
+            let term = factory.between_n_and_m_of(&subset,rng.gen_range(0..2),rng.gen_range(1..4));
+            let term = if rng.gen_bool(0.3) { let v = factory.single_variable(variables[rng.gen_range(0..N as usize)]); factory.and(term,v) } else { term };
+            let term = factory.scale(term,rng.gen_range(1..3));
+            f = factory.or(f,term);
+        }
+        let len = factory.len();
+        let classes = factory.symmetric_variables(f);
+        assert_eq!(len,factory.len());
+        let mut all : Vec<VariableIndex> = classes.concat();
+        all.sort();
+        assert_eq!(variables,all);
+        let class_of = |v:u16| classes.iter().position(|c|c.contains(&VariableIndex(v))).unwrap();
+        for i in 0..N {
+            for j in i+1..N {
+                let symmetric = (0..1u32<<N).all(|x|factory.evaluate(f,&assignment(x))==factory.evaluate(f,&assignment(swap(x,i,j))));
+                assert_eq!(symmetric,class_of(i)==class_of(j),"variables {} and {}",i,j);
+            }
+        }
+    }
+    assert_eq!(vec![variables.clone()],factory.symmetric_variables(NodeIndex::TRUE));
+}
+
+#[test]
+fn symmetry_bdd() { check_symmetry::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn symmetry_zdd() { check_symmetry::<ZDDFactory<u32,u32>>(); }
@@ -0,0 +1,84 @@
+//! Check tropical multiplicities find the best weight of a solution.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, Multiplicity, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::generating_function::GeneratingFunctionWithMultiplicity;
+use xdd::tropical::{MaxPlus, MinPlus};
+use common::random_function;
+
+const NUM_VARIABLES : u16 = 7;
+
+/// f, with each solution weighted by the sum of the weights of the variables that are true.
+fn weighted<M:Multiplicity,F:DecisionDiagramFactory<u32,M>>(factory:&mut F,f:NodeIndex<u32,M>,weights:&[i64],to_m:impl Fn(i64)->M) -> NodeIndex<u32,M> {
+    let mut res = f;
+    for (v,&w) in weights.iter().enumerate() {
+        let x = factory.single_variable(VariableIndex(v as u16));
+        let not_x = factory.not(x);
+        let weight = factory.or(x.multiply(to_m(w)),not_x);
+        res = factory.and(res,weight);
+    }
+    res
+}
+
+fn check_best<M:Multiplicity+GeneratingFunctionWithMultiplicity<M>,F:DecisionDiagramFactory<u32,M>>(mut factory:F,to_m:impl Fn(i64)->M,best:impl Fn(i64,i64)->i64,none:M) {
+    let mut rng = StdRng::seed_from_u64(11);
+    for _ in 0..50 {
+        let weights : Vec<i64> = (0..NUM_VARIABLES).map(|_|rng.gen_range(-10..10)).collect();
+        let f = random_function(&mut factory,&mut rng,NUM_VARIABLES,4);
+        let g = weighted(&mut factory,f,&weights,&to_m);
+        let expected = factory.solutions(f).map(|s|s.iter().map(|v|weights[v.0 as usize]).sum::<i64>()).reduce(&best);
+        assert_eq!(expected.map(&to_m).unwrap_or(none),factory.number_solutions::<M>(g));
+    }
+}
+
+#[test]
+fn max_plus_bdd() { check_best(BDDFactory::<u32,MaxPlus>::new(NUM_VARIABLES),MaxPlus,i64::max,MaxPlus::NONE); }
+
+#[test]
+fn max_plus_zdd() { check_best(ZDDFactory::<u32,MaxPlus>::new(NUM_VARIABLES),MaxPlus,i64::max,MaxPlus::NONE); }
+
+#[test]
+fn min_plus_bdd() { check_best(BDDFactory::<u32,MinPlus>::new(NUM_VARIABLES),MinPlus,i64::min,MinPlus::NONE); }
+
+#[test]
+fn min_plus_zdd() { check_best(ZDDFactory::<u32,MinPlus>::new(NUM_VARIABLES),MinPlus,i64::min,MinPlus::NONE); }
+
+#[test]
+fn exists_takes_best() {
+    // f is weighted 5 when v0 is true, plus 2 when v1 is true. exists v0 takes the smaller over both values of v0.
+    let mut factory = BDDFactory::<u32,MinPlus>::new(2);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let v1 = factory.single_variable(VariableIndex(1));
+    let f = factory.or(v0.multiply(MinPlus(5)),v1.multiply(MinPlus(2)));
+    let best = factory.exists(f,&[VariableIndex(0)]);
+    let expected = factory.or(v1.multiply(MinPlus(2)),NodeIndex::TRUE.multiply(MinPlus(5)));
+    assert_eq!(expected,best);
+    assert_eq!(MinPlus(2),factory.number_solutions::<MinPlus>(best));
+}
+
+#[test]
+fn weights_near_the_limits() {
+    assert_eq!(MaxPlus(i64::MAX),<MaxPlus as Multiplicity>::multiply(MaxPlus(i64::MAX-1),MaxPlus(1)));
+    assert_eq!(MinPlus(i64::MIN),<MinPlus as Multiplicity>::multiply(MinPlus(i64::MIN+1),MinPlus(-1)));
+    assert_eq!((MaxPlus(0),MaxPlus(-10),MaxPlus(20)),MaxPlus::gcd(MaxPlus(20),MaxPlus(10)));
+    assert_eq!(MaxPlus::NONE,GeneratingFunctionWithMultiplicity::multiply(MaxPlus::NONE,MaxPlus(-5)));
+}
+
+#[test]
+#[should_panic(expected="out of range")]
+fn multiply_overflows() { <MaxPlus as Multiplicity>::multiply(MaxPlus(i64::MAX),MaxPlus(1)); }
+
+#[test]
+#[should_panic(expected="out of range")]
+fn multiply_reaches_none() { <MaxPlus as Multiplicity>::multiply(MaxPlus(i64::MIN+1),MaxPlus(-1)); }
+
+#[test]
+#[should_panic(expected="out of range")]
+fn gcd_overflows() { MinPlus::gcd(MinPlus(i64::MAX-1),MinPlus(-3)); }
+
+#[test]
+#[should_panic(expected="out of range")]
+fn generating_function_overflows() { GeneratingFunctionWithMultiplicity::multiply(MinPlus(i64::MAX-1),MinPlus(1)); }
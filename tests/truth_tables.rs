@@ -0,0 +1,60 @@
+//! Check truth tables, minterms and cubes against functions whose values are easy to work out directly.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, NoMultiplicity, PartialEvaluation, VariableIndex, ZDDFactory};
+
+const N : u16 = 7;
+
+/// The assignments, as in a truth table, covered by a cube.
+fn expand(cube:&[Option<bool>]) -> Vec<usize> {
+    (0..1usize<<cube.len()).filter(|&x|cube.iter().enumerate().all(|(v,value)|value.is_none_or(|value|value==((x>>v)&1==1)))).collect()
+}
+
+fn check_tables<F:DecisionDiagramFactory<u32,NoMultiplicity>>(is_bdd:bool) {
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let odd_variables : Vec<VariableIndex> = (1..N).step_by(2).map(VariableIndex).collect();
+    for k in 0..=3 {
+        let at_least = factory.at_least_n_of(&variables,k);
+        let odd = factory.exactly_n_of(&odd_variables,k);
+        let f = factory.xor(at_least,odd);
+        let expected : Vec<bool> = (0..1usize<<N).map(|x|(x.count_ones() as usize>=k)!=((x&0b0101010).count_ones() as usize==k)).collect();
+        let table = factory.truth_table(f);
+        assert_eq!(expected,table);
+        let minterms = factory.minterms(f);
+        assert_eq!((0..1<<N).filter(|&x|table[x]).collect::<Vec<usize>>(),minterms);
+        let cubes = factory.cubes(f);
+        let mut covered : Vec<usize> = cubes.iter().flat_map(|cube|expand(cube)).collect();
+        covered.sort();
+        assert_eq!(minterms,covered); // each minterm in exactly one cube.
+        for cube in &cubes {
+            assert_eq!(N as usize,cube.len());
+            assert!(is_bdd || cube.iter().all(|value|value.is_some()));
+            assert_eq!(PartialEvaluation::Determined(true),factory.evaluate_partial(f,cube));
+        }
+    }
+    assert!(factory.cubes(NodeIndex::FALSE).is_empty());
+    assert_eq!(Some(&0),factory.minterms(NodeIndex::TRUE).first()); // the empty set, with everything else too for a BDD.
+}
+
+#[test]
+fn tables_bdd() { check_tables::<BDDFactory<u32,NoMultiplicity>>(true); }
+
+#[test]
+fn tables_zdd() { check_tables::<ZDDFactory<u32,NoMultiplicity>>(false); }
+
+#[test]
+fn bdd_cubes_are_paths() {
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(3);
+    let v0 = factory.single_variable(VariableIndex(0));
+    let v2 = factory.single_variable(VariableIndex(2));
+    let f = factory.or(v0,v2);
+    assert_eq!(vec![vec![Some(false),None,Some(true)],vec![Some(true),None,None]],factory.cubes(f));
+    assert_eq!(vec![vec![None,None,None]],factory.cubes(NodeIndex::TRUE));
+}
+
+#[test]
+#[should_panic(expected = "too large")]
+fn too_many_variables() {
+    let factory = BDDFactory::<u32,NoMultiplicity>::new(40);
+    factory.truth_table(NodeIndex::TRUE);
+}
@@ -0,0 +1,46 @@
+//! Check unateness against flipping each variable in every assignment.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, Unateness, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+
+fn check_unate<F:DecisionDiagramFactory<u32,u32>>() {
+    let mut rng = StdRng::seed_from_u64(37);
+    let mut factory = F::new(N);
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut functions = vec![NodeIndex::FALSE,NodeIndex::TRUE,factory.not(NodeIndex::FALSE)];
+    for _ in 0..80 {
+        let mut f = NodeIndex::FALSE;
+        for _ in 0..rng.gen_range(1..3) {
+            let subset : Vec<VariableIndex> = variables.iter().cloned().filter(|_|rng.gen_bool(0.4)).collect();
+            // at least or at most some number of a subset is monotone, so there is plenty of unateness.
+            let term = if rng.gen_bool(0.5) { factory.at_least_n_of(&subset,rng.gen_range(0..3)) } else { factory.at_most_n_of(&subset,rng.gen_range(0..3)) };
+            let v = factory.single_variable(variables[rng.gen_range(0..N as usize)]);
+            let term = match rng.gen_range(0..3) { 0 => factory.and(term,v), 1 => factory.xor(term,v), _ => term };
+            let term = factory.scale(term,rng.gen_range(1..3));
+            f = factory.or(f,term);
+        }
+        functions.push(f);
+    }
+    let is_solution = |factory:&F,f:NodeIndex<u32,u32>,x:u32| factory.evaluate(f,&(0..N).map(|v|(x>>v)&1==1).collect::<Vec<_>>()).is_some();
+    for f in functions {
+        let unateness = factory.unateness(f);
+        for v in 0..N {
+            let bit = 1u32<<v;
+            let positive = (0..1u32<<N).filter(|x|x&bit==0).all(|x|!is_solution(&factory,f,x) || is_solution(&factory,f,x|bit));
+            let negative = (0..1u32<<N).filter(|x|x&bit==0).all(|x|!is_solution(&factory,f,x|bit) || is_solution(&factory,f,x));
+            assert_eq!(positive,factory.is_positive_unate(f,VariableIndex(v)));
+            assert_eq!(negative,factory.is_negative_unate(f,VariableIndex(v)));
+            let expected = match (positive,negative) { (true,true) => Unateness::Independent, (true,false) => Unateness::Positive, (false,true) => Unateness::Negative, (false,false) => Unateness::Binate };
+            assert_eq!(expected,unateness[v as usize]);
+        }
+    }
+}
+
+#[test]
+fn unate_bdd() { check_unate::<BDDFactory<u32,u32>>(); }
+
+#[test]
+fn unate_zdd() { check_unate::<ZDDFactory<u32,u32>>(); }
@@ -0,0 +1,64 @@
+//! Check that validate accepts diagrams made by the factories, and reports each kind of broken hand built diagram.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, Node, NodeIndex, VariableIndex, ZDDFactory};
+use xdd::xdd_with_multiplicity::{DiagramProblem, NodeList, XDDBase};
+
+fn check_factory<F:DecisionDiagramFactory<u32,u32>>() {
+    let variables : Vec<VariableIndex> = (0..8).map(VariableIndex).collect();
+    let mut factory = F::new(8);
+    let a = factory.exactly_n_of(&variables,3);
+    let b = factory.at_most_n_of(&variables[2..],2);
+    let c = factory.or(a,b);
+    let d = factory.scale(c,6);
+    let e = factory.not(d);
+    for f in [NodeIndex::FALSE,NodeIndex::TRUE,a,b,c,d,e] { assert_eq!(Ok(()),factory.validate(f)); }
+}
+
+#[test]
+fn factory_diagrams_are_valid() {
+    check_factory::<BDDFactory<u32,u32>>();
+    check_factory::<ZDDFactory<u32,u32>>();
+}
+
+fn index(address:u32,multiplicity:u32) -> NodeIndex<u32,u32> { NodeIndex::new(address,multiplicity) }
+
+/// The problems found in a diagram made of the given nodes, with the root being the last.
+fn problems<const BDD:bool>(nodes:&[Node<u32,u32>],root_multiplicity:u32) -> Vec<DiagramProblem<u32>> {
+    let mut list = NodeList::<u32,u32>::default();
+    for &node in nodes { list.add_node(node); }
+    let root = index(nodes.len() as u32+1,root_multiplicity);
+    list.validate::<BDD>(root,4).err().map(|e|e.problems).unwrap_or_default()
+}
+
+fn node(variable:u16,lo:NodeIndex<u32,u32>,hi:NodeIndex<u32,u32>) -> Node<u32,u32> { Node{variable:VariableIndex(variable),lo,hi} }
+
+#[test]
+fn each_problem() {
+    let t = NodeIndex::TRUE;
+    let f = NodeIndex::FALSE;
+    assert!(problems::<true>(&[node(3,f,t),node(1,index(2,1),t)],5).is_empty());
+    assert_eq!(vec![DiagramProblem::DanglingAddress{parent:Some(2),child:7}],problems::<true>(&[node(3,index(7,1),t)],1));
+    assert_eq!(vec![DiagramProblem::VariableOutOfRange{address:2,variable:VariableIndex(4)}],problems::<true>(&[node(4,f,t)],1));
+    assert_eq!(vec![DiagramProblem::OutOfOrder{parent:3,child:2}],problems::<true>(&[node(1,f,t),node(1,index(2,1),t)],1));
+    assert_eq!(vec![DiagramProblem::Redundant{address:2}],problems::<true>(&[node(1,t,t)],1));
+    assert!(problems::<false>(&[node(1,t,t)],1).is_empty()); // fine for a ZDD,
+    assert_eq!(vec![DiagramProblem::Redundant{address:2}],problems::<false>(&[node(1,t,f)],1)); // unlike this.
+    assert_eq!(vec![DiagramProblem::Duplicate{address:3,duplicate_of:2}],problems::<true>(&[node(2,f,t),node(2,f,t),node(1,index(2,1),index(3,1))],1));
+    assert_eq!(vec![DiagramProblem::UncanonicalMultiplicity{parent:Some(2)}],problems::<true>(&[node(1,index(1,2),index(1,4))],1));
+    assert_eq!(vec![DiagramProblem::UncanonicalMultiplicity{parent:Some(2)}],problems::<true>(&[node(1,index(0,3),t)],1));
+    assert_eq!(vec![DiagramProblem::UncanonicalMultiplicity{parent:Some(2)}],problems::<true>(&[node(1,f,index(1,3))],1));
+    let mut list = NodeList::<u32,u32>::default();
+    let err = list.validate::<true>(index(0,2),4).unwrap_err();
+    assert_eq!(vec![DiagramProblem::UncanonicalMultiplicity{parent:None}],err.problems);
+    assert_eq!("Invalid diagram: the root has multiplicities that are not canonical",err.to_string());
+    let problems = list.validate::<true>(index(3,1),4).unwrap_err().problems;
+    assert_eq!(vec![DiagramProblem::DanglingAddress{parent:None,child:3}],problems);
+    // several problems at once are all reported.
+    let v = list.add_node(node(2,t,t));
+    let root = index(list.add_node(node(2,f,index(v,1))),1);
+    let err = list.validate::<true>(root,2).unwrap_err();
+    assert_eq!(4,err.problems.len());
+    assert_eq!("Invalid diagram: node 3 has variable 2 which is out of range; node 3 does not have a variable before its child 2; node 2 has variable 2 which is out of range; node 2 is redundant",err.to_string());
+    let reduced = list.reduce_bdd(root);
+    assert_eq!(vec![DiagramProblem::VariableOutOfRange{address:4,variable:VariableIndex(2)}],list.validate::<true>(reduced,2).unwrap_err().problems);
+}
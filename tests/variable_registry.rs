@@ -0,0 +1,57 @@
+//! Check naming variables through the factory's registry, and its use for output.
+
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, VariableIndex, ZDDFactory};
+use xdd::variable_registry::VariableRegistry;
+
+#[test]
+fn registry() {
+    let mut registry = VariableRegistry::default();
+    assert!(registry.is_empty());
+    registry.set_names(&["a","b"]);
+    registry.set_name(VariableIndex(5),"f");
+    assert_eq!(3,registry.len());
+    assert_eq!(Some(VariableIndex(1)),registry.variable("b"));
+    assert_eq!(Some("f"),registry.get_name(VariableIndex(5)));
+    assert_eq!(None,registry.get_name(VariableIndex(3)));
+    assert_eq!("3",registry.name(VariableIndex(3)));
+    assert_eq!("300",registry.name(VariableIndex(300)));
+    // renaming forgets the old name.
+    registry.set_name(VariableIndex(1),"bee");
+    assert_eq!(None,registry.variable("b"));
+    assert_eq!("bee",registry.name(VariableIndex(1)));
+    registry.set_name(VariableIndex(1),"bee");
+    assert_eq!(3,registry.len());
+}
+
+#[test]
+#[should_panic(expected="already used")]
+fn duplicate_name() {
+    let mut registry = VariableRegistry::default();
+    registry.set_names(&["a","a"]);
+}
+
+fn check_output<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let mut factory = F::new(3);
+    factory.variable_registry_mut().set_name(VariableIndex(0),"x");
+    factory.variable_registry_mut().set_name(VariableIndex(2),"z");
+    let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(2)]);
+    let mut solutions : Vec<String> = factory.solutions(f).map(|s|factory.format_solution(&s)).collect();
+    solutions.sort();
+    assert_eq!(vec!["{1, z}","{x, 1}","{x}","{z}"],solutions);
+    assert_eq!("{}",factory.format_solution(&[]));
+    let mut named = vec![];
+    factory.make_dot_file_default_names(&mut named,"f",&[(f,None)]).unwrap();
+    let mut by_hand = vec![];
+    factory.make_dot_file(&mut by_hand,"f",&[(f,None)],|v|["x","1","z"][v.0 as usize].to_string()).unwrap();
+    assert_eq!(by_hand,named);
+    let mut named = vec![];
+    factory.make_json_file_default_names(&mut named,&[(f,None)]).unwrap();
+    let mut by_hand = vec![];
+    factory.make_json_file(&mut by_hand,&[(f,None)],|v|["x","1","z"][v.0 as usize].to_string()).unwrap();
+    assert_eq!(by_hand,named);
+}
+
+#[test]
+fn output_bdd() { check_output::<BDDFactory<u32,NoMultiplicity>>() }
+#[test]
+fn output_zdd() { check_output::<ZDDFactory<u32,NoMultiplicity>>() }
@@ -0,0 +1,39 @@
+//! Check weighted counts against summing the weights of each assignment directly.
+
+mod common;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use xdd::{BDDFactory, DecisionDiagramFactory, NodeIndex, ZDDFactory};
+use common::random_linear_combination;
+
+const N : u16 = 6;
+
+fn check_random<F:DecisionDiagramFactory<u32,i64>>() {
+    let mut rng = StdRng::seed_from_u64(4604);
+    for _ in 0..20 {
+        let mut factory = F::new(N);
+        let f = random_linear_combination(&mut factory,&mut rng,N);
+        let weights : Vec<i64> = (0..N).map(|_|rng.gen_range(-4..=4)).collect();
+        let expected : i64 = (0..1u32<<N).map(|x|{
+            let assignment : Vec<bool> = (0..N).map(|i|(x>>i)&1==1).collect();
+            let multiplicity = factory.evaluate(f,&assignment).unwrap_or(0);
+            multiplicity*(0..N as usize).filter(|&i|assignment[i]).map(|i|weights[i]).product::<i64>()
+        }).sum();
+        assert_eq!(expected,factory.weighted_count::<i64>(f,&weights));
+        assert_eq!(factory.number_solutions::<i64>(f),factory.weighted_count::<i64>(f,&[1;N as usize]));
+    }
+}
+
+#[test]
+fn random_bdd() { check_random::<BDDFactory<u32,i64>>(); }
+
+#[test]
+fn random_zdd() { check_random::<ZDDFactory<u32,i64>>(); }
+
+#[test]
+#[should_panic(expected="one weight per variable")]
+fn wrong_number_of_weights() {
+    let factory = BDDFactory::<u32,i64>::new(3);
+    factory.weighted_count::<i64>(NodeIndex::TRUE,&[1,2]);
+}
@@ -0,0 +1,76 @@
+//! Check that solutions written as CSV or JSON lines read back as the solutions of the diagram.
+
+use std::io::{ErrorKind, Write};
+use xdd::{BDDFactory, DecisionDiagramFactory, NoMultiplicity, SolutionTextFormat, VariableIndex, ZDDFactory};
+
+const N : u16 = 6;
+
+fn check_write<F:DecisionDiagramFactory<u32,NoMultiplicity>>() {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut factory = F::new(N);
+    let a = factory.exactly_n_of(&variables,2);
+    let b = factory.at_most_n_of(&variables[3..],1);
+    let f = factory.and(a,b);
+    let expected : Vec<Vec<VariableIndex>> = factory.solutions(f).collect();
+    let mut csv = vec![];
+    factory.write_all_solutions(f,&mut csv,SolutionTextFormat::Csv,|v|format!("v{}",v)).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(Some("v0,v1,v2,v3,v4,v5"),lines.next());
+    let read : Vec<Vec<VariableIndex>> = lines.map(|line|line.split(',').enumerate().filter(|(_,x)|*x=="1").map(|(v,_)|VariableIndex(v as u16)).collect()).collect();
+    assert_eq!(expected,read);
+    let mut json = vec![];
+    factory.write_all_solutions(f,&mut json,SolutionTextFormat::JsonLines,|v|format!("v{}",v)).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    let read : Vec<Vec<VariableIndex>> = json.lines().map(|line|{
+        let inside = line.strip_prefix('[').unwrap().strip_suffix(']').unwrap();
+        inside.split(',').filter(|x|!x.is_empty()).map(|x|VariableIndex(x.trim_matches('"')[1..].parse().unwrap())).collect()
+    }).collect();
+    assert_eq!(expected,read);
+    // no solutions gives just the header.
+    let mut csv = vec![];
+    factory.write_all_solutions(xdd::NodeIndex::FALSE,&mut csv,SolutionTextFormat::Csv,|v|format!("v{}",v)).unwrap();
+    assert_eq!(1,String::from_utf8(csv).unwrap().lines().count());
+}
+
+#[test]
+fn write_bdd() { check_write::<BDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn write_zdd() { check_write::<ZDDFactory<u32,NoMultiplicity>>(); }
+
+#[test]
+fn awkward_names() {
+    let mut factory = ZDDFactory::<u32,NoMultiplicity>::new(2);
+    let names = ["a,b","say \"hi\""];
+    let f = factory.exactly_one_of(&[VariableIndex(0),VariableIndex(1)]);
+    let x1 = factory.single_variable(VariableIndex(1));
+    let f = factory.and(f,x1);
+    let mut csv = vec![];
+    factory.write_all_solutions(f,&mut csv,SolutionTextFormat::Csv,|v|names[v.0 as usize].to_string()).unwrap();
+    assert_eq!("\"a,b\",\"say \"\"hi\"\"\"\n0,1\n",String::from_utf8(csv).unwrap());
+    let mut json = vec![];
+    factory.write_all_solutions(f,&mut json,SolutionTextFormat::JsonLines,|v|names[v.0 as usize].to_string()).unwrap();
+    assert_eq!("[\"say \\\"hi\\\"\"]\n",String::from_utf8(json).unwrap());
+}
+
+/// A writer that fails once it has been given more than some number of bytes.
+struct Full(usize);
+
+impl Write for Full {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len()>self.0 { return Err(std::io::Error::new(ErrorKind::WriteZero,"full")); }
+        self.0-=buf.len();
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn write_error_is_returned() {
+    let variables : Vec<VariableIndex> = (0..N).map(VariableIndex).collect();
+    let mut factory = BDDFactory::<u32,NoMultiplicity>::new(N);
+    let f = factory.at_least_n_of(&variables,1);
+    let err = factory.write_all_solutions(f,&mut Full(100),SolutionTextFormat::Csv,|v|v.to_string()).unwrap_err();
+    assert_eq!(ErrorKind::WriteZero,err.kind());
+}